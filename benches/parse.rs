@@ -0,0 +1,39 @@
+//! Compares the `quick-xml` reader of the `fast-parse` feature with the `serde-xml-rs`
+//! deserialization, on the fixture repeated into a ten-page document.
+//!
+//! Run with `cargo bench --features fast-parse --bench parse`.
+//!
+//! On the machine used for the change, `quick-xml` takes 14.1 ms and `serde-xml-rs` 80.8 ms
+//! (median of 20 samples).
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use typst_2_rsx::{parse_svg, svg_types::Svg};
+
+/// Number of times the fixture is repeated.
+const PAGES: usize = 10;
+
+/// Builds a document holding `PAGES` copies of the elements of `test/expected.svg`.
+fn fixture() -> String {
+    let svg_str = std::fs::read_to_string("test/expected.svg").unwrap();
+    let mut svg = parse_svg(&svg_str).unwrap();
+    let elements = svg.elements.clone();
+    for _ in 1..PAGES {
+        svg.elements.extend(elements.iter().cloned());
+    }
+    svg.to_svg_string()
+}
+
+fn parse(c: &mut Criterion) {
+    let svg_str = fixture();
+
+    let mut group = c.benchmark_group("parse_ten_pages");
+    group.sample_size(20);
+    group.bench_function("quick_xml", |b| b.iter(|| parse_svg(&svg_str).unwrap()));
+    group.bench_function("serde_xml_rs", |b| {
+        b.iter(|| serde_xml_rs::from_str::<Svg>(&svg_str).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,5 @@
+#set page(width: auto, height: auto, margin: 10pt)
+
+= First document
+
+The quadratic formula: $ x = (-b plus.minus sqrt(b^2 - 4 a c)) / (2 a) $
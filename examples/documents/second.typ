@@ -0,0 +1,5 @@
+#set page(width: auto, height: auto, margin: 10pt)
+
+= Second document
+
+Euler's identity: $ e^(i pi) + 1 = 0 $
@@ -0,0 +1,30 @@
+//! Shows a live preview of a Typst document with the `use_typst_live` hook.
+//!
+//! Edit `examples/documents/first.typ` while the window is open: the preview is updated every time
+//! the file is saved, and a compilation error is shown in its place until the next save fixes it.
+//!
+//! Run with the desktop renderer, e.g.
+//! `dx serve --example live_preview --features components --platform desktop`.
+
+use dioxus::prelude::*;
+use typst_2_rsx::components::{use_typst_live, TypstState};
+
+const DOCUMENT: &str = "examples/documents/first.typ";
+
+fn main() {
+    dioxus::launch(App);
+}
+
+#[component]
+fn App() -> Element {
+    let doc = use_typst_live(DOCUMENT);
+
+    rsx! {
+        p { "Watching {DOCUMENT}" }
+        match doc() {
+            TypstState::Loading => rsx! { p { "Compiling..." } },
+            TypstState::Ready(element) => element,
+            TypstState::Failed(e) => rsx! { pre { style: "color: #b00020", "{e}" } },
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Switches between two Typst documents with the `use_typst` hook.
+//!
+//! Each document is compiled in the background the first time it is shown; switching back to it
+//! afterwards is served from the content cache.
+//!
+//! Run with a Dioxus renderer enabled, e.g. `dx serve --example toggle_documents --features components`.
+
+use dioxus::prelude::*;
+use typst_2_rsx::components::{use_typst, TypstState};
+
+const DOCUMENTS: [&str; 2] = [
+    "examples/documents/first.typ",
+    "examples/documents/second.typ",
+];
+
+fn main() {
+    dioxus::launch(App);
+}
+
+#[component]
+fn App() -> Element {
+    let mut current = use_signal(|| 0);
+    let doc = use_typst(DOCUMENTS[current()]);
+
+    rsx! {
+        button { onclick: move |_| current.set(1 - current()), "Show the other document" }
+        match doc.state() {
+            TypstState::Loading => rsx! { p { "Compiling {DOCUMENTS[current()]}..." } },
+            TypstState::Ready(element) => element,
+            TypstState::Failed(e) => rsx! { pre { "{e}" } },
+        }
+    }
+}
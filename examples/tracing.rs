@@ -0,0 +1,32 @@
+//! Prints the spans of a conversion with `tracing_subscriber`.
+//!
+//! Every span is printed when it closes, with its fields and the time spent in it, so the output
+//! shows how long the compilation, the parsing and the RSX construction took:
+//!
+//! ```text
+//! DEBUG parse_svg_to_rsx:parse_svg: typst_2_rsx: close time.busy=9.1ms input_size=48211 elements=823 ...
+//! DEBUG parse_svg_to_rsx:svg_to_rsx: typst_2_rsx::convert: close time.busy=2.4ms elements=823 ...
+//!  INFO parse_svg_to_rsx: typst_2_rsx: close time.busy=11.7ms input_size=48211
+//! ```
+//!
+//! Run with `cargo run --example tracing --features tracing`. The second conversion needs the
+//! Typst CLI; without it, the `typst_to_rsx` span closes on the error.
+
+use tracing_subscriber::fmt::format::FmtSpan;
+use typst_2_rsx::{parse_svg_to_rsx, typst_to_rsx};
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+
+    let svg_str = std::fs::read_to_string("test/expected.svg").unwrap();
+    if let Err(e) = parse_svg_to_rsx(&svg_str) {
+        eprintln!("{}", e);
+    }
+
+    if let Err(e) = typst_to_rsx("examples/documents/first.typ") {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,30 @@
+//! Converts arbitrary input, which must return `Err` rather than panic, then runs the passes
+//! doing geometry on the attributes of whatever parsed.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use typst_2_rsx::{
+    options::RsxOptions, parse_svg, parse_svg_to_rsx, parse_svg_to_rsx_with_report,
+    parse_untrusted_svg_to_rsx, passes,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(svg_str) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = parse_svg_to_rsx(svg_str);
+    let _ = parse_svg_to_rsx_with_report(svg_str, &RsxOptions::default());
+    let _ = parse_untrusted_svg_to_rsx(svg_str, &RsxOptions::default());
+
+    let Ok(mut svg) = parse_svg(svg_str) else {
+        return;
+    };
+    let _ = svg.content_bbox();
+    let _ = svg.to_svg_string();
+    passes::flatten_transforms(&mut svg);
+    passes::minify_paths(&mut svg, 2);
+    passes::crop_to_content(&mut svg, 1.0);
+    passes::simplify(&mut svg);
+    let _ = passes::expand_uses(&mut svg);
+});
@@ -0,0 +1,16 @@
+//! Parses arbitrary path data, and checks that whatever parses is written back to data parsing
+//! to the same commands.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use typst_2_rsx::svg_types::path_data::PathData;
+
+fuzz_target!(|data: &str| {
+    if let Ok(path) = PathData::parse(data) {
+        let written = path.to_string();
+        let reparsed = PathData::parse(&written).expect("written path data parses");
+        // NaN never compares equal, but the data only holds finite numbers or infinities
+        assert_eq!(path, reparsed, "{:?} was written as {:?}", data, written);
+    }
+});
@@ -0,0 +1,20 @@
+//! Parses arbitrary transform lists, and checks that whatever parses is written back to a list
+//! parsing to the same operations.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use typst_2_rsx::svg_types::transform::Transform;
+
+fuzz_target!(|data: &str| {
+    if let Ok(transform) = Transform::parse(data) {
+        let _ = transform.to_matrix();
+        let written = transform.to_string();
+        let reparsed = Transform::parse(&written).expect("written transform parses");
+        assert_eq!(
+            transform, reparsed,
+            "{:?} was written as {:?}",
+            data, written
+        );
+    }
+});
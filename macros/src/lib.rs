@@ -0,0 +1,187 @@
+//! Compile-time macros that inline Typst documents as RSX.
+//!
+//! The Typst CLI runs while the crate is being compiled, and the resulting `rsx!` code is pasted at the
+//! call site, so neither Typst nor any SVG parsing is needed at runtime.
+//!
+//! **These macros require the Typst CLI to be installed and accessible from the system's PATH at build time.**
+//!
+//! ```rust,ignore
+//! use dioxus::prelude::*;
+//! use typst_2_rsx_macros::{typst, typst_str};
+//!
+//! fn Report() -> Element {
+//!     typst!("assets/report.typ")
+//! }
+//!
+//! fn Formula() -> Element {
+//!     typst_str!("#set page(width: auto, height: auto, margin: 0pt)\n$ e^(i pi) + 1 = 0 $")
+//! }
+//! ```
+//!
+//! The expansion calls `::dioxus::prelude::rsx!`. A crate depending on Dioxus under another name
+//! gives its path after the document:
+//!
+//! ```rust,ignore
+//! fn Report() -> Element {
+//!     typst!("assets/report.typ", dioxus = dioxus05)
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote, LitStr, Token,
+};
+
+/// The arguments of the macros: the document, then optionally `dioxus = <path>`.
+struct Input {
+    literal: LitStr,
+    dioxus: syn::Path,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let literal = input.parse()?;
+        let mut dioxus = parse_quote!(::dioxus);
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let name: syn::Ident = input.parse()?;
+            if name != "dioxus" {
+                return Err(syn::Error::new(name.span(), "expected `dioxus = <path>`"));
+            }
+            input.parse::<Token![=]>()?;
+            dioxus = input.parse()?;
+            input.parse::<Option<Token![,]>>()?;
+        }
+        Ok(Input { literal, dioxus })
+    }
+}
+
+/// Compiles a `.typ` file at build time and expands to the `rsx!` code rendering it.
+///
+/// The path is relative to the calling crate's `Cargo.toml`. The file is registered as a dependency,
+/// so editing it rebuilds the crate. Compilation failures become a compile error carrying the Typst
+/// diagnostics. `dioxus = <path>` after the path names the Dioxus crate, `::dioxus` by default.
+#[proc_macro]
+pub fn typst(input: TokenStream) -> TokenStream {
+    let Input { literal, dioxus } = parse_macro_input!(input as Input);
+    let path = manifest_dir().join(literal.value());
+    let path_str = path.to_string_lossy().into_owned();
+
+    match typst_2_rsx::typst_to_rsx_source(&path_str) {
+        Ok(source) => {
+            let rsx = match rsx_tokens(&source, &dioxus, literal.span()) {
+                Ok(rsx) => rsx,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            quote! {
+                {
+                    // Makes the document a dependency of the calling crate
+                    const _: &[u8] = include_bytes!(#path_str);
+                    #rsx
+                }
+            }
+            .into()
+        }
+        Err(e) => compile_error(literal.span(), &path_str, e),
+    }
+}
+
+/// Compiles inline Typst source at build time and expands to the `rsx!` code rendering it.
+///
+/// The source is compiled from a temporary file, so relative imports resolve against the system
+/// temporary directory; use [`typst!`] for documents that import other files. Takes
+/// `dioxus = <path>` like [`typst!`].
+#[proc_macro]
+pub fn typst_str(input: TokenStream) -> TokenStream {
+    let Input { literal, dioxus } = parse_macro_input!(input as Input);
+    let source = literal.value();
+
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    let path = env::temp_dir().join(format!("typst-2-rsx-macro-{:x}.typ", hasher.finish()));
+    let path_str = path.to_string_lossy().into_owned();
+    if let Err(e) = fs::write(&path, &source) {
+        let error = typst_2_rsx::error::Error::Io {
+            path: Some(path),
+            source: e,
+        };
+        return compile_error(literal.span(), &path_str, error);
+    }
+
+    let result = typst_2_rsx::typst_to_rsx_source(&path_str);
+    let _ = fs::remove_file(&path);
+    match result {
+        Ok(source) => match rsx_tokens(&source, &dioxus, literal.span()) {
+            Ok(rsx) => rsx.into(),
+            Err(e) => e.to_compile_error().into(),
+        },
+        Err(e) => compile_error(literal.span(), "inline Typst source", e),
+    }
+}
+
+/// Directory of the crate invoking the macro.
+fn manifest_dir() -> PathBuf {
+    env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+/// Parses the generated `rsx! { ... }` source, pointing it at the `rsx!` of `dioxus` so callers need
+/// no import.
+fn rsx_tokens(
+    source: &str,
+    dioxus: &syn::Path,
+    span: Span,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let source = source.strip_prefix("rsx!").unwrap_or(source);
+    let body: proc_macro2::TokenStream = source
+        .parse()
+        .map_err(|e| syn::Error::new(span, format!("invalid generated RSX: {}", e)))?;
+    Ok(quote! { #dioxus::prelude::rsx! #body })
+}
+
+fn compile_error(span: Span, what: &str, error: typst_2_rsx::error::Error) -> TokenStream {
+    syn::Error::new(span, format!("failed to convert {}: {}", what, error))
+        .to_compile_error()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rsx_tokens_test() {
+        let source = "rsx! {\n    svg {\n        width: \"10pt\",\n    }\n}\n";
+        let tokens = rsx_tokens(source, &parse_quote!(::dioxus), Span::call_site())
+            .unwrap()
+            .to_string();
+        assert!(tokens.starts_with(":: dioxus :: prelude :: rsx !"));
+        assert!(tokens.contains("\"10pt\""));
+        let tokens = rsx_tokens(source, &parse_quote!(dioxus05), Span::call_site())
+            .unwrap()
+            .to_string();
+        assert!(tokens.starts_with("dioxus05 :: prelude :: rsx !"));
+    }
+
+    #[test]
+    fn input_test() {
+        let input: Input = syn::parse_str("\"report.typ\"").unwrap();
+        assert_eq!(input.literal.value(), "report.typ");
+        let dioxus = input.dioxus;
+        assert_eq!(quote!(#dioxus).to_string(), ":: dioxus");
+        let input: Input = syn::parse_str("\"report.typ\", dioxus = dioxus05,").unwrap();
+        let dioxus = input.dioxus;
+        assert_eq!(quote!(#dioxus).to_string(), "dioxus05");
+        assert!(syn::parse_str::<Input>("\"report.typ\", path = dioxus05").is_err());
+        assert!(syn::parse_str::<Input>("\"report.typ\", dioxus").is_err());
+    }
+}
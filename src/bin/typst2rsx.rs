@@ -0,0 +1,217 @@
+//! `typst2rsx`: converts a Typst document or an SVG compiled by Typst from the command line.
+//!
+//! ```text
+//! typst2rsx figure.typ --out figure.rs --name Figure
+//! typst2rsx figure.svg --emit json
+//! ```
+//!
+//! A `.typ` input is compiled with the Typst CLI first; any other input is read as an SVG. The
+//! result is written to `--out`, or to the standard output. On failure, the error and the Typst
+//! diagnostics are printed on the standard error and the exit code is 1.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
+
+use clap::{Parser, ValueEnum};
+use typst_2_rsx::{
+    error::Error, options::CompileOptions, parse_svg, svg_to_json, svg_to_rsx_source, typst_to_svg,
+};
+
+/// Converts a Typst document, or the SVG compiled from one, to RSX source, JSON or SVG.
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// The `.typ` document to compile, or the SVG to convert.
+    input: PathBuf,
+
+    /// The file to write, instead of the standard output.
+    #[arg(short, long)]
+    out: Option<PathBuf>,
+
+    /// What to write.
+    #[arg(long, value_enum, default_value_t = Emit::Rsx)]
+    emit: Emit,
+
+    /// Wraps the RSX in a `#[component] pub fn <NAME>() -> Element`, ready to be used as a module.
+    #[arg(long)]
+    name: Option<String>,
+
+    /// The project root, which limits the files the document may import or read.
+    #[arg(long)]
+    root: Option<PathBuf>,
+
+    /// An extra font directory; may be repeated.
+    #[arg(long = "font-path", value_name = "DIR")]
+    font_paths: Vec<PathBuf>,
+
+    /// A `sys.inputs` entry; may be repeated.
+    #[arg(long = "input", value_name = "KEY=VALUE", value_parser = parse_input)]
+    inputs: Vec<(String, String)>,
+
+    /// How many seconds the Typst CLI may run before it is stopped.
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<f64>,
+
+    /// How many more times the Typst CLI is started when starting it fails for a transient reason.
+    #[arg(long, default_value_t = 0)]
+    retries: u8,
+}
+
+/// The output formats.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Emit {
+    /// The Rust source of an `rsx!` invocation, see `svg_to_rsx_source`.
+    Rsx,
+    /// The parsed tree as JSON, see `svg_to_json`.
+    Json,
+    /// The parsed tree written back as SVG.
+    Svg,
+}
+
+fn parse_input(input: &str) -> Result<(String, String), String> {
+    input
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{}`", input))
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if args.name.is_some() && args.emit != Emit::Rsx {
+        eprintln!("error: --name only applies to --emit rsx");
+        return ExitCode::from(2);
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), Error> {
+    let svg_str = if args
+        .input
+        .extension()
+        .is_some_and(|extension| extension == "typ")
+    {
+        let options = CompileOptions {
+            root: args.root.clone(),
+            font_paths: args.font_paths.clone(),
+            inputs: args.inputs.clone(),
+            timeout: args.timeout.map(Duration::from_secs_f64),
+            retries: args.retries,
+            ..Default::default()
+        };
+        typst_to_svg(&args.input, &options)?
+    } else {
+        fs::read_to_string(&args.input).map_err(|e| Error::Io {
+            path: Some(args.input.clone()),
+            source: e,
+        })?
+    };
+
+    let output = emit(&svg_str, args.emit, args.name.as_deref())?;
+    match &args.out {
+        Some(out) => fs::write(out, output).map_err(|e| Error::Io {
+            path: Some(out.clone()),
+            source: e,
+        }),
+        None => io::stdout()
+            .write_all(output.as_bytes())
+            .map_err(|e| Error::Io {
+                path: None,
+                source: e,
+            }),
+    }
+}
+
+/// Converts `svg_str` to the `emit` format, as a component called `name` if one is given.
+fn emit(svg_str: &str, emit: Emit, name: Option<&str>) -> Result<String, Error> {
+    match emit {
+        Emit::Rsx => {
+            let source = svg_to_rsx_source(svg_str)?;
+            Ok(match name {
+                Some(name) => component(name, &source),
+                None => source,
+            })
+        }
+        Emit::Json => Ok(svg_to_json(&parse_svg(svg_str)?) + "\n"),
+        Emit::Svg => Ok(parse_svg(svg_str)?.to_svg_string() + "\n"),
+    }
+}
+
+/// Wraps the `rsx!` source in a component function called `name`.
+fn component(name: &str, source: &str) -> String {
+    let mut code = format!(
+        "use dioxus::prelude::*;\n\n#[component]\npub fn {}() -> Element {{\n",
+        name
+    );
+    for line in source.lines() {
+        code.push_str("    ");
+        code.push_str(line);
+        code.push('\n');
+    }
+    code.push_str("}\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SVG: &str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+                       <path d='M 0 0 L 10 10'/></svg>";
+
+    #[test]
+    fn emit_test() {
+        let source = emit(SVG, Emit::Rsx, Some("Figure")).unwrap();
+        assert!(source.starts_with(
+            "use dioxus::prelude::*;\n\n#[component]\npub fn Figure() -> Element {\n    rsx! {\n"
+        ));
+        assert!(source.ends_with("    }\n}\n"));
+        assert_eq!(
+            emit(SVG, Emit::Rsx, None).unwrap(),
+            svg_to_rsx_source(SVG).unwrap()
+        );
+
+        let json = emit(SVG, Emit::Json, None).unwrap();
+        assert_eq!(
+            typst_2_rsx::svg_from_json(&json).unwrap(),
+            parse_svg(SVG).unwrap()
+        );
+        let svg = emit(SVG, Emit::Svg, None).unwrap();
+        assert_eq!(parse_svg(&svg).unwrap(), parse_svg(SVG).unwrap());
+
+        assert!(emit("<svg", Emit::Json, None).is_err());
+    }
+
+    #[test]
+    fn args_test() {
+        let args = Args::try_parse_from([
+            "typst2rsx",
+            "figure.typ",
+            "--input",
+            "theme=dark",
+            "--font-path",
+            "fonts",
+            "--timeout",
+            "1.5",
+            "--retries",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(args.inputs, [("theme".to_string(), "dark".to_string())]);
+        assert_eq!(args.font_paths, [PathBuf::from("fonts")]);
+        assert_eq!(args.emit, Emit::Rsx);
+        assert_eq!(args.retries, 2);
+        assert!(Args::try_parse_from(["typst2rsx", "a.typ", "--input", "theme"]).is_err());
+        assert!(Args::try_parse_from(["typst2rsx", "a.svg", "--emit", "html"]).is_err());
+    }
+}
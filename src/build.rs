@@ -0,0 +1,428 @@
+//! Helpers for precompiling Typst documents from a consumer's `build.rs`.
+//!
+//! ```rust,no_run
+//! // build.rs, in `fn main`
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! if let Err(e) = typst_2_rsx::build::compile_dir("typst", &out_dir) {
+//!     panic!("{}", e);
+//! }
+//! ```
+//!
+//! ```rust,ignore
+//! // src/main.rs
+//! use dioxus::prelude::*;
+//!
+//! include!(concat!(env!("OUT_DIR"), "/typst_assets.rs"));
+//!
+//! fn App() -> Element {
+//!     typst_assets::figure_1()
+//! }
+//! ```
+//!
+//! The generated module imports the Dioxus prelude from `::dioxus`; an application depending on
+//! Dioxus under another name sets [`CompileOptions::dioxus_path`].
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    compile_to_string,
+    error::Error,
+    invoker::{TypstCli, TypstInvoker},
+    manifest::{input_hash, Manifest, ManifestEntry, OutputRole},
+    options::CompileOptions,
+    parse_svg_with_limits, split_pages, svg_to_rsx_source,
+};
+
+/// Compiles every `.typ` file under `src_dir` into RSX-producing Rust code in `out_dir`.
+///
+/// Each input becomes `out_dir/<name>.rs` holding `pub fn <name>() -> Element`, where `<name>` is the
+/// file's path relative to `src_dir` turned into a snake_case identifier (`figures/plot-1.typ` becomes
+/// `figures_plot_1`). They are gathered in `out_dir/typst_assets.rs`, a `pub mod typst_assets` meant to
+/// be `include!`d by the application. The `cargo:rerun-if-changed` lines for `src_dir` and every input are
+/// printed, so the build script re-runs when a document changes.
+///
+/// The generated files are listed in a [`Manifest`] written to `out_dir/typst_manifest.json`. Every
+/// document is compiled again; see [`compile_dir_incremental`] for skipping the unchanged ones.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Return value
+///
+/// - `Ok(PathBuf)` : path of the generated `typst_assets.rs`.
+/// - `Err(Error)` : [`Error::BuildError`] naming the file that failed, wrapping the Typst diagnostics,
+///   the conversion error, or an [`Error::DuplicateName`] when two files get the same name, such as
+///   `figure-1.typ` and `figure_1.typ`. Nothing is compiled in that case.
+pub fn compile_dir(src_dir: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    compile_dir_with(src_dir, out_dir, &CompileOptions::default())
+}
+
+/// Like [`compile_dir`], compiling every document with the given [`CompileOptions`].
+pub fn compile_dir_with(
+    src_dir: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<PathBuf, Error> {
+    compile_inputs(
+        &TypstCli,
+        src_dir.as_ref(),
+        out_dir.as_ref(),
+        options,
+        Manifest::default(),
+    )
+}
+
+/// Like [`compile_dir_with`], only compiling the documents that changed since the last build.
+///
+/// The [`Manifest`] written by the last build is read from `out_dir/typst_manifest.json`. A
+/// document is compiled again when its content or `options` changed, or when its generated file
+/// is missing, according to [`Manifest::is_up_to_date`]. The files a document imports are not
+/// tracked, so a document is not compiled again when only they changed; delete the manifest to
+/// compile everything.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust,no_run
+/// // build.rs, in `fn main`
+/// use typst_2_rsx::{build::compile_dir_incremental, options::CompileOptions};
+///
+/// let out_dir = std::env::var("OUT_DIR").unwrap();
+/// compile_dir_incremental("typst", &out_dir, &CompileOptions::default()).unwrap();
+/// ```
+pub fn compile_dir_incremental(
+    src_dir: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<PathBuf, Error> {
+    compile_changed(&TypstCli, src_dir.as_ref(), out_dir.as_ref(), options)
+}
+
+/// [`compile_dir_incremental`] with the Typst CLI run by `invoker`.
+fn compile_changed(
+    invoker: &dyn TypstInvoker,
+    src_dir: &Path,
+    out_dir: &Path,
+    options: &CompileOptions,
+) -> Result<PathBuf, Error> {
+    let previous = Manifest::load(out_dir.join(Manifest::FILE_NAME))?;
+    compile_inputs(invoker, src_dir, out_dir, options, previous)
+}
+
+/// Compiles with `invoker` the documents under `src_dir` that are not up to date in the
+/// `previous` manifest, and writes the module and the new manifest.
+fn compile_inputs(
+    invoker: &dyn TypstInvoker,
+    src_dir: &Path,
+    out_dir: &Path,
+    options: &CompileOptions,
+    previous: Manifest,
+) -> Result<PathBuf, Error> {
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+    fs::create_dir_all(out_dir).map_err(|e| Error::io(out_dir, e))?;
+
+    let mut inputs = Vec::new();
+    find_typ_files(src_dir, &mut inputs)?;
+    inputs.sort();
+
+    // Checked before compiling anything, since the second file would overwrite the first
+    let mut names: HashMap<String, &PathBuf> = HashMap::new();
+    for input in &inputs {
+        let name = function_name(input.strip_prefix(src_dir).unwrap_or(input));
+        if let Some(first) = names.insert(name.clone(), input) {
+            return Err(Error::BuildError {
+                file: input.clone(),
+                source: Box::new(Error::DuplicateName {
+                    name,
+                    first: first.clone(),
+                }),
+            });
+        }
+    }
+
+    // Built from scratch, so that the documents deleted since the last build are left out
+    let mut manifest = Manifest::default();
+    let dioxus = options.dioxus_path.as_deref().unwrap_or("::dioxus");
+    let mut module = format!(
+        "pub mod typst_assets {{\n    use {}::prelude::*;\n\n",
+        dioxus
+    );
+    for input in &inputs {
+        println!("cargo:rerun-if-changed={}", input.display());
+        let name = function_name(input.strip_prefix(src_dir).unwrap_or(input));
+        let file_name = format!("{}.rs", name.trim_start_matches("r#"));
+        let path = out_dir.join(&file_name);
+        module.push_str(&format!("    include!({:?});\n", file_name));
+
+        let build_error = |e| Error::BuildError {
+            file: input.clone(),
+            source: Box::new(e),
+        };
+        let hash = input_hash(input, options).map_err(build_error)?;
+        if previous.is_up_to_date(input, &hash) {
+            manifest.insert(input, previous.entries[input].clone());
+            continue;
+        }
+        let source = compile_file(invoker, input, options).map_err(build_error)?;
+
+        let mut code = format!("pub fn {}() -> Element {{\n", name);
+        for line in source.lines() {
+            code.push_str("    ");
+            code.push_str(line);
+            code.push('\n');
+        }
+        code.push_str("}\n");
+        fs::write(&path, code).map_err(|e| Error::io(&path, e))?;
+        manifest.insert(
+            input,
+            ManifestEntry::new(hash).with_output(path, OutputRole::Module),
+        );
+    }
+    module.push_str("}\n");
+
+    let module_path = out_dir.join("typst_assets.rs");
+    fs::write(&module_path, module).map_err(|e| Error::io(&module_path, e))?;
+    manifest.save(out_dir.join(Manifest::FILE_NAME))?;
+    Ok(module_path)
+}
+
+/// Compiles a document and writes each of its pages to `out_dir` as a standalone SVG, named
+/// after the document and the page number from 1 (`slides-1.svg`, `slides-2.svg`), for serving
+/// the pages as static files. See [`split_pages`] for how the pages are split.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Return value
+///
+/// - `Ok(ManifestEntry)` : the entry listing the pages written, to [`insert`](Manifest::insert)
+///   in a manifest under `input`.
+/// - `Err(Error)` : the compilation failed, or a page could not be written.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{build::write_pages, manifest::Manifest, options::CompileOptions};
+///
+/// let mut manifest = Manifest::load("public/manifest.json").unwrap();
+/// let entry = write_pages("slides.typ", "public/slides", &CompileOptions::default()).unwrap();
+/// manifest.insert("slides.typ", entry);
+/// manifest.save("public/manifest.json").unwrap();
+/// ```
+pub fn write_pages(
+    input: impl AsRef<Path>,
+    out_dir: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<ManifestEntry, Error> {
+    compile_pages(&TypstCli, input.as_ref(), out_dir.as_ref(), options)
+}
+
+/// [`write_pages`] with the Typst CLI run by `invoker`.
+fn compile_pages(
+    invoker: &dyn TypstInvoker,
+    input: &Path,
+    out_dir: &Path,
+    options: &CompileOptions,
+) -> Result<ManifestEntry, Error> {
+    let mut entry = ManifestEntry::new(input_hash(input, options)?);
+    let (content, _) = compile_to_string(invoker, input, options)?;
+    let svg = parse_svg_with_limits(&content, &options.limits)?;
+    fs::create_dir_all(out_dir).map_err(|e| Error::io(out_dir, e))?;
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    for (i, page) in split_pages(&svg).iter().enumerate() {
+        let path = out_dir.join(format!("{}-{}.svg", stem, i + 1));
+        fs::write(&path, page.to_svg_string()).map_err(|e| Error::io(&path, e))?;
+        entry = entry.with_output(path, OutputRole::Page);
+    }
+    Ok(entry)
+}
+
+/// Compiles one document with `invoker` and generates its `rsx!` source.
+fn compile_file(
+    invoker: &dyn TypstInvoker,
+    input: &Path,
+    options: &CompileOptions,
+) -> Result<String, Error> {
+    let (content, _) = compile_to_string(invoker, input, options)?;
+    svg_to_rsx_source(&content)
+}
+
+/// Recursively collects the `.typ` files under `dir`.
+fn find_typ_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir).map_err(|e| Error::io(dir, e))? {
+        let path = entry.map_err(|e| Error::io(dir, e))?.path();
+        if path.is_dir() {
+            find_typ_files(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "typ") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Turns a relative `.typ` path into a snake_case Rust identifier.
+fn function_name(relative: &Path) -> String {
+    let stem = relative.with_extension("");
+    let mut name = String::new();
+    for c in stem.to_string_lossy().chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name = name.trim_matches('_').to_string();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else if matches!(name.as_str(), "self" | "super" | "crate") {
+        // These cannot be raw identifiers
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// Rust keywords that need the raw identifier prefix.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "yield",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{read_file, temp_file_path};
+
+    #[test]
+    fn function_name_test() {
+        assert_eq!(function_name(Path::new("figure-1.typ")), "figure_1");
+        assert_eq!(
+            function_name(Path::new("chapters/Intro Text.typ")),
+            "chapters_intro_text"
+        );
+        assert_eq!(function_name(Path::new("1st.typ")), "_1st");
+        assert_eq!(function_name(Path::new("fn.typ")), "r#fn");
+        assert_eq!(function_name(Path::new("self.typ")), "self_");
+        assert_eq!(
+            function_name(Path::new("Plot1.typ")),
+            function_name(Path::new("plot1.typ"))
+        );
+    }
+
+    #[test]
+    fn duplicate_name_test() {
+        let src_dir = temp_file_path("src");
+        let out_dir = temp_file_path("out");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(src_dir.join("figure-1.typ"), "= One").unwrap();
+        fs::write(src_dir.join("figure_1.typ"), "= Two").unwrap();
+        let error = compile_dir(&src_dir, &out_dir).unwrap_err();
+        assert_eq!(error.path(), Some(src_dir.join("figure_1.typ").as_path()));
+        let Error::BuildError { source, .. } = &error else {
+            panic!("expected a build error, got {:?}", error);
+        };
+        assert!(matches!(
+            source.as_ref(),
+            Error::DuplicateName { name, first }
+                if name == "figure_1" && *first == src_dir.join("figure-1.typ")
+        ));
+        assert!(!out_dir.join("figure_1.rs").exists());
+        let _ = fs::remove_dir_all(&src_dir);
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+
+    #[test]
+    fn empty_dir_test() {
+        let src_dir = temp_file_path("src");
+        let out_dir = temp_file_path("out");
+        fs::create_dir_all(&src_dir).unwrap();
+        let module = compile_dir(&src_dir, &out_dir).unwrap();
+        let content = fs::read_to_string(&module).unwrap();
+        assert!(content.contains("pub mod typst_assets {"));
+        assert!(content.contains("use ::dioxus::prelude::*;"));
+
+        let options = CompileOptions {
+            dioxus_path: Some("dioxus05".to_string()),
+            ..Default::default()
+        };
+        let module = compile_dir_with(&src_dir, &out_dir, &options).unwrap();
+        let content = fs::read_to_string(&module).unwrap();
+        assert!(content.contains("use dioxus05::prelude::*;"));
+        let _ = fs::remove_dir_all(&src_dir);
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+
+    /// Compiles a directory twice, and a third time after one document changed, counting the
+    /// compilations in the log of the fake `typst`.
+    #[cfg(unix)]
+    #[test]
+    fn incremental_test() {
+        let typst = &crate::tests::FakeTypst;
+        let src_dir = temp_file_path("src");
+        let out_dir = temp_file_path("out");
+        fs::create_dir_all(src_dir.join("chapters")).unwrap();
+        let svg_str = read_file("./test/formula.svg").unwrap();
+        fs::write(src_dir.join("figure.typ"), &svg_str).unwrap();
+        fs::write(src_dir.join("chapters/intro.typ"), &svg_str).unwrap();
+        let compiled = || {
+            let mut log = fs::read_to_string(src_dir.join("typst.log")).unwrap_or_default();
+            log += &fs::read_to_string(src_dir.join("chapters/typst.log")).unwrap_or_default();
+            log.lines().count()
+        };
+        let options = CompileOptions::default();
+
+        compile_changed(typst, &src_dir, &out_dir, &options).unwrap();
+        assert_eq!(compiled(), 2);
+        let manifest_path = out_dir.join(Manifest::FILE_NAME);
+        let first = Manifest::load(&manifest_path).unwrap();
+        assert_eq!(first.entries.len(), 2);
+        let outputs = &first.entries[&src_dir.join("figure.typ")].outputs;
+        assert_eq!(outputs[0].path, out_dir.join("figure.rs"));
+        assert_eq!(outputs[0].role, OutputRole::Module);
+
+        // Nothing changed: nothing is compiled, and the manifest is the same
+        let module = compile_changed(typst, &src_dir, &out_dir, &options).unwrap();
+        assert_eq!(compiled(), 2);
+        assert_eq!(Manifest::load(&manifest_path).unwrap(), first);
+        let content = fs::read_to_string(module).unwrap();
+        assert!(content.contains("include!(\"chapters_intro.rs\")"));
+
+        // Only the changed document is compiled again
+        fs::write(src_dir.join("figure.typ"), svg_str.replace("40pt", "41pt")).unwrap();
+        compile_changed(typst, &src_dir, &out_dir, &options).unwrap();
+        assert_eq!(compiled(), 3);
+        let third = Manifest::load(&manifest_path).unwrap();
+        let intro = src_dir.join("chapters/intro.typ");
+        assert_eq!(third.entries[&intro], first.entries[&intro]);
+
+        // Without the previous manifest, like `compile_dir`, everything is compiled
+        compile_inputs(typst, &src_dir, &out_dir, &options, Manifest::default()).unwrap();
+        assert_eq!(compiled(), 5);
+
+        let entry = compile_pages(typst, &src_dir.join("figure.typ"), &out_dir, &options).unwrap();
+        assert_eq!(entry.outputs.len(), 1);
+        assert_eq!(entry.outputs[0].path, out_dir.join("figure-1.svg"));
+        assert!(read_file(&entry.outputs[0].path).unwrap().contains("41pt"));
+        let _ = fs::remove_dir_all(&src_dir);
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+
+    #[test]
+    fn error_names_file_test() {
+        let src_dir = temp_file_path("src");
+        let out_dir = temp_file_path("out");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(src_dir.join("broken.typ"), "#let x = (").unwrap();
+        let error = compile_dir(&src_dir, &out_dir).unwrap_err();
+        assert!(error.to_string().contains("broken.typ"));
+        let _ = fs::remove_dir_all(&src_dir);
+        let _ = fs::remove_dir_all(&out_dir);
+    }
+}
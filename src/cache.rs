@@ -0,0 +1,154 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::Path,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use crate::{compile_to_string, error::Error, invoker::TypstCli, options::CompileOptions};
+
+/// How many compiled documents the cache keeps; the least recently used is evicted past it.
+const CAPACITY: usize = 32;
+
+/// Compiled SVG documents, keyed by [`cache_key`].
+static CACHE: OnceLock<Mutex<Lru>> = OnceLock::new();
+
+/// The documents of the cache, each with the tick of its last use.
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<u64, (Arc<str>, u64)>,
+    tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, key: u64) -> Option<Arc<str>> {
+        self.tick += 1;
+        let (svg, used) = self.entries.get_mut(&key)?;
+        *used = self.tick;
+        Some(svg.clone())
+    }
+
+    /// Inserts `svg`, evicting the least recently used documents past [`CAPACITY`].
+    fn insert(&mut self, key: u64, svg: Arc<str>) {
+        self.tick += 1;
+        self.entries.insert(key, (svg, self.tick));
+        while self.entries.len() > CAPACITY {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// Compiles `src` to an SVG string, reusing a previous compilation of the same document.
+///
+/// The cache key is the content of the `.typ` file with its canonical path, the project root and
+/// `options`, so editing the file recompiles while switching back and forth between documents
+/// does not, and two files with the same content in different directories, which may import
+/// different files, are compiled each. Files imported by the document are not part of the key.
+/// The cache keeps the last [`CAPACITY`] documents used.
+///
+/// Every compilation writes to its own temporary file, so this is safe to call from several threads.
+pub(crate) fn compile_cached(src: &str, options: &CompileOptions) -> Result<Arc<str>, Error> {
+    let key = cache_key(Path::new(src), options)?;
+
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(svg) = cache.lock().unwrap().get(key) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(src, key, "cache hit");
+        return Ok(svg);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(src, key, "cache miss");
+
+    let (content, _) = compile_to_string(&TypstCli, Path::new(src), options)?;
+    let svg: Arc<str> = content.into();
+    cache.lock().unwrap().insert(key, svg.clone());
+    Ok(svg)
+}
+
+/// The hash of the content of `src`, its canonical path, the canonical project root (the
+/// [`CompileOptions::root`], or the directory of `src` like for Typst) and `options`.
+fn cache_key(src: &Path, options: &CompileOptions) -> Result<u64, Error> {
+    let canonical = |path: &Path| fs::canonicalize(path).map_err(|e| Error::io(path, e));
+    let mut hasher = DefaultHasher::new();
+    fs::read(src)
+        .map_err(|e| Error::io(src, e))?
+        .hash(&mut hasher);
+    let path = canonical(src)?;
+    let root = match &options.root {
+        Some(root) => canonical(root)?,
+        None => path.parent().unwrap_or(&path).to_path_buf(),
+    };
+    path.hash(&mut hasher);
+    root.hash(&mut hasher);
+    options.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Removes every compiled document from the cache shared by the [`Typst`](crate::components::Typst)
+/// component and the [`use_typst`](crate::components::use_typst) hook.
+pub fn clear_cache() {
+    if let Some(cache) = CACHE.get() {
+        cache.lock().unwrap().entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_file_path;
+
+    #[test]
+    fn cache_key_test() {
+        let dir = temp_file_path("cache");
+        fs::create_dir_all(dir.join("other")).unwrap();
+        let (first, second) = (dir.join("doc.typ"), dir.join("other/doc.typ"));
+        fs::write(&first, "= Title").unwrap();
+        fs::write(&second, "= Title").unwrap();
+        let options = CompileOptions::default();
+
+        let key = cache_key(&first, &options).unwrap();
+        assert_eq!(
+            key,
+            cache_key(&dir.join("other/../doc.typ"), &options).unwrap()
+        );
+        // Same content, elsewhere
+        assert_ne!(key, cache_key(&second, &options).unwrap());
+        // Same file, another root
+        let rooted = CompileOptions {
+            root: Some(dir.join("..")),
+            ..Default::default()
+        };
+        assert_ne!(key, cache_key(&first, &rooted).unwrap());
+        fs::write(&first, "= Other title").unwrap();
+        assert_ne!(key, cache_key(&first, &options).unwrap());
+        assert!(matches!(
+            cache_key(&dir.join("missing.typ"), &options),
+            Err(Error::Io { .. })
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn eviction_test() {
+        let mut cache = Lru::default();
+        for key in 0..CAPACITY as u64 {
+            cache.insert(key, key.to_string().into());
+        }
+        // The first one is used again, the second one is the least recently used
+        assert_eq!(cache.get(0).as_deref(), Some("0"));
+        cache.insert(CAPACITY as u64, "new".into());
+        assert_eq!(cache.entries.len(), CAPACITY);
+        assert!(cache.get(1).is_none());
+        assert!(cache.get(0).is_some());
+        assert!(cache.get(CAPACITY as u64).is_some());
+    }
+}
@@ -0,0 +1,766 @@
+use crate::{convert::attribute_rank, error::Error, parse_svg, svg_types::*};
+
+/// Generates the Rust source of an `rsx!` invocation that builds the given SVG.
+///
+/// This is the build-time counterpart of [`parse_svg_to_rsx`](crate::parse_svg_to_rsx): instead of building
+/// an `Element` at runtime, it emits formatted Rust code that can be written to a `.rs` file and compiled
+/// into the application, removing the runtime dependency on Typst. Attribute values are emitted as escaped
+/// string literals, and absent optional attributes are left out. The attributes of every element
+/// follow the same order as in the converted `Element`: `id` and `class`, then the geometry, the
+/// paint, the stroke, the font, the rendering hints, and last the style and interactivity.
+///
+/// # Return value
+///
+/// - `Ok(String)` : the `rsx! { svg { ... } }` source, ending with a newline.
+/// - `Err(Error)` : the SVG string could not be parsed.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_to_rsx_source;
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 100 100' width='100' height='100'>\
+///                <path d='M 0 0 L 100 100'/></svg>";
+/// let source = svg_to_rsx_source(svg_str).unwrap();
+/// assert!(source.starts_with("rsx! {"));
+/// ```
+pub fn svg_to_rsx_source(svg_str: &str) -> Result<String, Error> {
+    let parsed = parse_svg(svg_str)?;
+
+    let mut writer = RsxWriter::default();
+    writer.line("rsx! {");
+    writer.open("svg");
+    writer.attr("view_box", Some(&parsed.view_box));
+    writer.attr("width", Some(&parsed.width));
+    writer.attr("height", Some(&parsed.height));
+    for element in &parsed.elements {
+        match element {
+            SvgElement::Path(path) => write_path(&mut writer, path),
+            SvgElement::G(g) => write_g(&mut writer, g),
+            SvgElement::Defs(defs) => {
+                writer.open("defs");
+                writer.attr("id", Some(&defs.id));
+                for symbol in &defs.elements {
+                    write_symbol(&mut writer, symbol);
+                }
+                for definition in &defs.definitions {
+                    write_definition(&mut writer, definition);
+                }
+                writer.close();
+            }
+            SvgElement::Rect(rect) => {
+                writer.open("rect");
+                writer.attr("x", Some(&rect.x));
+                writer.attr("y", Some(&rect.y));
+                writer.attr("width", Some(&rect.width));
+                writer.attr("height", Some(&rect.height));
+                writer.attr("fill", rect.fill.as_ref());
+                writer.close();
+            }
+        }
+    }
+    writer.close();
+    writer.close();
+    Ok(writer.out)
+}
+
+fn write_path(writer: &mut RsxWriter, path: &Path) {
+    writer.open("path");
+    writer.attr("id", path.id.as_ref());
+    writer.attr("class", path.class.as_ref());
+    writer.attr("d", Some(&path.d));
+    writer.attr("fill", path.fill.as_ref());
+    writer.attr("fill_rule", path.fill_rule.as_ref());
+    writer.attr("opacity", path.opacity.as_ref());
+    writer.attr("stroke", path.stroke.as_ref());
+    writer.attr("stroke_width", path.stroke_width.as_ref());
+    writer.attr("stroke_linecap", path.stroke_linecap.as_ref());
+    writer.attr("stroke_linejoin", path.stroke_linejoin.as_ref());
+    writer.attr("stroke_miterlimit", path.stroke_miterlimit.as_ref());
+    writer.attr("shape_rendering", path.shape_rendering.as_ref());
+    writer.attr("tabindex", path.tabindex.as_ref());
+    writer.attr("pointer_events", path.pointer_events.as_ref());
+    writer.close();
+}
+
+fn write_g(writer: &mut RsxWriter, g: &G) {
+    writer.open("g");
+    writer.attr("id", g.id.as_ref());
+    writer.attr("class", g.class.as_ref());
+    writer.attr("transform", g.transform.as_ref());
+    writer.attr("clip_path", g.clip_path.as_ref());
+    writer.attr("mask", g.mask.as_ref());
+    writer.attr("filter", g.filter.as_ref());
+    writer.attr("shape_rendering", g.shape_rendering.as_ref());
+    writer.attr("text_rendering", g.text_rendering.as_ref());
+    writer.attr("tabindex", g.tabindex.as_ref());
+    writer.attr("pointer_events", g.pointer_events.as_ref());
+    for element in g.elements.iter().flatten() {
+        write_g_ele(writer, element);
+    }
+    writer.close();
+}
+
+fn write_g_ele(writer: &mut RsxWriter, element: &GEle) {
+    match element {
+        GEle::G(g) => write_g(writer, g),
+        GEle::Use(uuse) => {
+            writer.open("r#use");
+            writer.attr("x", Some(&uuse.x));
+            writer.attr("width", uuse.width.as_ref());
+            writer.attr("height", uuse.height.as_ref());
+            writer.attr("href", Some(&uuse.href));
+            writer.attr("transform", uuse.transform.as_ref());
+            writer.attr("fill", uuse.fill.as_ref());
+            writer.attr("fill_rule", uuse.fill_rule.as_ref());
+            writer.close();
+        }
+        GEle::Path(path) => write_path(writer, path),
+        GEle::Image(image) => write_image(writer, image),
+        GEle::Text(text) => write_text(writer, text),
+        GEle::Switch(switch) => {
+            // Like the conversion with the default options, the first branch in a group
+            writer.open("g");
+            writer.attr("id", switch.id.as_ref());
+            writer.attr("transform", switch.transform.as_ref());
+            if let Some(SwitchBranch::Element(element)) = switch.branches.first() {
+                write_g_ele(writer, element);
+            }
+            writer.close();
+        }
+    }
+}
+
+fn write_text(writer: &mut RsxWriter, text: &Text) {
+    writer.open("text");
+    writer.attr("id", text.id.as_ref());
+    writer.attr("class", text.class.as_ref());
+    writer.attr("x", text.x.as_ref());
+    writer.attr("y", text.y.as_ref());
+    writer.attr("dx", text.dx.as_ref());
+    writer.attr("dy", text.dy.as_ref());
+    writer.attr("transform", text.transform.as_ref());
+    writer.attr("fill", text.fill.as_ref());
+    writer.attr("font_family", text.font_family.as_ref());
+    writer.attr("font_size", text.font_size.as_ref());
+    writer.attr("font_weight", text.font_weight.as_ref());
+    writer.attr("font_style", text.font_style.as_ref());
+    writer.attr("letter_spacing", text.letter_spacing.as_ref());
+    writer.attr("word_spacing", text.word_spacing.as_ref());
+    writer.attr("dominant_baseline", text.dominant_baseline.as_ref());
+    writer.attr("text_anchor", text.text_anchor.as_ref());
+    writer.attr("text_rendering", text.text_rendering.as_ref());
+    for content in &text.content {
+        match content {
+            TextContent::Characters(characters) => writer.line(&string_literal(characters)),
+            TextContent::Tspan(tspan) => {
+                writer.open("tspan");
+                writer.attr("x", tspan.x.as_ref());
+                writer.attr("y", tspan.y.as_ref());
+                writer.attr("dx", tspan.dx.as_ref());
+                writer.attr("dy", tspan.dy.as_ref());
+                writer.attr("fill", tspan.fill.as_ref());
+                writer.attr("font_family", tspan.font_family.as_ref());
+                writer.attr("font_size", tspan.font_size.as_ref());
+                writer.attr("font_weight", tspan.font_weight.as_ref());
+                writer.attr("font_style", tspan.font_style.as_ref());
+                writer.attr("letter_spacing", tspan.letter_spacing.as_ref());
+                writer.attr("word_spacing", tspan.word_spacing.as_ref());
+                writer.attr("dominant_baseline", tspan.dominant_baseline.as_ref());
+                writer.line(&string_literal(&tspan.characters));
+                writer.close();
+            }
+        }
+    }
+    writer.close();
+}
+
+fn write_image(writer: &mut RsxWriter, image: &Image) {
+    writer.open("image");
+    writer.attr("width", Some(&image.width));
+    writer.attr("height", Some(&image.height));
+    writer.attr("preserve_aspect_ratio", Some(&image.preserve_aspect_ratio));
+    writer.attr("href", Some(&image.href));
+    writer.attr("transform", image.transform.as_ref());
+    writer.close();
+}
+
+fn write_symbol(writer: &mut RsxWriter, symbol: &Symbol) {
+    writer.open("symbol");
+    writer.attr("id", Some(&symbol.id));
+    writer.attr("overflow", Some(&symbol.overflow));
+    writer.attr("view_box", symbol.view_box.as_ref());
+    writer.attr(
+        "preserve_aspect_ratio",
+        symbol.preserve_aspect_ratio.as_ref(),
+    );
+    match &symbol.element {
+        SymbolEle::Path(path) => write_path(writer, path),
+        SymbolEle::Image(image) => write_image(writer, image),
+    }
+    writer.close();
+}
+
+fn write_definition(writer: &mut RsxWriter, definition: &Definition) {
+    let (gradient_units, gradient_transform, spread_method, href, stops) = match definition {
+        Definition::LinearGradient(gradient) => {
+            writer.open("linearGradient");
+            writer.attr("x1", gradient.x1.as_ref());
+            writer.attr("y1", gradient.y1.as_ref());
+            writer.attr("x2", gradient.x2.as_ref());
+            writer.attr("y2", gradient.y2.as_ref());
+            (
+                &gradient.gradient_units,
+                &gradient.gradient_transform,
+                &gradient.spread_method,
+                &gradient.href,
+                &gradient.stops,
+            )
+        }
+        Definition::RadialGradient(gradient) => {
+            writer.open("radialGradient");
+            writer.attr("cx", gradient.cx.as_ref());
+            writer.attr("cy", gradient.cy.as_ref());
+            writer.attr("r", gradient.r.as_ref());
+            writer.attr("fx", gradient.fx.as_ref());
+            writer.attr("fy", gradient.fy.as_ref());
+            (
+                &gradient.gradient_units,
+                &gradient.gradient_transform,
+                &gradient.spread_method,
+                &gradient.href,
+                &gradient.stops,
+            )
+        }
+        Definition::ClipPath(clip) => {
+            writer.open("clipPath");
+            writer.attr("id", Some(&clip.id));
+            writer.attr("transform", clip.transform.as_ref());
+            writer.attr("clip_path_units", clip.clip_path_units.as_ref());
+            for path in &clip.elements {
+                write_path(writer, path);
+            }
+            writer.close();
+            return;
+        }
+        Definition::Mask(mask) => {
+            writer.open("mask");
+            writer.attr("id", Some(&mask.id));
+            writer.attr("x", mask.x.as_ref());
+            writer.attr("y", mask.y.as_ref());
+            writer.attr("width", mask.width.as_ref());
+            writer.attr("height", mask.height.as_ref());
+            writer.attr("mask_units", mask.mask_units.as_ref());
+            writer.attr("mask_content_units", mask.mask_content_units.as_ref());
+            for path in &mask.elements {
+                write_path(writer, path);
+            }
+            writer.close();
+            return;
+        }
+        Definition::Filter(filter) => {
+            writer.open("filter");
+            writer.attr("id", Some(&filter.id));
+            writer.attr("x", filter.x.as_ref());
+            writer.attr("y", filter.y.as_ref());
+            writer.attr("width", filter.width.as_ref());
+            writer.attr("height", filter.height.as_ref());
+            writer.attr("filterUnits", filter.filter_units.as_ref());
+            for primitive in &filter.primitives {
+                write_primitive(writer, primitive);
+            }
+            writer.close();
+            return;
+        }
+    };
+    writer.attr("id", Some(&definition.id()));
+    writer.attr("href", href.as_ref());
+    writer.attr("gradient_units", gradient_units.as_ref());
+    writer.attr("gradient_transform", gradient_transform.as_ref());
+    writer.attr("spread_method", spread_method.as_ref());
+    for stop in stops {
+        // Written like `convert` renders it
+        let mut stop = stop.clone();
+        stop.normalize_style();
+        writer.open("stop");
+        writer.attr("offset", stop.offset.as_ref());
+        writer.attr("stop_color", stop.stop_color.as_ref());
+        writer.attr("stop_opacity", stop.stop_opacity.as_ref());
+        writer.attr("style", stop.style.as_ref());
+        writer.close();
+    }
+    writer.close();
+}
+
+fn write_primitive(writer: &mut RsxWriter, primitive: &FilterPrimitive) {
+    writer.open(primitive.tag());
+    match primitive {
+        FilterPrimitive::GaussianBlur(blur) => {
+            writer.attr("\"in\"", blur.input.as_ref());
+            writer.attr("std_deviation", blur.std_deviation.as_ref());
+            writer.attr("result", blur.result.as_ref());
+        }
+        FilterPrimitive::Offset(offset) => {
+            writer.attr("\"in\"", offset.input.as_ref());
+            writer.attr("dx", offset.dx.as_ref());
+            writer.attr("dy", offset.dy.as_ref());
+            writer.attr("result", offset.result.as_ref());
+        }
+        FilterPrimitive::ColorMatrix(matrix) => {
+            writer.attr("\"in\"", matrix.input.as_ref());
+            writer.attr("r#type", matrix.kind.as_ref());
+            writer.attr("values", matrix.values.as_ref());
+            writer.attr("result", matrix.result.as_ref());
+        }
+        FilterPrimitive::DropShadow(shadow) => {
+            writer.attr("\"in\"", shadow.input.as_ref());
+            writer.attr("dx", shadow.dx.as_ref());
+            writer.attr("dy", shadow.dy.as_ref());
+            writer.attr("std_deviation", shadow.std_deviation.as_ref());
+            writer.attr("flood_color", shadow.flood_color.as_ref());
+            writer.attr("flood_opacity", shadow.flood_opacity.as_ref());
+            writer.attr("result", shadow.result.as_ref());
+        }
+    }
+    writer.close();
+}
+
+/// Escapes `value` as a string literal usable inside `rsx!`.
+///
+/// Literals in `rsx!` are format strings, so braces are doubled before the usual Rust escaping.
+fn string_literal(value: &str) -> String {
+    format!("{:?}", value.replace('{', "{{").replace('}', "}}"))
+}
+
+/// Accumulates indented `rsx!` source, one element or attribute per line.
+///
+/// The attributes of an element are held until its first child or its end, and then written in
+/// the canonical [`ATTRIBUTE_ORDER`](crate::convert::ATTRIBUTE_ORDER) whatever the order of the
+/// calls.
+#[derive(Default)]
+struct RsxWriter {
+    out: String,
+    indent: usize,
+    attributes: Vec<(usize, String)>,
+}
+
+impl RsxWriter {
+    fn line(&mut self, line: &str) {
+        self.flush_attributes();
+        self.write(line);
+    }
+
+    fn write(&mut self, line: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+        if line.ends_with('{') {
+            self.indent += 1;
+        }
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.line(&format!("{} {{", tag));
+    }
+
+    /// Writes `name: "value",`, skipping absent and empty values.
+    fn attr(&mut self, name: &str, value: Option<&impl AsRef<str>>) {
+        if let Some(value) = value.map(AsRef::as_ref).filter(|value| !value.is_empty()) {
+            let line = format!("{}: {},", name, string_literal(value));
+            self.attributes.push((attribute_rank(name), line));
+        }
+    }
+
+    fn flush_attributes(&mut self) {
+        let mut attributes = std::mem::take(&mut self.attributes);
+        attributes.sort_by_key(|(rank, _)| *rank);
+        for (_, line) in attributes {
+            self.write(&line);
+        }
+    }
+
+    fn close(&mut self) {
+        self.flush_attributes();
+        self.indent -= 1;
+        self.write("}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus::prelude::*;
+
+    /// Every kind of element the source is generated for, so that `source_compiles_test` compiles
+    /// each of them.
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 20 10" width="20pt" height="10pt">
+        <path class="typst-shape" fill="#ffffff" fill-rule="nonzero" d="M 0 0 L 0 10 L 20 10 L 20 0 Z "/>
+        <g transform="translate(1 2)">
+            <use href="#glyph0" x="0" fill="#000000"/>
+        </g>
+        <g clip-path="url(#c0)" mask="url(#m0)" filter="url(#f0)">
+            <image width="2" height="2" preserveAspectRatio="none" href="data:image/png;base64,AAAA"/>
+            <text x="1 2" y="8" font-family="Libertinus Serif" font-size="11">Fish <tspan font-weight="bold" fill="url(#g0)">&amp; {chips}</tspan></text>
+            <switch transform="translate(3 4)">
+                <path d="M 0 0 L 1 1" stroke="url(#r0)" stroke-width="0.5"/>
+            </switch>
+        </g>
+        <defs id="glyph">
+            <symbol id="glyph0" overflow="visible">
+                <path d="M 1 1 L 2 2 Z"/>
+            </symbol>
+        </defs>
+        <defs id="paint">
+            <linearGradient id="g0" x1="0" y1="0" x2="1" y2="0" gradientTransform="rotate(45)">
+                <stop offset="0" stop-color="#ff0000"/>
+                <stop offset="1" style="stop-color: #0000ff; stop-opacity: 0.5"/>
+            </linearGradient>
+            <radialGradient id="r0" href="#g0" cx="0.5" cy="0.5" r="0.5" spreadMethod="reflect" gradientUnits="objectBoundingBox"/>
+            <clipPath id="c0" clipPathUnits="userSpaceOnUse">
+                <path d="M 0 0 L 20 0 L 20 10 Z"/>
+            </clipPath>
+            <mask id="m0" x="0" y="0" width="20" height="10" maskUnits="userSpaceOnUse" maskContentUnits="userSpaceOnUse">
+                <path d="M 0 0 L 20 0 L 20 10 Z" fill="#ffffff"/>
+            </mask>
+            <filter id="f0" x="-10" y="-10" width="40" height="30" filterUnits="userSpaceOnUse">
+                <feColorMatrix in="SourceAlpha" type="matrix" values="0 0 0 0 0  0 0 0 0 0  0 0 0 0 0  0 0 0 0.3 0" result="color"/>
+                <feGaussianBlur in="color" stdDeviation="2" result="blur"/>
+                <feOffset in="blur" dx="0" dy="1" result="offset"/>
+                <feDropShadow dx="0" dy="2" stdDeviation="3" flood-color="#1f2328" flood-opacity="0.25"/>
+            </filter>
+        </defs>
+    </svg>"##;
+
+    const EXPECTED: &str = r##"rsx! {
+    svg {
+        view_box: "0 0 20 10",
+        width: "20pt",
+        height: "10pt",
+        path {
+            class: "typst-shape",
+            d: "M 0 0 L 0 10 L 20 10 L 20 0 Z ",
+            fill: "#ffffff",
+            fill_rule: "nonzero",
+        }
+        g {
+            transform: "translate(1 2)",
+            r#use {
+                x: "0",
+                href: "#glyph0",
+                fill: "#000000",
+            }
+        }
+        g {
+            clip_path: "url(#c0)",
+            mask: "url(#m0)",
+            filter: "url(#f0)",
+            image {
+                width: "2",
+                height: "2",
+                preserve_aspect_ratio: "none",
+                href: "data:image/png;base64,AAAA",
+            }
+            text {
+                x: "1 2",
+                y: "8",
+                font_family: "Libertinus Serif",
+                font_size: "11",
+                "Fish "
+                tspan {
+                    fill: "url(#g0)",
+                    font_weight: "bold",
+                    "& {{chips}}"
+                }
+            }
+            g {
+                transform: "translate(3 4)",
+                path {
+                    d: "M 0 0 L 1 1",
+                    stroke: "url(#r0)",
+                    stroke_width: "0.5",
+                }
+            }
+        }
+        defs {
+            id: "glyph",
+            symbol {
+                id: "glyph0",
+                overflow: "visible",
+                path {
+                    d: "M 1 1 L 2 2 Z",
+                }
+            }
+        }
+        defs {
+            id: "paint",
+            linearGradient {
+                id: "g0",
+                x1: "0",
+                y1: "0",
+                x2: "1",
+                y2: "0",
+                gradient_transform: "rotate(45)",
+                stop {
+                    offset: "0",
+                    stop_color: "#ff0000",
+                }
+                stop {
+                    offset: "1",
+                    stop_color: "#0000ff",
+                    stop_opacity: "0.5",
+                }
+            }
+            radialGradient {
+                id: "r0",
+                cx: "0.5",
+                cy: "0.5",
+                r: "0.5",
+                href: "#g0",
+                gradient_units: "objectBoundingBox",
+                spread_method: "reflect",
+            }
+            clipPath {
+                id: "c0",
+                clip_path_units: "userSpaceOnUse",
+                path {
+                    d: "M 0 0 L 20 0 L 20 10 Z",
+                }
+            }
+            mask {
+                id: "m0",
+                x: "0",
+                y: "0",
+                width: "20",
+                height: "10",
+                mask_units: "userSpaceOnUse",
+                mask_content_units: "userSpaceOnUse",
+                path {
+                    d: "M 0 0 L 20 0 L 20 10 Z",
+                    fill: "#ffffff",
+                }
+            }
+            filter {
+                id: "f0",
+                x: "-10",
+                y: "-10",
+                width: "40",
+                height: "30",
+                filterUnits: "userSpaceOnUse",
+                feColorMatrix {
+                    "in": "SourceAlpha",
+                    r#type: "matrix",
+                    values: "0 0 0 0 0  0 0 0 0 0  0 0 0 0 0  0 0 0 0.3 0",
+                    result: "color",
+                }
+                feGaussianBlur {
+                    "in": "color",
+                    std_deviation: "2",
+                    result: "blur",
+                }
+                feOffset {
+                    "in": "blur",
+                    dx: "0",
+                    dy: "1",
+                    result: "offset",
+                }
+                feDropShadow {
+                    dx: "0",
+                    dy: "2",
+                    std_deviation: "3",
+                    flood_color: "#1f2328",
+                    flood_opacity: "0.25",
+                }
+            }
+        }
+    }
+}
+"##;
+
+    #[test]
+    fn source_test() {
+        assert_eq!(svg_to_rsx_source(SVG).unwrap(), EXPECTED);
+    }
+
+    /// `EXPECTED`, pasted verbatim: the generated source must compile. The copy is compared with
+    /// `EXPECTED` token by token, so that it is pasted again whenever the generated source changes.
+    #[test]
+    fn source_compiles_test() {
+        /// Expands the RSX and returns it as text.
+        macro_rules! compiled_rsx {
+            ($($body:tt)*) => {{
+                let _element: Element = rsx! { $($body)* };
+                stringify!(rsx! { $($body)* })
+            }};
+        }
+
+        let pasted = compiled_rsx! {
+            svg {
+                view_box: "0 0 20 10",
+                width: "20pt",
+                height: "10pt",
+                path {
+                    class: "typst-shape",
+                    d: "M 0 0 L 0 10 L 20 10 L 20 0 Z ",
+                    fill: "#ffffff",
+                    fill_rule: "nonzero",
+                }
+                g {
+                    transform: "translate(1 2)",
+                    r#use {
+                        x: "0",
+                        href: "#glyph0",
+                        fill: "#000000",
+                    }
+                }
+                g {
+                    clip_path: "url(#c0)",
+                    mask: "url(#m0)",
+                    filter: "url(#f0)",
+                    image {
+                        width: "2",
+                        height: "2",
+                        preserve_aspect_ratio: "none",
+                        href: "data:image/png;base64,AAAA",
+                    }
+                    text {
+                        x: "1 2",
+                        y: "8",
+                        font_family: "Libertinus Serif",
+                        font_size: "11",
+                        "Fish "
+                        tspan {
+                            fill: "url(#g0)",
+                            font_weight: "bold",
+                            "& {{chips}}"
+                        }
+                    }
+                    g {
+                        transform: "translate(3 4)",
+                        path {
+                            d: "M 0 0 L 1 1",
+                            stroke: "url(#r0)",
+                            stroke_width: "0.5",
+                        }
+                    }
+                }
+                defs {
+                    id: "glyph",
+                    symbol {
+                        id: "glyph0",
+                        overflow: "visible",
+                        path {
+                            d: "M 1 1 L 2 2 Z",
+                        }
+                    }
+                }
+                defs {
+                    id: "paint",
+                    linearGradient {
+                        id: "g0",
+                        x1: "0",
+                        y1: "0",
+                        x2: "1",
+                        y2: "0",
+                        gradient_transform: "rotate(45)",
+                        stop {
+                            offset: "0",
+                            stop_color: "#ff0000",
+                        }
+                        stop {
+                            offset: "1",
+                            stop_color: "#0000ff",
+                            stop_opacity: "0.5",
+                        }
+                    }
+                    radialGradient {
+                        id: "r0",
+                        cx: "0.5",
+                        cy: "0.5",
+                        r: "0.5",
+                        href: "#g0",
+                        gradient_units: "objectBoundingBox",
+                        spread_method: "reflect",
+                    }
+                    clipPath {
+                        id: "c0",
+                        clip_path_units: "userSpaceOnUse",
+                        path {
+                            d: "M 0 0 L 20 0 L 20 10 Z",
+                        }
+                    }
+                    mask {
+                        id: "m0",
+                        x: "0",
+                        y: "0",
+                        width: "20",
+                        height: "10",
+                        mask_units: "userSpaceOnUse",
+                        mask_content_units: "userSpaceOnUse",
+                        path {
+                            d: "M 0 0 L 20 0 L 20 10 Z",
+                            fill: "#ffffff",
+                        }
+                    }
+                    filter {
+                        id: "f0",
+                        x: "-10",
+                        y: "-10",
+                        width: "40",
+                        height: "30",
+                        filterUnits: "userSpaceOnUse",
+                        feColorMatrix {
+                            "in": "SourceAlpha",
+                            r#type: "matrix",
+                            values: "0 0 0 0 0  0 0 0 0 0  0 0 0 0 0  0 0 0 0.3 0",
+                            result: "color",
+                        }
+                        feGaussianBlur {
+                            "in": "color",
+                            std_deviation: "2",
+                            result: "blur",
+                        }
+                        feOffset {
+                            "in": "blur",
+                            dx: "0",
+                            dy: "1",
+                            result: "offset",
+                        }
+                        feDropShadow {
+                            dx: "0",
+                            dy: "2",
+                            std_deviation: "3",
+                            flood_color: "#1f2328",
+                            flood_opacity: "0.25",
+                        }
+                    }
+                }
+            }
+        };
+        let tokens = |source: &str| source.split_whitespace().collect::<String>();
+        assert_eq!(tokens(pasted), tokens(EXPECTED));
+    }
+
+    #[test]
+    fn escape_round_trip_test() {
+        let values = [
+            "plain",
+            "quote \" and backslash \\",
+            "{braces} {{doubled}}",
+            "line\nbreak\ttab",
+        ];
+        for value in values {
+            let literal = string_literal(value);
+            // Undo the Rust escaping, then the rsx format-string escaping
+            let unescaped = literal[1..literal.len() - 1]
+                .replace("\\n", "\n")
+                .replace("\\t", "\t")
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\")
+                .replace("{{", "{")
+                .replace("}}", "}");
+            assert_eq!(unescaped, value);
+        }
+    }
+}
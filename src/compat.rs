@@ -0,0 +1,18 @@
+//! The parts of the conversion that depend on the version of Dioxus, gathered in one place.
+//!
+//! Dioxus changes the representation of an `Element` between major versions (an `Option<VNode>`
+//! in 0.5, a `Result<VNode, RenderError>` in 0.6), how a keyed node is built, how an
+//! `EventHandler` becomes a listener, and how `dioxus-ssr` renders one. The rest of the crate goes
+//! through these functions rather than building or taking `Element`s apart itself, and each
+//! version has its implementation of them, selected by the `dioxus-0-5` and `dioxus-0-6`
+//! features. The `rsx!` syntax used by the conversion is the same in both versions.
+
+#[cfg(all(feature = "dioxus-0-5", not(feature = "dioxus-0-6")))]
+mod dioxus_0_5;
+#[cfg(feature = "dioxus-0-6")]
+mod dioxus_0_6;
+
+#[cfg(all(feature = "dioxus-0-5", not(feature = "dioxus-0-6")))]
+pub(crate) use dioxus_0_5::*;
+#[cfg(feature = "dioxus-0-6")]
+pub(crate) use dioxus_0_6::*;
@@ -0,0 +1,54 @@
+//! The implementation of [`compat`](super) for Dioxus 0.5, where an `Element` is an
+//! `Option<VNode>` and the dynamic nodes of a `VNode` cannot be cloned.
+
+use dioxus::{
+    dioxus_core::{DynamicNode, Template, TemplateNode},
+    prelude::*,
+};
+
+/// An element rendering nothing.
+pub(crate) fn empty() -> Element {
+    VNode::empty()
+}
+
+/// The node of `element`, or `None` if it failed to render.
+#[cfg(any(test, all(feature = "components", not(target_arch = "wasm32"))))]
+pub(crate) fn node(element: &Element) -> Option<&VNode> {
+    element.as_ref()
+}
+
+/// Gives `element` the `key` that `rsx!` only accepts as a format string.
+///
+/// The nodes of `element` cannot be copied into a keyed node, so the keyed node holds it as a
+/// fragment, its single dynamic root.
+pub(crate) fn with_key(element: Element, key: String) -> Element {
+    static KEYED: Template = Template {
+        name: "typst-2-rsx/src/compat/dioxus_0_5.rs:keyed",
+        roots: &[TemplateNode::Dynamic { id: 0 }],
+        node_paths: &[&[0]],
+        attr_paths: &[],
+    };
+    let node = element?;
+    Some(VNode::new(
+        Some(key),
+        KEYED,
+        Box::new([DynamicNode::Fragment(vec![node])]),
+        Box::new([]),
+    ))
+}
+
+/// The `onclick` listener calling `handler`.
+pub(crate) fn onclick(handler: EventHandler<MouseEvent>) -> Attribute {
+    dioxus::html::events::onclick(move |event| handler.call(event))
+}
+
+/// Renders `element` to HTML with `dioxus-ssr`.
+#[cfg(any(feature = "ssr", test, feature = "test-utils"))]
+pub(crate) fn render_html(element: Element) -> String {
+    #[cfg(not(test))]
+    use dioxus::ssr as dioxus_ssr;
+    #[cfg(test)]
+    use dioxus_ssr05 as dioxus_ssr;
+
+    dioxus_ssr::render_element(element)
+}
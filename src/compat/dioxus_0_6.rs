@@ -0,0 +1,40 @@
+//! The implementation of [`compat`](super) for Dioxus 0.6, where an `Element` is a
+//! `Result<VNode, RenderError>`.
+
+use dioxus::prelude::*;
+
+/// An element rendering nothing.
+pub(crate) fn empty() -> Element {
+    VNode::empty()
+}
+
+/// The node of `element`, or `None` if it failed to render.
+#[cfg(any(test, all(feature = "components", not(target_arch = "wasm32"))))]
+pub(crate) fn node(element: &Element) -> Option<&VNode> {
+    element.as_ref().ok()
+}
+
+/// Gives `element` the `key` that `rsx!` only accepts as a format string.
+pub(crate) fn with_key(element: Element, key: String) -> Element {
+    let node = element?;
+    Ok(VNode::new(
+        Some(key),
+        node.template,
+        node.dynamic_nodes.clone(),
+        node.dynamic_attrs.clone(),
+    ))
+}
+
+/// The `onclick` listener calling `handler`.
+pub(crate) fn onclick(handler: EventHandler<MouseEvent>) -> Attribute {
+    dioxus::html::events::onclick(handler)
+}
+
+/// Renders `element` to HTML with `dioxus-ssr`.
+#[cfg(any(feature = "ssr", test, feature = "test-utils"))]
+pub(crate) fn render_html(element: Element) -> String {
+    #[cfg(not(test))]
+    use dioxus::ssr as dioxus_ssr;
+
+    dioxus_ssr::render_element(element)
+}
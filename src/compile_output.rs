@@ -0,0 +1,51 @@
+use std::{path::PathBuf, process::ExitStatus, time::Duration};
+
+/// What a run of the Typst CLI gave, for logging and monitoring the compilations.
+///
+/// Returned by [`typst_compile_with`](crate::typst_compile_with), whatever the exit status, and
+/// by [`typst_to_rsx_with_options`](crate::typst_to_rsx_with_options) along with the element.
+///
+/// # Field
+///
+/// - `status` : the exit status of `typst compile`.
+/// - `duration` : how long the process ran, from its start to its exit. The preparation of the
+///   output directory and the reading of the SVG are not counted.
+/// - `output_path` : the SVG file written, with the separators of the platform. The conversions
+///   compile to a temporary file, removed once parsed.
+/// - `output_size_bytes` : the size of the SVG file, `None` when the compilation failed or left
+///   no file.
+/// - `stderr` : what the CLI printed on its standard error, without trailing whitespace. Typst
+///   prints its warnings there even when the compilation succeeds.
+/// - `attempts` : how many times the CLI was started, more than one when starting it failed for a
+///   transient reason and was retried as [`CompileOptions::retries`](crate::options::CompileOptions)
+///   allows.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{options::CompileOptions, typst_compile_with};
+///
+/// let output = typst_compile_with("report.typ", "report.svg", &CompileOptions::default()).unwrap();
+/// println!(
+///     "compiled in {:?}, {} bytes",
+///     output.duration,
+///     output.output_size_bytes.unwrap_or_default()
+/// );
+/// if !output.stderr.is_empty() {
+///     eprintln!("{}", output.stderr);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileOutput {
+    pub status: ExitStatus,
+
+    pub duration: Duration,
+
+    pub output_path: PathBuf,
+
+    pub output_size_bytes: Option<u64>,
+
+    pub stderr: String,
+
+    pub attempts: u32,
+}
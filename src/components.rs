@@ -0,0 +1,669 @@
+use dioxus::{dioxus_core::DynamicNode, prelude::*};
+use futures_channel::{mpsc, oneshot};
+use futures_util::StreamExt;
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::{BTreeSet, HashMap},
+    fs, io,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+pub use crate::cache::clear_cache;
+use crate::{
+    cache::compile_cached,
+    compat, compile_to_string,
+    error::Error,
+    invoker::{TypstCli, TypstInvoker},
+    math_to_rsx,
+    options::{CompileOptions, RsxOptions},
+    parse_svg, parse_svg_to_rsx, split_pages, svg_to_rsx,
+    svg_types::Svg,
+    PreparedSvg,
+};
+
+/// Compiles a Typst file and renders it as an inline SVG.
+///
+/// The compilation result is memoized on `src` + `options`, so re-rendering the parent does not
+/// invoke the Typst CLI again; changing either prop recompiles. Compiled documents are shared with
+/// [`use_typst`] through a cache keyed on the file content and path, which keeps the last 32
+/// documents used.
+///
+/// **This component requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Props
+///
+/// - `src` : path to the `.typ` file to compile.
+/// - `options` (optional) : [`CompileOptions`] forwarded to the Typst CLI.
+/// - `error` (optional) : render prop called with the error when compilation or conversion fails.
+///   Without it, the error message is rendered in a `<pre class="typst-error">`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "dioxus-0-5")]
+/// # extern crate dioxus05 as dioxus;
+/// use dioxus::prelude::*;
+/// use typst_2_rsx::components::Typst;
+///
+/// fn App() -> Element {
+///     rsx! {
+///         Typst { src: "report.typ" }
+///         Typst {
+///             src: "figure.typ",
+///             error: move |e| rsx! { p { "Figure unavailable: {e}" } },
+///         }
+///     }
+/// }
+/// ```
+#[component]
+pub fn Typst(
+    src: String,
+    #[props(optional)] options: CompileOptions,
+    #[props(optional)] error: Option<Callback<Rc<Error>, Element>>,
+) -> Element {
+    let memo = use_hook(|| Rc::new(RefCell::new(None::<CompiledTypst>)));
+    let mut memo = memo.borrow_mut();
+    if !memo
+        .as_ref()
+        .is_some_and(|compiled| compiled.src == src && compiled.options == options)
+    {
+        let result = compile_cached(&src, &options)
+            .and_then(|svg| parse_svg_to_rsx(&svg))
+            .map_err(Rc::new);
+        *memo = Some(CompiledTypst {
+            src,
+            options,
+            result,
+        });
+    }
+
+    match memo.as_ref().map(|compiled| &compiled.result) {
+        Some(Ok(element)) => element.clone(),
+        Some(Err(e)) => match error {
+            Some(render) => render.call(e.clone()),
+            None => rsx!(pre { class: "typst-error", "{e}" }),
+        },
+        None => compat::empty(),
+    }
+}
+
+/// The last compilation performed by a [`Typst`] component, together with the props that produced it.
+struct CompiledTypst {
+    src: String,
+    options: CompileOptions,
+    result: Result<Element, Rc<Error>>,
+}
+
+/// Renders a Typst math expression inline with the surrounding text.
+///
+/// The expression is taken from the text children and compiled with [`typst_math_to_rsx`](crate::typst_math_to_rsx);
+/// the result is memoized on the expression, `class` and `options`. An expression holding a `$`
+/// that is not escaped as `\$` fails with [`Error::InvalidMath`].
+///
+/// **This component requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Props
+///
+/// - `children` : the math expression, as text.
+/// - `class` (optional) : class put on the root `svg` instead of the default `vertical-align: middle` style.
+/// - `options` (optional) : [`CompileOptions`] forwarded to the Typst CLI.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "dioxus-0-5")]
+/// # extern crate dioxus05 as dioxus;
+/// use dioxus::prelude::*;
+/// use typst_2_rsx::components::TypstMath;
+///
+/// fn Sentence() -> Element {
+///     rsx! {
+///         p { "The area is " TypstMath { "integral_0^1 x^2 dif x" } "." }
+///     }
+/// }
+/// ```
+#[component]
+pub fn TypstMath(
+    children: Element,
+    #[props(optional)] class: Option<String>,
+    #[props(optional)] options: CompileOptions,
+) -> Element {
+    let expr = element_text(&children);
+    let memo = use_hook(|| Rc::new(RefCell::new(None::<CompiledMath>)));
+    let mut memo = memo.borrow_mut();
+    if !memo.as_ref().is_some_and(|compiled| {
+        compiled.expr == expr && compiled.class == class && compiled.options == options
+    }) {
+        let style = class.is_none().then_some("vertical-align: middle");
+        let result = math_to_rsx(&expr, class.as_deref(), style, &options).map_err(Rc::new);
+        *memo = Some(CompiledMath {
+            expr,
+            class,
+            options,
+            result,
+        });
+    }
+
+    match memo.as_ref().map(|compiled| &compiled.result) {
+        Some(Ok(element)) => element.clone(),
+        Some(Err(e)) => rsx!(code { class: "typst-error", "{e}" }),
+        None => compat::empty(),
+    }
+}
+
+/// The last compilation performed by a [`TypstMath`] component, together with the props that produced it.
+struct CompiledMath {
+    expr: String,
+    class: Option<String>,
+    options: CompileOptions,
+    result: Result<Element, Rc<Error>>,
+}
+
+/// Concatenates the static and formatted text nodes of an element, ignoring everything else.
+fn element_text(element: &Element) -> String {
+    fn collect(node: &VNode, text: &mut String) {
+        for root in node.template.roots {
+            match root {
+                TemplateNode::Text { text: static_text } => text.push_str(static_text),
+                TemplateNode::Dynamic { id } => match &node.dynamic_nodes[*id] {
+                    DynamicNode::Text(dynamic_text) => text.push_str(&dynamic_text.value),
+                    DynamicNode::Fragment(nodes) => {
+                        nodes.iter().for_each(|node| collect(node, text))
+                    }
+                    _ => {}
+                },
+                TemplateNode::Element { .. } => {}
+            }
+        }
+    }
+
+    let mut text = String::new();
+    if let Some(node) = compat::node(element) {
+        collect(node, &mut text);
+    }
+    text
+}
+
+/// The loading state of a document requested with [`use_typst`].
+///
+/// # Variant
+///
+/// - `Loading` : the document is being compiled, or the path changed and the new document is not ready yet.
+/// - `Ready(Element)` : the converted document.
+/// - `Failed(Rc<Error>)` : compilation or conversion failed.
+#[derive(Debug, Clone)]
+pub enum TypstState {
+    Loading,
+
+    Ready(Element),
+
+    Failed(Rc<Error>),
+}
+
+/// Handle returned by [`use_typst`].
+#[derive(Clone)]
+pub struct UseTypst {
+    resource: Resource<LoadedTypst>,
+    src: String,
+    options: CompileOptions,
+}
+
+impl UseTypst {
+    /// Returns the current state of the document, subscribing the component to changes.
+    pub fn state(&self) -> TypstState {
+        match &*self.resource.read() {
+            Some(loaded) if loaded.src == self.src && loaded.options == self.options => {
+                match &loaded.result {
+                    Ok(prepared) => {
+                        TypstState::Ready(loaded.element.get_or_init(|| prepared.render()).clone())
+                    }
+                    Err(e) => TypstState::Failed(e.clone()),
+                }
+            }
+            _ => TypstState::Loading,
+        }
+    }
+
+    /// Compiles the document again, e.g. after the `.typ` file was edited.
+    pub fn restart(&mut self) {
+        self.resource.restart();
+    }
+}
+
+/// A finished compilation, together with the inputs that produced it.
+///
+/// The compilation thread only produces the `Send` [`PreparedSvg`]; its element is built the
+/// first time the state is read, on the thread of the component.
+struct LoadedTypst {
+    src: String,
+    options: CompileOptions,
+    result: Result<PreparedSvg, Rc<Error>>,
+    element: OnceCell<Element>,
+}
+
+/// Compiles a Typst file in the background and exposes its loading state.
+///
+/// The Typst CLI runs on a separate thread, so the UI stays responsive while compiling. The hook
+/// re-runs whenever `src` changes; pass `path()` from a signal to follow it. Compiled documents are
+/// cached on their content and path, so switching back to a recently shown document is instant.
+///
+/// **This hook requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "dioxus-0-5")]
+/// # extern crate dioxus05 as dioxus;
+/// use dioxus::prelude::*;
+/// use typst_2_rsx::components::{use_typst, TypstState};
+///
+/// fn Report() -> Element {
+///     let doc = use_typst("report.typ");
+///     match doc.state() {
+///         TypstState::Loading => rsx! { p { "Compiling..." } },
+///         TypstState::Ready(element) => element,
+///         TypstState::Failed(e) => rsx! { pre { "{e}" } },
+///     }
+/// }
+/// ```
+pub fn use_typst(src: impl Into<String>) -> UseTypst {
+    use_typst_with_options(src, CompileOptions::default())
+}
+
+/// Like [`use_typst`], compiling with the given [`CompileOptions`].
+pub fn use_typst_with_options(src: impl Into<String>, options: CompileOptions) -> UseTypst {
+    let src = src.into();
+    let resource = use_resource(use_reactive(
+        (&src, &options),
+        |(src, options)| async move {
+            let (sender, receiver) = oneshot::channel();
+            let (thread_src, thread_options) = (src.clone(), options.clone());
+            // The document is parsed on the thread as well; only its RSX is built here
+            thread::spawn(move || {
+                let prepared = compile_cached(&thread_src, &thread_options)
+                    .and_then(|svg| PreparedSvg::parse(&svg, &RsxOptions::default()));
+                let _ = sender.send(prepared);
+            });
+            let result = receiver
+                .await
+                .unwrap_or_else(|_| {
+                    Err(Error::Io {
+                        path: None,
+                        source: io::Error::other("the compilation thread panicked"),
+                    })
+                })
+                .map_err(Rc::new);
+            LoadedTypst {
+                src,
+                options,
+                result,
+                element: OnceCell::new(),
+            }
+        },
+    ));
+    UseTypst {
+        resource,
+        src,
+        options,
+    }
+}
+
+/// Renders a long document page by page, only mounting the pages near the visible ones.
+///
+/// Every page is put in a `<div class="typst-page">` of the page's size, taken from its `width`
+/// and `height` or, when they are relative, from the aspect ratio of its `viewBox`, so that the
+/// scroll height is right from the start. The pages within `window` pages of a visible one are
+/// rendered; the others are empty placeholders. Scrolling is followed through the `onvisible`
+/// event, which the web and desktop renderers support; elsewhere, only the first pages are shown.
+///
+/// Each page is converted once, when it is first shown, and its RSX is kept for when it is shown
+/// again. The pages are memoized on the props like in [`Typst`].
+///
+/// # Props
+///
+/// - `pages` (optional) : the pages to show, such as the result of [`split_pages`].
+/// - `src` (optional) : a `.typ` file to compile and split into pages when `pages` is not given.
+///   **This requires the Typst CLI to be installed and accessible from the system's PATH.**
+/// - `options` (optional) : [`CompileOptions`] forwarded to the Typst CLI for `src`.
+/// - `window` (optional) : how many pages before and after the visible ones are rendered, 2 by
+///   default.
+/// - `class` (optional) : class put on the `div` holding the pages.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "dioxus-0-5")]
+/// # extern crate dioxus05 as dioxus;
+/// use dioxus::prelude::*;
+/// use typst_2_rsx::components::TypstDocument;
+///
+/// fn Thesis() -> Element {
+///     rsx! {
+///         TypstDocument { src: "thesis.typ", window: 1 }
+///     }
+/// }
+/// ```
+#[component]
+pub fn TypstDocument(
+    #[props(optional)] pages: Option<Vec<Svg>>,
+    #[props(optional, into)] src: Option<String>,
+    #[props(optional)] options: CompileOptions,
+    #[props(default = 2)] window: usize,
+    #[props(optional, into)] class: Option<String>,
+) -> Element {
+    let mut visible = use_signal(|| BTreeSet::from([0]));
+    let memo = use_hook(|| Rc::new(RefCell::new(None::<PagedDocument>)));
+    let mut memo = memo.borrow_mut();
+    if !memo.as_ref().is_some_and(|document| {
+        document.pages == pages && document.src == src && document.options == options
+    }) {
+        let split = match (&pages, &src) {
+            (Some(pages), _) => Ok(pages.clone()),
+            (None, Some(src)) => compile_cached(src, &options)
+                .and_then(|svg| parse_svg(&svg))
+                .map(|svg| split_pages(&svg)),
+            (None, None) => Ok(Vec::new()),
+        };
+        *memo = Some(PagedDocument {
+            pages,
+            src,
+            options,
+            split: split.map_err(Rc::new),
+            elements: HashMap::new(),
+        });
+    }
+    let Some(document) = memo.as_mut() else {
+        return compat::empty();
+    };
+    let split = match &document.split {
+        Ok(split) => split,
+        Err(e) => return rsx!(pre { class: "typst-error", "{e}" }),
+    };
+
+    let shown = shown_pages(&visible.read(), window, split.len());
+    let pages = split.iter().enumerate().map(|(index, page)| {
+        let element = shown.contains(&index).then(|| {
+            document
+                .elements
+                .entry(index)
+                .or_insert_with(|| svg_to_rsx(page))
+                .clone()
+        });
+        rsx! {
+            div {
+                key: "{index}",
+                class: "typst-page",
+                style: page_style(page),
+                onvisible: move |event| {
+                    let Ok(intersecting) = event.is_intersecting() else {
+                        return;
+                    };
+                    if intersecting {
+                        visible.write().insert(index);
+                    } else {
+                        visible.write().remove(&index);
+                    }
+                },
+                {element}
+            }
+        }
+    });
+    rsx! {
+        div { class, {pages} }
+    }
+}
+
+/// The pages shown by a [`TypstDocument`], together with the props that produced them.
+struct PagedDocument {
+    pages: Option<Vec<Svg>>,
+    src: Option<String>,
+    options: CompileOptions,
+    split: Result<Vec<Svg>, Rc<Error>>,
+    /// The pages converted so far, by index
+    elements: HashMap<usize, Element>,
+}
+
+/// The pages to render among `count`: those within `window` pages of a `visible` one.
+fn shown_pages(visible: &BTreeSet<usize>, window: usize, count: usize) -> BTreeSet<usize> {
+    visible
+        .iter()
+        .flat_map(|&index| index.saturating_sub(window)..=index.saturating_add(window))
+        .filter(|&index| index < count)
+        .collect()
+}
+
+/// The CSS size of the box holding `page`: its own size when it is absolute, otherwise the full
+/// width with the aspect ratio of its `viewBox`.
+fn page_style(page: &Svg) -> String {
+    if page.width_px().is_some() && page.height_px().is_some() {
+        return format!(
+            "width: {}; height: {};",
+            page.width.trim(),
+            page.height.trim()
+        );
+    }
+    match page.view_box_parsed() {
+        Ok(view_box) if view_box.width > 0.0 && view_box.height > 0.0 => format!(
+            "width: 100%; aspect-ratio: {} / {};",
+            view_box.width, view_box.height
+        ),
+        _ => "width: 100%;".to_string(),
+    }
+}
+
+/// How often [`use_typst_live`] checks whether the file changed.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Compiles a Typst file in the background and again every time it is saved, for live previews.
+///
+/// The returned signal starts as [`TypstState::Loading`] and is updated after every compilation,
+/// so the components reading it re-render with the new document. A failed compilation, such as a
+/// syntax error while typing, sets it to [`TypstState::Failed`]; the file keeps being watched, and
+/// the next successful compilation replaces the error.
+///
+/// The file is watched by a thread checking its modification time every 250 ms; the files the
+/// document imports are not watched. The watcher belongs to the component: it stops when the
+/// component is unmounted, and is replaced when `src` changes. Unlike with [`use_typst`], the
+/// compiled documents are not cached, so a long editing session does not keep every revision.
+///
+/// **This hook requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "dioxus-0-5")]
+/// # extern crate dioxus05 as dioxus;
+/// use dioxus::prelude::*;
+/// use typst_2_rsx::components::{use_typst_live, TypstState};
+///
+/// fn Preview() -> Element {
+///     let doc = use_typst_live("draft.typ");
+///     match doc() {
+///         TypstState::Loading => rsx! { p { "Compiling..." } },
+///         TypstState::Ready(element) => element,
+///         TypstState::Failed(e) => rsx! { pre { "{e}" } },
+///     }
+/// }
+/// ```
+pub fn use_typst_live(src: impl Into<String>) -> ReadOnlySignal<TypstState> {
+    use_typst_live_with_options(src, CompileOptions::default())
+}
+
+/// Like [`use_typst_live`], compiling with the given [`CompileOptions`].
+pub fn use_typst_live_with_options(
+    src: impl Into<String>,
+    options: CompileOptions,
+) -> ReadOnlySignal<TypstState> {
+    let src = src.into();
+    let mut state = use_signal(|| TypstState::Loading);
+    // Dropping the future, on unmount or when the inputs change, drops the receiver, which stops
+    // the watcher thread
+    let _watcher = use_resource(use_reactive(
+        (&src, &options),
+        move |(src, options)| async move {
+            state.set(TypstState::Loading);
+            let mut receiver = watch(src, options, Arc::new(TypstCli));
+            while let Some(result) = receiver.next().await {
+                state.set(match result.and_then(|svg| parse_svg_to_rsx(&svg)) {
+                    Ok(element) => TypstState::Ready(element),
+                    Err(e) => TypstState::Failed(Rc::new(e)),
+                });
+            }
+        },
+    ));
+    state.into()
+}
+
+/// Compiles `src` with `invoker` on a new thread, then again whenever its modification time
+/// changes, until the returned receiver is dropped.
+fn watch(
+    src: String,
+    options: CompileOptions,
+    invoker: Arc<dyn TypstInvoker>,
+) -> mpsc::UnboundedReceiver<Result<Arc<str>, Error>> {
+    let (sender, receiver) = mpsc::unbounded();
+    thread::spawn(move || {
+        // `None` until the first compilation; a missing file has no modification time
+        let mut compiled: Option<Option<SystemTime>> = None;
+        while !sender.is_closed() {
+            let modified = fs::metadata(&src).and_then(|metadata| metadata.modified());
+            let time = modified.as_ref().ok().copied();
+            if compiled != Some(time) {
+                compiled = Some(time);
+                let result = match modified {
+                    Ok(_) => compile_to_string(&*invoker, Path::new(&src), &options)
+                        .map(|(svg, _)| svg.into()),
+                    Err(e) => Err(Error::io(&src, e)),
+                };
+                if sender.unbounded_send(result).is_err() {
+                    break;
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::invoker::{MockResponse, MockTypst};
+
+    #[test]
+    fn element_text_test() {
+        let power = 2;
+        assert_eq!(
+            element_text(&rsx! { "integral_0^1 x^{power} dif x" }),
+            "integral_0^1 x^2 dif x"
+        );
+        assert_eq!(element_text(&rsx! { "a" "+" "b" }), "a+b");
+    }
+
+    #[test]
+    fn shown_pages_test() {
+        let shown = |visible: &[usize], window| {
+            let visible = visible.iter().copied().collect();
+            shown_pages(&visible, window, 10)
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shown(&[0], 2), [0, 1, 2]);
+        assert_eq!(shown(&[5, 6], 1), [4, 5, 6, 7]);
+        assert_eq!(shown(&[9], 2), [7, 8, 9]);
+        assert_eq!(shown(&[], 2), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn page_style_test() {
+        let a4 = Svg::new("595.2756pt", "841.8898pt", "0 0 595.2756 841.8898");
+        assert_eq!(page_style(&a4), "width: 595.2756pt; height: 841.8898pt;");
+        let relative = Svg::new("100%", "100%", "0 0 200 100");
+        assert_eq!(
+            page_style(&relative),
+            "width: 100%; aspect-ratio: 200 / 100;"
+        );
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn typst_document_test() {
+        use crate::svg_types::{Path, SvgElement};
+
+        let page = |d: String| {
+            let mut page = Svg::new("100pt", "50pt", "0 0 100 50");
+            page.elements
+                .push(SvgElement::Path(Box::new(Path::with_d(d))));
+            page
+        };
+        let pages: Vec<_> = (0..5).map(|i| page(format!("M {} 0", i))).collect();
+        let mut dom = VirtualDom::new_with_props(
+            TypstDocument,
+            TypstDocumentProps::builder().pages(pages).window(1).build(),
+        );
+        dom.rebuild_in_place();
+        let html = dioxus_ssr::render(&dom);
+        assert_eq!(html.matches("class=\"typst-page\"").count(), 5);
+        assert!(html.contains("M 0 0") && html.contains("M 1 0"));
+        assert!(!html.contains("M 2 0"));
+        assert!(html.contains("width: 100pt; height: 50pt;"));
+    }
+
+    #[test]
+    fn watch_test() {
+        let src = crate::utils::temp_file_path("typ");
+        let mock = Arc::new(MockTypst::new([MockResponse::svg(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'></svg>",
+        )]));
+        let mut receiver = watch(
+            src.to_string_lossy().into(),
+            CompileOptions::default(),
+            mock.clone(),
+        );
+        let mut next = || {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                if let Ok(message) = receiver.try_next() {
+                    break message.unwrap();
+                }
+                assert!(
+                    Instant::now() < deadline,
+                    "the watcher sent nothing within 5 s"
+                );
+                thread::sleep(Duration::from_millis(10));
+            }
+        };
+        // Saves `content` with a modification time `seconds` after the epoch, so every save is
+        // seen whatever the resolution of the file system
+        let save = |content: &str, seconds| {
+            fs::write(&src, content).unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&src)
+                .unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+                .unwrap();
+        };
+
+        // A missing file is reported, and compiled once it is written
+        let missing = next().unwrap_err();
+        assert_eq!(missing.path(), Some(src.as_path()));
+        assert!(mock.commands().is_empty());
+        save("= Draft", 1);
+        assert!(next().is_ok());
+
+        // Every save compiles again, even back to content compiled before, since nothing is cached
+        save("= Edited", 2);
+        assert!(next().is_ok());
+        save("= Draft", 3);
+        assert!(next().is_ok());
+        assert_eq!(mock.commands().len(), 3);
+        let _ = fs::remove_file(&src);
+    }
+}
@@ -0,0 +1,882 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use dioxus::prelude::*;
+
+use crate::{
+    compat,
+    options::{ElementAttributes, RsxOptions, Selector},
+    svg_types::*,
+    trace,
+};
+
+/// The order of the attributes on every generated element, by their `rsx!` names.
+///
+/// The converter arms below and the source emitted by [`svg_to_rsx_source`](crate::svg_to_rsx_source)
+/// both follow it, so that the output is stable and diffs between versions stay readable: first
+/// the `id` and `class` naming the element and the `in` of a filter primitive, then its geometry,
+/// its paint, the other attributes of a filter primitive, its stroke, its font, the rendering
+/// hints, and last the style and interactivity attributes. The event handlers and
+/// the children come after all of them.
+pub(crate) const ATTRIBUTE_ORDER: &[&str] = &[
+    // Identity
+    "id",
+    "class",
+    "in",
+    // Geometry
+    "view_box",
+    "d",
+    "x",
+    "y",
+    "dx",
+    "dy",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "fx",
+    "fy",
+    "offset",
+    "width",
+    "height",
+    "preserve_aspect_ratio",
+    "href",
+    "transform",
+    "gradient_units",
+    "gradient_transform",
+    "spread_method",
+    "clip_path_units",
+    "mask_units",
+    "mask_content_units",
+    "filter_units",
+    "clip_path",
+    "mask",
+    "filter",
+    "overflow",
+    // Paint
+    "fill",
+    "fill_rule",
+    "opacity",
+    "stop_color",
+    "stop_opacity",
+    // Filter primitives
+    "type",
+    "values",
+    "std_deviation",
+    "flood_color",
+    "flood_opacity",
+    "result",
+    // Stroke
+    "stroke",
+    "stroke_width",
+    "stroke_linecap",
+    "stroke_linejoin",
+    "stroke_miterlimit",
+    // Font
+    "font_family",
+    "font_size",
+    "font_weight",
+    "font_style",
+    "letter_spacing",
+    "word_spacing",
+    "dominant_baseline",
+    "text_anchor",
+    // Rendering hints
+    "shape_rendering",
+    "text_rendering",
+    // Style and interactivity
+    "style",
+    "tabindex",
+    "pointer_events",
+];
+
+/// The rank of an attribute in [`ATTRIBUTE_ORDER`], by its `rsx!` name (`fill_rule`, `r#type`,
+/// `"in"`) or its rendered one (`fill-rule`, `viewBox`). Unknown attributes rank last.
+pub(crate) fn attribute_rank(name: &str) -> usize {
+    let name = name.trim_matches('"').trim_start_matches("r#");
+    let normalized = |name: &str| -> String {
+        name.chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    };
+    let name = normalized(name);
+    ATTRIBUTE_ORDER
+        .iter()
+        .position(|known| normalized(known) == name)
+        .unwrap_or(ATTRIBUTE_ORDER.len())
+}
+
+/// Builds the root `svg` node of a parsed document.
+///
+/// Of `options`, only the settings affecting the RSX itself are used here (sizing, root
+/// attributes, empty absent attributes); the passes must already have been applied to
+/// `parsed`. The class emitted by Typst is only put on the root along with `root_class`, so that
+/// the default output stays the same.
+///
+/// The tree is consumed: its attribute strings are moved into the RSX nodes instead of being
+/// copied, which matters for documents with tens of thousands of glyphs.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "svg_to_rsx",
+        level = "debug",
+        skip_all,
+        fields(
+            elements = tracing::field::Empty,
+            paths = tracing::field::Empty,
+            uses = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        ),
+    )
+)]
+pub(crate) fn svg_root(parsed: Svg, options: &RsxOptions) -> Element {
+    let _timing = trace::Timing::start();
+    trace::record_elements(&parsed);
+    let class = options
+        .root_class
+        .as_ref()
+        .map(|class| format!("{} {}", parsed.class, class).trim().to_string());
+    let root = element_attributes(options, |selector| *selector == Selector::Root);
+    let (shape_rendering, text_rendering) =
+        rendering_hint(options, |selector| *selector == Selector::Root);
+    rsx!(
+        svg {
+            id: options.root_id.clone(),
+            class,
+            view_box: parsed.view_box,
+            width: options.sizing.apply(&parsed.width),
+            height: options.sizing.apply(&parsed.height),
+            shape_rendering,
+            text_rendering,
+            style: options.root_style.clone(),
+            tabindex: root.tabindex,
+            pointer_events: root.pointer_events,
+            ..root_listeners(options),
+            {children(parsed.elements, options, from_svg_element)}
+        }
+    )
+}
+
+/// The value of an optional attribute: absent attributes are left out, unless `omit_absent` is
+/// unset and they are rendered as empty strings.
+fn attribute(value: Option<impl Into<String>>, omit_absent: bool) -> Option<String> {
+    let value = value.map(Into::into);
+    if omit_absent {
+        value
+    } else {
+        Some(value.unwrap_or_default())
+    }
+}
+
+/// Converts the children of one element with `convert`, giving each a `key` unique among them
+/// when `options.keys` is set. The paths without `d` are left out.
+///
+/// The key is the `id` of the child, or else a hash of its own attributes, so that it does not
+/// depend on the position of the child and survives the insertion of siblings. Children with the
+/// same key are told apart by their rank among them: the second becomes `key#1`, and so on.
+fn children<'a, T: Child + 'a>(
+    elements: impl IntoIterator<Item = T> + 'a,
+    options: &'a RsxOptions,
+    convert: fn(T, &RsxOptions) -> Element,
+) -> impl Iterator<Item = Element> + 'a {
+    let mut seen = HashMap::<String, usize>::new();
+    elements
+        .into_iter()
+        .filter(|element| !element.draws_nothing())
+        .map(move |element| {
+            if !options.keys {
+                return convert(element, options);
+            }
+            let base = element.key();
+            let rank = seen.entry(base.clone()).or_default();
+            let key = match *rank {
+                0 => base,
+                rank => format!("{}#{}", base, rank),
+            };
+            *rank += 1;
+            compat::with_key(convert(element, options), key)
+        })
+}
+
+/// A child element converted by [`children`].
+trait Child {
+    /// The `id` of the element, or else its tag followed by a hash of its own attributes,
+    /// excluding its children. The space cannot occur in an `id`, so the two never collide.
+    fn key(&self) -> String;
+
+    /// Whether the element is a path without path data, which is not emitted.
+    fn draws_nothing(&self) -> bool;
+}
+
+/// The key of an element without `id`, from its tag and attributes.
+fn hashed_key(tag: &str, attributes: impl Hash) -> String {
+    let mut hasher = DefaultHasher::new();
+    attributes.hash(&mut hasher);
+    format!("{} {:016x}", tag, hasher.finish())
+}
+
+impl Child for SvgElement {
+    fn draws_nothing(&self) -> bool {
+        matches!(self, SvgElement::Path(path) if path.draws_nothing())
+    }
+
+    fn key(&self) -> String {
+        match self {
+            SvgElement::Path(path) => path.key(),
+            SvgElement::G(g) => g.key(),
+            SvgElement::Defs(defs) => defs.id.clone(),
+            SvgElement::Rect(rect) => hashed_key(
+                "rect",
+                (&rect.x, &rect.y, &rect.width, &rect.height, &rect.fill),
+            ),
+        }
+    }
+}
+
+impl Child for GEle {
+    fn draws_nothing(&self) -> bool {
+        matches!(self, GEle::Path(path) if path.draws_nothing())
+    }
+
+    fn key(&self) -> String {
+        match self {
+            GEle::G(g) => g.key(),
+            GEle::Path(path) => path.key(),
+            GEle::Use(uuse) => hashed_key(
+                "use",
+                (
+                    &uuse.href,
+                    &uuse.x,
+                    &uuse.width,
+                    &uuse.height,
+                    &uuse.fill,
+                    &uuse.fill_rule,
+                    &uuse.transform,
+                ),
+            ),
+            GEle::Image(image) => hashed_key(
+                "image",
+                (
+                    &image.href,
+                    &image.width,
+                    &image.height,
+                    &image.preserve_aspect_ratio,
+                    &image.transform,
+                ),
+            ),
+            GEle::Text(text) => match &text.id {
+                Some(id) => id.clone(),
+                None => hashed_key(
+                    "text",
+                    (
+                        &text.class,
+                        &text.x,
+                        &text.y,
+                        &text.fill,
+                        &text.font_family,
+                        &text.font_size,
+                        &text.transform,
+                        text_characters(text),
+                    ),
+                ),
+            },
+            GEle::Switch(switch) => match &switch.id {
+                Some(id) => id.clone(),
+                None => hashed_key("switch", &switch.transform),
+            },
+        }
+    }
+}
+
+/// The characters of a text, its own and those of its `tspan`, in document order.
+fn text_characters(text: &Text) -> Vec<&str> {
+    text.content
+        .iter()
+        .map(|content| match content {
+            TextContent::Characters(characters) => characters.as_str(),
+            TextContent::Tspan(tspan) => tspan.characters.as_str(),
+        })
+        .collect()
+}
+
+impl Child for Path {
+    fn draws_nothing(&self) -> bool {
+        self.d.trim().is_empty()
+    }
+
+    fn key(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => hashed_key(
+                "path",
+                (
+                    &self.d,
+                    &self.class,
+                    &self.fill,
+                    &self.fill_rule,
+                    &self.stroke,
+                    &self.stroke_width,
+                    (
+                        &self.stroke_linecap,
+                        &self.stroke_linejoin,
+                        &self.stroke_miterlimit,
+                    ),
+                ),
+            ),
+        }
+    }
+}
+
+impl Child for G {
+    fn draws_nothing(&self) -> bool {
+        false
+    }
+
+    fn key(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => hashed_key("g", (&self.class, &self.transform)),
+        }
+    }
+}
+
+/// The event handlers of `options` that apply to the root `svg`.
+fn root_listeners(options: &RsxOptions) -> Vec<Attribute> {
+    match &options.on_click {
+        Some((Selector::Root, handler)) => vec![compat::onclick(*handler)],
+        _ => vec![],
+    }
+}
+
+/// The event handlers of `options` that apply to an element with this `id` and `class`.
+fn listeners(options: &RsxOptions, id: Option<&str>, class: Option<&str>) -> Vec<Attribute> {
+    match &options.on_click {
+        Some((selector, handler)) if selector.matches(id, class) => {
+            vec![compat::onclick(*handler)]
+        }
+        _ => vec![],
+    }
+}
+
+/// The attributes set by `options.element_attributes` on an element, given which selectors match
+/// it. The later entries override the earlier ones.
+fn element_attributes(
+    options: &RsxOptions,
+    matches: impl Fn(&Selector) -> bool,
+) -> ElementAttributes {
+    let mut attributes = ElementAttributes::default();
+    for (selector, set) in &options.element_attributes {
+        if !matches(selector) {
+            continue;
+        }
+        if set.tabindex.is_some() {
+            attributes.tabindex.clone_from(&set.tabindex);
+        }
+        if set.pointer_events.is_some() {
+            attributes.pointer_events.clone_from(&set.pointer_events);
+        }
+    }
+    attributes
+}
+
+/// The attributes set by `options.element_attributes` on the `g` or `path` with this `id` and
+/// `class`, over its own `tabindex` and `pointer_events`.
+fn interactivity(
+    options: &RsxOptions,
+    id: Option<&str>,
+    class: Option<&str>,
+    tabindex: Option<String>,
+    pointer_events: Option<String>,
+) -> ElementAttributes {
+    let set = element_attributes(options, |selector| selector.matches(id, class));
+    ElementAttributes {
+        tabindex: set.tabindex.or(tabindex),
+        pointer_events: set.pointer_events.or(pointer_events),
+    }
+}
+
+/// The value of `options.rendering_hint` for an element, given which selectors match it, as its
+/// `shape-rendering` and its `text-rendering`. Each property gets the value unless it belongs to
+/// the other one only, as `crispEdges` and `optimizeLegibility` do.
+fn rendering_hint(
+    options: &RsxOptions,
+    matches: impl Fn(&Selector) -> bool,
+) -> (Option<String>, Option<String>) {
+    match &options.rendering_hint {
+        Some((selector, value)) if matches(selector) => (
+            (value != "optimizeLegibility").then(|| value.clone()),
+            (value != "crispEdges").then(|| value.clone()),
+        ),
+        _ => (None, None),
+    }
+}
+
+/// Converts an `SvgElement` to the corresponding RSX `Element`.
+///
+/// # Parameters
+///
+/// - `tag`: The `SvgElement` to be converted, whose attributes are moved into the RSX.
+/// - `options`: The conversion options (empty absent attributes, event handlers).
+///
+/// # Returns
+///
+/// Returns the RSX `Element` corresponding to the input `SvgElement`.
+///
+pub(crate) fn from_svg_element(tag: SvgElement, options: &RsxOptions) -> Element {
+    let omit_absent = !options.empty_absent_attributes;
+    let omit_paint = omit_absent || options.strip_presentation_attributes;
+    match tag {
+        SvgElement::Path(path) => {
+            let listeners = listeners(options, path.id.as_deref(), path.class.as_deref());
+            let ElementAttributes {
+                tabindex,
+                pointer_events,
+            } = interactivity(
+                options,
+                path.id.as_deref(),
+                path.class.as_deref(),
+                path.tabindex,
+                path.pointer_events,
+            );
+            let (shape_rendering, _) = rendering_hint(options, |selector| {
+                selector.matches(path.id.as_deref(), path.class.as_deref())
+            });
+            rsx!(path {
+                id: path.id,
+                class: attribute(path.class.as_deref(), omit_absent),
+                d: path.d,
+                fill: attribute(path.fill.as_deref(), omit_paint),
+                fill_rule: attribute(path.fill_rule, omit_absent),
+                opacity: path.opacity,
+                stroke: attribute(path.stroke.as_deref(), omit_paint),
+                stroke_width: attribute(path.stroke_width.as_deref(), omit_paint),
+                stroke_linecap: attribute(path.stroke_linecap, omit_paint),
+                stroke_linejoin: attribute(path.stroke_linejoin, omit_paint),
+                stroke_miterlimit: attribute(path.stroke_miterlimit, omit_paint),
+                shape_rendering: shape_rendering.or(path.shape_rendering),
+                tabindex,
+                pointer_events,
+                ..listeners,
+            })
+        }
+        SvgElement::G(g) => {
+            let listeners = listeners(options, g.id.as_deref(), g.class.as_deref());
+            let ElementAttributes {
+                tabindex,
+                pointer_events,
+            } = interactivity(
+                options,
+                g.id.as_deref(),
+                g.class.as_deref(),
+                g.tabindex,
+                g.pointer_events,
+            );
+            let (shape_rendering, text_rendering) = rendering_hint(options, |selector| {
+                selector.matches(g.id.as_deref(), g.class.as_deref())
+            });
+            rsx!(
+                g {
+                    id: g.id,
+                    class: attribute(g.class.as_deref(), omit_absent),
+                    transform: attribute(g.transform, omit_absent),
+                    clip_path: g.clip_path.as_deref(),
+                    mask: g.mask.as_deref(),
+                    filter: g.filter.as_deref(),
+                    shape_rendering: shape_rendering.or(g.shape_rendering),
+                    text_rendering: text_rendering.or(g.text_rendering),
+                    tabindex,
+                    pointer_events,
+                    ..listeners,
+                    {children(g.elements.into_iter().flatten(), options, from_g_element)}
+                }
+            )
+        }
+        SvgElement::Defs(defs) => {
+            rsx!(
+                defs { id: (!defs.id.is_empty()).then_some(defs.id),
+                    {defs.elements.into_iter().map(from_symbol)}
+                    {defs.definitions.into_iter().map(from_definition)}
+                }
+            )
+        }
+        SvgElement::Rect(rect) => {
+            rsx!(rect {
+                x: rect.x,
+                y: rect.y,
+                width: rect.width,
+                height: rect.height,
+                fill: attribute(rect.fill.as_deref(), omit_paint),
+            })
+        }
+    }
+}
+
+/// Converts a `GEle` to the corresponding RSX `Element`.
+///
+/// # Parameters
+///
+/// - `tag`: The `GEle` to be converted, whose attributes are moved into the RSX.
+/// - `options`: The conversion options (empty absent attributes, event handlers).
+///
+/// # Returns
+///
+/// Returns the RSX `Element` corresponding to the input `GEle`.
+///
+pub(crate) fn from_g_element(tag: GEle, options: &RsxOptions) -> Element {
+    let omit_absent = !options.empty_absent_attributes;
+    let omit_paint = omit_absent || options.strip_presentation_attributes;
+    match tag {
+        GEle::G(g) => {
+            let listeners = listeners(options, g.id.as_deref(), g.class.as_deref());
+            let ElementAttributes {
+                tabindex,
+                pointer_events,
+            } = interactivity(
+                options,
+                g.id.as_deref(),
+                g.class.as_deref(),
+                g.tabindex,
+                g.pointer_events,
+            );
+            let (shape_rendering, text_rendering) = rendering_hint(options, |selector| {
+                selector.matches(g.id.as_deref(), g.class.as_deref())
+            });
+            rsx! {
+                g {
+                    id: g.id,
+                    class: attribute(g.class.as_deref(), omit_absent),
+                    transform: attribute(g.transform, omit_absent),
+                    clip_path: g.clip_path.as_deref(),
+                    mask: g.mask.as_deref(),
+                    filter: g.filter.as_deref(),
+                    shape_rendering: shape_rendering.or(g.shape_rendering),
+                    text_rendering: text_rendering.or(g.text_rendering),
+                    tabindex,
+                    pointer_events,
+                    ..listeners,
+                    {children(g.elements.into_iter().flatten(), options, from_g_element)}
+                }
+            }
+        }
+        GEle::Use(uuse) => {
+            rsx! {
+                r#use {
+                    x: uuse.x,
+                    width: uuse.width,
+                    height: uuse.height,
+                    href: &*uuse.href,
+                    transform: uuse.transform,
+                    fill: uuse.fill.as_deref(),
+                    fill_rule: uuse.fill_rule.map(String::from),
+                }
+            }
+        }
+        GEle::Path(path) => {
+            let listeners = listeners(options, path.id.as_deref(), path.class.as_deref());
+            let ElementAttributes {
+                tabindex,
+                pointer_events,
+            } = interactivity(
+                options,
+                path.id.as_deref(),
+                path.class.as_deref(),
+                path.tabindex,
+                path.pointer_events,
+            );
+            let (shape_rendering, _) = rendering_hint(options, |selector| {
+                selector.matches(path.id.as_deref(), path.class.as_deref())
+            });
+            rsx!(path {
+                id: path.id,
+                class: path.class.as_deref(),
+                d: path.d,
+                fill: attribute(path.fill.as_deref(), omit_paint),
+                fill_rule: attribute(path.fill_rule, omit_absent),
+                opacity: path.opacity,
+                stroke: attribute(path.stroke.as_deref(), omit_paint),
+                stroke_width: attribute(path.stroke_width.as_deref(), omit_paint),
+                stroke_linecap: attribute(path.stroke_linecap, omit_paint),
+                stroke_linejoin: attribute(path.stroke_linejoin, omit_paint),
+                stroke_miterlimit: attribute(path.stroke_miterlimit, omit_paint),
+                shape_rendering: shape_rendering.or(path.shape_rendering),
+                tabindex,
+                pointer_events,
+                ..listeners,
+            })
+        }
+        GEle::Image(image) => {
+            rsx!(image {
+                width: image.width,
+                height: image.height,
+                preserve_aspect_ratio: image.preserve_aspect_ratio,
+                href: image.href,
+                transform: image.transform,
+            })
+        }
+        GEle::Text(text) => {
+            let Text {
+                id,
+                class,
+                x,
+                y,
+                dx,
+                dy,
+                fill,
+                font_family,
+                font_size,
+                font_weight,
+                font_style,
+                letter_spacing,
+                word_spacing,
+                dominant_baseline,
+                text_anchor,
+                text_rendering,
+                transform,
+                content,
+            } = *text;
+            let (_, hint) = rendering_hint(options, |selector| {
+                selector.matches(id.as_deref(), class.as_deref())
+            });
+            rsx!(text {
+                id,
+                class: class.as_deref(),
+                x,
+                y,
+                dx,
+                dy,
+                transform,
+                fill: attribute(fill.as_deref(), omit_paint),
+                font_family,
+                font_size,
+                font_weight,
+                font_style,
+                letter_spacing,
+                word_spacing,
+                dominant_baseline,
+                text_anchor,
+                text_rendering: hint.or(text_rendering),
+                {content.into_iter().map(|content| from_text_content(content, omit_paint))}
+            })
+        }
+        GEle::Switch(switch) => {
+            // Only the branch chosen is converted, in a group keeping the attributes of the switch
+            let index = switch.rendered_branch(options.switch);
+            let Switch {
+                id,
+                transform,
+                branches,
+            } = *switch;
+            let rendered = index
+                .and_then(|index| branches.into_iter().nth(index))
+                .and_then(|branch| match branch {
+                    SwitchBranch::Element(element) => Some(from_g_element(element, options)),
+                    SwitchBranch::Unsupported(_) => None,
+                });
+            rsx!(g { id, transform, {rendered} })
+        }
+    }
+}
+
+/// Converts a child of a `text` to the corresponding RSX node: a text node for the characters.
+fn from_text_content(content: TextContent, omit_paint: bool) -> Element {
+    match content {
+        TextContent::Characters(characters) => rsx!("{characters}"),
+        TextContent::Tspan(tspan) => {
+            rsx!(tspan {
+                x: tspan.x,
+                y: tspan.y,
+                dx: tspan.dx,
+                dy: tspan.dy,
+                fill: attribute(tspan.fill.as_deref(), omit_paint),
+                font_family: tspan.font_family,
+                font_size: tspan.font_size,
+                font_weight: tspan.font_weight,
+                font_style: tspan.font_style,
+                letter_spacing: tspan.letter_spacing,
+                word_spacing: tspan.word_spacing,
+                dominant_baseline: tspan.dominant_baseline,
+                "{tspan.characters}"
+            })
+        }
+    }
+}
+
+/// Converts a [`Definition`] to the corresponding RSX `Element`, keeping its attributes as
+/// written so that the paints referencing it render as in the SVG.
+pub(crate) fn from_definition(definition: Definition) -> Element {
+    match definition {
+        Definition::LinearGradient(gradient) => rsx!(
+            linearGradient {
+                id: gradient.id,
+                x1: gradient.x1,
+                y1: gradient.y1,
+                x2: gradient.x2,
+                y2: gradient.y2,
+                href: gradient.href,
+                gradient_units: gradient.gradient_units.map(String::from),
+                gradient_transform: gradient.gradient_transform,
+                spread_method: gradient.spread_method.map(String::from),
+                {gradient.stops.into_iter().map(from_stop)}
+            }
+        ),
+        Definition::RadialGradient(gradient) => rsx!(
+            radialGradient {
+                id: gradient.id,
+                cx: gradient.cx,
+                cy: gradient.cy,
+                r: gradient.r,
+                fx: gradient.fx,
+                fy: gradient.fy,
+                href: gradient.href,
+                gradient_units: gradient.gradient_units.map(String::from),
+                gradient_transform: gradient.gradient_transform,
+                spread_method: gradient.spread_method.map(String::from),
+                {gradient.stops.into_iter().map(from_stop)}
+            }
+        ),
+        Definition::ClipPath(clip) => rsx!(
+            clipPath {
+                id: clip.id,
+                transform: clip.transform,
+                clip_path_units: clip.clip_path_units.map(String::from),
+                {clip.elements.into_iter().map(from_definition_path)}
+            }
+        ),
+        Definition::Mask(mask) => rsx!(
+            mask {
+                id: mask.id,
+                x: mask.x,
+                y: mask.y,
+                width: mask.width,
+                height: mask.height,
+                mask_units: mask.mask_units.map(String::from),
+                mask_content_units: mask.mask_content_units.map(String::from),
+                {mask.elements.into_iter().map(from_definition_path)}
+            }
+        ),
+        Definition::Filter(filter) => rsx!(
+            filter {
+                id: filter.id,
+                x: filter.x,
+                y: filter.y,
+                width: filter.width,
+                height: filter.height,
+                filterUnits: filter.filter_units.map(String::from),
+                {filter.primitives.into_iter().map(from_primitive)}
+            }
+        ),
+    }
+}
+
+/// Converts a primitive of a filter, its place among its siblings defining what it processes.
+fn from_primitive(primitive: FilterPrimitive) -> Element {
+    match primitive {
+        FilterPrimitive::GaussianBlur(blur) => rsx!(feGaussianBlur {
+            "in": blur.input,
+            std_deviation: blur.std_deviation,
+            result: blur.result,
+        }),
+        FilterPrimitive::Offset(offset) => rsx!(feOffset {
+            "in": offset.input,
+            dx: offset.dx,
+            dy: offset.dy,
+            result: offset.result,
+        }),
+        FilterPrimitive::ColorMatrix(matrix) => rsx!(feColorMatrix {
+            "in": matrix.input,
+            r#type: matrix.kind.map(String::from),
+            values: matrix.values,
+            result: matrix.result,
+        }),
+        FilterPrimitive::DropShadow(shadow) => rsx!(feDropShadow {
+            "in": shadow.input,
+            dx: shadow.dx,
+            dy: shadow.dy,
+            std_deviation: shadow.std_deviation,
+            flood_color: shadow.flood_color.as_deref(),
+            flood_opacity: shadow.flood_opacity,
+            result: shadow.result,
+        }),
+    }
+}
+
+/// Converts a path of a clip path or a mask, to which the options do not apply.
+fn from_definition_path(path: Path) -> Element {
+    rsx!(path {
+        id: path.id,
+        class: path.class.as_deref(),
+        d: path.d,
+        fill: path.fill.as_deref(),
+        fill_rule: path.fill_rule.map(String::from),
+        opacity: path.opacity,
+        stroke: path.stroke.as_deref(),
+        stroke_width: path.stroke_width.as_deref(),
+        stroke_linecap: path.stroke_linecap.map(String::from),
+        stroke_linejoin: path.stroke_linejoin.map(String::from),
+        stroke_miterlimit: path.stroke_miterlimit,
+        shape_rendering: path.shape_rendering,
+        tabindex: path.tabindex,
+        pointer_events: path.pointer_events,
+    })
+}
+
+/// Converts a `Stop`, the color given in its style rendered like the attribute.
+fn from_stop(mut stop: Stop) -> Element {
+    stop.normalize_style();
+    rsx!(stop {
+        offset: stop.offset,
+        stop_color: stop.stop_color.as_deref(),
+        stop_opacity: stop.stop_opacity,
+        style: stop.style,
+    })
+}
+
+/// Converts a `Symbol` to the corresponding RSX `Element`.
+///
+/// # Parameters
+///
+/// - `tag`: The `Symbol` to be converted, whose attributes are moved into the RSX.
+///
+/// # Returns
+///
+/// Returns the RSX `Element` corresponding to the input `Symbol`.
+///
+pub(crate) fn from_symbol(tag: Symbol) -> Element {
+    rsx!(
+        symbol { id: tag.id, view_box: tag.view_box,
+            preserve_aspect_ratio: tag.preserve_aspect_ratio, overflow: tag.overflow,
+            {
+                match tag.element {
+                    SymbolEle::Path(path) if path.draws_nothing() => compat::empty(),
+                    SymbolEle::Path(path) => {
+                        rsx! {
+                            path {
+                                class: path.class.as_deref(),
+                                d: path.d,
+                                fill: path.fill.as_deref(),
+                                fill_rule: path.fill_rule.map(String::from),
+                            }
+                        }
+                    }
+                    SymbolEle::Image(image) => {
+                        rsx! {
+                            image {
+                                width: image.width,
+                                height: image.height,
+                                preserve_aspect_ratio: image.preserve_aspect_ratio,
+                                href: image.href,
+                                transform: image.transform,
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,340 @@
+use std::{fmt, path::Path, sync::Arc};
+
+use dioxus::prelude::Element;
+
+use crate::{
+    compile_checked, compile_labeled, compile_to_pages, compile_to_rsx, compile_to_string,
+    error::Error,
+    invoker::{TypstCli, TypstInvoker},
+    labels::LabelAnchor,
+    options::{CompileOptions, RsxOptions},
+    CompileOutput,
+};
+
+/// Compiles and converts Typst files with the same options, running the Typst CLI through a
+/// [`TypstInvoker`].
+///
+/// The converter is built by chaining its setters from [`TypstConverter::new`], which compiles
+/// with the default options through [`TypstCli`], like the free functions. Giving it another
+/// invoker, such as a [`MockTypst`](crate::invoker::MockTypst), tests the code built on it
+/// without Typst installed. Cloning a converter shares its invoker.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{
+///     options::{CompileOptions, RsxOptions},
+///     TypstConverter,
+/// };
+///
+/// let converter = TypstConverter::new()
+///     .compile_options(CompileOptions {
+///         root: Some(".".into()),
+///         ..Default::default()
+///     })
+///     .rsx_options(RsxOptions {
+///         root_class: Some("figure".to_string()),
+///         ..Default::default()
+///     });
+/// let (figure, output) = converter.to_rsx("figure.typ").unwrap();
+/// let pages = converter.to_rsx_pages("report.typ").unwrap();
+/// ```
+#[derive(Clone)]
+pub struct TypstConverter {
+    compile_options: CompileOptions,
+    rsx_options: RsxOptions,
+    invoker: Arc<dyn TypstInvoker>,
+}
+
+impl TypstConverter {
+    /// Creates a converter with the default options, running the installed Typst CLI.
+    pub fn new() -> Self {
+        TypstConverter {
+            compile_options: CompileOptions::default(),
+            rsx_options: RsxOptions::default(),
+            invoker: Arc::new(TypstCli),
+        }
+    }
+
+    /// Sets the options the documents are compiled with.
+    pub fn compile_options(mut self, options: CompileOptions) -> Self {
+        self.compile_options = options;
+        self
+    }
+
+    /// Sets the options the compiled documents are converted with.
+    pub fn rsx_options(mut self, options: RsxOptions) -> Self {
+        self.rsx_options = options;
+        self
+    }
+
+    /// Sets what runs the commands of the Typst CLI. Passing an `Arc` keeps access to the
+    /// invoker, such as for reading the commands a [`MockTypst`](crate::invoker::MockTypst)
+    /// received.
+    pub fn invoker(mut self, invoker: impl TypstInvoker + 'static) -> Self {
+        self.invoker = Arc::new(invoker);
+        self
+    }
+
+    /// Compiles the Typst file to an SVG file at `output_svg_file`, creating its directory.
+    ///
+    /// Unlike [`typst_compile_with`](crate::typst_compile_with), a failed compilation is an
+    /// error, and a file left at `output_svg_file` by an earlier compilation is removed first.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(CompileOutput)` : the SVG was written; the output gives the warnings of Typst.
+    /// - `Err(Error)` : [`Error::TypstFailed`] with the Typst diagnostics, [`Error::MissingOutput`]
+    ///   when Typst wrote nothing, or an error starting it.
+    pub fn compile(
+        &self,
+        input_typ_file: impl AsRef<Path>,
+        output_svg_file: impl AsRef<Path>,
+    ) -> Result<CompileOutput, Error> {
+        compile_checked(
+            self.invoker.as_ref(),
+            input_typ_file.as_ref(),
+            output_svg_file.as_ref(),
+            &self.compile_options,
+        )
+    }
+
+    /// Compiles the Typst file and returns the SVG, like [`typst_to_svg`](crate::typst_to_svg).
+    pub fn to_svg(&self, input_typ_file: impl AsRef<Path>) -> Result<String, Error> {
+        compile_to_string(
+            self.invoker.as_ref(),
+            input_typ_file.as_ref(),
+            &self.compile_options,
+        )
+        .map(|(content, _)| content)
+    }
+
+    /// Converts the Typst file to an RSX element, together with the [`CompileOutput`], like
+    /// [`typst_to_rsx_with_options`](crate::typst_to_rsx_with_options).
+    pub fn to_rsx(
+        &self,
+        input_typ_file: impl AsRef<Path>,
+    ) -> Result<(Element, CompileOutput), Error> {
+        compile_to_rsx(
+            self.invoker.as_ref(),
+            input_typ_file.as_ref(),
+            &self.compile_options,
+            &self.rsx_options,
+        )
+    }
+
+    /// Converts the Typst file to one RSX element per page, like
+    /// [`typst_to_rsx_paged_with`](crate::typst_to_rsx_paged_with).
+    pub fn to_rsx_pages(&self, input_typ_file: impl AsRef<Path>) -> Result<Vec<Element>, Error> {
+        compile_to_pages(
+            self.invoker.as_ref(),
+            input_typ_file.as_ref(),
+            &self.compile_options,
+            &self.rsx_options,
+        )
+    }
+
+    /// Converts the Typst file to an RSX element with its labels as `id`, together with an
+    /// anchor for every label, like [`typst_to_rsx_labeled`](crate::typst_to_rsx_labeled).
+    pub fn to_rsx_labeled(
+        &self,
+        input_typ_file: impl AsRef<Path>,
+    ) -> Result<(Element, Vec<LabelAnchor>), Error> {
+        compile_labeled(
+            self.invoker.as_ref(),
+            input_typ_file.as_ref(),
+            &self.compile_options,
+            &self.rsx_options,
+        )
+    }
+}
+
+impl Default for TypstConverter {
+    fn default() -> Self {
+        TypstConverter::new()
+    }
+}
+
+impl fmt::Debug for TypstConverter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypstConverter")
+            .field("compile_options", &self.compile_options)
+            .field("rsx_options", &self.rsx_options)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, sync::Arc, time::Duration};
+
+    use super::*;
+    use crate::{
+        compat::render_html,
+        invoker::{MockResponse, MockTypst},
+        utils::temp_file_path,
+    };
+
+    const SVG: &str = r#"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt"><path d="M 0 0 L 10 10"/></svg>"#;
+
+    fn converter(
+        responses: impl IntoIterator<Item = MockResponse>,
+    ) -> (TypstConverter, Arc<MockTypst>) {
+        let mock = Arc::new(MockTypst::new(responses));
+        (TypstConverter::new().invoker(mock.clone()), mock)
+    }
+
+    fn failure(code: i32, stderr: &str) -> MockResponse {
+        MockResponse::Fail {
+            code,
+            stderr: stderr.to_string(),
+        }
+    }
+
+    #[test]
+    fn success_test() {
+        let (converter, mock) = converter([MockResponse::Svg {
+            svg: SVG.to_string(),
+            stderr: "warning: unknown font family: serif\n".to_string(),
+        }]);
+        let (element, output) = converter.to_rsx("doc.typ").unwrap();
+        assert!(render_html(element).contains("M 0 0 L 10 10"));
+        assert!(output.status.success());
+        assert_eq!(output.stderr, "warning: unknown font family: serif");
+        assert_eq!(output.output_size_bytes, Some(SVG.len() as u64));
+        assert_eq!(output.attempts, 1);
+        // The temporary output is removed once parsed
+        assert!(!output.output_path.exists());
+
+        assert_eq!(converter.to_svg("doc.typ").unwrap(), SVG);
+        let commands = mock.commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0][0], "compile");
+        assert_eq!(commands[0][1], "doc.typ");
+
+        let output_path = temp_file_path("dir").join("nested").join("doc.svg");
+        let output = converter.compile("doc.typ", &output_path).unwrap();
+        assert_eq!(output.output_path, output_path);
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), SVG);
+        fs::remove_dir_all(output_path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn failed_test() {
+        let (converter, _) = converter([failure(2, "error: unknown variable: x\n")]);
+        let error = converter.to_rsx("doc.typ").unwrap_err();
+        let Error::TypstFailed { status, stderr } = &error else {
+            panic!("{:?}", error);
+        };
+        assert_eq!(status.code(), Some(2));
+        assert_eq!(stderr, "error: unknown variable: x");
+        assert_eq!(error.stderr(), Some("error: unknown variable: x"));
+        assert!(matches!(
+            converter.to_rsx_pages("doc.typ"),
+            Err(Error::TypstFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn missing_output_test() {
+        let (converter, _) = converter([MockResponse::NoOutput]);
+        assert!(matches!(
+            converter.to_svg("doc.typ"),
+            Err(Error::MissingOutput { .. })
+        ));
+
+        // The output of an earlier compilation is removed, not read
+        let output_path = temp_file_path("svg");
+        fs::write(&output_path, SVG).unwrap();
+        let error = converter.compile("doc.typ", &output_path).unwrap_err();
+        assert!(matches!(error, Error::MissingOutput { path } if path == output_path));
+        assert!(!output_path.exists());
+    }
+
+    #[test]
+    fn invoker_error_test() {
+        let (converter, _) = converter([
+            MockResponse::SpawnError(io::ErrorKind::NotFound),
+            MockResponse::SpawnError(io::ErrorKind::PermissionDenied),
+            MockResponse::Timeout,
+        ]);
+        assert!(matches!(
+            converter.to_svg("doc.typ"),
+            Err(Error::TypstNotFound)
+        ));
+        assert!(matches!(
+            converter.to_svg("doc.typ"),
+            Err(Error::Io { path: None, source }) if source.kind() == io::ErrorKind::PermissionDenied
+        ));
+        let converter = converter.compile_options(CompileOptions {
+            timeout: Some(Duration::from_secs(3)),
+            ..Default::default()
+        });
+        let error = converter.to_svg("doc.typ").unwrap_err();
+        assert!(matches!(error, Error::TypstTimeout { after } if after == Duration::from_secs(3)));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn invalid_svg_test() {
+        let (converter, _) = converter([MockResponse::svg("<svg class='typst-doc'><path")]);
+        let error = converter.to_rsx("doc.typ").unwrap_err();
+        assert!(!matches!(error, Error::TypstFailed { .. }), "{:?}", error);
+        // The SVG is returned as Typst wrote it
+        assert_eq!(
+            converter.to_svg("doc.typ").unwrap(),
+            "<svg class='typst-doc'><path"
+        );
+    }
+
+    #[test]
+    fn pages_test() {
+        let (converter, _) = converter([MockResponse::svg(
+            r#"<svg class="typst-doc" viewBox="0 0 100 200" width="100pt" height="200pt">
+                <g class="typst-page" transform="translate(0, 0)"><path d="M 0 0 L 10 10"/></g>
+                <g class="typst-page" transform="translate(0, 100)"><path d="M 0 0 L 20 20"/></g>
+            </svg>"#,
+        )]);
+        let pages = converter.to_rsx_pages("doc.typ").unwrap();
+        assert_eq!(pages.len(), 2);
+        assert!(render_html(pages.into_iter().nth(1).unwrap()).contains("M 0 0 L 20 20"));
+    }
+
+    #[test]
+    fn options_test() {
+        let (converter, mock) = converter([MockResponse::svg(SVG)]);
+        let converter = converter
+            .compile_options(CompileOptions {
+                root: Some("docs".into()),
+                ..Default::default()
+            })
+            .rsx_options(RsxOptions {
+                root_id: Some("fig-1".to_string()),
+                ..Default::default()
+            });
+        let (element, _) = converter.to_rsx("doc.typ").unwrap();
+        assert!(render_html(element).contains(r#"id="fig-1""#));
+        let args = &mock.commands()[0];
+        let root = args.iter().position(|arg| arg == "--root").unwrap();
+        assert_eq!(args[root + 1], "docs");
+    }
+
+    #[test]
+    fn responses_test() {
+        let mock = MockTypst::new([MockResponse::Stdout("[]".to_string()), failure(1, "")]);
+        let options = CompileOptions::default();
+        let run = |mock: &MockTypst| {
+            mock.invoke(std::process::Command::new("typst"), &options)
+                .unwrap()
+                .output
+        };
+        assert_eq!(run(&mock).stdout, b"[]");
+        // The last response is repeated
+        assert_eq!(run(&mock).status.code(), Some(1));
+        assert_eq!(run(&mock).status.code(), Some(1));
+
+        let empty = MockTypst::default();
+        assert!(run(&empty).status.success());
+        assert_eq!(empty.commands(), [Vec::<std::ffi::OsString>::new()]);
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde_xml_rs::from_str;
+use xml::{
+    attribute::OwnedAttribute,
+    common::{Position, TextPosition},
+    reader::{ParserConfig, XmlEvent},
+};
+
+use crate::{error::Error, svg_types::*};
+
+/// An open element while the document is read.
+struct Frame {
+    tag: String,
+    path: String,
+    position: TextPosition,
+    /// Number of children seen so far, per tag, for the indices in the paths
+    children: HashMap<String, usize>,
+    /// Whether the element is, or is inside, an alternative of a `switch` kept by its name only,
+    /// whose content is not checked
+    unchecked: bool,
+}
+
+/// Finds the element of `svg_str` responsible for `error`, which the deserialization of the whole
+/// document reported without saying where.
+///
+/// The document is read again element by element: each element is checked against the elements
+/// its parent may hold, and its attributes are deserialized on their own into the matching type.
+/// The first element failing gives an [`Error::SvgStructure`] with its path, its position and a
+/// more specific cause. `error` is returned unchanged when no element can be blamed.
+pub(crate) fn locate(svg_str: &str, error: serde_xml_rs::Error) -> Error {
+    let mut reader = ParserConfig::new()
+        .trim_whitespace(true)
+        .whitespace_to_characters(true)
+        .create_reader(svg_str.as_bytes());
+    let mut stack: Vec<Frame> = Vec::new();
+    loop {
+        let event = reader.next();
+        let position = reader.position();
+        match event {
+            Ok(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => {
+                let tag = name.local_name;
+                let path = match stack.last_mut() {
+                    Some(parent) => {
+                        let index = parent.children.entry(tag.clone()).or_default();
+                        *index += 1;
+                        format!("{} > {}[{}]", parent.path, tag, *index - 1)
+                    }
+                    None => tag.clone(),
+                };
+                let parent = stack.last().map(|parent| parent.tag.as_str());
+                let unchecked = stack.last().is_some_and(|frame| {
+                    frame.unchecked
+                        || frame.tag == "switch" && !allowed_children(parent).contains(&&*tag)
+                });
+                if !unchecked {
+                    if let Err(source) = check(parent, &tag, &attributes) {
+                        return structure_error(path, position, source);
+                    }
+                }
+                stack.push(Frame {
+                    tag,
+                    path,
+                    position,
+                    children: HashMap::new(),
+                    unchecked,
+                });
+            }
+            Ok(XmlEvent::EndElement { .. }) => {
+                let Some(frame) = stack.pop() else { break };
+                let children: usize = frame.children.values().sum();
+                if frame.tag == "symbol" && children != 1 && !frame.unchecked {
+                    let source = serde_xml_rs::Error::Custom {
+                        field: format!(
+                            "a symbol holds exactly one path or image, found {} elements",
+                            children
+                        ),
+                    };
+                    return structure_error(frame.path, frame.position, source);
+                }
+            }
+            Ok(XmlEvent::EndDocument) => break,
+            Ok(_) => {}
+            Err(source) => {
+                let path = stack
+                    .last()
+                    .map_or_else(|| "svg".to_string(), |frame| frame.path.clone());
+                let position = source.position();
+                return structure_error(path, position, serde_xml_rs::Error::Syntax { source });
+            }
+        }
+    }
+    Error::svg_parse("the document", error)
+}
+
+fn structure_error(path: String, position: TextPosition, source: serde_xml_rs::Error) -> Error {
+    Error::SvgStructure {
+        path,
+        line: position.row + 1,
+        column: position.column + 1,
+        source,
+    }
+}
+
+/// The tags of the elements that the parsed tree can hold in `parent`, or at the root for `None`.
+pub(crate) fn allowed_children(parent: Option<&str>) -> &'static [&'static str] {
+    match parent {
+        None => &["svg"],
+        Some("svg") => &["path", "g", "defs", "rect"],
+        Some("g" | "switch") => &["g", "use", "path", "image", "text", "switch"],
+        Some("text") => &["tspan"],
+        Some("defs") => &[
+            "symbol",
+            "linearGradient",
+            "radialGradient",
+            "clipPath",
+            "mask",
+            "filter",
+        ],
+        Some("linearGradient" | "radialGradient") => &["stop"],
+        Some("symbol") => &["path", "image"],
+        Some("clipPath" | "mask") => &["path"],
+        Some("filter") => &[
+            "feGaussianBlur",
+            "feOffset",
+            "feColorMatrix",
+            "feDropShadow",
+        ],
+        Some(_) => &[],
+    }
+}
+
+/// Checks that `tag` may appear in `parent` and that its attributes deserialize.
+fn check(
+    parent: Option<&str>,
+    tag: &str,
+    attributes: &[OwnedAttribute],
+) -> Result<(), serde_xml_rs::Error> {
+    if !allowed_children(parent).contains(&tag) {
+        return Err(serde_xml_rs::Error::Custom {
+            field: match parent {
+                Some(parent) => format!("unexpected element `{}` in `{}`", tag, parent),
+                None => format!("unexpected root element `{}`", tag),
+            },
+        });
+    }
+
+    // The element alone, with its attributes but without its children
+    let mut element = format!("<{}", tag);
+    for attribute in attributes {
+        element.push_str(&format!(
+            " {}=\"{}\"",
+            attribute.name.local_name,
+            attribute
+                .value
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('"', "&quot;")
+        ));
+    }
+    match tag {
+        // The single child of a symbol is checked on its own
+        "symbol" => element.push_str("><path d=\"\"/></symbol>"),
+        _ => element.push_str("/>"),
+    }
+    match tag {
+        "svg" => deserializes::<Svg>(&element),
+        "g" => deserializes::<G>(&element),
+        "defs" => deserializes::<Defs>(&element),
+        "symbol" => deserializes::<Symbol>(&element),
+        "path" => deserializes::<Path>(&element),
+        "use" => deserializes::<Use>(&element),
+        "image" => deserializes::<Image>(&element),
+        "text" => deserializes::<Text>(&element),
+        "tspan" => deserializes::<Tspan>(&element),
+        "switch" => deserializes::<Switch>(&element),
+        "linearGradient" => deserializes::<LinearGradient>(&element),
+        "radialGradient" => deserializes::<RadialGradient>(&element),
+        "stop" => deserializes::<Stop>(&element),
+        "clipPath" => deserializes::<ClipPath>(&element),
+        "mask" => deserializes::<Mask>(&element),
+        "filter" => deserializes::<Filter>(&element),
+        "feGaussianBlur" => deserializes::<GaussianBlur>(&element),
+        "feOffset" => deserializes::<Offset>(&element),
+        "feColorMatrix" => deserializes::<ColorMatrix>(&element),
+        "feDropShadow" => deserializes::<DropShadow>(&element),
+        _ => deserializes::<Rect>(&element),
+    }
+}
+
+fn deserializes<T: DeserializeOwned>(element: &str) -> Result<(), serde_xml_rs::Error> {
+    from_str::<T>(element).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::Error, parse_svg};
+    use std::fs;
+
+    #[test]
+    fn locate_test() {
+        let error = parse_svg(&fs::read_to_string("./test/broken.svg").unwrap()).unwrap_err();
+        let Error::SvgStructure { path, line, .. } = &error else {
+            panic!("expected a located error, got {:?}", error)
+        };
+        assert_eq!(path, "svg > g[1] > g[0] > use[2]");
+        assert_eq!(*line, 10);
+        let message = error.to_string();
+        assert!(message.contains("svg > g[1] > g[0] > use[2]"));
+        assert!(message.contains("missing field `x`"));
+    }
+
+    #[test]
+    fn unexpected_element_test() {
+        let error = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'>\
+             <g><path d='M 0 0'/><circle r='1'/></g></svg>",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid SVG element svg > g[0] > circle[0] at line 1, column 87: \
+             custom: unexpected element `circle` in `g`"
+        );
+    }
+
+    #[test]
+    fn switch_test() {
+        // The content of an alternative kept by its name is not checked, the others are
+        let error = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'>\
+             <g><switch><foreignObject><circle r='1'/></foreignObject>\
+             <g><use href='#a'/></g></switch></g></svg>",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::SvgStructure { path, .. } if path == "svg > g[0] > switch[0] > g[0] > use[0]"
+        ));
+    }
+
+    #[test]
+    fn symbol_test() {
+        let error = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'>\n\
+             <defs id='glyph'>\n<symbol id='glyph0' overflow='visible'></symbol>\n</defs>\n</svg>",
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::SvgStructure { path, line: 3, .. } if path == "svg > defs[0] > symbol[0]"
+        ));
+    }
+}
@@ -1,21 +1,366 @@
-use std::io;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    time::Duration,
+};
+
+use crate::{limits::LimitKind, svg_types::path_data::PathDataError};
 
 /// Custom error type `Error` representing possible errors during I/O operations and type conversions.
 ///
 /// This enum includes the following variants:
 ///
-/// - `Io`: Encapsulates an [`io::Error`], indicating an I/O operation error.
-/// - `Convert`: Encapsulates a [`ConvertError`], indicating a type conversion error.
+/// - `Io`: Encapsulates an [`io::Error`], with the path of the file or directory involved when there is one.
+/// - `TypstNotFound`: The Typst CLI cannot be started because it is not on the `PATH`.
+/// - `TypstFailed`: The Typst CLI ran but reported errors, which are kept in `stderr`.
+/// - `TypstTimeout`: The Typst CLI did not finish within [`CompileOptions::timeout`](crate::options::CompileOptions).
+/// - `RetriesExhausted`: The Typst CLI could not be started in the attempts allowed by [`CompileOptions::retries`](crate::options::CompileOptions); gives the attempts and the last error.
+/// - `InvalidOutputPath`: The output path given to the Typst CLI cannot be written; gives the reason.
+/// - `MissingOutput`: The Typst CLI exited successfully without writing the output file.
+/// - `SvgParse`: An SVG is not well-formed or does not have the layout of the Typst output; says what was being parsed.
+/// - `JsonError`: JSON given to [`svg_from_json`](crate::svg_from_json) is invalid or does not follow the schema.
+/// - `UnresolvedReference`: A `use` element points at an id that no symbol carries.
+/// - `DuplicateIds`: Several elements of an SVG carry the same `id`; lists the ids.
+/// - `InvalidImage`: An image embedded as a `data:` URI cannot be decoded; names the element.
+/// - `Rasterize`: An SVG cannot be rasterized with `svg_to_png` (`raster` feature); gives the reason.
+/// - `SvgStructure`: An element of an SVG cannot be parsed; names the element by its path from the root, with its position.
+/// - `TooDeep`: The elements of an SVG are nested deeper than the maximum depth allowed.
+/// - `LimitExceeded`: An SVG exceeds one of the [`ParseLimits`](crate::limits::ParseLimits) checked before parsing.
+/// - `UnsupportedEncoding`: The XML declaration of an SVG names an encoding other than UTF-8.
+/// - `UnknownEntity`: An SVG uses an entity that XML does not predefine; names the entity.
+/// - `InvalidColor`: A paint value is not a color this crate can parse.
+/// - `InvalidViewBox`: The `viewBox` of an SVG is not four numbers; gives the reason.
+/// - `InvalidMath`: A math expression given to [`typst_math_to_rsx`](crate::typst_math_to_rsx) would leave math mode; gives the reason.
+/// - `InvalidTransform`: A transform list cannot be parsed; gives the reason.
+/// - `InvalidPathData`: Path data cannot be parsed; gives the position and reason.
+/// - `InFile`: Parsing an SVG file failed; names the file.
+/// - `DuplicateName`: Two documents under [`build::compile_dir`](crate::build::compile_dir) would generate functions with the same name; names the first.
+/// - `BuildError`: A document under [`build::compile_dir`](crate::build::compile_dir) failed; names the file.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm. The accessors
+/// such as [`Error::path`] and [`Error::stderr`] give the context shared by several variants.
 ///
 /// [`io::Error`]: https://doc.rust-lang.org/std/io/struct.Error.html
-/// [`ConvertError`]: crate::ConvertError
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
-    /// SVG parsing error.
-    #[error("SVG parsing error: {0}")]
-    SvgParseError(#[from] serde_xml_rs::Error),
+    /// An I/O operation failed, on `path` when it concerns a file or directory.
+    #[error(
+        "I/O error{}: {source}",
+        path.as_ref().map(|path| format!(" on {}", path.display())).unwrap_or_default()
+    )]
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+
+    /// The `typst` executable cannot be found.
+    #[error("Typst CLI not found: install it and make sure `typst` is on the PATH")]
+    TypstNotFound,
+
+    /// The Typst CLI exited unsuccessfully; `stderr` holds its diagnostics.
+    #[error("Typst compilation failed ({status}):\n{stderr}")]
+    TypstFailed { status: ExitStatus, stderr: String },
+
+    /// The Typst CLI was stopped after running for `after`.
+    #[error("Typst compilation did not finish within {after:?}")]
+    TypstTimeout { after: Duration },
+
+    /// The Typst CLI could not be started in `attempts` tries, the last one failing with `source`.
+    #[error("Typst CLI could not be started in {attempts} attempts: {source}")]
+    RetriesExhausted { attempts: u32, source: Box<Error> },
+
+    /// The output path cannot hold the compiled file.
+    #[error("Invalid output path {}: {reason}", path.display())]
+    InvalidOutputPath { path: PathBuf, reason: String },
+
+    /// The Typst CLI reported success but the output file does not exist.
+    #[error("Typst did not write the output file {}", path.display())]
+    MissingOutput { path: PathBuf },
+
+    /// SVG parsing error; `context` says what was being parsed, such as `"the document"`.
+    #[error("Cannot parse {context} as SVG: {source}")]
+    SvgParse {
+        context: String,
+        source: serde_xml_rs::Error,
+    },
+
+    /// An element of the SVG cannot be parsed.
+    ///
+    /// `path` designates the element from the root, each step giving the tag and the index among
+    /// the siblings with the same tag (`svg > g[3] > use[12]`); `line` and `column` are 1-based.
+    #[error("Invalid SVG element {path} at line {line}, column {column}: {source}")]
+    SvgStructure {
+        path: String,
+        line: u64,
+        column: u64,
+        source: serde_xml_rs::Error,
+    },
+
+    /// JSON parsing error.
+    #[error("JSON parsing error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    /// A reference does not match any element of the document.
+    #[error("Reference {href} does not match any symbol")]
+    UnresolvedReference { href: String },
+
+    /// Several elements carry each of the `ids`, in the order of their first duplicate.
+    #[error("Duplicate ids in the SVG: {}", ids.join(", "))]
+    DuplicateIds { ids: Vec<String> },
+
+    /// An embedded image cannot be extracted.
+    #[error("Invalid embedded image in {element}: {reason}")]
+    InvalidImage { element: String, reason: String },
+
+    /// An SVG cannot be rasterized to PNG.
+    #[error("Cannot rasterize the SVG: {reason}")]
+    Rasterize { reason: String },
+
+    /// The elements of the SVG are nested deeper than the given maximum.
+    #[error("SVG elements are nested deeper than {0} levels")]
+    TooDeep(usize),
+
+    /// The SVG exceeds one of the limits checked before parsing.
+    #[error("SVG exceeds the limit on the {kind}: {actual} > {limit}")]
+    LimitExceeded {
+        kind: LimitKind,
+        limit: usize,
+        actual: usize,
+    },
+
+    /// The SVG declares an encoding that cannot be parsed.
+    #[error("Unsupported SVG encoding {0}, expected UTF-8")]
+    UnsupportedEncoding(String),
+
+    /// The SVG uses an entity that is not one of the five predefined by XML.
+    #[error("Unknown XML entity &{entity};")]
+    UnknownEntity { entity: String },
+
+    /// A paint value is not a color, such as a gradient reference.
+    #[error("Invalid color {0:?}")]
+    InvalidColor(String),
+
+    /// The `viewBox` attribute cannot be parsed.
+    #[error("Invalid viewBox {view_box:?}: {reason}")]
+    InvalidViewBox { view_box: String, reason: String },
+
+    /// A math expression cannot be wrapped in a formula as it is.
+    #[error("Invalid math expression {expr:?}: {reason}")]
+    InvalidMath { expr: String, reason: String },
+
+    /// A transform list does not follow the SVG grammar.
+    #[error("Invalid transform {transform:?}: {reason}")]
+    InvalidTransform { transform: String, reason: String },
+
+    /// Path data does not follow the SVG grammar.
+    #[error(transparent)]
+    InvalidPathData(#[from] PathDataError),
+
+    /// An SVG file cannot be parsed.
+    #[error("{}: {source}", path.display())]
+    InFile { path: PathBuf, source: Box<Error> },
+
+    /// The function generated for a document has the name of the one generated for `first`.
+    #[error("The function {name} is already generated for {}", first.display())]
+    DuplicateName { name: String, first: PathBuf },
+
+    /// Precompiling a document in a build script failed.
+    #[error("Failed to build {}: {source}", file.display())]
+    BuildError { file: PathBuf, source: Box<Error> },
+}
+
+impl Error {
+    /// An [`Error::Io`] on `path`.
+    pub(crate) fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Io {
+            path: Some(path.into()),
+            source,
+        }
+    }
+
+    /// An [`Error::SvgParse`] raised while parsing `context`.
+    pub(crate) fn svg_parse(context: &str, source: serde_xml_rs::Error) -> Self {
+        Error::SvgParse {
+            context: context.to_string(),
+            source,
+        }
+    }
+
+    /// Whether the same operation may succeed if tried again.
+    ///
+    /// This holds for a timeout and for the I/O errors caused by a busy system (interrupted calls,
+    /// resources temporarily unavailable, an executable being written), not for errors in the
+    /// document or its compilation, which fail the same way every time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use typst_2_rsx::error::Error;
+    ///
+    /// assert!(Error::TypstTimeout { after: Duration::from_secs(30) }.is_retryable());
+    /// assert!(!Error::TypstNotFound.is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::TypstTimeout { .. } => true,
+            Error::Io { source, .. } => matches!(
+                source.kind(),
+                io::ErrorKind::Interrupted
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::ResourceBusy
+                    | io::ErrorKind::ExecutableFileBusy
+            ),
+            Error::InFile { source, .. }
+            | Error::BuildError { file: _, source }
+            | Error::RetriesExhausted { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// The file or directory the error is about, if any: the file read or written, the output path
+    /// of the compilation, or the file named by [`Error::InFile`] and [`Error::BuildError`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    /// use typst_2_rsx::parse_svg_file;
+    ///
+    /// let error = parse_svg_file("missing.svg").unwrap_err();
+    /// assert_eq!(error.path(), Some(Path::new("missing.svg")));
+    /// ```
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Io { path, .. } => path.as_deref(),
+            Error::InvalidOutputPath { path, .. }
+            | Error::MissingOutput { path }
+            | Error::InFile { path, .. } => Some(path),
+            Error::BuildError { file, .. } => Some(file),
+            _ => None,
+        }
+    }
+
+    /// The diagnostics printed by the Typst CLI, for an [`Error::TypstFailed`] possibly wrapped in
+    /// an [`Error::BuildError`].
+    pub fn stderr(&self) -> Option<&str> {
+        match self {
+            Error::TypstFailed { stderr, .. } => Some(stderr),
+            Error::InFile { source, .. } | Error::BuildError { file: _, source } => source.stderr(),
+            _ => None,
+        }
+    }
+
+    /// The exit status of the Typst CLI, for an [`Error::TypstFailed`] possibly wrapped in an
+    /// [`Error::BuildError`].
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        match self {
+            Error::TypstFailed { status, .. } => Some(*status),
+            Error::InFile { source, .. } | Error::BuildError { file: _, source } => {
+                source.exit_status()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        let not_found = || io::Error::new(io::ErrorKind::NotFound, "not found");
+        assert_eq!(
+            Error::io("figures/a.svg", not_found()).to_string(),
+            "I/O error on figures/a.svg: not found"
+        );
+        assert_eq!(
+            Error::Io {
+                path: None,
+                source: not_found()
+            }
+            .to_string(),
+            "I/O error: not found"
+        );
+        assert_eq!(
+            Error::TypstNotFound.to_string(),
+            "Typst CLI not found: install it and make sure `typst` is on the PATH"
+        );
+        assert_eq!(
+            Error::TypstTimeout {
+                after: Duration::from_millis(1500)
+            }
+            .to_string(),
+            "Typst compilation did not finish within 1.5s"
+        );
+        assert_eq!(
+            Error::InvalidOutputPath {
+                path: "out".into(),
+                reason: "a directory exists at this path".to_string()
+            }
+            .to_string(),
+            "Invalid output path out: a directory exists at this path"
+        );
+        assert_eq!(
+            Error::svg_parse(
+                "the document",
+                serde_xml_rs::Error::Custom {
+                    field: "missing field `class`".to_string()
+                }
+            )
+            .to_string(),
+            "Cannot parse the document as SVG: custom: missing field `class`"
+        );
+        assert_eq!(
+            Error::BuildError {
+                file: "docs/a.typ".into(),
+                source: Box::new(Error::TypstNotFound)
+            }
+            .to_string(),
+            "Failed to build docs/a.typ: Typst CLI not found: install it and make sure `typst` is \
+             on the PATH"
+        );
+        assert_eq!(
+            Error::DuplicateName {
+                name: "figure_1".to_string(),
+                first: "docs/figure-1.typ".into()
+            }
+            .to_string(),
+            "The function figure_1 is already generated for docs/figure-1.typ"
+        );
+    }
+
+    #[test]
+    fn accessors_test() {
+        let busy = Error::io("a.svg", io::Error::from(io::ErrorKind::ResourceBusy));
+        assert!(busy.is_retryable());
+        assert_eq!(busy.path(), Some(Path::new("a.svg")));
+        assert!(!Error::io("a.svg", io::Error::from(io::ErrorKind::NotFound)).is_retryable());
 
-    /// Typst compilation error.
-    #[error("Typst compile error: {0}")]
-    TypstCompileError(#[from] io::Error),
+        let build = Error::BuildError {
+            file: "a.typ".into(),
+            source: Box::new(Error::TypstTimeout {
+                after: Duration::from_secs(1),
+            }),
+        };
+        assert!(build.is_retryable());
+        let exhausted = Error::RetriesExhausted {
+            attempts: 3,
+            source: Box::new(Error::Io {
+                path: None,
+                source: io::Error::from(io::ErrorKind::ExecutableFileBusy),
+            }),
+        };
+        assert!(exhausted.is_retryable());
+        assert!(exhausted
+            .to_string()
+            .starts_with("Typst CLI could not be started in 3 attempts: I/O error: "));
+        assert_eq!(build.path(), Some(Path::new("a.typ")));
+        assert_eq!(build.stderr(), None);
+        assert_eq!(Error::TooDeep(3).path(), None);
+    }
 }
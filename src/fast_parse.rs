@@ -0,0 +1,398 @@
+use quick_xml::{
+    events::{BytesStart, Event},
+    name::ResolveResult,
+    NsReader,
+};
+
+use crate::svg_types::{intern::intern, *};
+
+/// Builds the [`Svg`] tree of `svg_str` with the pull parser of `quick-xml`, without going through
+/// serde.
+///
+/// The elements are dispatched by hand and their attributes are read straight into the fields,
+/// following what the `serde-xml-rs` deserialization accepts: attributes and tags are matched by
+/// their local name, unknown attributes, comments and processing instructions are skipped, and an
+/// empty `g` has no elements.
+///
+/// # Return value
+///
+/// - `Some(Svg)` : the same tree as the one deserialized by `serde-xml-rs`.
+/// - `None` : the document is malformed, or holds something this reader does not handle (an
+///   unknown element, a missing attribute, text or a child inside a `path`...). The caller parses
+///   it again with `serde-xml-rs`, which reports the error, so both parsers always agree.
+pub(crate) fn parse(svg_str: &str) -> Option<Svg> {
+    let mut parser = Parser {
+        reader: NsReader::from_str(svg_str),
+    };
+    let svg = loop {
+        match parser.reader.read_event().ok()? {
+            Event::Start(start) => break parser.svg(&start, false)?,
+            Event::Empty(start) => break parser.svg(&start, true)?,
+            Event::Decl(_) | Event::DocType(_) | Event::Comment(_) | Event::PI(_) => {}
+            Event::Text(text) if is_blank(&text) => {}
+            _ => return None,
+        }
+    };
+    loop {
+        match parser.reader.read_event().ok()? {
+            Event::Eof => return Some(svg),
+            Event::Comment(_) | Event::PI(_) => {}
+            Event::Text(text) if is_blank(&text) => {}
+            _ => return None,
+        }
+    }
+}
+
+struct Parser<'i> {
+    reader: NsReader<&'i [u8]>,
+}
+
+impl<'i> Parser<'i> {
+    /// Calls `f` with the local name and the decoded value of each attribute of `start`, stopping
+    /// at the first `None`.
+    fn attributes(
+        &self,
+        start: &BytesStart,
+        mut f: impl FnMut(&[u8], String) -> Option<()>,
+    ) -> Option<()> {
+        if let (ResolveResult::Unknown(_), _) = self.reader.resolve_element(start.name()) {
+            return None;
+        }
+        for attribute in start.attributes() {
+            let attribute = attribute.ok()?;
+            if attribute.key.as_namespace_binding().is_some() {
+                continue;
+            }
+            let (namespace, name) = self.reader.resolve_attribute(attribute.key);
+            if let ResolveResult::Unknown(_) = namespace {
+                return None;
+            }
+            f(name.as_ref(), attribute.unescape_value().ok()?.into_owned())?;
+        }
+        Some(())
+    }
+
+    /// Reads the children of the element that has just started, up to its end, with `child`
+    /// called for each of them along with whether it is self-closing.
+    fn children<T>(
+        &mut self,
+        mut child: impl FnMut(&mut Self, &BytesStart, bool) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let mut children = Vec::new();
+        loop {
+            match self.reader.read_event().ok()? {
+                Event::Start(start) => children.push(child(self, &start, false)?),
+                Event::Empty(start) => children.push(child(self, &start, true)?),
+                Event::End(_) => return Some(children),
+                Event::Comment(_) | Event::PI(_) => {}
+                Event::Text(text) if is_blank(&text) => {}
+                _ => return None,
+            }
+        }
+    }
+
+    /// Skips to the end of an element without children, such as a `path`.
+    fn leaf(&mut self, empty: bool) -> Option<()> {
+        if !empty {
+            self.children(|_, _, _| None::<()>)?;
+        }
+        Some(())
+    }
+
+    fn svg(&mut self, start: &BytesStart, empty: bool) -> Option<Svg> {
+        let (mut class, mut width, mut height, mut view_box) = (None, None, None, None);
+        self.attributes(start, |name, value| match name {
+            b"class" => set(&mut class, value),
+            b"width" => set(&mut width, value),
+            b"height" => set(&mut height, value),
+            b"viewBox" => set(&mut view_box, value),
+            _ => Some(()),
+        })?;
+        let elements = if empty {
+            Vec::new()
+        } else {
+            self.children(|parser, start, empty| match start.local_name().as_ref() {
+                b"path" => parser
+                    .path(start, empty)
+                    .map(Box::new)
+                    .map(SvgElement::Path),
+                b"g" => parser.g(start, empty).map(Box::new).map(SvgElement::G),
+                b"defs" => parser.defs(start, empty).map(SvgElement::Defs),
+                b"rect" => parser.rect(start, empty).map(SvgElement::Rect),
+                _ => None,
+            })?
+        };
+        Some(Svg {
+            class: class?,
+            width: width?,
+            height: height?,
+            view_box: view_box?,
+            elements,
+        })
+    }
+
+    fn path(&mut self, start: &BytesStart, empty: bool) -> Option<Path> {
+        let mut d = None;
+        let mut path = Path::default();
+        self.attributes(start, |name, value| match name {
+            b"d" => set(&mut d, value),
+            b"id" => set(&mut path.id, value),
+            b"class" => set(&mut path.class, intern(&value)),
+            b"fill" => set(&mut path.fill, intern(&value)),
+            b"stroke" => set(&mut path.stroke, intern(&value)),
+            b"fill-rule" => set(&mut path.fill_rule, value.into()),
+            b"stroke-width" => set(&mut path.stroke_width, intern(&value)),
+            b"stroke-linecap" => set(&mut path.stroke_linecap, value.into()),
+            b"stroke-linejoin" => set(&mut path.stroke_linejoin, value.into()),
+            b"stroke-miterlimit" => set(&mut path.stroke_miterlimit, value),
+            b"tabindex" => set(&mut path.tabindex, value),
+            b"pointer-events" => set(&mut path.pointer_events, value),
+            b"opacity" => set(&mut path.opacity, value),
+            b"shape-rendering" => set(&mut path.shape_rendering, value),
+            _ => Some(()),
+        })?;
+        self.leaf(empty)?;
+        path.d = d.unwrap_or_default();
+        Some(path)
+    }
+
+    fn g(&mut self, start: &BytesStart, empty: bool) -> Option<G> {
+        let mut g = G::default();
+        self.attributes(start, |name, value| match name {
+            b"id" => set(&mut g.id, value),
+            b"class" => set(&mut g.class, intern(&value)),
+            b"transform" => set(&mut g.transform, value),
+            b"clip-path" => set(&mut g.clip_path, intern(&value)),
+            b"mask" => set(&mut g.mask, intern(&value)),
+            b"filter" => set(&mut g.filter, intern(&value)),
+            b"tabindex" => set(&mut g.tabindex, value),
+            b"pointer-events" => set(&mut g.pointer_events, value),
+            b"shape-rendering" => set(&mut g.shape_rendering, value),
+            b"text-rendering" => set(&mut g.text_rendering, value),
+            _ => Some(()),
+        })?;
+        if !empty {
+            let elements =
+                self.children(|parser, start, empty| match start.local_name().as_ref() {
+                    b"g" => parser.g(start, empty).map(Box::new).map(GEle::G),
+                    b"use" => parser.uuse(start, empty).map(Box::new).map(GEle::Use),
+                    b"path" => parser.path(start, empty).map(Box::new).map(GEle::Path),
+                    b"image" => parser.image(start, empty).map(Box::new).map(GEle::Image),
+                    _ => None,
+                })?;
+            g.elements = Some(elements).filter(|elements| !elements.is_empty());
+        }
+        Some(g)
+    }
+
+    fn uuse(&mut self, start: &BytesStart, empty: bool) -> Option<Use> {
+        let (mut fill, mut x, mut width, mut height) = (None, None, None, None);
+        let (mut fill_rule, mut href, mut transform) = (None, None, None);
+        self.attributes(start, |name, value| match name {
+            b"fill" => set(&mut fill, intern(&value)),
+            b"x" => set(&mut x, value),
+            b"width" => set(&mut width, value),
+            b"height" => set(&mut height, value),
+            b"fill-rule" => set(&mut fill_rule, value.into()),
+            b"href" => set(&mut href, intern(&value)),
+            b"transform" => set(&mut transform, value),
+            _ => Some(()),
+        })?;
+        self.leaf(empty)?;
+        Some(Use {
+            fill,
+            x: x?,
+            width,
+            height,
+            fill_rule,
+            href: href.unwrap_or_default(),
+            transform,
+        })
+    }
+
+    fn image(&mut self, start: &BytesStart, empty: bool) -> Option<Image> {
+        let (mut width, mut height, mut preserve_aspect_ratio, mut href, mut transform) =
+            (None, None, None, None, None);
+        self.attributes(start, |name, value| match name {
+            b"width" => set(&mut width, value),
+            b"height" => set(&mut height, value),
+            b"preserveAspectRatio" => set(&mut preserve_aspect_ratio, value),
+            b"href" => set(&mut href, value),
+            b"transform" => set(&mut transform, value),
+            _ => Some(()),
+        })?;
+        self.leaf(empty)?;
+        Some(Image {
+            width: width?,
+            height: height?,
+            preserve_aspect_ratio: preserve_aspect_ratio?,
+            href: href.unwrap_or_default(),
+            transform,
+        })
+    }
+
+    fn rect(&mut self, start: &BytesStart, empty: bool) -> Option<Rect> {
+        let (mut x, mut y, mut width, mut height, mut fill) = (None, None, None, None, None);
+        self.attributes(start, |name, value| match name {
+            b"x" => set(&mut x, value),
+            b"y" => set(&mut y, value),
+            b"width" => set(&mut width, value),
+            b"height" => set(&mut height, value),
+            b"fill" => set(&mut fill, intern(&value)),
+            _ => Some(()),
+        })?;
+        self.leaf(empty)?;
+        Some(Rect {
+            x: x?,
+            y: y?,
+            width: width?,
+            height: height?,
+            fill,
+        })
+    }
+
+    fn defs(&mut self, start: &BytesStart, empty: bool) -> Option<Defs> {
+        let mut id = None;
+        self.attributes(start, |name, value| match name {
+            b"id" => set(&mut id, value),
+            _ => Some(()),
+        })?;
+        let elements = if empty {
+            Vec::new()
+        } else {
+            self.children(|parser, start, empty| match start.local_name().as_ref() {
+                b"symbol" if !empty => parser.symbol(start),
+                _ => None,
+            })?
+        };
+        Some(Defs {
+            id: id?,
+            elements,
+            definitions: Vec::new(),
+        })
+    }
+
+    fn symbol(&mut self, start: &BytesStart) -> Option<Symbol> {
+        let (mut id, mut overflow, mut view_box, mut preserve_aspect_ratio) =
+            (None, None, None, None);
+        self.attributes(start, |name, value| match name {
+            b"id" => set(&mut id, value),
+            b"overflow" => set(&mut overflow, value),
+            b"viewBox" => set(&mut view_box, value),
+            b"preserveAspectRatio" => set(&mut preserve_aspect_ratio, value),
+            _ => Some(()),
+        })?;
+        let mut elements =
+            self.children(|parser, start, empty| match start.local_name().as_ref() {
+                b"path" => parser.path(start, empty).map(SymbolEle::Path),
+                b"image" => parser.image(start, empty).map(SymbolEle::Image),
+                _ => None,
+            })?;
+        // A symbol holds exactly one element
+        let element = elements.pop().filter(|_| elements.is_empty())?;
+        Some(Symbol {
+            id: id.unwrap_or_default(),
+            overflow: overflow?,
+            view_box,
+            preserve_aspect_ratio,
+            element,
+        })
+    }
+}
+
+/// Sets an attribute read for the first time; a repeated attribute is an error.
+fn set<T>(field: &mut Option<T>, value: T) -> Option<()> {
+    match field {
+        Some(_) => None,
+        None => {
+            *field = Some(value);
+            Some(())
+        }
+    }
+}
+
+fn is_blank(text: &[u8]) -> bool {
+    text.iter().all(u8::is_ascii_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use serde_xml_rs::from_str;
+    use std::fs;
+
+    /// The trees built by both parsers, or `None` for a document they do not accept.
+    fn both(svg_str: &str) -> (Option<Svg>, Option<Svg>) {
+        (parse(svg_str), from_str::<Svg>(svg_str).ok())
+    }
+
+    #[test]
+    fn fixtures_test() {
+        for file in ["expected.svg", "formula.svg", "bom.svg", "degenerate.svg"] {
+            let svg_str = fs::read_to_string(format!("./test/{}", file)).unwrap();
+            let svg_str = svg_str.trim_start_matches('\u{feff}');
+            let (fast, serde) = both(svg_str);
+            assert!(fast.is_some(), "{}", file);
+            assert_eq!(fast, serde, "{}", file);
+        }
+    }
+
+    #[test]
+    fn same_tree_test() {
+        let header = "<svg class='a' viewBox='v' width='w' height='h' \
+                      xmlns='http://www.w3.org/2000/svg' xmlns:xlink='http://www.w3.org/1999/xlink'>";
+        for body in [
+            "",
+            "<g></g><g/><g> </g><defs id='x'/><defs id='y'> </defs>",
+            "<g><g/></g><g><!-- comment --><?pi?><use x='1' xlink:href='#b'/></g>",
+            "<path d='M\n0\t0' fill='&amp;&lt;&#x41;' stroke=''/><path d='M'> </path>",
+            "<path d='M' fill-rule='evenod' stroke-linecap='round' unknown='1'/>",
+            "<rect x='0' y='0' width='1' height='1' fill='white'/>",
+            "<defs id='d'><symbol id='s' overflow='o'> <path d='M'/> </symbol></defs>",
+            "<g class='x'><image width='1' height='2' preserveAspectRatio='none' href='data:x'/></g>",
+            "<g clip-path='url(#c)' mask='url(#m)' filter='url(#f)'><path d='M'/></g>",
+            "<path fill='none'/><g><use x='1'/><image width='1' height='2' preserveAspectRatio='n'/></g>",
+            "<defs id='d'><symbol overflow='o'><path/></symbol></defs>",
+            "<defs id='d'><symbol id='s' overflow='o' viewBox='0 0 1 1' preserveAspectRatio='none'><path d='M'/></symbol></defs><g><use x='0' width='2' height='3' xlink:href='#s'/></g>",
+            "<svg:g xmlns:svg='http://www.w3.org/2000/svg'/>",
+        ] {
+            let svg_str = format!("{}{}</svg>", header, body);
+            let (fast, serde) = both(&svg_str);
+            assert!(fast.is_some(), "{}", body);
+            assert_eq!(fast, serde, "{}", body);
+        }
+    }
+
+    #[test]
+    fn fallback_test() {
+        let header = "<svg class='a' viewBox='v' width='w' height='h'>";
+        for body in [
+            // Rejected by both parsers
+            "<circle r='1'/>",
+            "<g>text</g>",
+            "<g id='a' id='b'/>",
+            "<g><use x='1' href='#a' xlink:href='#b' xmlns:xlink='http://www.w3.org/1999/xlink'/></g>",
+            "<g><use x='1' xlink:href='#b'/></g>",
+            "<defs id='d'><symbol id='s' overflow='o'><path d='M'/><path d='L'/></symbol></defs>",
+            "<path d='&nbsp;'/>",
+            "<g>",
+            // Accepted by serde-xml-rs only, which the fast parser leaves to it
+            "<g><path d='M'><fill>red</fill></path></g>",
+            "<g><text x='0 5'>ab<tspan font-weight='bold'>c</tspan></text></g>",
+            "<g><switch><foreignObject><p>a</p></foreignObject><path d='M'/></switch></g>",
+            "<defs id='d'><linearGradient id='g'><stop offset='0' stop-color='red'/></linearGradient></defs>",
+            "<defs id='d'><clipPath id='c' clipPathUnits='userSpaceOnUse'><path d='M'/></clipPath></defs>",
+            "<defs id='d'><filter id='f'><feOffset dx='1'/><feGaussianBlur stdDeviation='2'/></filter></defs>",
+        ] {
+            let svg_str = format!("{}{}</svg>", header, body);
+            assert_eq!(parse(&svg_str), None, "{}", body);
+            assert_eq!(
+                parse_svg(&svg_str).ok(),
+                from_str::<Svg>(&svg_str).ok(),
+                "{}",
+                body
+            );
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! How the Typst CLI is run, behind the [`TypstInvoker`] trait.
+//!
+//! The compilation pipeline builds the `typst` command, removes a stale output, checks the exit
+//! status and reads the compiled file; running the command is left to an invoker. The free
+//! functions such as [`typst_to_rsx`](crate::typst_to_rsx) use [`TypstCli`], which starts the
+//! installed CLI. A [`TypstConverter`](crate::TypstConverter) can be given another one, such as
+//! [`MockTypst`] (`test-utils` feature), which answers with canned output where Typst is not
+//! installed.
+
+#[cfg(any(test, feature = "test-utils"))]
+use std::{ffi::OsString, fs, process::ExitStatus, sync::Mutex};
+use std::{
+    io::{self, Read},
+    process::{Command, Output, Stdio},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{error::Error, options::CompileOptions};
+
+/// Runs the commands of the Typst CLI for the compilation pipeline.
+///
+/// # Example
+///
+/// ```rust
+/// use std::process::Command;
+/// use typst_2_rsx::{
+///     error::Error,
+///     invoker::{Invocation, TypstCli, TypstInvoker},
+///     options::CompileOptions,
+/// };
+///
+/// /// Logs every command before running it with the installed CLI.
+/// struct Logged;
+///
+/// impl TypstInvoker for Logged {
+///     fn invoke(&self, command: Command, options: &CompileOptions) -> Result<Invocation, Error> {
+///         eprintln!("running {:?}", command);
+///         TypstCli.invoke(command, options)
+///     }
+/// }
+/// ```
+pub trait TypstInvoker: Send + Sync {
+    /// Runs `command` with its stdout and stderr captured: a `typst compile` whose last argument
+    /// is the output file, or a `typst query`, which may read the document from the stdin set on
+    /// the command.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(Invocation)` : the command ran, whatever its exit status.
+    /// - `Err(Error)` : it could not be started, such as [`Error::TypstNotFound`], or did not
+    ///   finish within [`CompileOptions::timeout`] ([`Error::TypstTimeout`]).
+    fn invoke(&self, command: Command, options: &CompileOptions) -> Result<Invocation, Error>;
+}
+
+impl<T: TypstInvoker + ?Sized> TypstInvoker for Arc<T> {
+    fn invoke(&self, command: Command, options: &CompileOptions) -> Result<Invocation, Error> {
+        T::invoke(self, command, options)
+    }
+}
+
+/// A command run by a [`TypstInvoker`].
+///
+/// # Field
+///
+/// - `output` : the exit status and what the command printed.
+/// - `duration` : from the start of the process to its exit.
+/// - `attempts` : how many times the process was started, the failed attempts included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub output: Output,
+
+    pub duration: Duration,
+
+    pub attempts: u32,
+}
+
+/// The [`TypstInvoker`] starting the installed `typst`, used by default.
+///
+/// The command is killed if it is still running after [`CompileOptions::timeout`]. A CLI that
+/// cannot be started for a transient reason is started again up to [`CompileOptions::retries`]
+/// times, waiting 50 ms before the first retry and twice as long before each next one, up to a
+/// second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TypstCli;
+
+impl TypstInvoker for TypstCli {
+    fn invoke(&self, mut command: Command, options: &CompileOptions) -> Result<Invocation, Error> {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        let mut attempts = 0;
+        let mut backoff = Duration::from_millis(50);
+        let mut child = loop {
+            attempts += 1;
+            match command.spawn() {
+                Ok(child) => break child,
+                Err(error) if is_transient(&error) && attempts <= u32::from(options.retries) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(attempts, %error, "retrying to start typst");
+                    std::thread::sleep(backoff);
+                    backoff = (backoff * 2).min(Duration::from_secs(1));
+                }
+                Err(error) if attempts > 1 => {
+                    return Err(Error::RetriesExhausted {
+                        attempts,
+                        source: Box::new(spawn_error(error)),
+                    })
+                }
+                Err(error) => return Err(spawn_error(error)),
+            }
+        };
+        // Reading the pipes on their own threads keeps the CLI from blocking on a full one
+        let read = |pipe: Option<Box<dyn Read + Send>>| {
+            std::thread::spawn(move || {
+                let mut buffer = Vec::new();
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut buffer);
+                }
+                buffer
+            })
+        };
+        let stdout = read(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+        let stderr = read(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+        let wait_error = |e| Error::Io {
+            path: None,
+            source: e,
+        };
+        let start = Instant::now();
+        let status = match options.timeout {
+            None => child.wait().map_err(wait_error)?,
+            Some(timeout) => loop {
+                if let Some(status) = child.try_wait().map_err(wait_error)? {
+                    break status;
+                }
+                if start.elapsed() >= timeout {
+                    // The reader ends once the pipe closes, it is not waited for
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(Error::TypstTimeout { after: timeout });
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            },
+        };
+        let duration = start.elapsed();
+        let output = Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        };
+        Ok(Invocation {
+            output,
+            duration,
+            attempts,
+        })
+    }
+}
+
+/// Whether starting a process failed for a reason that may be gone on the next try: a busy
+/// system, or an executable still open for writing, as on some network filesystems.
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::ExecutableFileBusy
+    )
+}
+
+/// The error for a Typst CLI that cannot be started: [`Error::TypstNotFound`] when it is not
+/// installed, [`Error::Io`] otherwise.
+fn spawn_error(error: io::Error) -> Error {
+    if error.kind() == io::ErrorKind::NotFound {
+        Error::TypstNotFound
+    } else {
+        Error::Io {
+            path: None,
+            source: error,
+        }
+    }
+}
+
+/// A [`TypstInvoker`] answering with canned responses instead of starting Typst, for testing the
+/// code built on a [`TypstConverter`](crate::TypstConverter) where Typst is not installed.
+///
+/// The responses are given in order, one per command, and the last one is repeated. The
+/// arguments of every command are recorded. Only available in the tests of this crate and with
+/// the `test-utils` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{
+///     error::Error,
+///     invoker::{MockResponse, MockTypst},
+///     TypstConverter,
+/// };
+///
+/// let svg = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///            <path d='M 0 0 L 10 10'/></svg>";
+/// let converter = TypstConverter::new().invoker(MockTypst::new([
+///     MockResponse::svg(svg),
+///     MockResponse::Fail {
+///         code: 1,
+///         stderr: "error: unknown variable: x".to_string(),
+///     },
+/// ]));
+///
+/// assert_eq!(converter.to_svg("figure.typ").unwrap(), svg);
+/// let error = converter.to_svg("figure.typ").unwrap_err();
+/// assert_eq!(error.stderr(), Some("error: unknown variable: x"));
+/// ```
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Default)]
+pub struct MockTypst {
+    responses: Vec<MockResponse>,
+    commands: Mutex<Vec<Vec<OsString>>>,
+}
+
+/// What a [`MockTypst`] does for a command.
+///
+/// # Variant
+///
+/// - `Svg` : exits successfully after writing `svg` to the output file, the last argument of the
+///   command, and printing `stderr`, such as the warnings of Typst.
+/// - `NoOutput` : exits successfully without writing anything.
+/// - `Fail` : exits with `code`, printing the diagnostics in `stderr`.
+/// - `Stdout` : exits successfully after printing the text, like a `typst query`.
+/// - `SpawnError` : cannot be started, with an I/O error of this kind; `NotFound` gives
+///   [`Error::TypstNotFound`].
+/// - `Timeout` : does not finish within [`CompileOptions::timeout`].
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockResponse {
+    Svg { svg: String, stderr: String },
+
+    NoOutput,
+
+    Fail { code: i32, stderr: String },
+
+    Stdout(String),
+
+    SpawnError(io::ErrorKind),
+
+    Timeout,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockResponse {
+    /// A successful compilation to `svg`, without warnings.
+    pub fn svg(svg: impl Into<String>) -> Self {
+        MockResponse::Svg {
+            svg: svg.into(),
+            stderr: String::new(),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockTypst {
+    /// Creates a mock giving `responses` in order, repeating the last one. Without any response,
+    /// every command exits successfully without output.
+    pub fn new(responses: impl IntoIterator<Item = MockResponse>) -> Self {
+        MockTypst {
+            responses: responses.into_iter().collect(),
+            commands: Mutex::default(),
+        }
+    }
+
+    /// The arguments of the commands run so far, without the `typst` program.
+    pub fn commands(&self) -> Vec<Vec<OsString>> {
+        self.commands
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl TypstInvoker for MockTypst {
+    fn invoke(&self, command: Command, options: &CompileOptions) -> Result<Invocation, Error> {
+        let args: Vec<OsString> = command.get_args().map(OsString::from).collect();
+        let response = {
+            let mut commands = self
+                .commands
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let index = commands.len().min(self.responses.len().saturating_sub(1));
+            commands.push(args.clone());
+            self.responses
+                .get(index)
+                .cloned()
+                .unwrap_or(MockResponse::NoOutput)
+        };
+        let (code, stdout, stderr) = match response {
+            MockResponse::Svg { svg, stderr } => {
+                if let Some(output) = args.last() {
+                    fs::write(output, svg).map_err(|e| Error::io(output, e))?;
+                }
+                (0, String::new(), stderr)
+            }
+            MockResponse::NoOutput => (0, String::new(), String::new()),
+            MockResponse::Fail { code, stderr } => (code, String::new(), stderr),
+            MockResponse::Stdout(stdout) => (0, stdout, String::new()),
+            MockResponse::SpawnError(kind) => return Err(spawn_error(kind.into())),
+            MockResponse::Timeout => {
+                return Err(Error::TypstTimeout {
+                    after: options.timeout.unwrap_or_default(),
+                })
+            }
+        };
+        Ok(Invocation {
+            output: Output {
+                status: exit_status(code),
+                stdout: stdout.into_bytes(),
+                stderr: stderr.into_bytes(),
+            },
+            duration: Duration::ZERO,
+            attempts: 1,
+        })
+    }
+}
+
+/// The status of a process exiting with `code`.
+#[cfg(any(test, feature = "test-utils"))]
+fn exit_status(code: i32) -> ExitStatus {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(code << 8)
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatus::from_raw(code as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::utils;
+
+    /// Starting an executable still open for writing fails with `ExecutableFileBusy`.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn retry_test() {
+        use std::{io::Write, os::unix::fs::OpenOptionsExt};
+
+        let dir = utils::temp_file_path("retry");
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("busy");
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o755)
+            .open(&script)
+            .unwrap();
+        file.write_all(b"#!/bin/sh\necho 'warning: busy' >&2\n")
+            .unwrap();
+        let options = |retries| CompileOptions {
+            retries,
+            ..Default::default()
+        };
+
+        let error = TypstCli
+            .invoke(Command::new(&script), &options(0))
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&error, Error::Io { source, .. } if source.kind() == io::ErrorKind::ExecutableFileBusy)
+        );
+        let error = TypstCli
+            .invoke(Command::new(&script), &options(1))
+            .err()
+            .unwrap();
+        assert!(matches!(
+            &error,
+            Error::RetriesExhausted { attempts: 2, .. }
+        ));
+        assert!(error.is_retryable());
+
+        // Closed while waiting to retry
+        let closing = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            drop(file);
+        });
+        let run = TypstCli.invoke(Command::new(&script), &options(5)).unwrap();
+        closing.join().unwrap();
+        assert!(run.output.status.success());
+        assert!(run.attempts >= 2);
+        assert_eq!(run.output.stderr, b"warning: busy\n");
+
+        // Not found is not transient
+        let error = TypstCli
+            .invoke(Command::new(dir.join("missing")), &options(5))
+            .err()
+            .unwrap();
+        assert!(matches!(error, Error::TypstNotFound));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn spawn_error_test() {
+        let not_found = io::Error::from(io::ErrorKind::NotFound);
+        assert!(matches!(spawn_error(not_found), Error::TypstNotFound));
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            spawn_error(denied),
+            Error::Io { path: None, source } if source.kind() == io::ErrorKind::PermissionDenied
+        ));
+    }
+}
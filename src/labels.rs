@@ -0,0 +1,422 @@
+//! Anchors for the labels of a Typst document (`<fig-results>`), for deep-linking into its
+//! rendering.
+//!
+//! Typst does not write its labels into the SVG. [`query_labels`] asks `typst query` where the
+//! labeled figures, headings and equations are laid out, and [`anchor_labels`] gives their label
+//! as `id` to the group drawn at that position, so that `#fig-results` scrolls to the figure.
+//! [`typst_to_rsx_labeled`](crate::typst_to_rsx_labeled) does both.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{ffi::OsString, fs, io, path::Path, process::Command};
+
+#[cfg(not(target_arch = "wasm32"))]
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{error::Error, invoker::TypstInvoker, options::CompileOptions};
+use crate::{
+    passes::geometry::{parse_transform, Matrix},
+    svg_types::*,
+};
+
+/// How far from the box of a group, in user units (`pt` for Typst), a labeled position still
+/// counts as inside it. The position of an element is the top-left corner of its layout box,
+/// which lies on the edge of its ink at best.
+const TOLERANCE: f64 = 1.0;
+
+/// The label of the metadata holding the positions in the wrapper document.
+#[cfg(not(target_arch = "wasm32"))]
+const METADATA_LABEL: &str = "typst-2-rsx-labels";
+
+/// Where a labeled element is laid out, as reported by `typst query`.
+///
+/// # Field
+///
+/// - `label` : the name of the label, without the angle brackets.
+/// - `page` : the page of the element, starting from 1.
+/// - `x`, `y` : the top-left corner of the element on its page, in `pt`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelPosition {
+    pub label: String,
+
+    pub page: usize,
+
+    pub x: f64,
+
+    pub y: f64,
+}
+
+/// A label of the document and the element that received it as `id`.
+///
+/// # Field
+///
+/// - `label` : the name of the label, without the angle brackets.
+/// - `page` : the page of the labeled element, starting from 1.
+/// - `id` : the `id` given to the group drawn at the position of the element, which is the
+///   label itself; `None` when no group without an `id` is found there.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LabelAnchor {
+    pub label: String,
+
+    pub page: usize,
+
+    pub id: Option<String>,
+}
+
+/// Queries the positions of the labeled figures, headings and equations of a Typst document,
+/// running `typst query` with `invoker`.
+///
+/// The query runs on a wrapper document including the input and appending the positions as
+/// metadata, which `typst query` then prints. The wrapper is written to the temporary directory
+/// and given to Typst on its standard input, where paths resolve from the project root: the
+/// [`CompileOptions::root`], or the directory of the input like for a compilation, so that the
+/// imports of the input resolve the same way. The other options are forwarded like for a
+/// compilation.
+///
+/// # Return value
+///
+/// - `Ok(Vec<LabelPosition>)` : the positions, in the order of the document.
+/// - `Err(Error)` : the input is not in the project root, the wrapper cannot be written, the query
+///   fails ([`Error::TypstFailed`]), or its output is not the expected JSON ([`Error::JsonError`]).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{invoker::TypstCli, labels::query_labels, options::CompileOptions};
+///
+/// let positions = query_labels(&TypstCli, "report.typ", &CompileOptions::default()).unwrap();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn query_labels(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<Vec<LabelPosition>, Error> {
+    query_wrapper(
+        invoker,
+        input_typ_file.as_ref(),
+        options,
+        wrapper_source,
+        METADATA_LABEL,
+    )
+}
+
+/// Runs `typst query` with `invoker` on the wrapper document that `source` writes around the path
+/// of `input` from the project root, returning the value of its metadata labeled `label`.
+///
+/// The wrapper is written to the temporary directory and given to Typst on its standard input,
+/// where paths resolve from the project root: the [`CompileOptions::root`], or the directory of the
+/// input like for a compilation.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn query_wrapper<T: DeserializeOwned>(
+    invoker: &dyn TypstInvoker,
+    input: &Path,
+    options: &CompileOptions,
+    source: impl FnOnce(&str) -> String,
+    label: &str,
+) -> Result<T, Error> {
+    let mut root_args: Vec<OsString> = Vec::new();
+    let root = match &options.root {
+        Some(root) => root.clone(),
+        None => {
+            let dir = input
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            root_args.extend(["--root".into(), dir.into()]);
+            dir.to_path_buf()
+        }
+    };
+    let wrapper = crate::utils::temp_file_path("typ");
+    fs::write(&wrapper, source(&root_path(input, &root)?)).map_err(|e| Error::io(&wrapper, e))?;
+
+    let run = fs::File::open(&wrapper)
+        .map_err(|e| Error::io(&wrapper, e))
+        .and_then(|source| {
+            let mut command = Command::new("typst");
+            command
+                .arg("query")
+                .args(root_args)
+                .args(options.to_args())
+                .arg("-") // The wrapper, from stdin
+                .arg(format!("<{}>", label))
+                .args(["--field", "value", "--one"])
+                .stdin(source);
+            invoker.invoke(command, options)
+        });
+    let _ = fs::remove_file(&wrapper);
+    let output = run?.output;
+    if !output.status.success() {
+        return Err(Error::TypstFailed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_string(),
+        });
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// The path of `input` in the project at `root`, as Typst writes it: `/chapters/intro.typ`.
+#[cfg(not(target_arch = "wasm32"))]
+fn root_path(input: &Path, root: &Path) -> Result<String, Error> {
+    let canonical = |path: &Path| fs::canonicalize(path).map_err(|e| Error::io(path, e));
+    let input_path = canonical(input)?;
+    let relative = input_path.strip_prefix(canonical(root)?).map_err(|_| {
+        Error::io(
+            input,
+            io::Error::new(io::ErrorKind::InvalidInput, "not in the project root"),
+        )
+    })?;
+    Ok(relative
+        .components()
+        .map(|component| format!("/{}", component.as_os_str().to_string_lossy()))
+        .collect())
+}
+
+/// The document including the file at `path`, from the project root, and appending the positions
+/// of its labeled elements.
+#[cfg(not(target_arch = "wasm32"))]
+fn wrapper_source(path: &str) -> String {
+    let path = path.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "#include \"{path}\"\n\
+         #context [#metadata(\n  \
+           query(selector.or(figure, heading, math.equation))\n    \
+             .filter(it => it.has(\"label\"))\n    \
+             .map(it => {{\n      \
+               let position = it.location().position()\n      \
+               (label: str(it.label), page: position.page, x: position.x.pt(), y: position.y.pt())\n    \
+             }})\n\
+         ) <{label}>]\n",
+        path = path,
+        label = METADATA_LABEL
+    )
+}
+
+/// Gives every label as `id` to the group drawn at its position, returning an anchor for each.
+///
+/// The position is looked up on its page, the `typst-page` group of the same rank, or in the
+/// whole document when it has no page groups and the position is on the first page. The group
+/// receiving the label is the smallest one whose box, with its transforms applied, contains the
+/// position, give or take a point; the page group when none does. The matching is approximate:
+/// the position of an element is the corner of its layout box, which may be away from its ink,
+/// as for a centered figure. Groups that already have an `id` are skipped for the next larger
+/// one.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{labels::{anchor_labels, LabelPosition}, parse_svg};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 100 200' width='100pt' height='200pt'>\
+///      <g class='typst-page' transform='translate(0 0)'><path d='M 0 0 L 100 100'/></g>\
+///      <g class='typst-page' transform='translate(0 100)'>\
+///      <g transform='translate(10 20)'><path d='M 0 0 L 30 30'/></g></g></svg>",
+/// )
+/// .unwrap();
+/// let position = LabelPosition { label: "fig-results".to_string(), page: 2, x: 10.0, y: 20.0 };
+/// let anchors = anchor_labels(&mut svg, &[position]);
+/// assert_eq!(anchors[0].id.as_deref(), Some("fig-results"));
+/// assert!(svg.find_by_id("fig-results").is_some());
+/// ```
+pub fn anchor_labels(svg: &mut Svg, positions: &[LabelPosition]) -> Vec<LabelAnchor> {
+    // The page groups, with their transform if it can be parsed
+    let pages: Vec<(usize, Option<Matrix>)> = svg
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(index, element)| match element {
+            SvgElement::G(g) if is_page(g) => {
+                let matrix = match g.transform.as_deref() {
+                    Some(transform) => parse_transform(transform),
+                    None => Some(Matrix::IDENTITY),
+                };
+                Some((index, matrix))
+            }
+            _ => None,
+        })
+        .collect();
+    let groups = svg.group_bboxes();
+
+    let mut anchors = Vec::with_capacity(positions.len());
+    for position in positions {
+        let located = if pages.is_empty() {
+            (position.page == 1).then_some((None, Matrix::IDENTITY))
+        } else {
+            match position
+                .page
+                .checked_sub(1)
+                .and_then(|page| pages.get(page))
+            {
+                Some(&(index, Some(matrix))) => Some((Some(index), matrix)),
+                _ => None,
+            }
+        };
+        let Some((page, matrix)) = located else {
+            anchors.push(anchor(position, None));
+            continue;
+        };
+        let (x, y) = matrix.apply((position.x, position.y));
+        let mut candidates: Vec<_> = groups
+            .iter()
+            .filter(|(group, bbox)| {
+                page.is_none_or(|page| group[0] == page)
+                    && bbox.min_x - TOLERANCE <= x
+                    && x <= bbox.max_x + TOLERANCE
+                    && bbox.min_y - TOLERANCE <= y
+                    && y <= bbox.max_y + TOLERANCE
+            })
+            .collect();
+        // A group and its only child have the same box: the child is the closest to the element
+        candidates.sort_by(|(a_group, a), (b_group, b)| {
+            (a.width() * a.height())
+                .total_cmp(&(b.width() * b.height()))
+                .then(b_group.len().cmp(&a_group.len()))
+        });
+        let page_group = page.map(|page| vec![page]);
+        let id = candidates
+            .into_iter()
+            .map(|(group, _)| group)
+            .chain(page_group.as_ref())
+            .find_map(|group| {
+                let g = group_mut(svg, group)?;
+                if g.id.is_some() {
+                    return None;
+                }
+                g.id = Some(position.label.clone());
+                g.id.clone()
+            });
+        anchors.push(anchor(position, id));
+    }
+    anchors
+}
+
+fn anchor(position: &LabelPosition, id: Option<String>) -> LabelAnchor {
+    LabelAnchor {
+        label: position.label.clone(),
+        page: position.page,
+        id,
+    }
+}
+
+/// Whether `g` is the group of a page, which Typst gives the `typst-page` class.
+pub(crate) fn is_page(g: &G) -> bool {
+    g.class
+        .as_deref()
+        .is_some_and(|class| class.split_whitespace().any(|class| class == "typst-page"))
+}
+
+/// The group at `position`, as given by [`Svg::group_bboxes`].
+fn group_mut<'a>(svg: &'a mut Svg, position: &[usize]) -> Option<&'a mut G> {
+    let (first, rest) = position.split_first()?;
+    let SvgElement::G(g) = svg.elements.get_mut(*first)? else {
+        return None;
+    };
+    let mut g: &mut G = g;
+    for index in rest {
+        let GEle::G(inner) = g.elements.as_mut()?.get_mut(*index)? else {
+            return None;
+        };
+        g = inner;
+    }
+    Some(g)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn anchor_labels_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 100 200" width="100pt" height="200pt">
+                <g class="typst-page" transform="translate(0 0)">
+                    <g class="typst-text" transform="translate(10 10)"><path d="M 0 0 L 80 5"/></g>
+                </g>
+                <g class="typst-page" transform="translate(0 100)">
+                    <g transform="translate(10 20)">
+                        <g id="typst-figure"><path d="M 0 0 L 80 40"/></g>
+                        <g transform="translate(35 50)"><path d="M 0 0 L 10 5"/></g>
+                    </g>
+                </g>
+            </svg>"##,
+        )
+        .unwrap();
+        let position = |label: &str, page, x, y| LabelPosition {
+            label: label.to_string(),
+            page,
+            x,
+            y,
+        };
+        let anchors = anchor_labels(
+            &mut svg,
+            &[
+                // The caption, inside the innermost group
+                position("caption", 2, 45.5, 70.0),
+                // The figure, whose group has an id: its parent is used
+                position("fig-results", 2, 10.0, 20.0),
+                // Nothing drawn there, the page group is used
+                position("intro", 1, 5.0, 60.0),
+                position("fig-results-2", 2, 10.0, 20.0),
+                position("missing-page", 3, 0.0, 0.0),
+            ],
+        );
+        let ids: Vec<_> = anchors.iter().map(|anchor| anchor.id.as_deref()).collect();
+        assert_eq!(
+            ids,
+            [
+                Some("caption"),
+                Some("fig-results"),
+                Some("intro"),
+                Some("fig-results-2"),
+                None
+            ]
+        );
+        assert_eq!(anchors[4].page, 3);
+
+        let SvgElement::G(page) = &svg.elements[1] else {
+            unreachable!()
+        };
+        assert_eq!(page.id.as_deref(), Some("fig-results-2"));
+        let Some(GEle::G(body)) = page.elements.as_ref().and_then(|elements| elements.first())
+        else {
+            unreachable!()
+        };
+        assert_eq!(body.id.as_deref(), Some("fig-results"));
+        assert!(svg.find_by_id("caption").is_some());
+        assert!(svg.find_by_id("typst-figure").is_some());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn wrapper_source_test() {
+        let source = wrapper_source("/a \"quoted\" name.typ");
+        assert!(source.starts_with("#include \"/a \\\"quoted\\\" name.typ\"\n"));
+        assert!(source.ends_with(") <typst-2-rsx-labels>]\n"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn root_path_test() {
+        let root = crate::utils::temp_file_path("root");
+        fs::create_dir_all(root.join("chapters")).unwrap();
+        let input = root.join("chapters/intro.typ");
+        fs::write(&input, "= Introduction").unwrap();
+
+        assert_eq!(root_path(&input, &root).unwrap(), "/chapters/intro.typ");
+        assert_eq!(
+            root_path(&input, &root.join("chapters")).unwrap(),
+            "/intro.typ"
+        );
+        fs::write(root.join("main.typ"), "#include \"chapters/intro.typ\"").unwrap();
+        let error = root_path(&root.join("main.typ"), &root.join("chapters")).unwrap_err();
+        assert!(
+            matches!(error, Error::Io { source, .. } if source.kind() == io::ErrorKind::InvalidInput)
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,20 +1,102 @@
+#[cfg(all(feature = "dioxus-0-5", feature = "dioxus-0-6"))]
+compile_error!(
+    "the `dioxus-0-5` and `dioxus-0-6` features are mutually exclusive; build with \
+     `default-features = false` to select Dioxus 0.5"
+);
+#[cfg(all(feature = "dioxus-0-5", feature = "components"))]
+compile_error!("the `components` feature requires Dioxus 0.6, the `dioxus-0-6` feature");
+#[cfg(not(any(feature = "dioxus-0-5", feature = "dioxus-0-6")))]
+compile_error!("one of the `dioxus-0-5` and `dioxus-0-6` features must be enabled");
+// Dioxus 0.5 is renamed in the manifest to be optional next to 0.6; the crate names it `dioxus`
+// either way
+#[cfg(all(feature = "dioxus-0-5", not(feature = "dioxus-0-6")))]
+extern crate dioxus05 as dioxus;
+
 use dioxus::prelude::*;
-use serde_xml_rs::from_str;
+use serde::Deserialize;
+use std::io::BufRead;
+#[cfg(not(target_arch = "wasm32"))]
 use std::{
     fs,
-    process::{Command, ExitStatus},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Output},
 };
+use xml::reader::{EventReader, ParserConfig};
 
 pub mod svg_types;
 use svg_types::*;
+pub use svg_types::{svg_from_json, svg_to_json, SvgStats};
 pub mod error;
 use error::*;
+pub mod options;
+use options::*;
+pub mod codegen;
+pub mod passes;
+pub mod report;
+pub use codegen::svg_to_rsx_source;
+use report::{ConversionReport, Warning};
+mod pages;
+mod trace;
+pub use pages::split_pages;
+mod prepared;
+pub use prepared::PreparedSvg;
+#[cfg(feature = "raster")]
+mod raster;
+#[cfg(feature = "raster")]
+pub use raster::{svg_to_png, Png};
+mod stream;
+pub use stream::parse_svg_to_rsx_streaming;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "rayon")]
+pub use parallel::{parse_svg_parallel, parse_svg_parallel_with_limits};
+#[cfg(not(target_arch = "wasm32"))]
+pub mod build;
+#[cfg(not(target_arch = "wasm32"))]
+mod compile_output;
+#[cfg(not(target_arch = "wasm32"))]
+pub use compile_output::CompileOutput;
+#[cfg(not(target_arch = "wasm32"))]
+mod converter;
+#[cfg(not(target_arch = "wasm32"))]
+pub use converter::TypstConverter;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod invoker;
+#[cfg(not(target_arch = "wasm32"))]
+use invoker::{Invocation, TypstCli, TypstInvoker};
+#[cfg(all(feature = "components", not(target_arch = "wasm32")))]
+mod cache;
+mod compat;
+#[cfg(all(feature = "components", not(target_arch = "wasm32")))]
+pub mod components;
+mod convert;
+mod diagnose;
+#[cfg(feature = "fast-parse")]
+mod fast_parse;
+pub mod labels;
+pub mod limits;
+pub mod selectable;
+#[cfg(not(target_arch = "wasm32"))]
+use labels::LabelAnchor;
+use limits::ParseLimits;
+pub mod manifest;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod snapshot;
+#[cfg(not(target_arch = "wasm32"))]
 mod utils;
+#[cfg(not(target_arch = "wasm32"))]
 use utils::*;
 
-#[cfg(test)]
+#[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
+    use std::{
+        sync::OnceLock,
+        time::{Duration, Instant},
+    };
+
     use super::*;
+    use invoker::{MockResponse, MockTypst};
+    use serde_xml_rs::from_str;
 
     // #[test]
     // fn compile_test() {
@@ -27,13 +109,1516 @@ mod tests {
     #[test]
     fn convert_test() {
         let output = typst_to_rsx("./tmp/temp.typ").unwrap();
-        let expected = read_file("./test/expected_rsx.txt").unwrap();
-        assert_eq!(format!("{:?}", output).trim(), expected.trim());
+        assert_rsx_snapshot!(output, "expected");
+    }
+
+    /// A [`TypstInvoker`] starting a fake `typst` instead of the installed one, which exits with
+    /// an error for `fail.typ`, runs for five seconds for `slow.typ` and exits successfully for
+    /// any other input. An input holding an SVG is copied to the output, and its path appended to
+    /// `typst.log` in its directory; nothing is written for the others. A file named `warn.typ`
+    /// also prints a warning.
+    #[cfg(unix)]
+    pub(crate) struct FakeTypst;
+
+    #[cfg(unix)]
+    impl TypstInvoker for FakeTypst {
+        fn invoke(&self, command: Command, options: &CompileOptions) -> Result<Invocation, Error> {
+            // Written once, and run by `sh` rather than executed, so that no test starts it while
+            // another one still has it open for writing
+            static SCRIPT: OnceLock<PathBuf> = OnceLock::new();
+            let script = SCRIPT.get_or_init(|| {
+                let script = utils::temp_file_path("sh");
+                fs::write(
+                    &script,
+                    "case \"$*\" in\n\
+                     *fail.typ*) echo 'error: unexpected end' >&2; exit 1;;\n\
+                     *slow.typ*) exec sleep 5;;\n\
+                     *warn.typ*) echo 'warning: unknown font family: serif' >&2;;\n\
+                     esac\n\
+                     if grep -q '<svg' \"$2\" 2>/dev/null; then\n\
+                     cp \"$2\" \"$3\"; echo \"$2\" >> \"$(dirname \"$2\")/typst.log\"\n\
+                     fi\n",
+                )
+                .unwrap();
+                script
+            });
+            let mut fake = Command::new("sh");
+            fake.arg(script).args(command.get_args());
+            TypstCli.invoke(fake, options)
+        }
+    }
+
+    /// The SVG returned along with the element is the compiled file, byte for byte.
+    #[test]
+    fn with_svg_test() {
+        let input = Path::new("figure.typ");
+        let svg_str = read_file("./test/formula.svg")
+            .unwrap()
+            .trim_end()
+            .replace('\n', "\r\n");
+        let mock = MockTypst::new([MockResponse::svg(&svg_str)]);
+
+        let (element, compiled) = compile_with_svg(
+            &mock,
+            input,
+            &CompileOptions::default(),
+            &RsxOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(compiled, svg_str);
+        assert_eq!(
+            format!("{:?}", element),
+            format!("{:?}", parse_svg_to_rsx(&svg_str).unwrap())
+        );
+
+        let options = RsxOptions {
+            id_prefix: Some("a-".to_string()),
+            ..Default::default()
+        };
+        let (element, compiled) =
+            compile_with_svg(&mock, input, &CompileOptions::default(), &options).unwrap();
+        assert_eq!(compiled, svg_str);
+        assert!(format!("{:?}", element).contains("#a-glyph0"));
+    }
+
+    #[cfg(feature = "raster")]
+    #[test]
+    fn raster_test() {
+        let svg_str = read_file("./test/formula.svg").unwrap();
+        let mock = MockTypst::new([MockResponse::svg(&svg_str)]);
+
+        let png = compile_to_png(&mock, Path::new("formula.typ"), 2.0).unwrap();
+        assert_eq!(png, svg_to_png(&svg_str, 2.0).unwrap());
+    }
+
+    #[test]
+    fn labeled_test() {
+        let dir = utils::temp_file_path("labeled");
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("report.typ");
+        fs::write(&input, "= Report").unwrap();
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 100 200" width="100pt" height="200pt">
+                <g class="typst-page" transform="translate(0 0)"><path d="M 0 0 L 100 100"/></g>
+                <g class="typst-page" transform="translate(0 100)">
+                    <g transform="translate(10 20)"><path d="M 0 0 L 30 30"/></g>
+                </g>
+            </svg>"##;
+        let labels = r#"[
+                {"label": "intro", "page": 1, "x": 50.0, "y": 50.0},
+                {"label": "fig-results", "page": 2, "x": 10.0, "y": 20.0}
+            ]"#;
+        let mock = MockTypst::new([
+            MockResponse::Stdout(labels.to_string()),
+            MockResponse::svg(svg_str),
+        ]);
+
+        let options = CompileOptions::default();
+        let (element, anchors) =
+            compile_labeled(&mock, &input, &options, &RsxOptions::default()).unwrap();
+        let ids: Vec<_> = anchors
+            .iter()
+            .map(|anchor| (anchor.label.as_str(), anchor.page, anchor.id.as_deref()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                ("intro", 1, Some("intro")),
+                ("fig-results", 2, Some("fig-results"))
+            ]
+        );
+        let html = compat::render_html(element);
+        assert!(html.contains(r#"<g id="fig-results" transform="translate(10 20)">"#));
+        // The wrapper is read from stdin, with the directory of the input as root
+        let query = &mock.commands()[0];
+        assert_eq!(query[0], "query");
+        assert_eq!(query[1], "--root");
+        assert_eq!(query[2], dir.as_os_str());
+        assert_eq!(query[3], "-");
+        // Nothing was written next to the input
+        let files: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, ["report.typ"]);
+
+        let mock = MockTypst::new([MockResponse::Stdout("{}".to_string())]);
+        assert!(matches!(
+            compile_labeled(&mock, &input, &options, &RsxOptions::default()),
+            Err(Error::JsonError(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn selectable_text_test() {
+        let dir = utils::temp_file_path("selectable");
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("paragraph.typ");
+        fs::write(&input, "Hello, world of Typst.").unwrap();
+        let words = r#"[
+                {"text": "Hello,", "page": 1, "x": 10.0, "y": 20.0, "size": 11.0, "font": "libertinus serif"},
+                {"text": "world", "page": 1, "x": 38.5, "y": 20.0, "size": 11.0, "font": "libertinus serif"},
+                {"text": "of", "page": 1, "x": 66.1, "y": 20.0, "size": 11.0, "font": "libertinus serif"},
+                {"text": "Typst.", "page": 1, "x": 10.0, "y": 33.2, "size": 11.0, "font": "libertinus serif"}
+            ]"#;
+        let mock = MockTypst::new([
+            MockResponse::svg(read_file("./test/expected.svg").unwrap()),
+            MockResponse::Stdout(words.to_string()),
+        ]);
+        let options = CompileOptions {
+            text_mode: TextMode::Selectable,
+            ..Default::default()
+        };
+
+        let (element, output) =
+            compile_to_rsx(&mock, &input, &options, &RsxOptions::default()).unwrap();
+        let html = compat::render_html(element);
+        // The text of the paragraph, without the tags of its lines and words
+        let mut text = String::new();
+        let mut in_tag = false;
+        for c in html[html.find("typst-text-layer").unwrap()..].chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                c if !in_tag => text.push(c),
+                _ => {}
+            }
+        }
+        assert!(text.contains("Hello, world of"));
+        assert!(text.contains("Typst."));
+        assert!(html.contains(r#"<tspan x="38.5">world </tspan>"#));
+        assert!(html.contains(r#"fill="transparent""#));
+        // The glyphs are still drawn, and the size is that of the rewritten file
+        assert!(html.contains("<use"));
+        assert!(output.output_size_bytes.unwrap() > 0);
+        let query = &mock.commands()[1];
+        assert_eq!(query[0], "query");
+
+        // Outlined text queries nothing
+        let mock = MockTypst::new([MockResponse::svg(read_file("./test/expected.svg").unwrap())]);
+        let (element, _) = compile_to_rsx(
+            &mock,
+            &input,
+            &CompileOptions::default(),
+            &RsxOptions::default(),
+        )
+        .unwrap();
+        assert!(!compat::render_html(element).contains("typst-text-layer"));
+        assert_eq!(mock.commands().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stale_output_test() {
+        let mock = MockTypst::new([
+            MockResponse::Fail {
+                code: 1,
+                stderr: "error: unexpected end\n".to_string(),
+            },
+            MockResponse::NoOutput,
+        ]);
+        let output = utils::temp_file_path("svg");
+        let options = CompileOptions::default();
+
+        fs::write(&output, read_file("./test/expected.svg").unwrap()).unwrap();
+        let error = compile_checked(&mock, Path::new("fail.typ"), &output, &options).unwrap_err();
+        assert!(
+            matches!(&error, Error::TypstFailed { stderr, .. } if stderr == "error: unexpected end")
+        );
+        assert!(!output.exists());
+
+        fs::write(&output, read_file("./test/expected.svg").unwrap()).unwrap();
+        let error = compile_checked(&mock, Path::new("ok.typ"), &output, &options).unwrap_err();
+        assert!(matches!(&error, Error::MissingOutput { path } if *path == output));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeout_test() {
+        let output = utils::temp_file_path("svg");
+        let options = CompileOptions {
+            timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        };
+
+        let start = Instant::now();
+        let error =
+            compile_checked(&FakeTypst, Path::new("slow.typ"), &output, &options).unwrap_err();
+        assert!(
+            matches!(error, Error::TypstTimeout { after } if after == options.timeout.unwrap())
+        );
+        assert!(error.is_retryable());
+        assert!(start.elapsed() < Duration::from_secs(4));
+
+        let error =
+            compile_checked(&FakeTypst, Path::new("fail.typ"), &output, &options).unwrap_err();
+        assert_eq!(error.stderr(), Some("error: unexpected end"));
+        assert!(error.exit_status().is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn compile_output_test() {
+        let options = CompileOptions::default();
+        let dir = utils::temp_file_path("compile-output");
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("warn.typ");
+        let svg_str = read_file("./test/formula.svg").unwrap();
+        fs::write(&input, &svg_str).unwrap();
+
+        // The warnings are kept on success
+        let output_path = dir.join("out/warn.svg");
+        let output = run_compile(&FakeTypst, &input, &output_path, &options).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stderr, "warning: unknown font family: serif");
+        assert_eq!(output.output_path, output_path);
+        assert_eq!(output.output_size_bytes, Some(svg_str.len() as u64));
+        assert!(output.duration > Duration::ZERO && output.duration < Duration::from_secs(4));
+        assert_eq!(output.attempts, 1);
+
+        let output =
+            run_compile(&FakeTypst, Path::new("fail.typ"), &output_path, &options).unwrap();
+        assert!(!output.status.success());
+        assert_eq!(output.stderr, "error: unexpected end");
+        assert_eq!(output.output_size_bytes, None);
+
+        let (element, output) =
+            compile_to_rsx(&FakeTypst, &input, &options, &RsxOptions::default()).unwrap();
+        assert_eq!(
+            format!("{:?}", element),
+            format!("{:?}", parse_svg_to_rsx(&svg_str).unwrap())
+        );
+        assert_eq!(output.stderr, "warning: unknown font family: serif");
+        assert_eq!(output.output_size_bytes, Some(svg_str.len() as u64));
+        // The temporary SVG is gone
+        assert!(!output.output_path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn output_path_test() {
+        let dir = utils::temp_file_path("out");
+        fs::create_dir_all(&dir).unwrap();
+        let error = prepare_output_path(&dir).unwrap_err();
+        assert!(matches!(&error, Error::InvalidOutputPath { path, .. } if *path == dir));
+        assert_eq!(error.path(), Some(dir.as_path()));
+        assert!(error.to_string().contains("a directory exists"));
+
+        let nested = dir.join("a/b/c/figure.svg");
+        assert_eq!(prepare_output_path(&nested).unwrap(), nested);
+        assert!(dir.join("a/b/c").is_dir());
+
+        fs::write(dir.join("file"), "").unwrap();
+        let error = prepare_output_path(&dir.join("file/figure.svg")).unwrap_err();
+        assert!(matches!(error, Error::InvalidOutputPath { .. }));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Overlapping shapes are painted in document order, so the parsers and the conversion must
+    /// keep the order of the children whatever their types.
+    #[test]
+    fn source_order_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+            <path id="p1" d="M 0 0"/>
+            <defs id="d1"><symbol id="s" overflow="visible"><path d="M 0 0"/></symbol></defs>
+            <g id="g1">
+                <image width="1" height="1" preserveAspectRatio="none" href="data:,"/>
+                <path id="p2" d="M 0 0"/>
+                <use href="#s" x="1"/>
+                <g id="g2"/>
+                <path id="p3" d="M 0 0"/>
+                <use href="#s" x="2"/>
+            </g>
+            <rect x="0" y="0" width="1" height="1"/>
+            <path id="p4" d="M 0 0"/>
+            <g id="g3"/>
+            <defs id="d2"/>
+            <path id="p5" d="M 0 0"/>
+        </svg>"##;
+        let root_order = |svg: &Svg| {
+            svg.elements
+                .iter()
+                .map(|element| match element {
+                    SvgElement::Path(path) => path.id.clone().unwrap(),
+                    SvgElement::G(g) => g.id.clone().unwrap(),
+                    SvgElement::Defs(defs) => defs.id.clone(),
+                    SvgElement::Rect(_) => "rect".to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let group_order = |svg: &Svg| {
+            let SvgElement::G(g) = &svg.elements[2] else {
+                panic!("expected g1");
+            };
+            g.elements
+                .iter()
+                .flatten()
+                .map(|element| match element {
+                    GEle::Path(path) => path.id.clone().unwrap(),
+                    GEle::G(g) => g.id.clone().unwrap(),
+                    GEle::Use(uuse) => format!("use {}", uuse.x),
+                    GEle::Image(_) => "image".to_string(),
+                    GEle::Text(_) => "text".to_string(),
+                    GEle::Switch(_) => "switch".to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Each parser that the features enable is checked
+        #[allow(unused_mut)]
+        let mut parsed = vec![parse_svg(svg_str).unwrap(), from_str(svg_str).unwrap()];
+        #[cfg(feature = "fast-parse")]
+        parsed.push(fast_parse::parse(svg_str).unwrap());
+        #[cfg(feature = "rayon")]
+        parsed.push(parse_svg_parallel(svg_str).unwrap());
+        for svg in &parsed {
+            assert_eq!(
+                root_order(svg),
+                ["p1", "d1", "g1", "rect", "p4", "g3", "d2", "p5"]
+            );
+            assert_eq!(
+                group_order(svg),
+                ["image", "p2", "use 1", "g2", "p3", "use 2"]
+            );
+        }
+
+        /// The ids of the elements, each before those of its children.
+        fn collect_ids(node: &VNode, ids: &mut Vec<String>) {
+            use dioxus::dioxus_core::{AttributeValue, DynamicNode};
+
+            for attribute in node.dynamic_attrs.iter().flatten() {
+                if let ("id", AttributeValue::Text(id)) = (attribute.name, &attribute.value) {
+                    ids.push(id.clone());
+                }
+            }
+            for dynamic in node.dynamic_nodes.iter() {
+                if let DynamicNode::Fragment(children) = dynamic {
+                    children.iter().for_each(|child| collect_ids(child, ids));
+                }
+            }
+        }
+        let mut rendered = Vec::new();
+        collect_ids(&parse_svg_to_rsx(svg_str).unwrap().unwrap(), &mut rendered);
+        assert_eq!(
+            rendered,
+            ["p1", "d1", "s", "g1", "p2", "g2", "p3", "p4", "g3", "d2", "p5"]
+        );
+    }
+
+    #[test]
+    fn degenerate_test() {
+        let svg_str = read_file("./test/degenerate.svg").unwrap();
+        let (svg, report) = parse_svg_lenient(&svg_str).unwrap();
+        let paths: Vec<_> = report
+            .warnings
+            .iter()
+            .map(|warning| match warning {
+                report::Warning::MissingAttribute { path, .. } => path.as_str(),
+                _ => panic!("unexpected warning {:?}", warning),
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "svg > path[0]",
+                "svg > g[0] > use[1]",
+                "svg > g[0] > path[0]",
+                "svg > defs[0] > symbol[1]",
+            ]
+        );
+        assert_eq!(svg.stats().paths, 2);
+
+        // Without the pass, the paths without `d` are still left out of the RSX
+        let rsx = format!("{:?}", parse_svg_to_rsx(&svg_str).unwrap());
+        assert_eq!(rsx.matches("tag: \"path\"").count(), 3);
+    }
+
+    #[test]
+    fn report_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+            <g><circle r="1"/><path d="M 0 0 L 1 1" fill="#ff0000"/></g>
+            <g><use fill="#000000" x="0"/></g>
+        </svg>"##;
+        assert!(parse_svg_to_rsx(svg_str).is_err());
+
+        let options = RsxOptions {
+            colors: Some(ColorMap::from([("#000000", "var(--ink)")])),
+            ..Default::default()
+        };
+        let (element, report) = parse_svg_to_rsx_with_report(svg_str, &options).unwrap();
+        let warnings: Vec<_> = report.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "svg > g[0] > circle[0]: unknown element `circle` skipped",
+                "svg > g[1] > use[0]: no `href` attribute, nothing to show",
+                "color \"#ff0000\" has no replacement in the color map",
+            ]
+        );
+        let rsx = format!("{:?}", element);
+        assert!(rsx.contains("tag: \"path\"") && !rsx.contains("circle"));
+
+        // A document that the tree holds entirely gives an empty report
+        let svg_str = read_file("./test/expected.svg").unwrap();
+        let (element, report) =
+            parse_svg_to_rsx_with_report(&svg_str, &RsxOptions::default()).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(
+            format!("{:?}", element),
+            format!("{:?}", parse_svg_to_rsx(&svg_str).unwrap())
+        );
+    }
+
+    #[test]
+    fn untrusted_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+            <g>
+                <a href="javascript:alert(1)"><path d="M 0 0 L 1 1"/></a>
+                <use href="https://example.com/font.svg#g0" x="0"/>
+                <use href="#g0" x="1"/>
+            </g>
+            <defs id="glyph"><symbol id="g0" overflow="visible"><path d="M 0 0 L 1 0"/></symbol></defs>
+        </svg>"##;
+        let (element, report) =
+            parse_untrusted_svg_to_rsx(svg_str, &RsxOptions::default()).unwrap();
+        let warnings: Vec<_> = report.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "svg > g[0] > a[0]: unknown element `a` skipped",
+                "svg > g[0] > use[0]: unsafe reference \"https://example.com/font.svg#g0\" removed",
+            ]
+        );
+        let rsx = format!("{:?}", element);
+        assert!(!rsx.contains("example.com") && !rsx.contains("javascript"));
+        assert!(rsx.contains("#g0"));
+
+        // The trusted functions keep the references
+        let (element, report) =
+            parse_svg_to_rsx_with_report(svg_str, &RsxOptions::default()).unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        assert!(format!("{:?}", element).contains("example.com"));
+    }
+
+    #[test]
+    fn parse_svg_test() {
+        let svg = parse_svg(&read_file("./test/expected.svg").unwrap()).unwrap();
+        assert_eq!(svg.class, "typst-doc");
+        assert_eq!(svg.elements.len(), 3);
+        match &svg.elements[0] {
+            SvgElement::Path(path) => assert_eq!(path.fill.as_deref(), Some("#ffffff")),
+            other => panic!("expected the page background path, got {:?}", other),
+        }
+        assert!(matches!(svg.elements[2], SvgElement::Defs(_)));
+    }
+
+    #[test]
+    fn parse_svg_file_test() {
+        let svg = parse_svg_file("./test/expected.svg").unwrap();
+        assert_eq!(
+            svg,
+            parse_svg(&read_file("./test/expected.svg").unwrap()).unwrap()
+        );
+
+        let error = parse_svg_file("./test/broken.svg").unwrap_err();
+        assert!(matches!(&error, Error::InFile { source, .. }
+            if matches!(**source, Error::SvgStructure { .. })));
+        assert!(error
+            .to_string()
+            .starts_with("./test/broken.svg: Invalid SVG element"));
+
+        let error = parse_svg_file("./test/missing.svg").unwrap_err();
+        assert!(matches!(&error, Error::Io { source, .. }
+            if source.kind() == std::io::ErrorKind::NotFound));
+        assert_eq!(error.path(), Some(Path::new("./test/missing.svg")));
+
+        let file = fs::File::open("./test/expected.svg").unwrap();
+        let element = parse_svg_to_rsx_from_reader(std::io::BufReader::new(file)).unwrap();
+        assert_eq!(
+            format!("{:?}", element),
+            format!("{:?}", svg_to_rsx_with_options(svg, &RsxOptions::default()))
+        );
+    }
+
+    #[test]
+    fn encoding_test() {
+        let svg = parse_svg(&read_file("./test/bom.svg").unwrap()).unwrap();
+        assert_eq!(svg.elements.len(), 1);
+
+        // Declared, and decoded
+        let svg_str = "<?xml version='1.0' encoding='UTF-16'?>\
+                       <svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'/>";
+        let error = parse_svg(svg_str).unwrap_err();
+        assert!(matches!(&error, Error::UnsupportedEncoding(encoding) if encoding == "UTF-16"));
+        assert_eq!(
+            error.to_string(),
+            "Unsupported SVG encoding UTF-16, expected UTF-8"
+        );
+
+        // Encoded in UTF-16LE, with a byte order mark
+        let error = parse_svg_file("./test/utf16.svg").unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::InFile { source, .. }
+                if matches!(&**source, Error::UnsupportedEncoding(encoding) if encoding == "UTF-16LE")
+        ));
+        assert!(matches!(
+            read_file("./test/utf16.svg"),
+            Err(Error::UnsupportedEncoding(_))
+        ));
+        // In UTF-16BE, without a byte order mark
+        let bytes: Vec<u8> = svg_str.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert!(matches!(
+            parse_svg_to_rsx_from_reader(bytes.as_slice()),
+            Err(Error::UnsupportedEncoding(encoding)) if encoding == "UTF-16BE"
+        ));
+        // In Latin-1, declared
+        let latin1 = b"<?xml version='1.0' encoding='ISO-8859-1'?><svg><text>caf\xe9</text></svg>";
+        assert!(matches!(
+            parse_svg_to_rsx_from_reader(&latin1[..]),
+            Err(Error::UnsupportedEncoding(encoding)) if encoding == "ISO-8859-1"
+        ));
+        let error = parse_svg_to_rsx_from_reader(&b"<svg>\xff</svg>"[..]).unwrap_err();
+        assert!(
+            matches!(error, Error::Io { source, .. } if source.kind() == std::io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn entities_test() {
+        let svg_str = r#"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt"
+            xmlns:xlink="http://www.w3.org/1999/xlink">
+            <path class="a&#x2014;b &quot;c&quot;" d="M 0 0"/>
+            <g><image width="1" height="1" preserveAspectRatio="none"
+                xlink:href="https://example.com/a.png?w=1&amp;h=2&#38;q=3"/></g>
+        </svg>"#;
+        let svg = parse_svg(svg_str).unwrap();
+        let SvgElement::Path(path) = &svg.elements[0] else {
+            panic!()
+        };
+        assert_eq!(path.class.as_deref(), Some("a\u{2014}b \"c\""));
+        let SvgElement::G(g) = &svg.elements[1] else {
+            panic!()
+        };
+        let Some([GEle::Image(image)]) = g.elements.as_deref() else {
+            panic!()
+        };
+        assert_eq!(image.href, "https://example.com/a.png?w=1&h=2&q=3");
+
+        let rsx = format!("{:?}", parse_svg_to_rsx(svg_str).unwrap());
+        assert!(rsx.contains("a\u{2014}b"));
+        assert!(rsx.contains("a.png?w=1&h=2&q=3"));
+        assert!(!rsx.contains("&amp;"));
+
+        let error = parse_svg(
+            r#"<svg class="a&nbsp;b" viewBox="0 0 10 10" width="10pt" height="10pt"><path d="M 0 0"/></svg>"#,
+        )
+        .unwrap_err();
+        assert!(matches!(&error, Error::UnknownEntity { entity } if entity == "nbsp"));
+        assert_eq!(error.to_string(), "Unknown XML entity &nbsp;");
+    }
+
+    /// Re-indents an SVG with one element per line, dropping the whitespace between elements
+    /// when `indent` and `newline` are empty.
+    fn reformat(svg_str: &str, indent: &str, newline: &str) -> String {
+        let mut out = String::new();
+        let mut depth = 0;
+        for tag in svg_str
+            .split('<')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+        {
+            let tag = tag.trim_end_matches(|c: char| c != '>');
+            if tag.starts_with('/') {
+                depth -= 1;
+            }
+            out.push_str(&indent.repeat(depth));
+            out.push('<');
+            out.push_str(tag);
+            out.push_str(newline);
+            if !tag.starts_with('/') && !tag.ends_with("/>") {
+                depth += 1;
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn whitespace_test() {
+        let svg_str = read_file("./test/expected.svg").unwrap();
+        let compact = format!(
+            "{:?}",
+            parse_svg_to_rsx(&reformat(&svg_str, "", "")).unwrap()
+        );
+        for (indent, newline) in [("  ", "\n"), ("\t", "\r\n"), ("", "\n\n")] {
+            let formatted = reformat(&svg_str, indent, newline);
+            assert_eq!(
+                format!("{:?}", parse_svg_to_rsx(&formatted).unwrap()),
+                compact
+            );
+        }
+
+        // Element lists holding nothing but whitespace are empty
+        let svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'>\n  \
+             <g class='typst-group'>\n  </g>\n  <defs id='glyph'>\n  </defs>\n</svg>",
+        )
+        .unwrap();
+        assert!(matches!(&svg.elements[0], SvgElement::G(g) if g.elements.is_none()));
+        assert!(matches!(&svg.elements[1], SvgElement::Defs(defs) if defs.elements.is_empty()));
+        assert!(parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'>\n</svg>"
+        )
+        .unwrap()
+        .elements
+        .is_empty());
+    }
+
+    #[test]
+    fn svg_to_rsx_test() {
+        let svg = parse_svg(&read_file("./test/expected.svg").unwrap()).unwrap();
+        assert_rsx_snapshot!(svg_to_rsx(&svg), "expected");
+        let svg = parse_svg(&read_file("./test/formula.svg").unwrap()).unwrap();
+        assert_rsx_snapshot!(svg_to_rsx(&svg), "formula");
+        // One fixture per kind of element beyond paths and glyphs: text, gradient, clip path and
+        // mask, filter, and switch with its fallback
+        for name in ["paragraph", "gradient", "clip", "shadow"] {
+            let svg = parse_svg(&read_file(format!("./test/{}.svg", name)).unwrap()).unwrap();
+            assert_rsx_snapshot!(svg_to_rsx(&svg), name);
+        }
+        let svg = parse_svg(&read_file("./test/switch.svg").unwrap()).unwrap();
+        let options = RsxOptions {
+            switch: SwitchMode::FirstSupported,
+            ..Default::default()
+        };
+        assert_rsx_snapshot!(svg_to_rsx_with_options(svg, &options), "switch");
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn html_test() {
+        let svg = parse_svg(&read_file("./test/expected.svg").unwrap()).unwrap();
+        let html = compat::render_html(svg_to_rsx(&svg));
+        assert!(html.starts_with("<svg"));
+        assert!(html.ends_with("</svg>"));
+        assert!(html.contains("viewBox=\""));
+        assert!(html.contains("<use "));
+        assert!(html.contains("fill-rule=\"nonzero\""));
+        assert!(!html.contains("r#use"));
+        assert!(!html.contains("fill_rule"));
+    }
+
+    /// Returns the text value of an attribute of the root element, if it is present.
+    fn root_attribute(element: &Element, name: &str) -> Option<String> {
+        element
+            .as_ref()
+            .unwrap()
+            .dynamic_attrs
+            .iter()
+            .flatten()
+            .find(|attribute| attribute.name == name)
+            .and_then(|attribute| match &attribute.value {
+                dioxus::dioxus_core::AttributeValue::Text(value) => Some(value.clone()),
+                _ => None,
+            })
+    }
+
+    #[test]
+    fn sizing_test() {
+        let svg_str = read_file("./test/expected.svg").unwrap();
+        let convert = |sizing| {
+            let options = RsxOptions {
+                sizing,
+                ..Default::default()
+            };
+            parse_svg_to_rsx_with_options(&svg_str, &options).unwrap()
+        };
+
+        let fixed = convert(Sizing::Fixed);
+        assert_eq!(
+            root_attribute(&fixed, "width").unwrap(),
+            "564.0302222222222pt"
+        );
+        assert_eq!(root_attribute(&fixed, "height").unwrap(), "1056.990575pt");
+
+        let responsive = convert(Sizing::Responsive);
+        assert_eq!(root_attribute(&responsive, "width"), None);
+        assert_eq!(root_attribute(&responsive, "height"), None);
+        assert_eq!(
+            root_attribute(&responsive, "viewBox").unwrap(),
+            "0 0 564.0302222222222 1056.990575"
+        );
+
+        let scaled = convert(Sizing::Scale(2.0));
+        assert_eq!(
+            root_attribute(&scaled, "width").unwrap(),
+            "1128.0604444444443pt"
+        );
+        assert_eq!(root_attribute(&scaled, "height").unwrap(), "2113.98115pt");
+    }
+
+    #[test]
+    fn root_attributes_test() {
+        let svg_str = read_file("./test/expected.svg").unwrap();
+        let plain = parse_svg_to_rsx(&svg_str).unwrap();
+        assert_eq!(root_attribute(&plain, "class"), None);
+
+        let options = RsxOptions {
+            root_class: Some("figure".to_string()),
+            root_id: Some("fig-3".to_string()),
+            root_style: Some("max-width: 100%".to_string()),
+            ..Default::default()
+        };
+        let element = parse_svg_to_rsx_with_options(&svg_str, &options).unwrap();
+        assert_eq!(
+            root_attribute(&element, "class").unwrap(),
+            "typst-doc figure"
+        );
+        assert_eq!(root_attribute(&element, "id").unwrap(), "fig-3");
+        assert_eq!(
+            root_attribute(&element, "style").unwrap(),
+            "max-width: 100%"
+        );
+    }
+
+    /// The keys of the elements of `element` in document order, each preceded by the keys of its
+    /// ancestors (`g 1/g 2/use 3`). The unkeyed nodes between them, such as the fragment a keyed
+    /// node holds its element in with Dioxus 0.5, add nothing to the path.
+    fn keys(element: &Element) -> Vec<String> {
+        fn collect(node: &VNode, parent: &str, keys: &mut Vec<String>) {
+            for dynamic in node.dynamic_nodes.iter() {
+                if let dioxus::dioxus_core::DynamicNode::Fragment(children) = dynamic {
+                    for child in children.iter() {
+                        match &child.key {
+                            Some(key) => {
+                                let key = format!("{}/{}", parent, key);
+                                keys.push(key.clone());
+                                collect(child, &key, keys);
+                            }
+                            None => collect(child, parent, keys),
+                        }
+                    }
+                }
+            }
+        }
+        let mut keys = Vec::new();
+        collect(element.as_ref().unwrap(), "", &mut keys);
+        keys
+    }
+
+    #[test]
+    fn keys_test() {
+        let line = |glyphs: &[&str]| {
+            let uses = glyphs
+                .iter()
+                .enumerate()
+                .map(|(i, glyph)| format!(r##"<use href="#{}" x="{}"/>"##, glyph, i * 5))
+                .collect::<String>();
+            format!(r#"<g class="typst-text">{}</g>"#, uses)
+        };
+        let document = |second_line: &[&str]| {
+            format!(
+                r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                    <g transform="translate(0 0)">{}</g>
+                    <g transform="translate(0 10)">{}</g>
+                    <path d="M 0 0 L 1 1"/><path d="M 0 0 L 1 1"/><path id="rule" d="M 0 0 L 1 1"/>
+                </svg>"##,
+                line(&["a", "b", "c"]),
+                line(second_line)
+            )
+        };
+        let options = RsxOptions {
+            keys: true,
+            ..Default::default()
+        };
+        let convert =
+            |svg_str: &str| keys(&parse_svg_to_rsx_with_options(svg_str, &options).unwrap());
+
+        let before = convert(&document(&["d", "e", "f", "g"]));
+        let after = convert(&document(&["d", "e", "x", "f", "g"]));
+        assert_eq!(before.len(), 3 + 2 + 3 + 2 + 4);
+        // Only the inserted glyph and the two glyphs it moved get new keys
+        assert_eq!(after.len(), before.len() + 1);
+        let kept = after.iter().filter(|key| before.contains(key)).count();
+        assert_eq!(kept, before.len() - 2);
+        // The identical paths are told apart and the `id` is used as is
+        assert!(after.contains(&format!("{}#1", after[after.len() - 3])));
+        assert_eq!(after.last().unwrap(), "/rule");
+        for keys in [&before, &after] {
+            let unique: std::collections::HashSet<_> = keys.iter().collect();
+            assert_eq!(unique.len(), keys.len());
+        }
+
+        let unkeyed = keys(&parse_svg_to_rsx(&document(&["d"])).unwrap());
+        assert!(unkeyed.is_empty());
+    }
+
+    fn click_listeners(selector: Selector) -> usize {
+        fn app(selector: Selector) -> Element {
+            let options = RsxOptions {
+                on_click: Some((selector, EventHandler::new(|_| {}))),
+                ..Default::default()
+            };
+            parse_svg_to_rsx_with_options(
+                r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                    <g id="step-1" class="typst-group step"><path d="M 0 0 L 1 1"/></g>
+                    <g id="step-2" class="typst-group step"><path id="arrow" d="M 0 0 L 2 2"/></g>
+                </svg>"##,
+                &options,
+            )
+            .unwrap()
+        }
+
+        let mut dom = VirtualDom::new_with_props(app, selector);
+        dom.rebuild_to_vec()
+            .edits
+            .iter()
+            .filter(|edit| {
+                matches!(
+                    edit,
+                    dioxus::dioxus_core::Mutation::NewEventListener { name, .. } if name == "click"
+                )
+            })
+            .count()
+    }
+
+    #[test]
+    fn on_click_test() {
+        assert_eq!(click_listeners(Selector::Root), 1);
+        assert_eq!(click_listeners(Selector::Id("arrow".to_string())), 1);
+        assert_eq!(click_listeners(Selector::Id("step-2".to_string())), 1);
+        assert_eq!(click_listeners(Selector::Class("step".to_string())), 2);
+        assert_eq!(click_listeners(Selector::Class("typst".to_string())), 0);
+    }
+
+    #[test]
+    fn element_attributes_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+            <g id="grid" class="typst-group" pointer-events="none"><path d="M 0 0 L 1 1"/></g>
+            <g id="step-1" class="typst-group step"><path d="M 0 0 L 2 2" tabindex="-1"/></g>
+            <g id="step-2" class="typst-group step" tabindex="1"/>
+        </svg>"##;
+        let svg = parse_svg(svg_str).unwrap();
+        let SvgElement::G(grid) = &svg.elements[0] else {
+            unreachable!()
+        };
+        assert_eq!(grid.pointer_events.as_deref(), Some("none"));
+        let SvgElement::G(step) = &svg.elements[1] else {
+            unreachable!()
+        };
+        let Some(GEle::Path(path)) = step.elements.as_ref().and_then(|elements| elements.first())
+        else {
+            unreachable!()
+        };
+        assert_eq!(path.tabindex.as_deref(), Some("-1"));
+        assert_eq!(svg_from_json(&svg_to_json(&svg)).unwrap(), svg);
+        assert_eq!(parse_svg(&svg.to_svg_string()).unwrap(), svg);
+
+        let html = compat::render_html(svg_to_rsx(&svg));
+        assert!(html.contains(r#"<g id="grid" class="typst-group" pointer-events="none">"#));
+        assert!(html.contains(r#"<path d="M 0 0 L 2 2" tabindex="-1"></path>"#));
+
+        let set = |tabindex: Option<&str>, pointer_events: Option<&str>| ElementAttributes {
+            tabindex: tabindex.map(String::from),
+            pointer_events: pointer_events.map(String::from),
+        };
+        let options = RsxOptions {
+            element_attributes: vec![
+                (Selector::Root, set(Some("-1"), None)),
+                (Selector::Class("step".to_string()), set(Some("0"), None)),
+                (Selector::Id("step-2".to_string()), set(None, Some("none"))),
+                (Selector::Id("grid".to_string()), set(None, Some("auto"))),
+            ],
+            ..Default::default()
+        };
+        let html = compat::render_html(svg_to_rsx_with_options(svg, &options));
+        assert!(html.starts_with(
+            "<svg viewBox=\"0 0 10 10\" width=\"10pt\" height=\"10pt\" tabindex=\"-1\">"
+        ));
+        assert!(html.contains(r#"<g id="grid" class="typst-group" pointer-events="auto">"#));
+        assert!(html.contains(r#"<g id="step-1" class="typst-group step" tabindex="0">"#));
+        assert!(html.contains(
+            r#"<g id="step-2" class="typst-group step" tabindex="0" pointer-events="none">"#
+        ));
+        // The path keeps its own value, not being matched
+        assert!(html.contains(r#"<path d="M 0 0 L 2 2" tabindex="-1"></path>"#));
+    }
+
+    #[test]
+    fn rendering_hint_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+            <g class="typst-group" text-rendering="geometricPrecision">
+                <path class="rule" d="M 0 0 L 10 0"/>
+                <path d="M 0 5 L 10 5" shape-rendering="optimizeSpeed"/>
+                <text class="rule" x="0">Total</text>
+            </g>
+        </svg>"##;
+        let svg = parse_svg(svg_str).unwrap();
+        let SvgElement::G(g) = &svg.elements[0] else {
+            unreachable!()
+        };
+        assert_eq!(g.text_rendering.as_deref(), Some("geometricPrecision"));
+        assert_eq!(svg_from_json(&svg_to_json(&svg)).unwrap(), svg);
+        assert_eq!(parse_svg(&svg.to_svg_string()).unwrap(), svg);
+
+        let hint = |selector: Selector, value: &str| RsxOptions {
+            rendering_hint: Some((selector, value.to_string())),
+            // The hints are emitted even when the absent attributes are
+            empty_absent_attributes: true,
+            strip_presentation_attributes: true,
+            ..Default::default()
+        };
+        let options = hint(Selector::Class("rule".to_string()), "crispEdges");
+        let html = compat::render_html(svg_to_rsx_with_options(svg.clone(), &options));
+        assert!(html.contains(
+            r#"<path class="rule" d="M 0 0 L 10 0" fill-rule="" shape-rendering="crispEdges">"#
+        ));
+        assert!(html.contains(r#"shape-rendering="optimizeSpeed""#));
+        // `crispEdges` is no `text-rendering`
+        assert!(html.contains(r#"<text class="rule" x="0">Total</text>"#));
+
+        let options = hint(Selector::Root, "geometricPrecision");
+        let html = compat::render_html(svg_to_rsx_with_options(svg.clone(), &options));
+        assert!(html.starts_with(
+            "<svg viewBox=\"0 0 10 10\" width=\"10pt\" height=\"10pt\" \
+             shape-rendering=\"geometricPrecision\" text-rendering=\"geometricPrecision\">"
+        ));
+
+        let options = hint(
+            Selector::Class("typst-group".to_string()),
+            "optimizeLegibility",
+        );
+        let html = compat::render_html(svg_to_rsx_with_options(svg, &options));
+        assert!(html.contains(
+            r#"<g class="typst-group" transform="" text-rendering="optimizeLegibility">"#
+        ));
+    }
+
+    /// Every element kind follows the canonical attribute order, in the RSX and in the source.
+    #[test]
+    fn attribute_order_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt"
+            xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+            <rect x="0" y="0" width="10" height="10" fill="#ffffff"/>
+            <path pointer-events="none" tabindex="0" shape-rendering="crispEdges"
+                stroke-miterlimit="4" stroke-linejoin="round" stroke-linecap="round"
+                stroke-width="1" stroke="#000000" opacity="0.5" fill-rule="nonzero" fill="none"
+                d="M 0 0 L 1 1" class="typst-shape" id="line"/>
+            <g pointer-events="none" tabindex="0" text-rendering="geometricPrecision"
+                shape-rendering="crispEdges" filter="url(#shadow)" mask="url(#fade)"
+                clip-path="url(#clip)"
+                transform="translate(1 1)" class="typst-group" id="group">
+                <use fill-rule="nonzero" fill="#000000" transform="scale(1)" xlink:href="#glyph0"
+                    height="2" width="2" x="0"/>
+                <path pointer-events="none" tabindex="0" shape-rendering="crispEdges"
+                    stroke-miterlimit="4" stroke-linejoin="round" stroke-linecap="round"
+                    stroke-width="1" stroke="#000000" opacity="0.5" fill-rule="nonzero"
+                    fill="none" d="M 0 0 L 1 1" class="typst-shape" id="inner"/>
+                <image transform="scale(1)" xlink:href="data:image/png;base64,"
+                    preserveAspectRatio="none" height="1" width="1"/>
+                <text text-rendering="geometricPrecision" text-anchor="middle"
+                    dominant-baseline="central" word-spacing="2" letter-spacing="0.5"
+                    font-style="italic" font-weight="700" font-size="8" font-family="serif"
+                    fill="#000000" transform="scale(1)" dy="1" dx="1" y="5" x="5"
+                    class="typst-text" id="label">a<tspan dominant-baseline="central"
+                    word-spacing="2" letter-spacing="0.5" font-style="italic" font-weight="700"
+                    font-size="8" font-family="serif" fill="#000000" dy="1" dx="1" y="5"
+                    x="5">b</tspan></text>
+            </g>
+            <defs id="glyph">
+                <symbol overflow="visible" id="glyph0">
+                    <path fill-rule="nonzero" fill="#000000" d="M 0 0 L 1 0 L 1 1 Z"
+                        class="outline"/>
+                </symbol>
+                <symbol overflow="visible" preserveAspectRatio="none" viewBox="0 0 1 1"
+                    id="glyph1">
+                    <image transform="scale(1)" xlink:href="data:image/png;base64,"
+                        preserveAspectRatio="none" height="1" width="1"/>
+                </symbol>
+                <linearGradient spreadMethod="pad" gradientTransform="rotate(45)"
+                    gradientUnits="userSpaceOnUse" xlink:href="#radial" y2="0" x2="1" y1="0" x1="0"
+                    id="linear">
+                    <stop stop-color="#000000" offset="0"/>
+                </linearGradient>
+                <radialGradient spreadMethod="repeat" gradientTransform="scale(2)" fy="0.5"
+                    fx="0.5" r="0.5" cy="0.5" cx="0.5" id="radial"/>
+                <clipPath clipPathUnits="userSpaceOnUse" transform="scale(1)" id="clip">
+                    <path fill-rule="nonzero" d="M 0 0 L 1 0 L 1 1 Z" id="clip-outline"/>
+                </clipPath>
+                <mask maskContentUnits="userSpaceOnUse" maskUnits="userSpaceOnUse" height="10"
+                    width="10" y="0" x="0" id="fade">
+                    <path stroke-width="1" stroke="#000000" opacity="0.5" fill="#ffffff"
+                        d="M 0 0 L 1 1"/>
+                </mask>
+                <filter filterUnits="userSpaceOnUse" height="12" width="12" y="-1" x="-1"
+                    id="shadow">
+                    <feColorMatrix result="alpha" values="0 0 0 0 0  0 0 0 0 0  0 0 0 0 0  0 0 0 1 0"
+                        type="matrix" in="SourceAlpha"/>
+                    <feGaussianBlur result="blur" stdDeviation="1" in="alpha"/>
+                    <feOffset result="offset" dy="1" dx="1" in="blur"/>
+                    <feDropShadow result="shadow" flood-opacity="0.5" flood-color="#000000"
+                        stdDeviation="1" dy="1" dx="1" in="SourceGraphic"/>
+                </filter>
+            </defs>
+        </svg>"##;
+        let assert_ordered = |tag: &str, names: &[&str]| {
+            let ranks: Vec<_> = names
+                .iter()
+                .map(|name| convert::attribute_rank(name))
+                .collect();
+            assert!(
+                ranks
+                    .iter()
+                    .all(|rank| *rank < convert::ATTRIBUTE_ORDER.len()),
+                "unknown attribute on {tag}: {names:?}"
+            );
+            assert!(
+                ranks.windows(2).all(|pair| pair[0] <= pair[1]),
+                "attributes of {tag} out of order: {names:?}"
+            );
+        };
+
+        let options = RsxOptions {
+            root_id: Some("doc".to_string()),
+            root_class: Some("figure".to_string()),
+            root_style: Some("max-width: 100%".to_string()),
+            rendering_hint: Some((Selector::Root, "geometricPrecision".to_string())),
+            ..Default::default()
+        };
+        let html = compat::render_html(svg_to_rsx_with_options(
+            parse_svg(svg_str).unwrap(),
+            &options,
+        ));
+        let mut tags = Vec::new();
+        for tag in html.split('<').skip(1).filter(|tag| !tag.starts_with('/')) {
+            let tag = &tag[..tag.find('>').unwrap()];
+            let name = tag.split(' ').next().unwrap();
+            // The values hold no `="`, so every attribute ends right before one
+            let parts: Vec<_> = tag.split("=\"").collect();
+            let names: Vec<_> = parts[..parts.len() - 1]
+                .iter()
+                .filter_map(|part| part.rsplit(' ').next())
+                .collect();
+            assert_ordered(name, &names);
+            tags.push(name);
+        }
+        for tag in [
+            "svg",
+            "rect",
+            "path",
+            "g",
+            "use",
+            "image",
+            "text",
+            "tspan",
+            "defs",
+            "symbol",
+            "linearGradient",
+            "radialGradient",
+            "stop",
+            "clipPath",
+            "mask",
+            "filter",
+            "feColorMatrix",
+            "feGaussianBlur",
+            "feOffset",
+            "feDropShadow",
+        ] {
+            assert!(tags.contains(&tag), "no {tag} rendered");
+        }
+        assert!(html.starts_with(
+            "<svg id=\"doc\" class=\"typst-doc figure\" viewBox=\"0 0 10 10\" width=\"10pt\" \
+             height=\"10pt\" shape-rendering=\"geometricPrecision\" \
+             text-rendering=\"geometricPrecision\" style=\"max-width: 100%\">"
+        ));
+
+        let source = svg_to_rsx_source(svg_str).unwrap();
+        let mut tag = "";
+        let mut names = Vec::new();
+        for line in source.lines().map(str::trim) {
+            match line.split_once(": ") {
+                Some((name, _)) if line.ends_with("\",") => names.push(name),
+                _ => {
+                    assert_ordered(tag, &names);
+                    names.clear();
+                    tag = line.trim_end_matches(" {");
+                }
+            }
+        }
+        assert!(source.contains(
+            "path {\n            id: \"line\",\n            class: \"typst-shape\",\n            \
+             d: \"M 0 0 L 1 1\",\n            fill: \"none\","
+        ));
+    }
+
+    #[test]
+    fn empty_absent_attributes_test() {
+        let svg_str = read_file("./test/expected.svg").unwrap();
+        let omitted = format!("{:?}", parse_svg_to_rsx(&svg_str).unwrap());
+        let options = RsxOptions {
+            empty_absent_attributes: true,
+            ..Default::default()
+        };
+        let legacy = format!(
+            "{:?}",
+            parse_svg_to_rsx_with_options(&svg_str, &options).unwrap()
+        );
+        assert!(legacy.contains("Text(\"\")"));
+        assert!(!omitted.contains("Text(\"\")"));
+        assert!(omitted.len() < legacy.len());
+    }
+
+    /// A path without `fill` must render without the attribute, so that it takes the fill of its
+    /// group instead of an empty, invalid one.
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn absent_fill_inherited_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+            <g class="typst-text"><path d="M 0 0 L 10 10"/></g>
+        </svg>"##;
+        let options = RsxOptions {
+            root_style: Some("fill: #ff0000".to_string()),
+            ..Default::default()
+        };
+        let html = compat::render_html(parse_svg_to_rsx_with_options(svg_str, &options).unwrap());
+        assert!(html.contains(r#"<g class="typst-text"><path d="M 0 0 L 10 10"></path></g>"#));
+        assert!(!html.contains("=\"\""));
+
+        let options = RsxOptions {
+            empty_absent_attributes: true,
+            ..options
+        };
+        let html = compat::render_html(parse_svg_to_rsx_with_options(svg_str, &options).unwrap());
+        assert!(html.contains(r#"fill="""#));
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn strip_presentation_attributes_test() {
+        let svg_str = read_file("./test/expected.svg").unwrap();
+        let options = RsxOptions {
+            strip_presentation_attributes: true,
+            ..Default::default()
+        };
+        let html = compat::render_html(parse_svg_to_rsx_with_options(&svg_str, &options).unwrap());
+        assert!(!html.contains("fill=\"#"));
+        assert!(!html.contains("stroke"));
+        assert!(html.contains("fill-rule=\"nonzero\""));
+    }
+
+    #[test]
+    fn text_elements_test() {
+        let svg_str = read_file("./test/paragraph.svg").unwrap();
+        let svg = parse_svg(&svg_str).unwrap();
+        assert_eq!(svg.stats().texts, 2);
+        let html = compat::render_html(svg_to_rsx(&svg));
+        assert!(html.contains(">Hello, world</text>"));
+        assert!(html.contains(r#"x="0 6.6 12.7 15.8 18.9 25.1 28.2 31.3 39.5 45.7 49.6 52.7""#));
+        assert!(html.contains(r#"font-family="Libertinus Serif""#));
+        assert!(html.contains(r##"<tspan fill="#1f4e8c" font-weight="bold">&amp; chips</tspan>"##));
+        assert!(html.contains(r#"<tspan dx="2.75" font-style="italic">for two</tspan>"#));
+        assert!(html.contains(">Fish <tspan"));
+
+        // The spaces between the runs are kept, also when the report rewrites the document
+        let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+                       <g><text>Hello <tspan>world</tspan> again</text></g></svg>";
+        let html = compat::render_html(parse_svg_to_rsx(svg_str).unwrap());
+        assert!(html.contains("<text>Hello <tspan>world</tspan> again</text>"));
+        let svg_str = svg_str.replace("</text>", "</text><foo/>");
+        let (element, report) =
+            parse_svg_to_rsx_with_report(&svg_str, &RsxOptions::default()).unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        let html = compat::render_html(element);
+        assert!(html.contains("<text>Hello <tspan>world</tspan> again</text>"));
+
+        // Characters spelling an element name are still characters
+        let svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+             <g><text>tspan<tspan>text</tspan></text></g></svg>",
+        )
+        .unwrap();
+        let html = compat::render_html(svg_to_rsx(&svg));
+        assert!(html.contains("<text>tspan<tspan>text</tspan></text>"));
+    }
+
+    #[test]
+    fn text_spacing_test() {
+        let svg_str = read_file("./test/tracking.svg").unwrap();
+        let (element, report) =
+            parse_svg_to_rsx_with_report(&svg_str, &RsxOptions::default()).unwrap();
+        assert!(report.is_empty(), "{:?}", report.warnings);
+        let html = compat::render_html(element);
+        assert!(
+            html.contains(
+                r#"letter-spacing="1.1" word-spacing="3.85">Tracked and justified</text>"#
+            ),
+            "{}",
+            html
+        );
+        assert!(html.contains(r#"dominant-baseline="alphabetic">Small caps"#));
+        assert!(html.contains(
+            r#"<tspan font-size="7.7" letter-spacing="0.55" dominant-baseline="central">SMALL</tspan>"#
+        ));
+        assert!(html.contains(r#"<tspan dx="2.75" word-spacing="-0.5">tight words</tspan>"#));
+
+        let source = svg_to_rsx_source(&svg_str).unwrap();
+        assert!(source.contains(r#"letter_spacing: "1.1","#));
+        assert!(source.contains(r#"dominant_baseline: "central","#));
+    }
+
+    #[test]
+    fn gradient_test() {
+        let svg_str = read_file("./test/gradient.svg").unwrap();
+        let svg = parse_svg(&svg_str).unwrap();
+        let SvgElement::Defs(defs) = &svg.elements[2] else {
+            panic!("expected defs");
+        };
+        let Definition::LinearGradient(gradient) = &defs.definitions[0] else {
+            panic!("expected linearGradient");
+        };
+        assert_eq!(
+            gradient.gradient_transform.as_deref(),
+            Some("rotate(45 0.5 0.5)")
+        );
+        assert_eq!(gradient.spread_method, Some(SpreadMethod::Pad));
+        assert_eq!(gradient.gradient_units, Some(Units::ObjectBoundingBox));
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(defs.definitions[1].href(), Some("#g0"));
+
+        let options = RsxOptions {
+            id_prefix: Some("fig-".to_string()),
+            ..Default::default()
+        };
+        let (element, report) = parse_svg_to_rsx_with_report(&svg_str, &options).unwrap();
+        assert!(report.is_empty(), "{:?}", report.warnings);
+        let html = compat::render_html(element);
+        assert!(html.contains(r#"<rect x="0" y="0" width="40" height="20" fill="url(#fig-g0)">"#));
+        assert!(
+            html.contains(
+                r##"<linearGradient id="fig-g0" x1="0" y1="0" x2="1" y2="0" gradientUnits="objectBoundingBox" gradientTransform="rotate(45 0.5 0.5)" spreadMethod="pad"><stop offset="0" stop-color="#ff0000"></stop><stop offset="1" stop-color="#0000ff"></stop></linearGradient>"##
+            ),
+            "{}",
+            html
+        );
+        assert!(html.contains(
+            r##"<linearGradient id="fig-g1" href="#fig-g0" spreadMethod="reflect"></linearGradient>"##
+        ));
+
+        let source = svg_to_rsx_source(&svg_str).unwrap();
+        assert!(source.contains(r#"gradient_transform: "rotate(45 0.5 0.5)","#));
+        assert!(source.contains(r##"stop_color: "#0000ff","##));
+    }
+
+    #[test]
+    fn filter_test() {
+        let svg_str = read_file("./test/shadow.svg").unwrap();
+        let svg = parse_svg(&svg_str).unwrap();
+        let SvgElement::G(card) = &svg.elements[0] else {
+            panic!("expected g");
+        };
+        assert_eq!(card.filter.as_deref(), Some("url(#shadow0)"));
+        let SvgElement::Defs(defs) = &svg.elements[4] else {
+            panic!("expected defs");
+        };
+        let Definition::Filter(shadow) = &defs.definitions[0] else {
+            panic!("expected filter");
+        };
+        let FilterPrimitive::DropShadow(drop_shadow) = &shadow.primitives[0] else {
+            panic!("expected feDropShadow");
+        };
+        assert_eq!(drop_shadow.std_deviation.as_deref(), Some("3"));
+        assert_eq!(drop_shadow.flood_color.as_deref(), Some("#1f2328"));
+        assert_eq!(drop_shadow.flood_opacity.as_deref(), Some("0.25"));
+        let Definition::Filter(chain) = &defs.definitions[1] else {
+            panic!("expected filter");
+        };
+        let tags: Vec<_> = chain.primitives.iter().map(FilterPrimitive::tag).collect();
+        assert_eq!(tags, ["feColorMatrix", "feGaussianBlur", "feOffset"]);
+        let FilterPrimitive::ColorMatrix(matrix) = &chain.primitives[0] else {
+            panic!("expected feColorMatrix");
+        };
+        assert_eq!(matrix.kind, Some(ColorMatrixType::Matrix));
+
+        let options = RsxOptions {
+            id_prefix: Some("card-".to_string()),
+            ..Default::default()
+        };
+        let (element, report) = parse_svg_to_rsx_with_report(&svg_str, &options).unwrap();
+        assert!(report.is_empty(), "{:?}", report.warnings);
+        let html = compat::render_html(element);
+        assert!(html.contains(
+            r#"<g class="typst-group" transform="translate(10 10)" filter="url(#card-shadow0)">"#
+        ));
+        assert!(
+            html.contains(
+                r##"<filter id="card-shadow0" x="-20%" y="-20%" width="140%" height="150%"><feDropShadow dx="0" dy="2" stdDeviation="3" flood-color="#1f2328" flood-opacity="0.25"></feDropShadow></filter>"##
+            ),
+            "{}",
+            html
+        );
+        // The primitives stay in the order of the chain, each reading the result of the one before
+        assert!(
+            html.contains(
+                r#"<filter id="card-shadow1" x="-10" y="-10" width="60" height="64" filterUnits="userSpaceOnUse"><feColorMatrix in="SourceAlpha" type="matrix" values="0 0 0 0 0  0 0 0 0 0  0 0 0 0 0  0 0 0 0.3 0" result="color"></feColorMatrix><feGaussianBlur in="color" stdDeviation="3" result="blur"></feGaussianBlur><feOffset in="blur" dx="0" dy="3"></feOffset></filter>"#
+            ),
+            "{}",
+            html
+        );
+
+        let source = svg_to_rsx_source(&svg_str).unwrap();
+        let position = |needle: &str| source.find(needle).unwrap();
+        assert!(position("feColorMatrix {") < position("feGaussianBlur {"));
+        assert!(position("feGaussianBlur {") < position("feOffset {"));
+        assert!(source.contains(r#""in": "SourceAlpha","#));
+        assert!(source.contains(r#"r#type: "matrix","#));
+        assert!(source.contains(r##"flood_color: "#1f2328","##));
+        assert!(source.contains(r#"filterUnits: "userSpaceOnUse","#));
+    }
+
+    #[test]
+    fn symbol_view_box_test() {
+        let svg_str = read_file("./test/icons.svg").unwrap();
+        let svg = parse_svg(&svg_str).unwrap();
+        let SvgElement::Defs(defs) = &svg.elements[2] else {
+            panic!("expected defs");
+        };
+        let icon = &defs.elements[0];
+        assert_eq!(icon.view_box.as_deref(), Some("0 0 10 10"));
+        assert_eq!(icon.preserve_aspect_ratio.as_deref(), Some("xMidYMid meet"));
+        // The triangle at twice and four times the size of its viewBox
+        let bbox = svg.content_bbox().unwrap();
+        assert_eq!(
+            (bbox.min_x, bbox.min_y, bbox.max_x, bbox.max_y),
+            (10.0, 10.0, 80.0, 50.0)
+        );
+
+        let html = compat::render_html(parse_svg_to_rsx(&svg_str).unwrap());
+        assert!(
+            html.contains(
+                r#"<symbol id="icon0" viewBox="0 0 10 10" preserveAspectRatio="xMidYMid meet" overflow="visible">"#
+            ),
+            "{}",
+            html
+        );
+        assert!(
+            html.contains(r##"<use x="0" width="20" height="20" href="#icon0" fill="#0969da">"##),
+            "{}",
+            html
+        );
+        assert!(html.contains(r#"width="40" height="40""#));
+
+        let mut expanded = svg.clone();
+        passes::expand_uses(&mut expanded).unwrap();
+        let transforms: Vec<_> = expanded
+            .elements
+            .iter()
+            .map(|element| match element {
+                SvgElement::G(g) => match g.elements.as_deref() {
+                    Some([GEle::G(copy)]) => copy.transform.clone(),
+                    _ => panic!("expected an expanded use"),
+                },
+                _ => panic!("expected g"),
+            })
+            .collect();
+        assert_eq!(
+            transforms,
+            [Some("scale(2)".to_string()), Some("scale(4)".to_string())]
+        );
+        assert_eq!(expanded.content_bbox(), svg.content_bbox());
+
+        let source = svg_to_rsx_source(&svg_str).unwrap();
+        assert!(source.contains(r#"view_box: "0 0 10 10","#));
+        assert!(source.contains(r#"preserve_aspect_ratio: "xMidYMid meet","#));
+    }
+
+    #[test]
+    fn switch_test() {
+        let svg_str = read_file("./test/switch.svg").unwrap();
+        let svg = parse_svg(&svg_str).unwrap();
+        let SvgElement::G(g) = &svg.elements[0] else {
+            panic!("expected g");
+        };
+        let Some(GEle::Switch(switch)) = g.elements.as_ref().and_then(|elements| elements.first())
+        else {
+            panic!("expected switch");
+        };
+        let names: Vec<_> = switch.branches.iter().map(SwitchBranch::name).collect();
+        assert_eq!(names, ["foreignObject", "g"]);
+
+        // The first branch by default, which renders nothing
+        let (element, report) =
+            parse_svg_to_rsx_with_report(&svg_str, &RsxOptions::default()).unwrap();
+        let html = compat::render_html(element);
+        assert!(
+            html.contains(r#"<g transform="translate(1 2)"></g>"#),
+            "{}",
+            html
+        );
+        assert!(!html.contains("Caption"));
+        let warnings: Vec<_> = report.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "svg > g[0] > switch[0] > foreignObject[0]: alternative `foreignObject` not rendered",
+                "svg > g[0] > switch[0] > g[0]: alternative `g` not rendered",
+            ]
+        );
+
+        // The first branch that the tree holds when configured
+        let options = RsxOptions {
+            switch: SwitchMode::FirstSupported,
+            ..Default::default()
+        };
+        let (element, report) = parse_svg_to_rsx_with_report(&svg_str, &options).unwrap();
+        let html = compat::render_html(element);
+        assert!(html.contains(
+            r#"<g transform="translate(1 2)"><g class="fallback"><text x="1" y="8">Caption</text></g></g>"#
+        ), "{}", html);
+        assert!(!html.contains("in bold"));
+        let warnings: Vec<_> = report.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            ["svg > g[0] > switch[0] > foreignObject[0]: alternative `foreignObject` not rendered"]
+        );
+    }
+
+    #[test]
+    fn math_dollar_test() {
+        // Refused before Typst runs, so the code after the `$` never does
+        let error = typst_math_to_rsx(r#"x $ #read("/etc/passwd") $ y"#).unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::InvalidMath { reason, .. } if reason.contains("byte 2")
+        ));
+        assert!(check_math(r"\$ + 2 \\$").is_err());
+        assert!(check_math(r"x^2 \$ \\\$").is_ok());
+        assert!(check_math("integral_0^1 x^2 dif x").is_ok());
+    }
+
+    #[test]
+    fn error_panel_test() {
+        let error = Error::Io {
+            path: None,
+            source: std::io::Error::other("first line\nsecond line"),
+        };
+        let panel = format!("{:?}", error_panel(&error, "my-panel"));
+        assert!(panel.contains("my-panel"));
+        assert!(panel.contains("first line"));
+        assert!(panel.contains("second line"));
+    }
+
+    #[test]
+    fn error_panel_fallback_test() {
+        let output = typst_to_rsx_or_error_panel("./tmp/missing.typ", "my-panel");
+        assert!(compat::node(&output).is_some());
+        assert!(format!("{:?}", output).contains("my-panel"));
     }
 }
 
 /// Compile the Typst file for SVG output.
 ///
+/// Not available on `wasm32` targets, where no process can be spawned.
+///
 /// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
 /// Ensure that you have Typst installed and properly configured before using this function.
 /// You can install Typst by following the instructions at: https://github.com/typst/typst
@@ -43,7 +1628,8 @@ mod tests {
 ///
 /// # parameter
 ///
-/// - `input_typ_file` : specifies the path to the Typst file, usually a.typ file.
+/// - `input_typ_file` : specifies the path to the Typst file, usually a.typ file. Any path type is accepted
+///   (`&str`, `String`, `&Path`, `PathBuf`), so paths that are not valid UTF-8 are passed as they are.
 /// - `output_svg_file` : path to the output SVG file that will contain the compiled image contents.
 ///
 /// # Return value
@@ -55,7 +1641,7 @@ mod tests {
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,no_run
 ///  use typst_2_rsx::typst_compile;
 ///
 ///  let input_file = "example.typ";
@@ -65,231 +1651,699 @@ mod tests {
 ///      Ok(status) => println! ("Compilation finished with status: {}", status),
 ///      Err(e) => eprintln! ("Failed to compile: {}", e),
 ///  }
+///
+///  // Paths built with `std::path` work the same way
+///  let output_dir = std::path::Path::new("figures");
+///  let _ = typst_compile(input_file, output_dir.join("output.svg"));
 /// ```
-pub fn typst_compile(input_typ_file: &str, output_svg_file: &str) -> Result<ExitStatus, Error> {
-    // Ensure the directory exists (create it recursively if it doesn't)
-    let path = std::path::Path::new(output_svg_file);
-    if !path.exists() {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?
-        }
-    }
-    // Call the typst cli to compile
-    let status = Command::new("typst")
-        .arg("compile") // Typst compile command
-        .arg(input_typ_file) // Input file
-        .arg(output_svg_file) // Output file
-        .status();
-    match status {
-        Ok(status_content) => Ok(status_content),
-        Err(e) => Err(Error::TypstCompileError(e)),
-    }
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_compile(
+    input_typ_file: impl AsRef<Path>,
+    output_svg_file: impl AsRef<Path>,
+) -> Result<ExitStatus, Error> {
+    typst_compile_with(input_typ_file, output_svg_file, &CompileOptions::default())
+        .map(|output| output.status)
 }
 
-/// Parses an SVG string and converts it to RSX code.
-///
-/// This function first parses the input SVG string into an `Svg` structure and then converts it into an RSX format element.
-/// RSX is a JSX-like syntax for building UI elements. This function recursively builds the RSX element and returns it.
+/// Compile the Typst file for SVG output, forwarding the given [`CompileOptions`] to the CLI.
 ///
-/// # parameter
-///
-/// - `svg_str` : a string reference representing the SVG content to be parsed.
+/// This behaves like [`typst_compile`], with the root directory, font paths, `sys.inputs` and
+/// timeout taken from `options`, and returns a [`CompileOutput`] with the exit status, the time
+/// the CLI ran, the size of the SVG and what the CLI printed on its standard error, which is
+/// captured rather than shown.
 ///
 /// # Return value
 ///
-/// Returns a `Result` type:
-///
-/// - `Ok(Element)` : If parsing is successful, return the converted RSX element.
-/// - `Err(ConvertError)` : If an error occurs, an error message string is returned.
+/// - `Ok(CompileOutput)` : the CLI ran, whether the compilation succeeded or not; see its `status`.
+/// - `Err(Error)` : the CLI could not be started, did not finish within the timeout, or the output
+///   directory could not be created.
 ///
 /// # Example
 ///
 /// ```rust
-/// use typst_2_rsx::parse_svg_to_rsx;
+/// use typst_2_rsx::{options::CompileOptions, typst_compile_with};
 ///
-/// let svg_str = "<svg viewBox='0 0 100 100' width='100' height='100'></svg>";
-/// match parse_svg_to_rsx(svg_str) {
-///     Ok(element) => println! ("RSX: {:?}", element),
-///     Err(e) => eprintln! ("Error: {}", e),
+/// let options = CompileOptions {
+///     root: Some(".".into()),
+///     ..Default::default()
+/// };
+/// match typst_compile_with("example.typ", "output.svg", &options) {
+///     Ok(output) if output.status.success() => println!("Compiled in {:?}", output.duration),
+///     Ok(output) => eprintln!("Failed to compile: {}", output.stderr),
+///     Err(e) => eprintln!("Failed to run Typst: {}", e),
 /// }
 /// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "typst_compile",
+        skip_all,
+        fields(
+            input = %input_typ_file.as_ref().display(),
+            output = %output_svg_file.as_ref().display(),
+            duration_ms = tracing::field::Empty,
+        ),
+    )
+)]
+pub fn typst_compile_with(
+    input_typ_file: impl AsRef<Path>,
+    output_svg_file: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<CompileOutput, Error> {
+    let _timing = trace::Timing::start();
+    run_compile(
+        &TypstCli,
+        input_typ_file.as_ref(),
+        output_svg_file.as_ref(),
+        options,
+    )
+}
+
+/// Runs `typst compile` with `invoker`, reading the size of the output when it succeeds, and lays
+/// the words over it with [`TextMode::Selectable`].
+#[cfg(not(target_arch = "wasm32"))]
+fn run_compile(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    output_svg_file: &Path,
+    options: &CompileOptions,
+) -> Result<CompileOutput, Error> {
+    let (command, output_path) = typst_command(input_typ_file, output_svg_file, options)?;
+    let Invocation {
+        output: Output { status, stderr, .. },
+        duration,
+        attempts,
+    } = invoker.invoke(command, options)?;
+    let mut output_size_bytes = fs::metadata(&output_path)
+        .ok()
+        .filter(|metadata| status.success() && metadata.is_file())
+        .map(|metadata| metadata.len());
+    if output_size_bytes.is_some() && options.text_mode == TextMode::Selectable {
+        output_size_bytes = Some(selectable::overlay_file(
+            invoker,
+            input_typ_file,
+            &output_path,
+            options,
+        )?);
+    }
+    Ok(CompileOutput {
+        status,
+        duration,
+        output_path,
+        output_size_bytes,
+        stderr: String::from_utf8_lossy(&stderr).trim_end().to_string(),
+        attempts,
+    })
+}
+
+/// Compiles like [`typst_compile_with`], but captures the CLI's stderr and turns a non-zero exit
+/// into [`Error::TypstFailed`] carrying the Typst diagnostics.
 ///
-/// # Attention
-///
-/// - This function relies on the `from_str` function to parse the SVG string, assuming that the string is properly formatted. Misformatted SVG strings can cause parsing failures.
-///
-pub fn parse_svg_to_rsx(svg_str: &str) -> Result<Element, Error> {
-    /// Converts an `SvgElement` to the corresponding RSX `Element`.
-    ///
-    /// # Parameters
-    ///
-    /// - `tag`: A reference to the `SvgElement` to be converted.
-    ///
-    /// # Returns
-    ///
-    /// Returns the RSX `Element` corresponding to the input `SvgElement`.
-    ///
-    fn from_svg_element(tag: &SvgElement) -> Element {
-        match tag {
-            SvgElement::Path(path) => {
-                rsx!(path {
-                    d: path.d.clone(),
-                    class: path.class.clone().unwrap_or_default(),
-                    fill: path.fill.clone().unwrap_or_default(),
-                    fill_rule: path.fill_rule.clone().unwrap_or_default(),
-                    stroke: path.stroke.clone().unwrap_or_default(),
-                    stroke_width: path.stroke_width.clone().unwrap_or_default(),
-                    stroke_linecap: path.stroke_linecap.clone().unwrap_or_default(),
-                    stroke_linejoin: path.stroke_linejoin.clone().unwrap_or_default(),
-                    stroke_miterlimit: path.stroke_miterlimit.clone().unwrap_or_default(),
-                })
-            }
-            SvgElement::G(g) => {
-                rsx!(
-                    g {
-                        class: g.class.clone().unwrap_or_default(),
-                        transform: g.transform.clone().unwrap_or_default(),
-                        {
-                            let map_fn = |element: &svg_types::GEle| from_g_element(element);
-                            g.elements
-                                .as_ref()
-                                .map(|elements| elements.iter().map(map_fn))
-                                .unwrap_or_else(|| [].iter().map(map_fn))
-                        }
-                    }
-                )
-            }
-            SvgElement::Defs(defs) => {
-                rsx!(
-                    defs { id: defs.id.clone(),
-                        {defs.elements.iter().map(|element| { from_symbol(&element) })}
-                    }
-                )
-            }
-        }
+/// An existing file at `output_path` is removed first, and a successful exit that leaves no
+/// output gives [`Error::MissingOutput`], so that the output of an earlier compilation is never
+/// read as the result of this one.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn typst_compile_checked(
+    input_typ_file: &Path,
+    output_path: &Path,
+    options: &CompileOptions,
+) -> Result<CompileOutput, Error> {
+    compile_checked(&TypstCli, input_typ_file, output_path, options)
+}
+
+/// [`typst_compile_checked`] with the Typst CLI run by `invoker`.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "typst_compile",
+        skip_all,
+        fields(
+            input = %input_typ_file.display(),
+            output = %output_path.display(),
+            output_size = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        ),
+    )
+)]
+pub(crate) fn compile_checked(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    output_path: &Path,
+    options: &CompileOptions,
+) -> Result<CompileOutput, Error> {
+    let _timing = trace::Timing::start();
+    if output_path.is_file() {
+        fs::remove_file(output_path).map_err(|e| Error::io(output_path, e))?;
+    }
+    let output = run_compile(invoker, input_typ_file, output_path, options)?;
+    if !output.status.success() {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(status = %output.status, stderr = %output.stderr, "typst failed");
+        return Err(Error::TypstFailed {
+            status: output.status,
+            stderr: output.stderr,
+        });
     }
+    let Some(output_size) = output.output_size_bytes else {
+        return Err(Error::MissingOutput {
+            path: output_path.to_path_buf(),
+        });
+    };
+    trace::record("output_size", output_size as usize);
+    Ok(output)
+}
 
-    /// Converts a `GEle` to the corresponding RSX `Element`.
-    ///
-    /// # Parameters
-    ///
-    /// - `tag`: A reference to the `GEle` to be converted.
-    ///
-    /// # Returns
-    ///
-    /// Returns the RSX `Element` corresponding to the input `GEle`.
-    ///
-    fn from_g_element(tag: &GEle) -> Element {
-        match tag {
-            GEle::G(g) => {
-                rsx! {
-                    g {
-                        class: g.class.clone().unwrap_or_default(),
-                        transform: g.transform.clone().unwrap_or_default(),
-                        {
-                            let map_fn = |element: &svg_types::GEle| from_g_element(element);
-                            g.elements
-                                .as_ref()
-                                .map(|elements| elements.iter().map(map_fn))
-                                .unwrap_or_else(|| [].iter().map(map_fn))
-                        }
-                    }
-                }
-            }
-            GEle::Use(uuse) => {
-                rsx! {
-                    r#use {
-                        fill: uuse.fill.clone(),
-                        x: uuse.x.clone(),
-                        fill_rule: uuse.fill_rule.clone(),
-                        href: uuse.href.clone(),
-                        transform: uuse.transform.clone(),
-                    }
-                }
-            }
-            GEle::Path(path) => {
-                rsx!(path {
-                    d: path.d.clone(),
-                    class: path.class.clone(),
-                    fill: path.fill.clone().unwrap_or_default(),
-                    fill_rule: path.fill_rule.clone().unwrap_or_default(),
-                    stroke: path.stroke.clone().unwrap_or_default(),
-                    stroke_width: path.stroke_width.clone().unwrap_or_default(),
-                    stroke_linecap: path.stroke_linecap.clone().unwrap_or_default(),
-                    stroke_linejoin: path.stroke_linejoin.clone().unwrap_or_default(),
-                    stroke_miterlimit: path.stroke_miterlimit.clone().unwrap_or_default(),
-                })
-            }
-            GEle::Image(image) => {
-                rsx!(image {
-                    width: image.width.clone(),
-                    height: image.height.clone(),
-                    preserve_aspect_ratio: image.preserve_aspect_ratio.clone(),
-                    href: image.href.clone(),
-                    transform: image.transform.clone(),
-                })
-            }
+/// Compiles a Typst file to a temporary SVG with `invoker` and returns its content, removing the
+/// file.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn compile_to_string(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    options: &CompileOptions,
+) -> Result<(String, CompileOutput), Error> {
+    let output = temp_file_path("svg");
+    let content = compile_checked(invoker, input_typ_file, &output, options)
+        .and_then(|compile_output| Ok((read_file(&output)?, compile_output)));
+    let _ = fs::remove_file(&output);
+    content
+}
+
+/// Builds the `typst compile` invocation, creating the output directory if needed, and returns
+/// it with the output path given to the CLI.
+#[cfg(not(target_arch = "wasm32"))]
+fn typst_command(
+    input_typ_file: &Path,
+    output_svg_file: &Path,
+    options: &CompileOptions,
+) -> Result<(Command, PathBuf), Error> {
+    let output_path = prepare_output_path(output_svg_file)?;
+    // Call the typst cli to compile
+    let mut command = Command::new("typst");
+    command
+        .arg("compile") // Typst compile command
+        .args(options.to_args()) // Root, font paths and inputs
+        .arg(input_typ_file) // Input file
+        .arg(&output_path); // Output file
+    Ok((command, output_path))
+}
+
+/// Checks that the output can be written at `output_svg_file`, creating its missing parent
+/// directories, and returns the path with the separators of the platform.
+#[cfg(not(target_arch = "wasm32"))]
+fn prepare_output_path(output_svg_file: &Path) -> Result<PathBuf, Error> {
+    // `components` drops redundant separators and, on Windows, turns `/` into `\`
+    let path: PathBuf = output_svg_file.components().collect();
+    let invalid = |reason: &str| Error::InvalidOutputPath {
+        path: path.clone(),
+        reason: reason.to_string(),
+    };
+    if path.as_os_str().is_empty() {
+        return Err(invalid("the path is empty"));
+    }
+    if path.is_dir() {
+        return Err(invalid("a directory exists at this path"));
+    }
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        if let Some(file) = parent.ancestors().find(|ancestor| ancestor.is_file()) {
+            return Err(invalid(&format!(
+                "{} is a file, not a directory",
+                file.display()
+            )));
         }
+        fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
     }
+    Ok(path)
+}
 
-    /// Converts a `Symbol` to the corresponding RSX `Element`.
-    ///
-    /// # Parameters
-    ///
-    /// - `tag`: A reference to the `Symbol` to be converted.
-    ///
-    /// # Returns
-    ///
-    /// Returns the RSX `Element` corresponding to the input `Symbol`.
-    ///
-    fn from_symbol(tag: &Symbol) -> Element {
-        rsx!(
-            symbol { id: tag.id.clone(), overflow: tag.overflow.clone(),
-                {
-                    match &tag.element {
-                        SymbolEle::Path(path) => {
-                            rsx! {
-                                path {
-                                    d: path.d.clone(),
-                                    class: path.class.clone(),
-                                    fill: path.fill.clone(),
-                                    fill_rule: path.fill_rule.clone(),
-                                }
-                            }
-                        }
-                        SymbolEle::Image(image) => {
-                            rsx! {
-                                image {
-                                    width: image.width.clone(),
-                                    height: image.height.clone(),
-                                    preserve_aspect_ratio: image.preserve_aspect_ratio.clone(),
-                                    href: image.href.clone(),
-                                    transform: image.transform.clone(),
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        )
+/// Parses an SVG string and converts it to RSX code.
+///
+/// This function first parses the input SVG string into an `Svg` structure and then converts it into an RSX format element.
+/// RSX is a JSX-like syntax for building UI elements. This function recursively builds the RSX element and returns it.
+///
+/// This function does not touch the filesystem or spawn processes, so it is also available on `wasm32` targets,
+/// e.g. to convert an SVG string fetched in the browser.
+///
+/// # parameter
+///
+/// - `svg_str` : a string reference representing the SVG content to be parsed.
+///
+/// # Return value
+///
+/// Returns a `Result` type:
+///
+/// - `Ok(Element)` : If parsing is successful, return the converted RSX element.
+/// - `Err(ConvertError)` : If an error occurs, an error message string is returned.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::parse_svg_to_rsx;
+///
+/// let svg_str = "<svg viewBox='0 0 100 100' width='100' height='100'></svg>";
+/// match parse_svg_to_rsx(svg_str) {
+///     Ok(element) => println! ("RSX: {:?}", element),
+///     Err(e) => eprintln! ("Error: {}", e),
+/// }
+/// ```
+///
+/// # Attention
+///
+/// - This function relies on the `from_str` function to parse the SVG string, assuming that the string is properly formatted. Misformatted SVG strings can cause parsing failures.
+///
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "parse_svg_to_rsx", skip_all, fields(input_size = svg_str.len()))
+)]
+pub fn parse_svg_to_rsx(svg_str: &str) -> Result<Element, Error> {
+    Ok(convert::svg_root(
+        parse_svg(svg_str)?,
+        &RsxOptions::default(),
+    ))
+}
+
+/// Parses an SVG string into the structured [`Svg`] tree.
+///
+/// This is the first half of [`parse_svg_to_rsx`]. Use it when the document needs to be inspected or
+/// transformed before rendering, then hand the result to [`svg_to_rsx`].
+///
+/// A leading UTF-8 byte order mark is skipped. A document whose XML declaration names another
+/// encoding than UTF-8 is rejected with [`Error::UnsupportedEncoding`]. Entities and character
+/// references in attribute values (`&amp;`, `&#x2014;`) are decoded, so the parsed values and the
+/// rendered attributes hold the literal characters; an entity that XML does not predefine, such
+/// as `&nbsp;`, is rejected with [`Error::UnknownEntity`].
+///
+/// With the `fast-parse` feature, the tree is built by a hand-written `quick-xml` reader, several
+/// times faster than the `serde-xml-rs` deserialization. Both build the same tree; a document the
+/// fast reader does not accept is handed to `serde-xml-rs`, so the errors are the same too.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, svg_to_rsx};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 100 100' width='100' height='100'>\
+///                <path d='M 0 0 L 100 100'/></svg>";
+/// let svg = parse_svg(svg_str).unwrap();
+/// assert_eq!(svg.elements.len(), 1);
+/// let element = svg_to_rsx(&svg);
+/// ```
+pub fn parse_svg(svg_str: &str) -> Result<Svg, Error> {
+    parse_svg_with_limits(svg_str, &ParseLimits::default())
+}
+
+/// Parses an SVG string like [`parse_svg`], converting what the parsed tree cannot hold instead of
+/// failing, and reports every change in a [`ConversionReport`].
+///
+/// The elements that the tree has no type for, such as a `circle`, and the elements of other
+/// vocabularies, such as the metadata of an editor, are skipped along with their content. The
+/// attributes without a field are dropped, as [`parse_svg`] does silently. Then the elements that
+/// cannot show anything, such as a `path` without `d`, are removed by
+/// [`drop_degenerate`](passes::drop_degenerate). See [`report::Warning`] for the warnings.
+///
+/// Finding the elements to skip reads the document once more before parsing it, so this is slower
+/// than [`parse_svg`], which keeps failing on the first element it cannot parse.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::parse_svg_lenient;
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <circle r='1'/><path fill='none'/><path d='M 0 0 L 10 10'/></svg>";
+/// let (svg, report) = parse_svg_lenient(svg_str).unwrap();
+/// assert_eq!(svg.elements.len(), 1);
+/// assert_eq!(
+///     report.warnings[1].to_string(),
+///     "svg > path[0]: no `d` attribute, nothing to show"
+/// );
+/// ```
+pub fn parse_svg_lenient(svg_str: &str) -> Result<(Svg, ConversionReport), Error> {
+    parse_lenient(svg_str, &ParseLimits::default())
+}
+
+/// [`parse_svg_lenient`] with the given [`ParseLimits`], checked before the document is read.
+fn parse_lenient(svg_str: &str, limits: &ParseLimits) -> Result<(Svg, ConversionReport), Error> {
+    let svg_str = check_svg(svg_str, limits)?;
+    let (rewritten, mut warnings) = report::scan(svg_str);
+    let mut svg = parse_checked(rewritten.as_deref().unwrap_or(svg_str))?;
+    warnings.extend(passes::drop_degenerate(&mut svg));
+    Ok((
+        svg,
+        ConversionReport {
+            warnings,
+            ..Default::default()
+        },
+    ))
+}
+
+/// Parses an SVG string like [`parse_svg`], checking it against the given [`ParseLimits`] first.
+///
+/// The parser and the conversion recurse for every nested group and hold the whole document in
+/// memory, so a document from an untrusted source could exhaust the memory or overflow the stack
+/// and abort the process. The input is scanned before parsing, and a document exceeding a limit
+/// is rejected with [`Error::LimitExceeded`], or [`Error::TooDeep`] for the nesting depth.
+/// [`parse_svg`] applies `ParseLimits::default()`, which accepts what Typst produces.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{error::Error, limits::ParseLimits, parse_svg_with_limits};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <g><g><path d='M 0 0 L 10 10'/></g></g></svg>";
+/// let limits = ParseLimits {
+///     max_depth: 2,
+///     ..Default::default()
+/// };
+/// assert!(matches!(parse_svg_with_limits(svg_str, &limits), Err(Error::TooDeep(2))));
+/// assert!(parse_svg_with_limits(svg_str, &ParseLimits::unlimited()).is_ok());
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "parse_svg",
+        level = "debug",
+        skip_all,
+        fields(
+            input_size = svg_str.len(),
+            elements = tracing::field::Empty,
+            paths = tracing::field::Empty,
+            uses = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        ),
+    )
+)]
+pub fn parse_svg_with_limits(svg_str: &str, limits: &ParseLimits) -> Result<Svg, Error> {
+    let _timing = trace::Timing::start();
+    let svg = parse_checked(check_svg(svg_str, limits)?)?;
+    trace::record_elements(&svg);
+    Ok(svg)
+}
+
+/// Reads an SVG file and parses it into the structured [`Svg`] tree, like [`parse_svg`].
+///
+/// A file that cannot be read, or is not valid UTF-8, gives an [`Error::Io`] and a document that
+/// cannot be parsed an [`Error::InFile`], both naming the file. A file encoded in UTF-16 is
+/// recognized by its first bytes and rejected with an [`Error::UnsupportedEncoding`] in the
+/// [`Error::InFile`], like one declaring another encoding.
+///
+/// Not available on `wasm32` targets.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::parse_svg_file;
+///
+/// let svg = parse_svg_file("test/expected.svg").unwrap();
+/// assert!(!svg.elements.is_empty());
+///
+/// let error = parse_svg_file("test/missing.svg").unwrap_err();
+/// assert!(error.to_string().starts_with("I/O error on test/missing.svg: "));
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse_svg_file(path: impl AsRef<Path>) -> Result<Svg, Error> {
+    parse_file(path.as_ref(), &ParseLimits::default())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_file(path: &Path, limits: &ParseLimits) -> Result<Svg, Error> {
+    let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+    decode_svg(bytes, Some(path))
+        .and_then(|svg_str| parse_svg_with_limits(&svg_str, limits))
+        .map_err(|source| match source {
+            Error::Io { .. } => source,
+            source => Error::InFile {
+                path: path.to_path_buf(),
+                source: Box::new(source),
+            },
+        })
+}
+
+/// Reads an SVG from `reader` and converts it to RSX, like [`parse_svg_to_rsx`].
+///
+/// The document is read to the end before being parsed, since the limits and the location of the
+/// errors need all of it; see [`parse_svg_to_rsx_streaming`] to convert a large document without
+/// holding it whole.
+///
+/// # Example
+///
+/// ```rust
+/// use std::{fs::File, io::BufReader};
+/// use typst_2_rsx::parse_svg_to_rsx_from_reader;
+///
+/// let file = File::open("test/expected.svg").unwrap();
+/// let element = parse_svg_to_rsx_from_reader(BufReader::new(file)).unwrap();
+/// ```
+pub fn parse_svg_to_rsx_from_reader(mut reader: impl BufRead) -> Result<Element, Error> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|source| Error::Io { path: None, source })?;
+    parse_svg_to_rsx(&decode_svg(bytes, None)?)
+}
+
+/// Decodes the bytes of an SVG document, read from the file at `path` if any, as UTF-8.
+///
+/// A UTF-16 document is recognized before decoding, by its byte order mark or by its first
+/// characters (`<?` is `3C 00 3F 00` in UTF-16LE), and rejected with
+/// [`Error::UnsupportedEncoding`] rather than as invalid UTF-8. So is a document that is not
+/// UTF-8 but declares another encoding, such as ISO-8859-1. Other invalid UTF-8 gives an
+/// [`Error::Io`] of the kind `InvalidData`.
+pub(crate) fn decode_svg(bytes: Vec<u8>, path: Option<&std::path::Path>) -> Result<String, Error> {
+    let utf16 = if bytes.starts_with(&[0xff, 0xfe]) || bytes.starts_with(&[0x3c, 0, 0x3f, 0]) {
+        Some("UTF-16LE")
+    } else if bytes.starts_with(&[0xfe, 0xff]) || bytes.starts_with(&[0, 0x3c, 0, 0x3f]) {
+        Some("UTF-16BE")
+    } else {
+        None
+    };
+    if let Some(encoding) = utf16 {
+        return Err(Error::UnsupportedEncoding(encoding.to_string()));
     }
+    String::from_utf8(bytes).map_err(|error| {
+        let declared = declared_encoding(&String::from_utf8_lossy(error.as_bytes()))
+            .map(|encoding| Error::UnsupportedEncoding(encoding.to_string()));
+        declared.unwrap_or_else(|| Error::Io {
+            path: path.map(std::path::Path::to_path_buf),
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, error.utf8_error()),
+        })
+    })
+}
 
-    // First, parse the SVG string into an SVG structure
-    match from_str(svg_str) {
-        Ok(svg) => {
-            let parsed: Svg = svg;
-            // Recursively construct RSX
-            Ok(rsx!(
-                svg {
-                    view_box: parsed.view_box.clone(),
-                    width: parsed.width.clone(),
-                    height: parsed.height.clone(),
-                    {parsed.elements.iter().map(|element| { from_svg_element(&element) })}
-                }
-            ))
+/// Checks `svg_str` against `limits` and its declared encoding, returning it without its byte
+/// order mark.
+fn check_svg<'a>(svg_str: &'a str, limits: &ParseLimits) -> Result<&'a str, Error> {
+    limits::check(svg_str, limits)?;
+    // Some Windows tools start UTF-8 files with a byte order mark
+    let svg_str = svg_str.strip_prefix('\u{feff}').unwrap_or(svg_str);
+    if let Some(encoding) = declared_encoding(svg_str) {
+        if !["utf-8", "utf8", "us-ascii"]
+            .iter()
+            .any(|supported| encoding.eq_ignore_ascii_case(supported))
+        {
+            return Err(Error::UnsupportedEncoding(encoding.to_string()));
         }
-        Err(e) => Err(Error::SvgParseError(e)),
     }
+    Ok(svg_str)
+}
+
+/// Parses a document that passed [`check_svg`].
+fn parse_checked(svg_str: &str) -> Result<Svg, Error> {
+    svg_types::intern::interning(|| {
+        #[cfg(feature = "fast-parse")]
+        if let Some(svg) = fast_parse::parse(svg_str) {
+            return Ok(svg);
+        }
+        deserialize(svg_str).map_err(|error| {
+            unknown_entity(&error).unwrap_or_else(|| diagnose::locate(svg_str, error))
+        })
+    })
+}
+
+/// Deserializes `svg_str` with `serde-xml-rs`, configured like [`serde_xml_rs::from_str`] but
+/// keeping the whitespace at the ends of the characters of the `text` and `tspan` elements, which
+/// separates their words: `<text>Hello <tspan>world</tspan></text>`. The whitespace-only runs,
+/// such as the indentation between elements, are still skipped.
+fn deserialize(svg_str: &str) -> Result<Svg, serde_xml_rs::Error> {
+    let config = ParserConfig::new()
+        .trim_whitespace(false)
+        .cdata_to_characters(true)
+        .ignore_comments(true)
+        .coalesce_characters(true);
+    let reader = EventReader::new_with_config(svg_str.as_bytes(), config);
+    Svg::deserialize(&mut serde_xml_rs::Deserializer::new(reader))
+}
+
+/// The [`Error::UnknownEntity`] corresponding to `error`, if the parser stopped on an entity.
+///
+/// Only the five predefined XML entities and character references are known.
+fn unknown_entity(error: &serde_xml_rs::Error) -> Option<Error> {
+    let serde_xml_rs::Error::Syntax { source } = error else {
+        return None;
+    };
+    let entity = source.msg().strip_prefix("Unexpected entity: ")?;
+    Some(Error::UnknownEntity {
+        entity: entity.to_string(),
+    })
+}
+
+/// The `encoding` of the XML declaration at the start of `svg_str`, if it has one.
+fn declared_encoding(svg_str: &str) -> Option<&str> {
+    let declaration = svg_str.trim_start().strip_prefix("<?xml")?;
+    let declaration = &declaration[..declaration.find("?>")?];
+    let value = declaration[declaration.find("encoding")? + "encoding".len()..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let value = &value[1..];
+    Some(&value[..value.find(quote)?])
+}
+
+/// Converts a parsed [`Svg`] tree to an RSX element.
+///
+/// This is the second half of [`parse_svg_to_rsx`]; see [`parse_svg`]. The conversion moves the
+/// attribute strings into the RSX, so the borrowed tree is copied first; pass it by value to
+/// [`svg_to_rsx_with_options`] to avoid the copy.
+pub fn svg_to_rsx(svg: &Svg) -> Element {
+    convert::svg_root(svg.clone(), &RsxOptions::default())
+}
+
+/// Parses an SVG string and converts it to RSX code, applying the given [`RsxOptions`].
+///
+/// The document is parsed with [`parse_svg`], transformed by the [`passes`] enabled in `options`,
+/// then converted like [`parse_svg_to_rsx`].
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::RsxOptions, parse_svg_to_rsx_with_options};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 100 100' width='100' height='100'>\
+///                <path d='M 0 0 L 100 100'/></svg>";
+/// let options = RsxOptions {
+///     id_prefix: Some("figure-1-".to_string()),
+///     ..Default::default()
+/// };
+/// let element = parse_svg_to_rsx_with_options(svg_str, &options).unwrap();
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "parse_svg_to_rsx", skip_all, fields(input_size = svg_str.len()))
+)]
+pub fn parse_svg_to_rsx_with_options(
+    svg_str: &str,
+    options: &RsxOptions,
+) -> Result<Element, Error> {
+    convert_with_options(parse_svg(svg_str)?, options)
+}
+
+/// Parses an SVG string and converts it to an RSX element with the given [`RsxOptions`], together
+/// with a [`ConversionReport`] of what had to be changed.
+///
+/// The document is parsed with [`parse_svg_lenient`], so it may hold elements that
+/// [`parse_svg_to_rsx_with_options`] rejects. When `options` has [`colors`](RsxOptions::colors),
+/// the colors without a replacement are reported as well. With the `tracing` feature, every
+/// warning is also emitted as a `warn!` event.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::RsxOptions, parse_svg_to_rsx_with_report};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <text>Hello</text><path d='M 0 0 L 10 10'/></svg>";
+/// let (element, report) = parse_svg_to_rsx_with_report(svg_str, &RsxOptions::default()).unwrap();
+/// for warning in &report.warnings {
+///     eprintln!("warning: {}", warning);
+/// }
+/// ```
+pub fn parse_svg_to_rsx_with_report(
+    svg_str: &str,
+    options: &RsxOptions,
+) -> Result<(Element, ConversionReport), Error> {
+    report_and_convert(svg_str, &ParseLimits::default(), options)
+}
+
+fn report_and_convert(
+    svg_str: &str,
+    limits: &ParseLimits,
+    options: &RsxOptions,
+) -> Result<(Element, ConversionReport), Error> {
+    let (mut svg, mut report) = parse_lenient(svg_str, limits)?;
+    if let Some(colors) = &options.colors {
+        // Checking the colors of the styles too, which `passes::apply` moves the same way
+        passes::normalize_stops(&mut svg);
+        report
+            .warnings
+            .extend(report::unmapped_colors(&svg, colors));
+    }
+    report
+        .warnings
+        .extend(report::skipped_switch_branches(&svg, options.switch));
+    let applied = passes::apply(&mut svg, options)?;
+    report.warnings.extend(applied.warnings);
+    report.merged_paths = applied.merged_paths;
+    // After the passes, which must not leave references behind
+    if cfg!(debug_assertions) {
+        report
+            .warnings
+            .extend(svg.validate_references().into_iter().map(Warning::from));
+    }
+    report.trace();
+    Ok((convert::svg_root(svg, options), report))
+}
+
+/// Parses an SVG from an untrusted source, such as an upload, and converts it to an RSX element
+/// with the given [`RsxOptions`], together with a [`ConversionReport`] of what had to be changed.
+///
+/// This is [`parse_svg_to_rsx_with_report`] with [`RsxOptions::sanitize`] enabled, with the
+/// default [`SanitizeOptions`] unless `options` sets others: the `use` elements referring to
+/// other documents, the images that are not embedded images and the paint and reference
+/// attributes with a `url(…)` outside the document are removed and reported as
+/// [`Warning::UnsafeReference`](report::Warning::UnsafeReference). The elements the parsed tree
+/// has no type for, such as the `a` links and the `script` elements, are left out as with every
+/// function of the crate.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::RsxOptions, parse_untrusted_svg_to_rsx, report::Warning};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <g><image width='1' height='1' preserveAspectRatio='none' \
+///                href='https://tracker.example/pixel.gif'/></g></svg>";
+/// let (element, report) = parse_untrusted_svg_to_rsx(svg_str, &RsxOptions::default()).unwrap();
+/// assert!(matches!(report.warnings[0], Warning::UnsafeReference { .. }));
+/// ```
+pub fn parse_untrusted_svg_to_rsx(
+    svg_str: &str,
+    options: &RsxOptions,
+) -> Result<(Element, ConversionReport), Error> {
+    let options = RsxOptions {
+        sanitize: Some(options.sanitize.clone().unwrap_or_default()),
+        ..options.clone()
+    };
+    report_and_convert(svg_str, &ParseLimits::default(), &options)
+}
+
+/// Converts a parsed [`Svg`] tree to an RSX element, applying the given [`RsxOptions`].
+///
+/// The tree is taken by value because the [`passes`] enabled in `options` modify it.
+pub fn svg_to_rsx_with_options(mut svg: Svg, options: &RsxOptions) -> Element {
+    passes::apply_lenient(&mut svg, options);
+    convert::svg_root(svg, options)
+}
+
+/// [`svg_to_rsx_with_options`], failing on the duplicate ids with [`DuplicateIds::Error`].
+fn convert_with_options(mut svg: Svg, options: &RsxOptions) -> Result<Element, Error> {
+    passes::apply(&mut svg, options)?;
+    Ok(convert::svg_root(svg, options))
 }
 
 /// Convert the Typst file to an RSX format element.
@@ -297,9 +2351,12 @@ pub fn parse_svg_to_rsx(svg_str: &str) -> Result<Element, Error> {
 /// This function takes a Typst file path, compiles it to an SVG file,
 /// Then parses the contents of the SVG file and converts them to RSX format elements.
 ///
+/// Not available on `wasm32` targets; compile the SVG ahead of time and use [`parse_svg_to_rsx`] instead.
+///
 /// # parameter
 ///
-/// - `input_typ_file` : specifies the path to the Typst file. The function will compile the file into an SVG file for processing.
+/// - `input_typ_file` : specifies the path to the Typst file, as a `&str`, a `Path` or anything else implementing
+///   `AsRef<Path>`. The function will compile the file into an SVG file for processing.
 ///
 /// # Return value
 ///
@@ -319,10 +2376,648 @@ pub fn parse_svg_to_rsx(svg_str: &str) -> Result<Element, Error> {
 ///         eprintln!("错误：{:?}",e);
 ///     }   
 /// }
+///
+/// let path = std::path::PathBuf::from("docs").join("example.typ");
+/// let _ = typst_to_rsx(&path);
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx(input_typ_file: impl AsRef<Path>) -> Result<Element, Error> {
+    typst_to_rsx_with(
+        input_typ_file,
+        &CompileOptions::default(),
+        &RsxOptions::default(),
+    )
+}
+
+/// Convert the Typst file to an RSX format element, compiling it with the given [`CompileOptions`],
+/// together with the [`CompileOutput`] of the compilation.
+///
+/// The output gives the time Typst took and the warnings it printed, for logging them. See
+/// [`typst_to_rsx`] for details.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{options::CompileOptions, typst_to_rsx_with_options};
+///
+/// let (element, output) =
+///     typst_to_rsx_with_options("example.typ", &CompileOptions::default()).unwrap();
+/// println!("compiled in {:?}", output.duration);
+/// for warning in output.stderr.lines() {
+///     eprintln!("{}", warning);
+/// }
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_with_options(
+    input_typ_file: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<(Element, CompileOutput), Error> {
+    compile_to_rsx(
+        &TypstCli,
+        input_typ_file.as_ref(),
+        options,
+        &RsxOptions::default(),
+    )
+}
+
+/// Convert the Typst file to an RSX format element, compiling it with the given [`CompileOptions`]
+/// and converting it with the given [`RsxOptions`].
+///
+/// See [`typst_to_rsx`] for details.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{
+///     options::{CompileOptions, RsxOptions},
+///     typst_to_rsx_with,
+/// };
+///
+/// // <svg class="typst-doc figure" id="fig-3" ...>
+/// let options = RsxOptions {
+///     root_class: Some("figure".to_string()),
+///     root_id: Some("fig-3".to_string()),
+///     ..Default::default()
+/// };
+/// let figure = typst_to_rsx_with("figure.typ", &CompileOptions::default(), &options).unwrap();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_with(
+    input_typ_file: impl AsRef<Path>,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<Element, Error> {
+    compile_to_rsx(
+        &TypstCli,
+        input_typ_file.as_ref(),
+        compile_options,
+        rsx_options,
+    )
+    .map(|(element, _)| element)
+}
+
+/// Compiles a Typst file to a temporary SVG with `invoker` and converts it, returning the element
+/// with the output of the compilation.
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "typst_to_rsx",
+        skip_all,
+        fields(
+            input = %input_typ_file.display(),
+            elements = tracing::field::Empty,
+            paths = tracing::field::Empty,
+            uses = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        ),
+    )
+)]
+pub(crate) fn compile_to_rsx(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<(Element, CompileOutput), Error> {
+    let _timing = trace::Timing::start();
+    let output = temp_file_path("svg");
+    let compiled = compile_checked(invoker, input_typ_file, &output, compile_options).and_then(
+        |compile_output| {
+            Ok((
+                parse_file(&output, &compile_options.limits)?,
+                compile_output,
+            ))
+        },
+    );
+    let _ = fs::remove_file(&output);
+    let (svg, compile_output) = compiled?;
+    trace::record_elements(&svg);
+    Ok((convert_with_options(svg, rsx_options)?, compile_output))
+}
+
+/// Compiles a Typst file, parses it and applies the passes enabled in `rsx_options`, leaving only
+/// the construction of the RSX to [`PreparedSvg::render`].
+///
+/// Unlike an `Element`, the result is `Send + Sync`, so this can run on another thread than the
+/// one rendering the document, or be cached across the requests of a server.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::thread;
+/// use typst_2_rsx::{
+///     options::{CompileOptions, RsxOptions},
+///     typst_to_prepared_svg,
+/// };
+///
+/// let prepared = thread::spawn(|| {
+///     typst_to_prepared_svg("report.typ", &CompileOptions::default(), &RsxOptions::default())
+/// })
+/// .join()
+/// .unwrap()
+/// .unwrap();
+/// let element = prepared.render();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_prepared_svg(
+    input_typ_file: impl AsRef<Path>,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<PreparedSvg, Error> {
+    let output = temp_file_path("svg");
+    let svg = typst_compile_checked(input_typ_file.as_ref(), &output, compile_options)
+        .and_then(|_| parse_file(&output, &compile_options.limits));
+    let _ = fs::remove_file(&output);
+    PreparedSvg::try_new(svg?, rsx_options)
+}
+
+/// Convert the Typst file to an RSX format element like [`typst_to_rsx_with`], together with a
+/// [`ConversionReport`] of what had to be changed in the compiled document.
+///
+/// See [`parse_svg_to_rsx_with_report`] for the warnings.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{
+///     options::{CompileOptions, RsxOptions},
+///     typst_to_rsx_with_report,
+/// };
+///
+/// let (element, report) =
+///     typst_to_rsx_with_report("example.typ", &CompileOptions::default(), &RsxOptions::default())
+///         .unwrap();
+/// if !report.is_empty() {
+///     eprintln!("{} elements changed", report.warnings.len());
+/// }
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_with_report(
+    input_typ_file: impl AsRef<Path>,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<(Element, ConversionReport), Error> {
+    let (content, _) = compile_to_string(&TypstCli, input_typ_file.as_ref(), compile_options)?;
+    report_and_convert(&content, &compile_options.limits, rsx_options)
+}
+
+/// Convert the Typst file to an RSX format element, together with the [`SvgStats`] of the
+/// compiled document.
+///
+/// See [`typst_to_rsx`] for details.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::typst_to_rsx_with_stats;
+///
+/// let (element, stats) = typst_to_rsx_with_stats("example.typ").unwrap();
+/// println!("{}", stats);
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_with_stats(
+    input_typ_file: impl AsRef<Path>,
+) -> Result<(Element, SvgStats), Error> {
+    let (content, _) = compile_to_string(
+        &TypstCli,
+        input_typ_file.as_ref(),
+        &CompileOptions::default(),
+    )?;
+    let svg = parse_svg(&content)?;
+    Ok((svg_to_rsx(&svg), svg.stats()))
+}
+
+/// Convert the Typst file to an RSX format element, together with the SVG compiled by Typst.
+///
+/// The SVG is returned exactly as Typst wrote it, byte for byte, for offering it as a download or
+/// rasterizing it on a server without compiling the document twice. The temporary file it was
+/// written to is removed. See [`typst_to_rsx`] for details.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::typst_to_rsx_with_svg;
+///
+/// let (element, svg_str) = typst_to_rsx_with_svg("example.typ").unwrap();
+/// std::fs::write("example.svg", svg_str).unwrap();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_with_svg(input_typ_file: impl AsRef<Path>) -> Result<(Element, String), Error> {
+    typst_to_rsx_with_svg_and_options(
+        input_typ_file,
+        &CompileOptions::default(),
+        &RsxOptions::default(),
+    )
+}
+
+/// Like [`typst_to_rsx_with_svg`], compiling with the given [`CompileOptions`] and converting with
+/// the given [`RsxOptions`], like [`typst_to_rsx_with`].
+///
+/// The SVG is the one compiled by Typst, before the [`passes`] enabled in `rsx_options`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_with_svg_and_options(
+    input_typ_file: impl AsRef<Path>,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<(Element, String), Error> {
+    compile_with_svg(
+        &TypstCli,
+        input_typ_file.as_ref(),
+        compile_options,
+        rsx_options,
+    )
+}
+
+/// Compiles a Typst file with `invoker` and converts it, returning the element with the SVG as
+/// Typst wrote it.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn compile_with_svg(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<(Element, String), Error> {
+    let (svg_str, _) = compile_to_string(invoker, input_typ_file, compile_options)?;
+    let svg = parse_svg_with_limits(&svg_str, &compile_options.limits)?;
+    Ok((convert_with_options(svg, rsx_options)?, svg_str))
+}
+
+/// Convert the Typst file to an RSX element in which the labeled figures, headings and equations
+/// can be linked to, together with an anchor for every label.
+///
+/// The positions of the labels are queried with `typst query`, and each label is given as `id` to
+/// the group drawn at its position, so that a link to `#fig-results` scrolls to the figure
+/// labeled `<fig-results>`. The matching is approximate; see [`labels::anchor_labels`]. A label
+/// that found no group has no `id` in its [`LabelAnchor`].
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::typst_to_rsx_labeled;
+///
+/// let (element, anchors) = typst_to_rsx_labeled("report.typ").unwrap();
+/// for anchor in anchors {
+///     if let Some(id) = anchor.id {
+///         println!("page {}: #{}", anchor.page, id);
+///     }
+/// }
 /// ```
-pub fn typst_to_rsx(input_typ_file: &str) -> Result<Element, Error> {
-    typst_compile(input_typ_file, "./tmp/temp.svg")?;
-    let content = read_file("./tmp/temp.svg")?;
-    let rsx = parse_svg_to_rsx(&content)?;
-    Ok(rsx)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_labeled(
+    input_typ_file: impl AsRef<Path>,
+) -> Result<(Element, Vec<LabelAnchor>), Error> {
+    compile_labeled(
+        &TypstCli,
+        input_typ_file.as_ref(),
+        &CompileOptions::default(),
+        &RsxOptions::default(),
+    )
+}
+
+/// Queries the labels of a Typst file and compiles it with `invoker`, converting it with the
+/// labels given as `id`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn compile_labeled(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<(Element, Vec<LabelAnchor>), Error> {
+    let positions = labels::query_labels(invoker, input_typ_file, compile_options)?;
+    let (content, _) = compile_to_string(invoker, input_typ_file, compile_options)?;
+    let mut svg = parse_svg_with_limits(&content, &compile_options.limits)?;
+    let anchors = labels::anchor_labels(&mut svg, &positions);
+    Ok((convert_with_options(svg, rsx_options)?, anchors))
+}
+
+/// Convert the Typst file to one RSX element per page.
+///
+/// The document is compiled to a single SVG, split with [`split_pages`], and every page is
+/// converted on its own, so that a viewer can render, lazily load or paginate them separately.
+/// A document without page groups gives a single element.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::typst_to_rsx_paged;
+///
+/// let pages = typst_to_rsx_paged("example.typ").unwrap();
+/// println!("{} pages", pages.len());
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_paged(input_typ_file: impl AsRef<Path>) -> Result<Vec<Element>, Error> {
+    typst_to_rsx_paged_with(
+        input_typ_file,
+        &CompileOptions::default(),
+        &RsxOptions::default(),
+    )
+}
+
+/// Convert the Typst file to one RSX element per page, like [`typst_to_rsx_paged`], with the given
+/// [`CompileOptions`] and [`RsxOptions`].
+///
+/// The options are applied to every page separately: each page gets its own `background`, for
+/// instance.
+///
+/// With the `rayon` feature, the pages are parsed in parallel with `parse_svg_parallel`. The
+/// RSX elements cannot leave the thread that builds them, so the conversion stays on the calling
+/// thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_paged_with(
+    input_typ_file: impl AsRef<Path>,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<Vec<Element>, Error> {
+    compile_to_pages(
+        &TypstCli,
+        input_typ_file.as_ref(),
+        compile_options,
+        rsx_options,
+    )
+}
+
+/// Compiles a Typst file with `invoker` and converts each of its pages.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn compile_to_pages(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    compile_options: &CompileOptions,
+    rsx_options: &RsxOptions,
+) -> Result<Vec<Element>, Error> {
+    let (content, _) = compile_to_string(invoker, input_typ_file, compile_options)?;
+    #[cfg(feature = "rayon")]
+    let svg = parse_svg_parallel_with_limits(&content, &compile_options.limits)?;
+    #[cfg(not(feature = "rayon"))]
+    let svg = parse_svg_with_limits(&content, &compile_options.limits)?;
+    split_pages(&svg)
+        .into_iter()
+        .map(|page| convert_with_options(page, rsx_options))
+        .collect()
+}
+
+/// Compile a Typst math expression and convert it to an RSX element that sits inline with text.
+///
+/// The expression is wrapped in a minimal document (`#set page(width: auto, height: auto, margin: 0pt)`
+/// followed by `$ expr $`), so the resulting SVG is cropped tightly around the formula. The root `svg`
+/// gets `style="vertical-align: middle"` so it lines up with the surrounding text; use
+/// [`typst_math_to_rsx_with_class`] to control the alignment from CSS instead.
+///
+/// A `$` would end the formula early and let the rest of the expression run as Typst markup or
+/// code, so an expression holding one fails with [`Error::InvalidMath`] before Typst runs. Write
+/// `\$` for a dollar sign in the formula.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::typst_math_to_rsx;
+///
+/// match typst_math_to_rsx("integral_0^1 x^2 dif x") {
+///     Ok(formula) => println!("{:?}", formula),
+///     Err(e) => eprintln!("Failed to convert: {}", e),
+/// }
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_math_to_rsx(expr: &str) -> Result<Element, Error> {
+    math_to_rsx(
+        expr,
+        None,
+        Some("vertical-align: middle"),
+        &CompileOptions::default(),
+    )
+}
+
+/// Compile a Typst math expression like [`typst_math_to_rsx`], putting `class` on the root `svg`
+/// instead of the inline `vertical-align` style.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_math_to_rsx_with_class(expr: &str, class: &str) -> Result<Element, Error> {
+    math_to_rsx(expr, Some(class), None, &CompileOptions::default())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn math_to_rsx(
+    expr: &str,
+    class: Option<&str>,
+    style: Option<&str>,
+    options: &CompileOptions,
+) -> Result<Element, Error> {
+    check_math(expr)?;
+    let input = temp_file_path("typ");
+    let output = temp_file_path("svg");
+    fs::write(
+        &input,
+        format!(
+            "#set page(width: auto, height: auto, margin: 0pt)\n$ {} $\n",
+            expr
+        ),
+    )
+    .map_err(|e| Error::io(&input, e))?;
+    let content = typst_compile_checked(&input, &output, options).and_then(|_| read_file(&output));
+    let _ = fs::remove_file(&input);
+    let _ = fs::remove_file(&output);
+    let rsx_options = RsxOptions {
+        root_class: class.map(str::to_string),
+        root_style: style.map(str::to_string),
+        ..Default::default()
+    };
+    let svg = parse_svg_with_limits(&content?, &options.limits)?;
+    Ok(svg_to_rsx_with_options(svg, &rsx_options))
+}
+
+/// Checks that `expr` has no `$` ending the formula it is wrapped in: every `$` must be escaped
+/// by an odd number of backslashes.
+#[cfg(not(target_arch = "wasm32"))]
+fn check_math(expr: &str) -> Result<(), Error> {
+    let mut backslashes = 0;
+    for (position, c) in expr.char_indices() {
+        match c {
+            '\\' => backslashes += 1,
+            '$' if backslashes % 2 == 0 => {
+                return Err(Error::InvalidMath {
+                    expr: expr.to_string(),
+                    reason: format!("unescaped `$` at byte {}, write `\\$`", position),
+                })
+            }
+            _ => backslashes = 0,
+        }
+    }
+    Ok(())
+}
+
+/// Convert the Typst file to an RSX format element, rendering any error in place of the document.
+///
+/// This is the infallible counterpart of [`typst_to_rsx`], meant for prototyping: when compilation
+/// or conversion fails, the returned element is an [`error_panel`] showing the error message,
+/// including the diagnostics Typst printed to stderr.
+///
+/// # parameter
+///
+/// - `input_typ_file` : specifies the path to the Typst file.
+/// - `class` : class name put on the error panel's root `svg`, for styling it from CSS.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::typst_to_rsx_or_error_panel;
+///
+/// let element = typst_to_rsx_or_error_panel("example.typ", "typst-error");
+/// println!("{:?}", element);
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_or_error_panel(input_typ_file: impl AsRef<Path>, class: &str) -> Element {
+    typst_to_rsx(input_typ_file).unwrap_or_else(|e| error_panel(&e, class))
+}
+
+/// Renders an error as an SVG panel, one `text` line per line of the error message.
+///
+/// The panel uses a monospace font and a red fill by default; both can be overridden from CSS
+/// through `class`.
+pub fn error_panel(error: &Error, class: &str) -> Element {
+    let message = error.to_string();
+    let lines: Vec<&str> = message.lines().collect();
+    let height = format!("{}em", lines.len() as f32 * 1.4 + 0.6);
+    rsx!(
+        svg {
+            class,
+            role: "alert",
+            width: "100%",
+            height,
+            font_family: "monospace",
+            fill: "#b00020",
+            text {
+                {lines.iter().map(|line| rsx!(tspan { x: "0.5em", dy: "1.4em", {*line} }))}
+            }
+        }
+    )
+}
+
+/// Convert the Typst file to the Rust source of an `rsx!` invocation.
+///
+/// The file is compiled to SVG and passed to [`svg_to_rsx_source`]. Writing the result to a `.rs` file
+/// from a build script or xtask embeds the document in the application without needing Typst at runtime.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::typst_to_rsx_source;
+///
+/// match typst_to_rsx_source("example.typ") {
+///     Ok(source) => std::fs::write("figure.rs", source).unwrap(),
+///     Err(e) => eprintln!("Failed to convert: {}", e),
+/// }
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_rsx_source(input_typ_file: impl AsRef<Path>) -> Result<String, Error> {
+    svg_to_rsx_source(&typst_to_svg(input_typ_file, &CompileOptions::default())?)
+}
+
+/// Compile the Typst file with the given [`CompileOptions`] and return the SVG it produced.
+///
+/// The SVG is written to a temporary file, read and removed. A failed compilation gives
+/// [`Error::TypstFailed`] carrying the Typst diagnostics, like the other `typst_to_*` functions.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{options::CompileOptions, parse_svg, typst_to_svg};
+///
+/// let svg_str = typst_to_svg("example.typ", &CompileOptions::default()).unwrap();
+/// let svg = parse_svg(&svg_str).unwrap();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn typst_to_svg(
+    input_typ_file: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<String, Error> {
+    compile_to_string(&TypstCli, input_typ_file.as_ref(), options).map(|(content, _)| content)
+}
+
+/// Convert the Typst file to a static HTML string.
+///
+/// The document is compiled and converted like [`typst_to_rsx`], then rendered with `dioxus-ssr`
+/// into `<svg>...</svg>` markup that can be embedded as-is in an HTML page, without hydrating a
+/// component tree. Attribute names are written in their SVG spelling (`fill-rule`, `viewBox`), and
+/// the `r#use` elements are written as `use`.
+///
+/// Only available with the `ssr` feature.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::typst_to_html;
+///
+/// match typst_to_html("example.typ") {
+///     Ok(html) => std::fs::write("figure.html", html).unwrap(),
+///     Err(e) => eprintln!("Failed to convert: {}", e),
+/// }
+/// ```
+#[cfg(all(feature = "ssr", not(target_arch = "wasm32")))]
+pub fn typst_to_html(input_typ_file: impl AsRef<Path>) -> Result<String, Error> {
+    let content = typst_to_svg(input_typ_file, &CompileOptions::default())?;
+    Ok(compat::render_html(parse_svg_to_rsx(&content)?))
+}
+
+/// Compile the Typst file and rasterize it to a PNG, returned as a `data:image/png;base64,...`
+/// URI.
+///
+/// This is an escape hatch for the embedded webviews that render complex SVGs poorly: the image
+/// can be shown with a plain `img`. `scale` is the number of image pixels per CSS pixel, see
+/// [`svg_to_png`]. Only available with the `raster` feature.
+///
+/// **This function requires the Typst CLI to be installed and accessible from the system's PATH.**
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::typst_to_png_data_uri;
+///
+/// let src = typst_to_png_data_uri("example.typ", 2.0).unwrap();
+/// assert!(src.starts_with("data:image/png;base64,"));
+/// ```
+#[cfg(all(feature = "raster", not(target_arch = "wasm32")))]
+pub fn typst_to_png_data_uri(
+    input_typ_file: impl AsRef<Path>,
+    scale: f32,
+) -> Result<String, Error> {
+    Ok(compile_to_png(&TypstCli, input_typ_file.as_ref(), scale)?.data_uri())
+}
+
+/// Compile the Typst file, rasterize it to a PNG and return an `img` element showing it, with the
+/// `width` and `height` of the SVG.
+///
+/// See [`typst_to_png_data_uri`] for details. Only available with the `raster` feature.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::typst_to_rsx_raster;
+///
+/// // Twice the pixels, for high-density screens
+/// let element = typst_to_rsx_raster("example.typ", 2.0).unwrap();
+/// ```
+#[cfg(all(feature = "raster", not(target_arch = "wasm32")))]
+pub fn typst_to_rsx_raster(input_typ_file: impl AsRef<Path>, scale: f32) -> Result<Element, Error> {
+    Ok(compile_to_png(&TypstCli, input_typ_file.as_ref(), scale)?.to_rsx())
+}
+
+/// Compiles a Typst file with `invoker` and rasterizes it.
+#[cfg(all(feature = "raster", not(target_arch = "wasm32")))]
+pub(crate) fn compile_to_png(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    scale: f32,
+) -> Result<Png, Error> {
+    let (svg_str, _) = compile_to_string(invoker, input_typ_file, &CompileOptions::default())?;
+    svg_to_png(&svg_str, scale)
 }
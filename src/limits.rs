@@ -0,0 +1,285 @@
+//! Resource limits applied to an SVG before it is parsed.
+//!
+//! Converting documents from untrusted sources (user uploads on a server, for instance) should
+//! not let a single document exhaust the memory or the stack of the process. [`ParseLimits`]
+//! bounds the size of the input, the number of elements, the length of the attribute values and
+//! the nesting depth; the SVG is scanned against them before the parser runs.
+
+use std::fmt;
+
+use crate::error::Error;
+
+/// Default for the maximum nesting depth of the parsed elements, see [`ParseLimits`].
+///
+/// Typst documents rarely nest more than a few dozen groups; this leaves room for them while
+/// keeping the parsing of a debug build within the 2 MiB stack of a spawned thread.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Limits checked by [`parse_svg_with_limits`](crate::parse_svg_with_limits) before parsing.
+///
+/// The defaults accept any document Typst produces for a book-sized input, including embedded
+/// images, while rejecting inputs crafted to be expensive. [`ParseLimits::unlimited`] disables
+/// every check.
+///
+/// # Field
+///
+/// - `max_input_bytes` : size of the whole SVG, in bytes. 64 MiB by default.
+/// - `max_elements` : number of elements, the root included. 1 000 000 by default.
+/// - `max_attribute_len` : length of a single attribute value, in bytes. Embedded images are
+///   attribute values, hence the default of 16 MiB.
+/// - `max_depth` : nesting level of the deepest element, the children of the root `svg` being at
+///   level 1. [`DEFAULT_MAX_DEPTH`] by default; exceeding it gives [`Error::TooDeep`] rather than
+///   [`Error::LimitExceeded`].
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{error::Error, limits::ParseLimits, parse_svg_with_limits};
+///
+/// let limits = ParseLimits {
+///     max_elements: 2,
+///     ..Default::default()
+/// };
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <path d='M 0 0'/><path d='M 1 1'/></svg>";
+/// assert!(matches!(
+///     parse_svg_with_limits(svg_str, &limits),
+///     Err(Error::LimitExceeded { limit: 2, actual: 3, .. })
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseLimits {
+    pub max_input_bytes: usize,
+
+    pub max_elements: usize,
+
+    pub max_attribute_len: usize,
+
+    pub max_depth: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_input_bytes: 64 << 20,
+            max_elements: 1_000_000,
+            max_attribute_len: 16 << 20,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Limits that accept every document, for trusted input.
+    ///
+    /// Without a depth limit, a deeply nested document overflows the stack of the parser.
+    pub fn unlimited() -> Self {
+        ParseLimits {
+            max_input_bytes: usize::MAX,
+            max_elements: usize::MAX,
+            max_attribute_len: usize::MAX,
+            max_depth: usize::MAX,
+        }
+    }
+}
+
+/// The limit of [`ParseLimits`] a document exceeded, in [`Error::LimitExceeded`].
+///
+/// # Variant
+///
+/// - `InputBytes` : `max_input_bytes`.
+/// - `Elements` : `max_elements`.
+/// - `AttributeLength` : `max_attribute_len`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    InputBytes,
+
+    Elements,
+
+    AttributeLength,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LimitKind::InputBytes => "input size in bytes",
+            LimitKind::Elements => "number of elements",
+            LimitKind::AttributeLength => "attribute length in bytes",
+        })
+    }
+}
+
+/// Checks `svg_str` against `limits`, before the document is handed to the recursive parser.
+///
+/// This is a lightweight scan of the tags, not a validation: malformed documents are left for the
+/// parser to report.
+pub(crate) fn check(svg_str: &str, limits: &ParseLimits) -> Result<(), Error> {
+    exceeds(LimitKind::InputBytes, limits.max_input_bytes, svg_str.len())?;
+    let bytes = svg_str.as_bytes();
+    // Elements currently open, the root included
+    let mut open = 0usize;
+    let mut elements = 0usize;
+    let mut i = 0;
+    while let Some(offset) = bytes[i..].iter().position(|&byte| byte == b'<') {
+        i += offset + 1;
+        let rest = &svg_str[i..];
+        if rest.starts_with('/') {
+            open = open.saturating_sub(1);
+            i += rest.find('>').map_or(rest.len(), |end| end + 1);
+        } else if let Some(comment) = rest.strip_prefix("!--") {
+            i += 3 + comment.find("-->").map_or(comment.len(), |end| end + 3);
+        } else if let Some(cdata) = rest.strip_prefix("![CDATA[") {
+            i += 8 + cdata.find("]]>").map_or(cdata.len(), |end| end + 3);
+        } else if rest.starts_with('!') || rest.starts_with('?') {
+            i += rest.find('>').map_or(rest.len(), |end| end + 1);
+        } else {
+            // The root is at level 0, and this element one level below the open ones
+            if open > limits.max_depth {
+                return Err(Error::TooDeep(limits.max_depth));
+            }
+            elements += 1;
+            exceeds(LimitKind::Elements, limits.max_elements, elements)?;
+            let (end, longest_value) = scan_tag(rest.as_bytes());
+            exceeds(
+                LimitKind::AttributeLength,
+                limits.max_attribute_len,
+                longest_value,
+            )?;
+            if !rest[..end].ends_with("/>") {
+                open += 1;
+            }
+            i += end;
+        }
+    }
+    Ok(())
+}
+
+fn exceeds(kind: LimitKind, limit: usize, actual: usize) -> Result<(), Error> {
+    if actual > limit {
+        Err(Error::LimitExceeded {
+            kind,
+            limit,
+            actual,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// The length of a start tag up to and including its `>`, skipping the quoted attribute values,
+/// and the length of its longest attribute value.
+pub(crate) fn scan_tag(tag: &[u8]) -> (usize, usize) {
+    let mut quote = None;
+    let mut longest_value = 0;
+    for (index, &byte) in tag.iter().enumerate() {
+        match (quote, byte) {
+            (None, b'"' | b'\'') => quote = Some((byte, index)),
+            (Some((open, start)), _) if open == byte => {
+                longest_value = longest_value.max(index - start - 1);
+                quote = None;
+            }
+            (None, b'>') => return (index + 1, longest_value),
+            _ => {}
+        }
+    }
+    (tag.len(), longest_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_svg, parse_svg_with_limits};
+
+    fn nested(depth: usize) -> String {
+        format!(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'>{}<path d='M 0 0'/>{}</svg>",
+            "<g class='a>b'>".repeat(depth),
+            "</g>".repeat(depth)
+        )
+    }
+
+    fn with_depth(max_depth: usize) -> ParseLimits {
+        ParseLimits {
+            max_depth,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn depth_test() {
+        let error = parse_svg(&nested(10_000)).unwrap_err();
+        assert!(matches!(error, Error::TooDeep(128)));
+
+        // The path sits one level below the deepest group
+        let svg = parse_svg(&nested(127)).unwrap();
+        assert_eq!(svg.stats().max_depth, 128);
+        assert!(parse_svg(&nested(128)).is_err());
+        assert!(parse_svg_with_limits(&nested(10), &with_depth(11)).is_ok());
+        assert!(parse_svg_with_limits(&nested(2), &with_depth(2)).is_err());
+    }
+
+    #[test]
+    fn markup_test() {
+        let svg_str = "<?xml version='1.0'?><!-- <g><g><g> -->\
+                       <svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'>\
+                       <g><path d='M 0 0'/></g><g/></svg>";
+        assert!(parse_svg_with_limits(svg_str, &with_depth(2)).is_ok());
+        assert!(matches!(
+            parse_svg_with_limits(svg_str, &with_depth(1)),
+            Err(Error::TooDeep(1))
+        ));
+    }
+
+    #[test]
+    fn limits_test() {
+        let svg_str = std::fs::read_to_string("./test/expected.svg").unwrap();
+        let limited = |limits: ParseLimits| match parse_svg_with_limits(&svg_str, &limits) {
+            Err(Error::LimitExceeded {
+                kind,
+                limit,
+                actual,
+            }) => Some((kind, limit, actual)),
+            Err(error) => panic!("unexpected error {}", error),
+            Ok(_) => None,
+        };
+        assert_eq!(limited(ParseLimits::default()), None);
+        assert_eq!(limited(ParseLimits::unlimited()), None);
+
+        let (kind, _, actual) = limited(ParseLimits {
+            max_input_bytes: 1000,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!((kind, actual), (LimitKind::InputBytes, svg_str.len()));
+
+        let (kind, limit, actual) = limited(ParseLimits {
+            max_elements: 10,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!((kind, limit, actual), (LimitKind::Elements, 10, 11));
+
+        let (kind, limit, _) = limited(ParseLimits {
+            max_attribute_len: 64,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!((kind, limit), (LimitKind::AttributeLength, 64));
+    }
+
+    #[test]
+    fn message_test() {
+        let error = parse_svg_with_limits(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'/>",
+            &ParseLimits {
+                max_attribute_len: 8,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "SVG exceeds the limit on the attribute length in bytes: 9 > 8"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! A machine-readable record of the files written by the build helpers, for bundlers and
+//! incremental builds.
+//!
+//! [`compile_dir`](crate::build::compile_dir) writes a [`Manifest`] to `typst_manifest.json` in its
+//! output directory, listing for every input document the hash of its content and the files
+//! generated from it. [`compile_dir_incremental`](crate::build::compile_dir_incremental) reads it
+//! back to skip the documents that did not change. The other helpers writing files,
+//! [`write_pages`](crate::build::write_pages) and [`extract_images`](crate::passes::extract_images),
+//! return or describe entries to [`insert`](Manifest::insert) in a manifest of their own.
+//!
+//! ```json
+//! {
+//!   "entries": {
+//!     "typst/figure.typ": {
+//!       "hash": "5f3c0b3d9e8a7c21",
+//!       "outputs": [{ "path": "out/figure.rs", "role": "module" }],
+//!       "generated_at": 1760600000
+//!     }
+//!   }
+//! }
+//! ```
+
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, options::CompileOptions};
+
+/// The files generated from a set of input documents.
+///
+/// # Field
+///
+/// - `entries` : one entry per input, keyed by the path of the input.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::manifest::{input_hash, Manifest, ManifestEntry, OutputRole};
+/// use typst_2_rsx::options::CompileOptions;
+///
+/// let mut manifest = Manifest::load("public/manifest.json").unwrap();
+/// let hash = input_hash("slides.typ".as_ref(), &CompileOptions::default()).unwrap();
+/// if !manifest.is_up_to_date("slides.typ".as_ref(), &hash) {
+///     // ... write `public/slides.svg`
+///     let entry = ManifestEntry::new(hash).with_output("public/slides.svg", OutputRole::Page);
+///     manifest.insert("slides.typ", entry);
+///     manifest.save("public/manifest.json").unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub entries: BTreeMap<PathBuf, ManifestEntry>,
+}
+
+/// The files generated from one input document.
+///
+/// # Field
+///
+/// - `hash` : the hash of the input the outputs were generated from, see [`input_hash`].
+/// - `outputs` : the files generated, with their role.
+/// - `generated_at` : when the outputs were generated, in seconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub hash: String,
+
+    pub outputs: Vec<Output>,
+
+    pub generated_at: u64,
+}
+
+/// A file generated from an input document.
+///
+/// # Field
+///
+/// - `path` : the path of the file.
+/// - `role` : what the file holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Output {
+    pub path: PathBuf,
+
+    pub role: OutputRole,
+}
+
+/// What a generated file holds.
+///
+/// # Variant
+///
+/// - `Module` : Rust source generated by [`compile_dir`](crate::build::compile_dir).
+/// - `Page` : the SVG of one page of a document.
+/// - `Image` : an image extracted from a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputRole {
+    Module,
+
+    Page,
+
+    Image,
+}
+
+impl Manifest {
+    /// The name of the manifest written by [`compile_dir`](crate::build::compile_dir) in its
+    /// output directory.
+    pub const FILE_NAME: &'static str = "typst_manifest.json";
+
+    /// Reads a manifest written by [`save`](Manifest::save).
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(Manifest)` : the manifest, empty if the file does not exist, as before a first build.
+    /// - `Err(Error)` : the file cannot be read, or is not a manifest.
+    pub fn load(path: impl AsRef<Path>) -> Result<Manifest, Error> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(e) => Err(Error::io(path, e)),
+        }
+    }
+
+    /// Writes the manifest as indented JSON, creating the missing parent directories.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).map_err(|e| Error::io(parent, e))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json + "\n").map_err(|e| Error::io(path, e))
+    }
+
+    /// Records the outputs generated from `input`, replacing its previous entry.
+    pub fn insert(&mut self, input: impl Into<PathBuf>, entry: ManifestEntry) {
+        self.entries.insert(input.into(), entry);
+    }
+
+    /// Adds the entries of `other`, such as the manifest of another build step. For an input in
+    /// both manifests, the most recently generated entry is kept.
+    pub fn merge(&mut self, other: Manifest) {
+        for (input, entry) in other.entries {
+            match self.entries.get(&input) {
+                Some(existing) if existing.generated_at > entry.generated_at => {}
+                _ => {
+                    self.entries.insert(input, entry);
+                }
+            }
+        }
+    }
+
+    /// Whether the outputs of `input` were generated from the content with this `hash` and all
+    /// still exist, so that generating them again can be skipped.
+    pub fn is_up_to_date(&self, input: &Path, hash: &str) -> bool {
+        self.entries.get(input).is_some_and(|entry| {
+            entry.hash == hash && entry.outputs.iter().all(|output| output.path.exists())
+        })
+    }
+}
+
+impl ManifestEntry {
+    /// An entry for outputs generated now from the content with this `hash`, without outputs yet.
+    pub fn new(hash: impl Into<String>) -> ManifestEntry {
+        let generated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        ManifestEntry {
+            hash: hash.into(),
+            outputs: Vec::new(),
+            generated_at,
+        }
+    }
+
+    /// Adds an output to the entry.
+    pub fn with_output(mut self, path: impl Into<PathBuf>, role: OutputRole) -> ManifestEntry {
+        self.outputs.push(Output {
+            path: path.into(),
+            role,
+        });
+        self
+    }
+}
+
+/// Hashes the content of an input document together with the options it is compiled with, so
+/// that changing either makes the outputs out of date.
+///
+/// The files the document imports or reads are not part of the hash. The hash may also change
+/// between versions of Rust, which only makes the next build regenerate everything.
+pub fn input_hash(input: &Path, options: &CompileOptions) -> Result<String, Error> {
+    let content = fs::read(input).map_err(|e| Error::io(input, e))?;
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    options.hash(&mut hasher);
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::temp_file_path;
+
+    #[test]
+    fn manifest_test() {
+        let dir = temp_file_path("manifest");
+        let path = dir.join("nested").join(Manifest::FILE_NAME);
+        assert_eq!(Manifest::load(&path).unwrap(), Manifest::default());
+
+        let input = dir.join("figure.typ");
+        let output = dir.join("figure.svg");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&input, "= Figure").unwrap();
+        let hash = input_hash(&input, &CompileOptions::default()).unwrap();
+        let mut manifest = Manifest::default();
+        manifest.insert(
+            &input,
+            ManifestEntry::new(hash.clone()).with_output(&output, OutputRole::Page),
+        );
+        // The output is missing
+        assert!(!manifest.is_up_to_date(&input, &hash));
+        fs::write(&output, "<svg/>").unwrap();
+        assert!(manifest.is_up_to_date(&input, &hash));
+
+        let options = CompileOptions {
+            inputs: vec![("theme".to_string(), "dark".to_string())],
+            ..Default::default()
+        };
+        assert!(!manifest.is_up_to_date(&input, &input_hash(&input, &options).unwrap()));
+        fs::write(&input, "= Figure 2").unwrap();
+        let changed = input_hash(&input, &CompileOptions::default()).unwrap();
+        assert!(!manifest.is_up_to_date(&input, &changed));
+
+        manifest.save(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains("\"role\": \"page\""));
+        assert_eq!(Manifest::load(&path).unwrap(), manifest);
+
+        fs::write(&path, "[]").unwrap();
+        assert!(matches!(Manifest::load(&path), Err(Error::JsonError(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn merge_test() {
+        let entry = |hash: &str, generated_at| ManifestEntry {
+            hash: hash.to_string(),
+            outputs: Vec::new(),
+            generated_at,
+        };
+        let mut manifest = Manifest::default();
+        manifest.insert("a.typ", entry("a1", 10));
+        manifest.insert("b.typ", entry("b1", 10));
+        let mut other = Manifest::default();
+        other.insert("a.typ", entry("a0", 5));
+        other.insert("b.typ", entry("b2", 20));
+        other.insert("c.typ", entry("c1", 20));
+        manifest.merge(other);
+        let hashes: Vec<_> = manifest
+            .entries
+            .values()
+            .map(|entry| entry.hash.as_str())
+            .collect();
+        assert_eq!(hashes, ["a1", "b2", "c1"]);
+    }
+}
@@ -0,0 +1,640 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use dioxus::prelude::{EventHandler, MouseEvent};
+
+use crate::{
+    limits::ParseLimits,
+    svg_types::{color::Color, BBox, Length},
+};
+
+/// Options forwarded to the Typst CLI when compiling a document.
+///
+/// All fields are optional; `CompileOptions::default()` compiles exactly like a plain
+/// `typst compile input.typ output.svg` invocation.
+///
+/// # Field
+///
+/// - `root` : project root passed as `--root`, which limits the files the document may import or read.
+/// - `font_paths` : extra font directories, each passed as `--font-path`.
+/// - `inputs` : `sys.inputs` key/value pairs, each passed as `--input key=value`.
+/// - `limits` : the [`ParseLimits`] the compiled SVG is checked against before it is parsed.
+/// - `timeout` : how long the Typst CLI may run before it is killed and the compilation fails with
+///   [`Error::TypstTimeout`](crate::error::Error::TypstTimeout). `None` waits as long as it takes.
+/// - `text_mode` : how the text is exported; [`TextMode::Outlined`], the default, leaves it to the
+///   glyph outlines of Typst, [`TextMode::Selectable`] lays selectable text over them.
+/// - `retries` : how many more times the Typst CLI is started when starting it fails for a
+///   transient reason (`WouldBlock`, `Interrupted`, `ExecutableFileBusy`), with a short backoff.
+///   A compilation that fails is never retried, it would fail the same way. After the last
+///   attempt the error is an [`Error::RetriesExhausted`](crate::error::Error::RetriesExhausted).
+///   `0`, the default, tries once.
+/// - `dioxus_path` : the path of the Dioxus crate in the code generated by
+///   [`build`](crate::build), for an application depending on it under another name, such as
+///   `"dioxus05"` for `dioxus05 = { package = "dioxus", version = "0.5" }`. `None` imports it
+///   from `::dioxus`.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::options::CompileOptions;
+///
+/// let options = CompileOptions {
+///     font_paths: vec!["./fonts".into()],
+///     inputs: vec![("theme".to_string(), "dark".to_string())],
+///     ..Default::default()
+/// };
+/// assert!(options.root.is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CompileOptions {
+    pub root: Option<PathBuf>,
+
+    pub font_paths: Vec<PathBuf>,
+
+    pub inputs: Vec<(String, String)>,
+
+    pub limits: ParseLimits,
+
+    pub timeout: Option<Duration>,
+
+    pub text_mode: TextMode,
+
+    pub retries: u8,
+
+    pub dioxus_path: Option<String>,
+}
+
+/// How the text of a compiled document is exported.
+///
+/// # Variant
+///
+/// - `Outlined` : every glyph is a symbol drawn as a path and placed by a `use`, which looks the
+///   same everywhere without the fonts, but cannot be selected, searched or read by a screen
+///   reader. This is the default.
+/// - `Selectable` : the glyphs are drawn the same, and the words of the document are laid over
+///   them as transparent `text` elements, one per line, which can be selected, copied and
+///   searched. The Typst CLI only exports outlined text, so the words and their positions are
+///   asked to `typst query` before compiling; see [`selectable`](crate::selectable).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TextMode {
+    #[default]
+    Outlined,
+
+    Selectable,
+}
+
+impl CompileOptions {
+    /// Returns the extra command line arguments for `typst compile`, in the order they are passed.
+    pub fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let Some(root) = &self.root {
+            args.push("--root".into());
+            args.push(root.clone().into_os_string());
+        }
+        for font_path in &self.font_paths {
+            args.push("--font-path".into());
+            args.push(font_path.clone().into_os_string());
+        }
+        for (key, value) in &self.inputs {
+            args.push("--input".into());
+            args.push(format!("{}={}", key, value).into());
+        }
+        args
+    }
+}
+
+/// Options controlling how a parsed SVG is turned into RSX.
+///
+/// `RsxOptions::default()` converts the document unchanged, exactly like [`parse_svg_to_rsx`](crate::parse_svg_to_rsx).
+///
+/// # Field
+///
+/// - `id_prefix` : prepended to every `id` in the document and to every local reference to it
+///   (`href="#id"`, `url(#id)`). Typst names the glyph symbols of every document the same way, so
+///   when several converted documents are rendered on one page, each needs its own prefix for its
+///   `use` elements to resolve to its own glyphs. See [`RsxOptions::unique_id_prefix`].
+/// - `sizing` : how the `width` and `height` of the root `svg` are emitted; see [`Sizing`].
+/// - `region` : shows only this region of the document, in the coordinates of its `viewBox`, and
+///   removes the elements outside of it; see [`crop_to_region`](crate::passes::crop_to_region).
+/// - `root_class` : class added to the root `svg`, after the one emitted by Typst
+///   (`typst-doc figure`), for targeting the document from CSS.
+/// - `root_id` : `id` of the root `svg`.
+/// - `root_style` : inline `style` of the root `svg`.
+/// - `flatten_transforms` : applies the transforms of the groups to their content and removes
+///   them; see [`flatten_transforms`](crate::passes::flatten_transforms).
+/// - `simplify` : removes the groups without attributes and the empty optional attributes; see
+///   [`simplify`](crate::passes::simplify).
+/// - `merge_paths` : merges the consecutive paths with the same styling into one; see
+///   [`merge_paths`](crate::passes::merge_paths).
+/// - `path_precision` : rounds the path data to this many decimals and shortens it; see
+///   [`minify_paths`](crate::passes::minify_paths).
+/// - `colors` : replacements for the colors used by the document; see [`ColorMap`].
+/// - `dark_mode` : swaps near-black and near-white colors for display on a dark background,
+///   after `colors` was applied; see [`DarkMode`].
+/// - `background` : fills the page with this color, behind the content, which is otherwise
+///   transparent; see [`add_background`](crate::passes::add_background).
+/// - `prune_unused_defs` : removes the symbols no element refers to; see
+///   [`prune_unused_defs`](crate::passes::prune_unused_defs).
+/// - `dedupe_symbols` : merges the symbols with the same content; see
+///   [`dedupe_symbols`](crate::passes::dedupe_symbols).
+/// - `strip_presentation_attributes` : removes the fills and strokes, so that the document is
+///   styled from CSS; see [`strip_presentation_attributes`](crate::passes::strip_presentation_attributes).
+///   The removed attributes are left out of the RSX even with `empty_absent_attributes`.
+/// - `duplicate_ids` : checks that no two elements carry the same `id`, and fails, warns or
+///   renames the later ones as [`DuplicateIds`] says; see
+///   [`resolve_duplicate_ids`](crate::passes::resolve_duplicate_ids). The ids are not checked by
+///   default.
+/// - `sanitize` : removes the `use` and `image` elements referring to anything but the document
+///   itself and embedded images, for documents from untrusted sources; see
+///   [`sanitize_references`](crate::passes::sanitize_references) and
+///   [`parse_untrusted_svg_to_rsx`](crate::parse_untrusted_svg_to_rsx).
+/// - `on_click` : a click handler attached to the elements matched by the [`Selector`], for
+///   making parts of a document interactive.
+/// - `element_attributes` : `tabindex` and `pointer-events` values set on the elements matched by
+///   each [`Selector`], over the values of the document; see [`ElementAttributes`].
+/// - `rendering_hint` : a rendering hint set on the `svg`, `g`, `path` and `text` elements matched
+///   by the [`Selector`], over the hints of the document, such as `"crispEdges"` for the hairline
+///   rules of tables. It is set as the `shape-rendering` of the paths, the `text-rendering` of the
+///   texts and both on the groups and the root, except on the property that does not accept it:
+///   `crispEdges` is never a `text-rendering`, nor `optimizeLegibility` a `shape-rendering`. A hint
+///   is a value explicitly set, so it is always emitted, whatever `empty_absent_attributes` and
+///   `strip_presentation_attributes` say.
+/// - `switch` : which child of each `switch` element is rendered; see [`SwitchMode`].
+/// - `keys` : gives every element a `key` unique among its siblings, from its `id` or else from a
+///   hash of its attributes. When a document is converted again after an edit, the elements that
+///   did not change keep their key, so Dioxus patches only the changed ones instead of the
+///   siblings after them.
+/// - `empty_absent_attributes` : renders some of the attributes missing from the SVG as empty
+///   strings (`class: ""`, `fill: ""`), as versions up to 0.2 did, instead of leaving them out of
+///   the RSX. An empty `fill` is an invalid value that some renderers apply instead of inheriting
+///   the fill of the parent, so this is only meant for comparing with an older output.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{
+///     options::{RsxOptions, Sizing},
+///     parse_svg_to_rsx_with_options,
+/// };
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <path d='M 0 0 L 10 10'/></svg>";
+/// let options = RsxOptions {
+///     id_prefix: Some(RsxOptions::unique_id_prefix()),
+///     sizing: Sizing::Responsive,
+///     ..Default::default()
+/// };
+/// let element = parse_svg_to_rsx_with_options(svg_str, &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RsxOptions {
+    pub id_prefix: Option<String>,
+
+    pub sizing: Sizing,
+
+    pub region: Option<BBox>,
+
+    pub root_class: Option<String>,
+
+    pub root_id: Option<String>,
+
+    pub root_style: Option<String>,
+
+    pub flatten_transforms: bool,
+
+    pub simplify: bool,
+
+    pub merge_paths: bool,
+
+    pub path_precision: Option<u8>,
+
+    pub colors: Option<ColorMap>,
+
+    pub dark_mode: Option<DarkMode>,
+
+    pub background: Option<String>,
+
+    pub prune_unused_defs: bool,
+
+    pub dedupe_symbols: bool,
+
+    pub strip_presentation_attributes: bool,
+
+    pub keys: bool,
+
+    pub empty_absent_attributes: bool,
+
+    pub duplicate_ids: Option<DuplicateIds>,
+
+    pub sanitize: Option<SanitizeOptions>,
+
+    pub on_click: Option<(Selector, EventHandler<MouseEvent>)>,
+
+    pub element_attributes: Vec<(Selector, ElementAttributes)>,
+
+    pub rendering_hint: Option<(Selector, String)>,
+
+    pub switch: SwitchMode,
+}
+
+impl RsxOptions {
+    /// Returns an id prefix that differs from every other prefix returned in this process.
+    pub fn unique_id_prefix() -> String {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        format!("typst{}-", COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// What to do when several elements of a document carry the same `id`, for
+/// [`RsxOptions::duplicate_ids`].
+///
+/// In every mode, the first element keeps its `id`.
+///
+/// # Variant
+///
+/// - `Error` : fail with [`Error::DuplicateIds`](crate::error::Error::DuplicateIds), listing the
+///   duplicated ids. The functions that cannot fail, such as
+///   [`svg_to_rsx_with_options`](crate::svg_to_rsx_with_options) and
+///   [`PreparedSvg::new`](crate::PreparedSvg::new), handle the ids as with `Warn`.
+/// - `Warn` : remove the `id` from the later elements, and the later symbols, reporting each as a
+///   [`Warning::DuplicateId`](crate::report::Warning::DuplicateId).
+/// - `Rename` : suffix the `id` of the later elements (`glyph0-1`) and point at them the
+///   references that can only mean them, reporting each as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicateIds {
+    Error,
+
+    Warn,
+
+    Rename,
+}
+
+/// Which child of a [`Switch`](crate::svg_types::Switch) is rendered, for [`RsxOptions::switch`].
+///
+/// The conditions of the children cannot be evaluated, so the other children are left out of the
+/// RSX, and reported as [`Warning::SkippedSwitchBranch`](crate::report::Warning::SkippedSwitchBranch)
+/// by the conversions with a report.
+///
+/// # Variant
+///
+/// - `First` : the first child, as a browser supporting every condition would. A first child that
+///   the tree cannot hold, such as a `foreignObject`, renders nothing. This is the default.
+/// - `FirstSupported` : the first child that the tree can hold, such as the fallback drawn in SVG
+///   after a `foreignObject`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SwitchMode {
+    #[default]
+    First,
+
+    FirstSupported,
+}
+
+/// How the root `svg` of a converted document is sized.
+///
+/// Typst emits a fixed `width` and `height` (in `pt`) on the root `svg`. The `viewBox` is kept in
+/// every mode, so the content always scales with the element.
+///
+/// # Variant
+///
+/// - `Fixed` : keep the `width` and `height` emitted by Typst. This is the default.
+/// - `Responsive` : leave `width` and `height` out, so the figure fills its container's width
+///   and keeps its aspect ratio.
+/// - `Scale(f32)` : multiply `width` and `height` by the factor, keeping their unit
+///   (`"20pt"` scaled by `1.5` becomes `"30pt"`). Values without a numeric part are kept as is.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Sizing {
+    #[default]
+    Fixed,
+
+    Responsive,
+
+    Scale(f32),
+}
+
+impl Sizing {
+    /// Returns the value to emit for a `width` or `height` attribute, or `None` to leave it out.
+    pub fn apply(&self, length: &str) -> Option<String> {
+        match self {
+            Sizing::Fixed => Some(length.to_string()),
+            Sizing::Responsive => None,
+            Sizing::Scale(factor) => Some(Length::from(length).scale(*factor as f64).to_string()),
+        }
+    }
+}
+
+/// A color replacement callback, see [`ColorMap::Callback`].
+pub type ColorFn = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Designates the elements of a converted document that receive an event handler or
+/// [`ElementAttributes`].
+///
+/// # Variant
+///
+/// - `Root` : the root `svg` element.
+/// - `Id(String)` : the `g` or `path` with this `id`.
+/// - `Class(String)` : every `g` or `path` whose `class` list contains this class.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "dioxus-0-5")]
+/// # extern crate dioxus05 as dioxus;
+/// use dioxus::prelude::*;
+/// use typst_2_rsx::{
+///     options::{RsxOptions, Selector},
+///     parse_svg_to_rsx_with_options,
+/// };
+///
+/// #[component]
+/// fn Diagram(svg: String) -> Element {
+///     let mut clicks = use_signal(|| 0);
+///     let options = RsxOptions {
+///         on_click: Some((
+///             Selector::Id("step-3".to_string()),
+///             EventHandler::new(move |_| clicks += 1),
+///         )),
+///         ..Default::default()
+///     };
+///     parse_svg_to_rsx_with_options(&svg, &options).unwrap_or_else(|e| rsx!("{e}"))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Selector {
+    Root,
+
+    Id(String),
+
+    Class(String),
+}
+
+impl Selector {
+    /// Whether an element (other than the root) with these attributes is selected.
+    pub fn matches(&self, id: Option<&str>, class: Option<&str>) -> bool {
+        match self {
+            Selector::Root => false,
+            Selector::Id(selected) => id == Some(selected.as_str()),
+            Selector::Class(selected) => {
+                class.is_some_and(|class| class.split_whitespace().any(|class| class == selected))
+            }
+        }
+    }
+}
+
+/// Attributes for the interactivity of the elements matched by a [`Selector`], set during the
+/// conversion with [`RsxOptions::element_attributes`].
+///
+/// An absent value leaves the attribute of the element as it is in the document. When several
+/// entries match an element, the last one setting an attribute wins.
+///
+/// # Field
+///
+/// - `tabindex` : makes the element focusable with the keyboard (`"0"`), or only from scripts
+///   (`"-1"`).
+/// - `pointer_events` : `"none"` lets the clicks through the element, to the ones below it, as
+///   for a decorative layer over an interactive diagram.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::options::{ElementAttributes, RsxOptions, Selector};
+///
+/// let options = RsxOptions {
+///     element_attributes: vec![
+///         (
+///             Selector::Class("step".to_string()),
+///             ElementAttributes {
+///                 tabindex: Some("0".to_string()),
+///                 ..Default::default()
+///             },
+///         ),
+///         (
+///             Selector::Id("grid".to_string()),
+///             ElementAttributes {
+///                 pointer_events: Some("none".to_string()),
+///                 ..Default::default()
+///             },
+///         ),
+///     ],
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ElementAttributes {
+    pub tabindex: Option<String>,
+
+    pub pointer_events: Option<String>,
+}
+
+/// Replacements for the paint colors of a document, applied by [`recolor`](crate::passes::recolor).
+///
+/// The replacements are looked up for the `fill` and `stroke` values found in the tree. Values
+/// without a replacement are kept as they are, and `none` and `currentColor` are never looked up,
+/// so a catch-all callback cannot make transparent shapes visible by accident.
+///
+/// # Variant
+///
+/// - `Map(HashMap<String, String>)` : replacements such as `"#000000"` to `"var(--ink)"`. A value
+///   without an exact match uses the entry whose key denotes the same [`Color`], so `"#000"`,
+///   `"black"` and `"rgb(0, 0, 0)"` are all replaced by the entry for `"#000000"`.
+/// - `Callback(ColorFn)` : computes the replacement of a value,
+///   returning `None` to keep it.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::options::ColorMap;
+///
+/// let colors = ColorMap::from([("#000000", "var(--ink)"), ("#ffffff", "transparent")]);
+/// assert_eq!(colors.get("#000000").as_deref(), Some("var(--ink)"));
+/// assert_eq!(colors.get("#FFF").as_deref(), Some("transparent"));
+/// assert_eq!(colors.get("#ff0000"), None);
+///
+/// let colors = ColorMap::from_fn(|color| color.starts_with('#').then(|| "currentColor".to_string()));
+/// assert_eq!(colors.get("#123456").as_deref(), Some("currentColor"));
+/// assert_eq!(colors.get("none"), None);
+/// ```
+#[derive(Clone)]
+pub enum ColorMap {
+    Map(HashMap<String, String>),
+
+    Callback(ColorFn),
+}
+
+impl ColorMap {
+    /// Creates a color map computing replacements with `f`.
+    pub fn from_fn(f: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        ColorMap::Callback(Arc::new(f))
+    }
+
+    /// Returns the replacement for `color`, or `None` to keep it.
+    pub fn get(&self, color: &str) -> Option<String> {
+        let parsed = color.parse::<Color>().ok();
+        if matches!(parsed, Some(Color::None | Color::CurrentColor)) {
+            return None;
+        }
+        match self {
+            ColorMap::Map(map) => map.get(color).cloned().or_else(|| {
+                let parsed = parsed?;
+                map.iter()
+                    .find(|(key, _)| key.parse::<Color>().ok() == Some(parsed))
+                    .map(|(_, replacement)| replacement.clone())
+            }),
+            ColorMap::Callback(f) => f(color),
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>, const N: usize> From<[(K, V); N]> for ColorMap {
+    fn from(pairs: [(K, V); N]) -> Self {
+        ColorMap::Map(
+            pairs
+                .into_iter()
+                .map(|(from, to)| (from.into(), to.into()))
+                .collect(),
+        )
+    }
+}
+
+impl From<HashMap<String, String>> for ColorMap {
+    fn from(map: HashMap<String, String>) -> Self {
+        ColorMap::Map(map)
+    }
+}
+
+impl fmt::Debug for ColorMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorMap::Map(map) => f.debug_tuple("Map").field(map).finish(),
+            ColorMap::Callback(_) => f.write_str("Callback(..)"),
+        }
+    }
+}
+
+/// Maps compare equal when they hold the same replacements, callbacks when they are the same closure.
+impl PartialEq for ColorMap {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ColorMap::Map(a), ColorMap::Map(b)) => a == b,
+            (ColorMap::Callback(a), ColorMap::Callback(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Settings of the dark mode transform, see [`to_dark_mode`](crate::passes::to_dark_mode).
+///
+/// Colors are compared by their relative luminance, from `0.0` for black to `1.0` for white.
+///
+/// # Field
+///
+/// - `dark_threshold` : colors with a luminance up to this value count as near-black and are
+///   replaced by `light_color`.
+/// - `light_threshold` : colors with a luminance from this value count as near-white and are
+///   replaced by `dark_color`.
+/// - `light_color` : the color drawn instead of near-black, typically the text color of the dark theme.
+/// - `dark_color` : the color drawn instead of near-white, typically the background of the dark theme.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::options::{DarkMode, RsxOptions};
+///
+/// let options = RsxOptions {
+///     dark_mode: Some(DarkMode {
+///         light_color: "var(--text)".to_string(),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DarkMode {
+    pub dark_threshold: f32,
+
+    pub light_threshold: f32,
+
+    pub light_color: String,
+
+    pub dark_color: String,
+}
+
+impl Default for DarkMode {
+    fn default() -> Self {
+        DarkMode {
+            dark_threshold: 0.05,
+            light_threshold: 0.9,
+            light_color: "#e8e8e8".to_string(),
+            dark_color: "#1e1e1e".to_string(),
+        }
+    }
+}
+
+/// What [`sanitize_references`](crate::passes::sanitize_references) lets through.
+///
+/// # Field
+///
+/// - `image_types` : the MIME types of the `data:` URIs an `image` may show, compared without
+///   case. PNG, JPEG, GIF and WebP by default; SVG images are left out, since they can hold
+///   references of their own.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::options::{RsxOptions, SanitizeOptions};
+///
+/// let mut sanitize = SanitizeOptions::default();
+/// sanitize.image_types.push("image/avif".to_string());
+/// let options = RsxOptions {
+///     sanitize: Some(sanitize),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    pub image_types: Vec<String>,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        SanitizeOptions {
+            image_types: ["image/png", "image/jpeg", "image/gif", "image/webp"]
+                .map(String::from)
+                .to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizing_test() {
+        assert_eq!(Sizing::Fixed.apply("20pt").as_deref(), Some("20pt"));
+        assert_eq!(Sizing::Responsive.apply("20pt"), None);
+        assert_eq!(Sizing::Scale(1.5).apply("20pt").as_deref(), Some("30pt"));
+        assert_eq!(Sizing::Scale(2.0).apply("12.5px").as_deref(), Some("25px"));
+        assert_eq!(Sizing::Scale(2.0).apply("100").as_deref(), Some("200"));
+        assert_eq!(Sizing::Scale(2.0).apply("auto").as_deref(), Some("auto"));
+    }
+
+    #[test]
+    fn color_map_none_test() {
+        let colors = ColorMap::from_fn(|_| Some("red".to_string()));
+        assert_eq!(colors.get("#000000").as_deref(), Some("red"));
+        assert_eq!(colors.get("none"), None);
+        assert_eq!(colors.get("None"), None);
+
+        let colors = ColorMap::from([("none", "red")]);
+        assert_eq!(colors.get("none"), None);
+    }
+}
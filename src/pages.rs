@@ -0,0 +1,176 @@
+use crate::{
+    passes::{geometry::parse_transform, prune_unused_defs},
+    svg_types::{number::format_number, *},
+};
+
+/// Splits a document exported as a single SVG into one document per page.
+///
+/// Typst stacks the pages of a document vertically, each in a top-level
+/// `<g class="typst-page" transform="translate(0, y)">`. Every page becomes its own [`Svg`] with the
+/// translation removed, a `viewBox` and `height` covering only the page (up to the next one), and
+/// the symbols the page references. The other top-level elements are dropped.
+///
+/// A document without page groups, or whose page groups are not simply translated, is returned
+/// unchanged as the only element of the `Vec`.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, split_pages};
+///
+/// let svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 100 200' width='100pt' height='200pt'>\
+///      <g class='typst-page' transform='translate(0, 0)'><path d='M 0 0 L 10 10'/></g>\
+///      <g class='typst-page' transform='translate(0, 100)'><path d='M 0 0 L 20 20'/></g>\
+///      </svg>",
+/// )
+/// .unwrap();
+/// let pages = split_pages(&svg);
+/// assert_eq!(pages.len(), 2);
+/// assert_eq!(pages[1].view_box, "0 0 100 100");
+/// assert_eq!(pages[1].height, "100pt");
+/// ```
+pub fn split_pages(svg: &Svg) -> Vec<Svg> {
+    split(svg).unwrap_or_else(|| vec![svg.clone()])
+}
+
+fn split(svg: &Svg) -> Option<Vec<Svg>> {
+    let ViewBox {
+        min_x,
+        min_y,
+        width,
+        height,
+    } = svg.view_box_parsed().ok()?;
+
+    // The page groups and their vertical offset
+    let mut pages = Vec::new();
+    for element in &svg.elements {
+        let SvgElement::G(g) = element else {
+            continue;
+        };
+        let is_page = g
+            .class
+            .as_deref()
+            .is_some_and(|class| class.split_whitespace().any(|class| class == "typst-page"));
+        if !is_page {
+            continue;
+        }
+        let offset = match g.transform.as_deref() {
+            Some(transform) => {
+                let matrix = parse_transform(transform)?;
+                let translation = (matrix.a, matrix.b, matrix.c, matrix.d) == (1.0, 0.0, 0.0, 1.0);
+                if !translation {
+                    return None;
+                }
+                (matrix.e, matrix.f)
+            }
+            None => (0.0, 0.0),
+        };
+        pages.push((g, offset));
+    }
+    if pages.is_empty() {
+        return None;
+    }
+
+    // The height in user units is converted to the unit of the document's height
+    let document_height = svg.height_length();
+    let (scale, unit) = if document_height.value.is_finite() && height > 0.0 {
+        (document_height.value / height, document_height.unit)
+    } else {
+        (1.0, LengthUnit::None)
+    };
+
+    let defs: Vec<SvgElement> = svg
+        .elements
+        .iter()
+        .filter(|element| matches!(element, SvgElement::Defs(_)))
+        .cloned()
+        .collect();
+    let ends: Vec<f64> = pages
+        .iter()
+        .skip(1)
+        .map(|(_, (_, y))| *y)
+        .chain([min_y + height])
+        .collect();
+    Some(
+        pages
+            .iter()
+            .zip(ends)
+            .map(|((g, (x, y)), end)| {
+                let page_height = (end - y).max(0.0);
+                let mut page = G::clone(g);
+                page.transform = None;
+                let mut elements = vec![SvgElement::G(Box::new(page))];
+                elements.extend(defs.iter().cloned());
+                let mut page = Svg {
+                    class: svg.class.clone(),
+                    width: svg.width.clone(),
+                    height: format!("{}{}", format_number(page_height * scale), unit),
+                    view_box: ViewBox::new(min_x - x, 0.0, width, page_height).to_string(),
+                    elements,
+                };
+                prune_unused_defs(&mut page);
+                page
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use std::fs;
+
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 100 210" width="200pt" height="420pt">
+        <g class="typst-page" transform="translate(0, 0)">
+            <g class="typst-text"><use href="#glyph0" x="0"/></g>
+        </g>
+        <g class="typst-page" transform="translate(0 110)">
+            <g class="typst-text"><use href="#glyph1" x="0"/></g>
+        </g>
+        <defs id="glyph">
+            <symbol id="glyph0" overflow="visible"><path d="M 0 0 L 1 1"/></symbol>
+            <symbol id="glyph1" overflow="visible"><path d="M 0 0 L 2 2"/></symbol>
+        </defs>
+    </svg>"##;
+
+    fn symbol_ids(svg: &Svg) -> Vec<&str> {
+        svg.elements
+            .iter()
+            .flat_map(|element| match element {
+                SvgElement::Defs(defs) => defs.elements.iter().map(|s| s.id.as_str()).collect(),
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_test() {
+        let svg = parse_svg(SVG).unwrap();
+        let pages = split_pages(&svg);
+        assert_eq!(pages.len(), 2);
+
+        assert_eq!(pages[0].view_box, "0 0 100 110");
+        assert_eq!(pages[0].height, "220pt");
+        assert_eq!(pages[0].width, "200pt");
+        assert_eq!(symbol_ids(&pages[0]), ["glyph0"]);
+
+        assert_eq!(pages[1].view_box, "0 0 100 100");
+        assert_eq!(pages[1].height, "200pt");
+        assert_eq!(symbol_ids(&pages[1]), ["glyph1"]);
+        for page in &pages {
+            assert!(page.validate_references().is_empty());
+        }
+        let SvgElement::G(page) = &pages[1].elements[0] else {
+            panic!()
+        };
+        assert_eq!(page.transform, None);
+    }
+
+    #[test]
+    fn no_pages_test() {
+        let svg = parse_svg(&fs::read_to_string("./test/expected.svg").unwrap()).unwrap();
+        assert_eq!(split_pages(&svg), [svg]);
+    }
+}
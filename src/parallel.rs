@@ -0,0 +1,175 @@
+use rayon::prelude::*;
+
+use crate::{
+    check_svg, error::Error, limits::scan_tag, parse_checked, svg_types::Svg, ParseLimits,
+};
+
+/// Parses an SVG string like [`parse_svg`](crate::parse_svg), with its top-level elements parsed
+/// on the threads of the `rayon` pool.
+///
+/// Typst puts every page of a document in its own top-level `g`, so a document of many pages is
+/// parsed about as many times faster as there are cores. The document is cut at its top-level
+/// elements by a scan of its tags, and every element is parsed as the only child of the root; the
+/// trees are then put back together in order, on the calling thread.
+///
+/// The result is the same as [`parse_svg`](crate::parse_svg), errors included: a document that
+/// cannot be cut (a single top-level element, a `DOCTYPE` with declarations...) or that fails to
+/// parse is parsed again as a whole.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, parse_svg_parallel};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 100 200' width='100pt' height='200pt'>\
+///                <g class='typst-page'><path d='M 0 0 L 10 10'/></g>\
+///                <g class='typst-page' transform='translate(0, 100)'><path d='M 0 0 L 20 20'/></g>\
+///                </svg>";
+/// assert_eq!(parse_svg_parallel(svg_str).unwrap(), parse_svg(svg_str).unwrap());
+/// ```
+pub fn parse_svg_parallel(svg_str: &str) -> Result<Svg, Error> {
+    parse_svg_parallel_with_limits(svg_str, &ParseLimits::default())
+}
+
+/// Parses an SVG string like [`parse_svg_parallel`], checking it against the given
+/// [`ParseLimits`] first, like [`parse_svg_with_limits`](crate::parse_svg_with_limits).
+pub fn parse_svg_parallel_with_limits(svg_str: &str, limits: &ParseLimits) -> Result<Svg, Error> {
+    let svg_str = check_svg(svg_str, limits)?;
+    let Some((root, name, elements)) = split(svg_str).filter(|(_, _, elements)| elements.len() > 1)
+    else {
+        return parse_checked(svg_str);
+    };
+    let parsed: Result<Vec<Svg>, Error> = elements
+        .par_iter()
+        .map(|element| parse_checked(&format!("{}{}</{}>", root, element, name)))
+        .collect();
+    // The whole document locates the error
+    let Ok(parsed) = parsed else {
+        return parse_checked(svg_str);
+    };
+    let mut parsed = parsed.into_iter();
+    let mut svg = parsed.next().expect("more than one element");
+    for part in parsed {
+        svg.elements.extend(part.elements);
+    }
+    Ok(svg)
+}
+
+/// Cuts a document into the start tag of its root, the name of the root and the source of each
+/// top-level element, or returns `None` when anything else than whitespace and comments sits
+/// between the elements.
+fn split(svg_str: &str) -> Option<(&str, &str, Vec<&str>)> {
+    let bytes = svg_str.as_bytes();
+    let mut root = None;
+    let mut elements = Vec::new();
+    // Elements open below the root, and where the current top-level element starts
+    let mut open = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while let Some(offset) = bytes[i..].iter().position(|&byte| byte == b'<') {
+        if open == 0 && !svg_str[i..i + offset].trim().is_empty() {
+            return None;
+        }
+        let tag = i + offset;
+        let rest = &svg_str[tag + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            i = tag + 4 + comment.find("-->")? + 3;
+        } else if rest.starts_with('?') && root.is_none() {
+            i = tag + 1 + rest.find("?>")? + 2;
+        } else if rest.starts_with("!DOCTYPE") && root.is_none() {
+            let end = rest.find('>')?;
+            // Entity declarations would have to be repeated for every element
+            if rest[..end].contains('[') {
+                return None;
+            }
+            i = tag + 1 + end + 1;
+        } else if rest.starts_with('!') || rest.starts_with('?') {
+            return None;
+        } else if rest.starts_with('/') {
+            i = tag + 1 + rest.find('>')? + 1;
+            let (root, name) = root?;
+            if open == 0 {
+                return Some((root, name, elements));
+            }
+            open -= 1;
+            if open == 0 {
+                elements.push(&svg_str[start..i]);
+            }
+        } else {
+            let (len, _) = scan_tag(rest.as_bytes());
+            let end = tag + 1 + len;
+            let self_closing = svg_str[..end].ends_with("/>");
+            match root {
+                None if self_closing => return None,
+                None => {
+                    let name_len = rest
+                        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                        .unwrap_or(rest.len());
+                    root = Some((&svg_str[tag..end], &rest[..name_len]));
+                }
+                Some(_) => {
+                    if open == 0 {
+                        start = tag;
+                    }
+                    if self_closing {
+                        if open == 0 {
+                            elements.push(&svg_str[start..end]);
+                        }
+                    } else {
+                        open += 1;
+                    }
+                }
+            }
+            i = end;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use std::fs;
+
+    #[test]
+    fn split_test() {
+        let svg_str = "<?xml version='1.0'?>\n<!-- c --><svg class='a' viewBox='0 0 1 1'>\n\
+                       <g class='a>b'><g/><path d='M 0 0'></path></g>\n<!-- <g> -->\
+                       <path d='M 1 1'/>\n<defs id='d'></defs>\n</svg>\n";
+        let (root, name, elements) = split(svg_str).unwrap();
+        assert_eq!(root, "<svg class='a' viewBox='0 0 1 1'>");
+        assert_eq!(name, "svg");
+        assert_eq!(
+            elements,
+            [
+                "<g class='a>b'><g/><path d='M 0 0'></path></g>",
+                "<path d='M 1 1'/>",
+                "<defs id='d'></defs>"
+            ]
+        );
+        assert_eq!(split("<svg class='a'>text<g/></svg>"), None);
+        assert_eq!(split("<svg class='a'><g>"), None);
+        assert_eq!(split("<svg class='a'/>"), None);
+        assert_eq!(
+            split("<!DOCTYPE svg [<!ENTITY a 'b'>]><svg class='a'><g/></svg>"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_test() {
+        let svg_str = fs::read_to_string("./test/expected.svg").unwrap();
+        assert_eq!(
+            parse_svg_parallel(&svg_str).unwrap(),
+            parse_svg(&svg_str).unwrap()
+        );
+
+        // The errors come from the whole document
+        let broken = fs::read_to_string("./test/broken.svg").unwrap();
+        assert_eq!(
+            parse_svg_parallel(&broken).unwrap_err().to_string(),
+            parse_svg(&broken).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Transformations applied to a parsed [`Svg`] before it is converted to RSX.
+//!
+//! Each pass takes the document by `&mut` and can be called on its own between
+//! [`parse_svg`](crate::parse_svg) and [`svg_to_rsx`](crate::svg_to_rsx). The conversion functions
+//! taking an [`RsxOptions`] run the passes enabled by the options.
+
+use std::collections::HashMap;
+
+use crate::{
+    error::Error,
+    options::{DuplicateIds, RsxOptions},
+    report::ConversionReport,
+    svg_types::Svg,
+};
+
+mod background;
+mod colors;
+mod crop;
+mod css_vars;
+mod dark_mode;
+mod dedupe;
+mod degenerate;
+mod duplicates;
+mod expand;
+mod flatten;
+pub(crate) mod geometry;
+mod ids;
+mod images;
+mod merge;
+mod minify;
+mod prune;
+mod sanitize;
+mod simplify;
+mod stops;
+mod strip;
+
+pub use background::add_background;
+pub use colors::recolor;
+pub use crop::{crop_to_content, crop_to_region};
+pub use css_vars::{fills_to_css_vars, TYPST_THEME_VARS};
+pub use dark_mode::to_dark_mode;
+pub use dedupe::dedupe_symbols;
+pub use degenerate::drop_degenerate;
+pub use duplicates::resolve_duplicate_ids;
+pub use expand::expand_uses;
+pub use flatten::flatten_transforms;
+pub use ids::prefix_ids;
+pub use images::extract_images;
+pub use merge::merge_paths;
+pub use minify::minify_paths;
+pub use prune::prune_unused_defs;
+pub use sanitize::sanitize_references;
+pub use simplify::simplify;
+pub use stops::normalize_stops;
+pub use strip::strip_presentation_attributes;
+
+/// Runs the passes enabled by `options` on `svg`, returning a report with the warnings of those
+/// reporting what they removed and the number of merged paths, or the error of
+/// [`DuplicateIds::Error`] before changing anything.
+pub(crate) fn apply(svg: &mut Svg, options: &RsxOptions) -> Result<ConversionReport, Error> {
+    if options.duplicate_ids == Some(DuplicateIds::Error) {
+        resolve_duplicate_ids(svg, DuplicateIds::Error)?;
+    }
+    Ok(apply_lenient(svg, options))
+}
+
+/// Runs the passes enabled by `options` like [`apply`], handling the duplicate ids as with
+/// [`DuplicateIds::Warn`] when `options` asks for [`DuplicateIds::Error`].
+pub(crate) fn apply_lenient(svg: &mut Svg, options: &RsxOptions) -> ConversionReport {
+    let mut report = ConversionReport::default();
+    // First, so that no other pass sees the removed references
+    if let Some(sanitize) = &options.sanitize {
+        report.warnings.extend(sanitize_references(svg, sanitize));
+    }
+    // Before the passes following the references, which would mistake the duplicates
+    if let Some(mode) = options.duplicate_ids {
+        let mode = match mode {
+            DuplicateIds::Error => DuplicateIds::Warn,
+            mode => mode,
+        };
+        // Only `DuplicateIds::Error` fails
+        report
+            .warnings
+            .extend(resolve_duplicate_ids(svg, mode).unwrap_or_default());
+    }
+    // Always, so that the color passes see the colors given in the styles of the stops
+    normalize_stops(svg);
+    if let Some(region) = options.region {
+        crop_to_region(svg, region);
+    }
+    if options.prune_unused_defs {
+        prune_unused_defs(svg);
+    }
+    // Before the prefix, which would not change the duplicates
+    if options.dedupe_symbols {
+        dedupe_symbols(svg);
+    }
+    if let Some(prefix) = &options.id_prefix {
+        prefix_ids(svg, prefix);
+    }
+    if options.flatten_transforms {
+        flatten_transforms(svg);
+    }
+    if options.simplify {
+        simplify(svg);
+    }
+    // After the groups are flattened and simplified away, which leaves more paths side by side
+    if options.merge_paths {
+        report.merged_paths = merge_paths(svg);
+    }
+    if let Some(decimals) = options.path_precision {
+        minify_paths(svg, decimals);
+    }
+    if options.strip_presentation_attributes {
+        strip_presentation_attributes(svg);
+    }
+    if let Some(colors) = &options.colors {
+        recolor(svg, colors);
+    }
+    if let Some(dark_mode) = &options.dark_mode {
+        to_dark_mode(svg, dark_mode);
+    }
+    // Last, so that the color chosen is not recolored
+    if let Some(color) = &options.background {
+        add_background(svg, color);
+    }
+    report
+}
+
+/// Numbers the children of one element per tag, for the paths of the warnings.
+pub(crate) struct Siblings {
+    parent: String,
+    counts: HashMap<&'static str, usize>,
+}
+
+impl Siblings {
+    pub(crate) fn new(parent: impl Into<String>) -> Self {
+        Siblings {
+            parent: parent.into(),
+            counts: HashMap::new(),
+        }
+    }
+
+    /// The path of the next child with `tag`.
+    pub(crate) fn path(&mut self, tag: &'static str) -> String {
+        let index = self.counts.entry(tag).or_default();
+        *index += 1;
+        format!("{} > {}[{}]", self.parent, tag, *index - 1)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::svg_types::*;
+
+/// Inserts a `rect` filled with `color` and covering the whole `viewBox`, behind the content.
+///
+/// Typst pages are exported with a transparent background, so black text becomes unreadable on a
+/// dark page. The rectangle is inserted as the first child of the root `svg`, which draws it
+/// before everything else. If the `viewBox` cannot be parsed, the rectangle covers `100%` of the
+/// viewport instead.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::add_background, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 595 842' width='595pt' height='842pt'>\
+///      <path d='M 0 0 L 10 10'/></svg>",
+/// )
+/// .unwrap();
+/// add_background(&mut svg, "white");
+/// match &svg.elements[0] {
+///     SvgElement::Rect(rect) => assert_eq!((rect.width.as_str(), rect.height.as_str()), ("595", "842")),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn add_background(svg: &mut Svg, color: &str) {
+    let rect = match svg.view_box_parsed() {
+        Ok(view_box) => Rect::new(
+            view_box.min_x,
+            view_box.min_y,
+            view_box.width,
+            view_box.height,
+        ),
+        Err(_) => Rect {
+            x: "0".to_string(),
+            y: "0".to_string(),
+            width: "100%".to_string(),
+            height: "100%".to_string(),
+            fill: None,
+        },
+    };
+    svg.elements.insert(
+        0,
+        SvgElement::Rect(Rect {
+            fill: Some(color.into()),
+            ..rect
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compat, options::RsxOptions, parse_svg, parse_svg_to_rsx_with_options};
+    use std::fs;
+
+    #[test]
+    fn background_test() {
+        let mut svg = parse_svg(&fs::read_to_string("./test/expected.svg").unwrap()).unwrap();
+        add_background(&mut svg, "#1e1e1e");
+        let SvgElement::Rect(rect) = &svg.elements[0] else {
+            panic!()
+        };
+        let view_box: Vec<_> = svg.view_box.split_whitespace().collect();
+        assert_eq!(
+            [&rect.x, &rect.y, &rect.width, &rect.height],
+            view_box.as_slice()
+        );
+        assert_eq!(rect.fill.as_deref(), Some("#1e1e1e"));
+    }
+
+    /// The rectangle is the first child of the generated `svg`, below everything else.
+    #[test]
+    fn element_order_test() {
+        let options = RsxOptions {
+            background: Some("white".to_string()),
+            ..Default::default()
+        };
+        let element = parse_svg_to_rsx_with_options(
+            "<svg class='typst-doc' viewBox='0,0,10,20' width='10pt' height='20pt'>\
+             <path d='M 0 0 L 10 10'/></svg>",
+            &options,
+        )
+        .unwrap();
+        let html = compat::render_html(element);
+        let children: Vec<_> = html
+            .split('<')
+            .skip(2)
+            .filter(|tag| !tag.starts_with('/'))
+            .map(|tag| tag.split([' ', '>']).next().unwrap())
+            .collect();
+        assert_eq!(children, ["rect", "path"]);
+    }
+}
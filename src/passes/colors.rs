@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use crate::{options::ColorMap, svg_types::*};
+
+/// Replaces the paint colors of the document according to `colors`.
+///
+/// The `fill` and `stroke` of every `path` (including the ones inside symbols) and the `fill` of
+/// every `use`, `rect`, `text` and `tspan` element, as well as the `stop-color` of the gradient
+/// stops and the `flood-color` of the drop shadows, are looked up in `colors`; values without a
+/// replacement, and `none`, are kept.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::ColorMap, parse_svg, passes::recolor, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <path d='M 0 0 L 10 10' fill='#000000'/></svg>",
+/// )
+/// .unwrap();
+/// recolor(&mut svg, &ColorMap::from([("#000000", "var(--ink)")]));
+/// match &svg.elements[0] {
+///     SvgElement::Path(path) => assert_eq!(path.fill.as_deref(), Some("var(--ink)")),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn recolor(svg: &mut Svg, colors: &ColorMap) {
+    svg.walk_mut(&mut Recolor(colors));
+}
+
+struct Recolor<'a>(&'a ColorMap);
+
+impl VisitorMut for Recolor<'_> {
+    fn visit_path(&mut self, path: &mut Path) {
+        recolor_value(&mut path.fill, self.0);
+        recolor_value(&mut path.stroke, self.0);
+    }
+
+    fn visit_use(&mut self, uuse: &mut Use) {
+        recolor_value(&mut uuse.fill, self.0);
+    }
+
+    fn visit_rect(&mut self, rect: &mut Rect) {
+        recolor_value(&mut rect.fill, self.0);
+    }
+
+    fn visit_text(&mut self, text: &mut Text) {
+        recolor_value(&mut text.fill, self.0);
+        for content in &mut text.content {
+            if let TextContent::Tspan(tspan) = content {
+                recolor_value(&mut tspan.fill, self.0);
+            }
+        }
+    }
+
+    fn visit_definition(&mut self, definition: &mut Definition) {
+        if let Definition::Filter(filter) = definition {
+            for primitive in &mut filter.primitives {
+                if let FilterPrimitive::DropShadow(shadow) = primitive {
+                    recolor_value(&mut shadow.flood_color, self.0);
+                }
+            }
+        }
+    }
+
+    fn visit_stop(&mut self, stop: &mut Stop) {
+        recolor_value(&mut stop.stop_color, self.0);
+    }
+}
+
+fn recolor_value(value: &mut Option<Arc<str>>, colors: &ColorMap) {
+    if let Some(color) = value.as_deref().and_then(|color| colors.get(color)) {
+        *value = Some(color.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 20 10" width="20pt" height="10pt">
+        <path fill="#ffffff" d="M 0 0 L 0 10 Z "/>
+        <g>
+            <use href="#glyph0" x="0" fill="#000000"/>
+            <path fill="none" stroke="#000000" d="M 0 0 L 1 1"/>
+            <path fill="#ff0000" d="M 0 0 L 1 1"/>
+        </g>
+        <defs id="glyph">
+            <symbol id="glyph0" overflow="visible">
+                <path fill="#000000" d="M 1 1 L 2 2 Z"/>
+            </symbol>
+            <linearGradient id="fade">
+                <stop offset="0" stop-color="#000000"/>
+                <stop offset="1" stop-color="#ff0000"/>
+            </linearGradient>
+            <filter id="shadow">
+                <feDropShadow dy="2" stdDeviation="3" flood-color="#000000"/>
+            </filter>
+        </defs>
+    </svg>"##;
+
+    #[test]
+    fn recolor_test() {
+        let mut svg = parse_svg(SVG).unwrap();
+        recolor(
+            &mut svg,
+            &ColorMap::from([
+                ("#000000", "var(--ink)"),
+                ("#ffffff", "transparent"),
+                ("none", "red"),
+            ]),
+        );
+
+        let SvgElement::Path(background) = &svg.elements[0] else {
+            panic!()
+        };
+        assert_eq!(background.fill.as_deref(), Some("transparent"));
+
+        let SvgElement::G(g) = &svg.elements[1] else {
+            panic!()
+        };
+        let elements = g.elements.as_ref().unwrap();
+        let GEle::Use(uuse) = &elements[0] else {
+            panic!()
+        };
+        assert_eq!(uuse.fill.as_deref(), Some("var(--ink)"));
+        let GEle::Path(outline) = &elements[1] else {
+            panic!()
+        };
+        assert_eq!(outline.fill.as_deref(), Some("none"));
+        assert_eq!(outline.stroke.as_deref(), Some("var(--ink)"));
+        let GEle::Path(red) = &elements[2] else {
+            panic!()
+        };
+        assert_eq!(red.fill.as_deref(), Some("#ff0000"));
+
+        let SvgElement::Defs(defs) = &svg.elements[2] else {
+            panic!()
+        };
+        let SymbolEle::Path(glyph) = &defs.elements[0].element else {
+            panic!()
+        };
+        assert_eq!(glyph.fill.as_deref(), Some("var(--ink)"));
+        let stops: Vec<_> = defs.definitions[0]
+            .stops()
+            .iter()
+            .map(|stop| stop.stop_color.as_deref())
+            .collect();
+        assert_eq!(stops, [Some("var(--ink)"), Some("#ff0000")]);
+        let Definition::Filter(filter) = &defs.definitions[1] else {
+            panic!()
+        };
+        let FilterPrimitive::DropShadow(shadow) = &filter.primitives[0] else {
+            panic!()
+        };
+        assert_eq!(shadow.flood_color.as_deref(), Some("var(--ink)"));
+    }
+}
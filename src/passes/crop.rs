@@ -0,0 +1,229 @@
+use super::prune::references;
+use crate::svg_types::{number::format_number, *};
+
+/// Shrinks the `viewBox` of the document to its content, plus `padding` on every side.
+///
+/// The box is the one of [`Svg::content_bbox`], so the ink is never clipped. The `width` and
+/// `height` are scaled with the `viewBox`, keeping their unit, so the content is displayed at
+/// the same size. The document is left unchanged when it has no content or when its box cannot
+/// be computed.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::crop_to_content};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 100 100' width='200pt' height='200pt'>\
+///      <path d='M 10 20 L 30 60'/></svg>",
+/// )
+/// .unwrap();
+/// crop_to_content(&mut svg, 1.0);
+/// assert_eq!(svg.view_box, "9 19 22 42");
+/// assert_eq!((svg.width.as_str(), svg.height.as_str()), ("44pt", "84pt"));
+/// ```
+pub fn crop_to_content(svg: &mut Svg, padding: f32) {
+    let Some(bbox) = svg.content_bbox() else {
+        return;
+    };
+    let padding = padding as f64;
+    set_view_box(
+        svg,
+        ViewBox::new(
+            bbox.min_x - padding,
+            bbox.min_y - padding,
+            bbox.width() + 2.0 * padding,
+            bbox.height() + 2.0 * padding,
+        ),
+    );
+}
+
+/// Shows only `region` of the document, in the coordinates of its `viewBox`, such as one panel of
+/// a poster.
+///
+/// The `viewBox` becomes the region, and the `width` and `height` are scaled with it, keeping
+/// their unit, so the region is displayed at the same size as in the whole document. The elements
+/// whose ink lies entirely outside the region are removed, using the boxes of
+/// [`Svg::content_bbox`]: a group outside is removed as a whole, and the children of a group
+/// across the edge are checked one by one. The elements partially inside are kept, and so are
+/// text and the elements whose box cannot be computed. The symbols that only the removed elements
+/// used are removed too, along with the `defs` left empty.
+///
+/// The document is left unchanged when the region has no area.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::crop_to_region, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 200 100' width='400pt' height='200pt'>\
+///      <path d='M 10 10 L 90 90'/><path d='M 110 10 L 190 90'/></svg>",
+/// )
+/// .unwrap();
+/// let region = BBox { min_x: 0.0, min_y: 0.0, max_x: 100.0, max_y: 100.0 };
+/// crop_to_region(&mut svg, region);
+/// assert_eq!(svg.view_box, "0 0 100 100");
+/// assert_eq!((svg.width.as_str(), svg.height.as_str()), ("200pt", "200pt"));
+/// assert_eq!(svg.elements.len(), 1);
+/// ```
+pub fn crop_to_region(svg: &mut Svg, region: BBox) {
+    if !(region.width() > 0.0 && region.height() > 0.0) {
+        return;
+    }
+    let outside = svg.elements_outside(region);
+    if !outside.is_empty() {
+        let before = references(svg);
+        // From the last, so that the positions of the others stay valid
+        for position in outside.iter().rev() {
+            remove(&mut svg.elements, position);
+        }
+        let after = references(svg);
+        svg.elements.retain_mut(|element| match element {
+            SvgElement::Defs(defs) => {
+                defs.elements
+                    .retain(|symbol| !before.contains(&symbol.id) || after.contains(&symbol.id));
+                !defs.elements.is_empty() || !defs.definitions.is_empty()
+            }
+            _ => true,
+        });
+    }
+    set_view_box(
+        svg,
+        ViewBox::new(region.min_x, region.min_y, region.width(), region.height()),
+    );
+}
+
+/// Removes the element at `position` among `elements` and the children of their groups.
+fn remove(elements: &mut Vec<SvgElement>, position: &[usize]) {
+    match position {
+        [index] => {
+            elements.remove(*index);
+        }
+        [index, rest @ ..] => {
+            if let SvgElement::G(g) = &mut elements[*index] {
+                remove_child(g, rest);
+            }
+        }
+        [] => {}
+    }
+}
+
+fn remove_child(g: &mut G, position: &[usize]) {
+    let Some(elements) = &mut g.elements else {
+        return;
+    };
+    match position {
+        [index] => {
+            elements.remove(*index);
+        }
+        [index, rest @ ..] => {
+            if let GEle::G(g) = &mut elements[*index] {
+                remove_child(g, rest);
+            }
+        }
+        [] => {}
+    }
+}
+
+/// Sets the `viewBox`, scaling the `width` and `height` with it.
+fn set_view_box(svg: &mut Svg, view_box: ViewBox) {
+    if let Ok(old) = svg.view_box_parsed() {
+        svg.width = scale_length(&svg.width, view_box.width / old.width);
+        svg.height = scale_length(&svg.height, view_box.height / old.height);
+    }
+    svg.view_box = view_box.to_string();
+}
+
+/// Multiplies a length with an optional unit (`12.5pt`) by `factor`.
+fn scale_length(length: &str, factor: f64) -> String {
+    let length = Length::from(length);
+    if length.value.is_finite() && factor.is_finite() {
+        format!("{}{}", format_number(length.value * factor), length.unit)
+    } else {
+        length.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use std::fs;
+
+    #[test]
+    fn formula_test() {
+        let mut svg = parse_svg(&fs::read_to_string("./test/formula.svg").unwrap()).unwrap();
+        crop_to_content(&mut svg, 1.0);
+        assert_eq!(svg.view_box, "9 2.75 18.2 15.75");
+        assert_eq!(svg.width, "18.2pt");
+        assert_eq!(svg.height, "15.75pt");
+    }
+
+    fn region(min_x: f64, max_x: f64) -> BBox {
+        BBox {
+            min_x,
+            min_y: 0.0,
+            max_x,
+            max_y: 100.0,
+        }
+    }
+
+    /// The left column is kept, with the page behind it; the right one and the glyph only it
+    /// used are gone.
+    #[test]
+    fn columns_test() {
+        let source = fs::read_to_string("./test/columns.svg").unwrap();
+        let mut svg = parse_svg(&source).unwrap();
+        crop_to_region(&mut svg, region(0.0, 105.0));
+        assert_eq!(svg.view_box, "0 0 105 100");
+        assert_eq!(
+            (svg.width.as_str(), svg.height.as_str()),
+            ("105pt", "100pt")
+        );
+
+        let fills: Vec<_> = svg
+            .find_all(|node| matches!(node, SvgNodeRef::Path(path) if path.fill.is_some()))
+            .into_iter()
+            .filter_map(|node| match node {
+                SvgNodeRef::Path(path) => path.fill.as_deref(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fills, ["#ffffff", "none", "#1f4e8c"]);
+        let SvgElement::G(page) = &svg.elements[1] else {
+            panic!()
+        };
+        assert_eq!(page.elements.as_ref().unwrap().len(), 1);
+        let symbols: Vec<_> = svg
+            .find_all(|node| matches!(node, SvgNodeRef::Symbol(_)))
+            .into_iter()
+            .filter_map(|node| node.id())
+            .collect();
+        assert_eq!(symbols, ["glyph0", "glyph1"]);
+        assert!(svg.validate_references().is_empty());
+        assert_eq!(svg.content_bbox().unwrap().min_x, 0.0);
+
+        // Across the edge, the divider stays
+        let mut svg = parse_svg(&source).unwrap();
+        crop_to_region(&mut svg, region(0.0, 115.0));
+        let SvgElement::G(page) = &svg.elements[1] else {
+            panic!()
+        };
+        assert_eq!(page.elements.as_ref().unwrap().len(), 2);
+
+        // The right column, with a glyph used by both
+        let mut svg = parse_svg(&source).unwrap();
+        crop_to_region(&mut svg, region(115.0, 220.0));
+        let symbols: Vec<_> = svg
+            .find_all(|node| matches!(node, SvgNodeRef::Symbol(_)))
+            .into_iter()
+            .filter_map(|node| node.id())
+            .collect();
+        assert_eq!(symbols, ["glyph0", "glyph2"]);
+
+        let mut svg = parse_svg(&source).unwrap();
+        crop_to_region(&mut svg, region(10.0, 10.0));
+        assert_eq!(svg, parse_svg(&source).unwrap());
+    }
+}
@@ -0,0 +1,116 @@
+use super::recolor;
+use crate::{
+    options::ColorMap,
+    svg_types::{color::Color, *},
+};
+
+/// The mapping of [`fills_to_css_vars`] for theming a document with two variables: black becomes
+/// `var(--typst-fg, #000000)` and white becomes `var(--typst-bg, #ffffff)`.
+pub const TYPST_THEME_VARS: &[(&str, &str, &str)] = &[
+    ("#000000", "typst-fg", "#000000"),
+    ("#ffffff", "typst-bg", "#ffffff"),
+];
+
+/// Replaces the colors of the document with CSS custom properties, so it can be themed at
+/// runtime without being converted again.
+///
+/// Each entry of `mapping` is a `(color, var_name, fallback)` triple: every `fill` and `stroke`
+/// handled by [`recolor`] that denotes `color` becomes `var(--var_name, fallback)`. The colors are
+/// compared by value, so `#FFF`, `#ffffff`, `white` and `rgb(255, 255, 255)` all match each
+/// other; the leading `--` of `var_name` is optional. Colors without an entry are left alone.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::{fills_to_css_vars, TYPST_THEME_VARS}, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <path d='M 0 0 L 10 10' fill='#000'/></svg>",
+/// )
+/// .unwrap();
+/// fills_to_css_vars(&mut svg, TYPST_THEME_VARS);
+/// match &svg.elements[0] {
+///     SvgElement::Path(path) => assert_eq!(path.fill.as_deref(), Some("var(--typst-fg, #000000)")),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn fills_to_css_vars(svg: &mut Svg, mapping: &[(&str, &str, &str)]) {
+    let mapping: Vec<(Color, String)> = mapping
+        .iter()
+        .filter_map(|(color, var_name, fallback)| {
+            let value = format!("var(--{}, {})", var_name.trim_start_matches('-'), fallback);
+            Some((color.parse().ok()?, value))
+        })
+        .collect();
+    recolor(
+        svg,
+        &ColorMap::from_fn(move |color| {
+            let color = color.parse::<Color>().ok()?;
+            mapping
+                .iter()
+                .find(|(mapped, _)| *mapped == color)
+                .map(|(_, value)| value.clone())
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn css_vars_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <path fill="#FFFFFF" d="M 0 0 L 0 10 Z"/>
+                <g>
+                    <use href="#glyph0" x="0" fill="#000"/>
+                    <path fill="none" stroke="rgb(0, 0, 0)" d="M 0 0 L 1 1"/>
+                    <path fill="#ff0000" d="M 0 0 L 1 1"/>
+                </g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path fill="black" d="M 1 1 L 2 2"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        let mut mapping = TYPST_THEME_VARS.to_vec();
+        mapping.push(("#f00", "--accent", "red"));
+        fills_to_css_vars(&mut svg, &mapping);
+
+        let mut paints = Vec::new();
+        svg.walk(&mut Paints(&mut paints));
+        assert_eq!(
+            paints,
+            [
+                "var(--typst-bg, #ffffff)",
+                "var(--typst-fg, #000000)",
+                "none",
+                "var(--typst-fg, #000000)",
+                "var(--accent, red)",
+                "var(--typst-fg, #000000)",
+            ]
+        );
+    }
+
+    /// Collects the fills and strokes of the document, in document order.
+    struct Paints<'a>(&'a mut Vec<String>);
+
+    impl Visitor<'_> for Paints<'_> {
+        fn visit_path(&mut self, path: &Path) {
+            self.0.extend(
+                path.fill
+                    .iter()
+                    .chain(&path.stroke)
+                    .map(|paint| paint.to_string()),
+            );
+        }
+
+        fn visit_use(&mut self, uuse: &Use) {
+            self.0
+                .extend(uuse.fill.iter().map(|paint| paint.to_string()));
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::recolor;
+use crate::{
+    options::{ColorMap, DarkMode},
+    svg_types::{color::Color, *},
+};
+
+/// Swaps the near-black and near-white colors of the document, for display on a dark background.
+///
+/// Every `fill` and `stroke` handled by [`recolor`] is parsed as a [`Color`] and compared by
+/// relative luminance with the thresholds of `settings`: near-black becomes `settings.light_color`, near-white becomes
+/// `settings.dark_color`. Mid-tones, colors that cannot be parsed, `none`, `currentColor`
+/// and images are left alone.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::DarkMode, parse_svg, passes::to_dark_mode, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <path d='M 0 0 L 10 10' fill='#000000'/></svg>",
+/// )
+/// .unwrap();
+/// to_dark_mode(&mut svg, &DarkMode::default());
+/// match &svg.elements[0] {
+///     SvgElement::Path(path) => assert_eq!(path.fill.as_deref(), Some("#e8e8e8")),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn to_dark_mode(svg: &mut Svg, settings: &DarkMode) {
+    let settings = settings.clone();
+    recolor(
+        svg,
+        &ColorMap::from_fn(move |color| {
+            let luminance = color.parse::<Color>().ok()?.luminance()?;
+            if luminance <= settings.dark_threshold {
+                Some(settings.light_color.clone())
+            } else if luminance >= settings.light_threshold {
+                Some(settings.dark_color.clone())
+            } else {
+                None
+            }
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 20 10" width="20pt" height="10pt">
+        <path fill="#ffffff" d="M 0 0 L 0 10 Z "/>
+        <g>
+            <use href="#glyph0" x="0" fill="#000000"/>
+            <path fill="none" stroke="rgb(3, 3, 3)" d="M 0 0 L 1 1"/>
+            <path fill="#808080" stroke="#fafafa" d="M 0 0 L 1 1"/>
+            <path fill="#ff0000" stroke="black" d="M 0 0 L 1 1"/>
+            <image width="1" height="1" preserveAspectRatio="none" href="data:image/png;base64,AAAA"/>
+        </g>
+    </svg>"##;
+
+    #[test]
+    fn dark_mode_test() {
+        let mut svg = parse_svg(SVG).unwrap();
+        to_dark_mode(
+            &mut svg,
+            &DarkMode {
+                light_color: "light".to_string(),
+                dark_color: "dark".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let SvgElement::Path(background) = &svg.elements[0] else {
+            panic!()
+        };
+        assert_eq!(background.fill.as_deref(), Some("dark"));
+
+        let SvgElement::G(g) = &svg.elements[1] else {
+            panic!()
+        };
+        let elements = g.elements.as_ref().unwrap();
+        let GEle::Use(uuse) = &elements[0] else {
+            panic!()
+        };
+        assert_eq!(uuse.fill.as_deref(), Some("light"));
+        let paints: Vec<_> = elements[1..4]
+            .iter()
+            .map(|element| match element {
+                GEle::Path(path) => (path.fill.as_deref(), path.stroke.as_deref()),
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(
+            paints,
+            [
+                (Some("none"), Some("light")),
+                (Some("#808080"), Some("dark")),
+                (Some("#ff0000"), Some("light")),
+            ]
+        );
+        let GEle::Image(image) = &elements[4] else {
+            panic!()
+        };
+        assert_eq!(image.href, "data:image/png;base64,AAAA");
+    }
+}
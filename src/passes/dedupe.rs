@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::svg_types::*;
+
+/// Merges the symbols with the same content, keeping the first `id` of each.
+///
+/// Typst can emit the same glyph outline under several ids, notably when the pages of several
+/// documents are merged into one SVG. The symbols are compared by their content (with the
+/// whitespace of the path data normalized), every `use` pointing at a duplicate is redirected to
+/// the symbol kept, and the duplicates are removed, along with the `defs` left empty.
+///
+/// This must run before [`prefix_ids`](super::prefix_ids) when both are used, which is the order
+/// chosen by the conversion functions taking an [`RsxOptions`](crate::options::RsxOptions).
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::dedupe_symbols, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><use href='#a' x='0'/><use href='#b' x='1'/></g>\
+///      <defs id='glyph'>\
+///      <symbol id='a' overflow='visible'><path d='M 0 0 L 1 1'/></symbol>\
+///      <symbol id='b' overflow='visible'><path d='M 0 0 L 1 1'/></symbol>\
+///      </defs></svg>",
+/// )
+/// .unwrap();
+/// dedupe_symbols(&mut svg);
+/// let SvgElement::Defs(defs) = &svg.elements[1] else { unreachable!() };
+/// assert_eq!(defs.elements.len(), 1);
+/// ```
+pub fn dedupe_symbols(svg: &mut Svg) {
+    let mut canonical: HashMap<String, String> = HashMap::new();
+    let mut renamed: HashMap<String, String> = HashMap::new();
+    svg.elements.retain_mut(|element| match element {
+        SvgElement::Defs(defs) => {
+            defs.elements
+                .retain(|symbol| match canonical.get(&content_key(symbol)) {
+                    Some(id) if *id != symbol.id => {
+                        renamed.insert(symbol.id.clone(), id.clone());
+                        false
+                    }
+                    Some(_) => true,
+                    None => {
+                        canonical.insert(content_key(symbol), symbol.id.clone());
+                        true
+                    }
+                });
+            !defs.elements.is_empty() || !defs.definitions.is_empty()
+        }
+        _ => true,
+    });
+
+    if renamed.is_empty() {
+        return;
+    }
+    for element in &mut svg.elements {
+        if let SvgElement::G(g) = element {
+            redirect_uses(g, &renamed);
+        }
+    }
+}
+
+/// The content of `symbol` in a form that is equal for identical outlines.
+fn content_key(symbol: &Symbol) -> String {
+    let element = match &symbol.element {
+        SymbolEle::Path(path) => SymbolEle::Path(Path {
+            d: path.d.split_whitespace().collect::<Vec<_>>().join(" "),
+            ..path.clone()
+        }),
+        SymbolEle::Image(image) => SymbolEle::Image(image.clone()),
+    };
+    format!(
+        "{:?} {:?} {:?} {:?}",
+        symbol.overflow, symbol.view_box, symbol.preserve_aspect_ratio, element
+    )
+}
+
+fn redirect_uses(g: &mut G, renamed: &HashMap<String, String>) {
+    for element in g.elements.iter_mut().flatten() {
+        redirect_use(element, renamed);
+    }
+}
+
+fn redirect_use(element: &mut GEle, renamed: &HashMap<String, String>) {
+    match element {
+        GEle::G(g) => redirect_uses(g, renamed),
+        GEle::Use(uuse) => {
+            if let Some(id) = uuse.href.strip_prefix('#').and_then(|id| renamed.get(id)) {
+                uuse.href = format!("#{}", id).into();
+            }
+        }
+        GEle::Switch(switch) => {
+            for element in switch.elements_mut() {
+                redirect_use(element, renamed);
+            }
+        }
+        GEle::Path(_) | GEle::Image(_) | GEle::Text(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{options::RsxOptions, parse_svg, passes::apply};
+
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+        <g>
+            <use href="#glyph0" x="0"/>
+            <use href="#glyph1" x="1"/>
+            <use href="#glyph2" x="2"/>
+        </g>
+        <defs id="glyph">
+            <symbol id="glyph0" overflow="visible"><path d="M 0 0 L 1 1 Z"/></symbol>
+            <symbol id="glyph1" overflow="visible"><path d="M 0 0 L 1 1 Z"/></symbol>
+            <symbol id="glyph2" overflow="visible"><path d="M 0 0 L 2 2 Z"/></symbol>
+        </defs>
+    </svg>"##;
+
+    fn ids_and_hrefs(svg: &Svg) -> (Vec<String>, Vec<String>) {
+        let SvgElement::G(g) = &svg.elements[0] else {
+            panic!()
+        };
+        let hrefs = g
+            .elements
+            .iter()
+            .flatten()
+            .map(|element| match element {
+                GEle::Use(uuse) => uuse.href.to_string(),
+                _ => panic!(),
+            })
+            .collect();
+        let SvgElement::Defs(defs) = &svg.elements[1] else {
+            panic!()
+        };
+        let ids = defs
+            .elements
+            .iter()
+            .map(|symbol| symbol.id.clone())
+            .collect();
+        (ids, hrefs)
+    }
+
+    #[test]
+    fn dedupe_test() {
+        let mut svg = parse_svg(SVG).unwrap();
+        dedupe_symbols(&mut svg);
+        let (ids, hrefs) = ids_and_hrefs(&svg);
+        assert_eq!(ids, ["glyph0", "glyph2"]);
+        assert_eq!(hrefs, ["#glyph0", "#glyph0", "#glyph2"]);
+        assert!(svg.validate_references().is_empty());
+    }
+
+    #[test]
+    fn view_box_test() {
+        // The same outline in another viewBox is drawn at another size
+        let svg_str = SVG.replace(
+            r#"<symbol id="glyph1" overflow="visible">"#,
+            r#"<symbol id="glyph1" overflow="visible" viewBox="0 0 2 2">"#,
+        );
+        let mut svg = parse_svg(&svg_str).unwrap();
+        dedupe_symbols(&mut svg);
+        let (ids, hrefs) = ids_and_hrefs(&svg);
+        assert_eq!(ids, ["glyph0", "glyph1", "glyph2"]);
+        assert_eq!(hrefs, ["#glyph0", "#glyph1", "#glyph2"]);
+    }
+
+    #[test]
+    fn dedupe_then_prefix_test() {
+        let mut svg = parse_svg(SVG).unwrap();
+        let options = RsxOptions {
+            id_prefix: Some("a-".to_string()),
+            dedupe_symbols: true,
+            ..Default::default()
+        };
+        apply(&mut svg, &options).unwrap();
+        let (ids, hrefs) = ids_and_hrefs(&svg);
+        assert_eq!(ids, ["a-glyph0", "a-glyph2"]);
+        assert_eq!(hrefs, ["#a-glyph0", "#a-glyph0", "#a-glyph2"]);
+        assert!(svg.validate_references().is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+use super::Siblings;
+use crate::{report::Warning, svg_types::*};
+
+/// Removes the elements that cannot show anything, returning a [`Warning::MissingAttribute`] for
+/// each.
+///
+/// The parsers accept these elements instead of failing on the whole document: a `path` without
+/// `d` (the `<path fill="none"/>` placeholders left by some optimizers), a `use` or an `image`
+/// without `href`, and a `symbol` without `id`, which nothing can reference. A symbol whose path
+/// has no `d` is kept, since uses may refer to it, but is reported as well.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::drop_degenerate};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><path fill='none'/><path d='M 0 0 L 10 10'/></g></svg>",
+/// )
+/// .unwrap();
+/// let warnings = drop_degenerate(&mut svg);
+/// assert_eq!(warnings[0].to_string(), "svg > g[0] > path[0]: no `d` attribute, nothing to show");
+/// ```
+pub fn drop_degenerate(svg: &mut Svg) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let mut siblings = Siblings::new("svg");
+    svg.elements.retain_mut(|element| match element {
+        SvgElement::Path(path) => keep(siblings.path("path"), path_problem(path), &mut warnings),
+        SvgElement::G(g) => {
+            drop_in_g(g, siblings.path("g"), &mut warnings);
+            true
+        }
+        SvgElement::Defs(defs) => {
+            let mut symbols = Siblings::new(siblings.path("defs"));
+            defs.elements.retain(|symbol| {
+                let path = symbols.path("symbol");
+                if symbol.id.is_empty() {
+                    return keep(path, Some("id"), &mut warnings);
+                }
+                if let SymbolEle::Path(symbol_path) = &symbol.element {
+                    if let Some(attribute) = path_problem(symbol_path) {
+                        warnings.push(Warning::MissingAttribute {
+                            attribute,
+                            path: format!("{} > path[0]", path),
+                        });
+                    }
+                }
+                true
+            });
+            true
+        }
+        SvgElement::Rect(_) => {
+            siblings.path("rect");
+            true
+        }
+    });
+    warnings
+}
+
+fn drop_in_g(g: &mut G, path: String, warnings: &mut Vec<Warning>) {
+    let mut siblings = Siblings::new(path);
+    if let Some(elements) = &mut g.elements {
+        elements.retain_mut(|element| keep_g_ele(element, &mut siblings, warnings));
+    }
+}
+
+fn keep_g_ele(element: &mut GEle, siblings: &mut Siblings, warnings: &mut Vec<Warning>) -> bool {
+    match element {
+        GEle::G(g) => {
+            drop_in_g(g, siblings.path("g"), warnings);
+            true
+        }
+        GEle::Use(uuse) => {
+            let missing = uuse.href.is_empty().then_some("href");
+            keep(siblings.path("use"), missing, warnings)
+        }
+        GEle::Path(path) => keep(siblings.path("path"), path_problem(path), warnings),
+        GEle::Image(image) => {
+            let missing = image.href.is_empty().then_some("href");
+            keep(siblings.path("image"), missing, warnings)
+        }
+        GEle::Text(_) => {
+            siblings.path("text");
+            true
+        }
+        GEle::Switch(switch) => {
+            let mut branches = Siblings::new(siblings.path("switch"));
+            switch.branches.retain_mut(|branch| match branch {
+                SwitchBranch::Element(element) => keep_g_ele(element, &mut branches, warnings),
+                SwitchBranch::Unsupported(_) => true,
+            });
+            true
+        }
+    }
+}
+
+/// The attribute missing for `path` to draw anything, if any.
+fn path_problem(path: &Path) -> Option<&'static str> {
+    path.d.trim().is_empty().then_some("d")
+}
+
+/// Whether to keep the element at `path`, reporting the `missing` attribute if there is one.
+fn keep(path: String, missing: Option<&'static str>, warnings: &mut Vec<Warning>) -> bool {
+    match missing {
+        Some(attribute) => {
+            warnings.push(Warning::MissingAttribute { attribute, path });
+            false
+        }
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn drop_degenerate_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <path fill="none"/>
+                <g>
+                    <use href="#s0" x="0"/><use x="1"/>
+                    <image width="1" height="1" preserveAspectRatio="none"/>
+                    <g><path d=" "/><path d="M 0 0 L 1 1"/></g>
+                </g>
+                <defs id="glyph">
+                    <symbol id="s0" overflow="visible"><path/></symbol>
+                    <symbol overflow="visible"><path d="M 0 0"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        let warnings = drop_degenerate(&mut svg);
+        let missing = |attribute, path: &str| Warning::MissingAttribute {
+            attribute,
+            path: path.to_string(),
+        };
+        assert_eq!(
+            warnings,
+            [
+                missing("d", "svg > path[0]"),
+                missing("href", "svg > g[0] > use[1]"),
+                missing("href", "svg > g[0] > image[0]"),
+                missing("d", "svg > g[0] > g[0] > path[0]"),
+                missing("d", "svg > defs[0] > symbol[0] > path[0]"),
+                missing("id", "svg > defs[0] > symbol[1]"),
+            ]
+        );
+
+        assert_eq!(svg.elements.len(), 2);
+        let SvgElement::G(g) = &svg.elements[0] else {
+            panic!("expected the group");
+        };
+        let elements = g.elements.as_ref().unwrap();
+        assert_eq!(elements.len(), 2);
+        let GEle::G(inner) = &elements[1] else {
+            panic!("expected the inner group");
+        };
+        assert_eq!(inner.elements.as_ref().unwrap().len(), 1);
+        let SvgElement::Defs(defs) = &svg.elements[1] else {
+            panic!("expected the defs");
+        };
+        assert_eq!(defs.elements.len(), 1);
+        // Only the kept symbol is reported again
+        assert_eq!(drop_degenerate(&mut svg).len(), 1);
+    }
+}
@@ -0,0 +1,352 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use super::Siblings;
+use crate::{error::Error, options::DuplicateIds, report::Warning, svg_types::*};
+
+/// Finds the `id`s carried by more than one element and handles them as `mode` says.
+///
+/// The `id`s of `defs`, `symbol`, gradient, `g`, `path` and `text` elements are compared. Typst gives every
+/// document the same glyph ids, so two documents pasted into one file, or a hand-edited label,
+/// leave several elements with the same `id`: a `use` then shows whichever the renderer finds
+/// first, and [`Svg::find_by_id`] returns a single one of them. The first element with an `id`,
+/// in document order, always keeps it.
+///
+/// - [`DuplicateIds::Error`] fails with the duplicated ids, leaving the document unchanged.
+/// - [`DuplicateIds::Warn`] removes the `id` from the later elements, like the renderers ignore
+///   it. A later symbol, which nothing can refer to any more, is removed along with its content.
+/// - [`DuplicateIds::Rename`] suffixes the `id` of the later elements with `-1`, `-2`, … skipping
+///   the ids already taken. A `use` referring to the id is pointed at the renamed element when it
+///   is the only symbol among the elements sharing the id, the one a `use` can show; references
+///   that could mean either element are left on the first.
+///
+/// # Return value
+///
+/// - `Ok(Vec<Warning>)` : a [`Warning::DuplicateId`] for each later element, in document order.
+/// - `Err(Error)` : [`Error::DuplicateIds`] with `DuplicateIds::Error`, when an id is duplicated.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::DuplicateIds, parse_svg, passes::resolve_duplicate_ids};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g id='step'><path d='M 0 0 L 1 1'/></g><g id='step'><path d='M 0 0 L 2 2'/></g></svg>",
+/// )
+/// .unwrap();
+/// let warnings = resolve_duplicate_ids(&mut svg, DuplicateIds::Rename).unwrap();
+/// assert_eq!(
+///     warnings[0].to_string(),
+///     "svg > g[1]: duplicate id `step` renamed to `step-1`"
+/// );
+/// assert!(svg.find_by_id("step-1").is_some());
+/// ```
+pub fn resolve_duplicate_ids(svg: &mut Svg, mode: DuplicateIds) -> Result<Vec<Warning>, Error> {
+    let mut occurrences = Vec::new();
+    walk_ids(svg, &mut |id, symbol, path| {
+        occurrences.push((id.to_string(), symbol, path));
+        Action::Keep
+    });
+
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    let later: Vec<bool> = occurrences
+        .iter()
+        .map(|(id, _, _)| {
+            let later = !seen.insert(id.as_str());
+            if later && !duplicates.contains(id) {
+                duplicates.push(id.clone());
+            }
+            later
+        })
+        .collect();
+    if duplicates.is_empty() {
+        return Ok(Vec::new());
+    }
+    if mode == DuplicateIds::Error {
+        return Err(Error::DuplicateIds { ids: duplicates });
+    }
+
+    let mut taken: HashSet<String> = occurrences.iter().map(|(id, _, _)| id.clone()).collect();
+    let mut suffixes: HashMap<&str, usize> = HashMap::new();
+    let mut warnings = Vec::new();
+    let mut actions = Vec::with_capacity(occurrences.len());
+    for ((id, _, path), later) in occurrences.iter().zip(&later) {
+        if !later {
+            actions.push(Action::Keep);
+            continue;
+        }
+        let renamed = (mode == DuplicateIds::Rename).then(|| {
+            let suffix = suffixes.entry(id.as_str()).or_default();
+            loop {
+                *suffix += 1;
+                let renamed = format!("{}-{}", id, suffix);
+                if taken.insert(renamed.clone()) {
+                    break renamed;
+                }
+            }
+        });
+        warnings.push(Warning::DuplicateId {
+            id: id.clone(),
+            path: path.clone(),
+            renamed: renamed.clone(),
+        });
+        actions.push(renamed.map_or(Action::Remove, Action::Rename));
+    }
+
+    // The `use` references that can only mean a renamed symbol
+    let mut retargeted = HashMap::new();
+    for id in &duplicates {
+        let mut symbols = occurrences
+            .iter()
+            .zip(&actions)
+            .filter(|((other, symbol, _), _)| other == id && *symbol);
+        if let (Some((_, Action::Rename(renamed))), None) = (symbols.next(), symbols.next()) {
+            retargeted.insert(format!("#{}", id), Arc::from(format!("#{}", renamed)));
+        }
+    }
+
+    let mut actions = actions.into_iter();
+    walk_ids(svg, &mut |_, _, _| actions.next().unwrap_or(Action::Keep));
+    if !retargeted.is_empty() {
+        for element in &mut svg.elements {
+            if let SvgElement::G(g) = element {
+                retarget_uses(g, &retargeted);
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+/// What becomes of one `id`.
+enum Action {
+    Keep,
+    Remove,
+    Rename(String),
+}
+
+/// Calls `decide` on every non-empty `id` in document order, with whether it is the `id` of a
+/// symbol and the path of its element, and applies the action returned.
+fn walk_ids(svg: &mut Svg, decide: &mut impl FnMut(&str, bool, String) -> Action) {
+    let mut siblings = Siblings::new("svg");
+    for element in &mut svg.elements {
+        match element {
+            SvgElement::Path(path) => optional_id(&mut path.id, siblings.path("path"), decide),
+            SvgElement::G(g) => walk_g(g, siblings.path("g"), decide),
+            SvgElement::Defs(defs) => {
+                let path = siblings.path("defs");
+                if !defs.id.is_empty() {
+                    match decide(&defs.id, false, path.clone()) {
+                        Action::Keep => {}
+                        Action::Remove => defs.id.clear(),
+                        Action::Rename(id) => defs.id = id,
+                    }
+                }
+                let mut symbols = Siblings::new(path);
+                defs.elements.retain_mut(|symbol| {
+                    let path = symbols.path("symbol");
+                    let mut keep = true;
+                    if !symbol.id.is_empty() {
+                        match decide(&symbol.id, true, path.clone()) {
+                            Action::Keep => {}
+                            Action::Remove => keep = false,
+                            Action::Rename(id) => symbol.id = id,
+                        }
+                    }
+                    // Visited even in a removed symbol, for every id to be decided in both walks
+                    if let SymbolEle::Path(symbol_path) = &mut symbol.element {
+                        optional_id(&mut symbol_path.id, format!("{} > path[0]", path), decide);
+                    }
+                    keep
+                });
+                for definition in &mut defs.definitions {
+                    let path = symbols.path(definition.tag());
+                    let id = definition.id_mut();
+                    if !id.is_empty() {
+                        match decide(id, false, path) {
+                            Action::Keep => {}
+                            Action::Remove => id.clear(),
+                            Action::Rename(renamed) => *id = renamed,
+                        }
+                    }
+                }
+            }
+            SvgElement::Rect(_) => {}
+        }
+    }
+}
+
+fn walk_g(g: &mut G, path: String, decide: &mut impl FnMut(&str, bool, String) -> Action) {
+    optional_id(&mut g.id, path.clone(), decide);
+    let mut siblings = Siblings::new(path);
+    for element in g.elements.iter_mut().flatten() {
+        walk_g_ele(element, &mut siblings, decide);
+    }
+}
+
+fn walk_g_ele(
+    element: &mut GEle,
+    siblings: &mut Siblings,
+    decide: &mut impl FnMut(&str, bool, String) -> Action,
+) {
+    match element {
+        GEle::G(g) => walk_g(g, siblings.path("g"), decide),
+        GEle::Path(path) => optional_id(&mut path.id, siblings.path("path"), decide),
+        GEle::Text(text) => optional_id(&mut text.id, siblings.path("text"), decide),
+        GEle::Switch(switch) => {
+            let path = siblings.path("switch");
+            optional_id(&mut switch.id, path.clone(), decide);
+            let mut branches = Siblings::new(path);
+            for element in switch.elements_mut() {
+                walk_g_ele(element, &mut branches, decide);
+            }
+        }
+        GEle::Use(_) | GEle::Image(_) => {}
+    }
+}
+
+fn optional_id(
+    id: &mut Option<String>,
+    path: String,
+    decide: &mut impl FnMut(&str, bool, String) -> Action,
+) {
+    let Some(current) = id.as_deref().filter(|id| !id.is_empty()) else {
+        return;
+    };
+    match decide(current, false, path) {
+        Action::Keep => {}
+        Action::Remove => *id = None,
+        Action::Rename(renamed) => *id = Some(renamed),
+    }
+}
+
+fn retarget_uses(g: &mut G, retargeted: &HashMap<String, Arc<str>>) {
+    for element in g.elements.iter_mut().flatten() {
+        retarget_use(element, retargeted);
+    }
+}
+
+fn retarget_use(element: &mut GEle, retargeted: &HashMap<String, Arc<str>>) {
+    match element {
+        GEle::G(g) => retarget_uses(g, retargeted),
+        GEle::Use(uuse) => {
+            if let Some(href) = retargeted.get(&*uuse.href) {
+                uuse.href = href.clone();
+            }
+        }
+        GEle::Switch(switch) => {
+            for element in switch.elements_mut() {
+                retarget_use(element, retargeted);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compat::render_html, options::RsxOptions, parse_svg, parse_svg_to_rsx_with_options,
+        svg_to_rsx_with_options, utils::read_file,
+    };
+
+    fn fixture() -> Svg {
+        parse_svg(&read_file("./test/duplicate_ids.svg").unwrap()).unwrap()
+    }
+
+    fn hrefs(svg: &Svg) -> Vec<&str> {
+        svg.find_all(|node| matches!(node, SvgNodeRef::Use(_)))
+            .into_iter()
+            .map(|node| match node {
+                SvgNodeRef::Use(uuse) => &*uuse.href,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    fn ids(svg: &Svg) -> Vec<&str> {
+        svg.find_all(|node| node.id().is_some())
+            .into_iter()
+            .filter_map(|node| node.id())
+            .collect()
+    }
+
+    #[test]
+    fn error_test() {
+        let mut svg = fixture();
+        let error = resolve_duplicate_ids(&mut svg, DuplicateIds::Error).unwrap_err();
+        assert!(
+            matches!(&error, Error::DuplicateIds { ids } if ids == &["glyph", "glyph0", "mark"])
+        );
+        assert_eq!(
+            error.to_string(),
+            "Duplicate ids in the SVG: glyph, glyph0, mark"
+        );
+        assert_eq!(svg, fixture());
+
+        // The conversions that can fail do, the others keep the first element
+        let options = RsxOptions {
+            duplicate_ids: Some(DuplicateIds::Error),
+            ..Default::default()
+        };
+        let svg_str = read_file("./test/duplicate_ids.svg").unwrap();
+        assert!(matches!(
+            parse_svg_to_rsx_with_options(&svg_str, &options),
+            Err(Error::DuplicateIds { .. })
+        ));
+        let html = render_html(svg_to_rsx_with_options(fixture(), &options));
+        assert_eq!(html.matches(r#"id="glyph0""#).count(), 1);
+    }
+
+    #[test]
+    fn warn_test() {
+        let mut svg = fixture();
+        let warnings = resolve_duplicate_ids(&mut svg, DuplicateIds::Warn).unwrap();
+        let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "svg > defs[1]: duplicate id `glyph` removed, the first element keeps it",
+                "svg > defs[1] > symbol[0]: duplicate id `glyph0` removed, the first element keeps it",
+                "svg > defs[1] > symbol[1]: duplicate id `mark` removed, the first element keeps it",
+            ]
+        );
+        // The later symbols go, the references stay
+        assert_eq!(ids(&svg), ["mark", "glyph0", "glyph1"]);
+        assert_eq!(hrefs(&svg), hrefs(&fixture()));
+        assert!(resolve_duplicate_ids(&mut svg, DuplicateIds::Error).is_ok());
+    }
+
+    #[test]
+    fn rename_test() {
+        let mut svg = fixture();
+        let warnings = resolve_duplicate_ids(&mut svg, DuplicateIds::Rename).unwrap();
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(
+            ids(&svg),
+            ["mark", "glyph0", "glyph1", "glyph0-1", "mark-1"]
+        );
+        let SvgElement::Defs(defs) = &svg.elements[3] else {
+            panic!()
+        };
+        assert_eq!(defs.id, "glyph-1");
+        // Only a symbol can be shown by a `use`, the two `glyph0` are both symbols
+        assert_eq!(hrefs(&svg), ["#glyph0", "#glyph1", "#glyph0", "#mark-1"]);
+        assert!(svg.validate_references().is_empty());
+        assert!(resolve_duplicate_ids(&mut svg, DuplicateIds::Error).is_ok());
+
+        // The suffixes skip the ids in use
+        let mut svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+             <path id='a' d='M 0 0'/><path id='a-1' d='M 0 0'/><path id='a' d='M 0 0'/>\
+             <path id='a' d='M 0 0'/></svg>",
+        )
+        .unwrap();
+        resolve_duplicate_ids(&mut svg, DuplicateIds::Rename).unwrap();
+        assert_eq!(ids(&svg), ["a", "a-1", "a-2", "a-3"]);
+    }
+}
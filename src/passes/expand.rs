@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::Error,
+    svg_types::{
+        transform::{Matrix, Transform, TransformOp},
+        *,
+    },
+};
+
+/// Replaces every `use` element with a copy of the symbol it references, then removes the symbols
+/// and the `defs` left empty. The gradients stay, for the paints referencing them.
+///
+/// Each `use` becomes a `g` holding a copy of the symbol's content, with the `use`'s `transform`
+/// followed by a translation by its `x`. The `fill` and `fill-rule` of the `use` are inherited by
+/// the copied path unless it sets its own, as they would be through the reference. The result
+/// renders like the original without any `use` element, for renderers that do not support them.
+///
+/// The content of a symbol with a `viewBox` is also fitted to the `width` and `height` of the
+/// `use`, as [`Symbol::viewport_matrix`] gives, with a translation and a scaling after the one by
+/// `x`. When that size cannot be resolved, the content is copied at its own size.
+///
+/// In this crate's model a symbol holds a single path or image, so references cannot be chained
+/// and every `use` is resolved in one step.
+///
+/// # Return value
+///
+/// - `Ok(())` : every `use` was expanded.
+/// - `Err(Error)` : [`Error::UnresolvedReference`] when a `use` points at an id no symbol carries.
+///   The document is left unchanged in that case.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::expand_uses, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><use href='#glyph0' x='5' fill='#000000'/></g>\
+///      <defs id='glyph'><symbol id='glyph0' overflow='visible'><path d='M 0 0 L 1 1'/></symbol></defs>\
+///      </svg>",
+/// )
+/// .unwrap();
+/// expand_uses(&mut svg).unwrap();
+/// assert_eq!(svg.elements.len(), 1);
+/// ```
+pub fn expand_uses(svg: &mut Svg) -> Result<(), Error> {
+    let mut symbols = HashMap::new();
+    for element in &svg.elements {
+        if let SvgElement::Defs(defs) = element {
+            for symbol in &defs.elements {
+                symbols.entry(symbol.id.as_str()).or_insert(symbol);
+            }
+        }
+    }
+
+    // Resolve everything first so that a failure leaves the document untouched
+    fn check(element: &GEle, symbols: &HashMap<&str, &Symbol>) -> Result<(), Error> {
+        match element {
+            GEle::G(g) => g
+                .elements
+                .iter()
+                .flatten()
+                .try_for_each(|element| check(element, symbols))?,
+            GEle::Use(uuse) => {
+                let id = uuse.href.strip_prefix('#').unwrap_or(&uuse.href);
+                if !symbols.contains_key(id) {
+                    return Err(Error::UnresolvedReference {
+                        href: uuse.href.to_string(),
+                    });
+                }
+            }
+            GEle::Switch(switch) => switch
+                .elements()
+                .try_for_each(|element| check(element, symbols))?,
+            GEle::Path(_) | GEle::Image(_) | GEle::Text(_) => {}
+        }
+        Ok(())
+    }
+    for element in &svg.elements {
+        if let SvgElement::G(g) = element {
+            g.elements
+                .iter()
+                .flatten()
+                .try_for_each(|element| check(element, &symbols))?;
+        }
+    }
+
+    let symbols: HashMap<String, Symbol> = symbols
+        .into_iter()
+        .map(|(id, symbol)| (id.to_string(), symbol.clone()))
+        .collect();
+    let viewport = svg
+        .view_box_parsed()
+        .ok()
+        .map(|view_box| (view_box.width, view_box.height));
+    svg.elements.retain_mut(|element| match element {
+        SvgElement::Defs(defs) => {
+            defs.elements.clear();
+            !defs.definitions.is_empty()
+        }
+        _ => true,
+    });
+    for element in &mut svg.elements {
+        if let SvgElement::G(g) = element {
+            expand_g(g, &symbols, viewport);
+        }
+    }
+    Ok(())
+}
+
+fn expand_g(g: &mut G, symbols: &HashMap<String, Symbol>, viewport: Option<(f64, f64)>) {
+    for element in g.elements.iter_mut().flatten() {
+        expand_g_ele(element, symbols, viewport);
+    }
+}
+
+fn expand_g_ele(
+    element: &mut GEle,
+    symbols: &HashMap<String, Symbol>,
+    viewport: Option<(f64, f64)>,
+) {
+    match element {
+        GEle::G(g) => expand_g(g, symbols, viewport),
+        GEle::Use(uuse) => {
+            let id = uuse.href.strip_prefix('#').unwrap_or(&uuse.href);
+            let symbol = &symbols[id];
+            let content = match &symbol.element {
+                SymbolEle::Path(path) => {
+                    let mut path = path.clone();
+                    if path.fill.is_none() {
+                        path.fill = uuse.fill.clone();
+                    }
+                    if path.fill_rule.is_none() {
+                        path.fill_rule = uuse.fill_rule.clone();
+                    }
+                    GEle::Path(Box::new(path))
+                }
+                SymbolEle::Image(image) => GEle::Image(Box::new(image.clone())),
+            };
+            let translate = (uuse.x.trim().parse::<f64>() != Ok(0.0))
+                .then(|| format!("translate({} 0)", uuse.x.trim()));
+            let viewport = symbol
+                .viewport_matrix(uuse, viewport)
+                .map(viewport_transform)
+                .filter(|transform| !transform.is_empty());
+            let transform = [uuse.transform.take(), translate, viewport]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            let transform = (!transform.is_empty()).then(|| transform.join(" "));
+            *element = GEle::G(Box::new(G {
+                transform,
+                ..G::with_elements(vec![content])
+            }));
+        }
+        GEle::Switch(switch) => {
+            for element in switch.elements_mut() {
+                expand_g_ele(element, symbols, viewport);
+            }
+        }
+        GEle::Path(_) | GEle::Image(_) | GEle::Text(_) => {}
+    }
+}
+
+/// Writes the fitting of a `viewBox` as a translation and a scaling, leaving out those that do
+/// nothing.
+fn viewport_transform(matrix: Matrix) -> String {
+    let mut transform = Transform::default();
+    if matrix.e != 0.0 || matrix.f != 0.0 {
+        transform.ops.push(TransformOp::Translate {
+            x: matrix.e,
+            y: matrix.f,
+        });
+    }
+    if matrix.a != 1.0 || matrix.d != 1.0 {
+        transform.ops.push(TransformOp::Scale {
+            x: matrix.a,
+            y: matrix.d,
+        });
+    }
+    transform.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use std::fs;
+
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 20 10" width="20pt" height="10pt">
+        <g class="typst-text" transform="translate(1 2)">
+            <use href="#glyph0" x="0" fill="#000000" fill-rule="nonzero"/>
+            <use href="#glyph0" x="3.5" fill="#ff0000" transform="scale(2)"/>
+            <use href="#glyph1" x="1"/>
+        </g>
+        <defs id="glyph">
+            <symbol id="glyph0" overflow="visible">
+                <path d="M 1 1 L 2 2 Z"/>
+            </symbol>
+            <symbol id="glyph1" overflow="visible">
+                <path d="M 0 0 L 1 0 Z" fill="#0000ff"/>
+            </symbol>
+        </defs>
+    </svg>"##;
+
+    fn expanded(element: &GEle) -> (Option<&str>, &Path) {
+        let GEle::G(g) = element else {
+            panic!("expected an expanded use, got {:?}", element)
+        };
+        let Some([GEle::Path(path)]) = g.elements.as_deref() else {
+            panic!()
+        };
+        (g.transform.as_deref(), path)
+    }
+
+    #[test]
+    fn expand_test() {
+        let mut svg = parse_svg(SVG).unwrap();
+        expand_uses(&mut svg).unwrap();
+        assert_eq!(svg.elements.len(), 1);
+        let SvgElement::G(text) = &svg.elements[0] else {
+            panic!()
+        };
+        assert_eq!(text.transform.as_deref(), Some("translate(1 2)"));
+        let elements = text.elements.as_ref().unwrap();
+
+        let (transform, path) = expanded(&elements[0]);
+        assert_eq!(transform, None);
+        assert_eq!(path.d, "M 1 1 L 2 2 Z");
+        assert_eq!(path.fill.as_deref(), Some("#000000"));
+        assert_eq!(path.fill_rule, Some(FillRule::NonZero));
+
+        let (transform, path) = expanded(&elements[1]);
+        assert_eq!(transform, Some("scale(2) translate(3.5 0)"));
+        assert_eq!(path.fill.as_deref(), Some("#ff0000"));
+
+        // The symbol's own fill wins over the inherited one
+        let (transform, path) = expanded(&elements[2]);
+        assert_eq!(transform, Some("translate(1 0)"));
+        assert_eq!(path.fill.as_deref(), Some("#0000ff"));
+    }
+
+    #[test]
+    fn unresolved_test() {
+        let mut svg = parse_svg(&SVG.replace("#glyph1", "#glyph2")).unwrap();
+        let error = expand_uses(&mut svg).unwrap_err();
+        assert!(matches!(error, Error::UnresolvedReference { href } if href == "#glyph2"));
+        assert_eq!(svg.elements.len(), 2);
+    }
+
+    #[test]
+    fn fixture_test() {
+        fn count_uses(g: &G) -> usize {
+            g.elements
+                .iter()
+                .flatten()
+                .map(|element| match element {
+                    GEle::G(g) => count_uses(g),
+                    GEle::Use(_) => 1,
+                    _ => 0,
+                })
+                .sum()
+        }
+
+        let mut svg = parse_svg(&fs::read_to_string("./test/expected.svg").unwrap()).unwrap();
+        expand_uses(&mut svg).unwrap();
+        for element in &svg.elements {
+            match element {
+                SvgElement::G(g) => assert_eq!(count_uses(g), 0),
+                SvgElement::Defs(_) => panic!("the defs should be removed"),
+                SvgElement::Path(_) | SvgElement::Rect(_) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,457 @@
+use super::geometry::{parse_path, parse_transform, write_path, Matrix};
+use crate::svg_types::{number::format_number, *};
+
+/// Applies the `transform` of every group to its content, then removes it.
+///
+/// The transforms of nested groups are composed down the tree and the result is applied to the
+/// coordinates of every `path` (`translate`, `scale`, `rotate`, `matrix`, `skewX` and `skewY` are
+/// supported; relative commands and arcs are converted to absolute ones). The stroke width of a
+/// scaled path is scaled by the same factor, which is only the same stroke when the transform
+/// scales both axes the same. `use` and `image` elements cannot be moved the same
+/// way, because they are positioned by their own coordinate system: they receive the composed
+/// transformation as a single `matrix(...)` transform instead.
+///
+/// Symbols are not modified, since every `use` places them in its own position. A group whose
+/// transform or path data cannot be parsed keeps a transform, combined with the ones of its
+/// ancestors, and its content is left as is, like a group with a `clip-path`, a `mask` or a
+/// `filter`, which are placed in the coordinates the group sets, and a group holding a path
+/// painted with a gradient or a pattern through `url(…)`, which would stop following the shape.
+/// So is a group holding a stroked path when the composed transform scales the axes differently
+/// or skews them, which makes the stroke thicker in some directions than in others, or when the
+/// `stroke-width` has a unit (`1pt`) or is a percentage, which is not scaled with the user units.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::flatten_transforms, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g transform='translate(1 2)'><g transform='scale(2)'><path d='M 0 0 L 1 1'/></g></g>\
+///      </svg>",
+/// )
+/// .unwrap();
+/// flatten_transforms(&mut svg);
+/// let SvgElement::G(outer) = &svg.elements[0] else { unreachable!() };
+/// let GEle::G(inner) = &outer.elements.as_ref().unwrap()[0] else { unreachable!() };
+/// let GEle::Path(path) = &inner.elements.as_ref().unwrap()[0] else { unreachable!() };
+/// assert_eq!(path.d, "M 1 2 L 3 4");
+/// assert!(outer.transform.is_none() && inner.transform.is_none());
+/// ```
+pub fn flatten_transforms(svg: &mut Svg) {
+    for element in &mut svg.elements {
+        if let SvgElement::G(g) = element {
+            flatten_g(g, Matrix::IDENTITY);
+        }
+    }
+}
+
+/// Flattens `g`, placed in a coordinate system transformed by `matrix`.
+fn flatten_g(g: &mut G, matrix: Matrix) {
+    let own = match g.transform.as_deref() {
+        Some(transform) => parse_transform(transform),
+        None => Some(Matrix::IDENTITY),
+    };
+    let matrix = match own.map(|own| matrix.then(own)) {
+        Some(combined) if can_flatten(g, &combined) => combined,
+        _ => {
+            // Keep the group as a unit, under the transformations removed from its ancestors
+            if !matrix.is_identity() {
+                let own = g.transform.take().unwrap_or_default();
+                g.transform = Some(format!("{} {}", matrix, own).trim_end().to_string());
+            }
+            return;
+        }
+    };
+    g.transform = None;
+
+    for element in g.elements.iter_mut().flatten() {
+        match element {
+            GEle::G(g) => flatten_g(g, matrix),
+            GEle::Path(path) => transform_path(path, &matrix),
+            GEle::Use(uuse) => uuse.transform = combine(&matrix, uuse.transform.take()),
+            GEle::Image(image) => image.transform = combine(&matrix, image.transform.take()),
+            GEle::Text(text) => text.transform = combine(&matrix, text.transform.take()),
+            GEle::Switch(switch) => switch.transform = combine(&matrix, switch.transform.take()),
+        }
+    }
+}
+
+/// Whether `g` is neither clipped, masked nor filtered and the paths directly inside it can be
+/// parsed, are not painted with a paint server, and keep their stroke under `matrix`.
+///
+/// Nested groups are checked when they are flattened themselves.
+fn can_flatten(g: &G, matrix: &Matrix) -> bool {
+    g.clip_path.is_none()
+        && g.mask.is_none()
+        && g.filter.is_none()
+        && g.elements.iter().flatten().all(|element| match element {
+            GEle::Path(path) => {
+                parse_path(&path.d).is_some()
+                    && !path.has_paint_server()
+                    && (!is_stroked(path)
+                        || similarity_scale(matrix).is_some() && stroke_width(path).is_some())
+            }
+            _ => true,
+        })
+}
+
+fn is_stroked(path: &Path) -> bool {
+    path.stroke
+        .as_deref()
+        .is_some_and(|stroke| stroke != "none")
+}
+
+/// The stroke width of `path` in user units, `None` when it has a unit or is a percentage.
+fn stroke_width(path: &Path) -> Option<f64> {
+    match path.stroke_width.as_deref() {
+        Some(width) => width
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|width| width.is_finite()),
+        None => Some(1.0),
+    }
+}
+
+/// The factor by which `matrix` scales every length, when it is a similarity: a scaling by the
+/// same factor along both axes, combined with rotations, reflections and translations.
+fn similarity_scale(matrix: &Matrix) -> Option<f64> {
+    const EPSILON: f64 = 1e-9;
+    // The images of the unit vectors, which have the same length and are orthogonal
+    let x = matrix.a.hypot(matrix.b);
+    let y = matrix.c.hypot(matrix.d);
+    let dot = matrix.a * matrix.c + matrix.b * matrix.d;
+    ((x - y).abs() <= EPSILON * x.max(y) && dot.abs() <= EPSILON * x * y).then_some(x)
+}
+
+fn transform_path(path: &mut Path, matrix: &Matrix) {
+    if matrix.is_identity() {
+        return;
+    }
+    if let Some(segments) = parse_path(&path.d) {
+        let segments: Vec<_> = segments
+            .into_iter()
+            .map(|segment| segment.transform(matrix))
+            .collect();
+        path.d = write_path(&segments);
+    }
+
+    // `can_flatten` checked that both are known for a stroked path
+    if let (true, Some(factor), Some(width)) = (
+        is_stroked(path),
+        similarity_scale(matrix),
+        stroke_width(path),
+    ) {
+        if (factor - 1.0).abs() > 1e-9 {
+            path.stroke_width = Some(format_number(width * factor).into());
+        }
+    }
+}
+
+/// Prepends `matrix` to an element's own transform, dropping the result if it is the identity.
+fn combine(matrix: &Matrix, transform: Option<String>) -> Option<String> {
+    let own = match transform.as_deref() {
+        Some(transform) => parse_transform(transform),
+        None => Some(Matrix::IDENTITY),
+    };
+    match own {
+        Some(own) => {
+            let combined = matrix.then(own);
+            (!combined.is_identity()).then(|| combined.to_string())
+        }
+        // Unknown syntax; compose the lists as strings
+        None if !matrix.is_identity() => {
+            Some(format!("{} {}", matrix, transform.unwrap_or_default()))
+        }
+        None => transform,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_svg, passes::geometry::Segment};
+    use std::fs;
+
+    /// Compares path data numerically, with a tolerance.
+    fn assert_path_eq(actual: &str, expected: &str) {
+        let (actual_segments, expected_segments) =
+            (parse_path(actual).unwrap(), parse_path(expected).unwrap());
+        assert_eq!(
+            actual_segments.len(),
+            expected_segments.len(),
+            "{} != {}",
+            actual,
+            expected
+        );
+        for (a, e) in actual_segments.iter().zip(&expected_segments) {
+            let numbers = |segment: &Segment| -> Vec<f64> {
+                match *segment {
+                    Segment::MoveTo(p) | Segment::LineTo(p) => vec![p.0, p.1],
+                    Segment::CubicTo(c1, c2, p) => vec![c1.0, c1.1, c2.0, c2.1, p.0, p.1],
+                    Segment::QuadTo(c, p) => vec![c.0, c.1, p.0, p.1],
+                    Segment::ArcTo {
+                        rx,
+                        ry,
+                        rotation,
+                        large_arc,
+                        sweep,
+                        to,
+                    } => vec![
+                        rx,
+                        ry,
+                        rotation,
+                        large_arc as u8 as f64,
+                        sweep as u8 as f64,
+                        to.0,
+                        to.1,
+                    ],
+                    Segment::Close => vec![],
+                }
+            };
+            assert_eq!(std::mem::discriminant(a), std::mem::discriminant(e));
+            for (x, y) in numbers(a).into_iter().zip(numbers(e)) {
+                assert!((x - y).abs() < 1e-6, "{} != {}", actual, expected);
+            }
+        }
+    }
+
+    /// Flattens `path` nested in one group per transform, and returns the outermost group.
+    fn flatten_nested(transforms: &[&str], path: &str) -> G {
+        let mut svg_str =
+            String::from("<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>");
+        for transform in transforms {
+            svg_str.push_str(&format!("<g transform='{}'>", transform));
+        }
+        svg_str.push_str(path);
+        svg_str.push_str(&"</g>".repeat(transforms.len()));
+        svg_str.push_str("</svg>");
+
+        let mut svg = parse_svg(&svg_str).unwrap();
+        flatten_transforms(&mut svg);
+        let SvgElement::G(g) = svg.elements.remove(0) else {
+            panic!()
+        };
+        *g
+    }
+
+    fn flatten_single_path(transforms: &[&str], d: &str) -> Path {
+        let path = format!("<path d='{}' stroke='#000000' stroke-width='1'/>", d);
+        single_path(flatten_nested(transforms, &path))
+    }
+
+    /// The path at the bottom of groups which were all flattened.
+    fn single_path(mut g: G) -> Path {
+        loop {
+            assert!(g.transform.is_none());
+            match g.elements.unwrap().remove(0) {
+                GEle::G(inner) => g = *inner,
+                GEle::Path(path) => return *path,
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn translate_scale_test() {
+        let path = flatten_single_path(&["translate(10 20)", "scale(2)"], "M 0 0 l 1 1 h 2 v 3 Z");
+        assert_path_eq(&path.d, "M 10 20 L 12 22 L 16 22 L 16 28 Z");
+        assert_eq!(path.stroke_width.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn rotate_skew_test() {
+        let path = flatten_single_path(&["rotate(90)"], "M 1 0 L 2 0");
+        assert_path_eq(&path.d, "M 0 1 L 0 2");
+        assert_eq!(path.stroke_width.as_deref(), Some("1"));
+
+        let path = flatten_single_path(&["rotate(30)", "scale(-3 3)"], "M 0 0 L 1 0");
+        assert_eq!(path.stroke_width.as_deref(), Some("3"));
+
+        let path = single_path(flatten_nested(&["skewX(45)"], "<path d='M 0 1 L 1 1'/>"));
+        assert_path_eq(&path.d, "M 1 1 L 2 1");
+    }
+
+    /// A stroke cannot follow a non-uniform scaling or a skew, so the group keeps it.
+    #[test]
+    fn distorted_stroke_test() {
+        for transform in ["scale(2 1)", "skewX(45)"] {
+            let path = "<path d='M 0 0 L 1 1' stroke='#000000' stroke-width='1'/>";
+            let outer = flatten_nested(&["translate(1 2)", transform], path);
+            assert!(outer.transform.is_none());
+            let GEle::G(inner) = &outer.elements.as_ref().unwrap()[0] else {
+                panic!()
+            };
+            assert_eq!(
+                inner.transform.as_deref(),
+                Some(format!("matrix(1 0 0 1 1 2) {}", transform).as_str())
+            );
+            let GEle::Path(path) = &inner.elements.as_ref().unwrap()[0] else {
+                panic!()
+            };
+            assert_eq!(path.d, "M 0 0 L 1 1");
+            assert_eq!(path.stroke_width.as_deref(), Some("1"));
+        }
+
+        // Without a stroke, the path is moved
+        let path = "<path d='M 0 0 L 1 1' stroke='none'/>";
+        let path = single_path(flatten_nested(&["scale(2 1)"], path));
+        assert_path_eq(&path.d, "M 0 0 L 2 1");
+    }
+
+    /// A width with a unit is not in user units, so it cannot be scaled with them.
+    #[test]
+    fn stroke_width_unit_test() {
+        let path = "<path d='M 0 0 L 1 1' stroke='#000000' stroke-width='1pt'/>";
+        let g = flatten_nested(&["scale(2)"], path);
+        assert_eq!(g.transform.as_deref(), Some("scale(2)"));
+        let GEle::Path(path) = &g.elements.as_ref().unwrap()[0] else {
+            panic!()
+        };
+        assert_eq!(path.d, "M 0 0 L 1 1");
+        assert_eq!(path.stroke_width.as_deref(), Some("1pt"));
+
+        // The default width is 1
+        let path = "<path d='M 0 0 L 1 1' stroke='#000000'/>";
+        let path = single_path(flatten_nested(&["scale(2)"], path));
+        assert_eq!(path.stroke_width.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn curves_and_arcs_test() {
+        let path = flatten_single_path(
+            &["matrix(1 0 0 -1 0 10)"],
+            "M 0 0 c 1 0 2 1 2 2 s 1 2 2 2 q 1 0 1 1 t 1 1",
+        );
+        assert_path_eq(
+            &path.d,
+            "M 0 10 C 1 10 2 9 2 8 C 2 7 3 6 4 6 Q 5 6 5 5 Q 5 4 6 4",
+        );
+
+        let path = "<path d='M 0 0 a 1 1 0 0 1 2 0'/>";
+        let path = single_path(flatten_nested(&["scale(2 1)"], path));
+        assert_path_eq(&path.d, "M 0 0 A 2 1 0 0 1 4 0");
+
+        let path = flatten_single_path(&["scale(-1 1)"], "M 0 0 A 1 1 0 1 1 2 0");
+        assert_path_eq(&path.d, "M 0 0 A 1 1 0 1 0 -2 0");
+    }
+
+    #[test]
+    fn use_and_image_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <g transform="translate(1 2)">
+                    <g transform="scale(2)">
+                        <use href="#glyph0" x="3"/>
+                        <image width="1" height="1" preserveAspectRatio="none" href="a.png" transform="translate(1 1)"/>
+                    </g>
+                </g>
+            </svg>"##,
+        )
+        .unwrap();
+        flatten_transforms(&mut svg);
+        let SvgElement::G(outer) = &svg.elements[0] else {
+            panic!()
+        };
+        let GEle::G(inner) = &outer.elements.as_ref().unwrap()[0] else {
+            panic!()
+        };
+        let elements = inner.elements.as_ref().unwrap();
+        let GEle::Use(uuse) = &elements[0] else {
+            panic!()
+        };
+        assert_eq!(uuse.transform.as_deref(), Some("matrix(2 0 0 2 1 2)"));
+        assert_eq!(uuse.x, "3");
+        let GEle::Image(image) = &elements[1] else {
+            panic!()
+        };
+        assert_eq!(image.transform.as_deref(), Some("matrix(2 0 0 2 3 4)"));
+    }
+
+    #[test]
+    fn unparseable_transform_test() {
+        let mut svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+             <g transform='translate(1 2)'><g transform='perspective(3)'><path d='M 0 0'/></g></g>\
+             </svg>",
+        )
+        .unwrap();
+        flatten_transforms(&mut svg);
+        let SvgElement::G(outer) = &svg.elements[0] else {
+            panic!()
+        };
+        let GEle::G(inner) = &outer.elements.as_ref().unwrap()[0] else {
+            panic!()
+        };
+        assert_eq!(
+            inner.transform.as_deref(),
+            Some("matrix(1 0 0 1 1 2) perspective(3)")
+        );
+    }
+
+    #[test]
+    fn clipped_test() {
+        let mut svg = parse_svg(&fs::read_to_string("./test/clip.svg").unwrap()).unwrap();
+        let bbox = svg.content_bbox();
+        flatten_transforms(&mut svg);
+        let SvgElement::G(g) = &svg.elements[1] else {
+            panic!()
+        };
+        assert_eq!(g.transform.as_deref(), Some("translate(40 0)"));
+        assert_eq!(svg.content_bbox(), bbox);
+    }
+
+    /// The gradient keeps the rotation of the shape it fills.
+    #[test]
+    fn paint_server_test() {
+        let mut svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+             <g transform='translate(1 2)'><g transform='rotate(45)'>\
+             <path fill='url(#g)' d='M 0 0 L 4 0 L 4 4 Z'/></g></g>\
+             <defs id='d'><linearGradient id='g'><stop offset='0' stop-color='#000000'/>\
+             <stop offset='1' stop-color='#ffffff'/></linearGradient></defs>\
+             </svg>",
+        )
+        .unwrap();
+        let bbox = svg.content_bbox();
+        flatten_transforms(&mut svg);
+        let SvgElement::G(outer) = &svg.elements[0] else {
+            panic!()
+        };
+        let GEle::G(inner) = &outer.elements.as_ref().unwrap()[0] else {
+            panic!()
+        };
+        assert_eq!(
+            inner.transform.as_deref(),
+            Some("matrix(1 0 0 1 1 2) rotate(45)")
+        );
+        let GEle::Path(path) = &inner.elements.as_ref().unwrap()[0] else {
+            panic!()
+        };
+        assert_eq!(path.d, "M 0 0 L 4 0 L 4 4 Z");
+        assert_eq!(svg.content_bbox(), bbox);
+    }
+
+    #[test]
+    fn fixture_test() {
+        fn check(g: &G) {
+            assert!(g.transform.is_none());
+            for element in g.elements.iter().flatten() {
+                match element {
+                    GEle::G(g) => check(g),
+                    GEle::Path(path) => assert!(parse_path(&path.d).is_some()),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut svg = parse_svg(&fs::read_to_string("./test/expected.svg").unwrap()).unwrap();
+        flatten_transforms(&mut svg);
+        for element in &svg.elements {
+            if let SvgElement::G(g) = element {
+                check(g);
+            }
+        }
+    }
+}
@@ -0,0 +1,375 @@
+//! Parsing and arithmetic for the geometry found in Typst's SVG output: transform lists and path data.
+
+use crate::svg_types::{
+    number::format_number,
+    path_data::{PathCommand, PathData},
+    transform::Transform,
+};
+
+pub(crate) use crate::svg_types::transform::Matrix;
+
+/// Parses an SVG transform list (`translate(10 20) scale(.5)`) into the matrix it composes to.
+///
+/// Returns `None` when the list is malformed.
+pub(crate) fn parse_transform(transform: &str) -> Option<Matrix> {
+    Transform::parse(transform)
+        .ok()
+        .map(|transform| transform.to_matrix())
+}
+
+pub(crate) type Point = (f64, f64);
+
+/// One segment of a path, with absolute coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Segment {
+    MoveTo(Point),
+    LineTo(Point),
+    CubicTo(Point, Point, Point),
+    QuadTo(Point, Point),
+    ArcTo {
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    },
+    Close,
+}
+
+impl Segment {
+    /// Applies `matrix` to the segment. Arcs get the radii and rotation of the transformed ellipse.
+    pub fn transform(self, matrix: &Matrix) -> Segment {
+        match self {
+            Segment::MoveTo(to) => Segment::MoveTo(matrix.apply(to)),
+            Segment::LineTo(to) => Segment::LineTo(matrix.apply(to)),
+            Segment::CubicTo(c1, c2, to) => {
+                Segment::CubicTo(matrix.apply(c1), matrix.apply(c2), matrix.apply(to))
+            }
+            Segment::QuadTo(c, to) => Segment::QuadTo(matrix.apply(c), matrix.apply(to)),
+            Segment::ArcTo {
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                let (rx, ry, rotation) = transform_ellipse(rx, ry, rotation, matrix);
+                Segment::ArcTo {
+                    rx,
+                    ry,
+                    rotation,
+                    large_arc,
+                    // A mirroring transformation reverses the direction of the arc
+                    sweep: sweep != (matrix.determinant() < 0.0),
+                    to: matrix.apply(to),
+                }
+            }
+            Segment::Close => Segment::Close,
+        }
+    }
+}
+
+/// Computes the radii and x-axis rotation (in degrees) of an ellipse after a linear transformation.
+fn transform_ellipse(rx: f64, ry: f64, rotation: f64, matrix: &Matrix) -> (f64, f64, f64) {
+    const EPSILON: f64 = 1e-10;
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    // Columns of the transformed ellipse axes
+    let m0 = rx * (matrix.a * cos + matrix.c * sin);
+    let m1 = rx * (matrix.b * cos + matrix.d * sin);
+    let m2 = ry * (-matrix.a * sin + matrix.c * cos);
+    let m3 = ry * (-matrix.b * sin + matrix.d * cos);
+
+    let j = m0 * m0 + m2 * m2;
+    let k = m1 * m1 + m3 * m3;
+    let jk = (j + k) / 2.0;
+    let discriminant =
+        ((m0 - m3).powi(2) + (m2 + m1).powi(2)) * ((m0 + m3).powi(2) + (m2 - m1).powi(2));
+    if discriminant < EPSILON * jk {
+        // Still a circle
+        return (jk.sqrt(), jk.sqrt(), 0.0);
+    }
+
+    let l = m0 * m1 + m2 * m3;
+    let discriminant = discriminant.sqrt();
+    let l1 = jk + discriminant / 2.0;
+    let l2 = jk - discriminant / 2.0;
+    let angle = if l.abs() < EPSILON && (l1 - k).abs() < EPSILON {
+        90.0
+    } else if l.abs() > (l1 - k).abs() {
+        ((l1 - j) / l).atan().to_degrees()
+    } else {
+        (l / (l1 - k)).atan().to_degrees()
+    };
+    if angle >= 0.0 {
+        (l1.sqrt(), l2.max(0.0).sqrt(), angle)
+    } else {
+        (l2.max(0.0).sqrt(), l1.sqrt(), angle + 90.0)
+    }
+}
+
+/// Parses path data into absolute segments.
+///
+/// Relative commands are resolved against the current point, `H`/`V` become lines and the smooth
+/// curves `S`/`T` get their reflected control point. Returns `None` on malformed data.
+pub(crate) fn parse_path(data: &str) -> Option<Vec<Segment>> {
+    let data = PathData::parse(data).ok()?;
+    let mut segments = Vec::with_capacity(data.commands.len());
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    // Last control point, used to reflect smooth curves
+    let mut last_cubic = None;
+    let mut last_quad = None;
+
+    for command in data {
+        let resolve = |relative: bool, (x, y): Point| {
+            if relative {
+                (current.0 + x, current.1 + y)
+            } else {
+                (x, y)
+            }
+        };
+        let segment = match command {
+            PathCommand::MoveTo { relative, to } => {
+                let to = resolve(relative, to);
+                start = to;
+                Segment::MoveTo(to)
+            }
+            PathCommand::LineTo { relative, to } => Segment::LineTo(resolve(relative, to)),
+            PathCommand::HorizontalTo { relative, x } => {
+                Segment::LineTo((if relative { current.0 + x } else { x }, current.1))
+            }
+            PathCommand::VerticalTo { relative, y } => {
+                Segment::LineTo((current.0, if relative { current.1 + y } else { y }))
+            }
+            PathCommand::CubicTo {
+                relative,
+                c1,
+                c2,
+                to,
+            } => Segment::CubicTo(
+                resolve(relative, c1),
+                resolve(relative, c2),
+                resolve(relative, to),
+            ),
+            PathCommand::SmoothCubicTo { relative, c2, to } => Segment::CubicTo(
+                reflect(last_cubic, current),
+                resolve(relative, c2),
+                resolve(relative, to),
+            ),
+            PathCommand::QuadTo { relative, c, to } => {
+                Segment::QuadTo(resolve(relative, c), resolve(relative, to))
+            }
+            PathCommand::SmoothQuadTo { relative, to } => {
+                Segment::QuadTo(reflect(last_quad, current), resolve(relative, to))
+            }
+            PathCommand::Arc {
+                relative,
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => Segment::ArcTo {
+                rx: rx.abs(),
+                ry: ry.abs(),
+                rotation,
+                large_arc,
+                sweep,
+                to: resolve(relative, to),
+            },
+            PathCommand::Close => Segment::Close,
+        };
+
+        (last_cubic, last_quad) = (None, None);
+        match segment {
+            Segment::MoveTo(to) | Segment::LineTo(to) | Segment::ArcTo { to, .. } => current = to,
+            Segment::CubicTo(_, c2, to) => {
+                last_cubic = Some(c2);
+                current = to;
+            }
+            Segment::QuadTo(c, to) => {
+                last_quad = Some(c);
+                current = to;
+            }
+            Segment::Close => current = start,
+        }
+        segments.push(segment);
+    }
+    Some(segments)
+}
+
+/// Reflects the previous control point around the current point, or uses the current point.
+fn reflect(control: Option<Point>, current: Point) -> Point {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current,
+    }
+}
+
+/// Serializes absolute segments in the `M 0 0 L 1 1 Z` style of Typst.
+pub(crate) fn write_path(segments: &[Segment]) -> String {
+    let mut out = String::new();
+    let mut push = |parts: &[String]| {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(&parts.join(" "));
+    };
+    let point = |(x, y): Point| format!("{} {}", format_number(x), format_number(y));
+    for segment in segments {
+        match *segment {
+            Segment::MoveTo(to) => push(&["M".into(), point(to)]),
+            Segment::LineTo(to) => push(&["L".into(), point(to)]),
+            Segment::CubicTo(c1, c2, to) => push(&["C".into(), point(c1), point(c2), point(to)]),
+            Segment::QuadTo(c, to) => push(&["Q".into(), point(c), point(to)]),
+            Segment::ArcTo {
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => push(&[
+                "A".into(),
+                format_number(rx),
+                format_number(ry),
+                format_number(rotation),
+                (large_arc as u8).to_string(),
+                (sweep as u8).to_string(),
+                point(to),
+            ]),
+            Segment::Close => push(&["Z".into()]),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matrix_eq(actual: Matrix, expected: Matrix) {
+        let (a, e) = (actual, expected);
+        for (x, y) in [
+            (a.a, e.a),
+            (a.b, e.b),
+            (a.c, e.c),
+            (a.d, e.d),
+            (a.e, e.e),
+            (a.f, e.f),
+        ] {
+            assert!((x - y).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn parse_transform_test() {
+        assert_matrix_eq(
+            parse_transform("translate(10 20) scale(2)").unwrap(),
+            Matrix {
+                a: 2.0,
+                d: 2.0,
+                e: 10.0,
+                f: 20.0,
+                ..Matrix::IDENTITY
+            },
+        );
+        assert_matrix_eq(
+            parse_transform("matrix(1,0,0,-1,0,5e1)").unwrap(),
+            Matrix {
+                d: -1.0,
+                f: 50.0,
+                ..Matrix::IDENTITY
+            },
+        );
+        let rotated = parse_transform("rotate(90 10 10)").unwrap();
+        let (x, y) = rotated.apply((20.0, 10.0));
+        assert!((x - 10.0).abs() < 1e-9 && (y - 20.0).abs() < 1e-9);
+        assert_eq!(parse_transform(""), Some(Matrix::IDENTITY));
+        assert_eq!(parse_transform("translate(1 2"), None);
+        assert_eq!(parse_transform("perspective(1)"), None);
+    }
+
+    #[test]
+    fn parse_path_test() {
+        assert_eq!(
+            parse_path("m1 1 2 0 h 1 v-1 z l.5.5").unwrap(),
+            vec![
+                Segment::MoveTo((1.0, 1.0)),
+                Segment::LineTo((3.0, 1.0)),
+                Segment::LineTo((4.0, 1.0)),
+                Segment::LineTo((4.0, 0.0)),
+                Segment::Close,
+                Segment::LineTo((1.5, 1.5)),
+            ]
+        );
+        assert_eq!(
+            parse_path("M0 0C1 0 2 1 2 2S3 4 4 4").unwrap()[2],
+            Segment::CubicTo((2.0, 3.0), (3.0, 4.0), (4.0, 4.0))
+        );
+        assert_eq!(
+            parse_path("M0 0a1 1 0 011 1").unwrap()[1],
+            Segment::ArcTo {
+                rx: 1.0,
+                ry: 1.0,
+                rotation: 0.0,
+                large_arc: false,
+                sweep: true,
+                to: (1.0, 1.0),
+            }
+        );
+        assert_eq!(
+            parse_path("M 1e2-1E-1").unwrap(),
+            vec![Segment::MoveTo((100.0, -0.1))]
+        );
+        assert_eq!(parse_path("L 1"), None);
+        assert_eq!(parse_path("1 2"), None);
+    }
+
+    #[test]
+    fn transform_arc_test() {
+        let arc = |segment| match segment {
+            Segment::ArcTo {
+                rx,
+                ry,
+                rotation,
+                sweep,
+                ..
+            } => (rx, ry, rotation, sweep),
+            _ => panic!(),
+        };
+        let circle = Segment::ArcTo {
+            rx: 1.0,
+            ry: 1.0,
+            rotation: 0.0,
+            large_arc: false,
+            sweep: true,
+            to: (2.0, 0.0),
+        };
+
+        let (rx, ry, rotation, sweep) = arc(circle.transform(&Matrix::scale(2.0, 1.0)));
+        assert!((rx - 2.0).abs() < 1e-9 && (ry - 1.0).abs() < 1e-9 && rotation.abs() < 1e-9);
+        assert!(sweep);
+
+        let (rx, ry, _, sweep) = arc(circle.transform(&Matrix::rotate(30.0)));
+        assert!((rx - 1.0).abs() < 1e-9 && (ry - 1.0).abs() < 1e-9);
+        assert!(sweep);
+
+        let (_, _, _, sweep) = arc(circle.transform(&Matrix::scale(-1.0, 1.0)));
+        assert!(!sweep);
+
+        let (rx, ry, rotation, _) =
+            arc(circle.transform(&Matrix::rotate(90.0).then(Matrix::scale(2.0, 1.0))));
+        assert!((rx - 2.0).abs() < 1e-9 && (ry - 1.0).abs() < 1e-9);
+        assert!((rotation - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn write_path_test() {
+        let segments = parse_path("M 0 0 L 0.1 -0.30000000000000004 Z").unwrap();
+        assert_eq!(write_path(&segments), "M 0 0 L .1 -.3 Z");
+    }
+}
@@ -0,0 +1,238 @@
+use std::sync::Arc;
+
+use crate::svg_types::*;
+
+/// Prepends `prefix` to every `id` in the document and to every local reference to one.
+///
+/// The `id`s of `defs`, `symbol`, gradient, `clipPath`, `mask`, `filter`, `g`, `path` and `text`
+/// elements are renamed, and so are the references pointing at them: `href="#id"` on `use`, `image`
+/// and gradient elements, and `url(#id)` in paint attributes such as `fill` and `stroke` and in the
+/// `clip-path`, `mask` and `filter` of groups. References to external resources are left alone. The
+/// `in` and `result` names of filter primitives are local to their filter and kept.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::prefix_ids, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><use href='#glyph0' x='0'/></g>\
+///      <defs id='glyph'><symbol id='glyph0' overflow='visible'><path d='M 0 0'/></symbol></defs>\
+///      </svg>",
+/// )
+/// .unwrap();
+/// prefix_ids(&mut svg, "fig1-");
+/// match &svg.elements[1] {
+///     SvgElement::Defs(defs) => assert_eq!(defs.elements[0].id, "fig1-glyph0"),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn prefix_ids(svg: &mut Svg, prefix: &str) {
+    for element in &mut svg.elements {
+        match element {
+            SvgElement::Path(path) => prefix_path(path, prefix),
+            SvgElement::G(g) => prefix_g(g, prefix),
+            SvgElement::Defs(defs) => {
+                defs.id.insert_str(0, prefix);
+                for symbol in &mut defs.elements {
+                    symbol.id.insert_str(0, prefix);
+                    match &mut symbol.element {
+                        SymbolEle::Path(path) => prefix_path(path, prefix),
+                        SymbolEle::Image(image) => prefix_image(image, prefix),
+                    }
+                }
+                for definition in &mut defs.definitions {
+                    prefix_definition(definition, prefix);
+                }
+            }
+            SvgElement::Rect(rect) => prefix_urls(rect.fill.as_mut(), prefix),
+        }
+    }
+}
+
+fn prefix_g(g: &mut G, prefix: &str) {
+    prefix_id(&mut g.id, prefix);
+    prefix_urls(g.clip_path.as_mut(), prefix);
+    prefix_urls(g.mask.as_mut(), prefix);
+    prefix_urls(g.filter.as_mut(), prefix);
+    for element in g.elements.iter_mut().flatten() {
+        prefix_g_ele(element, prefix);
+    }
+}
+
+fn prefix_g_ele(element: &mut GEle, prefix: &str) {
+    match element {
+        GEle::G(g) => prefix_g(g, prefix),
+        GEle::Use(uuse) => {
+            prefix_href(&mut uuse.href, prefix);
+            prefix_urls(uuse.fill.as_mut(), prefix);
+        }
+        GEle::Path(path) => prefix_path(path, prefix),
+        GEle::Image(image) => prefix_image(image, prefix),
+        GEle::Text(text) => {
+            prefix_id(&mut text.id, prefix);
+            prefix_urls(text.fill.as_mut(), prefix);
+            for content in &mut text.content {
+                if let TextContent::Tspan(tspan) = content {
+                    prefix_urls(tspan.fill.as_mut(), prefix);
+                }
+            }
+        }
+        GEle::Switch(switch) => {
+            prefix_id(&mut switch.id, prefix);
+            for element in switch.elements_mut() {
+                prefix_g_ele(element, prefix);
+            }
+        }
+    }
+}
+
+fn prefix_path(path: &mut Path, prefix: &str) {
+    prefix_id(&mut path.id, prefix);
+    prefix_urls(path.fill.as_mut(), prefix);
+    prefix_urls(path.stroke.as_mut(), prefix);
+}
+
+fn prefix_definition(definition: &mut Definition, prefix: &str) {
+    for path in definition.paths_mut() {
+        prefix_path(path, prefix);
+    }
+    let (id, href) = match definition {
+        Definition::LinearGradient(gradient) => (&mut gradient.id, &mut gradient.href),
+        Definition::RadialGradient(gradient) => (&mut gradient.id, &mut gradient.href),
+        Definition::ClipPath(clip) => (&mut clip.id, &mut None),
+        Definition::Mask(mask) => (&mut mask.id, &mut None),
+        Definition::Filter(filter) => (&mut filter.id, &mut None),
+    };
+    id.insert_str(0, prefix);
+    if let Some(href) = href {
+        prefix_href(href, prefix);
+    }
+}
+
+fn prefix_image(image: &mut Image, prefix: &str) {
+    prefix_href(&mut image.href, prefix);
+}
+
+fn prefix_id(id: &mut Option<String>, prefix: &str) {
+    if let Some(id) = id {
+        id.insert_str(0, prefix);
+    }
+}
+
+/// Renames the target of a `#id` reference.
+fn prefix_href<T: AsRef<str> + From<String>>(href: &mut T, prefix: &str) {
+    if let Some(id) = href.as_ref().strip_prefix('#') {
+        *href = format!("#{}{}", prefix, id).into();
+    }
+}
+
+/// Renames the target of every `url(#id)` reference in an attribute value.
+fn prefix_urls(value: Option<&mut Arc<str>>, prefix: &str) {
+    if let Some(value) = value.filter(|value| value.contains("url(")) {
+        *value = value
+            .replace("url(#", &format!("url(#{}", prefix))
+            .replace("url('#", &format!("url('#{}", prefix))
+            .replace("url(\"#", &format!("url(\"#{}", prefix))
+            .into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_svg, svg_to_rsx};
+    use std::fs;
+
+    #[test]
+    fn prefix_urls_test() {
+        let mut fill = Arc::from("url(#gradient0)");
+        prefix_urls(Some(&mut fill), "a-");
+        assert_eq!(&*fill, "url(#a-gradient0)");
+
+        let mut fill = Arc::from("#000000");
+        prefix_urls(Some(&mut fill), "a-");
+        assert_eq!(&*fill, "#000000");
+
+        let mut href = "data:image/png;base64,AAAA".to_string();
+        prefix_href(&mut href, "a-");
+        assert_eq!(href, "data:image/png;base64,AAAA");
+    }
+
+    /// Two copies of the same document must not share any `id`.
+    #[test]
+    fn side_by_side_test() {
+        let source = fs::read_to_string("./test/expected.svg").unwrap();
+        let (mut first, mut second) = (parse_svg(&source).unwrap(), parse_svg(&source).unwrap());
+        prefix_ids(&mut first, "first-");
+        prefix_ids(&mut second, "second-");
+
+        for (svg, prefix) in [(&first, "first-"), (&second, "second-")] {
+            let mut ids = Vec::new();
+            let mut hrefs = Vec::new();
+            for element in &svg.elements {
+                match element {
+                    SvgElement::Defs(defs) => {
+                        ids.extend(defs.elements.iter().map(|symbol| symbol.id.clone()))
+                    }
+                    SvgElement::G(g) => collect_hrefs(g, &mut hrefs),
+                    SvgElement::Path(_) | SvgElement::Rect(_) => {}
+                }
+            }
+            assert!(!hrefs.is_empty());
+            for href in hrefs {
+                assert!(href.starts_with(&format!("#{}", prefix)));
+                assert!(ids.contains(&href[1..].to_string()));
+            }
+        }
+
+        let (first, second) = (
+            format!("{:?}", svg_to_rsx(&first)),
+            format!("{:?}", svg_to_rsx(&second)),
+        );
+        assert!(!first.contains("second-"));
+        assert!(!second.contains("first-"));
+    }
+
+    #[test]
+    fn gradient_href_test() {
+        let mut svg = parse_svg(&fs::read_to_string("./test/gradient.svg").unwrap()).unwrap();
+        prefix_ids(&mut svg, "fig1-");
+        let SvgElement::Defs(defs) = &svg.elements[2] else {
+            panic!("expected defs");
+        };
+        let ids: Vec<_> = defs.definitions.iter().map(Definition::id).collect();
+        assert_eq!(ids, ["fig1-g0", "fig1-g1"]);
+        assert_eq!(defs.definitions[1].href(), Some("#fig1-g0"));
+        let SvgElement::Path(path) = &svg.elements[1] else {
+            panic!("expected path");
+        };
+        assert_eq!(path.fill.as_deref(), Some("url(#fig1-g1)"));
+    }
+
+    #[test]
+    fn clip_path_test() {
+        let mut svg = parse_svg(&fs::read_to_string("./test/clip.svg").unwrap()).unwrap();
+        prefix_ids(&mut svg, "fig1-");
+        assert!(svg.validate_references().is_empty());
+        let SvgElement::G(g) = &svg.elements[1] else {
+            panic!("expected g");
+        };
+        assert_eq!(g.clip_path.as_deref(), Some("url(#fig1-c1)"));
+        let SvgElement::G(g) = &svg.elements[2] else {
+            panic!("expected g");
+        };
+        assert_eq!(g.mask.as_deref(), Some("url(#fig1-m0)"));
+    }
+
+    fn collect_hrefs(g: &G, hrefs: &mut Vec<String>) {
+        for element in g.elements.iter().flatten() {
+            match element {
+                GEle::G(g) => collect_hrefs(g, hrefs),
+                GEle::Use(uuse) => hrefs.push(uuse.href.to_string()),
+                GEle::Path(_) | GEle::Image(_) | GEle::Text(_) | GEle::Switch(_) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::{
+    fs,
+    path::{Path as FsPath, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{error::Error, svg_types::*};
+
+/// Writes the images embedded as base64 `data:` URIs to files, and points the `href`s at them.
+///
+/// Typst embeds every image of the document in the SVG, which makes the RSX tree (and the
+/// hydration payload) as large as the images. Each embedded image is decoded and written to
+/// `out_dir`, named by the hash of its content with the extension of its MIME type, and its
+/// `href` becomes `{url_prefix}/{hash}.{extension}`. Images already referring to an external URL
+/// are left alone, and an image used several times is written once. The hash is FNV-1a, so an
+/// image keeps its name across builds and versions of Rust.
+///
+/// Every image is decoded before any file is written, so an invalid image leaves the document
+/// and `out_dir` unchanged. An I/O error may leave some files written, but the document is only
+/// rewritten once all of them are.
+///
+/// To list the files in a [`Manifest`](crate::manifest::Manifest), add them to an entry with the
+/// role [`OutputRole::Image`](crate::manifest::OutputRole::Image).
+///
+/// # parameter
+///
+/// - `svg` : the document whose images are extracted.
+/// - `out_dir` : the directory receiving the files, created if it does not exist.
+/// - `url_prefix` : the URL under which the content of `out_dir` is served.
+///
+/// # Return value
+///
+/// - `Ok(Vec<PathBuf>)` : the files written, once each.
+/// - `Err(Error)` : [`Error::InvalidImage`] when an image is not valid base64 or has an
+///   unsupported MIME type, or an I/O error from writing the files.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{parse_svg, passes::extract_images};
+/// use std::{fs, path::Path};
+///
+/// let mut svg = parse_svg(&fs::read_to_string("document.svg").unwrap()).unwrap();
+/// let files = extract_images(&mut svg, Path::new("public/assets"), "/assets").unwrap();
+/// ```
+pub fn extract_images(
+    svg: &mut Svg,
+    out_dir: &FsPath,
+    url_prefix: &str,
+) -> Result<Vec<PathBuf>, Error> {
+    // Every image is decoded before anything is written, so that an invalid one leaves both the
+    // document and `out_dir` untouched
+    let mut decoded = Vec::new();
+    for_each_image(svg, &mut |image, element| {
+        decoded.push(decode(&image.href, element)?);
+        Ok(())
+    })?;
+
+    let mut written = Vec::new();
+    for (name, bytes) in decoded.iter().flatten() {
+        let file = out_dir.join(name);
+        if !written.contains(&file) {
+            fs::create_dir_all(out_dir).map_err(|e| Error::io(out_dir, e))?;
+            fs::write(&file, bytes).map_err(|e| Error::io(&file, e))?;
+            written.push(file);
+        }
+    }
+
+    let url_prefix = url_prefix.trim_end_matches('/');
+    let mut decoded = decoded.into_iter();
+    for_each_image(svg, &mut |image, _| {
+        if let Some(Some((name, _))) = decoded.next() {
+            image.href = format!("{}/{}", url_prefix, name);
+        }
+        Ok(())
+    })?;
+    Ok(written)
+}
+
+/// Calls `f` on every image of the document, in document order, with a description of the
+/// image for the errors.
+fn for_each_image(
+    svg: &mut Svg,
+    f: &mut dyn FnMut(&mut Image, String) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut walker = Walker { f, count: 0 };
+    for element in &mut svg.elements {
+        match element {
+            SvgElement::G(g) => walker.walk_g(g)?,
+            SvgElement::Defs(defs) => {
+                for symbol in &mut defs.elements {
+                    if let SymbolEle::Image(image) = &mut symbol.element {
+                        (walker.f)(image, format!("symbol `{}`", symbol.id))?;
+                    }
+                }
+            }
+            SvgElement::Path(_) | SvgElement::Rect(_) => {}
+        }
+    }
+    Ok(())
+}
+
+struct Walker<'a> {
+    f: &'a mut dyn FnMut(&mut Image, String) -> Result<(), Error>,
+    /// Number of images met outside of symbols, to identify them in errors.
+    count: usize,
+}
+
+impl Walker<'_> {
+    fn walk_g(&mut self, g: &mut G) -> Result<(), Error> {
+        for element in g.elements.iter_mut().flatten() {
+            self.walk_g_ele(element)?;
+        }
+        Ok(())
+    }
+
+    fn walk_g_ele(&mut self, element: &mut GEle) -> Result<(), Error> {
+        match element {
+            GEle::G(g) => self.walk_g(g)?,
+            GEle::Image(image) => {
+                self.count += 1;
+                (self.f)(image, format!("image #{}", self.count))?;
+            }
+            GEle::Switch(switch) => {
+                for element in switch.elements_mut() {
+                    self.walk_g_ele(element)?;
+                }
+            }
+            GEle::Use(_) | GEle::Path(_) | GEle::Text(_) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Decodes an image embedded as a base64 `data:` URI, returning the name of its file and its
+/// content, or `None` when `href` is not one.
+fn decode(href: &str, element: String) -> Result<Option<(String, Vec<u8>)>, Error> {
+    let Some((header, data)) = href
+        .strip_prefix("data:")
+        .and_then(|uri| uri.split_once(','))
+    else {
+        return Ok(None);
+    };
+    let Some(mime) = header.strip_suffix(";base64") else {
+        // Not base64 (a percent-encoded SVG, say), which is not large enough to matter
+        return Ok(None);
+    };
+    let extension = match mime.trim().to_ascii_lowercase().as_str() {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        mime => {
+            return Err(Error::InvalidImage {
+                element,
+                reason: format!("unsupported MIME type `{}`", mime),
+            })
+        }
+    };
+    let data: String = data.split_whitespace().collect();
+    let bytes = STANDARD.decode(data).map_err(|error| Error::InvalidImage {
+        element,
+        reason: format!("invalid base64 ({})", error),
+    })?;
+    let name = format!("{:016x}.{}", fnv1a(&bytes), extension);
+    Ok(Some((name, bytes)))
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, which unlike the hasher of the standard library is the
+/// same on every version of Rust, so that the files keep their names from one build to the next.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use std::env;
+
+    fn document(hrefs: &[&str]) -> Svg {
+        let images: String = hrefs
+            .iter()
+            .map(|href| {
+                format!(
+                    "<image width='1' height='1' preserveAspectRatio='none' href='{}'/>",
+                    href
+                )
+            })
+            .collect();
+        parse_svg(&format!(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'><g>{}</g></svg>",
+            images
+        ))
+        .unwrap()
+    }
+
+    fn hrefs(svg: &Svg) -> Vec<String> {
+        let SvgElement::G(g) = &svg.elements[0] else {
+            panic!()
+        };
+        g.elements
+            .iter()
+            .flatten()
+            .map(|element| match element {
+                GEle::Image(image) => image.href.clone(),
+                _ => panic!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extract_test() {
+        let out_dir = env::temp_dir().join(format!("typst-2-rsx-images-{}", std::process::id()));
+        let png = format!(
+            "data:image/png;base64,{}",
+            STANDARD.encode(b"\x89PNG pixels")
+        );
+        let mut svg = document(&[&png, "photo.jpg", &png]);
+
+        let files = extract_images(&mut svg, &out_dir, "/assets/").unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(fs::read(&files[0]).unwrap(), b"\x89PNG pixels");
+        let name = files[0].file_name().unwrap().to_str().unwrap();
+        assert_eq!(name, format!("{:016x}.png", fnv1a(b"\x89PNG pixels")));
+        let url = format!("/assets/{}", name);
+        assert_eq!(hrefs(&svg), [url.as_str(), "photo.jpg", url.as_str()]);
+
+        fs::remove_dir_all(out_dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_base64_test() {
+        let out_dir = env::temp_dir().join(format!("typst-2-rsx-invalid-{}", std::process::id()));
+        let png = format!("data:image/png;base64,{}", STANDARD.encode(b"pixels"));
+        let hrefs_before = [png.as_str(), "data:image/gif;base64,not*base64"];
+        let mut svg = document(&hrefs_before);
+        let error = extract_images(&mut svg, &out_dir, "/assets").unwrap_err();
+        assert!(
+            matches!(&error, Error::InvalidImage { element, .. } if element == "image #2"),
+            "{}",
+            error
+        );
+        // The valid image before it was neither written nor rewritten
+        assert!(!out_dir.exists());
+        assert_eq!(hrefs(&svg), hrefs_before);
+    }
+
+    #[test]
+    fn fnv1a_test() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::svg_types::{
+    path_data::{PathCommand, PathData},
+    *,
+};
+
+/// Merges the runs of sibling paths with the same styling into one path each.
+///
+/// Typst emits a path for every shape and rule segment, so a table or a plot can hold hundreds of
+/// paths with the same fill and stroke. Among the children of every group and of the root `svg`,
+/// a path is appended to the path before it when both have the same `class`, paint, stroke,
+/// `opacity` and `shape-rendering`, and neither has an `id`, a `tabindex` or `pointer-events`,
+/// which make a path a target of its own. A path painted with a gradient or a pattern through
+/// `url(…)` is never merged, as the paint would be laid out in the box of the merged path. Typst
+/// places most shapes in a positioning group of their own, so the pass finds the most siblings
+/// after [`flatten_transforms`](super::flatten_transforms) and [`simplify`](super::simplify).
+///
+/// Every path data starts a subpath with a move, so the data are concatenated, except that a
+/// leading relative `m`, which would move from the end of the previous path, is made absolute.
+/// The filled subpaths of one path combine through its fill rule, and a path with an `opacity` is
+/// blended as a whole, so the paths with a fill or an `opacity` are only merged when their boxes
+/// do not overlap: the rendering is unchanged. Paths whose data cannot be parsed are left alone.
+///
+/// # Return value
+///
+/// The number of paths merged into the one before them, which is how many fewer `paths`
+/// [`Svg::stats`] counts afterwards. When the pass runs through [`RsxOptions::merge_paths`], the
+/// reports of [`parse_svg_to_rsx_with_report`](crate::parse_svg_to_rsx_with_report) give it as
+/// [`ConversionReport::merged_paths`](crate::report::ConversionReport).
+///
+/// [`RsxOptions::merge_paths`]: crate::options::RsxOptions
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::merge_paths, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'><g>\
+///      <path fill='none' stroke='#000000' d='M 0 0 L 10 0'/>\
+///      <path fill='none' stroke='#000000' d='M 0 5 L 10 5'/>\
+///      <path fill='none' stroke='#ff0000' d='M 0 9 L 10 9'/>\
+///      </g></svg>",
+/// )
+/// .unwrap();
+/// assert_eq!(merge_paths(&mut svg), 1);
+/// assert_eq!(svg.stats().paths, 2);
+/// ```
+pub fn merge_paths(svg: &mut Svg) -> usize {
+    let mut merged = 0;
+    for element in &mut svg.elements {
+        if let SvgElement::G(g) = element {
+            merged += merge_g(g);
+        }
+    }
+    merged
+        + merge_siblings(&mut svg.elements, |element| match element {
+            SvgElement::Path(path) => Some(path),
+            _ => None,
+        })
+}
+
+fn merge_g(g: &mut G) -> usize {
+    let Some(elements) = &mut g.elements else {
+        return 0;
+    };
+    let mut merged = 0;
+    for element in elements.iter_mut() {
+        if let GEle::G(g) = element {
+            merged += merge_g(g);
+        }
+    }
+    merged
+        + merge_siblings(elements, |element| match element {
+            GEle::Path(path) => Some(path),
+            _ => None,
+        })
+}
+
+/// Merges the runs of paths among `elements`, returning how many were merged.
+fn merge_siblings<T>(elements: &mut Vec<T>, path_of: fn(&mut T) -> Option<&mut Path>) -> usize {
+    let mut merged = 0;
+    let mut kept = Vec::with_capacity(elements.len());
+    // The box of the run ending with the last kept element, when it is a path that can take more
+    let mut run: Option<BBox> = None;
+    for mut element in elements.drain(..) {
+        let Some((path, bbox)) = path_of(&mut element).and_then(|path| {
+            let bbox = mergeable(path)?;
+            Some((path, bbox))
+        }) else {
+            run = None;
+            kept.push(element);
+            continue;
+        };
+        if let (Some(run_bbox), Some(last)) = (run, kept.last_mut().and_then(path_of)) {
+            if same_style(last, path) && !(must_stay_apart(path) && overlap(run_bbox, bbox)) {
+                last.d = format!("{} {}", last.d.trim_end(), absolute_start(&path.d));
+                run = Some(run_bbox.union(bbox));
+                merged += 1;
+                continue;
+            }
+        }
+        run = Some(bbox);
+        kept.push(element);
+    }
+    *elements = kept;
+    merged
+}
+
+/// The box of a path that can be merged with its siblings.
+fn mergeable(path: &Path) -> Option<BBox> {
+    if path.id.is_some()
+        || path.tabindex.is_some()
+        || path.pointer_events.is_some()
+        || path.has_paint_server()
+    {
+        return None;
+    }
+    PathData::parse(&path.d).ok()?;
+    path.bbox()
+}
+
+fn same_style(a: &Path, b: &Path) -> bool {
+    a.class == b.class
+        && a.fill == b.fill
+        && a.fill_rule == b.fill_rule
+        && a.stroke == b.stroke
+        && a.stroke_width == b.stroke_width
+        && a.stroke_linecap == b.stroke_linecap
+        && a.stroke_linejoin == b.stroke_linejoin
+        && a.stroke_miterlimit == b.stroke_miterlimit
+        && a.opacity == b.opacity
+        && a.shape_rendering == b.shape_rendering
+}
+
+/// Whether overlapping parts of the path would render differently once merged: fills combine
+/// through the fill rule, and an `opacity` applies once to the whole path. The fill is black when
+/// absent.
+fn must_stay_apart(path: &Path) -> bool {
+    path.fill.as_deref().map(str::trim) != Some("none") || path.opacity.is_some()
+}
+
+/// Whether the insides of two boxes intersect; boxes sharing an edge do not overlap.
+fn overlap(a: BBox, b: BBox) -> bool {
+    a.min_x < b.max_x && b.min_x < a.max_x && a.min_y < b.max_y && b.min_y < a.max_y
+}
+
+/// The path data `d`, starting with an absolute move.
+fn absolute_start(d: &str) -> String {
+    let d = d.trim();
+    if !d.starts_with('m') {
+        return d.to_string();
+    }
+    // Parsed by `mergeable` already
+    let mut data = PathData::parse(d).unwrap_or_default();
+    if let Some(PathCommand::MoveTo { relative, .. }) = data.commands.first_mut() {
+        *relative = false;
+    }
+    data.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        options::RsxOptions,
+        parse_svg, parse_svg_to_rsx_with_report,
+        passes::{flatten_transforms, simplify},
+    };
+    use std::fs;
+
+    fn paths(svg: &Svg) -> Vec<&str> {
+        svg.find_all(|node| matches!(node, SvgNodeRef::Path(_)))
+            .into_iter()
+            .map(|node| match node {
+                SvgNodeRef::Path(path) => path.d.as_str(),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merge_test() {
+        let mut svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 100 100' width='100pt' height='100pt'>\
+             <path fill='#000000' d='M 0 0 L 10 0 L 10 10 Z'/>\
+             <path fill='#000000' d='m 20 0 l 10 0 l 0 10 z'/>\
+             <path fill='#000000' d='M 25 5 L 35 5 L 35 15 Z'/>\
+             <path fill='#000000' id='target' d='M 50 0 L 60 0'/>\
+             <path fill='#000000' d='M 70 0 L 80 0 L 80 10 Z'/>\
+             <g/>\
+             <path fill='#000000' d='M 90 0 L 100 0 L 100 10 Z'/>\
+             <path fill='none' stroke='#000000' d='M 0 50 L 50 50'/>\
+             <path fill='none' stroke='#000000' d='M 0 50 L 50 50'/>\
+             </svg>",
+        )
+        .unwrap();
+        let before = svg.stats().paths;
+        let merged = merge_paths(&mut svg);
+        assert_eq!(merged, 2);
+        assert_eq!(svg.stats().paths, before - merged);
+        assert_eq!(
+            paths(&svg),
+            [
+                // The relative move is made absolute
+                "M 0 0 L 10 0 L 10 10 Z M20 0l10 0 0 10Z",
+                // Overlapping the run, the fill would combine with it
+                "M 25 5 L 35 5 L 35 15 Z",
+                "M 50 0 L 60 0",
+                "M 70 0 L 80 0 L 80 10 Z",
+                "M 90 0 L 100 0 L 100 10 Z",
+                // A stroke painted twice looks the same
+                "M 0 50 L 50 50 M 0 50 L 50 50",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_test() {
+        let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'><g>\
+                       <path fill='none' stroke='#000000' d='M 0 0 L 10 0'/>\
+                       <path fill='none' stroke='#000000' d='M 0 5 L 10 5'/>\
+                       <path fill='none' stroke='#000000' d='M 0 9 L 10 9'/>\
+                       </g></svg>";
+        let options = RsxOptions {
+            merge_paths: true,
+            ..Default::default()
+        };
+        let (_, report) = parse_svg_to_rsx_with_report(svg_str, &options).unwrap();
+        assert_eq!(report.merged_paths, 2);
+        assert!(report.is_empty());
+        let (_, report) = parse_svg_to_rsx_with_report(svg_str, &RsxOptions::default()).unwrap();
+        assert_eq!(report.merged_paths, 0);
+    }
+
+    /// A gradient in `objectBoundingBox` units would stretch over the box of the merged path.
+    #[test]
+    fn paint_server_test() {
+        let mut svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 100 100' width='100pt' height='100pt'>\
+             <path fill='url(#g)' d='M 0 0 L 10 0 L 10 10 Z'/>\
+             <path fill='url(#g)' d='M 20 0 L 30 0 L 30 10 Z'/>\
+             <path fill='none' stroke='url(#g)' d='M 0 50 L 50 50'/>\
+             <path fill='none' stroke='url(#g)' d='M 0 60 L 50 60'/>\
+             <defs id='d'><linearGradient id='g'><stop offset='0' stop-color='#000000'/>\
+             <stop offset='1' stop-color='#ffffff'/></linearGradient></defs>\
+             </svg>",
+        )
+        .unwrap();
+        assert_eq!(merge_paths(&mut svg), 0);
+        assert_eq!(svg.stats().paths, 4);
+    }
+
+    /// The merged document draws the same ink, in the same places.
+    #[test]
+    fn geometry_test() {
+        let mut svg = parse_svg(&fs::read_to_string("./test/expected.svg").unwrap()).unwrap();
+        flatten_transforms(&mut svg);
+        simplify(&mut svg);
+        let (before, paths) = (svg.content_bbox().unwrap(), svg.stats().paths);
+        // The groups move up when paths before them are merged, but keep their box
+        let group_bboxes = |svg: &Svg| -> Vec<BBox> {
+            svg.group_bboxes()
+                .into_iter()
+                .map(|(_, bbox)| bbox)
+                .collect()
+        };
+        let groups = group_bboxes(&svg);
+
+        let merged = merge_paths(&mut svg);
+        assert!(merged > 0);
+        assert_eq!(svg.stats().paths, paths - merged);
+        assert_eq!(svg.content_bbox().unwrap(), before);
+        assert_eq!(group_bboxes(&svg), groups);
+        assert_eq!(merge_paths(&mut svg), 0);
+    }
+}
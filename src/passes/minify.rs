@@ -0,0 +1,172 @@
+use crate::svg_types::{number::round_to, path_data::PathData, *};
+
+/// Shortens the path data of every `path` in the document.
+///
+/// Numbers are rounded to `decimals` decimal places and written in their shortest form (`0.50000`
+/// becomes `.5`, `-0.25` becomes `-.25`), separators that the grammar does not need are dropped,
+/// and command letters implied by the previous command are omitted. The commands themselves are
+/// kept: relative commands stay relative, so rounding errors can add up along a path with many
+/// relative segments. The data is read with [`PathData::parse`] and written back by its `Display`,
+/// after the rounding. Path data that cannot be parsed is left as is.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::minify_paths, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <path d='M 0.50000 1.23456 L 2.0 3.0 L 4.0 -5.0 Z '/></svg>",
+/// )
+/// .unwrap();
+/// minify_paths(&mut svg, 2);
+/// match &svg.elements[0] {
+///     SvgElement::Path(path) => assert_eq!(path.d, "M.5 1.23 2 3 4-5Z"),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn minify_paths(svg: &mut Svg, decimals: u8) {
+    for element in &mut svg.elements {
+        match element {
+            SvgElement::Path(path) => minify_path(path, decimals),
+            SvgElement::G(g) => minify_g(g, decimals),
+            SvgElement::Defs(defs) => {
+                for symbol in &mut defs.elements {
+                    if let SymbolEle::Path(path) = &mut symbol.element {
+                        minify_path(path, decimals);
+                    }
+                }
+            }
+            SvgElement::Rect(_) => {}
+        }
+    }
+}
+
+fn minify_g(g: &mut G, decimals: u8) {
+    for element in g.elements.iter_mut().flatten() {
+        minify_g_ele(element, decimals);
+    }
+}
+
+fn minify_g_ele(element: &mut GEle, decimals: u8) {
+    match element {
+        GEle::G(g) => minify_g(g, decimals),
+        GEle::Path(path) => minify_path(path, decimals),
+        GEle::Switch(switch) => {
+            for element in switch.elements_mut() {
+                minify_g_ele(element, decimals);
+            }
+        }
+        GEle::Use(_) | GEle::Image(_) | GEle::Text(_) => {}
+    }
+}
+
+fn minify_path(path: &mut Path, decimals: u8) {
+    if let Some(d) = minify_data(&path.d, decimals) {
+        path.d = d;
+    }
+}
+
+/// Rewrites path data compactly, or returns `None` if it is malformed.
+fn minify_data(data: &str, decimals: u8) -> Option<String> {
+    let data = PathData::parse(data).ok()?;
+    let rounded = PathData {
+        commands: data
+            .into_iter()
+            .map(|command| command.map_numbers(|value| round_to(value, decimals)))
+            .collect(),
+    };
+    Some(rounded.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_svg, passes::geometry::parse_path};
+    use std::fs;
+
+    #[test]
+    fn rounding_test() {
+        assert_eq!(minify_data("M 0.5 -0.25", 3).unwrap(), "M.5-.25");
+        assert_eq!(minify_data("M 10.0 1.23456", 2).unwrap(), "M10 1.23");
+        assert_eq!(minify_data("M -0.0001 1.5e-7", 2).unwrap(), "M0 0");
+        assert_eq!(minify_data("M 1.5e-7 2.5e3", 0).unwrap(), "M0 2500");
+        assert_eq!(
+            minify_data("M 1e300 -1e300", 6).unwrap(),
+            format!("M{}-{}", 1e300, 1e300)
+        );
+    }
+
+    #[test]
+    fn minify_data_test() {
+        assert_eq!(
+            minify_data("M 10.123456 -0.50000 L 20.0 30 L 40 50 Z ", 2).unwrap(),
+            "M10.12-.5 20 30 40 50Z"
+        );
+        // Separators between numbers that could otherwise merge
+        assert_eq!(minify_data("M 0.5 0.5 L 3 0.5", 2).unwrap(), "M.5.5 3 .5");
+        // Repeated movetos are not implicit; the linetos after a moveto are
+        assert_eq!(minify_data("M 0 0 M 1 1 L 2 2", 2).unwrap(), "M0 0M1 1 2 2");
+        assert_eq!(
+            minify_data("m 0 0 l 1 1 z m 1 1", 2).unwrap(),
+            "m0 0 1 1Zm1 1"
+        );
+        // Arc flags, exponents and glued negative numbers
+        assert_eq!(
+            minify_data("M0 0a1 1 0 011.5.5A 1e1 1E1 0 1 0 -1-1", 2).unwrap(),
+            "M0 0a1 1 0 0 1 1.5.5A10 10 0 1 0-1-1"
+        );
+        assert_eq!(minify_data("M 0 0 X 1", 2), None);
+        assert_eq!(minify_data("M 0", 2), None);
+    }
+
+    #[test]
+    fn fixture_test() {
+        fn paths<'a>(svg: &'a Svg, out: &mut Vec<&'a str>) {
+            fn from_g<'a>(g: &'a G, out: &mut Vec<&'a str>) {
+                for element in g.elements.iter().flatten() {
+                    match element {
+                        GEle::G(g) => from_g(g, out),
+                        GEle::Path(path) => out.push(&path.d),
+                        _ => {}
+                    }
+                }
+            }
+            for element in &svg.elements {
+                match element {
+                    SvgElement::Path(path) => out.push(&path.d),
+                    SvgElement::G(g) => from_g(g, out),
+                    SvgElement::Defs(defs) => {
+                        for symbol in &defs.elements {
+                            if let SymbolEle::Path(path) = &symbol.element {
+                                out.push(&path.d);
+                            }
+                        }
+                    }
+                    SvgElement::Rect(_) => {}
+                }
+            }
+        }
+
+        let source = fs::read_to_string("./test/expected.svg").unwrap();
+        let original = parse_svg(&source).unwrap();
+        let mut minified = parse_svg(&source).unwrap();
+        minify_paths(&mut minified, 2);
+
+        let (mut before, mut after) = (Vec::new(), Vec::new());
+        paths(&original, &mut before);
+        paths(&minified, &mut after);
+        assert_eq!(before.len(), after.len());
+        for (before, after) in before.iter().zip(&after) {
+            assert!(after.len() < before.len());
+            let (before, after) = (parse_path(before).unwrap(), parse_path(after).unwrap());
+            assert_eq!(before.len(), after.len());
+            for (before, after) in before.iter().zip(&after) {
+                assert_eq!(
+                    std::mem::discriminant(before),
+                    std::mem::discriminant(after)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+
+use crate::svg_types::*;
+
+/// Removes the symbols that no element refers to, and the `defs` left empty.
+///
+/// The references are the `href="#id"` of `use`, `image` and gradient elements and the `url(#id)`
+/// in paint attributes and in the `clip-path`, `mask` and `filter` of groups, anywhere in the
+/// document (including inside the symbols). The other definitions are kept, as a document
+/// holds few of them. After the pages or the
+/// region of a long document were selected, most of its glyph symbols are no longer used; this
+/// keeps them from being converted and sent to the client.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::prune_unused_defs, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><path d='M 0 0 L 1 1'/></g>\
+///      <defs id='glyph'><symbol id='glyph0' overflow='visible'><path d='M 0 0'/></symbol></defs>\
+///      </svg>",
+/// )
+/// .unwrap();
+/// prune_unused_defs(&mut svg);
+/// assert_eq!(svg.elements.len(), 1);
+/// ```
+pub fn prune_unused_defs(svg: &mut Svg) {
+    let references = references(svg);
+    svg.elements.retain_mut(|element| match element {
+        SvgElement::Defs(defs) => {
+            defs.elements
+                .retain(|symbol| references.contains(symbol.id.as_str()));
+            !defs.elements.is_empty() || !defs.definitions.is_empty()
+        }
+        _ => true,
+    });
+}
+
+/// Collects the ids referred to by `href="#id"` and `url(#id)` anywhere in `svg`.
+pub(super) fn references(svg: &Svg) -> HashSet<String> {
+    let mut references = HashSet::new();
+    for element in &svg.elements {
+        match element {
+            SvgElement::Path(path) => path_references(path, &mut references),
+            SvgElement::G(g) => g_references(g, &mut references),
+            SvgElement::Defs(defs) => {
+                for symbol in &defs.elements {
+                    match &symbol.element {
+                        SymbolEle::Path(path) => path_references(path, &mut references),
+                        SymbolEle::Image(image) => href_reference(&image.href, &mut references),
+                    }
+                }
+                for definition in &defs.definitions {
+                    href_reference(definition.href().unwrap_or_default(), &mut references);
+                    for path in definition.paths() {
+                        path_references(path, &mut references);
+                    }
+                }
+            }
+            SvgElement::Rect(rect) => url_references(rect.fill.as_deref(), &mut references),
+        }
+    }
+    references
+}
+
+fn g_references(g: &G, references: &mut HashSet<String>) {
+    url_references(g.clip_path.as_deref(), references);
+    url_references(g.mask.as_deref(), references);
+    url_references(g.filter.as_deref(), references);
+    for element in g.elements.iter().flatten() {
+        g_ele_references(element, references);
+    }
+}
+
+fn g_ele_references(element: &GEle, references: &mut HashSet<String>) {
+    match element {
+        GEle::G(g) => g_references(g, references),
+        GEle::Use(uuse) => {
+            href_reference(&uuse.href, references);
+            url_references(uuse.fill.as_deref(), references);
+        }
+        GEle::Path(path) => path_references(path, references),
+        GEle::Image(image) => href_reference(&image.href, references),
+        GEle::Text(text) => {
+            url_references(text.fill.as_deref(), references);
+            for content in &text.content {
+                if let TextContent::Tspan(tspan) = content {
+                    url_references(tspan.fill.as_deref(), references);
+                }
+            }
+        }
+        GEle::Switch(switch) => {
+            for element in switch.elements() {
+                g_ele_references(element, references);
+            }
+        }
+    }
+}
+
+fn path_references(path: &Path, references: &mut HashSet<String>) {
+    url_references(path.fill.as_deref(), references);
+    url_references(path.stroke.as_deref(), references);
+}
+
+fn href_reference(href: &str, references: &mut HashSet<String>) {
+    if let Some(id) = href_target(href) {
+        references.insert(id.to_string());
+    }
+}
+
+/// Collects the targets of the `url(#id)` references in an attribute value.
+fn url_references(value: Option<&str>, references: &mut HashSet<String>) {
+    references.extend(
+        url_targets(value.unwrap_or_default())
+            .into_iter()
+            .map(String::from),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn prune_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <g><use href="#glyph1" x="0"/></g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path d="M 0 0 L 1 1"/></symbol>
+                    <symbol id="glyph1" overflow="visible"><path d="M 0 0 L 2 2"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        prune_unused_defs(&mut svg);
+        let SvgElement::Defs(defs) = &svg.elements[1] else {
+            panic!()
+        };
+        let ids: Vec<_> = defs
+            .elements
+            .iter()
+            .map(|symbol| symbol.id.as_str())
+            .collect();
+        assert_eq!(ids, ["glyph1"]);
+        assert!(svg.validate_references().is_empty());
+    }
+}
@@ -0,0 +1,331 @@
+use std::sync::Arc;
+
+use super::Siblings;
+use crate::{options::SanitizeOptions, report::Warning, svg_types::*};
+
+/// Longest `href` quoted in full in a warning; the `data:` URIs of images can be megabytes long.
+const MAX_QUOTED_HREF: usize = 64;
+
+/// Removes the references that could load or run anything from outside the document, returning
+/// a [`Warning::UnsafeReference`] for each.
+///
+/// Meant for documents from untrusted sources, such as SVGs uploaded by users:
+///
+/// - a `use` is kept only when its `href` is a fragment (`#glyph0`), referring to the document
+///   itself, and not to another file, a tracker or a `javascript:` URL;
+/// - an `image` is kept only when its `href` is a `data:` URI with one of the
+///   [`SanitizeOptions::image_types`]. A symbol holding a removed image is removed as well.
+/// - the `href` of a gradient is removed unless it is a fragment, the gradient being kept with
+///   its own stops.
+/// - a paint or reference attribute (`fill`, `stroke`, `clip-path`, `mask`, `filter`) holding a
+///   `url(…)` that is not a fragment (`url(#gradient0)`) is removed, the element being kept with
+///   its default paint, unclipped or unfiltered.
+///
+/// Links do not need to be removed: the parsed tree has no `a` element, so the anchors of a
+/// document never reach the RSX, and the lenient parsers report them as unknown elements.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::SanitizeOptions, parse_svg, passes::sanitize_references};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><use href='#glyph0' x='0'/><use href='https://example.com/a.svg#g' x='5'/></g></svg>",
+/// )
+/// .unwrap();
+/// let warnings = sanitize_references(&mut svg, &SanitizeOptions::default());
+/// assert_eq!(
+///     warnings[0].to_string(),
+///     "svg > g[0] > use[1]: unsafe reference \"https://example.com/a.svg#g\" removed"
+/// );
+/// ```
+pub fn sanitize_references(svg: &mut Svg, options: &SanitizeOptions) -> Vec<Warning> {
+    let mut sanitizer = Sanitizer {
+        options,
+        warnings: Vec::new(),
+    };
+    let mut siblings = Siblings::new("svg");
+    for element in &mut svg.elements {
+        match element {
+            SvgElement::G(g) => sanitizer.sanitize_g(g, siblings.path("g")),
+            SvgElement::Defs(defs) => {
+                let mut symbols = Siblings::new(siblings.path("defs"));
+                defs.elements.retain(|symbol| {
+                    let path = symbols.path("symbol");
+                    match &symbol.element {
+                        SymbolEle::Image(image) => {
+                            sanitizer.keep_image(image, format!("{} > image[0]", path))
+                        }
+                        SymbolEle::Path(_) => true,
+                    }
+                });
+                for definition in &mut defs.definitions {
+                    let path = symbols.path(definition.tag());
+                    let mut paths = Siblings::new(path.clone());
+                    for element in definition.paths_mut() {
+                        sanitizer.sanitize_path(element, paths.path("path"));
+                    }
+                    let href = match definition {
+                        Definition::LinearGradient(gradient) => &mut gradient.href,
+                        Definition::RadialGradient(gradient) => &mut gradient.href,
+                        Definition::ClipPath(_) | Definition::Mask(_) | Definition::Filter(_) => {
+                            continue
+                        }
+                    };
+                    if let Some(link) = href.as_deref() {
+                        if !sanitizer.keep(link, path, link.trim_start().starts_with('#')) {
+                            *href = None;
+                        }
+                    }
+                }
+            }
+            SvgElement::Path(path) => sanitizer.sanitize_path(path, siblings.path("path")),
+            SvgElement::Rect(rect) => {
+                let path = siblings.path("rect");
+                sanitizer.sanitize_url(&mut rect.fill, &path);
+            }
+        }
+    }
+    sanitizer.warnings
+}
+
+struct Sanitizer<'a> {
+    options: &'a SanitizeOptions,
+    warnings: Vec<Warning>,
+}
+
+impl Sanitizer<'_> {
+    fn sanitize_g(&mut self, g: &mut G, path: String) {
+        self.sanitize_url(&mut g.clip_path, &path);
+        self.sanitize_url(&mut g.mask, &path);
+        self.sanitize_url(&mut g.filter, &path);
+        let mut siblings = Siblings::new(path);
+        let Some(elements) = &mut g.elements else {
+            return;
+        };
+        elements.retain_mut(|element| self.keep_g_ele(element, &mut siblings));
+    }
+
+    fn keep_g_ele(&mut self, element: &mut GEle, siblings: &mut Siblings) -> bool {
+        match element {
+            GEle::G(g) => {
+                self.sanitize_g(g, siblings.path("g"));
+                true
+            }
+            GEle::Use(uuse) => {
+                let path = siblings.path("use");
+                self.sanitize_url(&mut uuse.fill, &path);
+                self.keep(&uuse.href, path, uuse.href.trim_start().starts_with('#'))
+            }
+            GEle::Image(image) => {
+                let path = siblings.path("image");
+                self.keep_image(image, path)
+            }
+            GEle::Path(path) => {
+                self.sanitize_path(path, siblings.path("path"));
+                true
+            }
+            GEle::Text(text) => {
+                let path = siblings.path("text");
+                self.sanitize_url(&mut text.fill, &path);
+                let mut tspans = Siblings::new(path);
+                for content in &mut text.content {
+                    if let TextContent::Tspan(tspan) = content {
+                        self.sanitize_url(&mut tspan.fill, &tspans.path("tspan"));
+                    }
+                }
+                true
+            }
+            GEle::Switch(switch) => {
+                let mut branches = Siblings::new(siblings.path("switch"));
+                switch.branches.retain_mut(|branch| match branch {
+                    SwitchBranch::Element(element) => self.keep_g_ele(element, &mut branches),
+                    SwitchBranch::Unsupported(_) => true,
+                });
+                true
+            }
+        }
+    }
+
+    fn sanitize_path(&mut self, element: &mut Path, path: String) {
+        self.sanitize_url(&mut element.fill, &path);
+        self.sanitize_url(&mut element.stroke, &path);
+    }
+
+    /// Removes the attribute `value` of the element at `path` if it has a `url(…)` pointing
+    /// outside the document, reporting it.
+    fn sanitize_url(&mut self, value: &mut Option<Arc<str>>, path: &str) {
+        if let Some(target) = value.as_deref().and_then(external_url) {
+            self.warnings.push(Warning::UnsafeReference {
+                href: quoted(target),
+                path: path.to_string(),
+            });
+            *value = None;
+        }
+    }
+
+    fn keep_image(&mut self, image: &Image, path: String) -> bool {
+        let allowed = data_type(&image.href).is_some_and(|mime| {
+            self.options
+                .image_types
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(mime))
+        });
+        self.keep(&image.href, path, allowed)
+    }
+
+    /// Whether to keep the element at `path`, reporting its `href` if it is not `safe`.
+    fn keep(&mut self, href: &str, path: String, safe: bool) -> bool {
+        if !safe {
+            self.warnings.push(Warning::UnsafeReference {
+                href: quoted(href),
+                path,
+            });
+        }
+        safe
+    }
+}
+
+/// The target of the first `url(…)` in an attribute value that is not a fragment of the
+/// document, such as `https://tracker.example/p.svg#p` in `url(https://tracker.example/p.svg#p)`.
+fn external_url(value: &str) -> Option<&str> {
+    // CSS function names are case-insensitive, and lowercasing keeps the byte offsets
+    let lowercase = value.to_ascii_lowercase();
+    let mut offset = 0;
+    while let Some(start) = lowercase[offset..].find("url(") {
+        let rest = &value[offset + start + 4..];
+        let end = rest.find(')').unwrap_or(rest.len());
+        let target = rest[..end].trim().trim_matches(['\'', '"']).trim();
+        if !target.starts_with('#') {
+            return Some(target);
+        }
+        offset += start + 4 + end;
+    }
+    None
+}
+
+/// The MIME type of a `data:` URI, such as `image/png` for `data:image/png;base64,...`.
+fn data_type(href: &str) -> Option<&str> {
+    let href = href.trim_start();
+    let scheme = href.get(..5)?;
+    if !scheme.eq_ignore_ascii_case("data:") {
+        return None;
+    }
+    let rest = &href[5..];
+    let end = rest.find([';', ',']).unwrap_or(rest.len());
+    Some(rest[..end].trim())
+}
+
+/// `href`, shortened to [`MAX_QUOTED_HREF`] characters.
+fn quoted(href: &str) -> String {
+    match href.char_indices().nth(MAX_QUOTED_HREF) {
+        Some((end, _)) => format!("{}...", &href[..end]),
+        None => href.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compat::render_html, options::RsxOptions, parse_svg, parse_untrusted_svg_to_rsx};
+
+    #[test]
+    fn sanitize_references_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt"
+                    xmlns:xlink="http://www.w3.org/1999/xlink">
+                <g>
+                    <use xlink:href="#g0" x="0"/>
+                    <use xlink:href="javascript:alert(1)" x="1"/>
+                    <g>
+                        <image width="1" height="1" preserveAspectRatio="none" href="data:IMAGE/PNG;base64,AAAA"/>
+                        <image width="1" height="1" preserveAspectRatio="none" href="data:image/svg+xml;base64,AAAA"/>
+                        <image width="1" height="1" preserveAspectRatio="none" href="https://tracker.example/pixel.gif"/>
+                    </g>
+                </g>
+                <defs id="glyph">
+                    <symbol id="g0" overflow="visible"><path d="M 0 0 L 1 1"/></symbol>
+                    <symbol id="g1" overflow="visible">
+                        <image width="1" height="1" preserveAspectRatio="none" href="data:text/html,&lt;script&gt;"/>
+                    </symbol>
+                    <linearGradient id="fade" xlink:href="https://example.com/a.svg#g"/>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        let warnings: Vec<_> = sanitize_references(&mut svg, &SanitizeOptions::default())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                "svg > g[0] > use[1]: unsafe reference \"javascript:alert(1)\" removed",
+                "svg > g[0] > g[0] > image[1]: unsafe reference \"data:image/svg+xml;base64,AAAA\" removed",
+                "svg > g[0] > g[0] > image[2]: unsafe reference \"https://tracker.example/pixel.gif\" removed",
+                "svg > defs[0] > symbol[1] > image[0]: unsafe reference \"data:text/html,<script>\" removed",
+                "svg > defs[0] > linearGradient[0]: unsafe reference \"https://example.com/a.svg#g\" removed",
+            ]
+        );
+        let stats = svg.stats();
+        assert_eq!((stats.uses, stats.images, stats.symbols), (1, 1, 1));
+        let SvgElement::Defs(defs) = &svg.elements[1] else {
+            panic!("expected defs");
+        };
+        assert_eq!(defs.definitions[0].href(), None);
+
+        let mut options = SanitizeOptions::default();
+        options.image_types.push("image/svg+xml".to_string());
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <g><image width="1" height="1" preserveAspectRatio="none" href="data:image/svg+xml,x"/></g>
+            </svg>"##,
+        )
+        .unwrap();
+        assert!(sanitize_references(&mut svg, &options).is_empty());
+    }
+
+    #[test]
+    fn external_url_test() {
+        assert_eq!(external_url("url(#gradient0)"), None);
+        assert_eq!(external_url("url( '#g' ) #000000"), None);
+        assert_eq!(external_url("#000000"), None);
+        assert_eq!(
+            external_url("url(#a) URL(\"https://tracker.example/p.svg#p\")"),
+            Some("https://tracker.example/p.svg#p")
+        );
+        assert_eq!(external_url("url(p.svg#p"), Some("p.svg#p"));
+    }
+
+    #[test]
+    fn untrusted_urls_test() {
+        let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+                       <g filter='url(https://tracker.example/f.svg#f)'>\
+                       <path fill='url(https://tracker.example/p.svg#p)' d='M 0 0 L 1 1'/>\
+                       <path fill='url(#local)' stroke='url(data:image/png,x)' d='M 0 0 L 1 1'/>\
+                       </g><defs id='paints'><linearGradient id='local'/></defs></svg>";
+        let (element, report) =
+            parse_untrusted_svg_to_rsx(svg_str, &RsxOptions::default()).unwrap();
+        let warnings: Vec<_> = report.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "svg > g[0]: unsafe reference \"https://tracker.example/f.svg#f\" removed",
+                "svg > g[0] > path[0]: unsafe reference \"https://tracker.example/p.svg#p\" removed",
+                "svg > g[0] > path[1]: unsafe reference \"data:image/png,x\" removed",
+            ]
+        );
+        let html = render_html(element);
+        assert!(!html.contains("tracker.example"));
+        assert!(!html.contains("data:"));
+        assert!(html.contains("url(#local)"));
+    }
+
+    #[test]
+    fn quoted_test() {
+        assert_eq!(quoted("#g0"), "#g0");
+        let long = format!("data:image/png;base64,{}", "A".repeat(100));
+        assert_eq!(quoted(&long), format!("{}...", &long[..64]));
+    }
+}
@@ -0,0 +1,234 @@
+use crate::svg_types::*;
+
+/// Removes the groups that carry no attribute and the optional attributes that are empty.
+///
+/// Optional attributes holding an empty string are set to `None`. A `g` without `id`, `class`,
+/// `transform`, `clip-path`, `mask`, `filter`, `tabindex`, `pointer-events` and rendering hints is
+/// then replaced by its children, and dropped when it has none. At the top level, a group is only
+/// unwrapped when all its children may appear directly in the `svg` (groups and paths). The
+/// rendering is unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::simplify, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><g class=''><path d='M 0 0 L 10 10' fill=''/></g></g><g/></svg>",
+/// )
+/// .unwrap();
+/// simplify(&mut svg);
+/// assert_eq!(svg.elements.len(), 1);
+/// match &svg.elements[0] {
+///     SvgElement::Path(path) => assert!(path.fill.is_none()),
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn simplify(svg: &mut Svg) {
+    let mut elements = Vec::with_capacity(svg.elements.len());
+    for element in svg.elements.drain(..) {
+        match element {
+            SvgElement::Path(mut path) => {
+                simplify_path(&mut path);
+                elements.push(SvgElement::Path(path));
+            }
+            SvgElement::G(mut g) => {
+                simplify_g(&mut g);
+                if has_attributes(&g) {
+                    elements.push(SvgElement::G(g));
+                    continue;
+                }
+                let children = g.elements.take().unwrap_or_default();
+                let hoistable = children
+                    .iter()
+                    .all(|child| matches!(child, GEle::G(_) | GEle::Path(_)));
+                if !hoistable {
+                    g.elements = Some(children);
+                    elements.push(SvgElement::G(g));
+                    continue;
+                }
+                elements.extend(children.into_iter().map(|child| match child {
+                    GEle::G(g) => SvgElement::G(g),
+                    GEle::Path(path) => SvgElement::Path(path),
+                    GEle::Use(_) | GEle::Image(_) | GEle::Text(_) | GEle::Switch(_) => {
+                        unreachable!("checked above")
+                    }
+                }));
+            }
+            SvgElement::Defs(mut defs) => {
+                for symbol in &mut defs.elements {
+                    match &mut symbol.element {
+                        SymbolEle::Path(path) => simplify_path(path),
+                        SymbolEle::Image(image) => clear_empty(&mut image.transform),
+                    }
+                }
+                elements.push(SvgElement::Defs(defs));
+            }
+            SvgElement::Rect(mut rect) => {
+                clear_empty(&mut rect.fill);
+                elements.push(SvgElement::Rect(rect));
+            }
+        }
+    }
+    svg.elements = elements;
+}
+
+fn simplify_g(g: &mut G) {
+    clear_empty(&mut g.id);
+    clear_empty(&mut g.class);
+    clear_empty(&mut g.transform);
+    clear_empty(&mut g.clip_path);
+    clear_empty(&mut g.mask);
+    clear_empty(&mut g.filter);
+    clear_empty(&mut g.tabindex);
+    clear_empty(&mut g.pointer_events);
+    clear_empty(&mut g.shape_rendering);
+    clear_empty(&mut g.text_rendering);
+    let Some(children) = g.elements.take() else {
+        return;
+    };
+
+    let mut elements = Vec::with_capacity(children.len());
+    for mut child in children {
+        simplify_g_ele(&mut child);
+        match child {
+            GEle::G(inner) if !has_attributes(&inner) => {
+                elements.extend(inner.elements.unwrap_or_default())
+            }
+            child => elements.push(child),
+        }
+    }
+    g.elements = (!elements.is_empty()).then_some(elements);
+}
+
+/// Simplifies `element` in place. The alternatives of a `switch` are kept as they are, even the
+/// groups without attributes, for the same one to be rendered.
+fn simplify_g_ele(element: &mut GEle) {
+    match element {
+        GEle::G(g) => simplify_g(g),
+        GEle::Use(uuse) => {
+            clear_empty(&mut uuse.fill);
+            clear_empty(&mut uuse.fill_rule);
+            clear_empty(&mut uuse.transform);
+        }
+        GEle::Path(path) => simplify_path(path),
+        GEle::Image(image) => clear_empty(&mut image.transform),
+        GEle::Text(text) => simplify_text(text),
+        GEle::Switch(switch) => {
+            clear_empty(&mut switch.id);
+            clear_empty(&mut switch.transform);
+            switch.elements_mut().for_each(simplify_g_ele);
+        }
+    }
+}
+
+fn simplify_text(text: &mut Text) {
+    for attribute in [
+        &mut text.id,
+        &mut text.x,
+        &mut text.y,
+        &mut text.dx,
+        &mut text.dy,
+        &mut text.font_family,
+        &mut text.font_size,
+        &mut text.font_weight,
+        &mut text.font_style,
+        &mut text.letter_spacing,
+        &mut text.word_spacing,
+        &mut text.dominant_baseline,
+        &mut text.text_anchor,
+        &mut text.text_rendering,
+        &mut text.transform,
+    ] {
+        clear_empty(attribute);
+    }
+    clear_empty(&mut text.class);
+    clear_empty(&mut text.fill);
+}
+
+fn simplify_path(path: &mut Path) {
+    for attribute in [
+        &mut path.class,
+        &mut path.fill,
+        &mut path.stroke,
+        &mut path.stroke_width,
+    ] {
+        clear_empty(attribute);
+    }
+    clear_empty(&mut path.id);
+    clear_empty(&mut path.stroke_miterlimit);
+    clear_empty(&mut path.fill_rule);
+    clear_empty(&mut path.stroke_linecap);
+    clear_empty(&mut path.stroke_linejoin);
+    clear_empty(&mut path.tabindex);
+    clear_empty(&mut path.pointer_events);
+    clear_empty(&mut path.opacity);
+    clear_empty(&mut path.shape_rendering);
+}
+
+fn has_attributes(g: &G) -> bool {
+    g.id.is_some()
+        || g.class.is_some()
+        || g.transform.is_some()
+        || g.clip_path.is_some()
+        || g.mask.is_some()
+        || g.filter.is_some()
+        || g.tabindex.is_some()
+        || g.pointer_events.is_some()
+        || g.shape_rendering.is_some()
+        || g.text_rendering.is_some()
+}
+
+fn clear_empty(attribute: &mut Option<impl AsRef<str>>) {
+    if attribute
+        .as_ref()
+        .is_some_and(|value| value.as_ref().is_empty())
+    {
+        *attribute = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn simplify_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <g>
+                    <g class="typst-text" transform="">
+                        <g><use href="#glyph0" x="0" fill=""/></g>
+                        <g></g>
+                    </g>
+                </g>
+                <g>
+                    <use href="#glyph0" x="1"/>
+                </g>
+            </svg>"##,
+        )
+        .unwrap();
+        simplify(&mut svg);
+
+        assert_eq!(svg.elements.len(), 2);
+        let SvgElement::G(text) = &svg.elements[0] else {
+            panic!()
+        };
+        assert_eq!(text.class.as_deref(), Some("typst-text"));
+        assert_eq!(text.transform, None);
+        let elements = text.elements.as_ref().unwrap();
+        assert_eq!(elements.len(), 1);
+        let GEle::Use(uuse) = &elements[0] else {
+            panic!()
+        };
+        assert_eq!(uuse.fill, None);
+
+        // A `use` cannot be hoisted into the root `svg`
+        let SvgElement::G(wrapper) = &svg.elements[1] else {
+            panic!()
+        };
+        assert!(matches!(wrapper.elements.as_deref(), Some([GEle::Use(_)])));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::svg_types::*;
+
+/// Moves the `stop-color` and `stop-opacity` given in the `style` of the gradient stops into
+/// their attributes, with [`Stop::normalize_style`].
+///
+/// The conversions do it before the other passes, so that [`recolor`](super::recolor) changes the
+/// colors of `<stop style="stop-color:…"/>` too.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::normalize_stops, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <defs id='g'><linearGradient id='fade'>\
+///      <stop offset='0' style='stop-color:#000000;stop-opacity:0.5'/>\
+///      </linearGradient></defs></svg>",
+/// )
+/// .unwrap();
+/// normalize_stops(&mut svg);
+/// let SvgElement::Defs(defs) = &svg.elements[0] else {
+///     unreachable!()
+/// };
+/// let stop = &defs.definitions[0].stops()[0];
+/// assert_eq!(stop.stop_color.as_deref(), Some("#000000"));
+/// assert_eq!(stop.stop_opacity.as_deref(), Some("0.5"));
+/// assert_eq!(stop.style, None);
+/// ```
+pub fn normalize_stops(svg: &mut Svg) {
+    svg.walk_mut(&mut NormalizeStops);
+}
+
+struct NormalizeStops;
+
+impl VisitorMut for NormalizeStops {
+    fn visit_stop(&mut self, stop: &mut Stop) {
+        stop.normalize_style();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compat::render_html,
+        options::{ColorMap, RsxOptions},
+        parse_svg, parse_svg_to_rsx, parse_svg_to_rsx_with_options,
+    };
+
+    fn document(stops: &str) -> String {
+        format!(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <rect x="0" y="0" width="10" height="10" fill="url(#fade)"/>
+                <defs id="gradient">
+                    <linearGradient id="fade">{}</linearGradient>
+                </defs>
+            </svg>"##,
+            stops
+        )
+    }
+
+    #[test]
+    fn same_rsx_test() {
+        let attributes = document(
+            r##"<stop offset="0" stop-color="#000000" stop-opacity="0.5"/>
+                <stop offset="1" stop-color="#ff0000"/>"##,
+        );
+        let styles = document(
+            r##"<stop offset="0" style="stop-color: #000000; stop-opacity: 0.5"/>
+                <stop offset="1" stop-color="#0000ff" style="STOP-COLOR:#ff0000;"/>"##,
+        );
+        let html = render_html(parse_svg_to_rsx(&attributes).unwrap());
+        assert!(html.contains(r##"stop-opacity="0.5""##), "{}", html);
+        assert_eq!(render_html(parse_svg_to_rsx(&styles).unwrap()), html);
+
+        let options = RsxOptions {
+            colors: Some(ColorMap::from([("#000000", "var(--ink)")])),
+            ..Default::default()
+        };
+        let html = render_html(parse_svg_to_rsx_with_options(&attributes, &options).unwrap());
+        assert!(html.contains(r#"stop-color="var(--ink)""#), "{}", html);
+        assert_eq!(
+            render_html(parse_svg_to_rsx_with_options(&styles, &options).unwrap()),
+            html
+        );
+    }
+
+    #[test]
+    fn other_declarations_test() {
+        let mut svg = parse_svg(&document(
+            r##"<stop style="color: red; stop-color:#fff; ;"/>"##,
+        ))
+        .unwrap();
+        normalize_stops(&mut svg);
+        let SvgElement::Defs(defs) = &svg.elements[1] else {
+            panic!()
+        };
+        let stop = &defs.definitions[0].stops()[0];
+        assert_eq!(stop.stop_color.as_deref(), Some("#fff"));
+        assert_eq!(stop.stop_opacity, None);
+        assert_eq!(stop.style.as_deref(), Some("color: red"));
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use crate::svg_types::*;
+
+/// Removes the paint attributes of the document, so that its appearance is entirely controlled
+/// from CSS (`.typst-doc path { fill: currentColor }`).
+///
+/// `fill`, `stroke`, `stroke-width`, `stroke-linecap`, `stroke-linejoin` and `stroke-miterlimit`
+/// are removed from every `path` (including the ones inside symbols), and `fill` from every `use`,
+/// `rect`, `text` and `tspan`. `fill-rule` is kept since it changes the shape being filled, and so is
+/// `fill="none"`, without which the open paths drawn as strokes would be filled by the CSS fill.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, passes::strip_presentation_attributes, svg_types::*};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <path d='M 0 0 L 10 10' fill='#000000' fill-rule='evenodd'/></svg>",
+/// )
+/// .unwrap();
+/// strip_presentation_attributes(&mut svg);
+/// match &svg.elements[0] {
+///     SvgElement::Path(path) => {
+///         assert_eq!(path.fill, None);
+///         assert_eq!(path.fill_rule, Some(FillRule::EvenOdd));
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+pub fn strip_presentation_attributes(svg: &mut Svg) {
+    svg.walk_mut(&mut Strip);
+}
+
+struct Strip;
+
+impl VisitorMut for Strip {
+    fn visit_path(&mut self, path: &mut Path) {
+        strip_fill(&mut path.fill);
+        path.stroke = None;
+        path.stroke_width = None;
+        path.stroke_linecap = None;
+        path.stroke_linejoin = None;
+        path.stroke_miterlimit = None;
+    }
+
+    fn visit_use(&mut self, uuse: &mut Use) {
+        strip_fill(&mut uuse.fill);
+    }
+
+    fn visit_rect(&mut self, rect: &mut Rect) {
+        strip_fill(&mut rect.fill);
+    }
+
+    fn visit_text(&mut self, text: &mut Text) {
+        strip_fill(&mut text.fill);
+        for content in &mut text.content {
+            if let TextContent::Tspan(tspan) = content {
+                strip_fill(&mut tspan.fill);
+            }
+        }
+    }
+}
+
+fn strip_fill(fill: &mut Option<Arc<str>>) {
+    if fill.as_deref().map(str::trim) != Some("none") {
+        *fill = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn strip_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <rect x="0" y="0" width="10" height="10" fill="#ffffff"/>
+                <g>
+                    <use href="#glyph0" x="0" fill="#000000" fill-rule="evenodd"/>
+                    <path fill="none" stroke="#000000" stroke-width="2" stroke-linecap="round" d="M 0 0 L 1 1"/>
+                </g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path fill="#000000" d="M 1 1 L 2 2"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        strip_presentation_attributes(&mut svg);
+
+        let SvgElement::Rect(rect) = &svg.elements[0] else {
+            panic!()
+        };
+        assert_eq!(rect.fill, None);
+        let SvgElement::G(g) = &svg.elements[1] else {
+            panic!()
+        };
+        let Some([GEle::Use(uuse), GEle::Path(stroke)]) = g.elements.as_deref() else {
+            panic!()
+        };
+        assert_eq!(uuse.fill, None);
+        assert_eq!(uuse.fill_rule, Some(FillRule::EvenOdd));
+        assert_eq!(
+            stroke.as_ref(),
+            &Path {
+                d: "M 0 0 L 1 1".to_string(),
+                fill: Some("none".into()),
+                ..Default::default()
+            }
+        );
+        let SvgElement::Defs(defs) = &svg.elements[2] else {
+            panic!()
+        };
+        let SymbolEle::Path(glyph) = &defs.elements[0].element else {
+            panic!()
+        };
+        assert_eq!(glyph.fill, None);
+    }
+}
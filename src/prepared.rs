@@ -0,0 +1,199 @@
+use dioxus::prelude::*;
+
+use crate::{
+    convert,
+    error::Error,
+    options::{ElementAttributes, RsxOptions, Selector, Sizing, SwitchMode},
+    parse_svg, passes,
+    svg_types::Svg,
+};
+
+/// A document ready to be rendered: the parsed tree, transformed by the [`passes`] of some
+/// [`RsxOptions`], together with the options affecting the RSX itself.
+///
+/// An `Element` belongs to the Dioxus runtime that created it and is not `Send`, so it can be
+/// neither built on another thread nor shared between the requests of a server. A `PreparedSvg`
+/// is `Send + Sync`: the expensive part of a conversion (compiling, parsing and transforming) can
+/// run in a background task or be cached, and [`render`](PreparedSvg::render) builds the
+/// `Element` inside the component, without parsing again.
+///
+/// The [`on_click`](RsxOptions::on_click) handler of the options is not kept, an `EventHandler`
+/// being bound to a component; pass it to [`render_with_on_click`](PreparedSvg::render_with_on_click)
+/// instead.
+///
+/// # Example
+///
+/// ```rust
+/// use std::thread;
+/// use typst_2_rsx::{options::RsxOptions, PreparedSvg};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <g><path d='M 0 0 L 10 10'/></g></svg>";
+/// let prepared = thread::spawn(move || {
+///     let options = RsxOptions {
+///         simplify: true,
+///         ..Default::default()
+///     };
+///     PreparedSvg::parse(svg_str, &options)
+/// })
+/// .join()
+/// .unwrap()
+/// .unwrap();
+///
+/// // In a component
+/// let element = prepared.render();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedSvg {
+    svg: Svg,
+    options: RenderOptions,
+}
+
+/// The settings of [`RsxOptions`] read when building the RSX, after the passes.
+#[derive(Debug, Clone, PartialEq)]
+struct RenderOptions {
+    sizing: Sizing,
+    root_class: Option<String>,
+    root_id: Option<String>,
+    root_style: Option<String>,
+    strip_presentation_attributes: bool,
+    keys: bool,
+    empty_absent_attributes: bool,
+    element_attributes: Vec<(Selector, ElementAttributes)>,
+    rendering_hint: Option<(Selector, String)>,
+    switch: SwitchMode,
+}
+
+impl PreparedSvg {
+    /// Prepares a parsed tree for rendering, applying the passes enabled in `options`.
+    ///
+    /// This cannot fail: duplicate ids are handled as with
+    /// [`DuplicateIds::Warn`](crate::options::DuplicateIds::Warn) even when `options` asks for
+    /// [`DuplicateIds::Error`](crate::options::DuplicateIds::Error), which
+    /// [`parse`](PreparedSvg::parse) honors.
+    pub fn new(mut svg: Svg, options: &RsxOptions) -> PreparedSvg {
+        passes::apply_lenient(&mut svg, options);
+        PreparedSvg::prepared(svg, options)
+    }
+
+    /// Prepares a parsed tree for rendering like [`new`](PreparedSvg::new), failing on the
+    /// duplicate ids with [`DuplicateIds::Error`](crate::options::DuplicateIds::Error).
+    pub(crate) fn try_new(mut svg: Svg, options: &RsxOptions) -> Result<PreparedSvg, Error> {
+        passes::apply(&mut svg, options)?;
+        Ok(PreparedSvg::prepared(svg, options))
+    }
+
+    fn prepared(svg: Svg, options: &RsxOptions) -> PreparedSvg {
+        PreparedSvg {
+            svg,
+            options: RenderOptions {
+                sizing: options.sizing,
+                root_class: options.root_class.clone(),
+                root_id: options.root_id.clone(),
+                root_style: options.root_style.clone(),
+                strip_presentation_attributes: options.strip_presentation_attributes,
+                keys: options.keys,
+                empty_absent_attributes: options.empty_absent_attributes,
+                element_attributes: options.element_attributes.clone(),
+                rendering_hint: options.rendering_hint.clone(),
+                switch: options.switch,
+            },
+        }
+    }
+
+    /// Parses an SVG string with [`parse_svg`] and prepares it for rendering, applying the passes
+    /// enabled in `options`.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(PreparedSvg)` : the prepared document.
+    /// - `Err(Error)` : the document could not be parsed, or has duplicate ids with
+    ///   [`DuplicateIds::Error`](crate::options::DuplicateIds::Error).
+    pub fn parse(svg_str: &str, options: &RsxOptions) -> Result<PreparedSvg, Error> {
+        PreparedSvg::try_new(parse_svg(svg_str)?, options)
+    }
+
+    /// Returns the tree, after the passes.
+    pub fn svg(&self) -> &Svg {
+        &self.svg
+    }
+
+    /// Builds the RSX element of the document, like
+    /// [`parse_svg_to_rsx_with_options`](crate::parse_svg_to_rsx_with_options) with the options
+    /// the document was prepared with.
+    ///
+    /// The tree is copied into the RSX, which is much faster than parsing it again, but not
+    /// free for a large document: a component should keep the element rather than render it on
+    /// every update.
+    pub fn render(&self) -> Element {
+        convert::svg_root(self.svg.clone(), &self.rsx_options(None))
+    }
+
+    /// Like [`render`](PreparedSvg::render), attaching `handler` to the elements matched by
+    /// `selector`, like [`RsxOptions::on_click`].
+    pub fn render_with_on_click(
+        &self,
+        selector: Selector,
+        handler: EventHandler<MouseEvent>,
+    ) -> Element {
+        convert::svg_root(
+            self.svg.clone(),
+            &self.rsx_options(Some((selector, handler))),
+        )
+    }
+
+    fn rsx_options(&self, on_click: Option<(Selector, EventHandler<MouseEvent>)>) -> RsxOptions {
+        let options = &self.options;
+        RsxOptions {
+            sizing: options.sizing,
+            root_class: options.root_class.clone(),
+            root_id: options.root_id.clone(),
+            root_style: options.root_style.clone(),
+            strip_presentation_attributes: options.strip_presentation_attributes,
+            keys: options.keys,
+            empty_absent_attributes: options.empty_absent_attributes,
+            element_attributes: options.element_attributes.clone(),
+            rendering_hint: options.rendering_hint.clone(),
+            switch: options.switch,
+            on_click,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_svg_to_rsx_with_options, utils::read_file};
+
+    #[test]
+    fn prepared_svg_test() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PreparedSvg>();
+
+        let svg_str = read_file("./test/expected.svg").unwrap();
+        let options = RsxOptions {
+            id_prefix: Some("doc-".to_string()),
+            sizing: Sizing::Responsive,
+            root_class: Some("figure".to_string()),
+            simplify: true,
+            keys: true,
+            rendering_hint: Some((Selector::Root, "geometricPrecision".to_string())),
+            ..Default::default()
+        };
+        let prepared = PreparedSvg::parse(&svg_str, &options).unwrap();
+        assert_eq!(
+            format!("{:?}", prepared.render()),
+            format!(
+                "{:?}",
+                parse_svg_to_rsx_with_options(&svg_str, &options).unwrap()
+            )
+        );
+        assert!(prepared.svg().stats().uses > 0);
+
+        // The passes ran once, when preparing
+        let rendered = format!("{:?}", prepared.render());
+        assert!(rendered.contains("#doc-"));
+        assert!(!rendered.contains("#doc-doc-"));
+    }
+}
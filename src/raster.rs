@@ -0,0 +1,163 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use dioxus::prelude::*;
+use resvg::{tiny_skia, usvg};
+
+use crate::error::Error;
+
+/// An SVG rasterized by [`svg_to_png`].
+///
+/// # Field
+///
+/// - `data` : the PNG file.
+/// - `width`, `height` : the size of the image, in pixels.
+/// - `css_width`, `css_height` : the size the SVG is displayed at, in CSS pixels, rounded up. The
+///   image is `scale` times larger, to stay sharp on high-density screens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Png {
+    pub data: Vec<u8>,
+
+    pub width: u32,
+
+    pub height: u32,
+
+    pub css_width: u32,
+
+    pub css_height: u32,
+}
+
+impl Png {
+    /// The PNG as a `data:image/png;base64,...` URI, to use as the `src` of an `img`.
+    pub fn data_uri(&self) -> String {
+        format!("data:image/png;base64,{}", STANDARD.encode(&self.data))
+    }
+
+    /// An `img` element showing the PNG at the size of the SVG.
+    pub fn to_rsx(&self) -> Element {
+        let src = self.data_uri();
+        rsx! {
+            img {
+                src,
+                width: "{self.css_width}",
+                height: "{self.css_height}",
+            }
+        }
+    }
+}
+
+/// Rasterizes an SVG to PNG with `resvg`, for the embedded webviews that render complex SVGs
+/// poorly.
+///
+/// The size of the image is the size of the SVG (its `width` and `height`, `pt` being converted to
+/// CSS pixels) multiplied by `scale`, rounded up. Typst outlines the text of its documents into
+/// paths, so no font is needed; a `text` element in another SVG is not drawn. Only available with
+/// the `raster` feature.
+///
+/// # parameter
+///
+/// - `svg_str` : the SVG, which does not need to follow the layout of the Typst output.
+/// - `scale` : the number of image pixels per CSS pixel, such as `2.0` for a high-density screen.
+///
+/// # Return value
+///
+/// - `Ok(Png)` : the image.
+/// - `Err(Error)` : [`Error::Rasterize`] when the SVG cannot be parsed, `scale` is not a positive
+///   number, or the image would be empty or too large.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_to_png;
+///
+/// let png = svg_to_png(
+///     "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 10 10' width='30pt' height='15pt'>\
+///      <path d='M 0 0 L 10 10'/></svg>",
+///     2.0,
+/// )
+/// .unwrap();
+/// assert_eq!((png.css_width, png.css_height), (40, 20));
+/// assert_eq!((png.width, png.height), (80, 40));
+/// ```
+pub fn svg_to_png(svg_str: &str, scale: f32) -> Result<Png, Error> {
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(rasterize_error(format!("invalid scale {}", scale)));
+    }
+    let tree = usvg::Tree::from_str(svg_str, &usvg::Options::default())
+        .map_err(|e| rasterize_error(e.to_string()))?;
+    let size = tree.size();
+    let pixels = |length: f32| (length * scale).ceil() as u32;
+    let (width, height) = (pixels(size.width()), pixels(size.height()));
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or_else(|| {
+        rasterize_error(format!(
+            "cannot allocate an image of {}x{} pixels",
+            width, height
+        ))
+    })?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    let data = pixmap
+        .encode_png()
+        .map_err(|e| rasterize_error(e.to_string()))?;
+    Ok(Png {
+        data,
+        width,
+        height,
+        css_width: size.width().ceil() as u32,
+        css_height: size.height().ceil() as u32,
+    })
+}
+
+fn rasterize_error(reason: String) -> Error {
+    Error::Rasterize { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The width and height in the `IHDR` chunk of a PNG, after checking its signature.
+    fn png_size(data: &[u8]) -> (u32, u32) {
+        assert_eq!(&data[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&data[12..16], b"IHDR");
+        let read = |at: usize| u32::from_be_bytes(data[at..at + 4].try_into().unwrap());
+        (read(16), read(20))
+    }
+
+    #[test]
+    fn svg_to_png_test() {
+        let svg_str = crate::utils::read_file("./test/formula.svg").unwrap();
+        let png = svg_to_png(&svg_str, 1.5).unwrap();
+        assert_eq!(png_size(&png.data), (png.width, png.height));
+        let svg = crate::parse_svg(&svg_str).unwrap();
+        let px = |length: &str| length.trim_end_matches("pt").parse::<f32>().unwrap() * 4.0 / 3.0;
+        let (width, height) = (px(&svg.width), px(&svg.height));
+        assert_eq!(
+            (png.css_width, png.css_height),
+            (width.ceil() as u32, height.ceil() as u32)
+        );
+        assert_eq!(
+            (png.width, png.height),
+            ((width * 1.5).ceil() as u32, (height * 1.5).ceil() as u32)
+        );
+        // The formula is drawn, not only the background
+        assert!(png.data.len() > 200);
+
+        let html = crate::compat::render_html(png.to_rsx());
+        assert!(html.starts_with("<img src=\"data:image/png;base64,iVBORw0KGgo"));
+        assert!(html.ends_with(&format!(
+            " width=\"{}\" height=\"{}\"/>",
+            png.css_width, png.css_height
+        )));
+
+        assert!(matches!(
+            svg_to_png(&svg_str, 0.0),
+            Err(Error::Rasterize { reason }) if reason == "invalid scale 0"
+        ));
+        assert!(matches!(
+            svg_to_png("<svg", 1.0),
+            Err(Error::Rasterize { .. })
+        ));
+    }
+}
@@ -0,0 +1,665 @@
+//! Warnings about the parts of a document that were changed or left out of its conversion.
+//!
+//! The parsers fail on the first element they cannot represent, and the conversion silently drops
+//! what the parsed tree has no room for. The `*_with_report` functions, such as
+//! [`parse_svg_to_rsx_with_report`](crate::parse_svg_to_rsx_with_report), convert such documents
+//! anyway and list every change in a [`ConversionReport`]. With the `tracing` feature, each
+//! warning is also emitted as a `warn!` event.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use xml::{
+    name::OwnedName,
+    reader::{ParserConfig, XmlEvent},
+    writer::EmitterConfig,
+    EventWriter,
+};
+
+use crate::{
+    diagnose::allowed_children,
+    options::{ColorMap, SwitchMode},
+    passes::Siblings,
+    svg_types::*,
+};
+
+/// The namespace of the SVG elements.
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+/// The warnings gathered while converting one document, in document order.
+///
+/// # Field
+///
+/// - `warnings` : the changes made to the document, each naming the element concerned.
+/// - `merged_paths` : the number of paths merged into the one before them by
+///   [`RsxOptions::merge_paths`](crate::options::RsxOptions), as
+///   [`merge_paths`](crate::passes::merge_paths) returns it. Not a change of the rendering, so
+///   [`is_empty`](ConversionReport::is_empty) does not count it.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{options::RsxOptions, parse_svg_to_rsx_with_report, report::Warning};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <circle r='1'/><path d='M 0 0 L 10 10' stroke-dasharray='2'/></svg>";
+/// let (element, report) = parse_svg_to_rsx_with_report(svg_str, &RsxOptions::default()).unwrap();
+/// assert!(matches!(&report.warnings[0], Warning::UnknownElement { name, .. } if name == "circle"));
+/// assert_eq!(report.warnings[1].to_string(), "svg > path[0]: unsupported attribute `stroke-dasharray` dropped");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub warnings: Vec<Warning>,
+
+    pub merged_paths: usize,
+}
+
+impl ConversionReport {
+    /// Whether the document was converted without any change.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Emits every warning as a `tracing` `warn!` event, with the `tracing` feature.
+    pub(crate) fn trace(&self) {
+        #[cfg(feature = "tracing")]
+        for warning in &self.warnings {
+            tracing::warn!(target: "typst_2_rsx", "{}", warning);
+        }
+    }
+}
+
+/// A change made to a document so that it could be converted.
+///
+/// The `path` of an element designates it from the root like in
+/// [`Error::SvgStructure`](crate::error::Error::SvgStructure), each step giving the tag and the
+/// index among the siblings with the same tag (`svg > g[3] > circle[0]`).
+///
+/// # Variant
+///
+/// - `UnknownElement` : an SVG element that the parsed tree cannot hold, such as a `circle` or
+///   a `text`, was left out along with its content.
+/// - `SkippedForeignContent` : an element from another vocabulary than SVG, such as the metadata
+///   of an editor or a `foreignObject`, was left out along with its content.
+/// - `DroppedAttribute` : an attribute that the parsed tree has no field for was dropped.
+/// - `MissingAttribute` : an element lacks the attribute it needs to show anything, such as a
+///   `path` without `d`. It is removed, except for the path of a symbol, which is only reported.
+/// - `UnsafeReference` : a `use` referring to another document, an `image` showing anything but
+///   an embedded image of an allowed type, or an attribute with a `url(…)` outside the document,
+///   such as a `fill` or a `filter`, was removed by
+///   [`sanitize_references`](crate::passes::sanitize_references).
+/// - `UnmappedColor` : a color has no replacement in [`RsxOptions::colors`](crate::options::RsxOptions)
+///   and was kept. Reported once per color.
+/// - `BrokenReference` : a local reference, `href="#id"` or `url(#id)`, matches no element of the
+///   converted document, which shows nothing in its place. Checked in debug builds only; see
+///   [`Svg::validate_references`].
+/// - `DuplicateId` : an element carries the `id` of an element before it. It was removed, or
+///   changed to `renamed`, by [`resolve_duplicate_ids`](crate::passes::resolve_duplicate_ids).
+/// - `SkippedSwitchBranch` : a child of a `switch`, named `name`, is not rendered, another one or
+///   none being chosen as [`RsxOptions::switch`](crate::options::RsxOptions) says.
+///
+/// New variants may be added in minor releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    UnknownElement {
+        name: String,
+        path: String,
+    },
+
+    SkippedForeignContent {
+        name: String,
+        path: String,
+    },
+
+    DroppedAttribute {
+        name: String,
+        path: String,
+    },
+
+    MissingAttribute {
+        attribute: &'static str,
+        path: String,
+    },
+
+    UnsafeReference {
+        href: String,
+        path: String,
+    },
+
+    UnmappedColor {
+        color: String,
+    },
+
+    DuplicateId {
+        id: String,
+        path: String,
+        renamed: Option<String>,
+    },
+
+    BrokenReference {
+        target: String,
+        path: String,
+    },
+
+    SkippedSwitchBranch {
+        name: String,
+        path: String,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UnknownElement { name, path } => {
+                write!(f, "{}: unknown element `{}` skipped", path, name)
+            }
+            Warning::SkippedForeignContent { name, path } => {
+                write!(f, "{}: foreign element `{}` skipped", path, name)
+            }
+            Warning::DroppedAttribute { name, path } => {
+                write!(f, "{}: unsupported attribute `{}` dropped", path, name)
+            }
+            Warning::MissingAttribute { attribute, path } => {
+                write!(f, "{}: no `{}` attribute, nothing to show", path, attribute)
+            }
+            Warning::UnsafeReference { href, path } => {
+                write!(f, "{}: unsafe reference {:?} removed", path, href)
+            }
+            Warning::UnmappedColor { color } => {
+                write!(f, "color {:?} has no replacement in the color map", color)
+            }
+            Warning::BrokenReference { target, path } => {
+                write!(f, "{}: reference to `#{}` matches no element", path, target)
+            }
+            Warning::SkippedSwitchBranch { name, path } => {
+                write!(f, "{}: alternative `{}` not rendered", path, name)
+            }
+            Warning::DuplicateId {
+                id,
+                path,
+                renamed: Some(renamed),
+            } => write!(
+                f,
+                "{}: duplicate id `{}` renamed to `{}`",
+                path, id, renamed
+            ),
+            Warning::DuplicateId {
+                id,
+                path,
+                renamed: None,
+            } => write!(
+                f,
+                "{}: duplicate id `{}` removed, the first element keeps it",
+                path, id
+            ),
+        }
+    }
+}
+
+/// The attributes that the parsed tree keeps for an element with `tag`.
+fn known_attributes(tag: &str) -> &'static [&'static str] {
+    match tag {
+        "svg" => &["class", "viewBox", "width", "height"],
+        "g" => &[
+            "id",
+            "class",
+            "transform",
+            "clip-path",
+            "mask",
+            "filter",
+            "tabindex",
+            "pointer-events",
+            "shape-rendering",
+            "text-rendering",
+        ],
+        "path" => &[
+            "d",
+            "id",
+            "class",
+            "fill",
+            "stroke",
+            "fill-rule",
+            "stroke-width",
+            "stroke-linecap",
+            "stroke-linejoin",
+            "stroke-miterlimit",
+            "tabindex",
+            "pointer-events",
+            "opacity",
+            "shape-rendering",
+        ],
+        "use" => &[
+            "fill",
+            "x",
+            "width",
+            "height",
+            "fill-rule",
+            "href",
+            "transform",
+        ],
+        "image" => &[
+            "width",
+            "height",
+            "preserveAspectRatio",
+            "href",
+            "transform",
+        ],
+        "text" => &[
+            "id",
+            "class",
+            "x",
+            "y",
+            "dx",
+            "dy",
+            "fill",
+            "font-family",
+            "font-size",
+            "font-weight",
+            "font-style",
+            "letter-spacing",
+            "word-spacing",
+            "dominant-baseline",
+            "text-anchor",
+            "text-rendering",
+            "transform",
+        ],
+        "tspan" => &[
+            "x",
+            "y",
+            "dx",
+            "dy",
+            "fill",
+            "font-family",
+            "font-size",
+            "font-weight",
+            "font-style",
+            "letter-spacing",
+            "word-spacing",
+            "dominant-baseline",
+        ],
+        "rect" => &["x", "y", "width", "height", "fill"],
+        "defs" => &["id"],
+        "symbol" => &["id", "overflow", "viewBox", "preserveAspectRatio"],
+        "switch" => &["id", "transform"],
+        "linearGradient" => &[
+            "id",
+            "x1",
+            "y1",
+            "x2",
+            "y2",
+            "gradientUnits",
+            "gradientTransform",
+            "spreadMethod",
+            "href",
+        ],
+        "radialGradient" => &[
+            "id",
+            "cx",
+            "cy",
+            "r",
+            "fx",
+            "fy",
+            "gradientUnits",
+            "gradientTransform",
+            "spreadMethod",
+            "href",
+        ],
+        "stop" => &["offset", "stop-color", "stop-opacity", "style"],
+        "clipPath" => &["id", "clipPathUnits", "transform"],
+        "mask" => &[
+            "id",
+            "x",
+            "y",
+            "width",
+            "height",
+            "maskUnits",
+            "maskContentUnits",
+        ],
+        "filter" => &["id", "x", "y", "width", "height", "filterUnits"],
+        "feGaussianBlur" => &["in", "stdDeviation", "result"],
+        "feOffset" => &["in", "dx", "dy", "result"],
+        "feColorMatrix" => &["in", "type", "values", "result"],
+        "feDropShadow" => &[
+            "in",
+            "dx",
+            "dy",
+            "stdDeviation",
+            "flood-color",
+            "flood-opacity",
+            "result",
+        ],
+        _ => &[],
+    }
+}
+
+/// The conditional processing attributes, read by the `switch` holding an element and not kept.
+const CONDITIONS: [&str; 3] = ["requiredFeatures", "requiredExtensions", "systemLanguage"];
+
+/// `name` as written in the document, with its prefix (`sodipodi:namedview`).
+fn qualified(name: &OwnedName) -> String {
+    match &name.prefix {
+        Some(prefix) => format!("{}:{}", prefix, name.local_name),
+        None => name.local_name.clone(),
+    }
+}
+
+/// Whether `name` is an element of another vocabulary than SVG, or holds one.
+fn is_foreign(name: &OwnedName) -> bool {
+    name.namespace
+        .as_deref()
+        .is_some_and(|namespace| namespace != SVG_NAMESPACE)
+        || matches!(name.local_name.as_str(), "metadata" | "foreignObject")
+}
+
+/// An open element while the document is scanned.
+struct Frame {
+    tag: String,
+    path: String,
+    /// Number of children seen so far, per tag, for the indices in the paths
+    children: HashMap<String, usize>,
+}
+
+impl From<BrokenRef> for Warning {
+    fn from(broken: BrokenRef) -> Self {
+        Warning::BrokenReference {
+            target: broken.target_id,
+            path: broken.from_path,
+        }
+    }
+}
+
+/// Reads `svg_str`, reporting the elements and attributes that the parsed tree cannot hold.
+///
+/// Returns the document without the skipped elements when there are any, or `None` when it can
+/// be parsed as is. A document that is not well-formed is returned unchanged, without warnings,
+/// for the parser to report the error.
+pub(crate) fn scan(svg_str: &str) -> (Option<String>, Vec<Warning>) {
+    let mut reader = ParserConfig::new()
+        .trim_whitespace(false)
+        .ignore_comments(true)
+        .create_reader(svg_str.as_bytes());
+    let mut events = Vec::new();
+    let mut warnings = Vec::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    // Depth of the skipped element being read, inside which everything is skipped
+    let mut skipping: Option<usize> = None;
+    // Depth of the alternative of a switch kept by its name only, inside which nothing is checked
+    let mut opaque: Option<usize> = None;
+    let mut skipped_any = false;
+    loop {
+        let event = match reader.next() {
+            Ok(XmlEvent::EndDocument) => break,
+            Ok(event) => event,
+            Err(_) => return (None, Vec::new()),
+        };
+        match &event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let tag = name.local_name.clone();
+                let path = match stack.last_mut() {
+                    Some(parent) => {
+                        let index = parent.children.entry(tag.clone()).or_default();
+                        *index += 1;
+                        format!("{} > {}[{}]", parent.path, tag, *index - 1)
+                    }
+                    None => tag.clone(),
+                };
+                let parent = stack.last().map(|frame| frame.tag.as_str());
+                let in_switch = parent == Some("switch");
+                if skipping.is_none() && opaque.is_none() && parent.is_some() {
+                    if in_switch && !allowed_children(parent).contains(&tag.as_str()) {
+                        // Reported by `skipped_switch_branches` unless it is rendered
+                        opaque = Some(stack.len());
+                    } else if is_foreign(name) {
+                        warnings.push(Warning::SkippedForeignContent {
+                            name: qualified(name),
+                            path: path.clone(),
+                        });
+                        skipping = Some(stack.len());
+                    } else if !allowed_children(parent).contains(&tag.as_str()) {
+                        warnings.push(Warning::UnknownElement {
+                            name: tag.clone(),
+                            path: path.clone(),
+                        });
+                        skipping = Some(stack.len());
+                    }
+                }
+                if skipping.is_none() && opaque.is_none() {
+                    let known = known_attributes(&tag);
+                    for attribute in attributes {
+                        let name = attribute.name.local_name.as_str();
+                        let condition = in_switch && CONDITIONS.contains(&name);
+                        if !known.contains(&name) && !condition {
+                            warnings.push(Warning::DroppedAttribute {
+                                name: qualified(&attribute.name),
+                                path: path.clone(),
+                            });
+                        }
+                    }
+                }
+                stack.push(Frame {
+                    tag,
+                    path,
+                    children: HashMap::new(),
+                });
+            }
+            XmlEvent::EndElement { .. } => {
+                stack.pop();
+                if opaque == Some(stack.len()) {
+                    opaque = None;
+                }
+                if skipping == Some(stack.len()) {
+                    skipping = None;
+                    skipped_any = true;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        if skipping.is_none() && !matches!(event, XmlEvent::StartDocument { .. }) {
+            events.push(event);
+        }
+    }
+    if !skipped_any {
+        return (None, warnings);
+    }
+
+    let mut writer = EventWriter::new_with_config(
+        Vec::new(),
+        EmitterConfig::new().write_document_declaration(false),
+    );
+    for event in &events {
+        if let Some(event) = event.as_writer_event() {
+            if writer.write(event).is_err() {
+                return (None, warnings);
+            }
+        }
+    }
+    (String::from_utf8(writer.into_inner()).ok(), warnings)
+}
+
+/// The alternatives of the `switch` elements of `svg` that are not rendered with `mode`, in
+/// document order.
+///
+/// An alternative that the tree cannot hold is reported even when it is the one chosen, since it
+/// renders nothing either.
+pub(crate) fn skipped_switch_branches(svg: &Svg, mode: SwitchMode) -> Vec<Warning> {
+    fn g(g: &G, path: String, mode: SwitchMode, warnings: &mut Vec<Warning>) {
+        let mut siblings = Siblings::new(path);
+        for element in g.elements.iter().flatten() {
+            g_ele(element, siblings.path(element.tag()), mode, warnings);
+        }
+    }
+
+    fn g_ele(element: &GEle, path: String, mode: SwitchMode, warnings: &mut Vec<Warning>) {
+        match element {
+            GEle::G(inner) => g(inner, path, mode, warnings),
+            GEle::Switch(switch) => {
+                let rendered = switch.rendered_branch(mode);
+                // Counted here, the names of the unsupported alternatives not being static
+                let mut counts = HashMap::<&str, usize>::new();
+                for (index, branch) in switch.branches.iter().enumerate() {
+                    let count = counts.entry(branch.name()).or_default();
+                    let branch_path = format!("{} > {}[{}]", path, branch.name(), count);
+                    *count += 1;
+                    match branch {
+                        SwitchBranch::Element(element) if rendered == Some(index) => {
+                            g_ele(element, branch_path, mode, warnings)
+                        }
+                        _ => warnings.push(Warning::SkippedSwitchBranch {
+                            name: branch.name().to_string(),
+                            path: branch_path,
+                        }),
+                    }
+                }
+            }
+            GEle::Use(_) | GEle::Path(_) | GEle::Image(_) | GEle::Text(_) => {}
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let mut siblings = Siblings::new("svg");
+    for element in &svg.elements {
+        match element {
+            SvgElement::G(inner) => g(inner, siblings.path("g"), mode, &mut warnings),
+            SvgElement::Path(_) => {
+                siblings.path("path");
+            }
+            SvgElement::Defs(_) => {
+                siblings.path("defs");
+            }
+            SvgElement::Rect(_) => {
+                siblings.path("rect");
+            }
+        }
+    }
+    warnings
+}
+
+/// The colors of `svg` that `colors` has no replacement for, once each and sorted.
+pub(crate) fn unmapped_colors(svg: &Svg, colors: &ColorMap) -> Vec<Warning> {
+    struct Paints<'a>(BTreeSet<&'a str>);
+
+    impl<'a> Visitor<'a> for Paints<'a> {
+        fn visit_path(&mut self, path: &'a Path) {
+            self.0.extend(path.fill.as_deref());
+            self.0.extend(path.stroke.as_deref());
+        }
+
+        fn visit_use(&mut self, uuse: &'a Use) {
+            self.0.extend(uuse.fill.as_deref());
+        }
+
+        fn visit_rect(&mut self, rect: &'a Rect) {
+            self.0.extend(rect.fill.as_deref());
+        }
+
+        fn visit_text(&mut self, text: &'a Text) {
+            self.0.extend(text.fill.as_deref());
+            self.0
+                .extend(text.content.iter().filter_map(|content| match content {
+                    TextContent::Tspan(tspan) => tspan.fill.as_deref(),
+                    TextContent::Characters(_) => None,
+                }));
+        }
+
+        fn visit_definition(&mut self, definition: &'a Definition) {
+            let floods = definition
+                .primitives()
+                .iter()
+                .filter_map(|primitive| match primitive {
+                    FilterPrimitive::DropShadow(shadow) => shadow.flood_color.as_deref(),
+                    _ => None,
+                });
+            self.0.extend(floods);
+        }
+
+        fn visit_stop(&mut self, stop: &'a Stop) {
+            self.0.extend(stop.stop_color.as_deref());
+        }
+    }
+
+    let mut paints = Paints(BTreeSet::new());
+    svg.walk(&mut paints);
+    paints
+        .0
+        .into_iter()
+        .filter(|paint| {
+            // `none`, `currentColor` and the references to gradients are not replaced either
+            let is_color = !matches!(
+                paint.parse::<color::Color>(),
+                Ok(color::Color::None | color::Color::CurrentColor) | Err(_)
+            );
+            is_color && colors.get(paint).is_none()
+        })
+        .map(|color| Warning::UnmappedColor {
+            color: color.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn scan_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt"
+                xmlns="http://www.w3.org/2000/svg" xmlns:sodipodi="http://sodipodi.sourceforge.net">
+            <sodipodi:namedview pagecolor="#ffffff"/>
+            <metadata><rdf>...</rdf></metadata>
+            <g xml:space="preserve">
+                <text x="0" lengthAdjust="spacing">Hello<tspan>!</tspan></text>
+                <a href="https://example.com">link</a>
+                <path d="M 0 0 L 1 1" stroke-dasharray="2"/>
+            </g>
+            <circle r="1"/>
+        </svg>"##;
+        let (rewritten, warnings) = scan(svg_str);
+        let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "svg > namedview[0]: foreign element `sodipodi:namedview` skipped",
+                "svg > metadata[0]: foreign element `metadata` skipped",
+                "svg > g[0]: unsupported attribute `xml:space` dropped",
+                "svg > g[0] > text[0]: unsupported attribute `lengthAdjust` dropped",
+                "svg > g[0] > a[0]: unknown element `a` skipped",
+                "svg > g[0] > path[0]: unsupported attribute `stroke-dasharray` dropped",
+                "svg > circle[0]: unknown element `circle` skipped",
+            ]
+        );
+        let svg = parse_svg(&rewritten.unwrap()).unwrap();
+        assert_eq!((svg.stats().paths, svg.stats().texts), (1, 1));
+        assert_eq!(svg.elements.len(), 1);
+
+        // Documents holding only what the tree supports are parsed as they are
+        let svg_str = std::fs::read_to_string("./test/expected.svg").unwrap();
+        assert_eq!(scan(&svg_str), (None, vec![]));
+        assert_eq!(scan("<svg"), (None, vec![]));
+    }
+
+    #[test]
+    fn unmapped_colors_test() {
+        let svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <path d="M 0 0" fill="#000" stroke="#ff0000"/>
+                <path d="M 0 0" fill="none" stroke="url(#gradient)"/>
+                <rect x="0" y="0" width="1" height="1" fill="#ff0000"/>
+            </svg>"##,
+        )
+        .unwrap();
+        let colors = ColorMap::from([("#000000", "var(--ink)")]);
+        assert_eq!(
+            unmapped_colors(&svg, &colors),
+            [Warning::UnmappedColor {
+                color: "#ff0000".to_string()
+            }]
+        );
+    }
+}
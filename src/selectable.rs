@@ -0,0 +1,334 @@
+//! Selectable text laid over the outlined glyphs of a Typst document.
+//!
+//! Typst draws every glyph as a path, which cannot be selected, copied or searched, and the CLI
+//! has no export keeping the text. [`query_words`] asks `typst query` where the words of a document
+//! are laid out, and [`overlay_words`] lays them over the glyphs as transparent `text` elements,
+//! one per line. Compiling with [`TextMode::Selectable`](crate::options::TextMode::Selectable)
+//! does both.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    error::Error, invoker::TypstInvoker, labels::query_wrapper, options::CompileOptions,
+    parse_svg_with_limits, utils::read_file,
+};
+use crate::{labels::is_page, svg_types::number::format_number, svg_types::*};
+
+/// The class of the groups holding the words of each page.
+pub const TEXT_LAYER_CLASS: &str = "typst-text-layer";
+
+/// How far apart vertically, in `pt`, two words may start and still be on the same line.
+const LINE_TOLERANCE: f64 = 0.5;
+
+/// The label of the metadata holding the words and their positions in the wrapper document.
+#[cfg(not(target_arch = "wasm32"))]
+const METADATA_LABEL: &str = "typst-2-rsx-text";
+
+/// Records every word with its size and font, labeled `<typst-2-rsx-word>`, ahead of the included
+/// document.
+#[cfg(not(target_arch = "wasm32"))]
+const SHOW_WORDS: &str = r#"#show regex("\\S+"): it => context [#metadata((
+  text: it.text,
+  size: text.size.pt(),
+  font: (text.font,).flatten().map(font => if type(font) == dictionary { font.name } else { font }).first(),
+)) <typst-2-rsx-word>#it]
+"#;
+
+/// Gathers the recorded words with their positions, after the included document.
+#[cfg(not(target_arch = "wasm32"))]
+const QUERY_WORDS: &str = r#"#context [#metadata(query(<typst-2-rsx-word>).map(it => {
+  let position = it.location().position()
+  it.value + (page: position.page, x: position.x.pt(), y: position.y.pt())
+})) <typst-2-rsx-text>]
+"#;
+
+/// A word of a Typst document and where it is laid out, as reported by `typst query`.
+///
+/// # Field
+///
+/// - `text` : the characters of the word, without whitespace.
+/// - `page` : the page of the word, starting from 1.
+/// - `x`, `y` : the start of the word on its baseline, on its page, in `pt`.
+/// - `size` : the font size, in `pt`.
+/// - `font` : the first font family of the word, such as `"libertinus serif"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordPosition {
+    pub text: String,
+
+    pub page: usize,
+
+    pub x: f64,
+
+    pub y: f64,
+
+    pub size: f64,
+
+    pub font: Option<String>,
+}
+
+/// Queries the words of a Typst document and their positions, running `typst query` with
+/// `invoker`.
+///
+/// The query runs on a wrapper document including the input, with a show rule recording every
+/// run of non-whitespace characters, like [`query_labels`](crate::labels::query_labels) does for
+/// the labels. The words are found by a regular expression, so a word split by a style change,
+/// as in `*bold*face`, is two words.
+///
+/// # Return value
+///
+/// - `Ok(Vec<WordPosition>)` : the words, in the order of the document.
+/// - `Err(Error)` : the input is not in the project root, the wrapper cannot be written, the query
+///   fails ([`Error::TypstFailed`]), or its output is not the expected JSON ([`Error::JsonError`]).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{invoker::TypstCli, options::CompileOptions, selectable::query_words};
+///
+/// let words = query_words(&TypstCli, "report.typ", &CompileOptions::default()).unwrap();
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn query_words(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: impl AsRef<Path>,
+    options: &CompileOptions,
+) -> Result<Vec<WordPosition>, Error> {
+    query_wrapper(
+        invoker,
+        input_typ_file.as_ref(),
+        options,
+        wrapper_source,
+        METADATA_LABEL,
+    )
+}
+
+/// The document including the file at `path`, from the project root, and appending its words.
+#[cfg(not(target_arch = "wasm32"))]
+fn wrapper_source(path: &str) -> String {
+    let path = path.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("{}#include \"{}\"\n{}", SHOW_WORDS, path, QUERY_WORDS)
+}
+
+/// Lays `words` over the glyphs of `svg` as transparent `text` elements, one per line.
+///
+/// The lines of a page are added at the end of its `typst-page` group, in a group of the
+/// [`TEXT_LAYER_CLASS`], so that they are above the glyphs and selected instead of them; in the
+/// whole document when it has no page groups, for the words of the first page. Each word is a
+/// `tspan` starting at its position, followed by a space unless it ends its line, so that the
+/// copied text keeps its words apart. The words of the pages that the document does not have are
+/// left out.
+///
+/// The `text` elements are in the fonts of the words, but the browser lays them out itself: a
+/// word may end a little before or after its glyphs, and a missing font makes the difference
+/// larger. The selection follows the words, not the exact glyphs.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, selectable::{overlay_words, WordPosition}};
+///
+/// let mut svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 100 100' width='100pt' height='100pt'>\
+///      <g class='typst-page' transform='translate(0 0)'><path d='M 0 0 L 100 100'/></g></svg>",
+/// )
+/// .unwrap();
+/// let word = |text: &str, x| WordPosition {
+///     text: text.to_string(), page: 1, x, y: 20.0, size: 11.0, font: None,
+/// };
+/// overlay_words(&mut svg, &[word("Hello", 10.0), word("world", 40.0)]);
+/// assert_eq!(svg.stats().texts, 1);
+/// ```
+pub fn overlay_words(svg: &mut Svg, words: &[WordPosition]) {
+    let mut lines: Vec<Vec<&WordPosition>> = Vec::new();
+    for word in words {
+        match lines.last_mut() {
+            Some(line) if same_line(line[line.len() - 1], word) => line.push(word),
+            _ => lines.push(vec![word]),
+        }
+    }
+
+    let pages: Vec<usize> = svg
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(index, element)| match element {
+            SvgElement::G(g) if is_page(g) => Some(index),
+            _ => None,
+        })
+        .collect();
+    let page_count = pages.len().max(1);
+    let mut layers: Vec<Vec<GEle>> = vec![Vec::new(); page_count];
+    for line in lines {
+        if let Some(layer) = line[0]
+            .page
+            .checked_sub(1)
+            .and_then(|page| layers.get_mut(page))
+        {
+            layer.push(GEle::Text(Box::new(line_text(&line))));
+        }
+    }
+
+    for (page, layer) in layers.into_iter().enumerate().rev() {
+        if layer.is_empty() {
+            continue;
+        }
+        let layer = G {
+            class: Some(TEXT_LAYER_CLASS.into()),
+            elements: Some(layer),
+            ..Default::default()
+        };
+        match pages.get(page).map(|&index| &mut svg.elements[index]) {
+            Some(SvgElement::G(g)) => g
+                .elements
+                .get_or_insert_with(Vec::new)
+                .push(GEle::G(Box::new(layer))),
+            _ => svg.elements.push(SvgElement::G(Box::new(layer))),
+        }
+    }
+}
+
+/// Whether `word` continues the line ending with `last`: on the same page and baseline, further
+/// right.
+fn same_line(last: &WordPosition, word: &WordPosition) -> bool {
+    word.page == last.page && (word.y - last.y).abs() <= LINE_TOLERANCE && word.x > last.x
+}
+
+/// The `text` element of a line, its font being that of its first word, with one `tspan` per word.
+fn line_text(line: &[&WordPosition]) -> Text {
+    let first = line[0];
+    let size = format_number(first.size);
+    let content = line
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            let mut characters = word.text.clone();
+            if index + 1 < line.len() {
+                characters.push(' ');
+            }
+            let word_size = format_number(word.size);
+            TextContent::Tspan(Box::new(Tspan {
+                x: Some(format_number(word.x)),
+                font_family: word
+                    .font
+                    .clone()
+                    .filter(|font| first.font.as_ref() != Some(font)),
+                font_size: (word_size != size).then_some(word_size),
+                characters,
+                ..Default::default()
+            }))
+        })
+        .collect();
+    Text {
+        y: Some(format_number(first.y)),
+        fill: Some("transparent".into()),
+        font_family: first.font.clone(),
+        font_size: Some(size),
+        content,
+        ..Default::default()
+    }
+}
+
+/// Queries the words of `input_typ_file` and lays them over the SVG compiled from it at
+/// `output_path`, rewriting the file, and returns its new size.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn overlay_file(
+    invoker: &dyn TypstInvoker,
+    input_typ_file: &Path,
+    output_path: &Path,
+    options: &CompileOptions,
+) -> Result<u64, Error> {
+    let words = query_words(invoker, input_typ_file, options)?;
+    let mut svg = parse_svg_with_limits(&read_file(output_path)?, &options.limits)?;
+    overlay_words(&mut svg, &words);
+    let svg_str = svg.to_svg_string();
+    fs::write(output_path, &svg_str).map_err(|e| Error::io(output_path, e))?;
+    Ok(svg_str.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    fn word(text: &str, page: usize, x: f64, y: f64) -> WordPosition {
+        WordPosition {
+            text: text.to_string(),
+            page,
+            x,
+            y,
+            size: 11.0,
+            font: Some("libertinus serif".to_string()),
+        }
+    }
+
+    #[test]
+    fn overlay_words_test() {
+        let mut svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 100 200" width="100pt" height="200pt">
+                <g class="typst-page" transform="translate(0 0)"><path d="M 0 0 L 100 100"/></g>
+                <g class="typst-page" transform="translate(0 100)"/>
+            </svg>"##,
+        )
+        .unwrap();
+        let mut bold = word("world", 1, 40.0, 20.2);
+        bold.size = 12.0;
+        overlay_words(
+            &mut svg,
+            &[
+                word("Hello", 1, 10.0, 20.0),
+                bold,
+                word("again", 1, 10.0, 35.0),
+                word("Next", 2, 10.0, 20.0),
+                word("Missing", 3, 10.0, 20.0),
+            ],
+        );
+        assert_eq!(svg.stats().texts, 3);
+        let svg_str = svg.to_svg_string();
+        assert!(svg_str.contains(
+            "<text y=\"20\" fill=\"transparent\" font-family=\"libertinus serif\" \
+             font-size=\"11\"><tspan x=\"10\">Hello </tspan>\
+             <tspan x=\"40\" font-size=\"12\">world</tspan></text>"
+        ));
+        assert!(svg_str.contains("<tspan x=\"10\">again</tspan>"));
+        // Each page has its layer, at its end
+        for (index, text) in [(0, "Hello "), (1, "Next")] {
+            let SvgElement::G(page) = &svg.elements[index] else {
+                unreachable!()
+            };
+            let Some(GEle::G(layer)) = page.elements.as_ref().and_then(|e| e.last()) else {
+                unreachable!()
+            };
+            assert_eq!(layer.class.as_deref(), Some(TEXT_LAYER_CLASS));
+            let Some(GEle::Text(line)) = layer.elements.as_ref().and_then(|e| e.first()) else {
+                unreachable!()
+            };
+            let TextContent::Tspan(tspan) = &line.content[0] else {
+                unreachable!()
+            };
+            assert_eq!(tspan.characters, text);
+        }
+
+        // Without page groups, the first page is the document
+        let mut svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+             <path d='M 0 0 L 10 10'/></svg>",
+        )
+        .unwrap();
+        overlay_words(&mut svg, &[word("x", 1, 0.0, 5.0), word("y", 2, 0.0, 5.0)]);
+        assert_eq!(svg.stats().texts, 1);
+        assert!(matches!(&svg.elements[1], SvgElement::G(layer) if layer.class.is_some()));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn wrapper_source_test() {
+        let source = wrapper_source("/a \"quoted\" name.typ");
+        assert!(source.starts_with("#show regex(\"\\\\S+\"): it => context"));
+        assert!(source.contains("\n#include \"/a \\\"quoted\\\" name.typ\"\n"));
+        assert!(source.ends_with(") <typst-2-rsx-text>]\n"));
+    }
+}
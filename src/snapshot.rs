@@ -0,0 +1,229 @@
+//! Golden-file snapshots of converted documents, for the tests of this crate and of the crates
+//! using it (with the `test-utils` feature).
+//!
+//! An element is compared through the HTML `dioxus-ssr` renders for it, one tag per line, rather
+//! than through its `Debug` output, which follows the internals of Dioxus and changes between
+//! its versions:
+//!
+//! ```rust,no_run
+//! use typst_2_rsx::{assert_rsx_snapshot, parse_svg_to_rsx};
+//!
+//! let element = parse_svg_to_rsx(&std::fs::read_to_string("formula.svg").unwrap()).unwrap();
+//! // Compares against `tests/snapshots/formula.html`
+//! assert_rsx_snapshot!(element, "formula");
+//! ```
+//!
+//! On a mismatch, the new rendering is written next to the snapshot as `<name>.html.new`, to be
+//! compared with a diff tool. Run the tests with [`UPDATE_VAR`] set (`UPDATE_SNAPSHOTS=1 cargo
+//! test`) to write the snapshots instead, after checking that the changes are the expected ones.
+
+use std::{fs, path::Path};
+
+use dioxus::prelude::Element;
+
+/// The environment variable which, when set to anything else than `0`, makes the snapshot
+/// assertions write the snapshots instead of comparing against them.
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Asserts that an element renders like the snapshot called `name`.
+///
+/// # parameter
+///
+/// - `element` : the `Element` to compare.
+/// - `name` : the name of the snapshot, stored in `<name>.html`.
+/// - `dir` (optional) : the directory of the snapshots, by default `tests/snapshots` in the
+///   directory of the crate running the test.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use typst_2_rsx::{assert_rsx_snapshot, parse_svg_to_rsx};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <path d='M 0 0 L 10 10'/></svg>";
+/// assert_rsx_snapshot!(parse_svg_to_rsx(svg_str).unwrap(), "line");
+/// assert_rsx_snapshot!(parse_svg_to_rsx(svg_str).unwrap(), "line", "tests/golden");
+/// ```
+#[macro_export]
+macro_rules! assert_rsx_snapshot {
+    ($element:expr, $name:expr $(,)?) => {
+        $crate::assert_rsx_snapshot!(
+            $element,
+            $name,
+            ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots")
+        )
+    };
+    ($element:expr, $name:expr, $dir:expr $(,)?) => {
+        $crate::snapshot::assert_snapshot(&$element, $name, $dir)
+    };
+}
+
+/// Renders an element to HTML with one tag per line, indented by depth, so that the snapshots
+/// diff line by line.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg_to_rsx, snapshot::render};
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///                <g><path d='M 0 0 L 10 10'/></g></svg>";
+/// let html = render(&parse_svg_to_rsx(svg_str).unwrap());
+/// assert!(html.ends_with("  <g>\n    <path d=\"M 0 0 L 10 10\"></path>\n  </g>\n</svg>\n"));
+/// ```
+pub fn render(element: &Element) -> String {
+    indent(&crate::compat::render_html(element.clone()))
+}
+
+/// Compares the rendering of `element` with the snapshot `<dir>/<name>.html`, panicking on a
+/// mismatch, or writes the snapshot when [`UPDATE_VAR`] is set. See [`assert_rsx_snapshot!`].
+#[track_caller]
+pub fn assert_snapshot(element: &Element, name: &str, dir: impl AsRef<Path>) {
+    let update = std::env::var(UPDATE_VAR).is_ok_and(|value| value != "0");
+    if let Err(message) = check(&render(element), name, dir.as_ref(), update) {
+        panic!("{}", message);
+    }
+}
+
+/// Compares `rendered` with the snapshot called `name` in `dir`, writing `<name>.html.new` on a
+/// mismatch, or the snapshot itself if `update` is true.
+fn check(rendered: &str, name: &str, dir: &Path, update: bool) -> Result<(), String> {
+    let path = dir.join(format!("{}.html", name));
+    let new_path = dir.join(format!("{}.html.new", name));
+    let write = |path: &Path| {
+        fs::create_dir_all(dir)
+            .and_then(|()| fs::write(path, rendered))
+            .map_err(|e| format!("cannot write the snapshot {}: {}", path.display(), e))
+    };
+    if update {
+        write(&path)?;
+        let _ = fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    let Ok(expected) = fs::read_to_string(&path) else {
+        write(&new_path)?;
+        return Err(format!(
+            "no snapshot {}: the rendering was written to {}; rename it, or run the tests with \
+             {}=1, to accept it",
+            path.display(),
+            new_path.display(),
+            UPDATE_VAR
+        ));
+    };
+    // Git may check the snapshots out with CRLF line endings on Windows
+    let expected = expected.replace("\r\n", "\n");
+    if expected == rendered {
+        let _ = fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    write(&new_path)?;
+    let (line, (expected_line, rendered_line)) = expected
+        .lines()
+        .chain(std::iter::repeat(""))
+        .zip(rendered.lines().chain(std::iter::repeat("")))
+        .enumerate()
+        .find(|(_, (expected, rendered))| expected != rendered)
+        .unwrap_or_default();
+    Err(format!(
+        "the rendering differs from the snapshot {} at line {}:\n  expected: {}\n  rendered: {}\n\
+         The rendering was written to {}; run the tests with {}=1 to accept it",
+        path.display(),
+        line + 1,
+        expected_line,
+        rendered_line,
+        new_path.display(),
+        UPDATE_VAR
+    ))
+}
+
+/// Puts every tag of `html` on its own line, indented by two spaces per level, except for the
+/// closing tag of an element without children, which stays on the line of its opening tag.
+fn indent(html: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0usize;
+    let mut line = |depth: usize, content: &str| {
+        if !content.is_empty() {
+            out.push_str(&"  ".repeat(depth));
+            out.push_str(content);
+            out.push('\n');
+        }
+    };
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        line(depth, rest[..start].trim());
+        let end = start + tag_len(&rest[start..]);
+        let tag = &rest[start..end];
+        rest = &rest[end..];
+        if tag.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            line(depth, tag);
+        } else if tag.ends_with("/>") {
+            line(depth, tag);
+        } else if rest.starts_with("</") {
+            let close = tag_len(rest);
+            line(depth, &format!("{}{}", tag, &rest[..close]));
+            rest = &rest[close..];
+        } else {
+            line(depth, tag);
+            depth += 1;
+        }
+    }
+    line(depth, rest.trim());
+    out
+}
+
+/// The length of the tag at the start of `html`, up to its `>`. `dioxus-ssr` does not escape the
+/// attribute values, so a `>` between quotes does not end the tag.
+fn tag_len(html: &str) -> usize {
+    let mut quoted = false;
+    for (i, byte) in html.bytes().enumerate() {
+        match byte {
+            b'"' => quoted = !quoted,
+            b'>' if !quoted => return i + 1,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indent_test() {
+        assert_eq!(
+            indent(r#"<svg a="1"><g style="x > y"><path d="M 0 0"></path><use/></g>text</svg>"#),
+            "<svg a=\"1\">\n  <g style=\"x > y\">\n    <path d=\"M 0 0\"></path>\n    <use/>\n  \
+             </g>\n  text\n</svg>\n"
+        );
+        assert_eq!(indent("<svg"), "<svg\n");
+    }
+
+    #[test]
+    fn check_test() {
+        let dir = std::env::temp_dir().join(format!("typst-2-rsx-snapshot-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let new_path = dir.join("page.html.new");
+
+        let error = check("<svg>\n</svg>\n", "page", &dir, false).unwrap_err();
+        assert!(error.starts_with("no snapshot "));
+        assert_eq!(fs::read_to_string(&new_path).unwrap(), "<svg>\n</svg>\n");
+
+        check("<svg>\n</svg>\n", "page", &dir, true).unwrap();
+        assert!(!new_path.exists());
+        check("<svg>\n</svg>\n", "page", &dir, false).unwrap();
+
+        let error = check("<svg>\n  <g></g>\n</svg>\n", "page", &dir, false).unwrap_err();
+        assert!(error.contains("at line 2:\n  expected: </svg>\n  rendered:   <g></g>\n"));
+        assert_eq!(
+            fs::read_to_string(&new_path).unwrap(),
+            "<svg>\n  <g></g>\n</svg>\n"
+        );
+        check("<svg>\n</svg>\n", "page", &dir, false).unwrap();
+        assert!(!new_path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
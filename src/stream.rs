@@ -0,0 +1,234 @@
+use std::io::BufRead;
+
+use dioxus::prelude::*;
+use xml::{
+    reader::{ParserConfig, XmlEvent},
+    writer::EmitterConfig,
+    EventWriter,
+};
+
+use crate::{
+    convert::from_svg_element, error::Error, options::RsxOptions, parse_svg, svg_types::*,
+    unknown_entity,
+};
+
+/// Parses an SVG from `reader` and converts it to RSX one top-level element at a time, handing
+/// each one to `sink` as soon as it is complete.
+///
+/// Typst puts every page of a document in its own top-level `g`, so the memory used stays
+/// proportional to the largest page instead of the whole document: an 80 MB thesis never needs
+/// to be held as a `String`, an [`Svg`] tree and an RSX tree at once.
+///
+/// The elements are emitted in document order, converted like [`parse_svg_to_rsx`] does. A `defs`
+/// met before any other element is held back and emitted along with the first one, in the same
+/// fragment, so that the glyphs are in the DOM by the time the first page references them. Typst
+/// writes its `defs` after the pages, where they are emitted on their own.
+///
+/// # parameter
+///
+/// - `reader` : the SVG document, such as a `BufReader` over the file compiled by Typst.
+/// - `sink` : called with each converted element. The caller puts them in an `svg` with the
+///   attributes of the returned root.
+///
+/// # Return value
+///
+/// - `Ok(Svg)` : the root `svg` with its attributes, without elements.
+/// - `Err(Error)` : the document is malformed or an element cannot be parsed. The elements before
+///   it have already been emitted.
+///
+/// # Trade-offs
+///
+/// Compared with [`parse_svg_to_rsx`]:
+///
+/// - The [`passes`](crate::passes) and [`RsxOptions`] need the whole tree and are not available.
+/// - The [`ParseLimits`](crate::limits::ParseLimits) apply to each top-level element on its own.
+/// - The `defs` are kept whole, so a document with a large font still holds all its glyphs.
+/// - The path of an [`Error::SvgStructure`] starts from the element being parsed, as if it were
+///   the only child of the root (`svg > g[0] > use[2]` for the third `use` of any page).
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "dioxus-0-5")]
+/// # extern crate dioxus05 as dioxus;
+/// use dioxus::prelude::*;
+/// use typst_2_rsx::parse_svg_to_rsx_streaming;
+///
+/// let svg_str = "<svg class='typst-doc' viewBox='0 0 100 200' width='100pt' height='200pt'>\
+///                <g class='typst-page'><path d='M 0 0 L 10 10'/></g>\
+///                <g class='typst-page' transform='translate(0, 100)'><path d='M 0 0 L 20 20'/></g>\
+///                </svg>";
+/// let mut pages = Vec::new();
+/// let root = parse_svg_to_rsx_streaming(svg_str.as_bytes(), |page| pages.push(page)).unwrap();
+/// assert_eq!(pages.len(), 2);
+/// let element = rsx! {
+///     svg { view_box: root.view_box, width: root.width, height: root.height, {pages.into_iter()} }
+/// };
+/// ```
+///
+/// [`parse_svg_to_rsx`]: crate::parse_svg_to_rsx
+pub fn parse_svg_to_rsx_streaming(
+    reader: impl BufRead,
+    mut sink: impl FnMut(Element),
+) -> Result<Svg, Error> {
+    let options = RsxOptions::default();
+    let mut events = ParserConfig::new()
+        .trim_whitespace(false)
+        .create_reader(reader);
+
+    let root = loop {
+        match events.next().map_err(syntax_error)? {
+            start @ XmlEvent::StartElement { .. } => break start,
+            XmlEvent::EndDocument => return Err(custom_error("the document has no root element")),
+            _ => {}
+        }
+    };
+    let svg = parse_svg(&write(&root, &[])?)?;
+
+    let mut held_defs = Vec::new();
+    let mut emitted = false;
+    let mut element = Vec::new();
+    let mut depth = 0usize;
+    loop {
+        let event = events.next().map_err(syntax_error)?;
+        match &event {
+            XmlEvent::StartElement { .. } => depth += 1,
+            XmlEvent::EndElement { .. } if depth == 0 => break,
+            XmlEvent::EndElement { .. } => depth -= 1,
+            XmlEvent::Characters(_) | XmlEvent::CData(_) if depth == 0 => {
+                return Err(custom_error("unexpected text in the root element"))
+            }
+            XmlEvent::EndDocument => break,
+            XmlEvent::Whitespace(_) if depth == 0 => continue,
+            XmlEvent::Comment(_) | XmlEvent::ProcessingInstruction { .. } => continue,
+            _ => {}
+        }
+        element.push(event);
+        if depth > 0 {
+            continue;
+        }
+
+        // A top-level element is complete
+        let parsed = parse_svg(&write(&root, &element)?)?.elements.pop();
+        element.clear();
+        match parsed {
+            Some(defs @ SvgElement::Defs(_)) if !emitted => held_defs.push(defs),
+            Some(parsed) => {
+                let parsed = from_svg_element(parsed, &options);
+                if held_defs.is_empty() {
+                    sink(parsed);
+                } else {
+                    let defs = held_defs
+                        .drain(..)
+                        .map(|defs| from_svg_element(defs, &options));
+                    sink(rsx! {
+                        {defs}
+                        {parsed}
+                    });
+                }
+                emitted = true;
+            }
+            None => {}
+        }
+    }
+    if !held_defs.is_empty() {
+        let defs = held_defs
+            .into_iter()
+            .map(|defs| from_svg_element(defs, &options));
+        sink(rsx! {
+            {defs}
+        });
+    }
+    Ok(svg)
+}
+
+/// Writes a document made of the `root` start tag, the `element` events and the end of the root.
+fn write(root: &XmlEvent, element: &[XmlEvent]) -> Result<String, Error> {
+    let error = |source: serde_xml_rs::Error| Error::svg_parse("a top-level element", source);
+    let mut writer = EventWriter::new_with_config(
+        Vec::new(),
+        EmitterConfig::new().write_document_declaration(false),
+    );
+    for event in [root].into_iter().chain(element) {
+        if let Some(event) = event.as_writer_event() {
+            writer.write(event).map_err(|e| error(e.into()))?;
+        }
+    }
+    writer
+        .write(xml::writer::XmlEvent::end_element())
+        .map_err(|e| error(e.into()))?;
+    String::from_utf8(writer.into_inner()).map_err(|e| error(e.into()))
+}
+
+fn syntax_error(source: xml::reader::Error) -> Error {
+    let error = serde_xml_rs::Error::Syntax { source };
+    unknown_entity(&error).unwrap_or_else(|| Error::svg_parse("the document", error))
+}
+
+fn custom_error(field: &str) -> Error {
+    Error::svg_parse(
+        "the document",
+        serde_xml_rs::Error::Custom {
+            field: field.to_string(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, io::BufReader};
+
+    #[test]
+    fn streaming_test() {
+        let file = fs::File::open("./test/expected.svg").unwrap();
+        let mut elements = Vec::new();
+        let root =
+            parse_svg_to_rsx_streaming(BufReader::new(file), |element| elements.push(element))
+                .unwrap();
+
+        let svg = parse_svg(&fs::read_to_string("./test/expected.svg").unwrap()).unwrap();
+        assert_eq!(
+            root,
+            Svg {
+                elements: Vec::new(),
+                ..svg.clone()
+            }
+        );
+        let expected: Vec<Element> = svg
+            .elements
+            .into_iter()
+            .map(|element| from_svg_element(element, &RsxOptions::default()))
+            .collect();
+        assert_eq!(format!("{:?}", elements), format!("{:?}", expected));
+    }
+
+    #[test]
+    fn held_defs_test() {
+        let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt' \
+                       xmlns:xlink='http://www.w3.org/1999/xlink'>\
+                       <defs id='glyph'><symbol id='g0' overflow='visible'><path d='M 0 0'/></symbol></defs>\
+                       <g><use xlink:href='#g0' x='0'/></g>\
+                       <g><use xlink:href='#g0' x='5'/></g>\
+                       </svg>";
+        let mut elements = Vec::new();
+        parse_svg_to_rsx_streaming(svg_str.as_bytes(), |element| elements.push(element)).unwrap();
+        assert_eq!(elements.len(), 2);
+        let first = format!("{:?}", elements[0]);
+        assert!(first.contains("symbol") && first.contains("#g0"));
+        assert!(!format!("{:?}", elements[1]).contains("symbol"));
+    }
+
+    #[test]
+    fn error_test() {
+        let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+                       <g><path d='M 0 0'/></g><g><circle r='1'/></g></svg>";
+        let mut count = 0;
+        let error = parse_svg_to_rsx_streaming(svg_str.as_bytes(), |_| count += 1).unwrap_err();
+        assert_eq!(count, 1);
+        assert!(matches!(error, Error::SvgStructure { .. }));
+
+        let error = parse_svg_to_rsx_streaming("<svg class='a'><g>".as_bytes(), |_| {});
+        assert!(error.is_err());
+    }
+}
@@ -1,5 +1,47 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
+use crate::options::SwitchMode;
+
+mod attributes;
+mod bbox;
+mod builders;
+pub mod color;
+mod diff;
+pub(crate) mod intern;
+mod json;
+mod legacy;
+mod length;
+pub(crate) mod lexer;
+mod mapper;
+pub(crate) mod number;
+mod overlay;
+pub mod path_data;
+mod query;
+mod references;
+mod stats;
+pub mod transform;
+mod view_box;
+mod visit;
+mod writer;
+
+pub use attributes::{ColorMatrixType, FillRule, LineCap, LineJoin, SpreadMethod, Units};
+pub use bbox::BBox;
+pub use diff::{svg_diff, SvgDiff};
+pub use json::{svg_from_json, svg_to_json};
+#[allow(deprecated)]
+pub use legacy::{Class, Fill, PathEle, D};
+pub use length::{Length, LengthUnit, PX_PER_INCH};
+pub use mapper::{Align, CoordinateMapper, PreserveAspectRatio};
+pub use overlay::{OverlayGeometry, OverlayShape, OVERLAY_CLASS};
+pub use query::SvgNodeRef;
+pub use references::BrokenRef;
+pub(crate) use references::{href_target, url_targets};
+pub use stats::SvgStats;
+pub use view_box::ViewBox;
+pub use visit::{Visitor, VisitorMut};
+
 /// Represents a serializable/deserialized SVG image structure.
 ///
 /// This structure is used to store the basic information of an SVG image, including class, width, height, and viewBox
@@ -10,10 +52,11 @@ use serde::{Deserialize, Serialize};
 /// - `class` : name of a CSS style class that can be used for SVG appearance control.
 /// - `width` : the width of the SVG, usually in pixels (px).
 /// - `height` : the height of the SVG, usually in pixels (px).
-/// - `view_box` : the viewBox attribute of SVG, which defines the coordinate system range of SVG.
+/// - `view_box` : the viewBox attribute of SVG, which defines the coordinate system range of SVG. See
+///   [`Svg::view_box_parsed`] for its numbers.
 /// - `elements` : a list of elements inside SVG, including the `SvgElement` enumeration, representing different SVG child elements.
 ///
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Svg {
     pub class: String,
 
@@ -24,7 +67,7 @@ pub struct Svg {
     #[serde(rename = "viewBox")]
     pub view_box: String,
 
-    #[serde(rename = "$value")]
+    #[serde(rename = "$value", default)]
     pub elements: Vec<SvgElement>,
 }
 
@@ -38,18 +81,21 @@ pub struct Svg {
 ///
 /// # Variant
 ///
-/// - `Path(Path)` : SVG `<path>` element, containing `d` attribute defines path data.
-/// - `G(G)` : SVG `<g>` grouping element, used to organize child elements.
+/// - `Path(Box<Path>)` : SVG `<path>` element, containing `d` attribute defines path data.
+/// - `G(Box<G>)` : SVG `<g>` grouping element, used to organize child elements.
 /// - `Defs(Defs)` : SVG `<defs>` Defines a container for storing reusable graphic elements.
+/// - `Rect(Rect)` : SVG `<rect>` element, such as the page background added by the `background` option.
 ///
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum SvgElement {
-    Path(Path),
+    Path(Box<Path>),
 
-    G(G),
+    G(Box<G>),
 
     Defs(Defs),
+
+    Rect(Rect),
 }
 
 /// Represents an SVG path with various styling attributes.
@@ -66,10 +112,10 @@ pub enum SvgElement {
 ///
 /// let path = Path {
 ///     d: "M10 10 H 90 V 90 H 10 Z".to_string(),
-///     class: Some("my-path".to_string()),
-///     fill: Some("red".to_string()),
-///     stroke: Some("black".to_string()),
-///     stroke_width: Some("2".to_string()),
+///     class: Some("my-path".into()),
+///     fill: Some("red".into()),
+///     stroke: Some("black".into()),
+///     stroke_width: Some("2".into()),
 ///     ..Default::default()
 /// };
 /// println!("{:?}", path);
@@ -77,71 +123,65 @@ pub enum SvgElement {
 ///
 /// # Variants
 ///
-/// - `d`: A string containing the path data that defines the shape of the path.
+/// - `d`: A string containing the path data that defines the shape of the path. Empty when the
+///   attribute is missing, as in the `<path fill="none"/>` placeholders of some optimizers; such a
+///   path draws nothing and is left out of the RSX.
+/// - `id`: Optional unique identifier of the path.
 /// - `class`: Optional string to assign a CSS class to the path.
 /// - `fill`: Optional string for the fill color of the path.
 /// - `stroke`: Optional string for the stroke (outline) color of the path.
-/// - `fill_rule`: Optional string to specify the fill rule (e.g., `"nonzero"`, `"evenodd"`).
+/// - `fill_rule`: Optional [`FillRule`] (`"nonzero"` or `"evenodd"`).
 /// - `stroke_width`: Optional string specifying the width of the stroke.
-/// - `stroke_linecap`: Optional string to specify the stroke's linecap (e.g., `"butt"`, `"round"`, `"square"`).
-/// - `stroke_linejoin`: Optional string to specify the stroke's linejoin (e.g., `"miter"`, `"round"`, `"bevel"`).
+/// - `stroke_linecap`: Optional [`LineCap`] of the stroke (`"butt"`, `"round"` or `"square"`).
+/// - `stroke_linejoin`: Optional [`LineJoin`] of the stroke (`"miter"`, `"round"` or `"bevel"`).
 /// - `stroke_miterlimit`: Optional string to define the miter limit for the stroke, used when `stroke-linejoin` is `"miter"`.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// - `tabindex`: Optional `tabindex`, such as `"0"` to make the path focusable with the keyboard.
+/// - `pointer_events`: Optional `pointer-events`, such as `"none"` to let clicks through the path.
+/// - `opacity`: Optional `opacity` of the whole path, between `"0"` and `"1"`.
+/// - `shape_rendering`: Optional `shape-rendering` hint, such as `"crispEdges"` for hairline rules.
+///
+/// `class`, `fill`, `stroke` and `stroke_width` take a handful of values across a whole document,
+/// so they are `Arc<str>`: the parsers give equal values one shared allocation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct Path {
+    #[serde(default)]
     pub d: String,
 
-    pub class: Option<String>,
+    pub id: Option<String>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub class: Option<Arc<str>>,
 
-    pub fill: Option<String>,
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub fill: Option<Arc<str>>,
 
-    pub stroke: Option<String>,
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub stroke: Option<Arc<str>>,
 
     #[serde(rename = "fill-rule")]
-    pub fill_rule: Option<String>,
+    pub fill_rule: Option<FillRule>,
 
     #[serde(rename = "stroke-width")]
-    pub stroke_width: Option<String>,
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub stroke_width: Option<Arc<str>>,
 
     #[serde(rename = "stroke-linecap")]
-    pub stroke_linecap: Option<String>,
+    pub stroke_linecap: Option<LineCap>,
 
     #[serde(rename = "stroke-linejoin")]
-    pub stroke_linejoin: Option<String>,
+    pub stroke_linejoin: Option<LineJoin>,
 
     #[serde(rename = "stroke-miterlimit")]
     pub stroke_miterlimit: Option<String>,
-}
 
-/// Represents a collection of attributes for SVG `<path>` elements.
-///
-/// The `PathEle` enumeration is used to define common attributes of the `<path>` element, such as `class`, `fill`, `d` (path data),
-/// and support **JSON serialization/deserialization**, using **kebab-case** naming format.
-///
-/// For example:
-///
-/// ```json
-/// { "class": "stroke-primary" }
-/// ```
-///
-/// # JSON format
-///
-/// - **`class`** : Specifies SVG `<path>` CSS class (string)
-/// - **`fill`** : Specifies the fill color of `<path>` (string)
-/// - **`d`** : Define path data (`d` attribute, string)
-/// - **`fill-rule`** : define filling rules (such as `"evenodd"` or `"nonzero"`)
-///
-/// Because of `#[serde(rename_all = "kebab-case")]`, all JSON fields will be automatically converted to **kebab-case** format.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "kebab-case")]
-pub enum PathEle {
-    Class(String),
+    pub tabindex: Option<String>,
 
-    Fill(String),
+    pub pointer_events: Option<String>,
 
-    D(String),
+    pub opacity: Option<String>,
 
-    FillRule(String),
+    pub shape_rendering: Option<String>,
 }
 
 /// Represents the `<g>` (Group) element in SVG,
@@ -151,15 +191,51 @@ pub enum PathEle {
 ///
 /// # Field
 ///
+/// - `id` (optional) : The unique identifier of the group.
 /// - `class` (optional) : The CSS class name of the SVG `<g>` element, used to apply the style.
 /// - `transform` (optional) : The transform attribute, such as ` rotate(45) `, affects all elements in the group.
+/// - `clip_path` (optional) : The `clip-path` attribute, such as `"url(#c0)"`, clipping the group with a [`ClipPath`].
+/// - `mask` (optional) : The `mask` attribute, such as `"url(#m0)"`, masking the group with a [`Mask`].
+/// - `filter` (optional) : The `filter` attribute, such as `"url(#shadow)"`, applying a [`Filter`] to the group.
+/// - `tabindex` (optional) : The `tabindex` attribute, such as `"0"` to make the group focusable with the keyboard.
+/// - `pointer_events` (optional) : The `pointer-events` attribute, such as `"none"` to let clicks through the group.
+/// - `shape_rendering`, `text_rendering` (optional) : The `shape-rendering` and `text-rendering` hints, inherited by the children.
 /// - `elements` : List of included SVG child elements (type ` GEle `).
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+///
+/// `class` is an `Arc<str>` shared by the groups with the same class, such as `"typst-text"`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct G {
-    pub class: Option<String>,
+    pub id: Option<String>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub class: Option<Arc<str>>,
 
     pub transform: Option<String>,
 
+    #[serde(
+        rename = "clip-path",
+        default,
+        deserialize_with = "intern::deserialize_option"
+    )]
+    pub clip_path: Option<Arc<str>>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub mask: Option<Arc<str>>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub filter: Option<Arc<str>>,
+
+    pub tabindex: Option<String>,
+
+    #[serde(rename = "pointer-events")]
+    pub pointer_events: Option<String>,
+
+    #[serde(rename = "shape-rendering")]
+    pub shape_rendering: Option<String>,
+
+    #[serde(rename = "text-rendering")]
+    pub text_rendering: Option<String>,
+
     #[serde(rename = "$value")]
     pub elements: Option<Vec<GEle>>,
 }
@@ -171,20 +247,45 @@ pub struct G {
 ///
 /// # Variants
 ///
-/// - `G(G)` : stands for `<g>` element (grouping), used to organize multiple SVG elements.
-/// - `Use(Use)` : represents the `<use>` element, representing references to other SVG elements.
-/// - `Path(Path)` : represents the `<path>` element, which defines a path in SVG.
-/// - `Image(Image)` : represents the `<image>` element, which is used to embed raster or vector images in SVG.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// - `G(Box<G>)` : stands for `<g>` element (grouping), used to organize multiple SVG elements.
+/// - `Use(Box<Use>)` : represents the `<use>` element, representing references to other SVG elements.
+/// - `Path(Box<Path>)` : represents the `<path>` element, which defines a path in SVG.
+/// - `Image(Box<Image>)` : represents the `<image>` element, which is used to embed raster or vector images in SVG.
+/// - `Text(Box<Text>)` : represents the `<text>` element, holding selectable text instead of outlined glyphs.
+/// - `Switch(Box<Switch>)` : represents the `<switch>` element, of which only one child is rendered.
+///
+/// A group of text holds a `Use` for every glyph, so every variant is boxed to keep the elements
+/// of a group to a pointer and a tag.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum GEle {
-    G(G),
+    G(Box<G>),
 
-    Use(Use),
+    Use(Box<Use>),
 
-    Path(Path),
+    Path(Box<Path>),
 
-    Image(Image),
+    Image(Box<Image>),
+
+    Text(Box<Text>),
+
+    Switch(Box<Switch>),
+}
+
+const _: () = assert!(std::mem::size_of::<GEle>() <= 64);
+
+impl GEle {
+    /// The tag of the element.
+    pub(crate) fn tag(&self) -> &'static str {
+        match self {
+            GEle::G(_) => "g",
+            GEle::Use(_) => "use",
+            GEle::Path(_) => "path",
+            GEle::Image(_) => "image",
+            GEle::Text(_) => "text",
+            GEle::Switch(_) => "switch",
+        }
+    }
 }
 
 /// Represents the structure of the SVG `<use>` element.
@@ -196,19 +297,30 @@ pub enum GEle {
 ///
 /// - `fill` : fill color, such as `"red"`, `"#ff0000"` or `"none"`.
 /// - `x` : the x coordinate of the element, usually a pixel value or a percentage string.
-/// - `fill_rule` : Fill rule. Possible values include `nonzero` or `evenodd`.
+/// - `width`, `height` (optional) : the size of the viewport given to a symbol with a `viewBox`,
+///   `100%` by default. See [`Symbol::viewport_matrix`].
+/// - `fill_rule` : Fill rule, a [`FillRule`] such as `nonzero` or `evenodd`.
 /// - `href` : The ID of the referenced SVG element, usually in the form "#id", for example "#circle1".
 /// - `transform` : Transformation applied to the element, such as translation, scaling, rotation, or skewing.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+///
+/// `fill` and `href` are `Arc<str>` shared by the uses with the same value: a glyph is referenced
+/// by every one of its occurrences. A missing `href` is parsed as an empty one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct Use {
-    pub fill: Option<String>,
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub fill: Option<Arc<str>>,
 
     pub x: String,
 
-    pub fill_rule: Option<String>,
+    pub width: Option<String>,
 
-    pub href: String,
+    pub height: Option<String>,
+
+    pub fill_rule: Option<FillRule>,
+
+    #[serde(default, deserialize_with = "intern::deserialize")]
+    pub href: Arc<str>,
 
     pub transform: Option<String>,
 }
@@ -223,8 +335,9 @@ pub struct Use {
 /// - `height`: Specifies the height of the image, also as a string with potential units.
 /// - `preserve_aspect_ratio`: Determines how the image should scale within its viewport while preserving its aspect ratio.
 /// - `href`: Contains the URI of the image resource. This is used by the SVG renderer to locate and display the image.
+///   Empty when the attribute is missing.
 /// - `transform` : Transformation applied to the element, such as translation, scaling, rotation, or skewing.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Image {
     pub width: String,
 
@@ -233,11 +346,342 @@ pub struct Image {
     #[serde(rename = "preserveAspectRatio")]
     pub preserve_aspect_ratio: String,
 
+    #[serde(default)]
     pub href: String,
 
     pub transform: Option<String>,
 }
 
+/// Represents an SVG `<text>` element.
+///
+/// Typst outlines its text into glyphs referenced by `use`, but SVGs exported with their text kept
+/// as text, which can be selected and searched, hold one `text` element per line or run, often
+/// positioning every letter through the lists of `x` and `y`.
+///
+/// # Field
+///
+/// - `id` (optional) : the unique identifier of the text.
+/// - `class` (optional) : the CSS class name of the text.
+/// - `x`, `y` (optional) : the position of the text, or a list of positions, one per character,
+///   such as `"0 5.2 10.4"`.
+/// - `dx`, `dy` (optional) : shifts from the previous character, as single values or lists.
+/// - `fill` (optional) : fill color of the letters, shared like the fills of [`Path`].
+/// - `font_family`, `font_size`, `font_weight`, `font_style` (optional) : the font attributes,
+///   such as `"Libertinus Serif"`, `"11"`, `"bold"` and `"italic"`.
+/// - `letter_spacing`, `word_spacing` (optional) : the space added between letters and between
+///   words, such as `"0.5"` for the tracking of a paragraph or the stretching of justified lines.
+/// - `dominant_baseline` (optional) : the baseline the text is aligned on, such as `"central"`.
+/// - `text_anchor` (optional) : the `text-anchor`, such as `"middle"`.
+/// - `text_rendering` (optional) : the `text-rendering` hint, such as `"optimizeLegibility"`.
+/// - `transform` (optional) : Transformation applied to the text.
+/// - `content` : the characters and `tspan` elements of the text, in document order.
+///
+/// The whitespace at the ends of a run of characters is kept, as in `Hello <tspan>world</tspan>`,
+/// but a run of whitespace only is skipped by the parser like the indentation between elements.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Text {
+    pub id: Option<String>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub class: Option<Arc<str>>,
+
+    pub x: Option<String>,
+
+    pub y: Option<String>,
+
+    pub dx: Option<String>,
+
+    pub dy: Option<String>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub fill: Option<Arc<str>>,
+
+    pub font_family: Option<String>,
+
+    pub font_size: Option<String>,
+
+    pub font_weight: Option<String>,
+
+    pub font_style: Option<String>,
+
+    pub letter_spacing: Option<String>,
+
+    pub word_spacing: Option<String>,
+
+    pub dominant_baseline: Option<String>,
+
+    pub text_anchor: Option<String>,
+
+    pub text_rendering: Option<String>,
+
+    pub transform: Option<String>,
+
+    #[serde(rename = "$value", default)]
+    pub content: Vec<TextContent>,
+}
+
+/// A child of a [`Text`]: characters or a `tspan`.
+///
+/// # Variant
+///
+/// - `Characters(String)` : a run of characters, with entities decoded.
+/// - `Tspan(Box<Tspan>)` : a `<tspan>`, restyling or moving part of the text.
+///
+/// `serde-xml-rs` names a run of characters after its content when deserializing an enum, so the
+/// `Deserialize` implementation is written by hand: everything but a `tspan` is characters.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextContent {
+    Characters(String),
+
+    Tspan(Box<Tspan>),
+}
+
+/// Represents an SVG `<tspan>` element, a run of characters inside a [`Text`].
+///
+/// # Field
+///
+/// - `x`, `y`, `dx`, `dy` (optional) : the position of the run, as for [`Text`].
+/// - `fill` (optional) : fill color of the letters.
+/// - `font_family`, `font_size`, `font_weight`, `font_style` (optional) : the font attributes.
+/// - `letter_spacing`, `word_spacing`, `dominant_baseline` (optional) : the spacing and the
+///   baseline of the run, as for [`Text`].
+/// - `characters` : the characters of the run, without nested `tspan`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Tspan {
+    pub x: Option<String>,
+
+    pub y: Option<String>,
+
+    pub dx: Option<String>,
+
+    pub dy: Option<String>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub fill: Option<Arc<str>>,
+
+    pub font_family: Option<String>,
+
+    pub font_size: Option<String>,
+
+    pub font_weight: Option<String>,
+
+    pub font_style: Option<String>,
+
+    pub letter_spacing: Option<String>,
+
+    pub word_spacing: Option<String>,
+
+    pub dominant_baseline: Option<String>,
+
+    #[serde(rename = "$value", default)]
+    pub characters: String,
+}
+
+/// The characters and the `tspan`s are told apart by what the XML deserializer meets, a string or
+/// an element, rather than by a name: characters reading `tspan` would otherwise be taken for one.
+impl<'de> Deserialize<'de> for TextContent {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ContentVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ContentVisitor {
+            type Value = TextContent;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("characters or a tspan")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, characters: &str) -> Result<TextContent, E> {
+                Ok(TextContent::Characters(characters.to_string()))
+            }
+
+            fn visit_string<E: serde::de::Error>(
+                self,
+                characters: String,
+            ) -> Result<TextContent, E> {
+                Ok(TextContent::Characters(characters))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> Result<TextContent, A::Error> {
+                let deserializer = serde::de::value::MapAccessDeserializer::new(map);
+                Tspan::deserialize(deserializer).map(|tspan| TextContent::Tspan(Box::new(tspan)))
+            }
+        }
+
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
+/// Represents an SVG `<switch>` element, which renders the first of its children whose conditions
+/// hold.
+///
+/// The conditions are the `requiredFeatures`, `requiredExtensions` and `systemLanguage` attributes
+/// of the children. Editors such as draw.io use them to put a `foreignObject` holding HTML before
+/// a fallback drawn in SVG, for the renderers without HTML. Dioxus cannot evaluate the conditions,
+/// so the conversion renders the first child, or the first one the tree can hold, as
+/// [`RsxOptions::switch`](crate::options::RsxOptions) says, and the conditions are not kept.
+///
+/// # Field
+///
+/// - `id` (optional) : the unique identifier of the switch.
+/// - `transform` (optional) : Transformation applied to the rendered child.
+/// - `branches` : the alternatives, in document order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Switch {
+    pub id: Option<String>,
+
+    pub transform: Option<String>,
+
+    #[serde(rename = "$value", default)]
+    pub branches: Vec<SwitchBranch>,
+}
+
+/// A child of a [`Switch`].
+///
+/// # Variant
+///
+/// - `Element(GEle)` : an element that the tree can hold, as in a group.
+/// - `Unsupported(String)` : an element that it cannot, such as a `foreignObject`, kept by its
+///   name only: its attributes and content are dropped. It renders nothing.
+///
+/// Any element may be an alternative, so the `Deserialize` implementation is written by hand,
+/// reading the unknown elements into `Unsupported` instead of failing.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SwitchBranch {
+    Element(GEle),
+
+    Unsupported(String),
+}
+
+impl<'de> Deserialize<'de> for SwitchBranch {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BranchVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for BranchVisitor {
+            type Value = SwitchBranch;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an element")
+            }
+
+            fn visit_enum<A: serde::de::EnumAccess<'de>>(
+                self,
+                data: A,
+            ) -> Result<SwitchBranch, A::Error> {
+                use serde::de::VariantAccess;
+
+                let (name, variant) = data.variant::<String>()?;
+                let element = match name.as_str() {
+                    "g" => GEle::G(Box::new(variant.newtype_variant()?)),
+                    "use" => GEle::Use(Box::new(variant.newtype_variant()?)),
+                    "path" => GEle::Path(Box::new(variant.newtype_variant()?)),
+                    "image" => GEle::Image(Box::new(variant.newtype_variant()?)),
+                    "text" => GEle::Text(Box::new(variant.newtype_variant()?)),
+                    "switch" => GEle::Switch(Box::new(variant.newtype_variant()?)),
+                    _ => {
+                        variant.newtype_variant::<serde::de::IgnoredAny>()?;
+                        return Ok(SwitchBranch::Unsupported(name));
+                    }
+                };
+                Ok(SwitchBranch::Element(element))
+            }
+        }
+
+        deserializer.deserialize_enum("SwitchBranch", GELE_VARIANTS, BranchVisitor)
+    }
+}
+
+impl SwitchBranch {
+    /// The tag of the alternative, such as `g` or `foreignObject`.
+    pub fn name(&self) -> &str {
+        match self {
+            SwitchBranch::Element(element) => element.tag(),
+            SwitchBranch::Unsupported(name) => name,
+        }
+    }
+}
+
+/// The tags of the [`GEle`] variants.
+const GELE_VARIANTS: &[&str] = &["g", "use", "path", "image", "text", "switch"];
+
+impl Switch {
+    /// The alternatives that the tree can hold, in document order.
+    pub fn elements(&self) -> impl Iterator<Item = &GEle> {
+        self.branches.iter().filter_map(|branch| match branch {
+            SwitchBranch::Element(element) => Some(element),
+            SwitchBranch::Unsupported(_) => None,
+        })
+    }
+
+    /// The alternatives that the tree can hold, in document order, for modifying them.
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut GEle> {
+        self.branches.iter_mut().filter_map(|branch| match branch {
+            SwitchBranch::Element(element) => Some(element),
+            SwitchBranch::Unsupported(_) => None,
+        })
+    }
+
+    /// The index of the branch rendered with `mode`, if any.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::{
+    ///     options::SwitchMode,
+    ///     svg_types::{GEle, Switch, SwitchBranch, G},
+    /// };
+    ///
+    /// let switch = Switch {
+    ///     branches: vec![
+    ///         SwitchBranch::Unsupported("foreignObject".to_string()),
+    ///         SwitchBranch::Element(GEle::G(Box::default())),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(switch.rendered_branch(SwitchMode::First), Some(0));
+    /// assert_eq!(switch.rendered_branch(SwitchMode::FirstSupported), Some(1));
+    /// ```
+    pub fn rendered_branch(&self, mode: SwitchMode) -> Option<usize> {
+        match mode {
+            SwitchMode::First => (!self.branches.is_empty()).then_some(0),
+            SwitchMode::FirstSupported => self
+                .branches
+                .iter()
+                .position(|branch| matches!(branch, SwitchBranch::Element(_))),
+        }
+    }
+}
+
+/// Represents an SVG `<rect>` element.
+///
+/// Typst draws its shapes as paths, so this element mostly appears as the page background added
+/// by [`RsxOptions::background`](crate::options::RsxOptions).
+///
+/// # Field
+///
+/// - `x`, `y` : the position of the top-left corner, in user units.
+/// - `width`, `height` : the size of the rectangle, in user units.
+/// - `fill` : fill color, such as `"white"` or `"#ffffff"`, shared like the fills of [`Path`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rect {
+    pub x: String,
+
+    pub y: String,
+
+    pub width: String,
+
+    pub height: String,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub fill: Option<Arc<str>>,
+}
+
 /// Represents the struct of the `<defs>` element, which is used to store reusable SVG definitions.
 ///
 /// The `Defs` structure is usually used to contain reusable SVG elements such as `symbols`, which are not rendered directly.
@@ -247,106 +691,683 @@ pub struct Image {
 ///
 /// - `id` : The ID of the `<defs>` element, which can be used to uniquely identify the definition block.
 /// - `elements` : contains a list of `Symbol` elements to store reusable graphic definitions.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// - `definitions` : the other definitions, such as gradients, clip paths and filters, referenced
+///   by `url(#id)` values. They are written after the symbols.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(from = "RawDefs")]
 pub struct Defs {
     pub id: String,
 
-    #[serde(rename = "$value")]
     pub elements: Vec<Symbol>,
+
+    pub definitions: Vec<Definition>,
 }
 
-/// Represents an SVG symbol (`<symbol>`) structure.
+/// The `<defs>` element as read from the document, its children in document order.
+#[derive(Deserialize)]
+struct RawDefs {
+    id: String,
+
+    #[serde(rename = "$value", default)]
+    children: Vec<DefsChild>,
+}
+
+#[derive(Deserialize)]
+enum DefsChild {
+    #[serde(rename = "symbol")]
+    Symbol(Symbol),
+
+    #[serde(rename = "linearGradient")]
+    LinearGradient(LinearGradient),
+
+    #[serde(rename = "radialGradient")]
+    RadialGradient(RadialGradient),
+
+    #[serde(rename = "clipPath")]
+    ClipPath(ClipPath),
+
+    #[serde(rename = "mask")]
+    Mask(Mask),
+
+    #[serde(rename = "filter")]
+    Filter(Filter),
+}
+
+impl From<RawDefs> for Defs {
+    fn from(raw: RawDefs) -> Self {
+        let mut defs = Defs {
+            id: raw.id,
+            ..Default::default()
+        };
+        for child in raw.children {
+            match child {
+                DefsChild::Symbol(symbol) => defs.elements.push(symbol),
+                DefsChild::LinearGradient(gradient) => {
+                    defs.definitions.push(Definition::LinearGradient(gradient))
+                }
+                DefsChild::RadialGradient(gradient) => {
+                    defs.definitions.push(Definition::RadialGradient(gradient))
+                }
+                DefsChild::ClipPath(clip) => defs.definitions.push(Definition::ClipPath(clip)),
+                DefsChild::Mask(mask) => defs.definitions.push(Definition::Mask(mask)),
+                DefsChild::Filter(filter) => defs.definitions.push(Definition::Filter(filter)),
+            }
+        }
+        defs
+    }
+}
+
+/// A definition of a [`Defs`] other than a symbol, rendered only where it is referenced.
 ///
-/// This structure is used to store the basic information of SVG symbol elements, including `id`, `overflow`, and other attributes.
-/// and the internal `Path` element (path information).
+/// # Variant
 ///
-/// The `Symbol` structure is commonly used to define reusable SVG fragments and can be referenced in multiple places via the `<use>` tag.
+/// - `LinearGradient(LinearGradient)` : SVG `<linearGradient>` element.
+/// - `RadialGradient(RadialGradient)` : SVG `<radialGradient>` element.
+/// - `ClipPath(ClipPath)` : SVG `<clipPath>` element.
+/// - `Mask(Mask)` : SVG `<mask>` element.
+/// - `Filter(Filter)` : SVG `<filter>` element.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub enum Definition {
+    #[serde(rename = "linearGradient")]
+    LinearGradient(LinearGradient),
+
+    #[serde(rename = "radialGradient")]
+    RadialGradient(RadialGradient),
+
+    #[serde(rename = "clipPath")]
+    ClipPath(ClipPath),
+
+    #[serde(rename = "mask")]
+    Mask(Mask),
+
+    #[serde(rename = "filter")]
+    Filter(Filter),
+}
+
+impl Definition {
+    /// The id that `url(#id)` paints and `href` links reference.
+    pub fn id(&self) -> &str {
+        match self {
+            Definition::LinearGradient(gradient) => &gradient.id,
+            Definition::RadialGradient(gradient) => &gradient.id,
+            Definition::ClipPath(clip) => &clip.id,
+            Definition::Mask(mask) => &mask.id,
+            Definition::Filter(filter) => &filter.id,
+        }
+    }
+
+    /// The id, for renaming it.
+    pub(crate) fn id_mut(&mut self) -> &mut String {
+        match self {
+            Definition::LinearGradient(gradient) => &mut gradient.id,
+            Definition::RadialGradient(gradient) => &mut gradient.id,
+            Definition::ClipPath(clip) => &mut clip.id,
+            Definition::Mask(mask) => &mut mask.id,
+            Definition::Filter(filter) => &mut filter.id,
+        }
+    }
+
+    /// The `href` link to another definition, such as `"#g0"`, if any.
+    pub fn href(&self) -> Option<&str> {
+        match self {
+            Definition::LinearGradient(gradient) => gradient.href.as_deref(),
+            Definition::RadialGradient(gradient) => gradient.href.as_deref(),
+            Definition::ClipPath(_) | Definition::Mask(_) | Definition::Filter(_) => None,
+        }
+    }
+
+    /// The color stops of a gradient, none for the other definitions.
+    pub fn stops(&self) -> &[Stop] {
+        match self {
+            Definition::LinearGradient(gradient) => &gradient.stops,
+            Definition::RadialGradient(gradient) => &gradient.stops,
+            Definition::ClipPath(_) | Definition::Mask(_) | Definition::Filter(_) => &[],
+        }
+    }
+
+    /// The color stops of a gradient, for modifying them.
+    pub fn stops_mut(&mut self) -> &mut [Stop] {
+        match self {
+            Definition::LinearGradient(gradient) => &mut gradient.stops,
+            Definition::RadialGradient(gradient) => &mut gradient.stops,
+            Definition::ClipPath(_) | Definition::Mask(_) | Definition::Filter(_) => &mut [],
+        }
+    }
+
+    /// The paths of a clip path or a mask, none for the other definitions.
+    pub fn paths(&self) -> &[Path] {
+        match self {
+            Definition::ClipPath(clip) => &clip.elements,
+            Definition::Mask(mask) => &mask.elements,
+            Definition::LinearGradient(_)
+            | Definition::RadialGradient(_)
+            | Definition::Filter(_) => &[],
+        }
+    }
+
+    /// The paths of a clip path or a mask, for modifying them.
+    pub fn paths_mut(&mut self) -> &mut [Path] {
+        match self {
+            Definition::ClipPath(clip) => &mut clip.elements,
+            Definition::Mask(mask) => &mut mask.elements,
+            Definition::LinearGradient(_)
+            | Definition::RadialGradient(_)
+            | Definition::Filter(_) => &mut [],
+        }
+    }
+
+    /// The primitives of a filter, in processing order, none for the other definitions.
+    pub fn primitives(&self) -> &[FilterPrimitive] {
+        match self {
+            Definition::Filter(filter) => &filter.primitives,
+            Definition::LinearGradient(_)
+            | Definition::RadialGradient(_)
+            | Definition::ClipPath(_)
+            | Definition::Mask(_) => &[],
+        }
+    }
+
+    /// The tag of the definition, such as `linearGradient`.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Definition::LinearGradient(_) => "linearGradient",
+            Definition::RadialGradient(_) => "radialGradient",
+            Definition::ClipPath(_) => "clipPath",
+            Definition::Mask(_) => "mask",
+            Definition::Filter(_) => "filter",
+        }
+    }
+}
+
+/// Represents an SVG `<linearGradient>` element, a paint fading along a line.
+///
+/// Typst writes one for each gradient fill, with `gradientUnits="userSpaceOnUse"` and a
+/// `gradientTransform` rotating it to the angle of the gradient.
 ///
 /// # Field
 ///
-/// - `id` : a unique identifier for the SVG symbol, which can be used for `<use>` tag references.
-/// - `overflow` : The overflow style attribute of the symbol that defines whether content overflow is allowed.
-/// - `element` : The Path inside the symbol, representing the graphic content inside the symbol.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-pub struct Symbol {
+/// - `id` : the identifier that `url(#id)` paints reference. Empty when the attribute is missing.
+/// - `x1`, `y1`, `x2`, `y2` (optional) : the start and end of the gradient vector.
+/// - `gradient_units` (optional) : the [`Units`] of the coordinates.
+/// - `gradient_transform` (optional) : Transformation applied to the gradient, such as
+///   `"rotate(45 0.5 0.5)"`.
+/// - `spread_method` (optional) : the [`SpreadMethod`] painting past the ends of the vector.
+/// - `href` (optional) : a link such as `"#g0"` to a gradient whose stops and attributes this one
+///   inherits.
+/// - `stops` : the color stops, in offset order.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_types::{LinearGradient, SpreadMethod, Stop, Units};
+///
+/// let gradient = LinearGradient {
+///     id: "g0".to_string(),
+///     gradient_units: Some(Units::ObjectBoundingBox),
+///     gradient_transform: Some("rotate(45 0.5 0.5)".to_string()),
+///     spread_method: Some(SpreadMethod::Reflect),
+///     stops: vec![
+///         Stop {
+///             offset: Some("0".to_string()),
+///             stop_color: Some("#ff0000".into()),
+///             ..Default::default()
+///         },
+///         Stop {
+///             offset: Some("1".to_string()),
+///             stop_color: Some("#0000ff".into()),
+///             ..Default::default()
+///         },
+///     ],
+///     ..Default::default()
+/// };
+/// assert_eq!(gradient.stops.len(), 2);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct LinearGradient {
+    #[serde(default)]
     pub id: String,
 
-    pub overflow: String,
+    pub x1: Option<String>,
 
-    #[serde(rename = "$value")]
-    pub element: SymbolEle,
+    pub y1: Option<String>,
+
+    pub x2: Option<String>,
+
+    pub y2: Option<String>,
+
+    #[serde(rename = "gradientUnits")]
+    pub gradient_units: Option<Units>,
+
+    #[serde(rename = "gradientTransform")]
+    pub gradient_transform: Option<String>,
+
+    #[serde(rename = "spreadMethod")]
+    pub spread_method: Option<SpreadMethod>,
+
+    pub href: Option<String>,
+
+    #[serde(rename = "$value", default)]
+    pub stops: Vec<Stop>,
 }
 
-/// Represents a symbolic element that can be either a `Path` or an `Image`.
+/// Represents an SVG `<radialGradient>` element, a paint fading from a focal point to a circle.
 ///
-/// This enum is serialized and deserialized using kebab-case naming conventions.
+/// # Field
 ///
-/// # Variants
+/// - `id` : the identifier that `url(#id)` paints reference. Empty when the attribute is missing.
+/// - `cx`, `cy`, `r` (optional) : the end circle of the gradient.
+/// - `fx`, `fy` (optional) : the focal point where the gradient starts, the center by default.
+/// - `gradient_units`, `gradient_transform`, `spread_method`, `href`, `stops` : as for a
+///   [`LinearGradient`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct RadialGradient {
+    #[serde(default)]
+    pub id: String,
+
+    pub cx: Option<String>,
+
+    pub cy: Option<String>,
+
+    pub r: Option<String>,
+
+    pub fx: Option<String>,
+
+    pub fy: Option<String>,
+
+    #[serde(rename = "gradientUnits")]
+    pub gradient_units: Option<Units>,
+
+    #[serde(rename = "gradientTransform")]
+    pub gradient_transform: Option<String>,
+
+    #[serde(rename = "spreadMethod")]
+    pub spread_method: Option<SpreadMethod>,
+
+    pub href: Option<String>,
+
+    #[serde(rename = "$value", default)]
+    pub stops: Vec<Stop>,
+}
+
+/// Represents an SVG `<clipPath>` element, the outline that a group referencing it with
+/// `clip-path="url(#id)"` is cut to.
 ///
-/// - `Path` : Represents a vector path element.
-/// - `Image` : Represents an image element.
+/// Typst writes one for each clipped block, with `clipPathUnits="userSpaceOnUse"`: leaving the
+/// units out would read the outline as fractions of the box of the group, the default of SVG.
+///
+/// # Field
+///
+/// - `id` : the identifier that `url(#id)` references. Empty when the attribute is missing.
+/// - `clip_path_units` (optional) : the [`Units`] of the paths, `userSpaceOnUse` by default.
+/// - `transform` (optional) : Transformation applied to the paths.
+/// - `elements` : the paths whose union is the clipping region.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_types::{ClipPath, Path, Units};
+///
+/// let clip = ClipPath {
+///     id: "c0".to_string(),
+///     clip_path_units: Some(Units::UserSpaceOnUse),
+///     elements: vec![Path {
+///         d: "M 0 0 L 10 0 L 10 10 L 0 10 Z".to_string(),
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// };
+/// assert_eq!(clip.elements.len(), 1);
 /// ```
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "kebab-case")]
-pub enum SymbolEle {
-    Path(Path),
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ClipPath {
+    #[serde(default)]
+    pub id: String,
 
-    Image(Image),
+    #[serde(rename = "clipPathUnits")]
+    pub clip_path_units: Option<Units>,
+
+    pub transform: Option<String>,
+
+    #[serde(rename = "$value", default)]
+    pub elements: Vec<Path>,
 }
 
-/// Represents a `Class` struct containing text content.
+/// Represents an SVG `<mask>` element, whose luminance sets the opacity of a group referencing it
+/// with `mask="url(#id)"`.
 ///
-/// This structure is mainly used to store text content in SVG or other XML formats, and supports Serialize and
-/// `Deserialize`.
+/// # Field
+///
+/// - `id` : the identifier that `url(#id)` references. Empty when the attribute is missing.
+/// - `x`, `y`, `width`, `height` (optional) : the region of the mask, outside of which the group
+///   is hidden.
+/// - `mask_units` (optional) : the [`Units`] of the region, `objectBoundingBox` by default.
+/// - `mask_content_units` (optional) : the [`Units`] of the paths, `userSpaceOnUse` by default.
+/// - `elements` : the paths painting the mask.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Mask {
+    #[serde(default)]
+    pub id: String,
+
+    pub x: Option<String>,
+
+    pub y: Option<String>,
+
+    pub width: Option<String>,
+
+    pub height: Option<String>,
+
+    #[serde(rename = "maskUnits")]
+    pub mask_units: Option<Units>,
+
+    #[serde(rename = "maskContentUnits")]
+    pub mask_content_units: Option<Units>,
+
+    #[serde(rename = "$value", default)]
+    pub elements: Vec<Path>,
+}
+
+/// Represents an SVG `<filter>` element, the chain of primitives applied to a group referencing it
+/// with `filter="url(#id)"`.
+///
+/// Each primitive reads the output of the one before it, or the result named by its `in`, so the
+/// order of `primitives` is the processing order and is kept as written. Packages drawing shadows
+/// write a single `feDropShadow`, or an `feColorMatrix` turning the group into its shadow color
+/// followed by a blur and an offset.
 ///
 /// # Field
 ///
-/// - `content` : The actual stored text content, represented as a JSON direct string when serialized.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-pub struct Class {
-    #[serde(rename = "$value")]
-    pub content: String,
+/// - `id` : the identifier that `url(#id)` references. Empty when the attribute is missing.
+/// - `x`, `y`, `width`, `height` (optional) : the region of the filter, outside of which its output
+///   is clipped. Shadows extend it past the box of the group, such as with `"-20%"` and `"140%"`.
+/// - `filter_units` (optional) : the [`Units`] of the region, `objectBoundingBox` by default.
+/// - `primitives` : the [`FilterPrimitive`]s, in document order.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_types::{DropShadow, Filter, FilterPrimitive};
+///
+/// let filter = Filter {
+///     id: "shadow".to_string(),
+///     primitives: vec![FilterPrimitive::DropShadow(DropShadow {
+///         dy: Some("2".to_string()),
+///         std_deviation: Some("4".to_string()),
+///         flood_color: Some("#000000".into()),
+///         flood_opacity: Some("0.25".to_string()),
+///         ..Default::default()
+///     })],
+///     ..Default::default()
+/// };
+/// assert_eq!(filter.primitives[0].tag(), "feDropShadow");
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Filter {
+    #[serde(default)]
+    pub id: String,
+
+    pub x: Option<String>,
+
+    pub y: Option<String>,
+
+    pub width: Option<String>,
+
+    pub height: Option<String>,
+
+    #[serde(rename = "filterUnits")]
+    pub filter_units: Option<Units>,
+
+    #[serde(rename = "$value", default)]
+    pub primitives: Vec<FilterPrimitive>,
 }
 
-/// Represents the structure of the SVG fill attribute (`fill`).
+/// A primitive of a [`Filter`].
 ///
-/// This structure is used to store the value of the SVG `fill` attribute and supports Serialize and Deserialize.
-/// where the `content` field is serde serialized/deserialized to **direct text values** instead of JSON key-value pairs.
+/// # Variant
+///
+/// - `GaussianBlur(GaussianBlur)` : SVG `<feGaussianBlur>` element.
+/// - `Offset(Offset)` : SVG `<feOffset>` element.
+/// - `ColorMatrix(ColorMatrix)` : SVG `<feColorMatrix>` element.
+/// - `DropShadow(DropShadow)` : SVG `<feDropShadow>` element.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FilterPrimitive {
+    #[serde(rename = "feGaussianBlur")]
+    GaussianBlur(GaussianBlur),
+
+    #[serde(rename = "feOffset")]
+    Offset(Offset),
+
+    #[serde(rename = "feColorMatrix")]
+    ColorMatrix(ColorMatrix),
+
+    #[serde(rename = "feDropShadow")]
+    DropShadow(DropShadow),
+}
+
+impl FilterPrimitive {
+    /// The name of the element, such as `"feDropShadow"`.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            FilterPrimitive::GaussianBlur(_) => "feGaussianBlur",
+            FilterPrimitive::Offset(_) => "feOffset",
+            FilterPrimitive::ColorMatrix(_) => "feColorMatrix",
+            FilterPrimitive::DropShadow(_) => "feDropShadow",
+        }
+    }
+}
+
+/// Represents an SVG `<feGaussianBlur>` element, blurring its input.
 ///
 /// # Field
 ///
-/// - `content` : fill color values, such as `"red"`, `"#FF0000"`, `"none"`.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-pub struct Fill {
-    #[serde(rename = "$value")]
-    pub content: String,
+/// - `input` (optional) : the `in` attribute, such as `"SourceAlpha"` or the `result` of an
+///   earlier primitive. The output of the previous primitive by default.
+/// - `std_deviation` (optional) : the `stdDeviation` attribute, the amount of blur, such as `"4"`
+///   or `"4 2"` for different amounts along x and y.
+/// - `result` (optional) : the name that later primitives read the output by.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct GaussianBlur {
+    #[serde(rename = "in")]
+    pub input: Option<String>,
+
+    #[serde(rename = "stdDeviation")]
+    pub std_deviation: Option<String>,
+
+    pub result: Option<String>,
 }
 
-/// Represents the `fill-rule` attribute in SVG, which defines the fill rule.
+/// Represents an SVG `<feOffset>` element, moving its input.
 ///
-/// `fill-rule` determines how to determine the fill area according to the path direction, and is usually used for the `path`, `polygon`, and `clipPath` elements.
-/// Its value is usually `nonzero` or `evenodd`.
+/// # Field
 ///
-/// # field
+/// - `input` (optional) : the `in` attribute, as for [`GaussianBlur`].
+/// - `dx`, `dy` (optional) : the distance moved along x and y, in the units of the filter.
+/// - `result` (optional) : the name that later primitives read the output by.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Offset {
+    #[serde(rename = "in")]
+    pub input: Option<String>,
+
+    pub dx: Option<String>,
+
+    pub dy: Option<String>,
+
+    pub result: Option<String>,
+}
+
+/// Represents an SVG `<feColorMatrix>` element, transforming the colors of its input.
 ///
-/// - `content` : The value of the filling rule. Common values include:
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-pub struct FillRule {
-    /// The value of `fill-rule`, such as ` nonzero ` or ` evenodd `
-    #[serde(rename = "$value")]
-    pub content: String,
+/// # Field
+///
+/// - `input` (optional) : the `in` attribute, as for [`GaussianBlur`].
+/// - `kind` (optional) : the `type` attribute, a [`ColorMatrixType`], `matrix` by default.
+/// - `values` (optional) : the numbers of the transform, read as `kind` says, such as the 20
+///   numbers of `"0 0 0 0 0  0 0 0 0 0  0 0 0 0 0  0 0 0 0.3 0"` turning the input into a
+///   translucent black.
+/// - `result` (optional) : the name that later primitives read the output by.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ColorMatrix {
+    #[serde(rename = "in")]
+    pub input: Option<String>,
+
+    #[serde(rename = "type")]
+    pub kind: Option<ColorMatrixType>,
+
+    pub values: Option<String>,
+
+    pub result: Option<String>,
+}
+
+/// Represents an SVG `<feDropShadow>` element, drawing its input over a blurred, moved and
+/// recolored copy of it.
+///
+/// # Field
+///
+/// - `input` (optional) : the `in` attribute, as for [`GaussianBlur`].
+/// - `dx`, `dy` (optional) : the offset of the shadow, `"2"` each by default.
+/// - `std_deviation` (optional) : the `stdDeviation` attribute, the blur of the shadow.
+/// - `flood_color` (optional) : the `flood-color` attribute, the color of the shadow, shared like
+///   the fills of [`Path`].
+/// - `flood_opacity` (optional) : the `flood-opacity` attribute, the opacity of the shadow.
+/// - `result` (optional) : the name that later primitives read the output by.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DropShadow {
+    #[serde(rename = "in")]
+    pub input: Option<String>,
+
+    pub dx: Option<String>,
+
+    pub dy: Option<String>,
+
+    #[serde(rename = "stdDeviation")]
+    pub std_deviation: Option<String>,
+
+    #[serde(
+        rename = "flood-color",
+        default,
+        deserialize_with = "intern::deserialize_option"
+    )]
+    pub flood_color: Option<Arc<str>>,
+
+    #[serde(rename = "flood-opacity")]
+    pub flood_opacity: Option<String>,
+
+    pub result: Option<String>,
+}
+
+/// Represents an SVG `<stop>` element, a color of a gradient.
+///
+/// # Field
+///
+/// - `offset` (optional) : where the color is reached along the gradient, such as `"0.5"` or
+///   `"50%"`.
+/// - `stop_color` (optional) : the color, shared like the fills of [`Path`].
+/// - `stop_opacity` (optional) : the opacity of the color, between `"0"` and `"1"`.
+/// - `style` (optional) : the inline CSS of the stop, which some editors write the color in, as
+///   in `style="stop-color:#fff"`. See [`Stop::normalize_style`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Stop {
+    pub offset: Option<String>,
+
+    #[serde(default, deserialize_with = "intern::deserialize_option")]
+    pub stop_color: Option<Arc<str>>,
+
+    pub stop_opacity: Option<String>,
+
+    pub style: Option<String>,
+}
+
+impl Stop {
+    /// Moves the `stop-color` and `stop-opacity` declarations of the `style` into `stop_color`
+    /// and `stop_opacity`, where the passes such as [`recolor`](crate::passes::recolor) find
+    /// them.
+    ///
+    /// The declarations of the style win over the attributes, as in CSS. The other declarations
+    /// stay in the style, which is removed once empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::svg_types::Stop;
+    ///
+    /// let mut stop = Stop {
+    ///     stop_color: Some("#000000".into()),
+    ///     style: Some("stop-color: #ffffff; stop-opacity:0.5".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// stop.normalize_style();
+    /// assert_eq!(stop.stop_color.as_deref(), Some("#ffffff"));
+    /// assert_eq!(stop.stop_opacity.as_deref(), Some("0.5"));
+    /// assert_eq!(stop.style, None);
+    /// ```
+    pub fn normalize_style(&mut self) {
+        let Some(style) = &self.style else {
+            return;
+        };
+        let mut rest = Vec::new();
+        for declaration in style.split(';').map(str::trim) {
+            match declaration.split_once(':') {
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("stop-color") => {
+                    self.stop_color = Some(intern::intern(value.trim()));
+                }
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("stop-opacity") => {
+                    self.stop_opacity = Some(value.trim().to_string());
+                }
+                _ if declaration.is_empty() => {}
+                _ => rest.push(declaration),
+            }
+        }
+        self.style = Some(rest.join("; ")).filter(|style| !style.is_empty());
+    }
 }
 
-/// Represents a simple structure with serializable and deserialized capabilities that contains a content field.
+/// Represents an SVG symbol (`<symbol>`) structure.
+///
+/// This structure is used to store the basic information of SVG symbol elements, including `id`, `overflow`, and other attributes.
+/// and the internal `Path` element (path information).
 ///
-/// This struct is mainly used to store a string field `content`, which is renamed by `$value`
-/// is the value part of JSON, using specific field names when serializing and deserializing.
+/// The `Symbol` structure is commonly used to define reusable SVG fragments and can be referenced in multiple places via the `<use>` tag.
 ///
 /// # Field
 ///
-/// - `content` : Stores the content of the string, renaming it as `$value`, which becomes the value part of the JSON when serialized.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
-pub struct D {
+/// - `id` : a unique identifier for the SVG symbol, which can be used for `<use>` tag references.
+///   Empty when the attribute is missing, which leaves the symbol unreachable.
+/// - `overflow` : The overflow style attribute of the symbol that defines whether content overflow is allowed.
+/// - `view_box` (optional) : the `viewBox` attribute, such as `"0 0 10 10"`, the region of the
+///   content scaled to the size of each `use`. Without it the content is drawn at its own size.
+/// - `preserve_aspect_ratio` (optional) : the `preserveAspectRatio` attribute, how the `viewBox`
+///   is fitted in a `use` of another aspect ratio, `xMidYMid meet` by default.
+/// - `element` : The Path inside the symbol, representing the graphic content inside the symbol.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Symbol {
+    #[serde(default)]
+    pub id: String,
+
+    pub overflow: String,
+
+    #[serde(rename = "viewBox")]
+    pub view_box: Option<String>,
+
+    #[serde(rename = "preserveAspectRatio")]
+    pub preserve_aspect_ratio: Option<String>,
+
     #[serde(rename = "$value")]
-    pub content: String,
+    pub element: SymbolEle,
+}
+
+/// Represents a symbolic element that can be either a `Path` or an `Image`.
+///
+/// This enum is serialized and deserialized using kebab-case naming conventions.
+///
+/// # Variants
+///
+/// - `Path` : Represents a vector path element.
+/// - `Image` : Represents an image element.
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymbolEle {
+    Path(Path),
+
+    Image(Image),
 }
@@ -0,0 +1,293 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Defines an enum for an attribute taking one of a fixed set of keywords.
+///
+/// Each keyword gets a variant, and any other value is kept verbatim in `Other` so that parsing
+/// never fails. The enum (de)serializes from and to the keyword string, converts with `FromStr`,
+/// `From<&str>` and `From<String>`, converts back into a `String`, and displays as the keyword.
+macro_rules! keyword_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $keyword:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+
+            /// Any other value, kept as written in the document.
+            Other(String),
+        }
+
+        impl $name {
+            /// Returns the value as written in an SVG attribute.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $keyword,)*
+                    $name::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($keyword => $name::$variant,)*
+                    other => $name::Other(other.to_string()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                match $name::from(value.as_str()) {
+                    $name::Other(_) => $name::Other(value),
+                    keyword => keyword,
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Ok(value.into())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::Other(value) => value,
+                    keyword => keyword.as_str().to_string(),
+                }
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.as_str()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map($name::from)
+            }
+        }
+    };
+}
+
+keyword_enum! {
+    /// The value of the `fill-rule` attribute, which decides which parts of a self-intersecting
+    /// path are inside it.
+    ///
+    /// # Variant
+    ///
+    /// - `NonZero` : `nonzero`, the default.
+    /// - `EvenOdd` : `evenodd`.
+    /// - `Other(String)` : any other value, such as a typo or `inherit`, kept verbatim.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::svg_types::FillRule;
+    ///
+    /// assert_eq!("evenodd".parse(), Ok(FillRule::EvenOdd));
+    /// assert_eq!(FillRule::from("evenod"), FillRule::Other("evenod".to_string()));
+    /// assert_eq!(FillRule::NonZero.to_string(), "nonzero");
+    /// ```
+    pub enum FillRule {
+        NonZero => "nonzero",
+        EvenOdd => "evenodd",
+    }
+}
+
+keyword_enum! {
+    /// The value of the `stroke-linecap` attribute, the shape drawn at the ends of open subpaths.
+    ///
+    /// # Variant
+    ///
+    /// - `Butt` : `butt`, the default.
+    /// - `Round` : `round`.
+    /// - `Square` : `square`.
+    /// - `Other(String)` : any other value, kept verbatim.
+    pub enum LineCap {
+        Butt => "butt",
+        Round => "round",
+        Square => "square",
+    }
+}
+
+keyword_enum! {
+    /// The value of the `stroke-linejoin` attribute, the shape drawn at the corners of a stroke.
+    ///
+    /// # Variant
+    ///
+    /// - `Miter` : `miter`, the default.
+    /// - `Round` : `round`.
+    /// - `Bevel` : `bevel`.
+    /// - `Other(String)` : any other value, such as `miter-clip` or `arcs`, kept verbatim.
+    pub enum LineJoin {
+        Miter => "miter",
+        Round => "round",
+        Bevel => "bevel",
+    }
+}
+
+keyword_enum! {
+    /// The value of the `spreadMethod` attribute of a gradient, how it paints past its ends.
+    ///
+    /// # Variant
+    ///
+    /// - `Pad` : `pad`, the default, extending the colors of the end stops.
+    /// - `Reflect` : `reflect`, repeating the gradient back and forth.
+    /// - `Repeat` : `repeat`, repeating the gradient from its start.
+    /// - `Other(String)` : any other value, kept verbatim.
+    pub enum SpreadMethod {
+        Pad => "pad",
+        Reflect => "reflect",
+        Repeat => "repeat",
+    }
+}
+
+keyword_enum! {
+    /// The coordinate system of the attributes of a paint server, a clip path or a mask, such as
+    /// `gradientUnits` or `clipPathUnits`.
+    ///
+    /// # Variant
+    ///
+    /// - `UserSpaceOnUse` : `userSpaceOnUse`, the user space of the element referencing it.
+    /// - `ObjectBoundingBox` : `objectBoundingBox`, fractions of the bounding box of that element,
+    ///   the default of gradients and of the region of masks.
+    /// - `Other(String)` : any other value, kept verbatim.
+    pub enum Units {
+        UserSpaceOnUse => "userSpaceOnUse",
+        ObjectBoundingBox => "objectBoundingBox",
+    }
+}
+
+keyword_enum! {
+    /// The value of the `type` attribute of an `feColorMatrix`, how its `values` are read.
+    ///
+    /// # Variant
+    ///
+    /// - `Matrix` : `matrix`, the default, a 4x5 matrix of 20 numbers.
+    /// - `Saturate` : `saturate`, a single saturation factor.
+    /// - `HueRotate` : `hueRotate`, a single angle in degrees.
+    /// - `LuminanceToAlpha` : `luminanceToAlpha`, taking no values.
+    /// - `Other(String)` : any other value, kept verbatim.
+    pub enum ColorMatrixType {
+        Matrix => "matrix",
+        Saturate => "saturate",
+        HueRotate => "hueRotate",
+        LuminanceToAlpha => "luminanceToAlpha",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_svg, svg_types::*};
+
+    #[test]
+    fn keywords_test() {
+        for (keyword, value) in [
+            ("nonzero", FillRule::NonZero),
+            ("evenodd", FillRule::EvenOdd),
+        ] {
+            assert_eq!(FillRule::from(keyword), value);
+            assert_eq!(value.as_str(), keyword);
+        }
+        for (keyword, value) in [
+            ("butt", LineCap::Butt),
+            ("round", LineCap::Round),
+            ("square", LineCap::Square),
+        ] {
+            assert_eq!(LineCap::from(keyword), value);
+            assert_eq!(value.as_str(), keyword);
+        }
+        for (keyword, value) in [
+            ("miter", LineJoin::Miter),
+            ("round", LineJoin::Round),
+            ("bevel", LineJoin::Bevel),
+        ] {
+            assert_eq!(LineJoin::from(keyword), value);
+            assert_eq!(value.as_str(), keyword);
+        }
+        for (keyword, value) in [
+            ("pad", SpreadMethod::Pad),
+            ("reflect", SpreadMethod::Reflect),
+            ("repeat", SpreadMethod::Repeat),
+        ] {
+            assert_eq!(SpreadMethod::from(keyword), value);
+            assert_eq!(value.as_str(), keyword);
+        }
+        for (keyword, value) in [
+            ("userSpaceOnUse", Units::UserSpaceOnUse),
+            ("objectBoundingBox", Units::ObjectBoundingBox),
+        ] {
+            assert_eq!(Units::from(keyword), value);
+            assert_eq!(value.as_str(), keyword);
+        }
+        for (keyword, value) in [
+            ("matrix", ColorMatrixType::Matrix),
+            ("saturate", ColorMatrixType::Saturate),
+            ("hueRotate", ColorMatrixType::HueRotate),
+            ("luminanceToAlpha", ColorMatrixType::LuminanceToAlpha),
+        ] {
+            assert_eq!(ColorMatrixType::from(keyword), value);
+            assert_eq!(value.as_str(), keyword);
+        }
+        assert_eq!(
+            FillRule::from("EvenOdd"),
+            FillRule::Other("EvenOdd".to_string())
+        );
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+            <path d="M 0 0" fill-rule="evenodd" stroke-linecap="round" stroke-linejoin="bevel"/>
+            <path d="M 0 0" fill-rule="evenod" stroke-linecap="" stroke-linejoin="miter-clip"/>
+        </svg>"##;
+        let svg = parse_svg(svg_str).unwrap();
+        let SvgElement::Path(known) = &svg.elements[0] else {
+            panic!()
+        };
+        assert_eq!(known.fill_rule, Some(FillRule::EvenOdd));
+        assert_eq!(known.stroke_linecap, Some(LineCap::Round));
+        assert_eq!(known.stroke_linejoin, Some(LineJoin::Bevel));
+        let SvgElement::Path(unknown) = &svg.elements[1] else {
+            panic!()
+        };
+        assert_eq!(
+            unknown.fill_rule,
+            Some(FillRule::Other("evenod".to_string()))
+        );
+        assert_eq!(unknown.stroke_linecap, Some(LineCap::Other(String::new())));
+        assert_eq!(
+            unknown.stroke_linejoin,
+            Some(LineJoin::Other("miter-clip".to_string()))
+        );
+
+        assert_eq!(parse_svg(&svg.to_svg_string()).unwrap(), svg);
+        assert_eq!(svg_from_json(&svg_to_json(&svg)).unwrap(), svg);
+    }
+}
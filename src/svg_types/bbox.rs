@@ -0,0 +1,664 @@
+use std::collections::HashMap;
+
+use super::{references::url_targets, *};
+use crate::passes::geometry::{parse_path, parse_transform, Matrix, Point, Segment};
+
+/// An axis-aligned rectangle, in the user units of the document.
+///
+/// # Field
+///
+/// - `min_x`, `min_y` : the top-left corner.
+/// - `max_x`, `max_y` : the bottom-right corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BBox {
+    pub min_x: f64,
+
+    pub min_y: f64,
+
+    pub max_x: f64,
+
+    pub max_y: f64,
+}
+
+impl BBox {
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// Whether the two boxes have a point in common, their edges included.
+    pub fn intersects(&self, other: &BBox) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
+    /// The box where `self` and `other` overlap, `None` when they do not.
+    pub fn intersection(self, other: BBox) -> Option<BBox> {
+        let bbox = BBox {
+            min_x: self.min_x.max(other.min_x),
+            min_y: self.min_y.max(other.min_y),
+            max_x: self.max_x.min(other.max_x),
+            max_y: self.max_y.min(other.max_y),
+        };
+        (bbox.min_x <= bbox.max_x && bbox.min_y <= bbox.max_y).then_some(bbox)
+    }
+
+    /// The smallest box containing both `self` and `other`.
+    pub fn union(self, other: BBox) -> BBox {
+        BBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    fn from_point((x, y): Point) -> BBox {
+        BBox {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        }
+    }
+
+    fn include(&mut self, point: Point) {
+        *self = self.union(BBox::from_point(point));
+    }
+
+    fn expand(self, margin: f64) -> BBox {
+        BBox {
+            min_x: self.min_x - margin,
+            min_y: self.min_y - margin,
+            max_x: self.max_x + margin,
+            max_y: self.max_y + margin,
+        }
+    }
+}
+
+impl Svg {
+    /// Computes the box enclosing the ink of the document: paths, used symbols, images and
+    /// rectangles, with the transforms of their groups applied and cut to the [`ClipPath`] of
+    /// the clipped groups, in the [`Units`] of its `clip_path_units`.
+    ///
+    /// Straight and Bézier segments are bounded exactly, arcs conservatively, and stroked paths
+    /// are widened by half their stroke width in every direction, so the box may be slightly
+    /// larger than the ink but never smaller.
+    ///
+    /// # Return value
+    ///
+    /// - `Some(BBox)` : the box, in the coordinates of the `viewBox`.
+    /// - `None` : the document has no content, holds text, whose extent depends on its font, or
+    ///   some transform, path data or size cannot be parsed, in which case no box can be
+    ///   guaranteed to hold the ink.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::parse_svg;
+    ///
+    /// let svg = parse_svg(
+    ///     "<svg class='typst-doc' viewBox='0 0 100 100' width='100pt' height='100pt'>\
+    ///      <g transform='translate(10 20)'><path d='M 0 0 L 30 40'/></g></svg>",
+    /// )
+    /// .unwrap();
+    /// let bbox = svg.content_bbox().unwrap();
+    /// assert_eq!((bbox.min_x, bbox.min_y, bbox.max_x, bbox.max_y), (10.0, 20.0, 40.0, 60.0));
+    /// ```
+    pub fn content_bbox(&self) -> Option<BBox> {
+        let targets = self.targets();
+        let mut bounds = Bounds {
+            targets: &targets,
+            bbox: None,
+        };
+        for element in &self.elements {
+            match element {
+                SvgElement::Path(path) => bounds.path(path, &Matrix::IDENTITY)?,
+                SvgElement::G(g) => bounds.g(g, Matrix::IDENTITY)?,
+                SvgElement::Rect(rect) => bounds.rect(rect)?,
+                SvgElement::Defs(_) => {}
+            }
+        }
+        bounds.bbox
+    }
+
+    /// The box of every group of the document whose box can be computed, with the transforms of
+    /// its ancestors applied, in document order. A group is designated by its position in the
+    /// tree: its index among the top-level elements, then among the children of each group on
+    /// the way to it.
+    pub(crate) fn group_bboxes(&self) -> Vec<(Vec<usize>, BBox)> {
+        let targets = self.targets();
+        let mut groups = Vec::new();
+        for (index, element) in self.elements.iter().enumerate() {
+            if let SvgElement::G(g) = element {
+                group_bboxes(&targets, g, Matrix::IDENTITY, &mut vec![index], &mut groups);
+            }
+        }
+        groups
+    }
+
+    /// The elements whose ink lies entirely outside of `region`, in document order, designated
+    /// by their position like in [`Svg::group_bboxes`]. The children of a group listed are not
+    /// listed on their own. Text and the elements whose box cannot be computed are never outside,
+    /// nor are the `defs`.
+    pub(crate) fn elements_outside(&self, region: BBox) -> Vec<Vec<usize>> {
+        let targets = self.targets();
+        let mut outside = Outside {
+            targets: &targets,
+            region,
+            position: Vec::new(),
+            found: Vec::new(),
+        };
+        for (index, element) in self.elements.iter().enumerate() {
+            outside.position.push(index);
+            match element {
+                SvgElement::Path(path) => {
+                    outside.check(|bounds| bounds.path(path, &Matrix::IDENTITY));
+                }
+                SvgElement::Rect(rect) => {
+                    outside.check(|bounds| bounds.rect(rect));
+                }
+                SvgElement::G(g) => outside.g(g, Matrix::IDENTITY),
+                SvgElement::Defs(_) => {}
+            }
+            outside.position.pop();
+        }
+        outside.found
+    }
+
+    /// The definitions that the elements refer to.
+    fn targets(&self) -> Targets<'_> {
+        let mut targets = Targets {
+            viewport: self
+                .view_box_parsed()
+                .ok()
+                .map(|view_box| (view_box.width, view_box.height)),
+            ..Default::default()
+        };
+        for element in &self.elements {
+            if let SvgElement::Defs(defs) = element {
+                for symbol in &defs.elements {
+                    targets.symbols.entry(symbol.id.as_str()).or_insert(symbol);
+                }
+                for definition in &defs.definitions {
+                    if let Definition::ClipPath(clip) = definition {
+                        targets.clips.entry(clip.id.as_str()).or_insert(clip);
+                    }
+                }
+            }
+        }
+        targets
+    }
+}
+
+impl Path {
+    /// The box of the ink of the path, in its own coordinates, widened by its stroke. `None` when
+    /// the path data or stroke width cannot be parsed, or the path draws nothing.
+    pub(crate) fn bbox(&self) -> Option<BBox> {
+        let mut bounds = Bounds {
+            targets: &Targets::default(),
+            bbox: None,
+        };
+        bounds.path(self, &Matrix::IDENTITY)?;
+        bounds.bbox
+    }
+}
+
+/// Adds the box of `g`, at `position`, and of the groups inside it to `groups`.
+fn group_bboxes(
+    targets: &Targets,
+    g: &G,
+    matrix: Matrix,
+    position: &mut Vec<usize>,
+    groups: &mut Vec<(Vec<usize>, BBox)>,
+) {
+    let mut bounds = Bounds {
+        targets,
+        bbox: None,
+    };
+    if bounds.g(g, matrix).is_some() {
+        if let Some(bbox) = bounds.bbox {
+            groups.push((position.clone(), bbox));
+        }
+    }
+    let Some(matrix) = (match g.transform.as_deref() {
+        Some(transform) => parse_transform(transform).map(|own| matrix.then(own)),
+        None => Some(matrix),
+    }) else {
+        return;
+    };
+    for (index, element) in g.elements.iter().flatten().enumerate() {
+        if let GEle::G(inner) = element {
+            position.push(index);
+            group_bboxes(targets, inner, matrix, position, groups);
+            position.pop();
+        }
+    }
+}
+
+/// Collects the positions of the elements outside of a region, for [`Svg::elements_outside`].
+struct Outside<'a> {
+    targets: &'a Targets<'a>,
+    region: BBox,
+    position: Vec<usize>,
+    found: Vec<Vec<usize>>,
+}
+
+impl Outside<'_> {
+    /// Lists the current position when the ink measured by `measure` lies outside the region,
+    /// returning whether it does.
+    fn check(&mut self, measure: impl FnOnce(&mut Bounds) -> Option<()>) -> bool {
+        let mut bounds = Bounds {
+            targets: self.targets,
+            bbox: None,
+        };
+        let outside = measure(&mut bounds).is_some()
+            && bounds
+                .bbox
+                .is_some_and(|bbox| !bbox.intersects(&self.region));
+        if outside {
+            self.found.push(self.position.clone());
+        }
+        outside
+    }
+
+    fn g(&mut self, g: &G, matrix: Matrix) {
+        if self.check(|bounds| bounds.g(g, matrix)) {
+            return;
+        }
+        let Some(matrix) = (match g.transform.as_deref() {
+            Some(transform) => parse_transform(transform).map(|own| matrix.then(own)),
+            None => Some(matrix),
+        }) else {
+            return;
+        };
+        for (index, element) in g.elements.iter().flatten().enumerate() {
+            self.position.push(index);
+            match element {
+                GEle::G(inner) => self.g(inner, matrix),
+                GEle::Path(path) => {
+                    self.check(|bounds| bounds.path(path, &matrix));
+                }
+                GEle::Image(image) => {
+                    self.check(|bounds| bounds.image(image, &matrix));
+                }
+                GEle::Use(uuse) => {
+                    self.check(|bounds| bounds.uuse(uuse, &matrix));
+                }
+                GEle::Text(_) | GEle::Switch(_) => {}
+            }
+            self.position.pop();
+        }
+    }
+}
+
+/// The symbols and the clip paths, by `id`, and the size of the viewport of the document, which
+/// the percentages of a `use` are relative to.
+#[derive(Default)]
+struct Targets<'a> {
+    symbols: HashMap<&'a str, &'a Symbol>,
+    clips: HashMap<&'a str, &'a ClipPath>,
+    viewport: Option<(f64, f64)>,
+}
+
+struct Bounds<'a> {
+    targets: &'a Targets<'a>,
+    bbox: Option<BBox>,
+}
+
+impl Bounds<'_> {
+    fn add(&mut self, bbox: BBox) {
+        self.bbox = Some(match self.bbox {
+            Some(current) => current.union(bbox),
+            None => bbox,
+        });
+    }
+
+    fn g(&mut self, g: &G, matrix: Matrix) -> Option<()> {
+        let matrix = match g.transform.as_deref() {
+            Some(transform) => matrix.then(parse_transform(transform)?),
+            None => matrix,
+        };
+        // A reference to no clip path is ignored, like by the browsers
+        let clip = url_targets(g.clip_path.as_deref().unwrap_or_default())
+            .first()
+            .and_then(|id| self.targets.clips.get(id));
+        let Some(clip) = clip else {
+            return self.children(g, matrix);
+        };
+
+        // Both boxes in the coordinates of the group, where the clip path is placed
+        let mut content = Bounds {
+            targets: self.targets,
+            bbox: None,
+        };
+        content.children(g, Matrix::IDENTITY)?;
+        let Some(content) = content.bbox else {
+            return Some(());
+        };
+        let units = match clip.clip_path_units {
+            Some(Units::ObjectBoundingBox) => Matrix {
+                a: content.width(),
+                d: content.height(),
+                ..Matrix::translate(content.min_x, content.min_y)
+            },
+            _ => Matrix::IDENTITY,
+        };
+        let units = match clip.transform.as_deref() {
+            Some(transform) => units.then(parse_transform(transform)?),
+            None => units,
+        };
+        let mut region = Bounds {
+            targets: self.targets,
+            bbox: None,
+        };
+        for path in &clip.elements {
+            region.path(path, &units)?;
+        }
+        // An empty clip path hides the whole group
+        if let Some(visible) = region.bbox.and_then(|region| region.intersection(content)) {
+            self.corners(
+                &matrix,
+                visible.min_x,
+                visible.min_y,
+                visible.width(),
+                visible.height(),
+            );
+        }
+        Some(())
+    }
+
+    /// Adds the boxes of the children of `g`, placed by `matrix`.
+    fn children(&mut self, g: &G, matrix: Matrix) -> Option<()> {
+        for element in g.elements.iter().flatten() {
+            match element {
+                GEle::G(g) => self.g(g, matrix)?,
+                GEle::Path(path) => self.path(path, &matrix)?,
+                GEle::Image(image) => self.image(image, &matrix)?,
+                // The extent of text depends on its font, and of a switch on the branch rendered
+                GEle::Text(_) | GEle::Switch(_) => return None,
+                GEle::Use(uuse) => self.uuse(uuse, &matrix)?,
+            }
+        }
+        Some(())
+    }
+
+    fn uuse(&mut self, uuse: &Use, matrix: &Matrix) -> Option<()> {
+        let mut matrix = match uuse.transform.as_deref() {
+            Some(transform) => matrix.then(parse_transform(transform)?),
+            None => *matrix,
+        };
+        matrix = matrix.then(Matrix::translate(uuse.x.trim().parse().ok()?, 0.0));
+        let id = uuse.href.strip_prefix('#').unwrap_or(&uuse.href);
+        // A dangling reference draws nothing
+        let Some(symbol) = self.targets.symbols.get(id) else {
+            return Some(());
+        };
+        let matrix = matrix.then(symbol.viewport_matrix(uuse, self.targets.viewport)?);
+        match &symbol.element {
+            SymbolEle::Path(path) => self.path(path, &matrix),
+            SymbolEle::Image(image) => self.image(image, &matrix),
+        }
+    }
+
+    fn path(&mut self, path: &Path, matrix: &Matrix) -> Option<()> {
+        let mut bbox: Option<BBox> = None;
+        let mut current = (0.0, 0.0);
+        let mut start = (0.0, 0.0);
+        for segment in parse_path(&path.d)? {
+            let segment_bbox = match segment.transform(matrix) {
+                Segment::MoveTo(to) => {
+                    // A lone move draws nothing
+                    current = to;
+                    start = to;
+                    continue;
+                }
+                Segment::LineTo(to) => {
+                    let mut bbox = BBox::from_point(current);
+                    bbox.include(to);
+                    current = to;
+                    bbox
+                }
+                Segment::QuadTo(c, to) => {
+                    let bbox = quad_bbox(current, c, to);
+                    current = to;
+                    bbox
+                }
+                Segment::CubicTo(c1, c2, to) => {
+                    let bbox = cubic_bbox(current, c1, c2, to);
+                    current = to;
+                    bbox
+                }
+                Segment::ArcTo { rx, ry, to, .. } => {
+                    // The arc stays within its diameter (or the chord, when the radii are too
+                    // small and get scaled up) of its start
+                    let chord = ((to.0 - current.0).powi(2) + (to.1 - current.1).powi(2)).sqrt();
+                    let reach = (2.0 * rx.abs().max(ry.abs())).max(chord);
+                    let mut bbox = BBox::from_point(current).expand(reach);
+                    bbox.include(to);
+                    current = to;
+                    bbox
+                }
+                Segment::Close => {
+                    let mut bbox = BBox::from_point(current);
+                    bbox.include(start);
+                    current = start;
+                    bbox
+                }
+            };
+            bbox = Some(match bbox {
+                Some(bbox) => bbox.union(segment_bbox),
+                None => segment_bbox,
+            });
+        }
+
+        if let Some(mut bbox) = bbox {
+            let stroked = path
+                .stroke
+                .as_deref()
+                .is_some_and(|stroke| stroke != "none");
+            if stroked {
+                let width = match path.stroke_width.as_deref() {
+                    Some(width) => width.trim().parse::<f64>().ok()?,
+                    None => 1.0,
+                };
+                bbox = bbox.expand(width / 2.0 * matrix.determinant().abs().sqrt());
+            }
+            self.add(bbox);
+        }
+        Some(())
+    }
+
+    fn image(&mut self, image: &Image, matrix: &Matrix) -> Option<()> {
+        let matrix = match image.transform.as_deref() {
+            Some(transform) => matrix.then(parse_transform(transform)?),
+            None => *matrix,
+        };
+        let (width, height) = (
+            image.width.trim().parse::<f64>().ok()?,
+            image.height.trim().parse::<f64>().ok()?,
+        );
+        self.corners(&matrix, 0.0, 0.0, width, height);
+        Some(())
+    }
+
+    fn rect(&mut self, rect: &Rect) -> Option<()> {
+        let [x, y, width, height] = [&rect.x, &rect.y, &rect.width, &rect.height]
+            .map(|value| value.trim().parse::<f64>().ok());
+        self.corners(&Matrix::IDENTITY, x?, y?, width?, height?);
+        Some(())
+    }
+
+    /// Adds the transformed corners of a rectangle.
+    fn corners(&mut self, matrix: &Matrix, x: f64, y: f64, width: f64, height: f64) {
+        let mut bbox = BBox::from_point(matrix.apply((x, y)));
+        for corner in [(x + width, y), (x, y + height), (x + width, y + height)] {
+            bbox.include(matrix.apply(corner));
+        }
+        self.add(bbox);
+    }
+}
+
+fn quad_bbox(p0: Point, p1: Point, p2: Point) -> BBox {
+    let mut bbox = BBox::from_point(p0);
+    bbox.include(p2);
+    let at = |t: f64| {
+        let u = 1.0 - t;
+        let coordinate = |a: f64, b: f64, c: f64| u * u * a + 2.0 * u * t * b + t * t * c;
+        (coordinate(p0.0, p1.0, p2.0), coordinate(p0.1, p1.1, p2.1))
+    };
+    for (a, b, c) in [(p0.0, p1.0, p2.0), (p0.1, p1.1, p2.1)] {
+        let denominator = a - 2.0 * b + c;
+        if denominator != 0.0 {
+            let t = (a - b) / denominator;
+            if t > 0.0 && t < 1.0 {
+                bbox.include(at(t));
+            }
+        }
+    }
+    bbox
+}
+
+fn cubic_bbox(p0: Point, p1: Point, p2: Point, p3: Point) -> BBox {
+    let mut bbox = BBox::from_point(p0);
+    bbox.include(p3);
+    let at = |t: f64| {
+        let u = 1.0 - t;
+        let coordinate = |a: f64, b: f64, c: f64, d: f64| {
+            u * u * u * a + 3.0 * u * u * t * b + 3.0 * u * t * t * c + t * t * t * d
+        };
+        (
+            coordinate(p0.0, p1.0, p2.0, p3.0),
+            coordinate(p0.1, p1.1, p2.1, p3.1),
+        )
+    };
+    for (a, b, c, d) in [(p0.0, p1.0, p2.0, p3.0), (p0.1, p1.1, p2.1, p3.1)] {
+        // Roots of the derivative, a quadratic in t
+        let qa = 3.0 * (-a + 3.0 * b - 3.0 * c + d);
+        let qb = 6.0 * (a - 2.0 * b + c);
+        let qc = 3.0 * (b - a);
+        let roots = if qa.abs() < 1e-12 {
+            if qb.abs() < 1e-12 {
+                vec![]
+            } else {
+                vec![-qc / qb]
+            }
+        } else {
+            let discriminant = qb * qb - 4.0 * qa * qc;
+            if discriminant < 0.0 {
+                vec![]
+            } else {
+                let root = discriminant.sqrt();
+                vec![(-qb + root) / (2.0 * qa), (-qb - root) / (2.0 * qa)]
+            }
+        };
+        for t in roots {
+            if t > 0.0 && t < 1.0 {
+                bbox.include(at(t));
+            }
+        }
+    }
+    bbox
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compat::render_html, parse_svg, svg_to_rsx};
+    use std::fs;
+
+    fn assert_bbox_eq(actual: BBox, expected: (f64, f64, f64, f64)) {
+        let actual_tuple = (actual.min_x, actual.min_y, actual.max_x, actual.max_y);
+        for (a, e) in [
+            (actual.min_x, expected.0),
+            (actual.min_y, expected.1),
+            (actual.max_x, expected.2),
+            (actual.max_y, expected.3),
+        ] {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual_tuple, expected);
+        }
+    }
+
+    #[test]
+    fn formula_test() {
+        let svg = parse_svg(&fs::read_to_string("./test/formula.svg").unwrap()).unwrap();
+        assert_bbox_eq(svg.content_bbox().unwrap(), (10.0, 3.75, 26.2, 17.5));
+    }
+
+    #[test]
+    fn stroke_and_arc_test() {
+        let svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 100 100' width='100pt' height='100pt'>\
+             <path d='M 10 10 L 20 10' stroke='#000000' stroke-width='2'/></svg>",
+        )
+        .unwrap();
+        assert_bbox_eq(svg.content_bbox().unwrap(), (9.0, 9.0, 21.0, 11.0));
+
+        // A half circle of radius 5 below the chord from (0, 0) to (10, 0)
+        let svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 100 100' width='100pt' height='100pt'>\
+             <path d='M 0 0 A 5 5 0 0 0 10 0'/></svg>",
+        )
+        .unwrap();
+        let bbox = svg.content_bbox().unwrap();
+        assert!(bbox.min_x <= 0.0 && bbox.max_x >= 10.0 && bbox.max_y >= 5.0);
+    }
+
+    /// The box is the same once the document went through the XML and the RSX, which would not
+    /// keep it were the units of the clip paths lost.
+    #[test]
+    fn clip_test() {
+        let svg = parse_svg(&fs::read_to_string("./test/clip.svg").unwrap()).unwrap();
+        let expected = (5.0, 0.0, 50.0, 25.0);
+        assert_bbox_eq(svg.content_bbox().unwrap(), expected);
+
+        let written = parse_svg(&svg.to_svg_string()).unwrap();
+        assert_bbox_eq(written.content_bbox().unwrap(), expected);
+        let html = render_html(svg_to_rsx(&svg));
+        assert!(
+            html.contains(r#"clipPathUnits="objectBoundingBox""#),
+            "{}",
+            html
+        );
+        // The root is rendered without the class that the parser requires
+        let rendered = parse_svg(&html.replacen("<svg ", "<svg class=\"typst-doc\" ", 1)).unwrap();
+        assert_bbox_eq(rendered.content_bbox().unwrap(), expected);
+
+        // Read in the user space, the second clip path keeps a corner of the group only
+        let mut user_space = svg.clone();
+        let SvgElement::Defs(defs) = &mut user_space.elements[3] else {
+            panic!()
+        };
+        let Definition::ClipPath(clip) = &mut defs.definitions[1] else {
+            panic!()
+        };
+        clip.clip_path_units = None;
+        assert_bbox_eq(user_space.content_bbox().unwrap(), (5.0, 0.0, 40.5, 25.0));
+
+        // A clip path missing is ignored, and one without paths hides its group
+        let mut unclipped = svg;
+        let SvgElement::Defs(defs) = &mut unclipped.elements[3] else {
+            panic!()
+        };
+        defs.definitions[1] = Definition::ClipPath(ClipPath {
+            id: "c1".to_string(),
+            ..Default::default()
+        });
+        defs.definitions.remove(0);
+        assert_bbox_eq(unclipped.content_bbox().unwrap(), (0.0, 0.0, 40.0, 30.0));
+    }
+
+    #[test]
+    fn empty_test() {
+        let svg = parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 1 1' width='1pt' height='1pt'><g/></svg>",
+        )
+        .unwrap();
+        assert_eq!(svg.content_bbox(), None);
+    }
+}
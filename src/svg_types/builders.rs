@@ -0,0 +1,168 @@
+use super::*;
+
+impl Svg {
+    /// Creates an empty document of class `typst-doc`, the class Typst gives its output.
+    ///
+    /// # parameter
+    ///
+    /// - `width`, `height` : the size of the document, with their unit (e.g. `"595.28pt"`).
+    /// - `view_box` : the `viewBox` attribute, such as `"0 0 595.28 841.89"`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::svg_types::{G, Path, Svg, SvgElement};
+    ///
+    /// let mut svg = Svg::new("10pt", "10pt", "0 0 10 10");
+    /// svg.elements.push(SvgElement::G(Box::new(G::with_elements(vec![]))));
+    /// svg.elements.push(SvgElement::Path(Box::new(Path::with_d("M 0 0 L 10 10"))));
+    /// assert_eq!(svg.elements.len(), 2);
+    /// ```
+    pub fn new(
+        width: impl Into<String>,
+        height: impl Into<String>,
+        view_box: impl Into<String>,
+    ) -> Self {
+        Svg {
+            class: "typst-doc".to_string(),
+            width: width.into(),
+            height: height.into(),
+            view_box: view_box.into(),
+            elements: Vec::new(),
+        }
+    }
+}
+
+impl Path {
+    /// Creates a path drawing `d`, with every other attribute absent.
+    pub fn with_d(d: impl Into<String>) -> Self {
+        Path {
+            d: d.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl G {
+    /// Creates an empty group without any attribute.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a group without any attribute holding `elements`.
+    pub fn with_elements(elements: Vec<GEle>) -> Self {
+        G {
+            elements: Some(elements),
+            ..Default::default()
+        }
+    }
+}
+
+impl Use {
+    /// Creates a reference to `href` (e.g. `"#glyph0"`) placed at `x`, without fill, size or
+    /// transform.
+    pub fn new(href: impl Into<Arc<str>>, x: impl Into<String>) -> Self {
+        Use {
+            fill: None,
+            x: x.into(),
+            width: None,
+            height: None,
+            fill_rule: None,
+            href: href.into(),
+            transform: None,
+        }
+    }
+}
+
+impl Image {
+    /// Creates an image of the given size showing `href`, stretched to that size like Typst does
+    /// with `preserveAspectRatio="none"`.
+    pub fn new(
+        width: impl Into<String>,
+        height: impl Into<String>,
+        href: impl Into<String>,
+    ) -> Self {
+        Image {
+            width: width.into(),
+            height: height.into(),
+            preserve_aspect_ratio: "none".to_string(),
+            href: href.into(),
+            transform: None,
+        }
+    }
+}
+
+impl Rect {
+    /// Creates a rectangle without fill at `x`, `y` of size `width` × `height`, in user units.
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Rect {
+            x: x.to_string(),
+            y: y.to_string(),
+            width: width.to_string(),
+            height: height.to_string(),
+            fill: None,
+        }
+    }
+}
+
+impl Defs {
+    /// Creates a `defs` element with the given id holding `symbols`.
+    pub fn new(id: impl Into<String>, symbols: Vec<Symbol>) -> Self {
+        Defs {
+            id: id.into(),
+            elements: symbols,
+            definitions: Vec::new(),
+        }
+    }
+}
+
+impl Symbol {
+    /// Creates a symbol with `overflow="visible"` and no `viewBox`, as Typst writes its glyphs.
+    pub fn new(id: impl Into<String>, element: SymbolEle) -> Self {
+        Symbol {
+            id: id.into(),
+            overflow: "visible".to_string(),
+            view_box: None,
+            preserve_aspect_ratio: None,
+            element,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    #[test]
+    fn builders_test() {
+        let mut svg = Svg::new("20pt", "10pt", "0 0 20 10");
+        svg.elements
+            .push(SvgElement::G(Box::new(G::with_elements(vec![
+                GEle::Use(Box::new(Use::new("#glyph0", "0"))),
+                GEle::G(Box::new(G::new())),
+            ]))));
+        svg.elements.push(SvgElement::Defs(Defs::new(
+            "glyph",
+            vec![Symbol::new(
+                "glyph0",
+                SymbolEle::Path(Path::with_d("M 0 0 L 1 1 Z")),
+            )],
+        )));
+        svg.elements
+            .push(SvgElement::Rect(Rect::new(0.0, 0.0, 20.0, 10.5)));
+
+        let parsed = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 20 10" width="20pt" height="10pt">
+                <g><use href="#glyph0" x="0"/><g></g></g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path d="M 0 0 L 1 1 Z"/></symbol>
+                </defs>
+                <rect x="0" y="0" width="20" height="10.5"/>
+            </svg>"##,
+        )
+        .unwrap();
+        assert_eq!(svg, parsed);
+        assert_eq!(svg.clone(), svg);
+    }
+}
@@ -0,0 +1,280 @@
+//! Parsing of the paint colors found in `fill` and `stroke` attributes.
+//!
+//! Typst writes its colors as `#rrggbb`, or `#rrggbbaa` when they are translucent, but documents
+//! edited by hand or transformed by other tools may use any CSS form. [`Color`] reads the common
+//! ones into their RGBA components, so that colors can be compared by value.
+//!
+//! # Example
+//!
+//! ```rust
+//! use typst_2_rsx::svg_types::color::Color;
+//!
+//! let color: Color = "#FFF".parse().unwrap();
+//! assert_eq!(color, "rgb(255, 255, 255)".parse().unwrap());
+//! assert_eq!(color, "white".parse().unwrap());
+//! assert_eq!(color.to_hex().as_deref(), Some("#ffffff"));
+//! assert_eq!("none".parse::<Color>().unwrap(), Color::None);
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use crate::error::Error;
+
+/// A paint color.
+///
+/// Two colors are equal when they denote the same value, whatever the syntax they were parsed
+/// from: `#FFF`, `#ffffff`, `#ffffffff`, `white` and `rgb(100% 100% 100%)` are all the same
+/// `Rgba`.
+///
+/// # Variant
+///
+/// - `Rgba { r, g, b, a }` : a color with its sRGB components, `a` being 255 for an opaque color.
+/// - `None` : the `none` keyword, no paint at all.
+/// - `CurrentColor` : the `currentColor` keyword, the CSS `color` of the element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Rgba { r: u8, g: u8, b: u8, a: u8 },
+
+    None,
+
+    CurrentColor,
+}
+
+/// The CSS named colors sharing their name with a predefined Typst color, with their CSS value.
+///
+/// Typst itself writes hexadecimal colors; the names show up in documents written or edited by
+/// hand.
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("black", [0, 0, 0]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("silver", [192, 192, 192]),
+    ("white", [255, 255, 255]),
+    ("navy", [0, 0, 128]),
+    ("blue", [0, 0, 255]),
+    ("aqua", [0, 255, 255]),
+    ("cyan", [0, 255, 255]),
+    ("teal", [0, 128, 128]),
+    ("purple", [128, 0, 128]),
+    ("fuchsia", [255, 0, 255]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("red", [255, 0, 0]),
+    ("orange", [255, 165, 0]),
+    ("yellow", [255, 255, 0]),
+    ("olive", [128, 128, 0]),
+    ("green", [0, 128, 0]),
+    ("lime", [0, 255, 0]),
+];
+
+impl Color {
+    /// An opaque color.
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color::Rgba { r, g, b, a: 255 }
+    }
+
+    /// Returns the red, green and blue components, or `None` for the keywords.
+    pub fn to_rgb(&self) -> Option<[u8; 3]> {
+        match *self {
+            Color::Rgba { r, g, b, .. } => Some([r, g, b]),
+            Color::None | Color::CurrentColor => None,
+        }
+    }
+
+    /// Returns the WCAG relative luminance, from `0.0` for black to `1.0` for white, ignoring
+    /// the alpha channel. The keywords have no luminance.
+    pub fn luminance(&self) -> Option<f32> {
+        let [r, g, b] = self.to_rgb()?.map(|channel| {
+            let channel = channel as f32 / 255.0;
+            if channel <= 0.04045 {
+                channel / 12.92
+            } else {
+                ((channel + 0.055) / 1.055).powf(2.4)
+            }
+        });
+        Some(0.2126 * r + 0.7152 * g + 0.0722 * b)
+    }
+
+    /// Returns the color as `#rrggbb`, or `#rrggbbaa` when it is not opaque, like Typst writes
+    /// it. The keywords have no hexadecimal form.
+    pub fn to_hex(&self) -> Option<String> {
+        match *self {
+            Color::Rgba { r, g, b, a: 255 } => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+            Color::Rgba { r, g, b, a } => Some(format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)),
+            Color::None | Color::CurrentColor => None,
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()` and `rgba()` with numbers or
+    /// percentages, the named colors shared with Typst, `transparent`, `none` and `currentColor`.
+    /// Keywords and function names are case-insensitive.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(Color)` : the parsed color.
+    /// - `Err(Error)` : [`Error::InvalidColor`] for any other value, such as `url(#gradient)`.
+    fn from_str(color: &str) -> Result<Self, Self::Err> {
+        parse(color.trim()).ok_or_else(|| Error::InvalidColor(color.to_string()))
+    }
+}
+
+impl fmt::Display for Color {
+    /// Writes the hexadecimal form of the color, or the keyword.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Rgba { .. } => f.write_str(&self.to_hex().unwrap_or_default()),
+            Color::None => f.write_str("none"),
+            Color::CurrentColor => f.write_str("currentColor"),
+        }
+    }
+}
+
+fn parse(color: &str) -> Option<Color> {
+    if let Some(hex) = color.strip_prefix('#') {
+        return parse_hex(hex);
+    }
+    if color.eq_ignore_ascii_case("none") {
+        return Some(Color::None);
+    }
+    if color.eq_ignore_ascii_case("currentColor") {
+        return Some(Color::CurrentColor);
+    }
+    if color.eq_ignore_ascii_case("transparent") {
+        return Some(Color::Rgba {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        });
+    }
+    if let Some((_, [r, g, b])) = NAMED_COLORS
+        .iter()
+        .find(|(name, _)| color.eq_ignore_ascii_case(name))
+    {
+        return Some(Color::rgb(*r, *g, *b));
+    }
+    parse_function(color)
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    let digits = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<Vec<_>>>()?;
+    let [r, g, b, a] = match digits[..] {
+        [r, g, b] => [r * 17, g * 17, b * 17, 255],
+        [r, g, b, a] => [r * 17, g * 17, b * 17, a * 17],
+        [r1, r2, g1, g2, b1, b2] => [r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, 255],
+        [r1, r2, g1, g2, b1, b2, a1, a2] => {
+            [r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2, a1 * 16 + a2]
+        }
+        _ => return None,
+    };
+    Some(Color::Rgba { r, g, b, a })
+}
+
+/// Parses `rgb()` or `rgba()`, with the legacy comma-separated syntax or the modern one where the
+/// alpha follows a `/`.
+fn parse_function(color: &str) -> Option<Color> {
+    let open = color.find('(')?;
+    let name = &color[..open];
+    if !(name.eq_ignore_ascii_case("rgb") || name.eq_ignore_ascii_case("rgba")) {
+        return None;
+    }
+    let arguments = color[open + 1..].strip_suffix(')')?;
+    let values: Vec<&str> = arguments
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .collect();
+    let channel = |value: &str| {
+        let value = match value.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok()? * 2.55,
+            None => value.parse::<f32>().ok()?,
+        };
+        Some(value.round().clamp(0.0, 255.0) as u8)
+    };
+    let alpha = |value: &str| {
+        let value = match value.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+            None => value.parse::<f32>().ok()?,
+        };
+        Some((value.clamp(0.0, 1.0) * 255.0).round() as u8)
+    };
+    match values[..] {
+        [r, g, b] => Some(Color::rgb(channel(r)?, channel(g)?, channel(b)?)),
+        [r, g, b, a] => Some(Color::Rgba {
+            r: channel(r)?,
+            g: channel(g)?,
+            b: channel(b)?,
+            a: alpha(a)?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let rgba = |r, g, b, a| Some(Color::Rgba { r, g, b, a });
+        for (color, expected) in [
+            // As written by Typst
+            ("#000000", Some(Color::rgb(0, 0, 0))),
+            ("#ffffff", Some(Color::rgb(255, 255, 255))),
+            ("#4b69c6", Some(Color::rgb(0x4b, 0x69, 0xc6))),
+            ("#d73a49", Some(Color::rgb(0xd7, 0x3a, 0x49))),
+            ("#fffd11a1", rgba(0xff, 0xfd, 0x11, 0xa1)),
+            ("none", Some(Color::None)),
+            // Other syntaxes
+            ("#000", Some(Color::rgb(0, 0, 0))),
+            ("#fFf", Some(Color::rgb(255, 255, 255))),
+            ("#f008", rgba(255, 0, 0, 0x88)),
+            ("#1A2B3C", Some(Color::rgb(0x1a, 0x2b, 0x3c))),
+            ("rgb(10, 20, 30)", Some(Color::rgb(10, 20, 30))),
+            ("RGB(100% 0% 50%)", Some(Color::rgb(255, 0, 128))),
+            ("rgba(255, 0, 0, 0.5)", rgba(255, 0, 0, 128)),
+            ("rgb(255 0 0 / 50%)", rgba(255, 0, 0, 128)),
+            (" Black ", Some(Color::rgb(0, 0, 0))),
+            ("orange", Some(Color::rgb(255, 165, 0))),
+            ("transparent", rgba(0, 0, 0, 0)),
+            ("currentcolor", Some(Color::CurrentColor)),
+            ("currentColor", Some(Color::CurrentColor)),
+            // Not colors
+            ("#12345", None),
+            ("#ggg", None),
+            ("rgb(1, 2)", None),
+            ("hsl(0, 0%, 0%)", None),
+            ("url(#gradient)", None),
+            ("var(--ink)", None),
+            ("", None),
+        ] {
+            assert_eq!(color.parse::<Color>().ok(), expected, "{}", color);
+        }
+    }
+
+    #[test]
+    fn methods_test() {
+        let black = Color::rgb(0, 0, 0);
+        let white: Color = "#FFF".parse().unwrap();
+        assert_eq!(black.luminance(), Some(0.0));
+        assert!((white.luminance().unwrap() - 1.0).abs() < 1e-6);
+        assert_eq!(Color::None.luminance(), None);
+        assert_eq!(white.to_hex().as_deref(), Some("#ffffff"));
+        assert_eq!(
+            "#fffd11a1".parse::<Color>().unwrap().to_string(),
+            "#fffd11a1"
+        );
+        assert_eq!(Color::CurrentColor.to_string(), "currentColor");
+        assert_eq!(Color::CurrentColor.to_hex(), None);
+        assert!(matches!(
+            "url(#a)".parse::<Color>(),
+            Err(Error::InvalidColor(color)) if color == "url(#a)"
+        ));
+    }
+}
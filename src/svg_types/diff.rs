@@ -0,0 +1,637 @@
+use std::fmt;
+
+use super::*;
+
+/// A difference between two documents, found by [`svg_diff`].
+///
+/// Elements are designated by their position from the root, each step giving the tag and the
+/// index of the element among the siblings with the same tag: `svg>g[2]>path[0]` is the first
+/// `path` of the third `g` of the document.
+///
+/// # Variant
+///
+/// - `Added` : the element at `path` exists only in the new document. Its content is not
+///   reported separately.
+/// - `Removed` : the element at `path` exists only in the old document.
+/// - `AttributeChanged` : the attribute `attribute` of the element at `path` differs. `old` and
+///   `new` are `None` when the attribute is absent from that side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvgDiff {
+    Added {
+        path: String,
+    },
+
+    Removed {
+        path: String,
+    },
+
+    AttributeChanged {
+        path: String,
+        attribute: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl fmt::Display for SvgDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn value(value: &Option<String>) -> String {
+            match value {
+                Some(value) => format!("{:?}", value),
+                None => "(absent)".to_string(),
+            }
+        }
+
+        match self {
+            SvgDiff::Added { path } => write!(f, "+ {}", path),
+            SvgDiff::Removed { path } => write!(f, "- {}", path),
+            SvgDiff::AttributeChanged {
+                path,
+                attribute,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ {} {}: {} -> {}",
+                path,
+                attribute,
+                value(old),
+                value(new)
+            ),
+        }
+    }
+}
+
+/// Lists the differences between two documents, for telling what changed between two
+/// compilations of a template rather than only that the output differs.
+///
+/// The elements are matched by position, per tag (see [`SvgDiff`]). Attribute values are compared
+/// after normalizing their numbers and separators, so that `1.0` and `1`, or `M1,2` and `M 1 2`,
+/// are not reported; attribute order never matters since the documents are compared parsed.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, svg_types::svg_diff};
+///
+/// let old = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><path d='M 0 0 L 1.0 1' fill='#000000'/></g></svg>",
+/// )
+/// .unwrap();
+/// let new = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <g><path d='M0,0 L1,1' fill='#ff0000'/><path d='M 2 2'/></g></svg>",
+/// )
+/// .unwrap();
+/// let diff = svg_diff(&old, &new);
+/// assert_eq!(diff[0].to_string(), r##"~ svg>g[0]>path[0] fill: "#000000" -> "#ff0000""##);
+/// assert_eq!(diff[1].to_string(), "+ svg>g[0]>path[1]");
+/// assert_eq!(diff.len(), 2);
+/// ```
+pub fn svg_diff(old: &Svg, new: &Svg) -> Vec<SvgDiff> {
+    let mut diffs = Vec::new();
+    diff_nodes(&Node::svg(old), &Node::svg(new), "svg", &mut diffs);
+    diffs
+}
+
+/// The tag, attributes and children of an element, in a form common to every element type.
+struct Node<'a> {
+    tag: &'a str,
+    attributes: Vec<(&'static str, Option<&'a str>)>,
+    children: Vec<Node<'a>>,
+}
+
+impl<'a> Node<'a> {
+    fn svg(svg: &'a Svg) -> Self {
+        Node {
+            tag: "svg",
+            attributes: vec![
+                ("class", Some(svg.class.as_str())),
+                ("width", Some(svg.width.as_str())),
+                ("height", Some(svg.height.as_str())),
+                ("viewBox", Some(svg.view_box.as_str())),
+            ],
+            children: svg
+                .elements
+                .iter()
+                .map(|element| match element {
+                    SvgElement::Path(path) => Node::path(path),
+                    SvgElement::G(g) => Node::g(g),
+                    SvgElement::Defs(defs) => Node {
+                        tag: "defs",
+                        attributes: vec![("id", Some(defs.id.as_str()))],
+                        children: defs
+                            .elements
+                            .iter()
+                            .map(Node::symbol)
+                            .chain(defs.definitions.iter().map(Node::definition))
+                            .collect(),
+                    },
+                    SvgElement::Rect(rect) => Node {
+                        tag: "rect",
+                        attributes: vec![
+                            ("x", Some(rect.x.as_str())),
+                            ("y", Some(rect.y.as_str())),
+                            ("width", Some(rect.width.as_str())),
+                            ("height", Some(rect.height.as_str())),
+                            ("fill", rect.fill.as_deref()),
+                        ],
+                        children: Vec::new(),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    fn path(path: &'a Path) -> Self {
+        Node {
+            tag: "path",
+            attributes: vec![
+                ("id", path.id.as_deref()),
+                ("class", path.class.as_deref()),
+                ("fill", path.fill.as_deref()),
+                ("fill-rule", path.fill_rule.as_ref().map(FillRule::as_str)),
+                ("stroke", path.stroke.as_deref()),
+                ("stroke-width", path.stroke_width.as_deref()),
+                (
+                    "stroke-linecap",
+                    path.stroke_linecap.as_ref().map(LineCap::as_str),
+                ),
+                (
+                    "stroke-linejoin",
+                    path.stroke_linejoin.as_ref().map(LineJoin::as_str),
+                ),
+                ("stroke-miterlimit", path.stroke_miterlimit.as_deref()),
+                ("tabindex", path.tabindex.as_deref()),
+                ("pointer-events", path.pointer_events.as_deref()),
+                ("opacity", path.opacity.as_deref()),
+                ("shape-rendering", path.shape_rendering.as_deref()),
+                ("d", Some(path.d.as_str())),
+            ],
+            children: Vec::new(),
+        }
+    }
+
+    fn g(g: &'a G) -> Self {
+        Node {
+            tag: "g",
+            attributes: vec![
+                ("id", g.id.as_deref()),
+                ("class", g.class.as_deref()),
+                ("transform", g.transform.as_deref()),
+                ("clip-path", g.clip_path.as_deref()),
+                ("mask", g.mask.as_deref()),
+                ("filter", g.filter.as_deref()),
+                ("tabindex", g.tabindex.as_deref()),
+                ("pointer-events", g.pointer_events.as_deref()),
+                ("shape-rendering", g.shape_rendering.as_deref()),
+                ("text-rendering", g.text_rendering.as_deref()),
+            ],
+            children: g.elements.iter().flatten().map(Node::g_ele).collect(),
+        }
+    }
+
+    fn g_ele(element: &'a GEle) -> Self {
+        match element {
+            GEle::G(g) => Node::g(g),
+            GEle::Use(uuse) => Node {
+                tag: "use",
+                attributes: vec![
+                    ("href", Some(&*uuse.href)),
+                    ("x", Some(uuse.x.as_str())),
+                    ("width", uuse.width.as_deref()),
+                    ("height", uuse.height.as_deref()),
+                    ("fill", uuse.fill.as_deref()),
+                    ("fill-rule", uuse.fill_rule.as_ref().map(FillRule::as_str)),
+                    ("transform", uuse.transform.as_deref()),
+                ],
+                children: Vec::new(),
+            },
+            GEle::Path(path) => Node::path(path),
+            GEle::Image(image) => Node::image(image),
+            GEle::Text(text) => Node::text(text),
+            GEle::Switch(switch) => Node {
+                tag: "switch",
+                attributes: vec![
+                    ("id", switch.id.as_deref()),
+                    ("transform", switch.transform.as_deref()),
+                ],
+                children: switch
+                    .branches
+                    .iter()
+                    .map(|branch| match branch {
+                        SwitchBranch::Element(element) => Node::g_ele(element),
+                        SwitchBranch::Unsupported(name) => Node {
+                            tag: name,
+                            attributes: Vec::new(),
+                            children: Vec::new(),
+                        },
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    fn text(text: &'a Text) -> Self {
+        Node {
+            tag: "text",
+            attributes: vec![
+                ("id", text.id.as_deref()),
+                ("class", text.class.as_deref()),
+                ("x", text.x.as_deref()),
+                ("y", text.y.as_deref()),
+                ("dx", text.dx.as_deref()),
+                ("dy", text.dy.as_deref()),
+                ("fill", text.fill.as_deref()),
+                ("font-family", text.font_family.as_deref()),
+                ("font-size", text.font_size.as_deref()),
+                ("font-weight", text.font_weight.as_deref()),
+                ("font-style", text.font_style.as_deref()),
+                ("letter-spacing", text.letter_spacing.as_deref()),
+                ("word-spacing", text.word_spacing.as_deref()),
+                ("dominant-baseline", text.dominant_baseline.as_deref()),
+                ("text-anchor", text.text_anchor.as_deref()),
+                ("text-rendering", text.text_rendering.as_deref()),
+                ("transform", text.transform.as_deref()),
+            ],
+            children: text
+                .content
+                .iter()
+                .map(|content| match content {
+                    // The characters are compared as an attribute of a pseudo-element
+                    TextContent::Characters(characters) => Node {
+                        tag: "#text",
+                        attributes: vec![("characters", Some(characters.as_str()))],
+                        children: Vec::new(),
+                    },
+                    TextContent::Tspan(tspan) => Node {
+                        tag: "tspan",
+                        attributes: vec![
+                            ("x", tspan.x.as_deref()),
+                            ("y", tspan.y.as_deref()),
+                            ("dx", tspan.dx.as_deref()),
+                            ("dy", tspan.dy.as_deref()),
+                            ("fill", tspan.fill.as_deref()),
+                            ("font-family", tspan.font_family.as_deref()),
+                            ("font-size", tspan.font_size.as_deref()),
+                            ("font-weight", tspan.font_weight.as_deref()),
+                            ("font-style", tspan.font_style.as_deref()),
+                            ("letter-spacing", tspan.letter_spacing.as_deref()),
+                            ("word-spacing", tspan.word_spacing.as_deref()),
+                            ("dominant-baseline", tspan.dominant_baseline.as_deref()),
+                            ("characters", Some(tspan.characters.as_str())),
+                        ],
+                        children: Vec::new(),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    fn image(image: &'a Image) -> Self {
+        Node {
+            tag: "image",
+            attributes: vec![
+                ("width", Some(image.width.as_str())),
+                ("height", Some(image.height.as_str())),
+                (
+                    "preserveAspectRatio",
+                    Some(image.preserve_aspect_ratio.as_str()),
+                ),
+                ("href", Some(image.href.as_str())),
+                ("transform", image.transform.as_deref()),
+            ],
+            children: Vec::new(),
+        }
+    }
+
+    fn symbol(symbol: &'a Symbol) -> Self {
+        Node {
+            tag: "symbol",
+            attributes: vec![
+                ("id", Some(symbol.id.as_str())),
+                ("overflow", Some(symbol.overflow.as_str())),
+                ("viewBox", symbol.view_box.as_deref()),
+                (
+                    "preserveAspectRatio",
+                    symbol.preserve_aspect_ratio.as_deref(),
+                ),
+            ],
+            children: vec![match &symbol.element {
+                SymbolEle::Path(path) => Node::path(path),
+                SymbolEle::Image(image) => Node::image(image),
+            }],
+        }
+    }
+
+    fn definition(definition: &'a Definition) -> Self {
+        let (mut attributes, gradient_units, gradient_transform, spread_method, href, stops) =
+            match definition {
+                Definition::LinearGradient(gradient) => (
+                    vec![
+                        ("id", Some(gradient.id.as_str())),
+                        ("x1", gradient.x1.as_deref()),
+                        ("y1", gradient.y1.as_deref()),
+                        ("x2", gradient.x2.as_deref()),
+                        ("y2", gradient.y2.as_deref()),
+                    ],
+                    &gradient.gradient_units,
+                    &gradient.gradient_transform,
+                    &gradient.spread_method,
+                    &gradient.href,
+                    &gradient.stops,
+                ),
+                Definition::RadialGradient(gradient) => (
+                    vec![
+                        ("id", Some(gradient.id.as_str())),
+                        ("cx", gradient.cx.as_deref()),
+                        ("cy", gradient.cy.as_deref()),
+                        ("r", gradient.r.as_deref()),
+                        ("fx", gradient.fx.as_deref()),
+                        ("fy", gradient.fy.as_deref()),
+                    ],
+                    &gradient.gradient_units,
+                    &gradient.gradient_transform,
+                    &gradient.spread_method,
+                    &gradient.href,
+                    &gradient.stops,
+                ),
+                Definition::ClipPath(clip) => {
+                    return Node {
+                        tag: "clipPath",
+                        attributes: vec![
+                            ("id", Some(clip.id.as_str())),
+                            (
+                                "clipPathUnits",
+                                clip.clip_path_units.as_ref().map(Units::as_str),
+                            ),
+                            ("transform", clip.transform.as_deref()),
+                        ],
+                        children: clip.elements.iter().map(Node::path).collect(),
+                    }
+                }
+                Definition::Mask(mask) => {
+                    return Node {
+                        tag: "mask",
+                        attributes: vec![
+                            ("id", Some(mask.id.as_str())),
+                            ("x", mask.x.as_deref()),
+                            ("y", mask.y.as_deref()),
+                            ("width", mask.width.as_deref()),
+                            ("height", mask.height.as_deref()),
+                            ("maskUnits", mask.mask_units.as_ref().map(Units::as_str)),
+                            (
+                                "maskContentUnits",
+                                mask.mask_content_units.as_ref().map(Units::as_str),
+                            ),
+                        ],
+                        children: mask.elements.iter().map(Node::path).collect(),
+                    }
+                }
+                Definition::Filter(filter) => {
+                    return Node {
+                        tag: "filter",
+                        attributes: vec![
+                            ("id", Some(filter.id.as_str())),
+                            ("x", filter.x.as_deref()),
+                            ("y", filter.y.as_deref()),
+                            ("width", filter.width.as_deref()),
+                            ("height", filter.height.as_deref()),
+                            (
+                                "filterUnits",
+                                filter.filter_units.as_ref().map(Units::as_str),
+                            ),
+                        ],
+                        children: filter
+                            .primitives
+                            .iter()
+                            .map(|primitive| Node {
+                                tag: primitive.tag(),
+                                attributes: writer::primitive_attributes(primitive),
+                                children: Vec::new(),
+                            })
+                            .collect(),
+                    }
+                }
+            };
+        attributes.extend([
+            ("gradientUnits", gradient_units.as_ref().map(Units::as_str)),
+            ("gradientTransform", gradient_transform.as_deref()),
+            (
+                "spreadMethod",
+                spread_method.as_ref().map(SpreadMethod::as_str),
+            ),
+            ("href", href.as_deref()),
+        ]);
+        Node {
+            tag: definition.tag(),
+            attributes,
+            children: stops
+                .iter()
+                .map(|stop| Node {
+                    tag: "stop",
+                    attributes: vec![
+                        ("offset", stop.offset.as_deref()),
+                        ("stop-color", stop.stop_color.as_deref()),
+                        ("stop-opacity", stop.stop_opacity.as_deref()),
+                        ("style", stop.style.as_deref()),
+                    ],
+                    children: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn diff_nodes(old: &Node, new: &Node, path: &str, diffs: &mut Vec<SvgDiff>) {
+    // Both nodes have the same tag, hence the same attributes in the same order
+    for ((attribute, old), (_, new)) in old.attributes.iter().zip(&new.attributes) {
+        let equal = match (old, new) {
+            (Some(old), Some(new)) => normalize(old) == normalize(new),
+            (old, new) => old == new,
+        };
+        if !equal {
+            diffs.push(SvgDiff::AttributeChanged {
+                path: path.to_string(),
+                attribute: attribute.to_string(),
+                old: old.map(str::to_string),
+                new: new.map(str::to_string),
+            });
+        }
+    }
+
+    // The children of each tag are matched by their index among the siblings with that tag
+    let mut tags: Vec<&str> = Vec::new();
+    for child in old.children.iter().chain(&new.children) {
+        if !tags.contains(&child.tag) {
+            tags.push(child.tag);
+        }
+    }
+    for tag in tags {
+        let old_children = old.children.iter().filter(|child| child.tag == tag);
+        let mut new_children = new.children.iter().filter(|child| child.tag == tag);
+        let mut index = 0;
+        for old_child in old_children {
+            let child_path = format!("{}>{}[{}]", path, tag, index);
+            match new_children.next() {
+                Some(new_child) => diff_nodes(old_child, new_child, &child_path, diffs),
+                None => diffs.push(SvgDiff::Removed { path: child_path }),
+            }
+            index += 1;
+        }
+        for _ in new_children {
+            diffs.push(SvgDiff::Added {
+                path: format!("{}>{}[{}]", path, tag, index),
+            });
+            index += 1;
+        }
+    }
+}
+
+/// Splits an attribute value into its words, hex colors and numbers, with every number written
+/// in its shortest form, so that only the significant differences remain.
+fn normalize(value: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = value.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+        } else if c == '#' || c.is_alphabetic() {
+            // Path commands are single letters, other words (`rgb`, `none`) are kept whole
+            chars.next();
+            let mut end = start + c.len_utf8();
+            while let Some(&(index, next)) = chars.peek() {
+                let continues = if c == '#' {
+                    next.is_alphanumeric()
+                } else {
+                    next.is_alphabetic() && !c.is_ascii_uppercase() && !next.is_ascii_uppercase()
+                };
+                if !continues {
+                    break;
+                }
+                chars.next();
+                end = index + next.len_utf8();
+            }
+            tokens.push(value[start..end].to_string());
+        } else if c.is_ascii_digit() || matches!(c, '.' | '-' | '+') {
+            let number = number_prefix(&value[start..]);
+            let end = start + number.len().max(c.len_utf8());
+            while chars.peek().is_some_and(|&(index, _)| index < end) {
+                chars.next();
+            }
+            match number.parse::<f64>() {
+                Ok(number) => tokens.push(format!("{}", number)),
+                Err(_) => tokens.push(value[start..end].to_string()),
+            }
+        } else {
+            chars.next();
+            tokens.push(c.to_string());
+        }
+    }
+    tokens
+}
+
+/// The longest prefix of `s` that is a number, with a sign, a single `.` and an exponent.
+fn number_prefix(s: &str) -> &str {
+    let bytes = s.as_bytes();
+    let mut end = 0;
+    if matches!(bytes.first(), Some(b'-' | b'+')) {
+        end += 1;
+    }
+    let mut dot = false;
+    while let Some(&byte) = bytes.get(end) {
+        match byte {
+            b'0'..=b'9' => end += 1,
+            b'.' if !dot => {
+                dot = true;
+                end += 1;
+            }
+            _ => break,
+        }
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exponent = end + 1;
+        if matches!(bytes.get(exponent), Some(b'-' | b'+')) {
+            exponent += 1;
+        }
+        if bytes.get(exponent).is_some_and(u8::is_ascii_digit) {
+            end = exponent;
+            while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                end += 1;
+            }
+        }
+    }
+    &s[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use std::fs;
+
+    #[test]
+    fn normalize_test() {
+        assert_eq!(normalize("M1,2L3.50 -4e1"), normalize("M 1 2 L 3.5 -40"));
+        assert_eq!(
+            normalize("matrix(1.0 0 0 1 10 .5)"),
+            normalize("matrix(1,0,0,1,10,0.5)")
+        );
+        assert_ne!(normalize("#ffe0e0"), normalize("#ffe0"));
+        assert_ne!(normalize("M 1 2"), normalize("M 1 2.01"));
+        assert_eq!(normalize("M1.5.5"), ["M", "1.5", "0.5"]);
+    }
+
+    #[test]
+    fn diff_test() {
+        let old = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <g transform="translate(1 2)">
+                    <use href="#glyph0" x="0" fill="#000000"/>
+                    <use href="#glyph1" x="3"/>
+                </g>
+                <g><path d="M 0 0"/></g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path d="M 0 0 L 1 1"/></symbol>
+                    <symbol id="glyph1" overflow="visible"><path d="M 0 0 L 2 2"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        let new = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 12" width="10pt" height="12pt">
+                <g transform="translate(1.0, 2.0)">
+                    <use href="#glyph0" x="0.0"/>
+                </g>
+                <g><path d="M0,0"/></g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path d="M 0 0 L 1 1.5"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        let diff: Vec<String> = svg_diff(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diff,
+            [
+                r#"~ svg height: "10pt" -> "12pt""#,
+                r#"~ svg viewBox: "0 0 10 10" -> "0 0 10 12""#,
+                r##"~ svg>g[0]>use[0] fill: "#000000" -> (absent)"##,
+                "- svg>g[0]>use[1]",
+                r#"~ svg>defs[0]>symbol[0]>path[0] d: "M 0 0 L 1 1" -> "M 0 0 L 1 1.5""#,
+                "- svg>defs[0]>symbol[1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_test() {
+        let svg = parse_svg(&fs::read_to_string("./test/expected.svg").unwrap()).unwrap();
+        let reparsed = parse_svg(&svg.to_svg_string()).unwrap();
+        assert!(svg_diff(&svg, &reparsed).is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use std::{cell::RefCell, collections::HashSet, sync::Arc};
+
+use serde::{Deserialize, Deserializer};
+
+thread_local! {
+    /// The values interned by the parse running on this thread, if any.
+    static INTERNER: RefCell<Option<HashSet<Arc<str>>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with interning enabled on this thread: equal values given to [`intern`] share one
+/// allocation until `f` returns.
+pub(crate) fn interning<T>(f: impl FnOnce() -> T) -> T {
+    struct Reset;
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            INTERNER.with(|interner| *interner.borrow_mut() = None);
+        }
+    }
+
+    if INTERNER.with(|interner| interner.borrow().is_some()) {
+        return f();
+    }
+    INTERNER.with(|interner| *interner.borrow_mut() = Some(HashSet::new()));
+    let _reset = Reset;
+    f()
+}
+
+/// Returns the shared copy of `value` when interning is enabled, or a new one otherwise.
+pub(crate) fn intern(value: &str) -> Arc<str> {
+    INTERNER.with(|interner| match interner.borrow_mut().as_mut() {
+        Some(values) => match values.get(value) {
+            Some(value) => value.clone(),
+            None => {
+                let value: Arc<str> = value.into();
+                values.insert(value.clone());
+                value
+            }
+        },
+        None => value.into(),
+    })
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Arc<str>, D::Error> {
+    Ok(intern(&String::deserialize(deserializer)?))
+}
+
+pub(crate) fn deserialize_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Arc<str>>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(|value| intern(&value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::svg_types::*;
+
+    #[test]
+    fn intern_test() {
+        let (a, b) = interning(|| (intern("typst-text"), intern("typst-text")));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&intern("typst-text"), &intern("typst-text")));
+
+        // Nested scopes share the outer interner
+        let (a, b) = interning(|| (intern("a"), interning(|| intern("a"))));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(INTERNER.with(|interner| interner.borrow().is_none()));
+    }
+
+    #[test]
+    fn parse_test() {
+        let svg = crate::parse_svg(
+            "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+             <g class='typst-text'><use href='#g0' x='0' fill='#000000'/></g>\
+             <g class='typst-text'><use href='#g0' x='5' fill='#000000'/></g></svg>",
+        )
+        .unwrap();
+        let groups: Vec<&G> = svg
+            .elements
+            .iter()
+            .map(|element| match element {
+                SvgElement::G(g) => g.as_ref(),
+                _ => panic!(),
+            })
+            .collect();
+        let uses: Vec<&Use> = groups
+            .iter()
+            .map(|g| match g.elements.as_deref() {
+                Some([GEle::Use(uuse)]) => &**uuse,
+                _ => panic!(),
+            })
+            .collect();
+        assert!(Arc::ptr_eq(
+            groups[0].class.as_ref().unwrap(),
+            groups[1].class.as_ref().unwrap()
+        ));
+        assert!(Arc::ptr_eq(&uses[0].href, &uses[1].href));
+        assert!(Arc::ptr_eq(
+            uses[0].fill.as_ref().unwrap(),
+            uses[1].fill.as_ref().unwrap()
+        ));
+    }
+}
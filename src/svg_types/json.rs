@@ -0,0 +1,1524 @@
+//! The JSON representation of the parsed SVG tree.
+//!
+//! The serde derives on [`Svg`] and its children describe the XML layout expected by `serde-xml-rs`
+//! (`$value` children, untagged element names), which does not translate into a usable JSON shape.
+//! This module defines the JSON schema explicitly with its own serde types.
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::error::Error;
+
+/// Serializes a parsed SVG tree to pretty-printed JSON.
+///
+/// The JSON schema is stable and independent of the XML layout of the SVG:
+///
+/// - Attributes keep their SVG spelling (`viewBox`, `fill-rule`, `preserveAspectRatio`, ...).
+///   Absent optional attributes are omitted.
+/// - Child lists are stored under `elements`; every child is an object whose `type` field holds
+///   the element name (`path`, `g`, `use`, `image`, `text`, `rect`, `defs`, `symbol`).
+/// - The single child of a `symbol` is stored under `element`.
+/// - The gradients, clip paths, masks and filters of a `defs` follow its symbols in `elements`,
+///   with their `stop`s, `path`s or primitives, such as `feDropShadow`, in their own `elements`.
+///   The `type` of an `feColorMatrix`, which would clash with the element name, is stored under
+///   `kind`.
+/// - The content of a `text` is stored under `content`, as strings for the characters and
+///   objects of `type` `tspan`.
+/// - The children of a `switch` that the tree cannot hold are objects of `type` `unsupported`,
+///   with the element name under `name`.
+///
+/// ```json
+/// {
+///   "class": "typst-doc",
+///   "width": "10pt",
+///   "height": "10pt",
+///   "viewBox": "0 0 10 10",
+///   "elements": [
+///     { "type": "g", "transform": "translate(1 2)", "elements": [
+///       { "type": "use", "x": "0", "href": "#glyph0" }
+///     ] },
+///     { "type": "defs", "id": "glyph", "elements": [
+///       { "type": "symbol", "id": "glyph0", "overflow": "visible",
+///         "element": { "type": "path", "d": "M 0 0 L 1 1" } }
+///     ] }
+///   ]
+/// }
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, svg_from_json, svg_to_json};
+///
+/// let svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <path d='M 0 0 L 10 10' fill-rule='nonzero'/></svg>",
+/// )
+/// .unwrap();
+/// let json = svg_to_json(&svg);
+/// assert!(json.contains(r#""type": "path""#));
+/// assert_eq!(svg_from_json(&json).unwrap(), svg);
+/// ```
+pub fn svg_to_json(svg: &Svg) -> String {
+    serde_json::to_string_pretty(&JsonSvg::from(svg)).expect("the JSON schema only holds strings")
+}
+
+/// Parses JSON produced by [`svg_to_json`] back into an SVG tree.
+///
+/// # Return value
+///
+/// - `Ok(Svg)` : the SVG tree.
+/// - `Err(Error)` : [`Error::JsonError`] when the input is not valid JSON or does not follow the schema.
+pub fn svg_from_json(json: &str) -> Result<Svg, Error> {
+    intern::interning(|| Ok(serde_json::from_str::<JsonSvg>(json)?.try_into()?))
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonSvg {
+    class: String,
+
+    width: String,
+
+    height: String,
+
+    #[serde(rename = "viewBox")]
+    view_box: String,
+
+    elements: Vec<JsonNode>,
+}
+
+/// Every element of the tree, tagged with its SVG element name.
+///
+/// The schema allows any element in any list; converting back to [`Svg`] rejects elements that
+/// the typed tree cannot hold at that position.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+enum JsonNode {
+    Path(JsonPath),
+
+    G {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "intern::deserialize_option"
+        )]
+        class: Option<Arc<str>>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        transform: Option<String>,
+
+        #[serde(
+            rename = "clip-path",
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "intern::deserialize_option"
+        )]
+        clip_path: Option<Arc<str>>,
+
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "intern::deserialize_option"
+        )]
+        mask: Option<Arc<str>>,
+
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "intern::deserialize_option"
+        )]
+        filter: Option<Arc<str>>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tabindex: Option<String>,
+
+        #[serde(
+            rename = "pointer-events",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        pointer_events: Option<String>,
+
+        #[serde(
+            rename = "shape-rendering",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        shape_rendering: Option<String>,
+
+        #[serde(
+            rename = "text-rendering",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        text_rendering: Option<String>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        elements: Option<Vec<JsonNode>>,
+    },
+
+    Use {
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "intern::deserialize_option"
+        )]
+        fill: Option<Arc<str>>,
+
+        x: String,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        width: Option<String>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        height: Option<String>,
+
+        #[serde(rename = "fill-rule", default, skip_serializing_if = "Option::is_none")]
+        fill_rule: Option<FillRule>,
+
+        #[serde(deserialize_with = "intern::deserialize")]
+        href: Arc<str>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        transform: Option<String>,
+    },
+
+    Image(JsonImage),
+
+    Text(JsonText),
+
+    Rect {
+        x: String,
+
+        y: String,
+
+        width: String,
+
+        height: String,
+
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "intern::deserialize_option"
+        )]
+        fill: Option<Arc<str>>,
+    },
+
+    Defs {
+        id: String,
+
+        elements: Vec<JsonNode>,
+    },
+
+    Symbol {
+        id: String,
+
+        overflow: String,
+
+        #[serde(rename = "viewBox", default, skip_serializing_if = "Option::is_none")]
+        view_box: Option<String>,
+
+        #[serde(
+            rename = "preserveAspectRatio",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        preserve_aspect_ratio: Option<String>,
+
+        element: Box<JsonNode>,
+    },
+
+    #[serde(rename = "linearGradient")]
+    LinearGradient(JsonLinearGradient),
+
+    #[serde(rename = "radialGradient")]
+    RadialGradient(JsonRadialGradient),
+
+    Stop(JsonStop),
+
+    #[serde(rename = "clipPath")]
+    ClipPath(JsonClipPath),
+
+    Mask(JsonMask),
+
+    Filter(JsonFilter),
+
+    #[serde(rename = "feGaussianBlur")]
+    GaussianBlur(JsonGaussianBlur),
+
+    #[serde(rename = "feOffset")]
+    Offset(JsonOffset),
+
+    #[serde(rename = "feColorMatrix")]
+    ColorMatrix(JsonColorMatrix),
+
+    #[serde(rename = "feDropShadow")]
+    DropShadow(JsonDropShadow),
+
+    Switch {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        transform: Option<String>,
+
+        elements: Vec<JsonNode>,
+    },
+
+    Unsupported {
+        name: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct JsonPath {
+    d: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    class: Option<Arc<str>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    fill: Option<Arc<str>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    stroke: Option<Arc<str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fill_rule: Option<FillRule>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    stroke_width: Option<Arc<str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stroke_linecap: Option<LineCap>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stroke_linejoin: Option<LineJoin>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stroke_miterlimit: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tabindex: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pointer_events: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    opacity: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shape_rendering: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonImage {
+    width: String,
+
+    height: String,
+
+    #[serde(rename = "preserveAspectRatio")]
+    preserve_aspect_ratio: String,
+
+    href: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transform: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonLinearGradient {
+    id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x1: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y1: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x2: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y2: Option<String>,
+
+    #[serde(
+        rename = "gradientUnits",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    gradient_units: Option<Units>,
+
+    #[serde(
+        rename = "gradientTransform",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    gradient_transform: Option<String>,
+
+    #[serde(
+        rename = "spreadMethod",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    spread_method: Option<SpreadMethod>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    href: Option<String>,
+
+    elements: Vec<JsonNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonRadialGradient {
+    id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cx: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cy: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    r: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fx: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fy: Option<String>,
+
+    #[serde(
+        rename = "gradientUnits",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    gradient_units: Option<Units>,
+
+    #[serde(
+        rename = "gradientTransform",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    gradient_transform: Option<String>,
+
+    #[serde(
+        rename = "spreadMethod",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    spread_method: Option<SpreadMethod>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    href: Option<String>,
+
+    elements: Vec<JsonNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonClipPath {
+    id: String,
+
+    #[serde(
+        rename = "clipPathUnits",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    clip_path_units: Option<Units>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transform: Option<String>,
+
+    elements: Vec<JsonNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonMask {
+    id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    width: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    height: Option<String>,
+
+    #[serde(rename = "maskUnits", default, skip_serializing_if = "Option::is_none")]
+    mask_units: Option<Units>,
+
+    #[serde(
+        rename = "maskContentUnits",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    mask_content_units: Option<Units>,
+
+    elements: Vec<JsonNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonFilter {
+    id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    width: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    height: Option<String>,
+
+    #[serde(
+        rename = "filterUnits",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    filter_units: Option<Units>,
+
+    elements: Vec<JsonNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonGaussianBlur {
+    #[serde(rename = "in", default, skip_serializing_if = "Option::is_none")]
+    input: Option<String>,
+
+    #[serde(
+        rename = "stdDeviation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    std_deviation: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonOffset {
+    #[serde(rename = "in", default, skip_serializing_if = "Option::is_none")]
+    input: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dx: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dy: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonColorMatrix {
+    #[serde(rename = "in", default, skip_serializing_if = "Option::is_none")]
+    input: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<ColorMatrixType>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    values: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct JsonDropShadow {
+    #[serde(rename = "in", default, skip_serializing_if = "Option::is_none")]
+    input: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dx: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dy: Option<String>,
+
+    #[serde(
+        rename = "stdDeviation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    std_deviation: Option<String>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    flood_color: Option<Arc<str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    flood_opacity: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct JsonStop {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<String>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    stop_color: Option<Arc<str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stop_opacity: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    style: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct JsonText {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    class: Option<Arc<str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dx: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dy: Option<String>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    fill: Option<Arc<str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_family: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_size: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_weight: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_style: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    letter_spacing: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    word_spacing: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dominant_baseline: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text_anchor: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text_rendering: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transform: Option<String>,
+
+    #[serde(default)]
+    content: Vec<JsonTextContent>,
+}
+
+/// The characters of a `text` are plain strings, its `tspan` objects tagged like the elements.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonTextContent {
+    Characters(String),
+
+    Element(JsonTextElement),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+enum JsonTextElement {
+    Tspan(Box<JsonTspan>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct JsonTspan {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dx: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dy: Option<String>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "intern::deserialize_option"
+    )]
+    fill: Option<Arc<str>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_family: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_size: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_weight: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_style: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    letter_spacing: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    word_spacing: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dominant_baseline: Option<String>,
+
+    characters: String,
+}
+
+impl From<&Svg> for JsonSvg {
+    fn from(svg: &Svg) -> Self {
+        JsonSvg {
+            class: svg.class.clone(),
+            width: svg.width.clone(),
+            height: svg.height.clone(),
+            view_box: svg.view_box.clone(),
+            elements: svg
+                .elements
+                .iter()
+                .map(|element| match element {
+                    SvgElement::Path(path) => JsonNode::Path(path.as_ref().into()),
+                    SvgElement::G(g) => g.as_ref().into(),
+                    SvgElement::Defs(defs) => JsonNode::Defs {
+                        id: defs.id.clone(),
+                        elements: defs
+                            .elements
+                            .iter()
+                            .map(JsonNode::from)
+                            .chain(defs.definitions.iter().map(JsonNode::from))
+                            .collect(),
+                    },
+                    SvgElement::Rect(rect) => JsonNode::Rect {
+                        x: rect.x.clone(),
+                        y: rect.y.clone(),
+                        width: rect.width.clone(),
+                        height: rect.height.clone(),
+                        fill: rect.fill.clone(),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&G> for JsonNode {
+    fn from(g: &G) -> Self {
+        JsonNode::G {
+            id: g.id.clone(),
+            class: g.class.clone(),
+            transform: g.transform.clone(),
+            clip_path: g.clip_path.clone(),
+            mask: g.mask.clone(),
+            filter: g.filter.clone(),
+            tabindex: g.tabindex.clone(),
+            pointer_events: g.pointer_events.clone(),
+            shape_rendering: g.shape_rendering.clone(),
+            text_rendering: g.text_rendering.clone(),
+            elements: g
+                .elements
+                .as_ref()
+                .map(|elements| elements.iter().map(JsonNode::from).collect()),
+        }
+    }
+}
+
+impl From<&GEle> for JsonNode {
+    fn from(element: &GEle) -> Self {
+        match element {
+            GEle::G(g) => g.as_ref().into(),
+            GEle::Use(uuse) => JsonNode::Use {
+                fill: uuse.fill.clone(),
+                x: uuse.x.clone(),
+                width: uuse.width.clone(),
+                height: uuse.height.clone(),
+                fill_rule: uuse.fill_rule.clone(),
+                href: uuse.href.clone(),
+                transform: uuse.transform.clone(),
+            },
+            GEle::Path(path) => JsonNode::Path(path.as_ref().into()),
+            GEle::Image(image) => JsonNode::Image(image.as_ref().into()),
+            GEle::Text(text) => JsonNode::Text(text.as_ref().into()),
+            GEle::Switch(switch) => JsonNode::Switch {
+                id: switch.id.clone(),
+                transform: switch.transform.clone(),
+                elements: switch
+                    .branches
+                    .iter()
+                    .map(|branch| match branch {
+                        SwitchBranch::Element(element) => element.into(),
+                        SwitchBranch::Unsupported(name) => {
+                            JsonNode::Unsupported { name: name.clone() }
+                        }
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl From<&Symbol> for JsonNode {
+    fn from(symbol: &Symbol) -> Self {
+        JsonNode::Symbol {
+            id: symbol.id.clone(),
+            overflow: symbol.overflow.clone(),
+            view_box: symbol.view_box.clone(),
+            preserve_aspect_ratio: symbol.preserve_aspect_ratio.clone(),
+            element: Box::new(match &symbol.element {
+                SymbolEle::Path(path) => JsonNode::Path(path.into()),
+                SymbolEle::Image(image) => JsonNode::Image(image.into()),
+            }),
+        }
+    }
+}
+
+impl From<&Definition> for JsonNode {
+    fn from(definition: &Definition) -> Self {
+        match definition {
+            Definition::LinearGradient(gradient) => JsonNode::LinearGradient(JsonLinearGradient {
+                id: gradient.id.clone(),
+                x1: gradient.x1.clone(),
+                y1: gradient.y1.clone(),
+                x2: gradient.x2.clone(),
+                y2: gradient.y2.clone(),
+                gradient_units: gradient.gradient_units.clone(),
+                gradient_transform: gradient.gradient_transform.clone(),
+                spread_method: gradient.spread_method.clone(),
+                href: gradient.href.clone(),
+                elements: gradient.stops.iter().map(JsonNode::from).collect(),
+            }),
+            Definition::RadialGradient(gradient) => JsonNode::RadialGradient(JsonRadialGradient {
+                id: gradient.id.clone(),
+                cx: gradient.cx.clone(),
+                cy: gradient.cy.clone(),
+                r: gradient.r.clone(),
+                fx: gradient.fx.clone(),
+                fy: gradient.fy.clone(),
+                gradient_units: gradient.gradient_units.clone(),
+                gradient_transform: gradient.gradient_transform.clone(),
+                spread_method: gradient.spread_method.clone(),
+                href: gradient.href.clone(),
+                elements: gradient.stops.iter().map(JsonNode::from).collect(),
+            }),
+            Definition::ClipPath(clip) => JsonNode::ClipPath(JsonClipPath {
+                id: clip.id.clone(),
+                clip_path_units: clip.clip_path_units.clone(),
+                transform: clip.transform.clone(),
+                elements: paths_to_json(&clip.elements),
+            }),
+            Definition::Mask(mask) => JsonNode::Mask(JsonMask {
+                id: mask.id.clone(),
+                x: mask.x.clone(),
+                y: mask.y.clone(),
+                width: mask.width.clone(),
+                height: mask.height.clone(),
+                mask_units: mask.mask_units.clone(),
+                mask_content_units: mask.mask_content_units.clone(),
+                elements: paths_to_json(&mask.elements),
+            }),
+            Definition::Filter(filter) => JsonNode::Filter(JsonFilter {
+                id: filter.id.clone(),
+                x: filter.x.clone(),
+                y: filter.y.clone(),
+                width: filter.width.clone(),
+                height: filter.height.clone(),
+                filter_units: filter.filter_units.clone(),
+                elements: filter.primitives.iter().map(JsonNode::from).collect(),
+            }),
+        }
+    }
+}
+
+fn paths_to_json(paths: &[Path]) -> Vec<JsonNode> {
+    paths
+        .iter()
+        .map(|path| JsonNode::Path(path.into()))
+        .collect()
+}
+
+impl From<&FilterPrimitive> for JsonNode {
+    fn from(primitive: &FilterPrimitive) -> Self {
+        match primitive {
+            FilterPrimitive::GaussianBlur(blur) => JsonNode::GaussianBlur(JsonGaussianBlur {
+                input: blur.input.clone(),
+                std_deviation: blur.std_deviation.clone(),
+                result: blur.result.clone(),
+            }),
+            FilterPrimitive::Offset(offset) => JsonNode::Offset(JsonOffset {
+                input: offset.input.clone(),
+                dx: offset.dx.clone(),
+                dy: offset.dy.clone(),
+                result: offset.result.clone(),
+            }),
+            FilterPrimitive::ColorMatrix(matrix) => JsonNode::ColorMatrix(JsonColorMatrix {
+                input: matrix.input.clone(),
+                kind: matrix.kind.clone(),
+                values: matrix.values.clone(),
+                result: matrix.result.clone(),
+            }),
+            FilterPrimitive::DropShadow(shadow) => JsonNode::DropShadow(JsonDropShadow {
+                input: shadow.input.clone(),
+                dx: shadow.dx.clone(),
+                dy: shadow.dy.clone(),
+                std_deviation: shadow.std_deviation.clone(),
+                flood_color: shadow.flood_color.clone(),
+                flood_opacity: shadow.flood_opacity.clone(),
+                result: shadow.result.clone(),
+            }),
+        }
+    }
+}
+
+impl From<&Stop> for JsonNode {
+    fn from(stop: &Stop) -> Self {
+        JsonNode::Stop(JsonStop {
+            offset: stop.offset.clone(),
+            stop_color: stop.stop_color.clone(),
+            stop_opacity: stop.stop_opacity.clone(),
+            style: stop.style.clone(),
+        })
+    }
+}
+
+impl From<&Path> for JsonPath {
+    fn from(path: &Path) -> Self {
+        JsonPath {
+            d: path.d.clone(),
+            id: path.id.clone(),
+            class: path.class.clone(),
+            fill: path.fill.clone(),
+            stroke: path.stroke.clone(),
+            fill_rule: path.fill_rule.clone(),
+            stroke_width: path.stroke_width.clone(),
+            stroke_linecap: path.stroke_linecap.clone(),
+            stroke_linejoin: path.stroke_linejoin.clone(),
+            stroke_miterlimit: path.stroke_miterlimit.clone(),
+            tabindex: path.tabindex.clone(),
+            pointer_events: path.pointer_events.clone(),
+            opacity: path.opacity.clone(),
+            shape_rendering: path.shape_rendering.clone(),
+        }
+    }
+}
+
+impl From<JsonPath> for Path {
+    fn from(path: JsonPath) -> Self {
+        Path {
+            d: path.d,
+            id: path.id,
+            class: path.class,
+            fill: path.fill,
+            stroke: path.stroke,
+            fill_rule: path.fill_rule,
+            stroke_width: path.stroke_width,
+            stroke_linecap: path.stroke_linecap,
+            stroke_linejoin: path.stroke_linejoin,
+            stroke_miterlimit: path.stroke_miterlimit,
+            tabindex: path.tabindex,
+            pointer_events: path.pointer_events,
+            opacity: path.opacity,
+            shape_rendering: path.shape_rendering,
+        }
+    }
+}
+
+impl From<&Image> for JsonImage {
+    fn from(image: &Image) -> Self {
+        JsonImage {
+            width: image.width.clone(),
+            height: image.height.clone(),
+            preserve_aspect_ratio: image.preserve_aspect_ratio.clone(),
+            href: image.href.clone(),
+            transform: image.transform.clone(),
+        }
+    }
+}
+
+impl From<JsonImage> for Image {
+    fn from(image: JsonImage) -> Self {
+        Image {
+            width: image.width,
+            height: image.height,
+            preserve_aspect_ratio: image.preserve_aspect_ratio,
+            href: image.href,
+            transform: image.transform,
+        }
+    }
+}
+
+impl From<&Text> for JsonText {
+    fn from(text: &Text) -> Self {
+        JsonText {
+            id: text.id.clone(),
+            class: text.class.clone(),
+            x: text.x.clone(),
+            y: text.y.clone(),
+            dx: text.dx.clone(),
+            dy: text.dy.clone(),
+            fill: text.fill.clone(),
+            font_family: text.font_family.clone(),
+            font_size: text.font_size.clone(),
+            font_weight: text.font_weight.clone(),
+            font_style: text.font_style.clone(),
+            letter_spacing: text.letter_spacing.clone(),
+            word_spacing: text.word_spacing.clone(),
+            dominant_baseline: text.dominant_baseline.clone(),
+            text_anchor: text.text_anchor.clone(),
+            text_rendering: text.text_rendering.clone(),
+            transform: text.transform.clone(),
+            content: text
+                .content
+                .iter()
+                .map(|content| match content {
+                    TextContent::Characters(characters) => {
+                        JsonTextContent::Characters(characters.clone())
+                    }
+                    TextContent::Tspan(tspan) => JsonTextContent::Element(JsonTextElement::Tspan(
+                        Box::new(tspan.as_ref().into()),
+                    )),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<JsonText> for Text {
+    fn from(text: JsonText) -> Self {
+        Text {
+            id: text.id,
+            class: text.class,
+            x: text.x,
+            y: text.y,
+            dx: text.dx,
+            dy: text.dy,
+            fill: text.fill,
+            font_family: text.font_family,
+            font_size: text.font_size,
+            font_weight: text.font_weight,
+            font_style: text.font_style,
+            letter_spacing: text.letter_spacing,
+            word_spacing: text.word_spacing,
+            dominant_baseline: text.dominant_baseline,
+            text_anchor: text.text_anchor,
+            text_rendering: text.text_rendering,
+            transform: text.transform,
+            content: text
+                .content
+                .into_iter()
+                .map(|content| match content {
+                    JsonTextContent::Characters(characters) => TextContent::Characters(characters),
+                    JsonTextContent::Element(JsonTextElement::Tspan(tspan)) => {
+                        TextContent::Tspan(Box::new((*tspan).into()))
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&Tspan> for JsonTspan {
+    fn from(tspan: &Tspan) -> Self {
+        JsonTspan {
+            x: tspan.x.clone(),
+            y: tspan.y.clone(),
+            dx: tspan.dx.clone(),
+            dy: tspan.dy.clone(),
+            fill: tspan.fill.clone(),
+            font_family: tspan.font_family.clone(),
+            font_size: tspan.font_size.clone(),
+            font_weight: tspan.font_weight.clone(),
+            font_style: tspan.font_style.clone(),
+            letter_spacing: tspan.letter_spacing.clone(),
+            word_spacing: tspan.word_spacing.clone(),
+            dominant_baseline: tspan.dominant_baseline.clone(),
+            characters: tspan.characters.clone(),
+        }
+    }
+}
+
+impl From<JsonTspan> for Tspan {
+    fn from(tspan: JsonTspan) -> Self {
+        Tspan {
+            x: tspan.x,
+            y: tspan.y,
+            dx: tspan.dx,
+            dy: tspan.dy,
+            fill: tspan.fill,
+            font_family: tspan.font_family,
+            font_size: tspan.font_size,
+            font_weight: tspan.font_weight,
+            font_style: tspan.font_style,
+            letter_spacing: tspan.letter_spacing,
+            word_spacing: tspan.word_spacing,
+            dominant_baseline: tspan.dominant_baseline,
+            characters: tspan.characters,
+        }
+    }
+}
+
+impl TryFrom<JsonSvg> for Svg {
+    type Error = serde_json::Error;
+
+    fn try_from(svg: JsonSvg) -> Result<Self, Self::Error> {
+        Ok(Svg {
+            class: svg.class,
+            width: svg.width,
+            height: svg.height,
+            view_box: svg.view_box,
+            elements: svg
+                .elements
+                .into_iter()
+                .map(|node| match node {
+                    JsonNode::Path(path) => Ok(SvgElement::Path(Box::new(path.into()))),
+                    node @ JsonNode::G { .. } => Ok(SvgElement::G(Box::new(g_from_json(node)?))),
+                    JsonNode::Defs { id, elements } => {
+                        Ok(SvgElement::Defs(defs_from_json(id, elements)?))
+                    }
+                    JsonNode::Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                        fill,
+                    } => Ok(SvgElement::Rect(Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                        fill,
+                    })),
+                    node => Err(misplaced(&node, "svg")),
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn g_from_json(node: JsonNode) -> Result<G, serde_json::Error> {
+    let JsonNode::G {
+        id,
+        class,
+        transform,
+        clip_path,
+        mask,
+        filter,
+        tabindex,
+        pointer_events,
+        shape_rendering,
+        text_rendering,
+        elements,
+    } = node
+    else {
+        return Err(misplaced(&node, "g"));
+    };
+    let elements = elements
+        .map(|elements| {
+            elements
+                .into_iter()
+                .map(|node| g_ele_from_json(node, "g"))
+                .collect::<Result<_, _>>()
+        })
+        .transpose()?;
+    Ok(G {
+        id,
+        class,
+        transform,
+        clip_path,
+        mask,
+        filter,
+        tabindex,
+        pointer_events,
+        shape_rendering,
+        text_rendering,
+        elements,
+    })
+}
+
+/// The child of a `g`, or of a `switch`, that `node` holds.
+fn g_ele_from_json(node: JsonNode, parent: &str) -> Result<GEle, serde_json::Error> {
+    match node {
+        node @ JsonNode::G { .. } => Ok(GEle::G(Box::new(g_from_json(node)?))),
+        JsonNode::Use {
+            fill,
+            x,
+            width,
+            height,
+            fill_rule,
+            href,
+            transform,
+        } => Ok(GEle::Use(Box::new(Use {
+            fill,
+            x,
+            width,
+            height,
+            fill_rule,
+            href,
+            transform,
+        }))),
+        JsonNode::Path(path) => Ok(GEle::Path(Box::new(path.into()))),
+        JsonNode::Image(image) => Ok(GEle::Image(Box::new(image.into()))),
+        JsonNode::Text(text) => Ok(GEle::Text(Box::new(text.into()))),
+        JsonNode::Switch {
+            id,
+            transform,
+            elements,
+        } => Ok(GEle::Switch(Box::new(Switch {
+            id,
+            transform,
+            branches: elements
+                .into_iter()
+                .map(|node| match node {
+                    JsonNode::Unsupported { name } => Ok(SwitchBranch::Unsupported(name)),
+                    node => g_ele_from_json(node, "switch").map(SwitchBranch::Element),
+                })
+                .collect::<Result<_, _>>()?,
+        }))),
+        node => Err(misplaced(&node, parent)),
+    }
+}
+
+fn defs_from_json(id: String, elements: Vec<JsonNode>) -> Result<Defs, serde_json::Error> {
+    let mut defs = Defs {
+        id,
+        ..Default::default()
+    };
+    for node in elements {
+        match node {
+            JsonNode::LinearGradient(gradient) => {
+                let stops = stops_from_json(gradient.elements, "linearGradient")?;
+                defs.definitions
+                    .push(Definition::LinearGradient(LinearGradient {
+                        id: gradient.id,
+                        x1: gradient.x1,
+                        y1: gradient.y1,
+                        x2: gradient.x2,
+                        y2: gradient.y2,
+                        gradient_units: gradient.gradient_units,
+                        gradient_transform: gradient.gradient_transform,
+                        spread_method: gradient.spread_method,
+                        href: gradient.href,
+                        stops,
+                    }));
+            }
+            JsonNode::RadialGradient(gradient) => {
+                let stops = stops_from_json(gradient.elements, "radialGradient")?;
+                defs.definitions
+                    .push(Definition::RadialGradient(RadialGradient {
+                        id: gradient.id,
+                        cx: gradient.cx,
+                        cy: gradient.cy,
+                        r: gradient.r,
+                        fx: gradient.fx,
+                        fy: gradient.fy,
+                        gradient_units: gradient.gradient_units,
+                        gradient_transform: gradient.gradient_transform,
+                        spread_method: gradient.spread_method,
+                        href: gradient.href,
+                        stops,
+                    }));
+            }
+            JsonNode::ClipPath(clip) => {
+                let elements = paths_from_json(clip.elements, "clipPath")?;
+                defs.definitions.push(Definition::ClipPath(ClipPath {
+                    id: clip.id,
+                    clip_path_units: clip.clip_path_units,
+                    transform: clip.transform,
+                    elements,
+                }));
+            }
+            JsonNode::Mask(mask) => {
+                let elements = paths_from_json(mask.elements, "mask")?;
+                defs.definitions.push(Definition::Mask(Mask {
+                    id: mask.id,
+                    x: mask.x,
+                    y: mask.y,
+                    width: mask.width,
+                    height: mask.height,
+                    mask_units: mask.mask_units,
+                    mask_content_units: mask.mask_content_units,
+                    elements,
+                }));
+            }
+            JsonNode::Filter(filter) => {
+                let primitives = primitives_from_json(filter.elements)?;
+                defs.definitions.push(Definition::Filter(Filter {
+                    id: filter.id,
+                    x: filter.x,
+                    y: filter.y,
+                    width: filter.width,
+                    height: filter.height,
+                    filter_units: filter.filter_units,
+                    primitives,
+                }));
+            }
+            node => defs.elements.push(symbol_from_json(node)?),
+        }
+    }
+    Ok(defs)
+}
+
+fn stops_from_json(elements: Vec<JsonNode>, parent: &str) -> Result<Vec<Stop>, serde_json::Error> {
+    elements
+        .into_iter()
+        .map(|node| match node {
+            JsonNode::Stop(stop) => Ok(Stop {
+                offset: stop.offset,
+                stop_color: stop.stop_color,
+                stop_opacity: stop.stop_opacity,
+                style: stop.style,
+            }),
+            node => Err(misplaced(&node, parent)),
+        })
+        .collect()
+}
+
+fn paths_from_json(elements: Vec<JsonNode>, parent: &str) -> Result<Vec<Path>, serde_json::Error> {
+    elements
+        .into_iter()
+        .map(|node| match node {
+            JsonNode::Path(path) => Ok(path.into()),
+            node => Err(misplaced(&node, parent)),
+        })
+        .collect()
+}
+
+fn primitives_from_json(
+    elements: Vec<JsonNode>,
+) -> Result<Vec<FilterPrimitive>, serde_json::Error> {
+    elements
+        .into_iter()
+        .map(|node| match node {
+            JsonNode::GaussianBlur(blur) => Ok(FilterPrimitive::GaussianBlur(GaussianBlur {
+                input: blur.input,
+                std_deviation: blur.std_deviation,
+                result: blur.result,
+            })),
+            JsonNode::Offset(offset) => Ok(FilterPrimitive::Offset(Offset {
+                input: offset.input,
+                dx: offset.dx,
+                dy: offset.dy,
+                result: offset.result,
+            })),
+            JsonNode::ColorMatrix(matrix) => Ok(FilterPrimitive::ColorMatrix(ColorMatrix {
+                input: matrix.input,
+                kind: matrix.kind,
+                values: matrix.values,
+                result: matrix.result,
+            })),
+            JsonNode::DropShadow(shadow) => Ok(FilterPrimitive::DropShadow(DropShadow {
+                input: shadow.input,
+                dx: shadow.dx,
+                dy: shadow.dy,
+                std_deviation: shadow.std_deviation,
+                flood_color: shadow.flood_color,
+                flood_opacity: shadow.flood_opacity,
+                result: shadow.result,
+            })),
+            node => Err(misplaced(&node, "filter")),
+        })
+        .collect()
+}
+
+fn symbol_from_json(node: JsonNode) -> Result<Symbol, serde_json::Error> {
+    let JsonNode::Symbol {
+        id,
+        overflow,
+        view_box,
+        preserve_aspect_ratio,
+        element,
+    } = node
+    else {
+        return Err(misplaced(&node, "defs"));
+    };
+    let element = match *element {
+        JsonNode::Path(path) => SymbolEle::Path(path.into()),
+        JsonNode::Image(image) => SymbolEle::Image(image.into()),
+        node => return Err(misplaced(&node, "symbol")),
+    };
+    Ok(Symbol {
+        id,
+        overflow,
+        view_box,
+        preserve_aspect_ratio,
+        element,
+    })
+}
+
+/// The error for an element that is valid JSON but cannot appear inside `parent`.
+fn misplaced(node: &JsonNode, parent: &str) -> serde_json::Error {
+    let name = match node {
+        JsonNode::Path(_) => "path",
+        JsonNode::G { .. } => "g",
+        JsonNode::Use { .. } => "use",
+        JsonNode::Image(_) => "image",
+        JsonNode::Text(_) => "text",
+        JsonNode::Rect { .. } => "rect",
+        JsonNode::Defs { .. } => "defs",
+        JsonNode::Symbol { .. } => "symbol",
+        JsonNode::LinearGradient(_) => "linearGradient",
+        JsonNode::RadialGradient(_) => "radialGradient",
+        JsonNode::Stop(_) => "stop",
+        JsonNode::ClipPath(_) => "clipPath",
+        JsonNode::Mask(_) => "mask",
+        JsonNode::Filter(_) => "filter",
+        JsonNode::GaussianBlur(_) => "feGaussianBlur",
+        JsonNode::Offset(_) => "feOffset",
+        JsonNode::ColorMatrix(_) => "feColorMatrix",
+        JsonNode::DropShadow(_) => "feDropShadow",
+        JsonNode::Switch { .. } => "switch",
+        JsonNode::Unsupported { name } => name,
+    };
+    serde::de::Error::custom(format!("`{}` is not allowed inside `{}`", name, parent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+    use std::fs;
+
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 20 10" width="20pt" height="10pt">
+        <path class="typst-shape" fill="#ffffff" fill-rule="nonzero" d="M 0 0 L 0 10 Z "/>
+        <g transform="translate(1 2)">
+            <g class="typst-text">
+                <use href="#glyph0" x="0" fill="#000000" fill-rule="nonzero"/>
+            </g>
+            <image width="4" height="4" preserveAspectRatio="none" href="data:image/png;base64,AAAA"/>
+            <g/>
+        </g>
+        <defs id="glyph">
+            <symbol id="glyph0" overflow="visible">
+                <path d="M 1 1 L 2 2 Z"/>
+            </symbol>
+            <symbol id="glyph1" overflow="visible">
+                <image width="1" height="1" preserveAspectRatio="none" href="data:image/png;base64,BBBB"/>
+            </symbol>
+        </defs>
+    </svg>"##;
+
+    #[test]
+    fn round_trip_test() {
+        let svg = parse_svg(SVG).unwrap();
+        let json = svg_to_json(&svg);
+        assert_eq!(svg_from_json(&json).unwrap(), svg);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["viewBox"], "0 0 20 10");
+        assert_eq!(value["elements"][0]["type"], "path");
+        assert_eq!(value["elements"][0]["fill-rule"], "nonzero");
+        assert!(value["elements"][0].get("stroke").is_none());
+        assert_eq!(
+            value["elements"][1]["elements"][0]["elements"][0]["type"],
+            "use"
+        );
+        assert_eq!(value["elements"][1]["elements"][1]["type"], "image");
+        assert_eq!(
+            value["elements"][2]["elements"][1]["element"]["type"],
+            "image"
+        );
+    }
+
+    #[test]
+    fn fixture_round_trip_test() {
+        for file in [
+            "expected.svg",
+            "paragraph.svg",
+            "gradient.svg",
+            "clip.svg",
+            "shadow.svg",
+            "icons.svg",
+        ] {
+            let svg = parse_svg(&fs::read_to_string(format!("./test/{}", file)).unwrap()).unwrap();
+            assert_eq!(svg_from_json(&svg_to_json(&svg)).unwrap(), svg, "{}", file);
+        }
+    }
+
+    #[test]
+    fn misplaced_element_test() {
+        let json = r##"{"class": "", "width": "", "height": "", "viewBox": "",
+            "elements": [{"type": "use", "x": "0", "href": "#a"}]}"##;
+        let error = svg_from_json(json).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("`use` is not allowed inside `svg`"));
+    }
+}
@@ -0,0 +1,81 @@
+//! Helper types kept for compatibility, which the parser and the converter do not use.
+#![allow(deprecated)]
+
+use serde::{Deserialize, Serialize};
+
+/// Represents a collection of attributes for SVG `<path>` elements.
+///
+/// The `PathEle` enumeration is used to define common attributes of the `<path>` element, such as `class`, `fill`, `d` (path data),
+/// and support **JSON serialization/deserialization**, using **kebab-case** naming format.
+///
+/// For example:
+///
+/// ```json
+/// { "class": "stroke-primary" }
+/// ```
+///
+/// # JSON format
+///
+/// - **`class`** : Specifies SVG `<path>` CSS class (string)
+/// - **`fill`** : Specifies the fill color of `<path>` (string)
+/// - **`d`** : Define path data (`d` attribute, string)
+/// - **`fill-rule`** : define filling rules (such as `"evenodd"` or `"nonzero"`)
+///
+/// Because of `#[serde(rename_all = "kebab-case")]`, all JSON fields will be automatically converted to **kebab-case** format.
+#[deprecated(note = "unused; the attributes of a path are fields of `Path`")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathEle {
+    Class(String),
+
+    Fill(String),
+
+    D(String),
+
+    FillRule(String),
+}
+
+/// Represents a `Class` struct containing text content.
+///
+/// This structure is mainly used to store text content in SVG or other XML formats, and supports Serialize and
+/// `Deserialize`.
+///
+/// # Field
+///
+/// - `content` : The actual stored text content, represented as a JSON direct string when serialized.
+#[deprecated(note = "unused; the attributes of a path are fields of `Path`")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Class {
+    #[serde(rename = "$value")]
+    pub content: String,
+}
+
+/// Represents the structure of the SVG fill attribute (`fill`).
+///
+/// This structure is used to store the value of the SVG `fill` attribute and supports Serialize and Deserialize.
+/// where the `content` field is serde serialized/deserialized to **direct text values** instead of JSON key-value pairs.
+///
+/// # Field
+///
+/// - `content` : fill color values, such as `"red"`, `"#FF0000"`, `"none"`.
+#[deprecated(note = "unused; the attributes of a path are fields of `Path`")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Fill {
+    #[serde(rename = "$value")]
+    pub content: String,
+}
+
+/// Represents a simple structure with serializable and deserialized capabilities that contains a content field.
+///
+/// This struct is mainly used to store a string field `content`, which is renamed by `$value`
+/// is the value part of JSON, using specific field names when serializing and deserializing.
+///
+/// # Field
+///
+/// - `content` : Stores the content of the string, renaming it as `$value`, which becomes the value part of the JSON when serialized.
+#[deprecated(note = "unused; the attributes of a path are fields of `Path`")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct D {
+    #[serde(rename = "$value")]
+    pub content: String,
+}
@@ -0,0 +1,316 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use super::*;
+
+/// Number of CSS pixels in an inch, the resolution used to convert between `px` and the
+/// absolute units.
+pub const PX_PER_INCH: f64 = 96.0;
+
+/// The unit of a [`Length`].
+///
+/// # Variant
+///
+/// - `None` : no unit, a length in user units (which are pixels for the root `svg`).
+/// - `Px`, `Pt`, `Pc`, `Mm`, `Cm`, `In` : the absolute CSS units. Typst writes its sizes in `pt`.
+/// - `Em`, `Ex`, `Percent` : the relative units, which cannot be converted without a context.
+/// - `Unknown(String)` : any other suffix, kept verbatim. When the value has no numeric part at
+///   all (such as `auto`), it holds the whole value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    None,
+
+    Px,
+
+    Pt,
+
+    Pc,
+
+    Mm,
+
+    Cm,
+
+    In,
+
+    Em,
+
+    Ex,
+
+    Percent,
+
+    Unknown(String),
+}
+
+impl LengthUnit {
+    /// Returns the suffix written after the number for this unit.
+    pub fn as_str(&self) -> &str {
+        match self {
+            LengthUnit::None => "",
+            LengthUnit::Px => "px",
+            LengthUnit::Pt => "pt",
+            LengthUnit::Pc => "pc",
+            LengthUnit::Mm => "mm",
+            LengthUnit::Cm => "cm",
+            LengthUnit::In => "in",
+            LengthUnit::Em => "em",
+            LengthUnit::Ex => "ex",
+            LengthUnit::Percent => "%",
+            LengthUnit::Unknown(unit) => unit,
+        }
+    }
+
+    /// Returns the number of pixels in one of this unit, or `None` for the relative and unknown
+    /// units.
+    pub fn px_per_unit(&self) -> Option<f64> {
+        match self {
+            LengthUnit::None | LengthUnit::Px => Some(1.0),
+            LengthUnit::Pt => Some(PX_PER_INCH / 72.0),
+            LengthUnit::Pc => Some(PX_PER_INCH / 6.0),
+            LengthUnit::Mm => Some(PX_PER_INCH / 25.4),
+            LengthUnit::Cm => Some(PX_PER_INCH / 2.54),
+            LengthUnit::In => Some(PX_PER_INCH),
+            LengthUnit::Em | LengthUnit::Ex | LengthUnit::Percent | LengthUnit::Unknown(_) => None,
+        }
+    }
+
+    fn from_suffix(suffix: &str) -> Self {
+        match suffix {
+            "" => LengthUnit::None,
+            "px" => LengthUnit::Px,
+            "pt" => LengthUnit::Pt,
+            "pc" => LengthUnit::Pc,
+            "mm" => LengthUnit::Mm,
+            "cm" => LengthUnit::Cm,
+            "in" => LengthUnit::In,
+            "em" => LengthUnit::Em,
+            "ex" => LengthUnit::Ex,
+            "%" => LengthUnit::Percent,
+            other => LengthUnit::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A length as written in a `width` or `height` attribute, such as `595.275591pt`.
+///
+/// Parsing never fails: a value without a numeric part is kept verbatim as a
+/// [`LengthUnit::Unknown`] with a `NaN` value, and is displayed back unchanged.
+///
+/// # Field
+///
+/// - `value` : the number, `NaN` when the attribute has no numeric part.
+/// - `unit` : the unit following the number.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_types::{Length, LengthUnit};
+///
+/// let length: Length = "72pt".parse().unwrap();
+/// assert_eq!(length.unit, LengthUnit::Pt);
+/// assert_eq!(length.to_px(), Some(96.0));
+/// assert_eq!(length.to(LengthUnit::In), Some(Length::new(1.0, LengthUnit::In)));
+/// assert_eq!(length.scale(2.0).to_string(), "144pt");
+///
+/// let auto: Length = "auto".parse().unwrap();
+/// assert_eq!(auto.unit, LengthUnit::Unknown("auto".to_string()));
+/// assert_eq!(auto.to_string(), "auto");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Length {
+    pub value: f64,
+
+    pub unit: LengthUnit,
+}
+
+impl Length {
+    /// Creates a length of `value` in `unit`.
+    pub fn new(value: f64, unit: LengthUnit) -> Self {
+        Length { value, unit }
+    }
+
+    /// Returns the length in pixels, or `None` when its unit is relative or unknown.
+    pub fn to_px(&self) -> Option<f64> {
+        Some(self.value * self.unit.px_per_unit()?).filter(|px| px.is_finite())
+    }
+
+    /// Converts the length to `unit`, or returns `None` when either unit is relative or unknown.
+    pub fn to(&self, unit: LengthUnit) -> Option<Length> {
+        let value = self.to_px()? / unit.px_per_unit()?;
+        Some(Length { value, unit })
+    }
+
+    /// Multiplies the value by `factor`, keeping the unit.
+    pub fn scale(&self, factor: f64) -> Length {
+        Length {
+            value: self.value * factor,
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+impl From<&str> for Length {
+    fn from(length: &str) -> Self {
+        let trimmed = length.trim();
+        let split = number_len(trimmed);
+        let (number, unit) = trimmed.split_at(split);
+        match number.parse::<f64>() {
+            Ok(value) => Length {
+                value,
+                unit: LengthUnit::from_suffix(unit),
+            },
+            Err(_) => Length {
+                value: f64::NAN,
+                unit: LengthUnit::Unknown(length.to_string()),
+            },
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = Infallible;
+
+    fn from_str(length: &str) -> Result<Self, Self::Err> {
+        Ok(length.into())
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.unit {
+            LengthUnit::Unknown(unit) if self.value.is_nan() => f.write_str(unit),
+            unit => write!(f, "{}{}", self.value, unit),
+        }
+    }
+}
+
+/// Returns the length of the number at the start of `s`, stopping before a unit such as `em`
+/// whose first letter could be read as an exponent.
+fn number_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let digits = |mut i: usize| {
+        while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        i
+    };
+    let mut end = usize::from(matches!(bytes.first(), Some(b'-' | b'+')));
+    end = digits(end);
+    if bytes.get(end) == Some(&b'.') {
+        end = digits(end + 1);
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exponent = end + 1;
+        if matches!(bytes.get(exponent), Some(b'-' | b'+')) {
+            exponent += 1;
+        }
+        if bytes.get(exponent).is_some_and(u8::is_ascii_digit) {
+            end = digits(exponent);
+        }
+    }
+    end
+}
+
+impl Svg {
+    /// Returns the parsed `width` attribute.
+    pub fn width_length(&self) -> Length {
+        Length::from(self.width.as_str())
+    }
+
+    /// Returns the parsed `height` attribute.
+    pub fn height_length(&self) -> Length {
+        Length::from(self.height.as_str())
+    }
+
+    /// Returns the `width` in pixels, or `None` when it is not an absolute length.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::svg_types::Svg;
+    ///
+    /// let svg = Svg::new("595.2756pt", "100%", "0 0 595.2756 841.8898");
+    /// assert_eq!(svg.width_px().map(f64::round), Some(794.0));
+    /// assert_eq!(svg.height_px(), None);
+    /// ```
+    pub fn width_px(&self) -> Option<f64> {
+        self.width_length().to_px()
+    }
+
+    /// Returns the `height` in pixels, or `None` when it is not an absolute length.
+    pub fn height_px(&self) -> Option<f64> {
+        self.height_length().to_px()
+    }
+}
+
+impl Image {
+    /// Returns the `width` in pixels, or `None` when it is not an absolute length.
+    pub fn width_px(&self) -> Option<f64> {
+        Length::from(self.width.as_str()).to_px()
+    }
+
+    /// Returns the `height` in pixels, or `None` when it is not an absolute length.
+    pub fn height_px(&self) -> Option<f64> {
+        Length::from(self.height.as_str()).to_px()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let cases = [
+            ("595.275591pt", 595.275591, LengthUnit::Pt),
+            (" 12px ", 12.0, LengthUnit::Px),
+            ("100", 100.0, LengthUnit::None),
+            ("1.5em", 1.5, LengthUnit::Em),
+            ("2ex", 2.0, LengthUnit::Ex),
+            ("1e2mm", 100.0, LengthUnit::Mm),
+            ("-.5in", -0.5, LengthUnit::In),
+            ("50%", 50.0, LengthUnit::Percent),
+            (
+                "3furlongs",
+                3.0,
+                LengthUnit::Unknown("furlongs".to_string()),
+            ),
+        ];
+        for (input, value, unit) in cases {
+            assert_eq!(Length::from(input), Length::new(value, unit), "{}", input);
+        }
+        assert_eq!(Length::from("3furlongs").to_string(), "3furlongs");
+        assert_eq!(Length::from("595.275591pt").to_string(), "595.275591pt");
+
+        let auto = Length::from("auto");
+        assert!(auto.value.is_nan());
+        assert_eq!(auto.unit, LengthUnit::Unknown("auto".to_string()));
+        assert_eq!(auto.to_string(), "auto");
+        assert_eq!(auto.to_px(), None);
+    }
+
+    #[test]
+    fn conversion_test() {
+        let inch = Length::new(1.0, LengthUnit::In);
+        assert_eq!(inch.to_px(), Some(96.0));
+        assert_eq!(
+            inch.to(LengthUnit::Pt),
+            Some(Length::new(72.0, LengthUnit::Pt))
+        );
+        let mm = inch.to(LengthUnit::Mm).unwrap();
+        assert!((mm.value - 25.4).abs() < 1e-9);
+        assert_eq!(
+            Length::new(3.0, LengthUnit::Px)
+                .to(LengthUnit::Pt)
+                .unwrap()
+                .value,
+            2.25
+        );
+        assert_eq!(Length::new(1.0, LengthUnit::Em).to_px(), None);
+        assert_eq!(inch.to(LengthUnit::Percent), None);
+    }
+}
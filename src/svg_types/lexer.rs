@@ -0,0 +1,135 @@
+//! The lexer shared by the parsers of path data ([`PathData`](super::path_data::PathData)) and
+//! transform lists ([`Transform`](super::transform::Transform)).
+
+/// A lexer over path data, reading command letters, numbers and arc flags.
+pub(crate) struct PathLexer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PathLexer<'a> {
+    pub fn new(data: &'a str) -> Self {
+        PathLexer {
+            bytes: data.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|&byte| byte == b',' || byte.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Returns the byte offset of the next token.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.bytes.len()
+    }
+
+    /// Reads a run of letters, such as the name of a transform function.
+    pub fn word(&mut self) -> &'a str {
+        self.skip_separators();
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|byte| byte.is_ascii_alphabetic())
+        {
+            self.pos += 1;
+        }
+        // The slice only holds ASCII letters
+        std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or_default()
+    }
+
+    /// Consumes `byte` if it is the next token.
+    pub fn eat(&mut self, byte: u8) -> bool {
+        self.skip_separators();
+        let found = self.bytes.get(self.pos) == Some(&byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Returns the next command letter, if the next token is one.
+    pub fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let byte = *self.bytes.get(self.pos)?;
+        if byte.is_ascii_alphabetic() {
+            self.pos += 1;
+            Some(byte)
+        } else {
+            None
+        }
+    }
+
+    /// Reads a number: optional sign, digits with at most one `.`, optional exponent.
+    pub fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let start = self.pos;
+        let peek = |pos: usize| self.bytes.get(pos).copied();
+        let mut pos = start;
+        if matches!(peek(pos), Some(b'+' | b'-')) {
+            pos += 1;
+        }
+        let mut digits = 0;
+        while peek(pos).is_some_and(|byte| byte.is_ascii_digit()) {
+            pos += 1;
+            digits += 1;
+        }
+        if peek(pos) == Some(b'.') {
+            pos += 1;
+            while peek(pos).is_some_and(|byte| byte.is_ascii_digit()) {
+                pos += 1;
+                digits += 1;
+            }
+        }
+        if digits == 0 {
+            return None;
+        }
+        if matches!(peek(pos), Some(b'e' | b'E')) {
+            let mut exponent = pos + 1;
+            if matches!(peek(exponent), Some(b'+' | b'-')) {
+                exponent += 1;
+            }
+            if peek(exponent).is_some_and(|byte| byte.is_ascii_digit()) {
+                pos = exponent;
+                while peek(pos).is_some_and(|byte| byte.is_ascii_digit()) {
+                    pos += 1;
+                }
+            }
+        }
+        // The slice only holds ASCII characters checked above
+        let number: f64 = std::str::from_utf8(&self.bytes[start..pos])
+            .ok()?
+            .parse()
+            .ok()?;
+        // `1e400` overflows to an infinity, which could not be written back
+        if !number.is_finite() {
+            return None;
+        }
+        self.pos = pos;
+        Some(number)
+    }
+
+    /// Reads an arc flag, which may be written without a separator before the next value.
+    pub fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.bytes.get(self.pos)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+}
@@ -0,0 +1,472 @@
+use super::{transform::Matrix, *};
+use crate::error::Error;
+
+/// How the `viewBox` is fitted in a viewport of another aspect ratio, as given by the
+/// `preserveAspectRatio` attribute.
+///
+/// # Variant
+///
+/// - `None` : the `viewBox` is stretched to fill the viewport, with a scale of its own on each
+///   axis.
+/// - `Align { x, y, slice }` : the `viewBox` keeps its aspect ratio, aligned in the viewport on
+///   each axis. It is scaled to fit inside the viewport (`meet`), leaving empty bands on two sides,
+///   or to cover it (`slice`), cutting two of its sides.
+///
+/// The default is `xMidYMid meet`, the value of an SVG without the attribute, such as those of
+/// Typst: the document is centered and letterboxed.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_types::{Align, PreserveAspectRatio};
+///
+/// assert_eq!(
+///     PreserveAspectRatio::parse("xMinYMax slice"),
+///     PreserveAspectRatio::Align { x: Align::Min, y: Align::Max, slice: true }
+/// );
+/// // Like browsers, an invalid value is the default
+/// assert_eq!(PreserveAspectRatio::parse("center"), PreserveAspectRatio::default());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreserveAspectRatio {
+    None,
+
+    Align { x: Align, y: Align, slice: bool },
+}
+
+/// Where the `viewBox` is placed on one axis of the viewport: at its start, its middle or its
+/// end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Min,
+
+    Mid,
+
+    Max,
+}
+
+impl Default for PreserveAspectRatio {
+    fn default() -> Self {
+        PreserveAspectRatio::Align {
+            x: Align::Mid,
+            y: Align::Mid,
+            slice: false,
+        }
+    }
+}
+
+impl PreserveAspectRatio {
+    /// Parses a `preserveAspectRatio` attribute, such as `"xMidYMid meet"` or `"none"`.
+    ///
+    /// A value that does not follow the grammar gives the default, `xMidYMid meet`, as browsers
+    /// ignore it.
+    pub fn parse(value: &str) -> Self {
+        let mut words = value.split_whitespace();
+        let align = words.next().unwrap_or_default();
+        let slice = match words.next() {
+            None | Some("meet") => false,
+            Some("slice") => true,
+            Some(_) => return Self::default(),
+        };
+        if words.next().is_some() {
+            return Self::default();
+        }
+        if align == "none" {
+            return PreserveAspectRatio::None;
+        }
+        let axis = |name: &str| match name {
+            "Min" => Some(Align::Min),
+            "Mid" => Some(Align::Mid),
+            "Max" => Some(Align::Max),
+            _ => None,
+        };
+        let parsed = align
+            .strip_prefix('x')
+            .filter(|align| align.len() == 7)
+            .and_then(|align| Some((axis(align.get(..3)?)?, align.get(3..)?.strip_prefix('Y')?)))
+            .and_then(|(x, y)| Some((x, axis(y)?)));
+        match parsed {
+            Some((x, y)) => PreserveAspectRatio::Align { x, y, slice },
+            None => Self::default(),
+        }
+    }
+}
+
+impl Align {
+    /// The share of the free space placed before the `viewBox`.
+    fn factor(self) -> f64 {
+        match self {
+            Align::Min => 0.0,
+            Align::Mid => 0.5,
+            Align::Max => 1.0,
+        }
+    }
+}
+
+/// Converts points between the coordinates of the `viewBox` of a document and the pixels of the
+/// element it is rendered in, for click-to-source or to place annotations.
+///
+/// The rendered size is the size of the `svg` element on the screen, as given by
+/// `getBoundingClientRect()`, whatever [`Sizing`](crate::options::Sizing) produced it: fixed,
+/// scaled or responsive. The rendered points are relative to the top-left corner of that
+/// element, so a mouse event gives `(client_x - rect.left, client_y - rect.top)`. When the
+/// element does not have the aspect ratio of the `viewBox`, the letterboxing of
+/// [`PreserveAspectRatio`] is taken into account.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, svg_types::CoordinateMapper};
+///
+/// let svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 100 50' width='100pt' height='50pt'></svg>",
+/// )
+/// .unwrap();
+/// let mapper = CoordinateMapper::from_svg(&svg).unwrap();
+/// // Rendered twice as large, the top-left quarter of the page is clicked
+/// assert_eq!(mapper.to_viewbox((50.0, 25.0), (200.0, 100.0)), Some((25.0, 12.5)));
+/// assert_eq!(mapper.to_rendered((25.0, 12.5), (200.0, 100.0)), (50.0, 25.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateMapper {
+    pub view_box: ViewBox,
+
+    pub preserve_aspect_ratio: PreserveAspectRatio,
+}
+
+impl CoordinateMapper {
+    /// Creates a mapper for a `viewBox` fitted as `preserve_aspect_ratio` says.
+    pub fn new(view_box: ViewBox, preserve_aspect_ratio: PreserveAspectRatio) -> Self {
+        CoordinateMapper {
+            view_box,
+            preserve_aspect_ratio,
+        }
+    }
+
+    /// Creates the mapper of a parsed document, whose `viewBox` is fitted with the default
+    /// `xMidYMid meet`.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(CoordinateMapper)` : the mapper.
+    /// - `Err(Error)` : [`Error::InvalidViewBox`] when the `viewBox` cannot be parsed or has no
+    ///   area, which renders nothing.
+    pub fn from_svg(svg: &Svg) -> Result<Self, Error> {
+        Self::from_attributes(&svg.view_box, None)
+    }
+
+    /// Creates the mapper of a rendered `svg` element from its attributes, such as those read
+    /// from the DOM.
+    ///
+    /// # parameter
+    ///
+    /// - `view_box` : the `viewBox` attribute.
+    /// - `preserve_aspect_ratio` : the `preserveAspectRatio` attribute, if any; see
+    ///   [`PreserveAspectRatio::parse`].
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(CoordinateMapper)` : the mapper.
+    /// - `Err(Error)` : [`Error::InvalidViewBox`] when the `viewBox` cannot be parsed or has no
+    ///   area, which renders nothing.
+    pub fn from_attributes(
+        view_box: &str,
+        preserve_aspect_ratio: Option<&str>,
+    ) -> Result<Self, Error> {
+        let parsed: ViewBox = view_box.parse()?;
+        if parsed.width == 0.0 || parsed.height == 0.0 {
+            return Err(Error::InvalidViewBox {
+                view_box: view_box.to_string(),
+                reason: "the width and height must be positive to map coordinates".to_string(),
+            });
+        }
+        Ok(Self::new(
+            parsed,
+            preserve_aspect_ratio
+                .map(PreserveAspectRatio::parse)
+                .unwrap_or_default(),
+        ))
+    }
+
+    /// Converts a point of the rendered element, in pixels, to the coordinates of the `viewBox`.
+    ///
+    /// # parameter
+    ///
+    /// - `point` : the point, relative to the top-left corner of the element.
+    /// - `rendered_size` : the width and height of the element, in pixels.
+    ///
+    /// # Return value
+    ///
+    /// - `Some((f64, f64))` : the point in the `viewBox`. A point in a letterboxing band maps
+    ///   outside of the `viewBox`.
+    /// - `None` : the element has no area, so no point of the document is rendered.
+    pub fn to_viewbox(&self, (x, y): (f64, f64), rendered_size: (f64, f64)) -> Option<(f64, f64)> {
+        let (scale_x, scale_y, offset_x, offset_y) = self.fit(rendered_size);
+        if scale_x == 0.0 || scale_y == 0.0 {
+            return None;
+        }
+        Some((
+            (x - offset_x) / scale_x + self.view_box.min_x,
+            (y - offset_y) / scale_y + self.view_box.min_y,
+        ))
+    }
+
+    /// Converts a point of the `viewBox` to the pixels of the rendered element, relative to its
+    /// top-left corner.
+    ///
+    /// # parameter
+    ///
+    /// - `point` : the point, in the coordinates of the `viewBox`.
+    /// - `rendered_size` : the width and height of the element, in pixels.
+    pub fn to_rendered(&self, (x, y): (f64, f64), rendered_size: (f64, f64)) -> (f64, f64) {
+        let (scale_x, scale_y, offset_x, offset_y) = self.fit(rendered_size);
+        (
+            (x - self.view_box.min_x) * scale_x + offset_x,
+            (y - self.view_box.min_y) * scale_y + offset_y,
+        )
+    }
+
+    /// Returns the transformation from the coordinates of the `viewBox` to the pixels of the
+    /// rendered element, the matrix of [`CoordinateMapper::to_rendered`].
+    pub fn to_rendered_matrix(&self, rendered_size: (f64, f64)) -> Matrix {
+        let (scale_x, scale_y, offset_x, offset_y) = self.fit(rendered_size);
+        Matrix::translate(offset_x, offset_y)
+            .then(Matrix::scale(scale_x, scale_y))
+            .then(Matrix::translate(
+                -self.view_box.min_x,
+                -self.view_box.min_y,
+            ))
+    }
+
+    /// The scales from the `viewBox` to the element and the position of the `viewBox` in it.
+    fn fit(&self, (width, height): (f64, f64)) -> (f64, f64, f64, f64) {
+        let scale_x = width / self.view_box.width;
+        let scale_y = height / self.view_box.height;
+        match self.preserve_aspect_ratio {
+            PreserveAspectRatio::None => (scale_x, scale_y, 0.0, 0.0),
+            PreserveAspectRatio::Align { x, y, slice } => {
+                let scale = if slice {
+                    scale_x.max(scale_y)
+                } else {
+                    scale_x.min(scale_y)
+                };
+                (
+                    scale,
+                    scale,
+                    (width - self.view_box.width * scale) * x.factor(),
+                    (height - self.view_box.height * scale) * y.factor(),
+                )
+            }
+        }
+    }
+}
+
+impl Symbol {
+    /// Returns the transformation from the content of the symbol to the coordinates of `uuse`,
+    /// after its `transform` and `x`: the `viewBox` of the symbol fitted to the size of the `use`
+    /// as its `preserveAspectRatio` says.
+    ///
+    /// The size of the `use` is its `width` and `height`, `100%` when they are missing or `auto`.
+    /// The overflow of the symbol is not cut.
+    ///
+    /// # parameter
+    ///
+    /// - `uuse` : the `use` referencing the symbol.
+    /// - `viewport` : the size of the viewport the percentages of the `use` are relative to, the
+    ///   `viewBox` of the document for the elements of its pages.
+    ///
+    /// # Return value
+    ///
+    /// - `Some(Matrix)` : the transformation, the identity for a symbol without a `viewBox`, or
+    ///   whose `viewBox` cannot be parsed or has no area, which is ignored like by the browsers.
+    /// - `None` : the size of the `use` cannot be resolved: a negative, relative or unknown
+    ///   length, or a percentage without a `viewport`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::svg_types::{Path, Symbol, SymbolEle, Use};
+    ///
+    /// let mut symbol = Symbol::new("s", SymbolEle::Path(Path::with_d("M 0 0 L 10 10")));
+    /// symbol.view_box = Some("0 0 10 10".to_string());
+    /// let mut uuse = Use::new("#s", "0");
+    /// uuse.width = Some("20".to_string());
+    /// uuse.height = Some("40".to_string());
+    /// // Scaled by 2 and centered vertically
+    /// assert_eq!(
+    ///     symbol.viewport_matrix(&uuse, None).unwrap().apply((10.0, 10.0)),
+    ///     (20.0, 30.0)
+    /// );
+    /// ```
+    pub fn viewport_matrix(&self, uuse: &Use, viewport: Option<(f64, f64)>) -> Option<Matrix> {
+        let mapper = self.view_box.as_deref().and_then(|view_box| {
+            CoordinateMapper::from_attributes(view_box, self.preserve_aspect_ratio.as_deref()).ok()
+        });
+        let Some(mapper) = mapper else {
+            return Some(Matrix::IDENTITY);
+        };
+        let size = |length: Option<&str>, viewport: Option<f64>| {
+            let length = Length::from(length.unwrap_or("100%"));
+            let px = match length.unit {
+                LengthUnit::Percent => length.value / 100.0 * viewport?,
+                LengthUnit::Unknown(unit) if unit.trim() == "auto" => viewport?,
+                _ => length.to_px()?,
+            };
+            Some(px).filter(|px| *px >= 0.0)
+        };
+        let width = size(uuse.width.as_deref(), viewport.map(|(width, _)| width))?;
+        let height = size(uuse.height.as_deref(), viewport.map(|(_, height)| height))?;
+        Some(mapper.to_rendered_matrix((width, height)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        for (value, expected) in [
+            ("", PreserveAspectRatio::default()),
+            ("none", PreserveAspectRatio::None),
+            ("none slice", PreserveAspectRatio::None),
+            (
+                " xMaxYMin  meet ",
+                PreserveAspectRatio::Align {
+                    x: Align::Max,
+                    y: Align::Min,
+                    slice: false,
+                },
+            ),
+            (
+                "xMidYMax slice",
+                PreserveAspectRatio::Align {
+                    x: Align::Mid,
+                    y: Align::Max,
+                    slice: true,
+                },
+            ),
+            ("xMidYMid cover", PreserveAspectRatio::default()),
+            ("xMidYMiddle", PreserveAspectRatio::default()),
+            ("xMinYMin meet extra", PreserveAspectRatio::default()),
+            ("xMéYMin", PreserveAspectRatio::default()),
+        ] {
+            assert_eq!(PreserveAspectRatio::parse(value), expected, "{:?}", value);
+        }
+    }
+
+    /// A page of 100 × 50 with its origin at (10, 20), in elements of several sizes.
+    #[test]
+    fn mapping_test() {
+        let view_box = ViewBox::new(10.0, 20.0, 100.0, 50.0);
+        let mapper =
+            |value: &str| CoordinateMapper::new(view_box, PreserveAspectRatio::parse(value));
+        for (preserve_aspect_ratio, size, viewbox_point, rendered_point) in [
+            // Same aspect ratio: a plain scale
+            ("", (200.0, 100.0), (10.0, 20.0), (0.0, 0.0)),
+            ("", (200.0, 100.0), (60.0, 45.0), (100.0, 50.0)),
+            // Too tall: bands of 75 above and below
+            ("", (200.0, 250.0), (10.0, 20.0), (0.0, 75.0)),
+            ("", (200.0, 250.0), (110.0, 70.0), (200.0, 175.0)),
+            ("xMinYMin", (200.0, 250.0), (10.0, 20.0), (0.0, 0.0)),
+            ("xMaxYMax meet", (200.0, 250.0), (10.0, 20.0), (0.0, 150.0)),
+            // Too wide: bands of 50 on the left and right
+            ("", (300.0, 100.0), (10.0, 20.0), (50.0, 0.0)),
+            ("xMaxYMid", (300.0, 100.0), (110.0, 70.0), (300.0, 100.0)),
+            // Covering: scaled by 5, 150 cut on both sides
+            (
+                "xMidYMid slice",
+                (200.0, 250.0),
+                (10.0, 20.0),
+                (-150.0, 0.0),
+            ),
+            (
+                "xMidYMid slice",
+                (200.0, 250.0),
+                (60.0, 45.0),
+                (100.0, 125.0),
+            ),
+            // Stretched: scaled by 2 and 5
+            ("none", (200.0, 250.0), (60.0, 45.0), (100.0, 125.0)),
+            ("none", (200.0, 250.0), (110.0, 70.0), (200.0, 250.0)),
+        ] {
+            let mapper = mapper(preserve_aspect_ratio);
+            assert_eq!(
+                mapper.to_rendered(viewbox_point, size),
+                rendered_point,
+                "{:?} in {:?}",
+                preserve_aspect_ratio,
+                size
+            );
+            assert_eq!(
+                mapper.to_viewbox(rendered_point, size),
+                Some(viewbox_point),
+                "{:?} in {:?}",
+                preserve_aspect_ratio,
+                size
+            );
+        }
+        assert_eq!(mapper("").to_viewbox((0.0, 0.0), (0.0, 100.0)), None);
+    }
+
+    #[test]
+    fn viewport_matrix_test() {
+        let mut symbol = Symbol::new("s", SymbolEle::Path(Path::with_d("M 0 0 L 10 10")));
+        let uuse = |width: Option<&str>, height: Option<&str>| Use {
+            width: width.map(String::from),
+            height: height.map(String::from),
+            ..Use::new("#s", "0")
+        };
+        // Without a viewBox the content keeps its size
+        assert_eq!(
+            symbol.viewport_matrix(&uuse(Some("20"), Some("20")), None),
+            Some(Matrix::IDENTITY)
+        );
+
+        symbol.view_box = Some("5 5 10 10".to_string());
+        let apply = |symbol: &Symbol, uuse: Use, viewport| {
+            symbol
+                .viewport_matrix(&uuse, viewport)
+                .map(|matrix| matrix.apply((15.0, 15.0)))
+        };
+        assert_eq!(
+            apply(&symbol, uuse(Some("20"), Some("40")), None),
+            Some((20.0, 30.0))
+        );
+        // 100% of the viewport by default
+        assert_eq!(
+            apply(&symbol, uuse(None, Some("50%")), Some((30.0, 60.0))),
+            Some((30.0, 30.0))
+        );
+        assert_eq!(
+            apply(&symbol, uuse(Some("auto"), None), Some((30.0, 60.0))),
+            Some((30.0, 45.0))
+        );
+        assert_eq!(apply(&symbol, uuse(None, None), None), None);
+        assert_eq!(apply(&symbol, uuse(Some("-1"), Some("1")), None), None);
+        assert_eq!(apply(&symbol, uuse(Some("1em"), Some("1")), None), None);
+
+        symbol.preserve_aspect_ratio = Some("none".to_string());
+        assert_eq!(
+            apply(&symbol, uuse(Some("20"), Some("40")), None),
+            Some((20.0, 40.0))
+        );
+        // A viewBox without area is ignored
+        symbol.view_box = Some("0 0 0 10".to_string());
+        assert_eq!(
+            apply(&symbol, uuse(Some("20"), Some("40")), None),
+            Some((15.0, 15.0))
+        );
+    }
+
+    #[test]
+    fn from_attributes_test() {
+        let mapper = CoordinateMapper::from_attributes("0 0 10 10", Some("none")).unwrap();
+        assert_eq!(mapper.preserve_aspect_ratio, PreserveAspectRatio::None);
+        assert!(matches!(
+            CoordinateMapper::from_attributes("0 0 0 10", None),
+            Err(Error::InvalidViewBox { .. })
+        ));
+        assert!(CoordinateMapper::from_attributes("0 0 10", None).is_err());
+    }
+}
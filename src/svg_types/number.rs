@@ -0,0 +1,53 @@
+//! The formatting of numbers shared by the writers of path data
+//! ([`PathData`](super::path_data::PathData)), transforms ([`Matrix`](super::transform::Matrix)),
+//! view boxes and the passes computing coordinates.
+
+/// Formats a computed number, dropping the floating point noise past 6 decimals, in the shortest
+/// form of [`format_shortest`].
+pub(crate) fn format_number(value: f64) -> String {
+    format_shortest(round_to(value, 6))
+}
+
+/// Formats `value` in its shortest form that parses back to it, without a leading `0` (`.5`) and
+/// never as `-0`.
+pub(crate) fn format_shortest(value: f64) -> String {
+    let number = if value == 0.0 {
+        "0".to_string()
+    } else {
+        value.to_string()
+    };
+    if let Some(fraction) = number.strip_prefix("0.") {
+        format!(".{}", fraction)
+    } else if let Some(fraction) = number.strip_prefix("-0.") {
+        format!("-.{}", fraction)
+    } else {
+        number
+    }
+}
+
+/// Rounds `value` to `decimals` decimal places, leaving the values too large to have them as is.
+pub(crate) fn round_to(value: f64, decimals: u8) -> f64 {
+    let scale = 10f64.powi(decimals.into());
+    // Past 2^53, a f64 has no decimals left to drop, and the scaled value could overflow
+    if (value * scale).abs() >= 9007199254740992.0 || !scale.is_finite() {
+        return value;
+    }
+    (value * scale).round() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_test() {
+        assert_eq!(format_shortest(0.5), ".5");
+        assert_eq!(format_shortest(-0.25), "-.25");
+        assert_eq!(format_shortest(-0.0), "0");
+        assert_eq!(format_shortest(12.0), "12");
+        assert_eq!(format_shortest(0.30000000000000004), ".30000000000000004");
+        assert_eq!(format_number(0.30000000000000004), ".3");
+        assert_eq!(format_number(-0.0000001), "0");
+        assert_eq!(format_number(1e300), format_shortest(1e300));
+    }
+}
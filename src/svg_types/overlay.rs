@@ -0,0 +1,286 @@
+use super::number::format_number;
+use super::*;
+
+/// The class of the group holding the shapes added by [`Svg::with_overlay`].
+pub const OVERLAY_CLASS: &str = "typst-overlay";
+
+/// A shape drawn above the document by [`Svg::with_overlay`], such as a highlight or a marker.
+///
+/// The coordinates are those of the `viewBox`, the ones returned by [`Svg::content_bbox`], so
+/// the shape scales and moves with the document. Every shape becomes a `<path>`.
+///
+/// # Field
+///
+/// - `geometry` : the outline of the shape.
+/// - `fill` : the `fill` of the shape, such as `"#ffeb3b"`; without one, the shape is black.
+/// - `opacity` : the `opacity` of the shape, from `0.0` to `1.0`.
+/// - `class` : the `class` of the shape, to style or select it.
+/// - `id` : the `id` of the shape, prefixed like every other by
+///   [`RsxOptions::id_prefix`](crate::options::RsxOptions::id_prefix).
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_types::OverlayShape;
+///
+/// let highlight = OverlayShape::rect(10.0, 20.0, 30.0, 5.0)
+///     .fill("#ffeb3b")
+///     .opacity(0.4)
+///     .class("highlight");
+/// assert_eq!(highlight.opacity, Some(0.4));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlayShape {
+    pub geometry: OverlayGeometry,
+
+    pub fill: Option<String>,
+
+    pub opacity: Option<f64>,
+
+    pub class: Option<String>,
+
+    pub id: Option<String>,
+}
+
+/// The outline of an [`OverlayShape`], in the coordinates of the `viewBox`.
+///
+/// # Variant
+///
+/// - `Rect` : a rectangle with its top-left corner at `x`, `y`.
+/// - `Circle` : a circle centered on `cx`, `cy` of radius `r`.
+/// - `Path(String)` : any outline, as path data such as `"M 0 0 L 10 10"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayGeometry {
+    Rect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+
+    Circle {
+        cx: f64,
+        cy: f64,
+        r: f64,
+    },
+
+    Path(String),
+}
+
+impl OverlayShape {
+    /// Creates a rectangle at `x`, `y` of size `width` × `height`, without any attribute.
+    pub fn rect(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self::new(OverlayGeometry::Rect {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Creates a circle centered on `cx`, `cy` of radius `r`, without any attribute.
+    pub fn circle(cx: f64, cy: f64, r: f64) -> Self {
+        Self::new(OverlayGeometry::Circle { cx, cy, r })
+    }
+
+    /// Creates a shape drawing the path data `d`, without any attribute.
+    pub fn path(d: impl Into<String>) -> Self {
+        Self::new(OverlayGeometry::Path(d.into()))
+    }
+
+    /// Creates a rectangle covering `bbox`, such as the one of an element found by a query.
+    pub fn around(bbox: BBox) -> Self {
+        Self::rect(bbox.min_x, bbox.min_y, bbox.width(), bbox.height())
+    }
+
+    fn new(geometry: OverlayGeometry) -> Self {
+        OverlayShape {
+            geometry,
+            fill: None,
+            opacity: None,
+            class: None,
+            id: None,
+        }
+    }
+
+    /// Sets the `fill` of the shape.
+    pub fn fill(self, fill: impl Into<String>) -> Self {
+        OverlayShape {
+            fill: Some(fill.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `opacity` of the shape.
+    pub fn opacity(self, opacity: f64) -> Self {
+        OverlayShape {
+            opacity: Some(opacity),
+            ..self
+        }
+    }
+
+    /// Sets the `class` of the shape.
+    pub fn class(self, class: impl Into<String>) -> Self {
+        OverlayShape {
+            class: Some(class.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `id` of the shape.
+    pub fn id(self, id: impl Into<String>) -> Self {
+        OverlayShape {
+            id: Some(id.into()),
+            ..self
+        }
+    }
+
+    /// The path data of the outline.
+    fn d(&self) -> String {
+        match &self.geometry {
+            OverlayGeometry::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => format!(
+                "M {} {} H {} V {} H {} Z",
+                format_number(*x),
+                format_number(*y),
+                format_number(x + width),
+                format_number(y + height),
+                format_number(*x),
+            ),
+            // Two half circles, as a single arc cannot end where it starts
+            OverlayGeometry::Circle { cx, cy, r } => format!(
+                "M {left} {cy} A {r} {r} 0 1 0 {right} {cy} A {r} {r} 0 1 0 {left} {cy} Z",
+                left = format_number(cx - r),
+                right = format_number(cx + r),
+                cy = format_number(*cy),
+                r = format_number(*r),
+            ),
+            OverlayGeometry::Path(d) => d.clone(),
+        }
+    }
+
+    fn to_path(&self) -> Path {
+        Path {
+            id: self.id.clone(),
+            class: self.class.as_deref().map(Arc::from),
+            fill: self.fill.as_deref().map(Arc::from),
+            opacity: self.opacity.map(format_number),
+            ..Path::with_d(self.d())
+        }
+    }
+}
+
+impl Svg {
+    /// Returns a copy of the document with `shapes` drawn above its content.
+    ///
+    /// The shapes are appended in a final `<g class="typst-overlay">`, which paints over
+    /// everything before it. They are in the coordinates of the `viewBox`, so they follow the
+    /// content when the document is resized, and the group goes through the passes of
+    /// [`RsxOptions`](crate::options::RsxOptions) like the rest of the document: its `id`s are
+    /// prefixed by `id_prefix`, for instance.
+    ///
+    /// # parameter
+    ///
+    /// - `shapes` : the shapes to draw, the last one on top.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::{parse_svg, svg_types::*};
+    ///
+    /// let svg = parse_svg(
+    ///     "<svg class='typst-doc' viewBox='0 0 100 100' width='100pt' height='100pt'>\
+    ///      <g transform='translate(10 20)'><path d='M 0 0 L 30 40'/></g></svg>",
+    /// )
+    /// .unwrap();
+    /// let bbox = svg.content_bbox().unwrap();
+    /// let annotated = svg.with_overlay(&[OverlayShape::around(bbox).fill("red").opacity(0.2)]);
+    /// match annotated.elements.last() {
+    ///     Some(SvgElement::G(g)) => assert_eq!(g.class.as_deref(), Some("typst-overlay")),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn with_overlay(&self, shapes: &[OverlayShape]) -> Svg {
+        let overlay = G {
+            class: Some(OVERLAY_CLASS.into()),
+            ..G::with_elements(
+                shapes
+                    .iter()
+                    .map(|shape| GEle::Path(Box::new(shape.to_path())))
+                    .collect(),
+            )
+        };
+        let mut svg = self.clone();
+        svg.elements.push(SvgElement::G(Box::new(overlay)));
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compat::render_html, options::RsxOptions, parse_svg, svg_to_rsx_with_options};
+
+    const SOURCE: &str = "<svg class='typst-doc' viewBox='0 0 100 50' width='100pt' height='50pt'>\
+         <g id='body'><path d='M 0 0 L 100 50'/></g></svg>";
+
+    #[test]
+    fn shapes_test() {
+        let svg = parse_svg(SOURCE).unwrap().with_overlay(&[
+            OverlayShape::rect(10.0, 5.0, 20.5, 10.0).id("box"),
+            OverlayShape::circle(50.0, 25.0, 5.0)
+                .fill("#ff0000")
+                .opacity(0.5),
+            OverlayShape::path("M 0 0 L 1 1").class("marker"),
+        ]);
+        assert_eq!(svg.elements.len(), 2);
+        let SvgElement::G(overlay) = &svg.elements[1] else {
+            panic!()
+        };
+        let paths: Vec<_> = overlay
+            .elements
+            .iter()
+            .flatten()
+            .map(|element| match element {
+                GEle::Path(path) => path,
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(paths[0].d, "M 10 5 H 30.5 V 15 H 10 Z");
+        assert_eq!(paths[0].id.as_deref(), Some("box"));
+        assert_eq!(paths[1].d, "M 45 25 A 5 5 0 1 0 55 25 A 5 5 0 1 0 45 25 Z");
+        assert_eq!(
+            (paths[1].fill.as_deref(), paths[1].opacity.as_deref()),
+            (Some("#ff0000"), Some(".5"))
+        );
+        assert_eq!(paths[2].class.as_deref(), Some("marker"));
+
+        // The outlines are bounded like any other path
+        let bbox = svg.content_bbox().unwrap();
+        assert_eq!((bbox.max_x, bbox.max_y), (100.0, 50.0));
+    }
+
+    /// The overlay comes last in the RSX and its ids are prefixed like the others.
+    #[test]
+    fn rsx_test() {
+        let svg = parse_svg(SOURCE)
+            .unwrap()
+            .with_overlay(&[OverlayShape::rect(0.0, 0.0, 10.0, 10.0)
+                .id("note")
+                .opacity(0.25)]);
+        let options = RsxOptions {
+            id_prefix: Some("a-".to_string()),
+            ..Default::default()
+        };
+        let html = render_html(svg_to_rsx_with_options(svg, &options));
+        let body = html.find(r#"id="a-body""#).unwrap();
+        let overlay = html.find(r#"class="typst-overlay""#).unwrap();
+        assert!(body < overlay);
+        assert!(html.contains(r#"id="a-note""#));
+        assert!(html.contains(r#"opacity=".25""#));
+    }
+}
@@ -0,0 +1,508 @@
+//! Parsing and serialization of the `d` attribute of `path` elements.
+//!
+//! [`PathData::parse`] reads path data into a list of [`PathCommand`]s, one per segment, keeping
+//! each command absolute or relative as written. This is the same grammar the crate's own passes
+//! use to compute bounding boxes and flatten transforms, exposed for doing other geometry on
+//! Typst's output.
+//!
+//! # Example
+//!
+//! ```rust
+//! use typst_2_rsx::svg_types::path_data::{PathCommand, PathData};
+//!
+//! let data = PathData::parse("M 10 10 L 20 10 l 0 10 Z").unwrap();
+//! let ends: Vec<_> = data
+//!     .iter()
+//!     .filter_map(|command| match command {
+//!         PathCommand::LineTo { relative, to } => Some((*relative, *to)),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(ends, vec![(false, (20.0, 10.0)), (true, (0.0, 10.0))]);
+//! assert_eq!(data.to_string(), "M10 10 20 10l0 10Z");
+//! ```
+
+use std::{fmt, slice, str::FromStr};
+
+use super::{lexer::PathLexer, number::format_shortest};
+
+/// A point of path data, absolute or relative to the current point depending on the command.
+pub type Point = (f64, f64);
+
+/// One command of path data, with its arguments.
+///
+/// Commands repeated implicitly in the source (`L 1 1 2 2`, or the coordinate pairs following a
+/// `M`) are separate commands. `relative` is `true` for the lowercase form of the command.
+///
+/// # Variant
+///
+/// - `MoveTo` : `M`/`m`, starts a new subpath at `to`.
+/// - `LineTo` : `L`/`l`, a straight line to `to`.
+/// - `HorizontalTo`, `VerticalTo` : `H`/`h` and `V`/`v`, a horizontal or vertical line.
+/// - `CubicTo` : `C`/`c`, a cubic Bézier curve with the control points `c1` and `c2`.
+/// - `SmoothCubicTo` : `S`/`s`, a cubic curve whose first control point is the reflection of
+///   the previous one.
+/// - `QuadTo` : `Q`/`q`, a quadratic Bézier curve with the control point `c`.
+/// - `SmoothQuadTo` : `T`/`t`, a quadratic curve with a reflected control point.
+/// - `Arc` : `A`/`a`, an elliptical arc with radii `rx` and `ry`, its x axis rotated by
+///   `rotation` degrees.
+/// - `Close` : `Z`/`z`, which means the same in both cases.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo {
+        relative: bool,
+        to: Point,
+    },
+
+    LineTo {
+        relative: bool,
+        to: Point,
+    },
+
+    HorizontalTo {
+        relative: bool,
+        x: f64,
+    },
+
+    VerticalTo {
+        relative: bool,
+        y: f64,
+    },
+
+    CubicTo {
+        relative: bool,
+        c1: Point,
+        c2: Point,
+        to: Point,
+    },
+
+    SmoothCubicTo {
+        relative: bool,
+        c2: Point,
+        to: Point,
+    },
+
+    QuadTo {
+        relative: bool,
+        c: Point,
+        to: Point,
+    },
+
+    SmoothQuadTo {
+        relative: bool,
+        to: Point,
+    },
+
+    Arc {
+        relative: bool,
+        rx: f64,
+        ry: f64,
+        rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: Point,
+    },
+
+    Close,
+}
+
+impl PathCommand {
+    /// Returns the letter of the command, lowercase for the relative commands.
+    pub fn letter(&self) -> char {
+        let (letter, relative) = match *self {
+            PathCommand::MoveTo { relative, .. } => ('M', relative),
+            PathCommand::LineTo { relative, .. } => ('L', relative),
+            PathCommand::HorizontalTo { relative, .. } => ('H', relative),
+            PathCommand::VerticalTo { relative, .. } => ('V', relative),
+            PathCommand::CubicTo { relative, .. } => ('C', relative),
+            PathCommand::SmoothCubicTo { relative, .. } => ('S', relative),
+            PathCommand::QuadTo { relative, .. } => ('Q', relative),
+            PathCommand::SmoothQuadTo { relative, .. } => ('T', relative),
+            PathCommand::Arc { relative, .. } => ('A', relative),
+            PathCommand::Close => ('Z', false),
+        };
+        if relative {
+            letter.to_ascii_lowercase()
+        } else {
+            letter
+        }
+    }
+
+    /// Returns the command with `f` applied to each of its numbers, the arc flags excepted.
+    pub(crate) fn map_numbers(self, f: impl Fn(f64) -> f64) -> PathCommand {
+        let point = |(x, y): Point| (f(x), f(y));
+        match self {
+            PathCommand::MoveTo { relative, to } => PathCommand::MoveTo {
+                relative,
+                to: point(to),
+            },
+            PathCommand::LineTo { relative, to } => PathCommand::LineTo {
+                relative,
+                to: point(to),
+            },
+            PathCommand::HorizontalTo { relative, x } => {
+                PathCommand::HorizontalTo { relative, x: f(x) }
+            }
+            PathCommand::VerticalTo { relative, y } => {
+                PathCommand::VerticalTo { relative, y: f(y) }
+            }
+            PathCommand::CubicTo {
+                relative,
+                c1,
+                c2,
+                to,
+            } => PathCommand::CubicTo {
+                relative,
+                c1: point(c1),
+                c2: point(c2),
+                to: point(to),
+            },
+            PathCommand::SmoothCubicTo { relative, c2, to } => PathCommand::SmoothCubicTo {
+                relative,
+                c2: point(c2),
+                to: point(to),
+            },
+            PathCommand::QuadTo { relative, c, to } => PathCommand::QuadTo {
+                relative,
+                c: point(c),
+                to: point(to),
+            },
+            PathCommand::SmoothQuadTo { relative, to } => PathCommand::SmoothQuadTo {
+                relative,
+                to: point(to),
+            },
+            PathCommand::Arc {
+                relative,
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => PathCommand::Arc {
+                relative,
+                rx: f(rx),
+                ry: f(ry),
+                rotation: f(rotation),
+                large_arc,
+                sweep,
+                to: point(to),
+            },
+            PathCommand::Close => PathCommand::Close,
+        }
+    }
+
+    /// Returns the arguments of the command in the order of the grammar, arc flags being 0 or 1.
+    fn arguments(&self) -> Vec<f64> {
+        match *self {
+            PathCommand::MoveTo { to, .. }
+            | PathCommand::LineTo { to, .. }
+            | PathCommand::SmoothQuadTo { to, .. } => vec![to.0, to.1],
+            PathCommand::HorizontalTo { x: value, .. }
+            | PathCommand::VerticalTo { y: value, .. } => vec![value],
+            PathCommand::CubicTo { c1, c2, to, .. } => vec![c1.0, c1.1, c2.0, c2.1, to.0, to.1],
+            PathCommand::SmoothCubicTo { c2: c, to, .. } | PathCommand::QuadTo { c, to, .. } => {
+                vec![c.0, c.1, to.0, to.1]
+            }
+            PathCommand::Arc {
+                rx,
+                ry,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+                ..
+            } => vec![
+                rx,
+                ry,
+                rotation,
+                large_arc as u8 as f64,
+                sweep as u8 as f64,
+                to.0,
+                to.1,
+            ],
+            PathCommand::Close => vec![],
+        }
+    }
+}
+
+/// The error returned by [`PathData::parse`] for malformed path data.
+///
+/// # Field
+///
+/// - `position` : the byte offset in the path data where parsing stopped.
+/// - `reason` : what was expected at that position.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("Invalid path data at byte {position}: {reason}")]
+pub struct PathDataError {
+    pub position: usize,
+    pub reason: String,
+}
+
+/// Parsed path data: the commands of a `d` attribute in the order they are written.
+///
+/// # Field
+///
+/// - `commands` : the commands, one per segment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathData {
+    pub commands: Vec<PathCommand>,
+}
+
+impl PathData {
+    /// Parses path data.
+    ///
+    /// Command letters implied by the previous command may be left out (coordinates following a
+    /// `M` being lines), numbers may follow each other without a separator when the grammar
+    /// allows it (`.5.5`, `1-2`), and arc flags may be written without spaces (`a1 1 0 011 1`).
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(PathData)` : the commands of the data, empty for empty data.
+    /// - `Err(PathDataError)` : the position and reason of the first error.
+    pub fn parse(data: &str) -> Result<PathData, PathDataError> {
+        let mut lexer = PathLexer::new(data);
+        let mut commands = Vec::new();
+        let mut previous: Option<u8> = None;
+        let error = |lexer: &PathLexer, reason: &str| PathDataError {
+            position: lexer.position(),
+            reason: reason.to_string(),
+        };
+
+        while !lexer.at_end() {
+            let letter = match lexer.command() {
+                Some(letter) => letter,
+                None => match previous {
+                    None => return Err(error(&lexer, "expected a command")),
+                    Some(b'Z' | b'z') => return Err(error(&lexer, "expected a command after `Z`")),
+                    Some(b'M') => b'L',
+                    Some(b'm') => b'l',
+                    Some(letter) => letter,
+                },
+            };
+            previous = Some(letter);
+            let relative = letter.is_ascii_lowercase();
+            let number = |lexer: &mut PathLexer| {
+                lexer
+                    .number()
+                    .ok_or_else(|| error(lexer, "expected a number"))
+            };
+            let point = |lexer: &mut PathLexer| Ok((number(lexer)?, number(lexer)?));
+            let flag = |lexer: &mut PathLexer| {
+                lexer
+                    .flag()
+                    .ok_or_else(|| error(lexer, "expected an arc flag"))
+            };
+
+            let command = match letter.to_ascii_uppercase() {
+                b'M' => PathCommand::MoveTo {
+                    relative,
+                    to: point(&mut lexer)?,
+                },
+                b'L' => PathCommand::LineTo {
+                    relative,
+                    to: point(&mut lexer)?,
+                },
+                b'H' => PathCommand::HorizontalTo {
+                    relative,
+                    x: number(&mut lexer)?,
+                },
+                b'V' => PathCommand::VerticalTo {
+                    relative,
+                    y: number(&mut lexer)?,
+                },
+                b'C' => PathCommand::CubicTo {
+                    relative,
+                    c1: point(&mut lexer)?,
+                    c2: point(&mut lexer)?,
+                    to: point(&mut lexer)?,
+                },
+                b'S' => PathCommand::SmoothCubicTo {
+                    relative,
+                    c2: point(&mut lexer)?,
+                    to: point(&mut lexer)?,
+                },
+                b'Q' => PathCommand::QuadTo {
+                    relative,
+                    c: point(&mut lexer)?,
+                    to: point(&mut lexer)?,
+                },
+                b'T' => PathCommand::SmoothQuadTo {
+                    relative,
+                    to: point(&mut lexer)?,
+                },
+                b'A' => PathCommand::Arc {
+                    relative,
+                    rx: number(&mut lexer)?,
+                    ry: number(&mut lexer)?,
+                    rotation: number(&mut lexer)?,
+                    large_arc: flag(&mut lexer)?,
+                    sweep: flag(&mut lexer)?,
+                    to: point(&mut lexer)?,
+                },
+                b'Z' => PathCommand::Close,
+                _ => {
+                    return Err(PathDataError {
+                        position: lexer.position() - 1,
+                        reason: format!("unknown command `{}`", letter as char),
+                    })
+                }
+            };
+            commands.push(command);
+        }
+        Ok(PathData { commands })
+    }
+
+    /// Returns an iterator over the commands.
+    pub fn iter(&self) -> slice::Iter<'_, PathCommand> {
+        self.commands.iter()
+    }
+}
+
+impl FromStr for PathData {
+    type Err = PathDataError;
+
+    fn from_str(data: &str) -> Result<Self, Self::Err> {
+        PathData::parse(data)
+    }
+}
+
+impl IntoIterator for PathData {
+    type Item = PathCommand;
+    type IntoIter = std::vec::IntoIter<PathCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PathData {
+    type Item = &'a PathCommand;
+    type IntoIter = slice::Iter<'a, PathCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.iter()
+    }
+}
+
+impl fmt::Display for PathData {
+    /// Writes the commands compactly: numbers in their shortest form, without the separators
+    /// the grammar does not need, and without the command letters implied by the previous
+    /// command. The output parses back to the same commands.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The letter repeated when the next command is implicit
+        let mut repeated = None;
+        // Whether the output ends with a number, and whether that number contains a `.`
+        let mut after_number = None;
+        for command in &self.commands {
+            let letter = command.letter();
+            if Some(letter) != repeated {
+                write!(f, "{}", letter)?;
+                after_number = None;
+            }
+            repeated = match letter {
+                'M' => Some('L'),
+                'm' => Some('l'),
+                'Z' => None,
+                letter => Some(letter),
+            };
+            for number in command.arguments().into_iter().map(format_shortest) {
+                let needs_separator = match after_number {
+                    None => false,
+                    Some(_) if number.starts_with('-') => false,
+                    Some(has_dot) => !(number.starts_with('.') && has_dot),
+                };
+                if needs_separator {
+                    f.write_str(" ")?;
+                }
+                f.write_str(&number)?;
+                after_number = Some(number.contains('.'));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn parse_test() {
+        let data = PathData::parse("m1 1 2 0h1v-1zl.5.5a1 1 0 011 1").unwrap();
+        assert_eq!(
+            data.commands,
+            vec![
+                PathCommand::MoveTo {
+                    relative: true,
+                    to: (1.0, 1.0)
+                },
+                PathCommand::LineTo {
+                    relative: true,
+                    to: (2.0, 0.0)
+                },
+                PathCommand::HorizontalTo {
+                    relative: true,
+                    x: 1.0
+                },
+                PathCommand::VerticalTo {
+                    relative: true,
+                    y: -1.0
+                },
+                PathCommand::Close,
+                PathCommand::LineTo {
+                    relative: true,
+                    to: (0.5, 0.5)
+                },
+                PathCommand::Arc {
+                    relative: true,
+                    rx: 1.0,
+                    ry: 1.0,
+                    rotation: 0.0,
+                    large_arc: false,
+                    sweep: true,
+                    to: (1.0, 1.0)
+                },
+            ]
+        );
+        assert_eq!(data.to_string(), "m1 1 2 0h1v-1Zl.5.5a1 1 0 0 1 1 1");
+        assert_eq!(PathData::parse(" ").unwrap(), PathData::default());
+    }
+
+    #[test]
+    fn error_test() {
+        for (data, position, reason) in [
+            ("1 2", 0, "expected a command"),
+            ("M 1", 3, "expected a number"),
+            ("M 1 2 Z 3 4", 8, "expected a command after `Z`"),
+            ("M 0 0 A 1 1 0 2 0 1 1", 14, "expected an arc flag"),
+            ("M 0 0 X 1", 6, "unknown command `X`"),
+        ] {
+            assert_eq!(
+                PathData::parse(data),
+                Err(PathDataError {
+                    position,
+                    reason: reason.to_string()
+                }),
+                "{}",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let svg = fs::read_to_string("./test/expected.svg").unwrap();
+        let mut count = 0;
+        for value in svg.split(" d=\"").skip(1) {
+            let value = &value[..value.find('"').unwrap()];
+            let data = PathData::parse(value).unwrap();
+            let written = data.to_string();
+            assert_eq!(PathData::parse(&written).unwrap(), data, "{}", value);
+            assert!(written.len() <= value.len(), "{}", value);
+            count += 1;
+        }
+        assert!(count > 10);
+    }
+}
@@ -0,0 +1,238 @@
+use dioxus::prelude::Element;
+
+use super::*;
+use crate::passes::prune_unused_defs;
+
+/// A reference to one element of an [`Svg`] tree, as returned by the queries of [`Svg`].
+///
+/// # Variant
+///
+/// - `Path(&Path)` : a `<path>`, at the top level, in a group or in a symbol.
+/// - `G(&G)` : a `<g>` group.
+/// - `Use(&Use)` : a `<use>` reference.
+/// - `Image(&Image)` : an `<image>`, in a group or in a symbol.
+/// - `Symbol(&Symbol)` : a `<symbol>` of the `<defs>`.
+/// - `Rect(&Rect)` : a `<rect>`.
+/// - `Text(&Text)` : a `<text>`, with its `tspan`.
+/// - `Switch(&Switch)` : a `<switch>`, followed by all its alternatives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SvgNodeRef<'a> {
+    Path(&'a Path),
+
+    G(&'a G),
+
+    Use(&'a Use),
+
+    Image(&'a Image),
+
+    Symbol(&'a Symbol),
+
+    Rect(&'a Rect),
+
+    Text(&'a Text),
+
+    Switch(&'a Switch),
+}
+
+impl<'a> SvgNodeRef<'a> {
+    /// The `id` of the element, for the elements that can have one.
+    pub fn id(&self) -> Option<&'a str> {
+        match *self {
+            SvgNodeRef::Path(path) => path.id.as_deref(),
+            SvgNodeRef::G(g) => g.id.as_deref(),
+            SvgNodeRef::Symbol(symbol) => Some(&symbol.id),
+            SvgNodeRef::Text(text) => text.id.as_deref(),
+            SvgNodeRef::Switch(switch) => switch.id.as_deref(),
+            SvgNodeRef::Use(_) | SvgNodeRef::Image(_) | SvgNodeRef::Rect(_) => None,
+        }
+    }
+
+    /// The `class` list of the element, for the elements that can have one.
+    pub fn class(&self) -> Option<&'a str> {
+        match *self {
+            SvgNodeRef::Path(path) => path.class.as_deref(),
+            SvgNodeRef::G(g) => g.class.as_deref(),
+            SvgNodeRef::Text(text) => text.class.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Collects the elements accepted by a predicate, in document order.
+struct Collect<'a, F> {
+    accept: F,
+    found: Vec<SvgNodeRef<'a>>,
+}
+
+impl<'a, F: FnMut(&SvgNodeRef<'a>) -> bool> Collect<'a, F> {
+    fn push(&mut self, node: SvgNodeRef<'a>) {
+        if (self.accept)(&node) {
+            self.found.push(node);
+        }
+    }
+}
+
+impl<'a, F: FnMut(&SvgNodeRef<'a>) -> bool> Visitor<'a> for Collect<'a, F> {
+    fn visit_path(&mut self, path: &'a Path) {
+        self.push(SvgNodeRef::Path(path));
+    }
+
+    fn visit_g(&mut self, g: &'a G) {
+        self.push(SvgNodeRef::G(g));
+    }
+
+    fn visit_use(&mut self, uuse: &'a Use) {
+        self.push(SvgNodeRef::Use(uuse));
+    }
+
+    fn visit_image(&mut self, image: &'a Image) {
+        self.push(SvgNodeRef::Image(image));
+    }
+
+    fn visit_symbol(&mut self, symbol: &'a Symbol) {
+        self.push(SvgNodeRef::Symbol(symbol));
+    }
+
+    fn visit_rect(&mut self, rect: &'a Rect) {
+        self.push(SvgNodeRef::Rect(rect));
+    }
+
+    fn visit_text(&mut self, text: &'a Text) {
+        self.push(SvgNodeRef::Text(text));
+    }
+
+    fn visit_switch(&mut self, switch: &'a Switch) {
+        self.push(SvgNodeRef::Switch(switch));
+    }
+}
+
+impl Svg {
+    /// Returns the elements accepted by `accept`, in document order, including the symbols and
+    /// their content.
+    pub fn find_all(&self, accept: impl FnMut(&SvgNodeRef) -> bool) -> Vec<SvgNodeRef<'_>> {
+        let mut collect = Collect {
+            accept,
+            found: Vec::new(),
+        };
+        self.walk(&mut collect);
+        collect.found
+    }
+
+    /// Returns the first element whose `id` is `id`, searching the symbols too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::{parse_svg, svg_types::SvgNodeRef};
+    ///
+    /// let svg = parse_svg(
+    ///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+    ///      <g id='fig-label-3'><path d='M 0 0 L 1 1'/></g></svg>",
+    /// )
+    /// .unwrap();
+    /// assert!(matches!(svg.find_by_id("fig-label-3"), Some(SvgNodeRef::G(_))));
+    /// ```
+    pub fn find_by_id(&self, id: &str) -> Option<SvgNodeRef<'_>> {
+        self.find_all(|node| node.id() == Some(id))
+            .into_iter()
+            .next()
+    }
+
+    /// Returns the elements whose `class` list contains `class`, in document order.
+    pub fn find_by_class(&self, class: &str) -> Vec<SvgNodeRef<'_>> {
+        self.find_all(|node| {
+            node.class()
+                .is_some_and(|classes| classes.split_whitespace().any(|name| name == class))
+        })
+    }
+
+    /// Renders one element of the document on its own, like [`svg_to_rsx`](crate::svg_to_rsx).
+    ///
+    /// The element keeps the size and `viewBox` of the document, so it appears where it was, and
+    /// the symbols it references are included. The transforms of the groups around it are not
+    /// applied. A symbol is rendered through its content, placed at the origin.
+    pub fn subtree_to_rsx(&self, node: &SvgNodeRef) -> Element {
+        let wrap = |element: GEle| SvgElement::G(Box::new(G::with_elements(vec![element])));
+        let element = match *node {
+            SvgNodeRef::Path(path) => SvgElement::Path(Box::new(path.clone())),
+            SvgNodeRef::G(g) => SvgElement::G(Box::new(g.clone())),
+            SvgNodeRef::Use(uuse) => wrap(GEle::Use(Box::new(uuse.clone()))),
+            SvgNodeRef::Image(image) => wrap(GEle::Image(Box::new(image.clone()))),
+            SvgNodeRef::Symbol(symbol) => match &symbol.element {
+                SymbolEle::Path(path) => SvgElement::Path(Box::new(path.clone())),
+                SymbolEle::Image(image) => wrap(GEle::Image(Box::new(image.clone()))),
+            },
+            SvgNodeRef::Rect(rect) => SvgElement::Rect(rect.clone()),
+            SvgNodeRef::Text(text) => wrap(GEle::Text(Box::new(text.clone()))),
+            SvgNodeRef::Switch(switch) => wrap(GEle::Switch(Box::new(switch.clone()))),
+        };
+
+        let mut elements = vec![element];
+        elements.extend(
+            self.elements
+                .iter()
+                .filter(|element| matches!(element, SvgElement::Defs(_)))
+                .cloned(),
+        );
+        let mut subtree = Svg {
+            class: self.class.clone(),
+            width: self.width.clone(),
+            height: self.height.clone(),
+            view_box: self.view_box.clone(),
+            elements,
+        };
+        prune_unused_defs(&mut subtree);
+        crate::svg_to_rsx(&subtree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    const SVG: &str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+        <g id="figure" class="typst-group">
+            <g id="label" class="typst-text highlight"><use href="#glyph0" x="0"/></g>
+            <path id="arrow" class="highlight" d="M 0 0 L 1 1"/>
+        </g>
+        <g class="typst-text"><use href="#glyph1" x="0"/></g>
+        <defs id="glyph">
+            <symbol id="glyph0" overflow="visible"><path d="M 1 1 L 2 2"/></symbol>
+            <symbol id="glyph1" overflow="visible"><path d="M 3 3 L 4 4"/></symbol>
+        </defs>
+    </svg>"##;
+
+    #[test]
+    fn find_test() {
+        let svg = parse_svg(SVG).unwrap();
+        assert!(
+            matches!(svg.find_by_id("label"), Some(SvgNodeRef::G(g)) if g.class.as_deref() == Some("typst-text highlight"))
+        );
+        assert!(matches!(svg.find_by_id("arrow"), Some(SvgNodeRef::Path(_))));
+        assert!(matches!(
+            svg.find_by_id("glyph1"),
+            Some(SvgNodeRef::Symbol(_))
+        ));
+        assert_eq!(svg.find_by_id("missing"), None);
+
+        let ids: Vec<_> = svg
+            .find_by_class("highlight")
+            .iter()
+            .map(|node| node.id().unwrap())
+            .collect();
+        assert_eq!(ids, ["label", "arrow"]);
+        assert_eq!(svg.find_by_class("typst-text").len(), 2);
+        assert!(svg.find_by_class("typst").is_empty());
+    }
+
+    #[test]
+    fn subtree_test() {
+        let svg = parse_svg(SVG).unwrap();
+        let label = svg.find_by_id("label").unwrap();
+        let rendered = format!("{:?}", svg.subtree_to_rsx(&label));
+        assert!(rendered.contains("glyph0"));
+        assert!(!rendered.contains("glyph1"));
+        assert!(!rendered.contains("M 0 0 L 1 1"));
+    }
+}
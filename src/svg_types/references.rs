@@ -0,0 +1,271 @@
+use std::{collections::HashSet, fmt};
+
+use super::*;
+use crate::passes::Siblings;
+
+/// A local reference of an [`Svg`] that no element of the document answers, returned by
+/// [`Svg::validate_references`].
+///
+/// # Field
+///
+/// - `from_path` : the element holding the reference, designated from the root like in the
+///   [`Warning`](crate::report::Warning)s (`svg > g[0] > use[3]`).
+/// - `target_id` : the `id` referred to, without the `#`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BrokenRef {
+    pub from_path: String,
+
+    pub target_id: String,
+}
+
+impl fmt::Display for BrokenRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: reference to `#{}` matches no element",
+            self.from_path, self.target_id
+        )
+    }
+}
+
+impl Svg {
+    /// Returns the local references that no element of the document answers, in document order.
+    ///
+    /// Every `href="#id"` of the `use`, `image` and gradient elements and every `url(#id)` of the
+    /// paint attributes and of the `clip-path`, `mask` and `filter` of groups is checked against
+    /// the `id`s of the `defs`, `symbol`, gradient, `clipPath`, `mask`, `filter`, `g`, `path` and `text` elements. A `use` whose symbol was never there, or was removed by a pass, renders nothing
+    /// and is not reported otherwise, so the tests of the passes that remove or rename elements
+    /// can assert that none is left. References to other documents and `data:` URIs are not
+    /// checked.
+    ///
+    /// The conversions with a [`ConversionReport`](crate::report::ConversionReport) report them
+    /// in debug builds, and [`Svg::stats`] counts them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::parse_svg;
+    ///
+    /// let svg = parse_svg(
+    ///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+    ///      <g><use href='#glyph0' x='0'/><use href='#glyph42' x='5'/></g>\
+    ///      <defs id='glyph'><symbol id='glyph0' overflow='visible'><path d='M 0 0'/></symbol></defs>\
+    ///      </svg>",
+    /// )
+    /// .unwrap();
+    /// let broken = svg.validate_references();
+    /// assert_eq!(broken.len(), 1);
+    /// assert_eq!(broken[0].to_string(), "svg > g[0] > use[1]: reference to `#glyph42` matches no element");
+    /// ```
+    pub fn validate_references(&self) -> Vec<BrokenRef> {
+        let mut validator = Validator {
+            ids: HashSet::new(),
+            references: Vec::new(),
+        };
+        let mut siblings = Siblings::new("svg");
+        for element in &self.elements {
+            match element {
+                SvgElement::Path(path) => validator.path(path, siblings.path("path")),
+                SvgElement::G(g) => validator.g(g, siblings.path("g")),
+                SvgElement::Defs(defs) => {
+                    let path = siblings.path("defs");
+                    validator.ids.insert(&defs.id);
+                    let mut symbols = Siblings::new(path);
+                    for symbol in &defs.elements {
+                        let path = symbols.path("symbol");
+                        validator.ids.insert(&symbol.id);
+                        match &symbol.element {
+                            SymbolEle::Path(symbol_path) => {
+                                validator.path(symbol_path, format!("{} > path[0]", path))
+                            }
+                            SymbolEle::Image(image) => {
+                                validator.href(&image.href, format!("{} > image[0]", path))
+                            }
+                        }
+                    }
+                    for definition in &defs.definitions {
+                        let path = symbols.path(definition.tag());
+                        validator.ids.insert(definition.id());
+                        if let Some(href) = definition.href() {
+                            validator.href(href, path.clone());
+                        }
+                        let mut paths = Siblings::new(path);
+                        for definition_path in definition.paths() {
+                            validator.path(definition_path, paths.path("path"));
+                        }
+                    }
+                }
+                SvgElement::Rect(rect) => {
+                    validator.urls(rect.fill.as_deref(), siblings.path("rect"));
+                }
+            }
+        }
+        let Validator { ids, references } = validator;
+        references
+            .into_iter()
+            .filter(|(_, target)| !ids.contains(target))
+            .map(|(from_path, target)| BrokenRef {
+                from_path,
+                target_id: target.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Gathers the `id`s of a document and its references, with the path of the element holding each.
+struct Validator<'a> {
+    ids: HashSet<&'a str>,
+    references: Vec<(String, &'a str)>,
+}
+
+impl<'a> Validator<'a> {
+    fn g(&mut self, g: &'a G, path: String) {
+        self.id(g.id.as_deref());
+        self.urls(g.clip_path.as_deref(), path.clone());
+        self.urls(g.mask.as_deref(), path.clone());
+        self.urls(g.filter.as_deref(), path.clone());
+        let mut siblings = Siblings::new(path);
+        for element in g.elements.iter().flatten() {
+            self.g_ele(element, &mut siblings);
+        }
+    }
+
+    fn g_ele(&mut self, element: &'a GEle, siblings: &mut Siblings) {
+        match element {
+            GEle::G(g) => self.g(g, siblings.path("g")),
+            GEle::Use(uuse) => {
+                let path = siblings.path("use");
+                self.href(&uuse.href, path.clone());
+                self.urls(uuse.fill.as_deref(), path);
+            }
+            GEle::Path(path) => self.path(path, siblings.path("path")),
+            GEle::Image(image) => self.href(&image.href, siblings.path("image")),
+            GEle::Text(text) => {
+                let path = siblings.path("text");
+                self.id(text.id.as_deref());
+                self.urls(text.fill.as_deref(), path.clone());
+                let mut tspans = Siblings::new(path);
+                for content in &text.content {
+                    if let TextContent::Tspan(tspan) = content {
+                        self.urls(tspan.fill.as_deref(), tspans.path("tspan"));
+                    }
+                }
+            }
+            GEle::Switch(switch) => {
+                self.id(switch.id.as_deref());
+                let mut branches = Siblings::new(siblings.path("switch"));
+                for element in switch.elements() {
+                    self.g_ele(element, &mut branches);
+                }
+            }
+        }
+    }
+
+    fn path(&mut self, path: &'a Path, element_path: String) {
+        self.id(path.id.as_deref());
+        self.urls(path.fill.as_deref(), element_path.clone());
+        self.urls(path.stroke.as_deref(), element_path);
+    }
+
+    fn id(&mut self, id: Option<&'a str>) {
+        if let Some(id) = id {
+            self.ids.insert(id);
+        }
+    }
+
+    fn href(&mut self, href: &'a str, path: String) {
+        if let Some(target) = href_target(href) {
+            self.references.push((path, target));
+        }
+    }
+
+    fn urls(&mut self, value: Option<&'a str>, path: String) {
+        for target in url_targets(value.unwrap_or_default()) {
+            self.references.push((path.clone(), target));
+        }
+    }
+}
+
+impl Path {
+    /// Whether the fill or the stroke is a paint server referenced by `url(…)`, such as a
+    /// gradient, which is laid out in the box of the path or in its user space.
+    pub(crate) fn has_paint_server(&self) -> bool {
+        [&self.fill, &self.stroke]
+            .into_iter()
+            .flatten()
+            .any(|paint| paint.trim_start().starts_with("url("))
+    }
+}
+
+/// The `id` a local `href="#id"` refers to.
+pub(crate) fn href_target(href: &str) -> Option<&str> {
+    href.strip_prefix('#')
+}
+
+/// The `id`s the local `url(#id)` references of an attribute value refer to, quoted or not.
+pub(crate) fn url_targets(value: &str) -> Vec<&str> {
+    let mut targets = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("url(") {
+        rest = rest[start + 4..].trim_start_matches(['\'', '"']);
+        let end = rest.find(['\'', '"', ')']).unwrap_or(rest.len());
+        if let Some(id) = rest[..end].strip_prefix('#') {
+            targets.push(id);
+        }
+        rest = &rest[end..];
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{options::RsxOptions, parse_svg, parse_svg_to_rsx_with_report, report::Warning};
+
+    #[test]
+    fn url_targets_test() {
+        assert_eq!(
+            url_targets("url(#a) url('#b') url(\"#c\") url(d.svg#e)"),
+            ["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn validate_test() {
+        let svg_str = r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <path fill="url(#paint)" d="M 0 0 L 1 1"/>
+                <g id="label">
+                    <use href="#glyph0" x="0"/>
+                    <use href="#label" x="0"/>
+                    <use href="other.svg#glyph0" x="0"/>
+                    <text fill="#000000" x="0">a<tspan fill="url('#gone')">b</tspan></text>
+                </g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path stroke="url(#glyph)" d="M 0 0"/></symbol>
+                </defs>
+            </svg>"##;
+        let svg = parse_svg(svg_str).unwrap();
+        let broken: Vec<_> = svg
+            .validate_references()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            broken,
+            [
+                "svg > path[0]: reference to `#paint` matches no element",
+                "svg > g[0] > text[0] > tspan[0]: reference to `#gone` matches no element",
+            ]
+        );
+        assert_eq!(svg.stats().broken_references, 2);
+
+        // Reported by the conversions in debug builds
+        let (_, report) = parse_svg_to_rsx_with_report(svg_str, &RsxOptions::default()).unwrap();
+        let reported = report
+            .warnings
+            .iter()
+            .filter(|warning| matches!(warning, Warning::BrokenReference { .. }))
+            .count();
+        assert_eq!(reported, if cfg!(debug_assertions) { 2 } else { 0 });
+    }
+}
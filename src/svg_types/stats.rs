@@ -0,0 +1,189 @@
+use std::fmt;
+
+use super::*;
+
+/// Element counts of an [`Svg`] document, returned by [`Svg::stats`].
+///
+/// # Field
+///
+/// - `paths` : number of `path` elements, including the ones inside symbols.
+/// - `groups` : number of `g` elements.
+/// - `uses` : number of `use` elements.
+/// - `symbols` : number of `symbol` elements.
+/// - `images` : number of `image` elements, including the ones inside symbols.
+/// - `rects` : number of `rect` elements.
+/// - `texts` : number of `text` elements, the `tspan` they hold being part of them.
+/// - `total_path_bytes` : total length of the `d` attributes of the paths, in bytes, which makes
+///   up most of the size of a document.
+/// - `max_depth` : nesting level of the deepest element, the children of the root `svg` being at
+///   level 1. The content of a symbol is at level 3, below the `defs` and the `symbol`, and so are
+///   the stops of a gradient.
+/// - `broken_references` : number of local references that no element answers; see
+///   [`Svg::validate_references`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SvgStats {
+    pub paths: usize,
+
+    pub groups: usize,
+
+    pub uses: usize,
+
+    pub symbols: usize,
+
+    pub images: usize,
+
+    pub rects: usize,
+
+    pub texts: usize,
+
+    pub total_path_bytes: usize,
+
+    pub max_depth: usize,
+
+    pub broken_references: usize,
+}
+
+impl fmt::Display for SvgStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} paths ({} bytes), {} groups, {} uses, {} symbols, {} images, {} rects, {} texts, \
+             depth {}, {} broken references",
+            self.paths,
+            self.total_path_bytes,
+            self.groups,
+            self.uses,
+            self.symbols,
+            self.images,
+            self.rects,
+            self.texts,
+            self.max_depth,
+            self.broken_references
+        )
+    }
+}
+
+impl Visitor<'_> for SvgStats {
+    fn visit_path(&mut self, path: &Path) {
+        self.paths += 1;
+        self.total_path_bytes += path.d.len();
+    }
+
+    fn visit_g(&mut self, _g: &G) {
+        self.groups += 1;
+    }
+
+    fn visit_use(&mut self, _uuse: &Use) {
+        self.uses += 1;
+    }
+
+    fn visit_image(&mut self, _image: &Image) {
+        self.images += 1;
+    }
+
+    fn visit_symbol(&mut self, _symbol: &Symbol) {
+        self.symbols += 1;
+    }
+
+    fn visit_rect(&mut self, _rect: &Rect) {
+        self.rects += 1;
+    }
+
+    fn visit_text(&mut self, _text: &Text) {
+        self.texts += 1;
+    }
+}
+
+impl Svg {
+    /// Counts the elements of the document, for keeping an eye on the size of the converted
+    /// output or checking the effect of a pass.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::parse_svg;
+    ///
+    /// let svg = parse_svg(
+    ///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+    ///      <g><path d='M 0 0 L 1 1'/></g></svg>",
+    /// )
+    /// .unwrap();
+    /// let stats = svg.stats();
+    /// assert_eq!((stats.paths, stats.groups, stats.max_depth), (1, 1, 2));
+    /// println!("{}", stats);
+    /// ```
+    pub fn stats(&self) -> SvgStats {
+        fn g_depth(g: &G) -> usize {
+            1 + g.elements.iter().flatten().map(depth).max().unwrap_or(0)
+        }
+
+        fn depth(element: &GEle) -> usize {
+            match element {
+                GEle::G(g) => g_depth(g),
+                GEle::Text(text) => {
+                    let tspan = |content: &TextContent| matches!(content, TextContent::Tspan(_));
+                    1 + usize::from(text.content.iter().any(tspan))
+                }
+                GEle::Switch(switch) => 1 + switch.elements().map(depth).max().unwrap_or(0),
+                GEle::Use(_) | GEle::Path(_) | GEle::Image(_) => 1,
+            }
+        }
+
+        let mut stats = SvgStats::default();
+        self.walk(&mut stats);
+        stats.max_depth = self
+            .elements
+            .iter()
+            .map(|element| match element {
+                SvgElement::G(g) => g_depth(g),
+                SvgElement::Defs(defs)
+                    if !defs.elements.is_empty()
+                        || defs.definitions.iter().any(|definition| {
+                            !definition.stops().is_empty()
+                                || !definition.paths().is_empty()
+                                || !definition.primitives().is_empty()
+                        }) =>
+                {
+                    3
+                }
+                SvgElement::Defs(defs) if !defs.definitions.is_empty() => 2,
+                SvgElement::Path(_) | SvgElement::Defs(_) | SvgElement::Rect(_) => 1,
+            })
+            .max()
+            .unwrap_or(0);
+        stats.broken_references = self.validate_references().len();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_svg, passes::prune_unused_defs};
+
+    #[test]
+    fn stats_test() {
+        let svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <path d="M 0 0 L 0 10 Z"/>
+                <g><g><use href="#glyph0" x="0"/></g></g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path d="M 1 1 L 2 2"/></symbol>
+                    <symbol id="glyph1" overflow="visible"><path d="M 1 1"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        let stats = svg.stats();
+        assert_eq!(
+            stats.to_string(),
+            "3 paths (30 bytes), 2 groups, 1 uses, 2 symbols, 0 images, 0 rects, 0 texts, depth 3, \
+             0 broken references"
+        );
+
+        let mut pruned = svg.clone();
+        prune_unused_defs(&mut pruned);
+        assert_eq!(pruned.stats().symbols, 1);
+        assert_eq!(pruned.stats().paths, 2);
+        assert_eq!(pruned.stats().broken_references, 0);
+    }
+}
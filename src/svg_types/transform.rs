@@ -0,0 +1,419 @@
+//! Structured access to the `transform` attribute of `g`, `use` and `image` elements.
+//!
+//! A [`Transform`] is the parsed list of operations of the SVG transform-list grammar
+//! (`translate(10 20) scale(.5)`). It composes to a [`Matrix`], applies to points, and displays
+//! back to a compact string that parses to the same operations.
+//!
+//! # Example
+//!
+//! ```rust
+//! use typst_2_rsx::svg_types::transform::{Transform, TransformOp};
+//!
+//! let transform: Transform = "translate(10, 20) scale(2)".parse().unwrap();
+//! assert_eq!(
+//!     transform.ops,
+//!     vec![
+//!         TransformOp::Translate { x: 10.0, y: 20.0 },
+//!         TransformOp::Scale { x: 2.0, y: 2.0 },
+//!     ]
+//! );
+//! assert_eq!(transform.apply((1.0, 1.0)), (12.0, 22.0));
+//! assert_eq!(transform.to_string(), "translate(10 20) scale(2)");
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use super::lexer::PathLexer;
+use super::number::format_number;
+use crate::error::Error;
+
+/// A 2D affine transformation, in the `matrix(a b c d e f)` layout of SVG.
+///
+/// A point `(x, y)` is mapped to `(a * x + c * y + e, b * x + d * y + f)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Matrix {
+    /// The transformation leaving every point in place.
+    pub const IDENTITY: Matrix = Matrix {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    /// A translation by `x`, `y`.
+    pub fn translate(x: f64, y: f64) -> Matrix {
+        Matrix {
+            e: x,
+            f: y,
+            ..Matrix::IDENTITY
+        }
+    }
+
+    /// A scaling by `x` horizontally and `y` vertically.
+    pub fn scale(x: f64, y: f64) -> Matrix {
+        Matrix {
+            a: x,
+            d: y,
+            ..Matrix::IDENTITY
+        }
+    }
+
+    /// A rotation by `angle` degrees.
+    pub fn rotate(angle: f64) -> Matrix {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Matrix {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            ..Matrix::IDENTITY
+        }
+    }
+
+    /// Returns `self * other`: `other` is applied first, then `self`.
+    pub fn then(self, other: Matrix) -> Matrix {
+        Matrix {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    /// Maps a point through the transformation.
+    pub fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Whether the matrix is the identity, up to floating point noise.
+    pub fn is_identity(&self) -> bool {
+        const EPSILON: f64 = 1e-12;
+        [self.a - 1.0, self.b, self.c, self.d - 1.0, self.e, self.f]
+            .iter()
+            .all(|value| value.abs() < EPSILON)
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "matrix({} {} {} {} {} {})",
+            format_number(self.a),
+            format_number(self.b),
+            format_number(self.c),
+            format_number(self.d),
+            format_number(self.e),
+            format_number(self.f)
+        )
+    }
+}
+
+/// One operation of a transform list.
+///
+/// # Variant
+///
+/// - `Matrix(Matrix)` : `matrix(a b c d e f)`.
+/// - `Translate { x, y }` : `translate(x y)`, `y` being 0 when omitted.
+/// - `Scale { x, y }` : `scale(x y)`, `y` being `x` when omitted.
+/// - `Rotate { angle, center }` : `rotate(angle)` around the origin, or `rotate(angle cx cy)`
+///   around `center`. The angle is in degrees.
+/// - `SkewX(f64)`, `SkewY(f64)` : `skewX(angle)` and `skewY(angle)`, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransformOp {
+    Matrix(Matrix),
+
+    Translate {
+        x: f64,
+        y: f64,
+    },
+
+    Scale {
+        x: f64,
+        y: f64,
+    },
+
+    Rotate {
+        angle: f64,
+        center: Option<(f64, f64)>,
+    },
+
+    SkewX(f64),
+
+    SkewY(f64),
+}
+
+impl TransformOp {
+    /// Returns the matrix of the operation.
+    pub fn to_matrix(&self) -> Matrix {
+        match *self {
+            TransformOp::Matrix(matrix) => matrix,
+            TransformOp::Translate { x, y } => Matrix::translate(x, y),
+            TransformOp::Scale { x, y } => Matrix::scale(x, y),
+            TransformOp::Rotate {
+                angle,
+                center: None,
+            } => Matrix::rotate(angle),
+            TransformOp::Rotate {
+                angle,
+                center: Some((x, y)),
+            } => Matrix::translate(x, y)
+                .then(Matrix::rotate(angle))
+                .then(Matrix::translate(-x, -y)),
+            TransformOp::SkewX(angle) => Matrix {
+                c: angle.to_radians().tan(),
+                ..Matrix::IDENTITY
+            },
+            TransformOp::SkewY(angle) => Matrix {
+                b: angle.to_radians().tan(),
+                ..Matrix::IDENTITY
+            },
+        }
+    }
+}
+
+impl fmt::Display for TransformOp {
+    /// Writes the shortest form of the operation, leaving out the optional parameters that have
+    /// their default value.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TransformOp::Matrix(Matrix {
+                a,
+                b,
+                c,
+                d,
+                e,
+                f: ff,
+            }) => {
+                write!(f, "matrix({} {} {} {} {} {})", a, b, c, d, e, ff)
+            }
+            TransformOp::Translate { x, y } => {
+                if y == 0.0 {
+                    write!(f, "translate({})", x)
+                } else {
+                    write!(f, "translate({} {})", x, y)
+                }
+            }
+            TransformOp::Scale { x, y } if x == y => write!(f, "scale({})", x),
+            TransformOp::Scale { x, y } => write!(f, "scale({} {})", x, y),
+            TransformOp::Rotate {
+                angle,
+                center: None,
+            } => write!(f, "rotate({})", angle),
+            TransformOp::Rotate {
+                angle,
+                center: Some((x, y)),
+            } => write!(f, "rotate({} {} {})", angle, x, y),
+            TransformOp::SkewX(angle) => write!(f, "skewX({})", angle),
+            TransformOp::SkewY(angle) => write!(f, "skewY({})", angle),
+        }
+    }
+}
+
+/// A parsed transform list, applied from right to left like in SVG.
+///
+/// An empty list is the identity.
+///
+/// # Field
+///
+/// - `ops` : the operations in the order they are written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transform {
+    pub ops: Vec<TransformOp>,
+}
+
+impl Transform {
+    /// Parses a transform list.
+    ///
+    /// Operations and their arguments may be separated by whitespace and commas, numbers may use
+    /// scientific notation, and optional parameters may be left out.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(Transform)` : the operations of the list.
+    /// - `Err(Error)` : [`Error::InvalidTransform`] naming the problem, such as an unknown
+    ///   function or a wrong number of arguments.
+    pub fn parse(transform: &str) -> Result<Transform, Error> {
+        let invalid = |reason: String| Error::InvalidTransform {
+            transform: transform.to_string(),
+            reason,
+        };
+        let mut lexer = PathLexer::new(transform);
+        let mut ops = Vec::new();
+        while !lexer.at_end() {
+            let name = lexer.word();
+            if name.is_empty() {
+                return Err(invalid("expected a transform function".to_string()));
+            }
+            if !lexer.eat(b'(') {
+                return Err(invalid(format!("expected `(` after `{}`", name)));
+            }
+            let mut arguments = Vec::new();
+            while let Some(number) = lexer.number() {
+                arguments.push(number);
+            }
+            if !lexer.eat(b')') {
+                return Err(invalid(format!("expected a number or `)` in `{}`", name)));
+            }
+            let op = match (name, &arguments[..]) {
+                ("matrix", &[a, b, c, d, e, f]) => TransformOp::Matrix(Matrix { a, b, c, d, e, f }),
+                ("translate", &[x]) => TransformOp::Translate { x, y: 0.0 },
+                ("translate", &[x, y]) => TransformOp::Translate { x, y },
+                ("scale", &[s]) => TransformOp::Scale { x: s, y: s },
+                ("scale", &[x, y]) => TransformOp::Scale { x, y },
+                ("rotate", &[angle]) => TransformOp::Rotate {
+                    angle,
+                    center: None,
+                },
+                ("rotate", &[angle, x, y]) => TransformOp::Rotate {
+                    angle,
+                    center: Some((x, y)),
+                },
+                ("skewX", &[angle]) => TransformOp::SkewX(angle),
+                ("skewY", &[angle]) => TransformOp::SkewY(angle),
+                ("matrix" | "translate" | "scale" | "rotate" | "skewX" | "skewY", _) => {
+                    return Err(invalid(format!(
+                        "wrong number of arguments to `{}`: {}",
+                        name,
+                        arguments.len()
+                    )))
+                }
+                _ => return Err(invalid(format!("unknown transform function `{}`", name))),
+            };
+            ops.push(op);
+        }
+        Ok(Transform { ops })
+    }
+
+    /// Returns the matrix the operations compose to.
+    pub fn to_matrix(&self) -> Matrix {
+        self.ops
+            .iter()
+            .fold(Matrix::IDENTITY, |matrix, op| matrix.then(op.to_matrix()))
+    }
+
+    /// Maps a point through the transformation.
+    pub fn apply(&self, point: (f64, f64)) -> (f64, f64) {
+        self.to_matrix().apply(point)
+    }
+
+    /// Returns the transformation applying `self` after `other`, like an element with the
+    /// transform `self` whose child has the transform `other`.
+    pub fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            ops: self.ops.iter().chain(&other.ops).copied().collect(),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = Error;
+
+    fn from_str(transform: &str) -> Result<Self, Self::Err> {
+        Transform::parse(transform)
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, op) in self.ops.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", op)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn parse_test() {
+        let transform =
+            Transform::parse("rotate(45)rotate(45 , 10,10),skewX(1e1) scale(.5-.5)").unwrap();
+        assert_eq!(
+            transform.ops,
+            vec![
+                TransformOp::Rotate {
+                    angle: 45.0,
+                    center: None
+                },
+                TransformOp::Rotate {
+                    angle: 45.0,
+                    center: Some((10.0, 10.0))
+                },
+                TransformOp::SkewX(10.0),
+                TransformOp::Scale { x: 0.5, y: -0.5 },
+            ]
+        );
+        assert_eq!(
+            transform.to_string(),
+            "rotate(45) rotate(45 10 10) skewX(10) scale(0.5 -0.5)"
+        );
+        assert_eq!(Transform::parse(" ").unwrap(), Transform::default());
+
+        for (input, reason) in [
+            ("translate(1 2", "expected a number or `)` in `translate`"),
+            ("perspective(1)", "unknown transform function `perspective`"),
+            ("rotate(1 2)", "wrong number of arguments to `rotate`: 2"),
+            ("scale 2", "expected `(` after `scale`"),
+            ("(1)", "expected a transform function"),
+        ] {
+            let error = Transform::parse(input).unwrap_err();
+            assert!(
+                matches!(&error, Error::InvalidTransform { reason: r, .. } if r == reason),
+                "{}: {}",
+                input,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let mut count = 0;
+        for entry in fs::read_dir("./test").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|extension| extension != "svg") {
+                continue;
+            }
+            // Skipping the fixtures in other encodings than UTF-8
+            let Ok(svg) = fs::read_to_string(&path) else {
+                continue;
+            };
+            for value in svg.split("transform=\"").skip(1) {
+                let value = &value[..value.find('"').unwrap()];
+                let transform = Transform::parse(value).unwrap();
+                let reparsed = Transform::parse(&transform.to_string()).unwrap();
+                assert_eq!(reparsed, transform, "{}", value);
+                assert!(transform.to_string().len() <= value.len(), "{}", value);
+                count += 1;
+            }
+        }
+        assert!(count > 100);
+    }
+}
@@ -0,0 +1,145 @@
+use std::{fmt, str::FromStr};
+
+use super::number::format_number;
+use super::*;
+use crate::error::Error;
+
+/// The four numbers of a `viewBox` attribute: the user-space rectangle shown by the viewport.
+///
+/// The attribute stays a string on [`Svg`], so that a malformed value is only an error for the
+/// operations that need its numbers; [`Svg::view_box_parsed`] parses it on demand.
+///
+/// # Field
+///
+/// - `min_x`, `min_y` : the top-left corner of the rectangle.
+/// - `width`, `height` : the size of the rectangle, never negative.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::svg_types::ViewBox;
+///
+/// let view_box: ViewBox = "0,0 595.28 841.89".parse().unwrap();
+/// assert_eq!(view_box.width, 595.28);
+/// assert_eq!(view_box.to_string(), "0 0 595.28 841.89");
+/// assert!("0 0 10".parse::<ViewBox>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewBox {
+    pub min_x: f64,
+
+    pub min_y: f64,
+
+    pub width: f64,
+
+    pub height: f64,
+}
+
+impl ViewBox {
+    /// Creates a view box from its corner and size.
+    pub fn new(min_x: f64, min_y: f64, width: f64, height: f64) -> Self {
+        ViewBox {
+            min_x,
+            min_y,
+            width,
+            height,
+        }
+    }
+}
+
+impl FromStr for ViewBox {
+    type Err = Error;
+
+    /// Parses four numbers separated by whitespace and/or commas.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(ViewBox)` : the parsed numbers.
+    /// - `Err(Error)` : [`Error::InvalidViewBox`] when there are not exactly four numbers, one of
+    ///   them is not a finite number, or the width or height is negative.
+    fn from_str(view_box: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| Error::InvalidViewBox {
+            view_box: view_box.to_string(),
+            reason,
+        };
+        let numbers = view_box
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|number| !number.is_empty())
+            .map(|number| match number.parse::<f64>() {
+                Ok(value) if value.is_finite() => Ok(value),
+                _ => Err(invalid(format!("`{}` is not a number", number))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let [min_x, min_y, width, height] = numbers[..] else {
+            return Err(invalid(format!(
+                "expected 4 numbers, found {}",
+                numbers.len()
+            )));
+        };
+        if width < 0.0 || height < 0.0 {
+            return Err(invalid(
+                "the width and height cannot be negative".to_string(),
+            ));
+        }
+        Ok(ViewBox::new(min_x, min_y, width, height))
+    }
+}
+
+impl fmt::Display for ViewBox {
+    /// Writes the four numbers separated by spaces, dropping the floating point noise past 6
+    /// decimals.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            format_number(self.min_x),
+            format_number(self.min_y),
+            format_number(self.width),
+            format_number(self.height)
+        )
+    }
+}
+
+impl Svg {
+    /// Parses the `viewBox` attribute.
+    ///
+    /// # Return value
+    ///
+    /// - `Ok(ViewBox)` : the four numbers of the attribute.
+    /// - `Err(Error)` : [`Error::InvalidViewBox`] describing why the attribute is malformed.
+    pub fn view_box_parsed(&self) -> Result<ViewBox, Error> {
+        self.view_box.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(
+            " 9 2.75,18.2  15.75 ".parse::<ViewBox>().unwrap(),
+            ViewBox::new(9.0, 2.75, 18.2, 15.75)
+        );
+        for (view_box, reason) in [
+            ("0 0 10", "expected 4 numbers, found 3"),
+            ("0 0 10 10 10", "expected 4 numbers, found 5"),
+            ("0 0 auto 10", "`auto` is not a number"),
+            ("0 0 NaN 10", "`NaN` is not a number"),
+            ("0 0 -1 10", "the width and height cannot be negative"),
+        ] {
+            let error = view_box.parse::<ViewBox>().unwrap_err();
+            assert!(
+                matches!(&error, Error::InvalidViewBox { reason: r, .. } if r == reason),
+                "{}: {}",
+                view_box,
+                error
+            );
+        }
+        assert_eq!(
+            ViewBox::new(0.0, -0.0, 18.200000000000003, 1e-9).to_string(),
+            "0 0 18.2 0"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use super::*;
+
+/// Receives the elements of an [`Svg`] tree walked by [`Svg::walk`].
+///
+/// Every method has an empty default implementation, so a visitor only implements the elements
+/// it is interested in. The elements are visited in document order, each parent before its
+/// children: a `g` before its content and a `symbol` before its path or image. The `defs`
+/// themselves are not visited, only their symbols and then their other definitions, such as a
+/// gradient before its stops, and a `text` is visited with its `tspan`. A
+/// `switch` is visited before all its alternatives, whichever is rendered. The paths of a clip
+/// path or a mask, which are not drawn, are not visited; [`Definition::paths`] gives them. Nor
+/// are the primitives of a filter, given by [`Definition::primitives`].
+///
+/// The lifetime `'a` is the one of the tree, so a visitor can keep references to the elements it
+/// visits.
+///
+/// # Example
+///
+/// ```rust
+/// use typst_2_rsx::{parse_svg, svg_types::*};
+///
+/// struct CountPaths(usize);
+///
+/// impl Visitor<'_> for CountPaths {
+///     fn visit_path(&mut self, _path: &Path) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// let svg = parse_svg(
+///     "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+///      <path d='M 0 0'/><g><path d='M 1 1'/></g></svg>",
+/// )
+/// .unwrap();
+/// let mut count = CountPaths(0);
+/// svg.walk(&mut count);
+/// assert_eq!(count.0, 2);
+/// ```
+pub trait Visitor<'a> {
+    fn visit_path(&mut self, _path: &'a Path) {}
+
+    fn visit_g(&mut self, _g: &'a G) {}
+
+    fn visit_use(&mut self, _uuse: &'a Use) {}
+
+    fn visit_image(&mut self, _image: &'a Image) {}
+
+    fn visit_symbol(&mut self, _symbol: &'a Symbol) {}
+
+    fn visit_definition(&mut self, _definition: &'a Definition) {}
+
+    fn visit_stop(&mut self, _stop: &'a Stop) {}
+
+    fn visit_rect(&mut self, _rect: &'a Rect) {}
+
+    fn visit_text(&mut self, _text: &'a Text) {}
+
+    fn visit_switch(&mut self, _switch: &'a Switch) {}
+}
+
+/// Modifies the elements of an [`Svg`] tree walked by [`Svg::walk_mut`].
+///
+/// The counterpart of [`Visitor`] for in-place transforms, visiting the elements in the same
+/// order. The children of a `g` are walked after [`visit_g`](VisitorMut::visit_g) returns, so
+/// the elements it adds to the group are visited too.
+pub trait VisitorMut {
+    fn visit_path(&mut self, _path: &mut Path) {}
+
+    fn visit_g(&mut self, _g: &mut G) {}
+
+    fn visit_use(&mut self, _uuse: &mut Use) {}
+
+    fn visit_image(&mut self, _image: &mut Image) {}
+
+    fn visit_symbol(&mut self, _symbol: &mut Symbol) {}
+
+    fn visit_definition(&mut self, _definition: &mut Definition) {}
+
+    fn visit_stop(&mut self, _stop: &mut Stop) {}
+
+    fn visit_rect(&mut self, _rect: &mut Rect) {}
+
+    fn visit_text(&mut self, _text: &mut Text) {}
+
+    fn visit_switch(&mut self, _switch: &mut Switch) {}
+}
+
+impl Svg {
+    /// Calls `visitor` on every element of the document, in document order.
+    pub fn walk<'a>(&'a self, visitor: &mut impl Visitor<'a>) {
+        for element in &self.elements {
+            match element {
+                SvgElement::Path(path) => visitor.visit_path(path),
+                SvgElement::G(g) => walk_g(g, visitor),
+                SvgElement::Defs(defs) => {
+                    for symbol in &defs.elements {
+                        visitor.visit_symbol(symbol);
+                        match &symbol.element {
+                            SymbolEle::Path(path) => visitor.visit_path(path),
+                            SymbolEle::Image(image) => visitor.visit_image(image),
+                        }
+                    }
+                    for definition in &defs.definitions {
+                        visitor.visit_definition(definition);
+                        for stop in definition.stops() {
+                            visitor.visit_stop(stop);
+                        }
+                    }
+                }
+                SvgElement::Rect(rect) => visitor.visit_rect(rect),
+            }
+        }
+    }
+
+    /// Calls `visitor` on every element of the document, in document order, allowing it to
+    /// modify them.
+    pub fn walk_mut(&mut self, visitor: &mut impl VisitorMut) {
+        for element in &mut self.elements {
+            match element {
+                SvgElement::Path(path) => visitor.visit_path(path),
+                SvgElement::G(g) => walk_g_mut(g, visitor),
+                SvgElement::Defs(defs) => {
+                    for symbol in &mut defs.elements {
+                        visitor.visit_symbol(symbol);
+                        match &mut symbol.element {
+                            SymbolEle::Path(path) => visitor.visit_path(path),
+                            SymbolEle::Image(image) => visitor.visit_image(image),
+                        }
+                    }
+                    for definition in &mut defs.definitions {
+                        visitor.visit_definition(definition);
+                        for stop in definition.stops_mut() {
+                            visitor.visit_stop(stop);
+                        }
+                    }
+                }
+                SvgElement::Rect(rect) => visitor.visit_rect(rect),
+            }
+        }
+    }
+}
+
+fn walk_g<'a>(g: &'a G, visitor: &mut impl Visitor<'a>) {
+    visitor.visit_g(g);
+    for element in g.elements.iter().flatten() {
+        walk_g_ele(element, visitor);
+    }
+}
+
+fn walk_g_ele<'a>(element: &'a GEle, visitor: &mut impl Visitor<'a>) {
+    match element {
+        GEle::G(g) => walk_g(g, visitor),
+        GEle::Use(uuse) => visitor.visit_use(uuse),
+        GEle::Path(path) => visitor.visit_path(path),
+        GEle::Image(image) => visitor.visit_image(image),
+        GEle::Text(text) => visitor.visit_text(text),
+        GEle::Switch(switch) => {
+            visitor.visit_switch(switch);
+            for element in switch.elements() {
+                walk_g_ele(element, visitor);
+            }
+        }
+    }
+}
+
+fn walk_g_mut(g: &mut G, visitor: &mut impl VisitorMut) {
+    visitor.visit_g(g);
+    for element in g.elements.iter_mut().flatten() {
+        walk_g_ele_mut(element, visitor);
+    }
+}
+
+fn walk_g_ele_mut(element: &mut GEle, visitor: &mut impl VisitorMut) {
+    match element {
+        GEle::G(g) => walk_g_mut(g, visitor),
+        GEle::Use(uuse) => visitor.visit_use(uuse),
+        GEle::Path(path) => visitor.visit_path(path),
+        GEle::Image(image) => visitor.visit_image(image),
+        GEle::Text(text) => visitor.visit_text(text),
+        GEle::Switch(switch) => {
+            visitor.visit_switch(switch);
+            for element in switch.elements_mut() {
+                walk_g_ele_mut(element, visitor);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_svg;
+
+    /// Records the name of every element visited.
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl Visitor<'_> for Trace {
+        fn visit_path(&mut self, path: &Path) {
+            self.0.push(format!("path {}", path.d));
+        }
+
+        fn visit_g(&mut self, g: &G) {
+            self.0
+                .push(format!("g {}", g.class.as_deref().unwrap_or_default()));
+        }
+
+        fn visit_use(&mut self, uuse: &Use) {
+            self.0.push(format!("use {}", uuse.href));
+        }
+
+        fn visit_symbol(&mut self, symbol: &Symbol) {
+            self.0.push(format!("symbol {}", symbol.id));
+        }
+    }
+
+    #[test]
+    fn order_test() {
+        let svg = parse_svg(
+            r##"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+                <path d="M 0 0"/>
+                <g class="outer">
+                    <g class="inner"><use href="#glyph0" x="0"/></g>
+                    <path d="M 1 1"/>
+                </g>
+                <defs id="glyph">
+                    <symbol id="glyph0" overflow="visible"><path d="M 2 2"/></symbol>
+                </defs>
+            </svg>"##,
+        )
+        .unwrap();
+        let mut trace = Trace::default();
+        svg.walk(&mut trace);
+        assert_eq!(
+            trace.0,
+            [
+                "path M 0 0",
+                "g outer",
+                "g inner",
+                "use #glyph0",
+                "path M 1 1",
+                "symbol glyph0",
+                "path M 2 2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,593 @@
+use super::*;
+
+impl Svg {
+    /// Serializes the SVG back to an XML string.
+    ///
+    /// The output uses the same element and attribute layout as Typst's SVG export (including
+    /// `xlink:href` references), so parsing it again yields an `Svg` equal to `self`. This makes it
+    /// possible to parse a document, transform it (recolor, prefix ids, ...) and hand the result to
+    /// consumers that expect SVG text rather than a Dioxus `Element`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use typst_2_rsx::svg_types::Svg;
+    ///
+    /// let svg_str = r#"<svg class="typst-doc" viewBox="0 0 10 10" width="10pt" height="10pt">
+    ///     <path d="M 0 0 L 10 10"/>
+    /// </svg>"#;
+    /// let svg: Svg = serde_xml_rs::from_str(svg_str).unwrap();
+    /// let output = svg.to_svg_string();
+    /// assert_eq!(serde_xml_rs::from_str::<Svg>(&output).unwrap(), svg);
+    /// ```
+    pub fn to_svg_string(&self) -> String {
+        let mut writer = XmlWriter::default();
+        writer.open(
+            "svg",
+            &[
+                ("class", Some(self.class.as_str())),
+                ("viewBox", Some(self.view_box.as_str())),
+                ("width", Some(self.width.as_str())),
+                ("height", Some(self.height.as_str())),
+            ],
+            &[
+                ("xmlns", "http://www.w3.org/2000/svg"),
+                ("xmlns:xlink", "http://www.w3.org/1999/xlink"),
+            ],
+            false,
+        );
+        for element in &self.elements {
+            match element {
+                SvgElement::Path(path) => write_path(&mut writer, path),
+                SvgElement::G(g) => write_g(&mut writer, g),
+                SvgElement::Defs(defs) => {
+                    writer.open("defs", &[("id", Some(defs.id.as_str()))], &[], false);
+                    for symbol in &defs.elements {
+                        write_symbol(&mut writer, symbol);
+                    }
+                    for definition in &defs.definitions {
+                        write_definition(&mut writer, definition);
+                    }
+                    writer.close("defs");
+                }
+                SvgElement::Rect(rect) => writer.open(
+                    "rect",
+                    &[
+                        ("x", Some(rect.x.as_str())),
+                        ("y", Some(rect.y.as_str())),
+                        ("width", Some(rect.width.as_str())),
+                        ("height", Some(rect.height.as_str())),
+                        ("fill", rect.fill.as_deref()),
+                    ],
+                    &[],
+                    true,
+                ),
+            }
+        }
+        writer.close("svg");
+        writer.out
+    }
+}
+
+fn write_path(writer: &mut XmlWriter, path: &Path) {
+    writer.open(
+        "path",
+        &[
+            ("id", path.id.as_deref()),
+            ("class", path.class.as_deref()),
+            ("fill", path.fill.as_deref()),
+            ("fill-rule", path.fill_rule.as_ref().map(FillRule::as_str)),
+            ("stroke", path.stroke.as_deref()),
+            ("stroke-width", path.stroke_width.as_deref()),
+            (
+                "stroke-linecap",
+                path.stroke_linecap.as_ref().map(LineCap::as_str),
+            ),
+            (
+                "stroke-linejoin",
+                path.stroke_linejoin.as_ref().map(LineJoin::as_str),
+            ),
+            ("stroke-miterlimit", path.stroke_miterlimit.as_deref()),
+            ("tabindex", path.tabindex.as_deref()),
+            ("pointer-events", path.pointer_events.as_deref()),
+            ("opacity", path.opacity.as_deref()),
+            ("shape-rendering", path.shape_rendering.as_deref()),
+            ("d", Some(path.d.as_str())),
+        ],
+        &[],
+        true,
+    );
+}
+
+fn write_g(writer: &mut XmlWriter, g: &G) {
+    let attributes = [
+        ("id", g.id.as_deref()),
+        ("class", g.class.as_deref()),
+        ("transform", g.transform.as_deref()),
+        ("clip-path", g.clip_path.as_deref()),
+        ("mask", g.mask.as_deref()),
+        ("filter", g.filter.as_deref()),
+        ("tabindex", g.tabindex.as_deref()),
+        ("pointer-events", g.pointer_events.as_deref()),
+        ("shape-rendering", g.shape_rendering.as_deref()),
+        ("text-rendering", g.text_rendering.as_deref()),
+    ];
+    match &g.elements {
+        Some(elements) => {
+            writer.open("g", &attributes, &[], false);
+            for element in elements {
+                write_g_ele(writer, element);
+            }
+            writer.close("g");
+        }
+        None => writer.open("g", &attributes, &[], true),
+    }
+}
+
+fn write_g_ele(writer: &mut XmlWriter, element: &GEle) {
+    match element {
+        GEle::G(g) => write_g(writer, g),
+        GEle::Use(uuse) => writer.open(
+            "use",
+            &[
+                ("xlink:href", Some(&*uuse.href)),
+                ("x", Some(uuse.x.as_str())),
+                ("width", uuse.width.as_deref()),
+                ("height", uuse.height.as_deref()),
+                ("fill", uuse.fill.as_deref()),
+                ("fill-rule", uuse.fill_rule.as_ref().map(FillRule::as_str)),
+                ("transform", uuse.transform.as_deref()),
+            ],
+            &[],
+            true,
+        ),
+        GEle::Path(path) => write_path(writer, path),
+        GEle::Image(image) => write_image(writer, image),
+        GEle::Text(text) => write_text(writer, text),
+        GEle::Switch(switch) => write_switch(writer, switch),
+    }
+}
+
+/// Writes the alternatives the tree does not model as empty elements of their name.
+fn write_switch(writer: &mut XmlWriter, switch: &Switch) {
+    let attributes = [
+        ("id", switch.id.as_deref()),
+        ("transform", switch.transform.as_deref()),
+    ];
+    if switch.branches.is_empty() {
+        writer.open("switch", &attributes, &[], true);
+        return;
+    }
+    writer.open("switch", &attributes, &[], false);
+    for branch in &switch.branches {
+        match branch {
+            SwitchBranch::Element(element) => write_g_ele(writer, element),
+            SwitchBranch::Unsupported(name) => writer.open(name, &[], &[], true),
+        }
+    }
+    writer.close("switch");
+}
+
+fn write_image(writer: &mut XmlWriter, image: &Image) {
+    writer.open(
+        "image",
+        &[
+            ("width", Some(image.width.as_str())),
+            ("height", Some(image.height.as_str())),
+            (
+                "preserveAspectRatio",
+                Some(image.preserve_aspect_ratio.as_str()),
+            ),
+            ("xlink:href", Some(image.href.as_str())),
+            ("transform", image.transform.as_deref()),
+        ],
+        &[],
+        true,
+    );
+}
+
+fn write_text(writer: &mut XmlWriter, text: &Text) {
+    let attributes = [
+        ("id", text.id.as_deref()),
+        ("class", text.class.as_deref()),
+        ("x", text.x.as_deref()),
+        ("y", text.y.as_deref()),
+        ("dx", text.dx.as_deref()),
+        ("dy", text.dy.as_deref()),
+        ("fill", text.fill.as_deref()),
+        ("font-family", text.font_family.as_deref()),
+        ("font-size", text.font_size.as_deref()),
+        ("font-weight", text.font_weight.as_deref()),
+        ("font-style", text.font_style.as_deref()),
+        ("letter-spacing", text.letter_spacing.as_deref()),
+        ("word-spacing", text.word_spacing.as_deref()),
+        ("dominant-baseline", text.dominant_baseline.as_deref()),
+        ("text-anchor", text.text_anchor.as_deref()),
+        ("text-rendering", text.text_rendering.as_deref()),
+        ("transform", text.transform.as_deref()),
+    ];
+    if text.content.is_empty() {
+        writer.open("text", &attributes, &[], true);
+        return;
+    }
+    writer.inline(|writer| {
+        writer.open("text", &attributes, &[], false);
+        write_text_content(writer, &text.content);
+        writer.close("text");
+    });
+}
+
+fn write_text_content(writer: &mut XmlWriter, content: &[TextContent]) {
+    for content in content {
+        match content {
+            TextContent::Characters(characters) => writer.characters(characters),
+            TextContent::Tspan(tspan) => {
+                let attributes = [
+                    ("x", tspan.x.as_deref()),
+                    ("y", tspan.y.as_deref()),
+                    ("dx", tspan.dx.as_deref()),
+                    ("dy", tspan.dy.as_deref()),
+                    ("fill", tspan.fill.as_deref()),
+                    ("font-family", tspan.font_family.as_deref()),
+                    ("font-size", tspan.font_size.as_deref()),
+                    ("font-weight", tspan.font_weight.as_deref()),
+                    ("font-style", tspan.font_style.as_deref()),
+                    ("letter-spacing", tspan.letter_spacing.as_deref()),
+                    ("word-spacing", tspan.word_spacing.as_deref()),
+                    ("dominant-baseline", tspan.dominant_baseline.as_deref()),
+                ];
+                let empty = tspan.characters.is_empty();
+                writer.open("tspan", &attributes, &[], empty);
+                if !empty {
+                    writer.characters(&tspan.characters);
+                    writer.close("tspan");
+                }
+            }
+        }
+    }
+}
+
+fn write_symbol(writer: &mut XmlWriter, symbol: &Symbol) {
+    writer.open(
+        "symbol",
+        &[
+            ("id", Some(symbol.id.as_str())),
+            ("overflow", Some(symbol.overflow.as_str())),
+            ("viewBox", symbol.view_box.as_deref()),
+            (
+                "preserveAspectRatio",
+                symbol.preserve_aspect_ratio.as_deref(),
+            ),
+        ],
+        &[],
+        false,
+    );
+    match &symbol.element {
+        SymbolEle::Path(path) => write_path(writer, path),
+        SymbolEle::Image(image) => write_image(writer, image),
+    }
+    writer.close("symbol");
+}
+
+fn write_definition(writer: &mut XmlWriter, definition: &Definition) {
+    let (mut attributes, gradient_units, gradient_transform, spread_method, href, stops) =
+        match definition {
+            Definition::LinearGradient(gradient) => (
+                vec![
+                    ("id", Some(gradient.id.as_str())),
+                    ("x1", gradient.x1.as_deref()),
+                    ("y1", gradient.y1.as_deref()),
+                    ("x2", gradient.x2.as_deref()),
+                    ("y2", gradient.y2.as_deref()),
+                ],
+                &gradient.gradient_units,
+                &gradient.gradient_transform,
+                &gradient.spread_method,
+                &gradient.href,
+                &gradient.stops,
+            ),
+            Definition::RadialGradient(gradient) => (
+                vec![
+                    ("id", Some(gradient.id.as_str())),
+                    ("cx", gradient.cx.as_deref()),
+                    ("cy", gradient.cy.as_deref()),
+                    ("r", gradient.r.as_deref()),
+                    ("fx", gradient.fx.as_deref()),
+                    ("fy", gradient.fy.as_deref()),
+                ],
+                &gradient.gradient_units,
+                &gradient.gradient_transform,
+                &gradient.spread_method,
+                &gradient.href,
+                &gradient.stops,
+            ),
+            Definition::ClipPath(clip) => {
+                let attributes = [
+                    ("id", Some(clip.id.as_str())),
+                    (
+                        "clipPathUnits",
+                        clip.clip_path_units.as_ref().map(Units::as_str),
+                    ),
+                    ("transform", clip.transform.as_deref()),
+                ];
+                return write_paths(writer, "clipPath", &attributes, &clip.elements);
+            }
+            Definition::Mask(mask) => {
+                let attributes = [
+                    ("id", Some(mask.id.as_str())),
+                    ("x", mask.x.as_deref()),
+                    ("y", mask.y.as_deref()),
+                    ("width", mask.width.as_deref()),
+                    ("height", mask.height.as_deref()),
+                    ("maskUnits", mask.mask_units.as_ref().map(Units::as_str)),
+                    (
+                        "maskContentUnits",
+                        mask.mask_content_units.as_ref().map(Units::as_str),
+                    ),
+                ];
+                return write_paths(writer, "mask", &attributes, &mask.elements);
+            }
+            Definition::Filter(filter) => return write_filter(writer, filter),
+        };
+    attributes.extend([
+        ("gradientUnits", gradient_units.as_ref().map(Units::as_str)),
+        ("gradientTransform", gradient_transform.as_deref()),
+        (
+            "spreadMethod",
+            spread_method.as_ref().map(SpreadMethod::as_str),
+        ),
+        ("xlink:href", href.as_deref()),
+    ]);
+    let tag = definition.tag();
+    writer.open(tag, &attributes, &[], stops.is_empty());
+    if !stops.is_empty() {
+        for stop in stops {
+            writer.open(
+                "stop",
+                &[
+                    ("offset", stop.offset.as_deref()),
+                    ("stop-color", stop.stop_color.as_deref()),
+                    ("stop-opacity", stop.stop_opacity.as_deref()),
+                    ("style", stop.style.as_deref()),
+                ],
+                &[],
+                true,
+            );
+        }
+        writer.close(tag);
+    }
+}
+
+/// Writes a `tag` element holding `paths`, empty when there are none.
+fn write_paths(
+    writer: &mut XmlWriter,
+    tag: &str,
+    attributes: &[(&str, Option<&str>)],
+    paths: &[Path],
+) {
+    writer.open(tag, attributes, &[], paths.is_empty());
+    if !paths.is_empty() {
+        for path in paths {
+            write_path(writer, path);
+        }
+        writer.close(tag);
+    }
+}
+
+fn write_filter(writer: &mut XmlWriter, filter: &Filter) {
+    let attributes = [
+        ("id", Some(filter.id.as_str())),
+        ("x", filter.x.as_deref()),
+        ("y", filter.y.as_deref()),
+        ("width", filter.width.as_deref()),
+        ("height", filter.height.as_deref()),
+        (
+            "filterUnits",
+            filter.filter_units.as_ref().map(Units::as_str),
+        ),
+    ];
+    let empty = filter.primitives.is_empty();
+    writer.open("filter", &attributes, &[], empty);
+    if !empty {
+        // In document order, each primitive reading the output of the one before
+        for primitive in &filter.primitives {
+            writer.open(primitive.tag(), &primitive_attributes(primitive), &[], true);
+        }
+        writer.close("filter");
+    }
+}
+
+/// The attributes of a filter primitive, in the order they are written.
+pub(super) fn primitive_attributes(
+    primitive: &FilterPrimitive,
+) -> Vec<(&'static str, Option<&str>)> {
+    match primitive {
+        FilterPrimitive::GaussianBlur(blur) => vec![
+            ("in", blur.input.as_deref()),
+            ("stdDeviation", blur.std_deviation.as_deref()),
+            ("result", blur.result.as_deref()),
+        ],
+        FilterPrimitive::Offset(offset) => vec![
+            ("in", offset.input.as_deref()),
+            ("dx", offset.dx.as_deref()),
+            ("dy", offset.dy.as_deref()),
+            ("result", offset.result.as_deref()),
+        ],
+        FilterPrimitive::ColorMatrix(matrix) => vec![
+            ("in", matrix.input.as_deref()),
+            ("type", matrix.kind.as_ref().map(ColorMatrixType::as_str)),
+            ("values", matrix.values.as_deref()),
+            ("result", matrix.result.as_deref()),
+        ],
+        FilterPrimitive::DropShadow(shadow) => vec![
+            ("in", shadow.input.as_deref()),
+            ("dx", shadow.dx.as_deref()),
+            ("dy", shadow.dy.as_deref()),
+            ("stdDeviation", shadow.std_deviation.as_deref()),
+            ("flood-color", shadow.flood_color.as_deref()),
+            ("flood-opacity", shadow.flood_opacity.as_deref()),
+            ("result", shadow.result.as_deref()),
+        ],
+    }
+}
+
+/// Escapes the characters that are not allowed verbatim in a double-quoted attribute value.
+fn escape_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\t' => escaped.push_str("&#9;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Accumulates indented XML, one element per line outside [`XmlWriter::inline`].
+#[derive(Default)]
+struct XmlWriter {
+    out: String,
+    depth: usize,
+    inline: bool,
+}
+
+impl XmlWriter {
+    /// Writes a start tag (or an empty-element tag when `empty`), skipping absent attributes.
+    fn open(
+        &mut self,
+        tag: &str,
+        attributes: &[(&str, Option<&str>)],
+        namespaces: &[(&str, &str)],
+        empty: bool,
+    ) {
+        self.indent();
+        self.out.push('<');
+        self.out.push_str(tag);
+        let attributes = attributes
+            .iter()
+            .filter_map(|(name, value)| value.map(|value| (*name, value)));
+        for (name, value) in attributes.chain(namespaces.iter().copied()) {
+            self.out
+                .push_str(&format!(" {}=\"{}\"", name, escape_attribute(value)));
+        }
+        if empty {
+            self.out.push_str("/>");
+        } else {
+            self.out.push('>');
+            self.depth += 1;
+        }
+        self.end_line();
+    }
+
+    /// Writes character data where it stands, so it keeps the whitespace at its ends. Only called
+    /// within [`XmlWriter::inline`], where no indentation is mixed into it.
+    fn characters(&mut self, characters: &str) {
+        self.out.push_str(&escape_attribute(characters));
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str(&format!("</{}>", tag));
+        self.end_line();
+    }
+
+    /// Writes the tags and characters of `write` on a single indented line, for elements with
+    /// mixed content such as `text`.
+    fn inline(&mut self, write: impl FnOnce(&mut Self)) {
+        self.indent();
+        self.inline = true;
+        write(self);
+        self.inline = false;
+        self.end_line();
+    }
+
+    fn indent(&mut self) {
+        if self.inline {
+            return;
+        }
+        for _ in 0..self.depth {
+            self.out.push_str("    ");
+        }
+    }
+
+    fn end_line(&mut self) {
+        if !self.inline {
+            self.out.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_xml_rs::from_str;
+    use std::fs;
+
+    #[test]
+    fn round_trip_test() {
+        for file in [
+            "expected.svg",
+            "paragraph.svg",
+            "gradient.svg",
+            "clip.svg",
+            "shadow.svg",
+            "icons.svg",
+        ] {
+            let original: Svg =
+                from_str(&fs::read_to_string(format!("./test/{}", file)).unwrap()).unwrap();
+            let output = original.to_svg_string();
+            let reparsed: Svg = from_str(&output).unwrap();
+            assert_eq!(reparsed, original, "{}", file);
+        }
+    }
+
+    #[test]
+    fn text_inline_test() {
+        let text = Text {
+            content: vec![
+                TextContent::Characters("Hello ".to_string()),
+                TextContent::Tspan(Box::new(Tspan {
+                    characters: "world".to_string(),
+                    ..Default::default()
+                })),
+                TextContent::Characters(" again".to_string()),
+            ],
+            ..Default::default()
+        };
+        let svg = Svg {
+            class: "typst-doc".to_string(),
+            width: "1pt".to_string(),
+            height: "1pt".to_string(),
+            view_box: "0 0 1 1".to_string(),
+            elements: vec![SvgElement::G(Box::new(G {
+                elements: Some(vec![GEle::Text(Box::new(text))]),
+                ..Default::default()
+            }))],
+        };
+        assert!(svg
+            .to_svg_string()
+            .contains("\n        <text>Hello <tspan>world</tspan> again</text>\n"));
+    }
+
+    #[test]
+    fn escape_test() {
+        let svg = Svg {
+            class: "a\"b&c<d>".to_string(),
+            width: "1pt".to_string(),
+            height: "1pt".to_string(),
+            view_box: "0 0 1 1".to_string(),
+            elements: vec![SvgElement::Path(Box::new(Path {
+                d: "M 0 0".to_string(),
+                ..Default::default()
+            }))],
+        };
+        let reparsed: Svg = from_str(&svg.to_svg_string()).unwrap();
+        assert_eq!(reparsed, svg);
+    }
+}
@@ -0,0 +1,115 @@
+//! Helpers for the spans and events of the `tracing` feature, which do nothing without it.
+//!
+//! The spans themselves are opened with `#[cfg_attr(feature = "tracing", tracing::instrument)]`
+//! on the functions they cover, declaring as `Empty` the fields known only at the end; these
+//! helpers fill them in on the current span.
+
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+use crate::svg_types::Svg;
+
+/// Records the time since its creation in the `duration_ms` field of the current span when
+/// dropped, whether the function returns early or not.
+pub(crate) struct Timing {
+    #[cfg(feature = "tracing")]
+    start: Instant,
+}
+
+impl Timing {
+    pub(crate) fn start() -> Self {
+        Timing {
+            #[cfg(feature = "tracing")]
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timing {
+    fn drop(&mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("duration_ms", self.start.elapsed().as_secs_f64() * 1000.0);
+    }
+}
+
+/// Records `value` in the field `name` of the current span.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+// Outside of `record_elements`, only the compilation records a field, which wasm leaves out
+#[cfg_attr(
+    all(target_arch = "wasm32", not(feature = "tracing")),
+    allow(dead_code)
+)]
+pub(crate) fn record(name: &str, value: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record(name, value);
+}
+
+/// Records the number of elements of `svg` in the `elements`, `paths` and `uses` fields of the
+/// current span.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn record_elements(svg: &Svg) {
+    // Counting walks the whole tree, which is only worth it when the span is recorded
+    #[cfg(feature = "tracing")]
+    if !tracing::Span::current().is_disabled() {
+        let stats = svg.stats();
+        let elements =
+            stats.paths + stats.groups + stats.uses + stats.symbols + stats.images + stats.rects;
+        record("elements", elements);
+        record("paths", stats.paths);
+        record("uses", stats.uses);
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing_subscriber::fmt::{format::FmtSpan, MakeWriter};
+
+    use crate::parse_svg_to_rsx;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn spans_test() {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_ansi(false)
+            .with_writer(buffer.clone())
+            .finish();
+        let svg_str = "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+                       <g><path d='M 0 0 L 10 10'/><path d='M 0 10 L 10 0'/></g></svg>";
+        tracing::subscriber::with_default(subscriber, || {
+            parse_svg_to_rsx(svg_str).unwrap().unwrap();
+        });
+        let span = format!("parse_svg_to_rsx{{input_size={}}}", svg_str.len());
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", output);
+        assert!(lines[0].contains(&format!("{}:parse_svg{{", span)));
+        assert!(lines[0].contains("elements=3 paths=2 uses=0 duration_ms="));
+        assert!(lines[1].contains(":svg_to_rsx{elements=3"));
+        assert!(lines[2].contains(&format!(" INFO {}: ", span)));
+    }
+}
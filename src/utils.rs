@@ -1,29 +1,64 @@
 use std::{
-    fs,
-    io::{BufRead, BufReader, Error},
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
-// Read file
-pub fn read_file(path: &str) -> Result<String, Error> {
-    match fs::File::open(path) {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-            let mut content = String::new();
-            for line in reader.lines() {
-                match line {
-                    Ok(line_string) => {
-                        content.push_str(&line_string);
-                        // Manually append a newline to preserve the original format
-                        content.push('\n');
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
-                }
-            }
-
-            Ok(content)
-        }
-        Err(e) => Err(e),
+use crate::error::Error;
+
+/// Counter used to give every temporary file its own name.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// Unique path in the system temp directory, e.g. for intermediate `.typ`/`.svg` files
+pub fn temp_file_path(extension: &str) -> PathBuf {
+    env::temp_dir().join(format!(
+        "typst-2-rsx-{}-{}.{}",
+        process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed),
+        extension
+    ))
+}
+
+// Read file, keeping its content byte for byte (line endings, BOM, missing trailing newline), and
+// rejecting the UTF-16 documents
+pub(crate) fn read_file(path: impl AsRef<Path>) -> Result<String, Error> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+    crate::decode_svg(bytes, Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(content: &str) -> String {
+        let path = temp_file_path("svg");
+        fs::write(&path, content).unwrap();
+        let read = read_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        read
+    }
+
+    #[test]
+    fn read_file_test() {
+        let crlf = "<svg>\r\n  <path d=\"M 0 0\"/>\r\n</svg>\r\n";
+        assert_eq!(round_trip(crlf), crlf);
+        assert_eq!(round_trip("<svg></svg>"), "<svg></svg>");
+        assert_eq!(round_trip("\u{feff}<svg></svg>\n"), "\u{feff}<svg></svg>\n");
+    }
+
+    #[test]
+    fn missing_file_test() {
+        let error = read_file("./test/missing.svg").unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::Io { path: Some(path), source }
+                if path == Path::new("./test/missing.svg")
+                    && source.kind() == std::io::ErrorKind::NotFound
+        ));
+        assert!(error
+            .to_string()
+            .starts_with("I/O error on ./test/missing.svg: "));
     }
 }
@@ -0,0 +1,75 @@
+//! Inputs found by the fuzz targets in `fuzz/`, or close to them, which must give an error or a
+//! result rather than a panic.
+//!
+//! When a fuzz target finds a new crash, the input goes here once the code is fixed.
+
+use typst_2_rsx::{
+    options::RsxOptions,
+    parse_svg, parse_svg_to_rsx, parse_svg_to_rsx_with_report, parse_untrusted_svg_to_rsx, passes,
+    svg_types::{path_data::PathData, transform::Transform},
+};
+
+/// Malformed documents, none of which may make the conversion panic.
+const DOCUMENTS: &[&str] = &[
+    "",
+    "<",
+    "<svg",
+    "<svg>",
+    "<svg/>",
+    "\u{feff}",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'><g>",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'></g></svg>",
+    "<svg class='typst-doc' viewBox='' width='' height=''></svg>",
+    "<svg class='typst-doc' viewBox='0 0 10' width='-1pt' height='NaN'></svg>",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+     <path d='M 0 0 L \u{e9}'/><path d='H22H66v.21e2666666666666666662'/></svg>",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+     <g transform='scale(1e999)'><path d='M 0 0 L 1 1'/></g></svg>",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+     <g transform='scale(1e300) translate(1e300 1e300)'><path d='M 1e300 0 L 0 1e300'/></g></svg>",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+     <g transform='matrix(0 0 0 0 0 0)'><path d='A 0 0 0 1 1 1e-300 0'/></g></svg>",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+     <g><use href='#' x='0'/><use x='1'/><image href='data:' width='1' height='1'/></g>\
+     <defs><symbol id='' overflow='visible'><path d=''/></symbol></defs></svg>",
+    "<svg class='typst-doc' viewBox='0 0 10 10' width='10pt' height='10pt'>\
+     <g><a href='javascript:'><circle r='1'/></a><![CDATA[\u{0}]]>&#x110000;</g></svg>",
+];
+
+#[test]
+fn documents_test() {
+    for document in DOCUMENTS {
+        let _ = parse_svg_to_rsx(document);
+        let _ = parse_svg_to_rsx_with_report(document, &RsxOptions::default());
+        let _ = parse_untrusted_svg_to_rsx(document, &RsxOptions::default());
+        if let Ok(mut svg) = parse_svg(document) {
+            let _ = svg.content_bbox();
+            passes::flatten_transforms(&mut svg);
+            passes::minify_paths(&mut svg, 2);
+            passes::crop_to_content(&mut svg, 1.0);
+            let _ = passes::expand_uses(&mut svg);
+        }
+    }
+    let nested = "<g>".repeat(100_000);
+    let document = format!("<svg class='typst-doc' viewBox='0 0 1 1'>{}", nested);
+    assert!(parse_svg_to_rsx(&document).is_err());
+}
+
+#[test]
+fn overflowing_numbers_test() {
+    // Numbers overflowing to infinity used to parse, and were written back as `inf`
+    let error = PathData::parse("H22H66v.21e2666666666666666662").unwrap_err();
+    assert_eq!(
+        (error.position, error.reason.as_str()),
+        (7, "expected a number")
+    );
+    assert!(PathData::parse("M 0 0 L 1e400 0").is_err());
+    assert!(Transform::parse("scale(1e999)").is_err());
+    assert!(Transform::parse("translate(-1e309)").is_err());
+
+    // The largest finite numbers are still written back exactly
+    let path = PathData::parse("M 1e300 -1.7976931348623157e308").unwrap();
+    assert_eq!(PathData::parse(&path.to_string()).unwrap(), path);
+    let transform = Transform::parse("scale(1e300)").unwrap();
+    assert_eq!(Transform::parse(&transform.to_string()).unwrap(), transform);
+}
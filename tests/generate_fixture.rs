@@ -0,0 +1,243 @@
+//! Generates Typst-like SVG documents of any size, deterministically from a seed, so that the
+//! benchmarks do not need megabytes of committed fixtures.
+//!
+//! The documents follow the layout of the Typst output: glyphs defined once as `symbol` elements
+//! in a trailing `defs`, lines of text as `use` elements in `typst-text` groups nested in
+//! positioned groups, shapes as `typst-shape` paths and one `typst-page` group per page.
+//!
+//! Run with `cargo test --test generate_fixture` to check that the fixtures parse and do not
+//! depend on anything else than the seed, and add `-- --write <dir>` to write them to `<dir>`.
+
+use std::fmt::Write;
+
+/// The generated documents, from the smallest to the largest.
+#[derive(Debug, Clone, Copy)]
+pub enum Fixture {
+    /// An inline formula: a few glyphs with a fraction bar, without pages.
+    SmallFormula,
+    /// A single page filled with text, about 4000 glyphs.
+    DensePage,
+    /// A 50-page document, about 1000 glyphs per page.
+    FiftyPages,
+}
+
+impl Fixture {
+    pub const ALL: [Fixture; 3] = [
+        Fixture::SmallFormula,
+        Fixture::DensePage,
+        Fixture::FiftyPages,
+    ];
+
+    /// The name of the fixture in snake case, such as `dense_page`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Fixture::SmallFormula => "small_formula",
+            Fixture::DensePage => "dense_page",
+            Fixture::FiftyPages => "fifty_pages",
+        }
+    }
+}
+
+/// The seed of the fixtures used by the benchmarks.
+pub const SEED: u64 = 0x7970_7374;
+
+const PAGE_WIDTH: f64 = 595.28;
+const PAGE_HEIGHT: f64 = 841.89;
+
+/// The SplitMix64 generator: small, fast and the same on every platform.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A number in `min..max`, rounded to two decimals like the coordinates written by Typst.
+    fn range(&mut self, min: f64, max: f64) -> f64 {
+        let unit = (self.next() >> 11) as f64 / (1u64 << 53) as f64;
+        ((min + unit * (max - min)) * 100.0).round() / 100.0
+    }
+}
+
+/// Generates `fixture` from `seed`.
+pub fn generate(fixture: Fixture, seed: u64) -> String {
+    let mut rng = Rng(seed);
+    match fixture {
+        Fixture::SmallFormula => formula(&mut rng),
+        Fixture::DensePage => document(&mut rng, 1, 60, 70),
+        Fixture::FiftyPages => document(&mut rng, 50, 20, 50),
+    }
+}
+
+/// The ids of `count` glyphs, hashes of 32 hexadecimal digits like Typst gives them.
+fn glyph_ids(rng: &mut Rng, count: usize) -> Vec<String> {
+    (0..count)
+        .map(|_| format!("g{:016X}{:016X}", rng.next(), rng.next()))
+        .collect()
+}
+
+/// The outline of a glyph, made of a few curves.
+fn glyph_outline(rng: &mut Rng) -> String {
+    let mut d = format!("M {} {} ", rng.range(0.0, 2.0), rng.range(0.0, 2.0));
+    for _ in 0..3 + rng.below(8) {
+        let _ = write!(
+            d,
+            "Q {} {} {} {} ",
+            rng.range(0.0, 8.0),
+            rng.range(-2.0, 10.0),
+            rng.range(0.0, 8.0),
+            rng.range(-2.0, 10.0)
+        );
+    }
+    d.push_str("Z ");
+    d
+}
+
+fn defs(rng: &mut Rng, glyphs: &[String], out: &mut String) {
+    out.push_str("<defs id=\"glyph\">\n");
+    for id in glyphs {
+        let _ = writeln!(
+            out,
+            "<symbol id=\"{}\" overflow=\"visible\"><path d=\"{}\"/></symbol>",
+            id,
+            glyph_outline(rng)
+        );
+    }
+    out.push_str("</defs>\n");
+}
+
+/// A line of `count` glyphs picked from `glyphs`, in a `typst-text` group.
+fn text(rng: &mut Rng, glyphs: &[String], count: usize, out: &mut String) {
+    out.push_str("<g class=\"typst-text\" transform=\"scale(1, -1)\">\n");
+    let mut x = 0.0;
+    for _ in 0..count {
+        let _ = writeln!(
+            out,
+            "<use xlink:href=\"#{}\" x=\"{}\" fill=\"#000000\" fill-rule=\"nonzero\"/>",
+            glyphs[rng.below(glyphs.len())],
+            x
+        );
+        x = ((x + rng.range(3.0, 9.0)) * 100.0_f64).round() / 100.0;
+    }
+    out.push_str("</g>\n");
+}
+
+fn root(width: f64, height: f64, out: &mut String) {
+    let _ = writeln!(
+        out,
+        "<svg class=\"typst-doc\" viewBox=\"0 0 {0} {1}\" width=\"{0}pt\" height=\"{1}pt\" \
+         xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">",
+        width, height
+    );
+}
+
+fn formula(rng: &mut Rng) -> String {
+    let glyphs = glyph_ids(rng, 12);
+    let mut out = String::new();
+    root(60.0, 24.0, &mut out);
+    // Numerator, denominator and exponent
+    for (x, y) in [(4.0, 10.0), (4.0, 22.0), (40.0, 6.0)] {
+        let _ = writeln!(out, "<g transform=\"translate({} {})\">", x, y);
+        let count = 4 + rng.below(4);
+        text(rng, &glyphs, count, &mut out);
+        out.push_str("</g>\n");
+    }
+    out.push_str(
+        "<path class=\"typst-shape\" fill=\"none\" stroke=\"#000000\" stroke-width=\"0.6\" \
+         stroke-linecap=\"butt\" stroke-linejoin=\"miter\" stroke-miterlimit=\"4\" \
+         d=\"M 4 12 L 36 12 \"/>\n",
+    );
+    defs(rng, &glyphs, &mut out);
+    out.push_str("</svg>\n");
+    out
+}
+
+/// A document of `pages` pages of `lines` lines of about `glyphs_per_line` glyphs, grouped in
+/// paragraphs of four lines, with a rule under some paragraphs.
+fn document(rng: &mut Rng, pages: usize, lines: usize, glyphs_per_line: usize) -> String {
+    let glyphs = glyph_ids(rng, 120);
+    let mut out = String::new();
+    root(PAGE_WIDTH, PAGE_HEIGHT * pages as f64, &mut out);
+    let line_height = (PAGE_HEIGHT - 120.0) / lines as f64;
+    for page in 0..pages {
+        let _ = writeln!(
+            out,
+            "<g class=\"typst-page\" transform=\"translate(0, {})\">",
+            page as f64 * PAGE_HEIGHT
+        );
+        let _ = writeln!(
+            out,
+            "<path class=\"typst-shape\" fill=\"#ffffff\" fill-rule=\"nonzero\" \
+             d=\"M 0 0 L 0 {0} L {1} {0} L {1} 0 Z \"/>",
+            PAGE_HEIGHT, PAGE_WIDTH
+        );
+        for paragraph in (0..lines).step_by(4) {
+            let _ = writeln!(
+                out,
+                "<g transform=\"translate(60 {})\">",
+                60.0 + paragraph as f64 * line_height
+            );
+            for line in 0..4.min(lines - paragraph) {
+                let _ = writeln!(
+                    out,
+                    "<g transform=\"translate(0 {})\">",
+                    line as f64 * line_height
+                );
+                let count = glyphs_per_line - rng.below(glyphs_per_line / 5 + 1);
+                text(rng, &glyphs, count, &mut out);
+                out.push_str("</g>\n");
+            }
+            if rng.below(4) == 0 {
+                let _ = writeln!(
+                    out,
+                    "<path class=\"typst-shape\" fill=\"none\" stroke=\"#000000\" \
+                     stroke-width=\"1\" d=\"M 0 {0} L {1} {0} \"/>",
+                    4.0 * line_height,
+                    rng.range(100.0, PAGE_WIDTH - 120.0)
+                );
+            }
+            out.push_str("</g>\n");
+        }
+        out.push_str("</g>\n");
+    }
+    defs(rng, &glyphs, &mut out);
+    out.push_str("</svg>\n");
+    out
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut write_to = None;
+    while let Some(arg) = args.next() {
+        if arg == "--write" {
+            write_to = args.next();
+        }
+    }
+
+    for fixture in Fixture::ALL {
+        let svg_str = generate(fixture, SEED);
+        assert_eq!(svg_str, generate(fixture, SEED));
+        assert_ne!(svg_str, generate(fixture, SEED + 1));
+        let svg = typst_2_rsx::parse_svg(&svg_str).unwrap();
+        println!(
+            "{}: {} bytes, {}",
+            fixture.name(),
+            svg_str.len(),
+            svg.stats()
+        );
+        if let Some(dir) = &write_to {
+            let path = std::path::Path::new(dir).join(format!("{}.svg", fixture.name()));
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(&path, svg_str).unwrap();
+        }
+    }
+}
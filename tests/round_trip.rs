@@ -0,0 +1,409 @@
+//! Arbitrary parsed trees, which must come back unchanged from [`Svg::to_svg_string`] through
+//! [`parse_svg`], and from [`svg_to_json`] through [`svg_from_json`].
+//!
+//! The strategies only build trees that a document can hold: the characters of a `text` are never
+//! whitespace only, which the parser skips with the indentation, and two of them are never next to
+//! each other, which the parser would read as one. A group has either no `elements` or some,
+//! `<g></g>` being read like `<g/>`.
+
+use std::sync::Arc;
+
+use proptest::{collection::vec, option, prelude::*, sample::select};
+use typst_2_rsx::{
+    parse_svg, svg_from_json, svg_to_json,
+    svg_types::{
+        ClipPath, ColorMatrix, ColorMatrixType, Definition, Defs, DropShadow, FillRule, Filter,
+        FilterPrimitive, GEle, GaussianBlur, Image, LineCap, LineJoin, LinearGradient, Mask,
+        Offset, Path, RadialGradient, Rect, SpreadMethod, Stop, Svg, SvgElement, Switch,
+        SwitchBranch, Symbol, SymbolEle, Text, TextContent, Tspan, Units, Use, G,
+    },
+};
+
+/// An attribute value, with the characters that must be escaped.
+fn value() -> impl Strategy<Value = String> {
+    "[ -~\t\n\r\u{e9}\u{4e2d}]{0,12}"
+}
+
+fn optional() -> impl Strategy<Value = Option<String>> {
+    option::of(value())
+}
+
+fn shared() -> impl Strategy<Value = Option<Arc<str>>> {
+    option::of(value().prop_map(Arc::from))
+}
+
+/// One of `keywords`, or any other value.
+fn keyword<T: From<String> + std::fmt::Debug>(
+    keywords: &'static [&'static str],
+) -> impl Strategy<Value = Option<T>> {
+    let value = prop_oneof![select(keywords).prop_map(String::from), value()];
+    option::of(value.prop_map(T::from))
+}
+
+/// Character data that is not whitespace only, sometimes the name of an element.
+fn characters() -> impl Strategy<Value = String> {
+    prop_oneof![
+        4 => "[ \t\n]{0,2}[!-~\u{e9}\u{4e2d}]([ -~\t\n\u{e9}\u{4e2d}]{0,10}[!-~\u{e9}\u{4e2d}])?[ \t\n]{0,2}",
+        1 => select(&["tspan", "text", "g"][..]).prop_map(String::from),
+    ]
+}
+
+prop_compose! {
+    fn path()(
+        d in value(),
+        (id, class, fill, stroke) in (optional(), shared(), shared(), shared()),
+        fill_rule in keyword::<FillRule>(&["nonzero", "evenodd"]),
+        stroke_width in shared(),
+        stroke_linecap in keyword::<LineCap>(&["butt", "round", "square"]),
+        stroke_linejoin in keyword::<LineJoin>(&["miter", "round", "bevel"]),
+        (stroke_miterlimit, tabindex, pointer_events) in (optional(), optional(), optional()),
+        (opacity, shape_rendering) in (optional(), optional()),
+    ) -> Path {
+        Path {
+            d,
+            id,
+            class,
+            fill,
+            stroke,
+            fill_rule,
+            stroke_width,
+            stroke_linecap,
+            stroke_linejoin,
+            stroke_miterlimit,
+            tabindex,
+            pointer_events,
+            opacity,
+            shape_rendering,
+        }
+    }
+}
+
+prop_compose! {
+    fn uuse()(
+        fill in shared(),
+        x in value(),
+        (width, height) in (optional(), optional()),
+        fill_rule in keyword::<FillRule>(&["nonzero", "evenodd"]),
+        href in value(),
+        transform in optional(),
+    ) -> Use {
+        Use { fill, x, width, height, fill_rule, href: href.into(), transform }
+    }
+}
+
+prop_compose! {
+    fn image()(
+        (width, height, preserve_aspect_ratio, href) in (value(), value(), value(), value()),
+        transform in optional(),
+    ) -> Image {
+        Image { width, height, preserve_aspect_ratio, href, transform }
+    }
+}
+
+prop_compose! {
+    fn tspan()(
+        (x, y, dx, dy, fill) in (optional(), optional(), optional(), optional(), shared()),
+        (font_family, font_size, font_weight, font_style) in
+            (optional(), optional(), optional(), optional()),
+        (letter_spacing, word_spacing, dominant_baseline) in (optional(), optional(), optional()),
+        characters in prop_oneof![Just(String::new()), characters()],
+    ) -> Tspan {
+        Tspan {
+            x,
+            y,
+            dx,
+            dy,
+            fill,
+            font_family,
+            font_size,
+            font_weight,
+            font_style,
+            letter_spacing,
+            word_spacing,
+            dominant_baseline,
+            characters,
+        }
+    }
+}
+
+/// Tspans with characters between some of them.
+fn content() -> impl Strategy<Value = Vec<TextContent>> {
+    vec((option::of(characters()), tspan()), 0..4).prop_flat_map(|runs| {
+        option::of(characters()).prop_map(move |last| {
+            let mut content = Vec::new();
+            for (characters, tspan) in runs.clone() {
+                content.extend(characters.map(TextContent::Characters));
+                content.push(TextContent::Tspan(Box::new(tspan)));
+            }
+            content.extend(last.map(TextContent::Characters));
+            content
+        })
+    })
+}
+
+prop_compose! {
+    fn text()(
+        (id, class, x, y, dx, dy) in
+            (optional(), shared(), optional(), optional(), optional(), optional()),
+        (fill, font_family, font_size, font_weight, font_style) in
+            (shared(), optional(), optional(), optional(), optional()),
+        (letter_spacing, word_spacing, dominant_baseline) in (optional(), optional(), optional()),
+        (text_anchor, text_rendering, transform) in (optional(), optional(), optional()),
+        content in content(),
+    ) -> Text {
+        Text {
+            id,
+            class,
+            x,
+            y,
+            dx,
+            dy,
+            fill,
+            font_family,
+            font_size,
+            font_weight,
+            font_style,
+            letter_spacing,
+            word_spacing,
+            dominant_baseline,
+            text_anchor,
+            text_rendering,
+            transform,
+            content,
+        }
+    }
+}
+
+/// A group holding the elements of `children`.
+fn group(children: impl Strategy<Value = GEle>) -> impl Strategy<Value = G> {
+    (
+        (optional(), shared(), optional(), optional()),
+        (shared(), shared(), shared()),
+        (optional(), optional(), optional()),
+        option::of(vec(children, 1..4)),
+    )
+        .prop_map(
+            |(
+                (id, class, transform, tabindex),
+                (clip_path, mask, filter),
+                (pointer_events, shape_rendering, text_rendering),
+                elements,
+            )| G {
+                id,
+                class,
+                transform,
+                clip_path,
+                mask,
+                filter,
+                tabindex,
+                pointer_events,
+                shape_rendering,
+                text_rendering,
+                elements,
+            },
+        )
+}
+
+/// A switch between the elements of `children` and some that the tree keeps by their name.
+fn switch(children: impl Strategy<Value = GEle>) -> impl Strategy<Value = Switch> {
+    let branch = prop_oneof![
+        children.prop_map(SwitchBranch::Element),
+        select(&["foreignObject", "video"][..])
+            .prop_map(|name| SwitchBranch::Unsupported(name.into())),
+    ];
+    (optional(), optional(), vec(branch, 0..3)).prop_map(|(id, transform, branches)| Switch {
+        id,
+        transform,
+        branches,
+    })
+}
+
+/// A child of a group, groups and switches nested a few levels deep.
+fn g_ele() -> impl Strategy<Value = GEle> {
+    // Boxed like the elements of `svg`, for the stack of a test thread
+    let leaf = prop_oneof![
+        uuse().prop_map(|uuse| GEle::Use(Box::new(uuse))).boxed(),
+        path().prop_map(|path| GEle::Path(Box::new(path))).boxed(),
+        image()
+            .prop_map(|image| GEle::Image(Box::new(image)))
+            .boxed(),
+        text().prop_map(|text| GEle::Text(Box::new(text))).boxed(),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            3 => group(inner.clone()).prop_map(|g| GEle::G(Box::new(g))),
+            1 => switch(inner).prop_map(|switch| GEle::Switch(Box::new(switch))),
+        ]
+    })
+}
+
+prop_compose! {
+    fn symbol()(
+        (id, overflow) in (value(), value()),
+        (view_box, preserve_aspect_ratio) in (optional(), optional()),
+        element in prop_oneof![
+            path().prop_map(SymbolEle::Path),
+            image().prop_map(SymbolEle::Image),
+        ],
+    ) -> Symbol {
+        Symbol { id, overflow, view_box, preserve_aspect_ratio, element }
+    }
+}
+
+prop_compose! {
+    fn stop()(
+        (offset, stop_color, stop_opacity, style) in (optional(), shared(), optional(), optional()),
+    ) -> Stop {
+        Stop { offset, stop_color, stop_opacity, style }
+    }
+}
+
+prop_compose! {
+    fn primitive()(
+        kind in 0..4,
+        (input, a, b, c, result) in (optional(), optional(), optional(), optional(), optional()),
+        matrix_type in keyword::<ColorMatrixType>(
+            &["matrix", "saturate", "hueRotate", "luminanceToAlpha"],
+        ),
+        (flood_color, flood_opacity) in (shared(), optional()),
+    ) -> FilterPrimitive {
+        match kind {
+            0 => FilterPrimitive::GaussianBlur(GaussianBlur { input, std_deviation: a, result }),
+            1 => FilterPrimitive::Offset(Offset { input, dx: a, dy: b, result }),
+            2 => FilterPrimitive::ColorMatrix(ColorMatrix {
+                input,
+                kind: matrix_type,
+                values: a,
+                result,
+            }),
+            _ => FilterPrimitive::DropShadow(DropShadow {
+                input,
+                dx: a,
+                dy: b,
+                std_deviation: c,
+                flood_color,
+                flood_opacity,
+                result,
+            }),
+        }
+    }
+}
+
+fn units() -> impl Strategy<Value = Option<Units>> {
+    keyword::<Units>(&["userSpaceOnUse", "objectBoundingBox"])
+}
+
+prop_compose! {
+    fn definition()(
+        kind in 0..5,
+        id in value(),
+        (a, b, c, d, e) in (optional(), optional(), optional(), optional(), optional()),
+        (units, content_units) in (units(), units()),
+        transform in optional(),
+        spread_method in keyword::<SpreadMethod>(&["pad", "reflect", "repeat"]),
+        href in optional(),
+        stops in vec(stop(), 0..3),
+        paths in vec(path(), 0..3),
+        primitives in vec(primitive(), 0..4),
+    ) -> Definition {
+        match kind {
+            0 => Definition::LinearGradient(LinearGradient {
+                id,
+                x1: a,
+                y1: b,
+                x2: c,
+                y2: d,
+                gradient_units: units,
+                gradient_transform: transform,
+                spread_method,
+                href,
+                stops,
+            }),
+            1 => Definition::RadialGradient(RadialGradient {
+                id,
+                cx: a,
+                cy: b,
+                r: c,
+                fx: d,
+                fy: e,
+                gradient_units: units,
+                gradient_transform: transform,
+                spread_method,
+                href,
+                stops,
+            }),
+            2 => Definition::ClipPath(ClipPath {
+                id,
+                clip_path_units: units,
+                transform,
+                elements: paths,
+            }),
+            3 => Definition::Mask(Mask {
+                id,
+                x: a,
+                y: b,
+                width: c,
+                height: d,
+                mask_units: units,
+                mask_content_units: content_units,
+                elements: paths,
+            }),
+            _ => Definition::Filter(Filter {
+                id,
+                x: a,
+                y: b,
+                width: c,
+                height: d,
+                filter_units: units,
+                primitives,
+            }),
+        }
+    }
+}
+
+prop_compose! {
+    fn rect()((x, y, width, height, fill) in (value(), value(), value(), value(), shared())) -> Rect {
+        Rect { x, y, width, height, fill }
+    }
+}
+
+prop_compose! {
+    fn svg()(
+        (class, width, height, view_box) in (value(), value(), value(), value()),
+        elements in vec(
+            // Boxed, the value trees of the arms being too large for the stack of a test thread
+            // otherwise
+            prop_oneof![
+                path().prop_map(|path| SvgElement::Path(Box::new(path))).boxed(),
+                group(g_ele()).prop_map(|g| SvgElement::G(Box::new(g))).boxed(),
+                (value(), vec(symbol(), 0..3), vec(definition(), 0..3))
+                    .prop_map(|(id, elements, definitions)| SvgElement::Defs(Defs {
+                        id,
+                        elements,
+                        definitions,
+                    }))
+                    .boxed(),
+                rect().prop_map(SvgElement::Rect).boxed(),
+            ],
+            0..4,
+        ),
+    ) -> Svg {
+        Svg { class, width, height, view_box, elements }
+    }
+}
+
+proptest! {
+    #[test]
+    fn xml_round_trip_test(svg in svg()) {
+        let output = svg.to_svg_string();
+        let reparsed = parse_svg(&output).map_err(|error| TestCaseError::fail(format!("{}\n{}", error, output)))?;
+        prop_assert_eq!(reparsed, svg, "{}", output);
+    }
+
+    #[test]
+    fn json_round_trip_test(svg in svg()) {
+        let json = svg_to_json(&svg);
+        let reparsed = svg_from_json(&json).map_err(|error| TestCaseError::fail(format!("{}\n{}", error, json)))?;
+        prop_assert_eq!(reparsed, svg, "{}", json);
+    }
+}